@@ -0,0 +1,13 @@
+use anyhow::Result;
+
+use crate::args::account::Command;
+
+mod decode;
+mod idl;
+mod layout;
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Decode(args) => decode::run(args).await,
+    }
+}
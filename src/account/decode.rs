@@ -0,0 +1,67 @@
+use anyhow::{Context as _, Result};
+use pythnet_heisenberg::output;
+use serde_json::json;
+
+use crate::{
+    account::{idl::Idl, layout::Layout},
+    args::{account::decode::DecodeArgs, json_rpc_url_args::get_rpc_client},
+    watch::decode::decode,
+};
+
+pub async fn run(
+    DecodeArgs {
+        json_rpc_url,
+        address,
+        layout,
+        idl,
+        account_type,
+        builtin,
+    }: DecodeArgs,
+) -> Result<()> {
+    // Decoder definitions are read first, so that mistakes in them are reported without waiting
+    // for the RPC node.
+    let layout = layout.as_deref().map(Layout::read).transpose()?;
+    let idl = idl.as_deref().map(Idl::read).transpose()?;
+
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let account = rpc_client
+        .get_account(&address)
+        .await
+        .with_context(|| format!("Failed to fetch account at {address}"))?;
+
+    let (account_type, decoded) = if let Some(layout) = layout {
+        (None, layout.decode(&account.data)?)
+    } else if let Some(idl) = idl {
+        let (name, decoded) = idl.decode(&account.data, account_type.as_deref())?;
+        (Some(name), decoded)
+    } else {
+        let builtin = builtin.expect("`decoder` argument group requires one of the decoders");
+        (None, decode(builtin, &account.data)?)
+    };
+
+    let mut text = format!(
+        "Account: {address}\nOwner: {}\nLamports: {}\nData: {} bytes",
+        account.owner,
+        account.lamports,
+        account.data.len()
+    );
+    if let Some(account_type) = &account_type {
+        text.push_str(&format!("\nType: {account_type}"));
+    }
+    text.push('\n');
+    text.push_str(&serde_json::to_string_pretty(&decoded).expect("`Value` is always serializable"));
+
+    output::result(
+        text,
+        json!({
+            "address": address.to_string(),
+            "owner": account.owner.to_string(),
+            "lamports": account.lamports,
+            "data_len": account.data.len(),
+            "account_type": account_type,
+            "decoded": decoded,
+        }),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,312 @@
+//! Decodes Borsh serialized accounts using an Anchor IDL.
+//!
+//! The IDL is processed as a JSON value, rather than a typed structure, so that both the current
+//! format, introduced in Anchor 0.30, and the legacy one are supported.  The differences that
+//! matter here are:
+//!
+//! * Current IDLs list account discriminators explicitly, while legacy IDLs expect them to be
+//!   computed from the account name.
+//! * Legacy IDLs put account type definitions into `accounts`, while current IDLs put them into
+//!   `types`.
+//! * `publicKey` became `pubkey`, and `defined` references changed from a string to an object.
+
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use serde_json::{Map, Value, json};
+use solana_sdk::{hash::hashv, pubkey::Pubkey};
+
+use super::layout::hex;
+
+const DISCRIMINATOR_SIZE: usize = 8;
+
+/// Protects against self referencing type definitions.
+const MAX_TYPE_DEPTH: usize = 64;
+
+pub struct Idl {
+    /// Account name and its discriminator.
+    accounts: Vec<(String, [u8; DISCRIMINATOR_SIZE])>,
+    /// Type name and its definition: the value of the `type` field.
+    types: HashMap<String, Value>,
+}
+
+impl Idl {
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read IDL: {}", path.to_string_lossy()))?;
+        let idl: Value = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse IDL: {}", path.to_string_lossy()))?;
+        Self::from_value(&idl).with_context(|| format!("Invalid IDL: {}", path.to_string_lossy()))
+    }
+
+    fn from_value(idl: &Value) -> Result<Self> {
+        let mut types = HashMap::new();
+        for type_def in idl["types"].as_array().into_iter().flatten() {
+            let name = type_def["name"]
+                .as_str()
+                .ok_or_else(|| anyhow!("A type without a name: {type_def}"))?;
+            types.insert(name.to_owned(), type_def["type"].clone());
+        }
+
+        let mut accounts = vec![];
+        for account in idl["accounts"].as_array().into_iter().flatten() {
+            let name = account["name"]
+                .as_str()
+                .ok_or_else(|| anyhow!("An account without a name: {account}"))?;
+
+            let discriminator = match &account["discriminator"] {
+                Value::Array(bytes) => bytes
+                    .iter()
+                    .map(|byte| byte.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+                    .collect::<Option<Vec<_>>>()
+                    .and_then(|bytes| <[u8; DISCRIMINATOR_SIZE]>::try_from(bytes).ok())
+                    .ok_or_else(|| anyhow!("Invalid discriminator for account {name}"))?,
+                _ => legacy_discriminator(name),
+            };
+
+            // Legacy IDLs define account types inline.
+            if !account["type"].is_null() {
+                types.insert(name.to_owned(), account["type"].clone());
+            }
+
+            accounts.push((name.to_owned(), discriminator));
+        }
+
+        Ok(Self { accounts, types })
+    }
+
+    /// Decodes the account `data`, returning the account type name, and the decoded value.  When
+    /// `account_type` is not specified, it is detected using the discriminator.
+    pub fn decode(&self, data: &[u8], account_type: Option<&str>) -> Result<(String, Value)> {
+        let Some((discriminator, body)) = data.split_first_chunk::<DISCRIMINATOR_SIZE>() else {
+            bail!(
+                "Account is too short for an Anchor account: {} bytes",
+                data.len()
+            );
+        };
+
+        let name = match account_type {
+            Some(account_type) => {
+                if !self.accounts.iter().any(|(name, _)| name == account_type) {
+                    bail!("IDL does not define an account named \"{account_type}\"");
+                }
+                account_type
+            }
+            None => self
+                .accounts
+                .iter()
+                .find(|(_, expected)| expected == discriminator)
+                .map(|(name, _)| name.as_str())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Account discriminator {} does not match any account in the IDL",
+                        hex(discriminator)
+                    )
+                })?,
+        };
+
+        let type_def = self
+            .types
+            .get(name)
+            .ok_or_else(|| anyhow!("IDL does not define the \"{name}\" type"))?;
+        if type_def["serialization"]
+            .as_str()
+            .is_some_and(|serialization| serialization != "borsh")
+        {
+            bail!(
+                "\"{name}\" uses {} serialization, only Borsh is supported.  Try a --layout \
+                 file instead.",
+                type_def["serialization"]
+            );
+        }
+
+        let mut reader = Reader { data: body };
+        let value = self
+            .decode_type_def(&mut reader, type_def, 0)
+            .with_context(|| format!("Decoding \"{name}\""))?;
+
+        Ok((name.to_owned(), value))
+    }
+
+    /// Decodes a type definition: a `struct`, an `enum`, or a `type` alias.
+    fn decode_type_def(
+        &self,
+        reader: &mut Reader,
+        type_def: &Value,
+        depth: usize,
+    ) -> Result<Value> {
+        match type_def["kind"].as_str() {
+            Some("struct") => self.decode_fields(reader, &type_def["fields"], depth),
+            Some("enum") => {
+                let variants = type_def["variants"]
+                    .as_array()
+                    .ok_or_else(|| anyhow!("An enum without variants"))?;
+                let index = reader.bytes::<1>()?[0];
+                let variant = variants
+                    .get(usize::from(index))
+                    .ok_or_else(|| anyhow!("Enum variant index {index} is out of range"))?;
+                let name = variant["name"].as_str().unwrap_or("unknown");
+                if variant["fields"].is_null() {
+                    Ok(json!(name))
+                } else {
+                    let fields = self.decode_fields(reader, &variant["fields"], depth)?;
+                    Ok(json!({ name: fields }))
+                }
+            }
+            Some("type") => self.decode_type(reader, &type_def["alias"], depth),
+            kind => bail!("Unsupported type definition kind: {kind:?}"),
+        }
+    }
+
+    /// Named fields become an object, tuple fields become an array.
+    fn decode_fields(&self, reader: &mut Reader, fields: &Value, depth: usize) -> Result<Value> {
+        let Some(fields) = fields.as_array() else {
+            return Ok(Value::Null);
+        };
+
+        let named = fields.iter().all(|field| field["name"].is_string());
+        if named {
+            let mut res = Map::new();
+            for field in fields {
+                let name = field["name"].as_str().expect("Checked above");
+                let value = self
+                    .decode_type(reader, &field["type"], depth)
+                    .with_context(|| format!("Field \"{name}\""))?;
+                res.insert(name.to_owned(), value);
+            }
+            Ok(Value::Object(res))
+        } else {
+            fields
+                .iter()
+                .map(|field_type| self.decode_type(reader, field_type, depth))
+                .collect::<Result<Vec<_>>>()
+                .map(Value::Array)
+        }
+    }
+
+    fn decode_type(&self, reader: &mut Reader, idl_type: &Value, depth: usize) -> Result<Value> {
+        if depth > MAX_TYPE_DEPTH {
+            bail!("Types are nested too deep");
+        }
+        let depth = depth + 1;
+
+        if let Some(name) = idl_type.as_str() {
+            return reader.primitive(name);
+        }
+
+        if let Some(inner) = idl_type.get("vec") {
+            let len = u32::from_le_bytes(reader.bytes()?);
+            return (0..len)
+                .map(|_| self.decode_type(reader, inner, depth))
+                .collect::<Result<Vec<_>>>()
+                .map(Value::Array);
+        }
+
+        if let Some(inner) = idl_type.get("option") {
+            return match reader.bytes::<1>()?[0] {
+                0 => Ok(Value::Null),
+                _ => self.decode_type(reader, inner, depth),
+            };
+        }
+
+        if let Some(inner) = idl_type.get("coption") {
+            return match u32::from_le_bytes(reader.bytes()?) {
+                0 => Ok(Value::Null),
+                _ => self.decode_type(reader, inner, depth),
+            };
+        }
+
+        if let Some(array) = idl_type.get("array") {
+            let (Some(inner), Some(len)) = (array.get(0), array.get(1).and_then(Value::as_u64))
+            else {
+                bail!("Unsupported array type: {idl_type}");
+            };
+            if inner == "u8" {
+                let len = usize::try_from(len).context("Array is too long")?;
+                return Ok(json!(hex(reader.take(len)?)));
+            }
+            return (0..len)
+                .map(|_| self.decode_type(reader, inner, depth))
+                .collect::<Result<Vec<_>>>()
+                .map(Value::Array);
+        }
+
+        if let Some(defined) = idl_type.get("defined") {
+            let name = defined
+                .as_str()
+                .or_else(|| defined["name"].as_str())
+                .ok_or_else(|| anyhow!("Unsupported defined type: {defined}"))?;
+            let type_def = self
+                .types
+                .get(name)
+                .ok_or_else(|| anyhow!("IDL does not define the \"{name}\" type"))?;
+            return self.decode_type_def(reader, type_def, depth);
+        }
+
+        bail!("Unsupported type: {idl_type}")
+    }
+}
+
+/// Legacy IDLs do not include discriminators.  Anchor computes them from the account name.
+fn legacy_discriminator(name: &str) -> [u8; DISCRIMINATOR_SIZE] {
+    let hash = hashv(&[format!("account:{name}").as_bytes()]);
+    hash.to_bytes()[..DISCRIMINATOR_SIZE]
+        .try_into()
+        .expect("Hash is longer than a discriminator")
+}
+
+struct Reader<'data> {
+    data: &'data [u8],
+}
+
+impl<'data> Reader<'data> {
+    fn take(&mut self, len: usize) -> Result<&'data [u8]> {
+        if self.data.len() < len {
+            bail!(
+                "Account data ended early: need {len} more bytes, only {} left",
+                self.data.len()
+            );
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self
+            .take(N)?
+            .try_into()
+            .expect("`take()` returns exactly `N` bytes"))
+    }
+
+    fn primitive(&mut self, name: &str) -> Result<Value> {
+        Ok(match name {
+            "bool" => json!(self.bytes::<1>()?[0] != 0),
+            "u8" => json!(self.bytes::<1>()?[0]),
+            "i8" => json!(i8::from_le_bytes(self.bytes()?)),
+            "u16" => json!(u16::from_le_bytes(self.bytes()?)),
+            "i16" => json!(i16::from_le_bytes(self.bytes()?)),
+            "u32" => json!(u32::from_le_bytes(self.bytes()?)),
+            "i32" => json!(i32::from_le_bytes(self.bytes()?)),
+            "f32" => json!(f32::from_le_bytes(self.bytes()?)),
+            "u64" => json!(u64::from_le_bytes(self.bytes()?)),
+            "i64" => json!(i64::from_le_bytes(self.bytes()?)),
+            "f64" => json!(f64::from_le_bytes(self.bytes()?)),
+            // JSON numbers are not precise enough for 128 bit values.
+            "u128" => json!(u128::from_le_bytes(self.bytes()?).to_string()),
+            "i128" => json!(i128::from_le_bytes(self.bytes()?).to_string()),
+            "pubkey" | "publicKey" => json!(Pubkey::new_from_array(self.bytes()?).to_string()),
+            "string" => {
+                let len = u32::from_le_bytes(self.bytes()?);
+                let len = usize::try_from(len).context("String is too long")?;
+                json!(String::from_utf8_lossy(self.take(len)?))
+            }
+            "bytes" => {
+                let len = u32::from_le_bytes(self.bytes()?);
+                let len = usize::try_from(len).context("Byte array is too long")?;
+                json!(hex(self.take(len)?))
+            }
+            _ => bail!("Unsupported primitive type: {name}"),
+        })
+    }
+}
@@ -0,0 +1,212 @@
+//! Decodes account data according to a user provided list of fields.
+
+use std::{fs, path::Path, str::FromStr};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Layout {
+    fields: Vec<FieldConfig>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct FieldConfig {
+    name: String,
+    offset: Option<usize>,
+    #[serde(rename = "type")]
+    field_type: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Scalar {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    F32,
+    F64,
+    Bool,
+    Pubkey,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FieldType {
+    Scalar(Scalar),
+    Array(Scalar, usize),
+    Bytes(usize),
+    String(usize),
+}
+
+impl Scalar {
+    fn size(self) -> usize {
+        match self {
+            Self::U8 | Self::I8 | Self::Bool => 1,
+            Self::U16 | Self::I16 => 2,
+            Self::U32 | Self::I32 | Self::F32 => 4,
+            Self::U64 | Self::I64 | Self::F64 => 8,
+            Self::U128 | Self::I128 => 16,
+            Self::Pubkey => 32,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> Value {
+        fn le<const N: usize>(bytes: &[u8]) -> [u8; N] {
+            bytes
+                .try_into()
+                .expect("Caller provides exactly `size()` bytes")
+        }
+
+        match self {
+            Self::U8 => json!(bytes[0]),
+            Self::U16 => json!(u16::from_le_bytes(le(bytes))),
+            Self::U32 => json!(u32::from_le_bytes(le(bytes))),
+            Self::U64 => json!(u64::from_le_bytes(le(bytes))),
+            // JSON numbers are not precise enough for 128 bit values.
+            Self::U128 => json!(u128::from_le_bytes(le(bytes)).to_string()),
+            Self::I8 => json!(i8::from_le_bytes(le(bytes))),
+            Self::I16 => json!(i16::from_le_bytes(le(bytes))),
+            Self::I32 => json!(i32::from_le_bytes(le(bytes))),
+            Self::I64 => json!(i64::from_le_bytes(le(bytes))),
+            Self::I128 => json!(i128::from_le_bytes(le(bytes)).to_string()),
+            Self::F32 => json!(f32::from_le_bytes(le(bytes))),
+            Self::F64 => json!(f64::from_le_bytes(le(bytes))),
+            Self::Bool => json!(bytes[0] != 0),
+            Self::Pubkey => json!(Pubkey::new_from_array(le(bytes)).to_string()),
+        }
+    }
+}
+
+impl FromStr for Scalar {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        Ok(match input {
+            "u8" => Self::U8,
+            "u16" => Self::U16,
+            "u32" => Self::U32,
+            "u64" => Self::U64,
+            "u128" => Self::U128,
+            "i8" => Self::I8,
+            "i16" => Self::I16,
+            "i32" => Self::I32,
+            "i64" => Self::I64,
+            "i128" => Self::I128,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            "bool" => Self::Bool,
+            "pubkey" => Self::Pubkey,
+            _ => bail!("Unknown type: {input}"),
+        })
+    }
+}
+
+impl FromStr for FieldType {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let input = input.trim();
+        let Some((element, len)) = input
+            .strip_suffix(']')
+            .and_then(|input| input.split_once('['))
+        else {
+            return input.parse().map(Self::Scalar);
+        };
+
+        let len = len
+            .trim()
+            .parse::<usize>()
+            .with_context(|| format!("Invalid array length in: {input}"))?;
+        Ok(match element.trim() {
+            "bytes" => Self::Bytes(len),
+            "string" => Self::String(len),
+            element => Self::Array(element.parse()?, len),
+        })
+    }
+}
+
+impl FieldType {
+    fn size(self) -> usize {
+        match self {
+            Self::Scalar(scalar) => scalar.size(),
+            Self::Array(scalar, len) => scalar.size() * len,
+            Self::Bytes(len) | Self::String(len) => len,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> Value {
+        match self {
+            Self::Scalar(scalar) => scalar.decode(bytes),
+            Self::Array(scalar, _len) => Value::Array(
+                bytes
+                    .chunks_exact(scalar.size())
+                    .map(|element| scalar.decode(element))
+                    .collect(),
+            ),
+            Self::Bytes(_len) => json!(hex(bytes)),
+            Self::String(_len) => {
+                let end = bytes
+                    .iter()
+                    .rposition(|byte| *byte != 0)
+                    .map_or(0, |pos| pos + 1);
+                json!(String::from_utf8_lossy(&bytes[..end]))
+            }
+        }
+    }
+}
+
+impl Layout {
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read layout: {}", path.to_string_lossy()))?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse layout: {}", path.to_string_lossy()))
+    }
+
+    /// Decodes all the fields into a JSON object, in the order they are listed in the layout.
+    pub fn decode(&self, data: &[u8]) -> Result<Value> {
+        let mut res = Map::new();
+        let mut next_offset = 0;
+
+        for FieldConfig {
+            name,
+            offset,
+            field_type,
+        } in &self.fields
+        {
+            let field_type: FieldType = field_type
+                .parse()
+                .with_context(|| format!("Field \"{name}\""))?;
+            let offset = offset.unwrap_or(next_offset);
+            let end = offset + field_type.size();
+            let bytes = data.get(offset..end).ok_or_else(|| {
+                anyhow!(
+                    "Field \"{name}\" at {offset}..{end} is outside of the account data, that is \
+                     {} bytes long",
+                    data.len()
+                )
+            })?;
+
+            if res.insert(name.clone(), field_type.decode(bytes)).is_some() {
+                bail!("Field \"{name}\" is defined more than once");
+            }
+            next_offset = end;
+        }
+
+        Ok(Value::Object(res))
+    }
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
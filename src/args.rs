@@ -1,12 +1,37 @@
-use clap::{Parser, Subcommand};
+use std::{env, ffi::OsString, path::PathBuf};
+
+use anyhow::Result;
+use clap::{CommandFactory as _, FromArgMatches as _, Parser, Subcommand};
 use num_format::{Locale, ToFormattedString, parsing::ParseFormatted};
+use pythnet_heisenberg::output::OutputFormat;
+use solana_sdk::commitment_config::CommitmentLevel;
 
+pub mod abort_args;
+pub mod account;
+pub mod arg_files;
+pub mod benchmark;
+pub mod block;
+pub mod bootstrap;
+pub mod cluster;
+pub mod cluster_config;
+pub mod dev;
+#[cfg(feature = "geyser")]
+pub mod geyser_args;
 pub mod json_rpc_url_args;
+pub mod keypair_dirs;
+pub mod keys;
+pub mod metrics_args;
 pub mod oracle;
 pub mod price_store;
 pub mod primordial_accounts;
+pub mod program;
+pub mod rpc;
+pub mod scenario;
+pub mod stake;
 pub mod stake_caps_parameters;
 pub mod transfer;
+pub mod tx;
+pub mod watch;
 
 pub use json_rpc_url_args::JsonRpcUrlArgs;
 
@@ -14,6 +39,54 @@ pub use json_rpc_url_args::JsonRpcUrlArgs;
 #[derive(Parser, Debug)]
 #[command(version, about)]
 pub struct Args {
+    /// Format of the command results printed on stdout.
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+
+    /// Name of a cluster alias from the config file.
+    ///
+    /// An alias provides default values for the RPC and WebSocket URLs, and the Oracle, Price
+    /// Store, and stake caps parameters program ids.  Values specified explicitly, either as
+    /// arguments or via `HEISENBERG_*` environment variables, take precedence.
+    #[arg(long, global = true, env = "HEISENBERG_CLUSTER")]
+    pub cluster: Option<String>,
+
+    /// A config file with cluster aliases.
+    ///
+    /// Defaults to `~/.config/pythnet-heisenberg/config.yaml`.
+    #[arg(long, global = true, env = "HEISENBERG_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Count RPC requests, per method, with the payload sizes and the error rates, and print a
+    /// summary when the command exits.
+    ///
+    /// Inside a `shell`, a summary is printed after every command.
+    #[arg(long, global = true, env = "HEISENBERG_RPC_TELEMETRY")]
+    pub rpc_telemetry: bool,
+
+    /// Write a CSV row for every transaction attempt into this file: the transaction index, label,
+    /// signature, send and confirmation times, slot, retries, and error.
+    ///
+    /// Covers the transactions sent with retries, by most of the setup commands, and the
+    /// transactions sent by the benchmarks.  Rows are appended, so the same file can hold multiple
+    /// runs.  Only CSV is supported.
+    #[arg(long, global = true, env = "HEISENBERG_TX_RECORDS_FILE")]
+    pub tx_records_file: Option<PathBuf>,
+
+    /// Build the transactions, and print what they would do, instead of sending them: the fee
+    /// payer, the signers, the invoked programs, the accounts written, and the lamports moved.
+    ///
+    /// Commands that can estimate more, like the fees of `transfer fill-up-to`, print their own
+    /// summary instead.  Commands that generate load, like the benchmarks, do not support a dry
+    /// run.
+    #[arg(long, global = true, env = "HEISENBERG_DRY_RUN")]
+    pub dry_run: bool,
+
+    /// In the `--dry-run` mode, also run every transaction through `simulateTransaction`, and
+    /// print the outcome, the consumed compute units, and the logs of the failed ones.
+    #[arg(long, global = true, requires = "dry_run")]
+    pub simulate: bool,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -27,6 +100,14 @@ pub enum Command {
     /// See `solana-genesis --primordial-accounts-file`.
     PrimordialAccounts(primordial_accounts::Command),
 
+    #[command(subcommand)]
+    /// Prepares a new test cluster.
+    Bootstrap(bootstrap::Command),
+
+    #[command(subcommand)]
+    /// Runs a local test cluster.
+    Dev(dev::Command),
+
     #[command(subcommand)]
     /// Sends SOL between accounts in parallel.
     ///
@@ -34,6 +115,10 @@ pub enum Command {
     /// accounts.
     Transfer(transfer::Command),
 
+    #[command(subcommand)]
+    /// Sets up the stake distribution of the cluster.
+    Stake(stake::Command),
+
     #[command(subcommand)]
     /// Interact with the stake caps parameters program.
     StakeCapsParameters(stake_caps_parameters::Command),
@@ -45,6 +130,71 @@ pub enum Command {
     #[command(subcommand)]
     /// Interacts with the Price Store program.
     PriceStore(price_store::Command),
+
+    #[command(subcommand)]
+    /// Deploys programs.
+    Program(program::Command),
+
+    #[command(subcommand)]
+    /// Manages keypair files.
+    Keys(keys::Command),
+
+    #[command(subcommand)]
+    /// Observes changes on the cluster.
+    Watch(watch::Command),
+
+    #[command(subcommand)]
+    /// Inspects individual accounts.
+    Account(account::Command),
+
+    #[command(subcommand)]
+    /// Inspects blocks.
+    Block(block::Command),
+
+    #[command(subcommand)]
+    /// Reports on the cluster state as a whole.
+    Cluster(cluster::Command),
+
+    #[command(subcommand)]
+    /// Checks how RPC nodes serve requests.
+    Rpc(rpc::Command),
+
+    #[command(subcommand)]
+    /// Inspects transactions that were already sent.
+    Tx(tx::Command),
+
+    #[command(subcommand)]
+    /// Works with the recorded results of benchmark runs.
+    Benchmark(benchmark::Command),
+
+    /// Starts an interactive shell, where other commands can be executed one after another.
+    ///
+    /// RPC clients, blockhash caches, and leader schedule trackers are kept running between
+    /// commands, saving on the startup cost of each command.
+    ///
+    /// Global arguments given to the `shell` command itself, like `--cluster`, apply to all the
+    /// commands executed in the shell.  `--output` can not be changed inside the shell.
+    Shell,
+}
+
+/// Parses the command line arguments, applying the cluster alias and the scenario defaults, if
+/// any are selected.
+pub fn parse() -> Result<Args> {
+    let args = arg_files::expand_args(&Args::command(), env::args_os().collect())?;
+    let args = keypair_dirs::expand_args(args)?;
+    let command = cluster_config::apply_cluster_alias(Args::command(), &args)?;
+    let command = scenario::apply_scenario(command, &args)?;
+    Ok(try_parse_from(command, args).unwrap_or_else(|err| err.exit()))
+}
+
+/// Parses `args` using the specified `command`, that is expected to be [`Args::command()`], with
+/// some adjustments.
+pub fn try_parse_from(
+    command: clap::Command,
+    args: impl IntoIterator<Item = impl Into<OsString> + Clone>,
+) -> Result<Args, clap::Error> {
+    let matches = command.try_get_matches_from(args)?;
+    Args::from_arg_matches(&matches)
 }
 
 fn u64_nice_parser(value: &str) -> Result<u64, String> {
@@ -62,3 +212,13 @@ fn u64_nice_printer(value: u64) -> String {
     let locale = Locale::en;
     value.to_formatted_string(&locale)
 }
+
+/// Only accepts commitment levels that are not deprecated.
+fn commitment_level_parser(value: &str) -> Result<CommitmentLevel, String> {
+    match value {
+        "processed" => Ok(CommitmentLevel::Processed),
+        "confirmed" => Ok(CommitmentLevel::Confirmed),
+        "finalized" => Ok(CommitmentLevel::Finalized),
+        _ => Err("expected one of: processed, confirmed, finalized".to_owned()),
+    }
+}
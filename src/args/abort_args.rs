@@ -0,0 +1,48 @@
+use std::time::Duration as StdDuration;
+
+use clap::Args;
+use humantime::Duration;
+use pythnet_heisenberg::failure_rate::FailureRateGuard;
+
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Abort")]
+pub struct AbortArgs {
+    /// Stop the run when this percentage of the transactions, or more, failed within the
+    /// `--abort-window`.  The results collected so far are still reported, and the command exits
+    /// with an error.
+    ///
+    /// Protects the scheduled test time from being spent on a cluster or an RPC node that is
+    /// clearly unhealthy.  The check starts once the run is at least `--abort-window` long.
+    #[arg(long, value_name = "PERCENT", value_parser = percent_parser)]
+    pub abort_on_failure_rate: Option<f64>,
+
+    /// A sliding window for the `--abort-on-failure-rate` check.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(
+        long,
+        default_value_t = StdDuration::from_secs(60).into(),
+        requires = "abort_on_failure_rate",
+    )]
+    pub abort_window: Duration,
+}
+
+impl AbortArgs {
+    /// Creates a guard, if `--abort-on-failure-rate` was specified.
+    pub fn failure_rate_guard(&self) -> Option<FailureRateGuard> {
+        self.abort_on_failure_rate
+            .map(|threshold| FailureRateGuard::new(threshold, self.abort_window.into()))
+    }
+}
+
+fn percent_parser(value: &str) -> Result<f64, String> {
+    let value = value.parse::<f64>().map_err(|err| err.to_string())?;
+    if !(value > 0.0 && value <= 100.0) {
+        return Err(format!(
+            "Expected a value above 0 and up to 100, got: {value}"
+        ));
+    }
+    Ok(value)
+}
@@ -0,0 +1,11 @@
+use clap::Subcommand;
+
+pub mod decode;
+
+#[derive(Subcommand, Debug)]
+#[command(name = "account")]
+pub enum Command {
+    /// Fetches an account and decodes its data, using a layout file, an Anchor IDL, or one of the
+    /// built-in decoders.
+    Decode(decode::DecodeArgs),
+}
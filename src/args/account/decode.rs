@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+use clap::{ArgGroup, Args};
+use solana_program::pubkey::Pubkey;
+
+use crate::args::{JsonRpcUrlArgs, watch::accounts::AccountDecoder};
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("decoder").required(true).args(["layout", "idl", "builtin"])))]
+pub struct DecodeArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// An account to decode.
+    #[arg(long)]
+    pub address: Pubkey,
+
+    /// A YAML file that describes the account fields:
+    ///
+    ///   fields:
+    ///     - name: version
+    ///       offset: 0
+    ///       type: u32
+    ///     - name: authority
+    ///       type: pubkey
+    ///     - name: prices
+    ///       type: i64[4]
+    ///     - name: symbol
+    ///       type: string[16]
+    ///
+    /// `offset` defaults to the end of the previous field.  Supported types are `u8` to `u128`,
+    /// `i8` to `i128`, `f32`, `f64`, `bool`, `pubkey`, `bytes[N]` (shown as hex), `string[N]` (a
+    /// fixed size UTF-8 string, with trailing zeros removed), and `T[N]` arrays of any of the
+    /// numeric types, `bool`, or `pubkey`.  All numbers are little endian.
+    #[arg(long)]
+    pub layout: Option<PathBuf>,
+
+    /// An Anchor IDL JSON file, for the program that owns the account.  Both the current and the
+    /// legacy (before Anchor 0.30) IDL formats are supported.
+    ///
+    /// The account type is detected using the account discriminator, unless `--account-type` is
+    /// specified.
+    #[arg(long)]
+    pub idl: Option<PathBuf>,
+
+    /// Name of the account type in the `--idl`.
+    #[arg(long, requires = "idl")]
+    pub account_type: Option<String>,
+
+    /// One of the decoders that are also available in `watch accounts --decode`.
+    #[arg(long, value_enum)]
+    pub builtin: Option<AccountDecoder>,
+}
@@ -0,0 +1,132 @@
+//! Expands `@file` values of repeatable arguments, so that large batches of values do not need to
+//! be spelled out on the command line:
+//!
+//!   --product-pubkey @products.txt
+//!
+//! becomes a `--product-pubkey` argument for every line of `products.txt`.  Leading and trailing
+//! whitespace is removed from every line, and empty lines, as well as lines starting with `#`, are
+//! ignored.  Only arguments that can be repeated are expanded.
+//!
+//! Expansion happens before the `dir://` expansion in [`super::keypair_dirs`], so a file listed
+//! for a `-keypair` argument may contain `dir://` sources.
+
+use std::{collections::HashSet, ffi::OsString, fs, path::Path};
+
+use anyhow::{Context as _, Result};
+use clap::ArgAction;
+
+/// Prefix of an argument value that names a file with the actual values.
+pub const ARG_FILE_PREFIX: char = '@';
+
+/// Replaces every `--<name> @file` and `--<name>=@file` argument, where `--<name>` is a repeatable
+/// argument of the selected (sub)command, with one argument per value listed in the file.
+pub fn expand_args(command: &clap::Command, args: Vec<OsString>) -> Result<Vec<OsString>> {
+    let repeatable = repeatable_args(command, &args);
+
+    let mut res = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let Some(arg_str) = arg.to_str() else {
+            res.push(arg);
+            continue;
+        };
+
+        // Everything after `--` is a positional argument.
+        if arg_str == "--" {
+            res.push(arg);
+            res.extend(args);
+            break;
+        }
+
+        if let Some((name, value)) = arg_str.split_once('=') {
+            let path = value
+                .strip_prefix(ARG_FILE_PREFIX)
+                .filter(|_| repeatable.contains(name));
+            if let Some(path) = path {
+                for value in read_values(name, path)? {
+                    res.push(format!("{name}={value}").into());
+                }
+                continue;
+            }
+        } else if repeatable.contains(arg_str) {
+            let name = arg_str.to_owned();
+            let Some(value) = args.next() else {
+                res.push(arg);
+                break;
+            };
+            match value
+                .to_str()
+                .and_then(|value| value.strip_prefix(ARG_FILE_PREFIX))
+            {
+                Some(path) => {
+                    for value in read_values(&name, path)? {
+                        res.push(name.clone().into());
+                        res.push(value.into());
+                    }
+                }
+                None => {
+                    res.push(arg);
+                    res.push(value);
+                }
+            }
+            continue;
+        }
+
+        res.push(arg);
+    }
+    Ok(res)
+}
+
+/// Long names, with the `--` prefix, of all the repeatable arguments of the `command`, and of the
+/// subcommands selected by the `args`.
+fn repeatable_args(command: &clap::Command, args: &[OsString]) -> HashSet<String> {
+    let mut res = HashSet::new();
+    let mut command = command;
+    let mut add_args = |command: &clap::Command| {
+        res.extend(
+            command
+                .get_arguments()
+                .filter(|arg| matches!(arg.get_action(), ArgAction::Append))
+                .filter_map(|arg| arg.get_long())
+                .map(|long| format!("--{long}")),
+        );
+    };
+
+    add_args(command);
+    for arg in args {
+        let Some(arg) = arg.to_str() else {
+            continue;
+        };
+        if arg == "--" {
+            break;
+        }
+        if arg.starts_with('-') {
+            continue;
+        }
+        // Argument values that happen to match a subcommand name are not distinguished from the
+        // subcommand names.  This could only add more repeatable arguments to the set.
+        let Some(subcommand) = command.find_subcommand(arg) else {
+            continue;
+        };
+        command = subcommand;
+        add_args(command);
+    }
+
+    res
+}
+
+fn read_values(name: &str, path: &str) -> Result<Vec<String>> {
+    let content = fs::read_to_string(Path::new(path)).with_context(|| {
+        format!(
+            "Argument: {}\nFailed to read values from: {path}",
+            name.trim_start_matches('-')
+        )
+    })?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_owned)
+        .collect())
+}
@@ -0,0 +1,14 @@
+use clap::Subcommand;
+
+pub mod compare;
+
+#[derive(Subcommand, Debug)]
+#[command(name = "benchmark")]
+pub enum Command {
+    /// Compares the results of two benchmark runs, and flags metrics that got worse by more than
+    /// a threshold.
+    ///
+    /// Runs are recorded by benchmarks started with `--results-dir`.  Fails if any of the metrics
+    /// regressed, so it can be used as a pre-release check.
+    Compare(compare::CompareArgs),
+}
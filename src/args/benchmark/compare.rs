@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct CompareArgs {
+    /// Directory the benchmark results are stored in.  Runs can then be specified by their ids.
+    #[arg(long, env = "HEISENBERG_RESULTS_DIR")]
+    pub results_dir: Option<PathBuf>,
+
+    /// A metric is flagged as a regression when it got worse by more than this many percent,
+    /// relative to the baseline.
+    #[arg(long, default_value_t = 5.0)]
+    pub threshold: f64,
+
+    /// The run to compare against: either a run id, or a path to a results file.
+    pub baseline: String,
+
+    /// The run to check: either a run id, or a path to a results file.
+    pub candidate: String,
+}
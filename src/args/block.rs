@@ -0,0 +1,13 @@
+use clap::Subcommand;
+
+pub mod inspect;
+
+#[derive(Subcommand, Debug)]
+#[command(name = "block")]
+pub enum Command {
+    /// Summarizes transactions in a block that touch the Oracle or the Price Store programs.
+    ///
+    /// Shows signers, instructions, and outcomes of each transaction.  Answers the "what actually
+    /// landed in this slot?" question.
+    Inspect(inspect::InspectArgs),
+}
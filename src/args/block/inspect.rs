@@ -0,0 +1,39 @@
+use clap::{ArgAction, Args};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::clock::Slot;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct InspectArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// Slot of the block to inspect.
+    #[arg(long)]
+    pub slot: Slot,
+
+    /// Address of the Oracle program.  Transactions that invoke it are included.
+    #[arg(long, env = "HEISENBERG_ORACLE_PROGRAM_ID")]
+    pub oracle_program_id: Option<Pubkey>,
+
+    /// Address of the Price Store program.  Transactions that invoke it are included.
+    #[arg(long, env = "HEISENBERG_PRICE_STORE_PROGRAM_ID")]
+    pub price_store_program_id: Option<Pubkey>,
+
+    /// Include transactions that invoke this program as well.
+    ///
+    /// Can be repeated.
+    #[arg(long, action = ArgAction::Append)]
+    pub program_id: Vec<Pubkey>,
+
+    /// Include all the transactions in the block, ignoring the program filters.
+    ///
+    /// Vote transactions are still excluded, unless `--include-votes` is specified.
+    #[arg(long)]
+    pub all: bool,
+
+    /// Include vote transactions, when `--all` is specified.
+    #[arg(long, requires = "all")]
+    pub include_votes: bool,
+}
@@ -0,0 +1,14 @@
+use clap::Subcommand;
+
+pub mod genesis;
+
+#[derive(Subcommand, Debug)]
+#[command(name = "bootstrap")]
+pub enum Command {
+    /// Produces a primordial accounts file for a Pythnet test cluster from a single config file,
+    /// and optionally creates the genesis ledger with `solana-genesis`.
+    ///
+    /// Combines the `primordial-accounts` generators for the Oracle, the Price Store and the stake
+    /// caps parameters programs, features and funded accounts.
+    Genesis(genesis::GenesisArgs),
+}
@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct GenesisArgs {
+    /// A YAML file describing the genesis content.
+    ///
+    /// All the sections are optional.  Relative paths are resolved relative to the config file
+    /// location:
+    ///
+    ///   oracle:
+    ///     program_id: FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH
+    ///     program_data: programs/oracle.so
+    ///     upgrade_authority: 5yR6...       # Optional.  Non-upgradable, if omitted.
+    ///   price_store:
+    ///     program_id: 3m6sv6HGqEbuyLV84mD7rJn4MAC9LhUa1y1AUNVqcPfr
+    ///     program_data: programs/price_store.so
+    ///   stake_caps_parameters:
+    ///     program_id: ujSFv8q8woXW5PUnby52PQyxYGUudxkrvgN6A631Qmm
+    ///     program_data: programs/stake_caps_parameters.so
+    ///     parameters:                      # Optional.  Initial parameters account content.
+    ///       m: 1000
+    ///       z: 2
+    ///       authority: 5yR6...
+    ///   programs:                          # Any other upgradeable loader programs.
+    ///     - program_id: ...
+    ///       program_data: ...
+    ///   features:
+    ///     - address: ...
+    ///       not_active: false              # Optional.
+    ///   funded_accounts:
+    ///     - address: ...
+    ///       lamports: 1000000000
+    ///   primordial_accounts_files:         # Merged into the output as is.
+    ///     - extra-accounts.yaml
+    #[arg(long)]
+    pub genesis_config: PathBuf,
+
+    /// Where to write the merged primordial accounts.
+    ///
+    /// Printed on stdout, if not specified.  Required with `--ledger`.
+    #[arg(long)]
+    pub primordial_accounts_file: Option<PathBuf>,
+
+    /// Run `solana-genesis`, creating a genesis in this ledger directory.
+    ///
+    /// `--primordial-accounts-file` is passed to `solana-genesis`, together with any arguments
+    /// specified after `--`.
+    #[arg(long)]
+    pub ledger: Option<PathBuf>,
+
+    /// Path to the `solana-genesis` binary.
+    #[arg(long, default_value = "solana-genesis")]
+    pub solana_genesis: PathBuf,
+
+    /// Additional arguments for `solana-genesis`, like the bootstrap validator keys.
+    #[arg(last = true)]
+    pub solana_genesis_args: Vec<String>,
+}
+
+/// Additional validation of the [`GenesisArgs`] instances.
+impl GenesisArgs {
+    pub fn check_are_valid(&self) -> Result<()> {
+        let Self {
+            primordial_accounts_file,
+            ledger,
+            solana_genesis_args,
+            ..
+        } = self;
+
+        if ledger.is_some() && primordial_accounts_file.is_none() {
+            bail!("--primordial-accounts-file is required with --ledger");
+        }
+
+        if ledger.is_none() && !solana_genesis_args.is_empty() {
+            bail!("Arguments for `solana-genesis` are only used with --ledger");
+        }
+
+        Ok(())
+    }
+}
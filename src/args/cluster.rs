@@ -0,0 +1,40 @@
+use clap::Subcommand;
+
+pub mod commitment_lag;
+pub mod fees;
+pub mod health;
+pub mod validators;
+pub mod wait_for_epoch;
+
+#[derive(Subcommand, Debug)]
+#[command(name = "cluster")]
+pub enum Command {
+    /// Periodically reports slot production, skipped slots, block time, TPS, and the RPC node lag.
+    ///
+    /// Useful as context for interpreting benchmark results.
+    Health(health::HealthArgs),
+
+    /// Lists the validators with their stake, commission, network addresses, and delinquency.
+    ///
+    /// Combines the vote accounts with the gossip information.  Nodes that are visible in gossip
+    /// but do not have a vote account, like RPC nodes, are listed as well.
+    Validators(validators::ValidatorsArgs),
+
+    /// Blocks until the cluster reaches the specified epoch or slot.
+    ///
+    /// Allows scripts to sequence steps that depend on the cluster progress, like a stake
+    /// activation followed by a benchmark.
+    WaitForEpoch(wait_for_epoch::WaitForEpochArgs),
+
+    /// Periodically reports the prioritization fees paid in recent slots.
+    ///
+    /// Shows what compute unit prices transactions need to compete with, helping to choose one
+    /// for benchmarks.
+    Fees(fees::FeesArgs),
+
+    /// Tracks the processed, confirmed, and finalized slots, and reports the lag between them.
+    ///
+    /// Flags periods when confirmation or finality stall, while slots are still being produced.
+    /// Such stalls are not visible in the slot production stats reported by `health`.
+    CommitmentLag(commitment_lag::CommitmentLagArgs),
+}
@@ -0,0 +1,42 @@
+use std::time::Duration as StdDuration;
+
+use clap::Args;
+use humantime::Duration;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct CommitmentLagArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// How often to read the processed, confirmed, and finalized slots.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_millis(400).into())]
+    pub interval: Duration,
+
+    /// Length of the window every report covers.
+    ///
+    /// Accepts the same formats as `--interval`.
+    #[arg(long, default_value_t = StdDuration::from_secs(10).into())]
+    pub window: Duration,
+
+    /// A confirmation stall is reported when the confirmed slot is more than this many slots
+    /// behind the processed slot.
+    #[arg(long, default_value_t = 16)]
+    pub max_confirmation_lag: u64,
+
+    /// A finality stall is reported when the finalized slot is more than this many slots behind
+    /// the confirmed slot.
+    ///
+    /// A healthy cluster finalizes slots about 32 slots after they are confirmed.
+    #[arg(long, default_value_t = 64)]
+    pub max_finalization_lag: u64,
+
+    /// Stop after this many reports.  Runs until interrupted, if not specified.
+    #[arg(long)]
+    pub count: Option<usize>,
+}
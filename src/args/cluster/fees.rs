@@ -0,0 +1,47 @@
+use std::{path::PathBuf, time::Duration as StdDuration};
+
+use clap::{ArgAction, Args};
+use humantime::Duration;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct FeesArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// Only consider transactions that write lock this account.
+    ///
+    /// Can be repeated.  When no accounts are specified, fees of all the transactions are
+    /// considered.
+    #[arg(long, action = ArgAction::Append)]
+    pub account: Vec<Pubkey>,
+
+    /// Only consider transactions that write lock accounts owned by this program, such as the
+    /// Oracle price accounts, or the Price Store buffers.
+    ///
+    /// Can be repeated.  The RPC node limits the number of accounts a query can use, so only the
+    /// first accounts are used, if the programs own too many.
+    #[arg(long, action = ArgAction::Append)]
+    pub accounts_of_program: Vec<Pubkey>,
+
+    /// How often to query the fees.
+    ///
+    /// The RPC node remembers fees for the last 150 slots, so intervals longer than a minute will
+    /// miss some slots.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(10).into())]
+    pub interval: Duration,
+
+    /// Stop after this many reports.  Runs until interrupted, if not specified.
+    #[arg(long)]
+    pub count: Option<usize>,
+
+    /// Write the fee of every observed slot into this CSV file.
+    #[arg(long)]
+    pub csv: Option<PathBuf>,
+}
@@ -0,0 +1,24 @@
+use std::time::Duration as StdDuration;
+
+use clap::Args;
+use humantime::Duration;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct HealthArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// Length of the window every report covers.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(10).into())]
+    pub window: Duration,
+
+    /// Stop after this many reports.  Runs until interrupted, if not specified.
+    #[arg(long)]
+    pub count: Option<usize>,
+}
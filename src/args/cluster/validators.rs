@@ -0,0 +1,13 @@
+use clap::Args;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct ValidatorsArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// Only show delinquent validators.
+    #[arg(long)]
+    pub delinquent_only: bool,
+}
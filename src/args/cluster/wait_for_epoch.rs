@@ -0,0 +1,36 @@
+use std::time::Duration as StdDuration;
+
+use clap::{ArgGroup, Args};
+use humantime::Duration;
+use solana_sdk::clock::{Epoch, Slot};
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("target").required(true).args(["epoch", "slot"])))]
+pub struct WaitForEpochArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// Wait until the cluster enters this epoch.
+    #[arg(long)]
+    pub epoch: Option<Epoch>,
+
+    /// Wait until the cluster reaches this slot.
+    #[arg(long)]
+    pub slot: Option<Slot>,
+
+    /// How often to check the cluster state.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(1).into())]
+    pub poll_interval: Duration,
+
+    /// Fail if the target is not reached within this time.  Waits indefinitely, if not specified.
+    ///
+    /// Same format as `--poll-interval`.
+    #[arg(long)]
+    pub timeout: Option<Duration>,
+}
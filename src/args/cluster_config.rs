@@ -0,0 +1,164 @@
+//! Named cluster aliases, defined in a config file.
+//!
+//! Switching between several test clusters requires changing the RPC URL, the WebSocket URL, and
+//! all the program ids.  A cluster alias, selected with `--cluster`, provides default values for
+//! all of these arguments at once.
+//!
+//! The config file is a YAML file that looks like this:
+//!
+//!   clusters:
+//!     pythnet-staging:
+//!       rpc_url: https://staging.example.com
+//!       websocket_url: wss://staging.example.com
+//!       oracle_program_id: FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH
+//!       price_store_program_id: 3m6sv6HGqEbuyLV84mD7rJn4MAC9LhUa1y1AUNVqcPfr
+//!       stake_caps_parameters_program_id: ujSFv8q8woXW5PUnby52PQyxYGUudxkrvgN6A631Qmm
+//!
+//! All the fields are optional.
+//!
+//! Program ids are used for the `--program-id` argument of the matching command area.  The Oracle
+//! and the Price Store program ids are also used for the `--oracle-program-id` and the
+//! `--price-store-program-id` arguments of any command.
+
+use std::{
+    collections::HashMap,
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use clap::Command;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    clusters: HashMap<String, ClusterAlias>,
+}
+
+/// Values are kept as strings, as they are used as argument default values, and are parsed by
+/// `clap`, together with all the other arguments.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct ClusterAlias {
+    rpc_url: Option<String>,
+    websocket_url: Option<String>,
+    oracle_program_id: Option<String>,
+    price_store_program_id: Option<String>,
+    stake_caps_parameters_program_id: Option<String>,
+}
+
+/// If a cluster alias is selected via `--cluster` or `HEISENBERG_CLUSTER`, updates default values
+/// of all the arguments this alias specifies.
+///
+/// `args` are searched for `--cluster` and `--config`.  The first occurrence is used.
+pub fn apply_cluster_alias(command: Command, args: &[OsString]) -> Result<Command> {
+    let Some(cluster) =
+        find_arg_value(args, "--cluster").or_else(|| env::var("HEISENBERG_CLUSTER").ok())
+    else {
+        return Ok(command);
+    };
+
+    let config_path = match find_arg_value(args, "--config") {
+        Some(config_path) => PathBuf::from(config_path),
+        None => default_config_path()?,
+    };
+
+    let config = read_config_file(&config_path)?;
+    let alias = config.clusters.get(&cluster).ok_or_else(|| {
+        anyhow!(
+            "Cluster alias \"{cluster}\" is not defined in: {}",
+            config_path.to_string_lossy()
+        )
+    })?;
+
+    Ok(apply_alias_defaults(command, alias, None))
+}
+
+/// `HEISENBERG_CONFIG`, if set, or `~/.config/pythnet-heisenberg/config.yaml`.
+fn default_config_path() -> Result<PathBuf> {
+    if let Some(path) = env::var_os("HEISENBERG_CONFIG") {
+        return Ok(PathBuf::from(path));
+    }
+
+    let home = env::var_os("HOME")
+        .context("Can not locate the config file: neither HEISENBERG_CONFIG nor HOME are set")?;
+    Ok(Path::new(&home).join(".config/pythnet-heisenberg/config.yaml"))
+}
+
+fn read_config_file(path: &Path) -> Result<ConfigFile> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.to_string_lossy()))?;
+    serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse config file: {}", path.to_string_lossy()))
+}
+
+/// `clap` needs to know the argument default values before it parses the command line, so we need
+/// to look for `--cluster` and `--config` ourselves.
+pub fn find_arg_value(args: &[OsString], name: &str) -> Option<String> {
+    let mut args = args.iter().filter_map(|arg| arg.to_str());
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next().map(str::to_owned);
+        }
+        if let Some(value) = arg
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value.to_owned());
+        }
+    }
+    None
+}
+
+/// Walks all the subcommands, setting default values for arguments with matching names.
+///
+/// `area` is the name of the top level command, as it determines which program id should be used
+/// for the `--program-id` argument.
+fn apply_alias_defaults(mut command: Command, alias: &ClusterAlias, area: Option<&str>) -> Command {
+    let ClusterAlias {
+        rpc_url,
+        websocket_url,
+        oracle_program_id,
+        price_store_program_id,
+        stake_caps_parameters_program_id,
+    } = alias;
+
+    let program_id = match area {
+        Some("oracle") => oracle_program_id,
+        Some("price-store") => price_store_program_id,
+        Some("stake-caps-parameters") => stake_caps_parameters_program_id,
+        _ => &None,
+    };
+
+    for (id, value) in [
+        ("rpc_url", rpc_url),
+        ("websocket_url", websocket_url),
+        ("program_id", program_id),
+        ("oracle_program_id", oracle_program_id),
+        ("price_store_program_id", price_store_program_id),
+    ] {
+        let Some(value) = value else {
+            continue;
+        };
+        if command.get_arguments().any(|arg| arg.get_id() == id) {
+            command = command.mut_arg(id, |arg| arg.default_value(value.clone()).required(false));
+        }
+    }
+
+    let subcommands = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_owned())
+        .collect::<Vec<_>>();
+    for name in subcommands {
+        let area = area.unwrap_or(&name).to_owned();
+        command = command.mut_subcommand(&name, |subcommand| {
+            apply_alias_defaults(subcommand, alias, Some(&area))
+        });
+    }
+
+    command
+}
@@ -0,0 +1,14 @@
+use clap::Subcommand;
+
+pub mod up;
+
+#[derive(Subcommand, Debug)]
+#[command(name = "dev")]
+pub enum Command {
+    /// Starts a local single node cluster with the Pythnet programs, and initializes them.
+    ///
+    /// Runs `solana-test-validator` with the accounts described by a `bootstrap genesis` config,
+    /// waits for the node to become healthy, and then runs the Oracle and the Price Store
+    /// initialization.  The validator is stopped when the command exits.
+    Up(up::UpArgs),
+}
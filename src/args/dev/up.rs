@@ -0,0 +1,65 @@
+use std::{path::PathBuf, time::Duration as StdDuration};
+
+use clap::Args;
+use humantime::Duration;
+use solana_program::pubkey::Pubkey;
+
+#[derive(Args, Debug)]
+pub struct UpArgs {
+    /// Path to the validator binary.
+    ///
+    /// Needs to accept the `solana-test-validator` arguments.
+    #[arg(long, default_value = "solana-test-validator")]
+    pub validator: PathBuf,
+
+    /// Ledger directory for the validator.  Any existing ledger in this directory is reset.
+    #[arg(long, default_value = "test-ledger")]
+    pub ledger: PathBuf,
+
+    /// RPC port for the validator.  The WebSocket port is the next one.
+    #[arg(long, default_value_t = 8899)]
+    pub rpc_port: u16,
+
+    /// A `bootstrap genesis` config, describing programs and accounts to add to the genesis.
+    ///
+    /// See `bootstrap genesis --help` for the format.
+    #[arg(long)]
+    pub genesis_config: Option<PathBuf>,
+
+    /// A keypair file for the account that pays for the initialization.
+    ///
+    /// The validator mints all the initial SOL into this account.  It also becomes the Oracle
+    /// master authority and the Price Store authority, so it needs to be the Oracle program
+    /// upgrade authority.
+    #[arg(long, env = "HEISENBERG_PAYER_KEYPAIR")]
+    pub payer_keypair: PathBuf,
+
+    /// Address of the Oracle program.  When specified, Oracle permissions and a mapping account
+    /// are initialized.
+    ///
+    /// The mapping keypair is written into the ledger directory.
+    #[arg(long)]
+    pub oracle_program_id: Option<Pubkey>,
+
+    /// Address of the Price Store program.  When specified, the Price Store is initialized.
+    #[arg(long)]
+    pub price_store_program_id: Option<Pubkey>,
+
+    /// How long to wait for the validator to become healthy.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(60).into())]
+    pub startup_timeout: Duration,
+
+    /// Stop the validator as soon as the initialization is done.
+    ///
+    /// By default, the validator keeps running until an INT or a TERM signal is received.
+    #[arg(long)]
+    pub exit_after_init: bool,
+
+    /// Additional arguments for the validator.
+    #[arg(last = true)]
+    pub validator_args: Vec<String>,
+}
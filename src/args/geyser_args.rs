@@ -0,0 +1,37 @@
+use clap::Args;
+use pythnet_heisenberg::geyser::GeyserConfig;
+use reqwest::Url;
+
+#[derive(Args, Debug)]
+pub struct GeyserArgs {
+    /// A Yellowstone gRPC endpoint to receive updates from, instead of the RPC WebSocket
+    /// subscriptions.
+    ///
+    /// The node needs to run the Yellowstone Geyser plugin.
+    #[arg(long, value_name = "URL")]
+    pub geyser_endpoint: Option<Url>,
+
+    /// An access token for the `--geyser-endpoint`, sent in the `x-token` header.
+    #[arg(
+        long,
+        value_name = "TOKEN",
+        env = "HEISENBERG_GEYSER_X_TOKEN",
+        requires = "geyser_endpoint"
+    )]
+    pub geyser_x_token: Option<String>,
+}
+
+impl GeyserArgs {
+    /// Returns a [`GeyserConfig`] if an endpoint was specified.
+    pub fn into_config(self) -> Option<GeyserConfig> {
+        let Self {
+            geyser_endpoint,
+            geyser_x_token,
+        } = self;
+
+        geyser_endpoint.map(|endpoint| GeyserConfig {
+            endpoint,
+            x_token: geyser_x_token,
+        })
+    }
+}
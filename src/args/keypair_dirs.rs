@@ -0,0 +1,93 @@
+//! Expands `dir://` keypair sources on the command line and in scenario files, so that a group of
+//! keypair files can be specified with a single argument:
+//!
+//!   --publisher-keypair dir://keys/publisher-*.json
+//!
+//! becomes a `--publisher-keypair` argument for every matching file.  Only arguments with names
+//! that end in `-keypair` are expanded.  See [`expand_keypair_dir()`] for the pattern syntax.
+
+use std::{ffi::OsString, path::PathBuf};
+
+use anyhow::{Context as _, Result};
+use pythnet_heisenberg::keypair_ext::{KEYPAIR_DIR_PREFIX, expand_keypair_dir};
+
+/// Replaces every `--<name>-keypair dir://...` and `--<name>-keypair=dir://...` argument with one
+/// argument per matching keypair file.
+pub fn expand_args(args: Vec<OsString>) -> Result<Vec<OsString>> {
+    let mut res = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let Some(arg_str) = arg.to_str() else {
+            res.push(arg);
+            continue;
+        };
+
+        // Everything after `--` is a positional argument.
+        if arg_str == "--" {
+            res.push(arg);
+            res.extend(args);
+            break;
+        }
+
+        if let Some((name, value)) = arg_str.split_once('=') {
+            if is_keypair_arg(name) && value.starts_with(KEYPAIR_DIR_PREFIX) {
+                for path in expand(name, value)? {
+                    let mut arg = OsString::from(format!("{name}="));
+                    arg.push(path);
+                    res.push(arg);
+                }
+                continue;
+            }
+        } else if is_keypair_arg(arg_str) {
+            let name = arg_str.to_owned();
+            res.push(arg);
+            let Some(value) = args.next() else {
+                break;
+            };
+            match value.to_str() {
+                Some(value) if value.starts_with(KEYPAIR_DIR_PREFIX) => {
+                    let mut paths = expand(&name, value)?.into_iter();
+                    if let Some(first) = paths.next() {
+                        res.push(first.into());
+                    }
+                    for path in paths {
+                        res.push(name.clone().into());
+                        res.push(path.into());
+                    }
+                }
+                _ => res.push(value),
+            }
+            continue;
+        }
+
+        res.push(arg);
+    }
+    Ok(res)
+}
+
+/// Expands `dir://` sources among scenario file values of the argument with the `name`.
+pub fn expand_values(name: &str, values: Vec<String>) -> Result<Vec<String>> {
+    if !name.ends_with("-keypair") {
+        return Ok(values);
+    }
+
+    let mut res = Vec::with_capacity(values.len());
+    for value in values {
+        if !value.starts_with(KEYPAIR_DIR_PREFIX) {
+            res.push(value);
+            continue;
+        }
+        for path in expand(name, &value)? {
+            res.push(path.to_string_lossy().into_owned());
+        }
+    }
+    Ok(res)
+}
+
+fn is_keypair_arg(arg: &str) -> bool {
+    arg.starts_with("--") && arg.ends_with("-keypair")
+}
+
+fn expand(name: &str, value: &str) -> Result<Vec<PathBuf>> {
+    expand_keypair_dir(value).with_context(|| format!("Argument: {}", name.trim_start_matches('-')))
+}
@@ -0,0 +1,10 @@
+use clap::Subcommand;
+
+pub mod generate;
+
+#[derive(Subcommand, Debug)]
+#[command(name = "keys")]
+pub enum Command {
+    /// Generates a set of consistently named keypair files.
+    Generate(generate::GenerateArgs),
+}
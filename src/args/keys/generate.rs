@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use clap::{ArgAction, Args};
+
+#[derive(Args, Debug)]
+pub struct GenerateArgs {
+    /// A directory to write the keypair files into.
+    ///
+    /// The directory is created, if it does not exist.
+    #[arg(long)]
+    pub dir: PathBuf,
+
+    /// Number of keypairs to generate for each `--prefix`.
+    #[arg(long)]
+    pub count: usize,
+
+    /// Keypairs are named `{prefix}-{i}.json`, where `i` goes from `--start-index` to
+    /// `--start-index` + `--count` - 1.
+    ///
+    /// Can be repeated, to generate multiple sets of keypairs at once.  For example, `--prefix
+    /// publisher --prefix payer` generates both `publisher-{i}.json` and `payer-{i}.json` files.
+    #[arg(long, action = ArgAction::Append, required = true)]
+    pub prefix: Vec<String>,
+
+    /// Index of the first generated keypair.
+    ///
+    /// Useful when extending an existing set of keypairs.
+    #[arg(long, default_value_t = 0)]
+    pub start_index: usize,
+
+    /// Generate new keypairs for the files that already exist, instead of reusing them.
+    ///
+    /// Existing keypairs are kept next to the new ones, as `{prefix}-{i}.json.bak`, or as
+    /// `{prefix}-{i}.json.bak.{n}`, when there are older backups.  Files that do not hold a keypair
+    /// are never replaced.
+    #[arg(long)]
+    pub replace_existing: bool,
+
+    /// A CSV file to write the manifest into.
+    ///
+    /// Each line holds the prefix, the index, the public key, and the keypair file path:
+    ///
+    ///   "[prefix],[index],[pubkey],[path]"
+    ///
+    /// The manifest is always printed on stdout.
+    #[arg(long)]
+    pub manifest_file: Option<PathBuf>,
+}
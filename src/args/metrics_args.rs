@@ -0,0 +1,72 @@
+use std::time::Duration as StdDuration;
+
+use clap::{ArgAction, Args};
+use humantime::Duration;
+use pythnet_heisenberg::metrics_sink::{MetricsConfig, MetricsSink};
+use reqwest::Url;
+
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Metrics")]
+pub struct MetricsArgs {
+    /// An InfluxDB line protocol write endpoint, to push measurements to.  Works with InfluxDB,
+    /// VictoriaMetrics, and other compatible databases.  Include any database or bucket
+    /// parameters into the URL:
+    ///
+    ///   http://localhost:8086/api/v2/write?org=pyth&bucket=soak&precision=ns
+    ///
+    ///   http://localhost:8428/write
+    #[arg(long, value_name = "URL", env = "HEISENBERG_METRICS_URL")]
+    pub metrics_url: Option<Url>,
+
+    /// An access token for the `--metrics-url`, sent as `Authorization: Token <TOKEN>`.
+    #[arg(long, value_name = "TOKEN", env = "HEISENBERG_METRICS_TOKEN")]
+    pub metrics_token: Option<String>,
+
+    /// A tag added to every measurement, in the `KEY=VALUE` form.  Useful to tell apart runs that
+    /// write into the same database.
+    ///
+    /// Can be repeated.
+    #[arg(
+        long,
+        value_name = "KEY=VALUE",
+        action = ArgAction::Append,
+        value_parser = tag_parser,
+    )]
+    pub metrics_tag: Vec<(String, String)>,
+
+    /// How often to send accumulated measurements.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(1).into())]
+    pub metrics_flush_interval: Duration,
+}
+
+impl MetricsArgs {
+    /// Starts a [`MetricsSink`] if an endpoint was specified.
+    pub fn start_sink(self) -> Option<MetricsSink> {
+        let Self {
+            metrics_url,
+            metrics_token,
+            metrics_tag,
+            metrics_flush_interval,
+        } = self;
+
+        metrics_url.map(|url| {
+            MetricsSink::start(MetricsConfig {
+                url,
+                token: metrics_token,
+                tags: metrics_tag,
+                flush_interval: metrics_flush_interval.into(),
+            })
+        })
+    }
+}
+
+fn tag_parser(input: &str) -> Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_owned(), value.to_owned())),
+        _ => Err(format!("Expected KEY=VALUE, got: {input}")),
+    }
+}
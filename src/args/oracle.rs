@@ -1,11 +1,18 @@
 use clap::Subcommand;
 
+pub mod accumulator_status;
 pub mod add_price;
 pub mod add_product;
 pub mod add_publisher;
+pub mod addresses;
+pub mod feed_index;
 pub mod get_price_feed_index;
 pub mod init_mapping;
+pub mod publisher_matrix;
+pub mod stale_aggregates;
+pub mod sync_parameters;
 pub mod update_permissions;
+pub mod verify_accumulator;
 
 #[derive(Subcommand, Debug)]
 #[command(name = "oracle")]
@@ -25,6 +32,41 @@ pub enum Command {
     /// Adds a publisher to a price account.
     AddPublisher(add_publisher::AddPublisherArgs),
 
+    /// Copies the `min_pub`, the `max_latency`, and optionally the exponent, of every feed from a
+    /// reference cluster to the matching feed of the target cluster.  Feeds are matched by the
+    /// product symbol.
+    SyncParameters(sync_parameters::SyncParametersArgs),
+
     /// Reads the price feed index for a particular price account.
     GetPriceFeedIndex(get_price_feed_index::GetPriceFeedIndexArgs),
+
+    /// Shows accumulator related state of price accounts: the accumulator flags, and the content
+    /// of the message buffer accounts.  Checks that the state is consistent with the price.
+    AccumulatorStatus(accumulator_status::AccumulatorStatusArgs),
+
+    /// Keeps checking that price updates are reflected into the accumulator, for a while.
+    VerifyAccumulator(verify_accumulator::VerifyAccumulatorArgs),
+
+    /// Shows which publishers are authorized on which price accounts, as a price account by
+    /// publisher table.
+    ///
+    /// In the text mode the table is printed as CSV.  In the JSON mode, every price account is
+    /// printed as an object, with the list of authorized publishers, and the list of requested
+    /// publishers that are missing.
+    PublisherMatrix(publisher_matrix::PublisherMatrixArgs),
+
+    /// Finds feeds whose aggregate price is no longer updated.
+    ///
+    /// Reads all the price accounts, and reports the ones where the last successful aggregation
+    /// happened more than `--max-lag-slots` slots ago, along with the aggregate status and the
+    /// number of publishers still quoting.  Fails if any are found.
+    StaleAggregates(stale_aggregates::StaleAggregatesArgs),
+
+    /// Prints the program derived addresses used by the Oracle program: the permissions account,
+    /// the program data account, and the authority used to write into the message buffers.
+    Addresses(addresses::AddressesArgs),
+
+    #[command(subcommand)]
+    /// Tracks feed index allocation across price accounts and benchmark configurations.
+    FeedIndex(feed_index::Command),
 }
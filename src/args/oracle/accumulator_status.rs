@@ -0,0 +1,32 @@
+use clap::{ArgAction, Args};
+use pythnet_heisenberg::oracle::accounts::message_buffer::MESSAGE_BUFFER_PROGRAM_ID;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct AccumulatorStatusArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    #[command(flatten)]
+    pub accumulator: AccumulatorArgs,
+}
+
+/// Accounts involved in getting price updates into the accumulator.
+#[derive(Args, Debug)]
+pub struct AccumulatorArgs {
+    /// Address of the Oracle program.
+    #[arg(long, env = "HEISENBERG_ORACLE_PROGRAM_ID")]
+    pub program_id: Pubkey,
+
+    /// Address of the Message Buffer program.  Message buffer accounts are derived from it.
+    #[arg(long, default_value_t = MESSAGE_BUFFER_PROGRAM_ID)]
+    pub message_buffer_program_id: Pubkey,
+
+    /// An address of a price account to check.
+    ///
+    /// Repeat to check multiple price accounts.
+    #[arg(long, required = true, action = ArgAction::Append)]
+    pub price_pubkey: Vec<Pubkey>,
+}
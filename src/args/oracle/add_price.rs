@@ -12,15 +12,12 @@ pub struct AddPriceArgs {
     pub json_rpc_url: JsonRpcUrlArgs,
 
     /// Address of the Oracle program.
-    #[arg(long)]
+    #[arg(long, env = "HEISENBERG_ORACLE_PROGRAM_ID")]
     pub program_id: Pubkey,
 
     /// An address of the permissions account for this Oracle.
     ///
-    /// It can be computed like this, and defaults to this value if not specified:
-    ///
-    ///   solana find-program-derived-address
-    ///     "[Oracle program pubkey]" string:permissions
+    /// Defaults to the program derived address, as printed by `oracle addresses`.
     #[arg(long)]
     pub permissions_account: Option<Pubkey>,
 
@@ -44,7 +41,8 @@ pub struct AddPriceArgs {
     /// If the path does not point to an existing file, a keypair will be generated and written to
     /// this file.
     ///
-    /// The account is not expected to exist before the call.
+    /// If the account already exists, as a price of the same product, it was added by an earlier
+    /// run, and is skipped.
     ///
     /// The tool will create an account at this address, with an appropriate size, funded by the
     /// `--funding_keypair`, and then transfer the ownership to the Oracle program.
@@ -56,6 +54,25 @@ pub struct AddPriceArgs {
     #[arg(long, action = ArgAction::Append)]
     pub price_keypair: Vec<PathBuf>,
 
+    /// Instead of listing `--price-keypair` files, generate `--count` price keypairs for every
+    /// `--product-pubkey` in this directory.
+    ///
+    /// Keypairs are named `price-{i}.json`, with `i` starting from 0.  Existing files are reused,
+    /// and prices that were already added are skipped, so a failed run can be repeated with the
+    /// same arguments.
+    ///
+    /// Once the prices are added, a manifest is printed and written into `manifest.csv` in this
+    /// directory.  It lists all the prices, including the ones added by the earlier runs.  Each line holds the product, the price, and the feed index assigned to the
+    /// price:
+    ///
+    ///   "[product pubkey],[price pubkey],[feed index]"
+    #[arg(long, conflicts_with = "price_keypair", requires = "count")]
+    pub price_keypair_dir: Option<PathBuf>,
+
+    /// Number of prices to add to every product, when using `--price-keypair-dir`.
+    #[arg(long, requires = "price_keypair_dir")]
+    pub count: Option<usize>,
+
     /// Exponent of the price integer value.
     ///
     /// To get an actual price from the integer price stored in the price feed, you need to multiply
@@ -75,10 +92,31 @@ impl AddPriceArgs {
         let Self {
             product_pubkey: product_pubkeys,
             price_keypair: price_keypairs,
+            price_keypair_dir,
+            count,
             exponent: exponents,
             ..
         } = self;
 
+        if price_keypair_dir.is_some() {
+            if *count == Some(0) {
+                bail!("--count should be above zero");
+            }
+
+            if product_pubkeys.len() != exponents.len() {
+                bail!(
+                    "--product-pubkey and --exponent arguments should be repeated the same number \
+                     of times.\n\
+                     Provided --product-pubkey arguments: {}\n\
+                     Provided --exponent arguments: {}",
+                    product_pubkeys.len(),
+                    exponents.len(),
+                );
+            }
+
+            return Ok(());
+        }
+
         if price_keypairs.len() != product_pubkeys.len() {
             bail!(
                 "--price-keypair and --product-keypair arguments should be repeated the same \
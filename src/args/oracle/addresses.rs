@@ -0,0 +1,21 @@
+use clap::{ArgAction, Args};
+use pythnet_heisenberg::oracle::accounts::message_buffer::MESSAGE_BUFFER_PROGRAM_ID;
+use solana_program::pubkey::Pubkey;
+
+#[derive(Args, Debug)]
+pub struct AddressesArgs {
+    /// Address of the Oracle program.
+    #[arg(long, env = "HEISENBERG_ORACLE_PROGRAM_ID")]
+    pub program_id: Pubkey,
+
+    /// Address of the Message Buffer program.  The Oracle program CPI authority, and the message
+    /// buffer accounts are derived from it.
+    #[arg(long, default_value_t = MESSAGE_BUFFER_PROGRAM_ID)]
+    pub message_buffer_program_id: Pubkey,
+
+    /// An address of a price account, to also print the message buffer account for.
+    ///
+    /// Can be repeated.
+    #[arg(long, action = ArgAction::Append)]
+    pub price_pubkey: Vec<Pubkey>,
+}
@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use solana_program::pubkey::Pubkey;
+
+pub mod allocate;
+pub mod check;
+pub mod gaps;
+pub mod list;
+
+#[derive(Subcommand, Debug)]
+#[command(name = "feed-index")]
+pub enum Command {
+    /// Lists feed indices used by the Oracle price accounts, and reserved in the registry.
+    List(list::ListArgs),
+
+    /// Lists unused feed index ranges, below the largest used index.
+    Gaps(gaps::GapsArgs),
+
+    /// Reserves the first free range of feed indices in the registry.
+    Allocate(allocate::AllocateArgs),
+
+    /// Detects feed indices used by more than one price account, or reserved by more than one
+    /// registry entry.
+    Check(check::CheckArgs),
+}
+
+/// Where used feed indices come from.
+#[derive(Args, Debug)]
+pub struct FeedIndexSourceArgs {
+    /// Address of the Oracle program.  Feed indices of all its price accounts are considered used.
+    #[arg(long, env = "HEISENBERG_ORACLE_PROGRAM_ID")]
+    pub program_id: Option<Pubkey>,
+
+    /// A YAML file with feed index ranges reserved for benchmarks and other test setups.  For
+    /// example, a `price-store benchmark1` run would reserve its
+    /// `--price-feed-index-start`..`--price-feed-index-end` range, so that concurrent runs do not
+    /// step on each other:
+    ///
+    ///   reservations:
+    ///     - name: benchmark-a
+    ///       start: 1000
+    ///       end: 1099
+    #[arg(long, env = "HEISENBERG_FEED_INDEX_REGISTRY")]
+    pub registry: Option<PathBuf>,
+}
@@ -0,0 +1,52 @@
+use anyhow::{Result, bail};
+use clap::Args;
+
+use crate::args::{JsonRpcUrlArgs, oracle::feed_index::FeedIndexSourceArgs};
+
+#[derive(Args, Debug)]
+pub struct AllocateArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    #[command(flatten)]
+    pub source: FeedIndexSourceArgs,
+
+    /// Name of the new reservation.  Must be unique in the registry.
+    #[arg(long)]
+    pub name: String,
+
+    /// Number of consecutive feed indices to reserve.
+    #[arg(long)]
+    pub count: u32,
+
+    /// Smallest feed index that can be reserved.  Index 0 means "no feed index" in the Oracle
+    /// program, so it is never allocated.
+    #[arg(long, default_value_t = 1)]
+    pub min_index: u32,
+}
+
+impl AllocateArgs {
+    pub fn check_are_valid(&self) -> Result<()> {
+        let Self {
+            json_rpc_url: _,
+            source,
+            name: _,
+            count,
+            min_index,
+        } = self;
+
+        if source.registry.is_none() {
+            bail!("--registry is required, as this is where the reservation is recorded");
+        }
+
+        if *count == 0 {
+            bail!("--count must be at least 1");
+        }
+
+        if *min_index == 0 {
+            bail!("--min-index must be at least 1");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use std::ops::RangeInclusive;
+
+use clap::{ArgAction, Args};
+
+use crate::args::{JsonRpcUrlArgs, oracle::feed_index::FeedIndexSourceArgs};
+
+#[derive(Args, Debug)]
+pub struct CheckArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    #[command(flatten)]
+    pub source: FeedIndexSourceArgs,
+
+    /// An additional reservation to check, as if it was in the registry, in the
+    /// `NAME:START-END` form.  Useful to check a benchmark configuration before starting it.
+    ///
+    /// Can be repeated.
+    #[arg(
+        long,
+        value_name = "NAME:START-END",
+        action = ArgAction::Append,
+        value_parser = reservation_parser,
+    )]
+    pub reservation: Vec<(String, RangeInclusive<u32>)>,
+}
+
+fn reservation_parser(input: &str) -> Result<(String, RangeInclusive<u32>), String> {
+    let Some((name, range)) = input.rsplit_once(':') else {
+        return Err(format!("Expected NAME:START-END, got: {input}"));
+    };
+    let Some((start, end)) = range.split_once('-') else {
+        return Err(format!("Expected START-END after the name, got: {range}"));
+    };
+
+    let start = start
+        .parse::<u32>()
+        .map_err(|err| format!("Invalid range start: {start}: {err}"))?;
+    let end = end
+        .parse::<u32>()
+        .map_err(|err| format!("Invalid range end: {end}: {err}"))?;
+    if start > end {
+        return Err(format!("Range start {start} is after the range end {end}"));
+    }
+
+    Ok((name.to_owned(), start..=end))
+}
@@ -0,0 +1,12 @@
+use clap::Args;
+
+use crate::args::{JsonRpcUrlArgs, oracle::feed_index::FeedIndexSourceArgs};
+
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    #[command(flatten)]
+    pub source: FeedIndexSourceArgs,
+}
@@ -11,15 +11,12 @@ pub struct InitMappingArgs {
     pub json_rpc_url: JsonRpcUrlArgs,
 
     /// Address of the Oracle program.
-    #[arg(long)]
+    #[arg(long, env = "HEISENBERG_ORACLE_PROGRAM_ID")]
     pub program_id: Pubkey,
 
     /// An address of the permissions account for this Oracle.
     ///
-    /// It can be computed like this, and defaults to this value if not specified:
-    ///
-    ///   solana find-program-derived-address
-    ///     "[Oracle program pubkey]" string:permissions
+    /// Defaults to the program derived address, as printed by `oracle addresses`.
     #[arg(long)]
     pub permissions_account: Option<Pubkey>,
 
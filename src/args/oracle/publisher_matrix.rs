@@ -0,0 +1,26 @@
+use clap::{ArgAction, Args};
+use solana_program::pubkey::Pubkey;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct PublisherMatrixArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// Address of the Oracle program.
+    #[arg(long, env = "HEISENBERG_ORACLE_PROGRAM_ID")]
+    pub program_id: Pubkey,
+
+    /// A publisher to include as a column.
+    ///
+    /// Can be repeated.  Defaults to all the publishers authorized on any of the price accounts.
+    #[arg(long, action = ArgAction::Append)]
+    pub publisher: Vec<Pubkey>,
+
+    /// Only include price accounts where at least one of the publishers is not authorized.
+    ///
+    /// Combined with `--publisher`, checks that an `add-publisher` batch covered every feed.
+    #[arg(long)]
+    pub missing_only: bool,
+}
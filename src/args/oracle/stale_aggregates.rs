@@ -0,0 +1,24 @@
+use clap::Args;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct StaleAggregatesArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// Address of the Oracle program.
+    #[arg(long, env = "HEISENBERG_ORACLE_PROGRAM_ID")]
+    pub program_id: Pubkey,
+
+    /// Aggregates last computed more than this many slots before the current slot are stale.
+    ///
+    /// Publishers that quoted in the same number of slots are counted as quoting.
+    #[arg(long, default_value_t = 25)]
+    pub max_lag_slots: u64,
+
+    /// Only report feeds with stale aggregates.
+    #[arg(long)]
+    pub stale_only: bool,
+}
@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use reqwest::Url;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct SyncParametersArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// Address of the Oracle program on the target cluster, set up by the `--rpc-url`.
+    #[arg(long, env = "HEISENBERG_ORACLE_PROGRAM_ID")]
+    pub program_id: Pubkey,
+
+    /// An address of the permissions account for the target Oracle.
+    ///
+    /// Defaults to the program derived address, as printed by `oracle addresses`.
+    #[arg(long)]
+    pub permissions_account: Option<Pubkey>,
+
+    /// A keypair file for the account that pays for the update transactions.
+    ///
+    /// It also needs to be the `master_authority` from the permissions account, so that the price
+    /// accounts do not need to sign.
+    #[arg(long)]
+    pub funding_keypair: PathBuf,
+
+    /// An HTTP address of a node of the reference cluster, usually production, to copy the
+    /// parameters from.
+    #[arg(long, value_name = "URL")]
+    pub reference_rpc_url: Url,
+
+    /// Address of the Oracle program on the reference cluster.
+    ///
+    /// Defaults to the `--program-id`.
+    #[arg(long)]
+    pub reference_program_id: Option<Pubkey>,
+
+    /// Also copy the exponent.  Changing the exponent resets the price account, so the price is
+    /// unavailable until the publishers update it again.
+    #[arg(long)]
+    pub sync_exponent: bool,
+}
@@ -11,22 +11,21 @@ pub struct UpdatePermissionsArgs {
     pub json_rpc_url: JsonRpcUrlArgs,
 
     /// Address of the Oracle program.
-    #[arg(long)]
+    #[arg(long, env = "HEISENBERG_ORACLE_PROGRAM_ID")]
     pub program_id: Pubkey,
 
     /// A keypair file for the account that would pay for the permissions account.
     ///
     /// It also needs to be an account that controls upgrades of the Oracle program.  This is the
     /// only account that can update permissions.
+    ///
+    /// Can also be a hardware wallet, specified as `usb://ledger?key=0`.
     #[arg(long)]
     pub funding_keypair: PathBuf,
 
     /// An address of the permissions account for this Oracle.
     ///
-    /// It can be computed like this, and defaults to this value if not specified:
-    ///
-    ///   solana find-program-derived-address
-    ///     "[Oracle program pubkey]" string:permissions
+    /// Defaults to the program derived address, as printed by `oracle addresses`.
     #[arg(long)]
     pub permissions_account: Option<Pubkey>,
 
@@ -69,4 +68,8 @@ pub struct UpdatePermissionsArgs {
     // See `pyth-client/program/rust/src/accounts/permission.rs`.
     #[arg(long)]
     pub security_authority: Pubkey,
+
+    /// Do not ask for a confirmation before sending the transaction.
+    #[arg(long)]
+    pub yes: bool,
 }
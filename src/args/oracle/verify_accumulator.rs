@@ -0,0 +1,33 @@
+use std::time::Duration as StdDuration;
+
+use clap::Args;
+use humantime::Duration;
+
+use crate::args::{JsonRpcUrlArgs, oracle::accumulator_status::AccumulatorArgs};
+
+#[derive(Args, Debug)]
+pub struct VerifyAccumulatorArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    #[command(flatten)]
+    pub accumulator: AccumulatorArgs,
+
+    /// For how long to keep checking the price accounts.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(30).into())]
+    pub duration: Duration,
+
+    /// How often to check the price accounts.
+    ///
+    /// Same format as `--duration`.
+    #[arg(long, default_value_t = StdDuration::from_secs(2).into())]
+    pub poll_interval: Duration,
+
+    /// Fail if a price account was not updated at least once while the command was running.
+    #[arg(long)]
+    pub require_updates: bool,
+}
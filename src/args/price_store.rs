@@ -1,9 +1,14 @@
 use clap::Subcommand;
 
+pub mod addresses;
 pub mod benchmark1;
+pub mod benchmark1_worker;
 pub mod initialize;
 pub mod initialize_publisher;
+pub mod stale_prices;
 pub mod submit_prices;
+pub mod verify_setup;
+pub mod whose_buffer;
 
 #[derive(Subcommand, Debug)]
 #[command(name = "price-store")]
@@ -21,5 +26,34 @@ pub enum Command {
     ///
     /// Will stop either when the specified duration has elapsed (`--duration`) or if an INT or a
     /// TERM signal is received.
-    Benchmark1(benchmark1::Benchmark1Args),
+    Benchmark1(Box<benchmark1::Benchmark1Args>),
+
+    /// Runs the publishers a `benchmark1` coordinator assigns to this instance.
+    ///
+    /// The coordinator is a `benchmark1` instance started with `--workers`.  It provides the load
+    /// parameters, the start time, and the duration, and aggregates stats of all the workers.
+    Benchmark1Worker(benchmark1_worker::Benchmark1WorkerArgs),
+
+    /// Checks that publishers, price buffers, payers, and price feeds are ready for a
+    /// `benchmark1` run with the same arguments.
+    ///
+    /// Reports every problem found, with a suggestion on how to fix it, and fails if there are
+    /// any.
+    VerifySetup(verify_setup::VerifySetupArgs),
+
+    /// Finds the publisher a price buffer account belongs to.
+    ///
+    /// Scans the publisher config accounts of the program for the ones that point to the buffer.
+    WhoseBuffer(whose_buffer::WhoseBufferArgs),
+
+    /// Prints the program derived addresses used by the Price Store program: the config account,
+    /// and the publisher config accounts.
+    Addresses(addresses::AddressesArgs),
+
+    /// Finds publishers that stopped submitting prices.
+    ///
+    /// Reads the price buffers of the publishers, and reports the ones that were last updated more
+    /// than `--max-lag-slots` slots ago, along with the feeds of their last update.  Fails if any
+    /// are found.
+    StalePrices(stale_prices::StalePricesArgs),
 }
@@ -0,0 +1,15 @@
+use clap::{ArgAction, Args};
+use solana_program::pubkey::Pubkey;
+
+#[derive(Args, Debug)]
+pub struct AddressesArgs {
+    /// Address of the Price Store program.
+    #[arg(long, env = "HEISENBERG_PRICE_STORE_PROGRAM_ID")]
+    pub program_id: Pubkey,
+
+    /// A publisher to also print the publisher config account for.
+    ///
+    /// Can be repeated.
+    #[arg(long, action = ArgAction::Append)]
+    pub publisher: Vec<Pubkey>,
+}
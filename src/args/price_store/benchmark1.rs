@@ -1,18 +1,30 @@
-use std::{path::PathBuf, time::Duration as StdDuration};
+use std::{net::SocketAddr, ops::RangeInclusive, path::PathBuf, time::Duration as StdDuration};
 
 use anyhow::{Result, bail};
-use clap::{ArgAction, Args, value_parser};
+use clap::{ArgAction, Args, ValueEnum, value_parser};
 use humantime::Duration;
 use reqwest::Url;
+use serde::{Deserialize, Serialize};
 use solana_program::pubkey::Pubkey;
 
-use crate::args::JsonRpcUrlArgs;
+use crate::args::{JsonRpcUrlArgs, abort_args::AbortArgs, metrics_args::MetricsArgs};
 
 #[derive(Args, Debug)]
 pub struct Benchmark1Args {
     #[command(flatten)]
     pub json_rpc_url: JsonRpcUrlArgs,
 
+    /// A YAML file with values for the other arguments of this command.
+    ///
+    /// The file has a `command` field, set to "price-store benchmark1", and an `args` mapping from
+    /// the long argument names, like "update-frequency", to their values.  Repeatable arguments
+    /// take a list of values.
+    ///
+    /// Values specified on the command line take precedence.  The scenario is included into the
+    /// benchmark output, so that the run can be reproduced.
+    #[arg(long)]
+    pub scenario: Option<PathBuf>,
+
     #[arg(long, value_name = "URL", default_value = "ws://localhost:8900")]
     /// A WebSocket address of a Pythnet node.
     pub websocket_url: Url,
@@ -25,7 +37,7 @@ pub struct Benchmark1Args {
     pub fanout_slots: u8,
 
     /// Address of the Price Store program.
-    #[arg(long)]
+    #[arg(long, env = "HEISENBERG_PRICE_STORE_PROGRAM_ID")]
     pub program_id: Pubkey,
 
     /// A keypair file for an account that would pay for transactions.
@@ -39,10 +51,22 @@ pub struct Benchmark1Args {
     #[arg(long, action = ArgAction::Append)]
     pub payer_keypair: Vec<PathBuf>,
 
+    /// A keypair file for an account that funds the payers, before the benchmark starts.
+    ///
+    /// Every payer is topped up to cover the fees of all the transactions the benchmark could send
+    /// on its behalf, for the configured duration and update frequency.  When running with a
+    /// canary, payers are funded on both clusters, from the same treasury keypair.
+    #[arg(long)]
+    pub treasury_keypair: Option<PathBuf>,
+
     /// An address of a publisher publishing a price update.
     ///
     /// The benchmark will send price updates on behalf of all of the specified publishers in
     /// parallel.
+    ///
+    /// `dir://keys/publisher-*.json` specifies all the matching keypair files at once, ordered by
+    /// the numbers in their names.  `dir://keys` specifies all the `.json` files in a directory.
+    /// This works for all the `--*-keypair` arguments that can be repeated.
     #[arg(long, action = ArgAction::Append)]
     pub publisher_keypair: Vec<PathBuf>,
 
@@ -78,6 +102,101 @@ pub struct Benchmark1Args {
     #[arg(long, default_value_t = StdDuration::from_millis(400).into())]
     pub update_frequency: Duration,
 
+    /// A load phase, in the "<duration>:<update frequency>" form.  For example, "1m:200ms".
+    ///
+    /// Can be repeated.  Phases run one after another, each using its own update frequency
+    /// instead of the `--update-frequency`.  After the last phase, the `--update-frequency` is
+    /// used again.  When `--duration` is not specified, the benchmark runs for the total duration
+    /// of all the phases.
+    #[arg(long, value_parser = load_phase_parser, action = ArgAction::Append)]
+    pub phase: Vec<LoadPhase>,
+
+    /// An update frequency for a range of price feeds, in the "<start>-<end>:<update frequency>"
+    /// or the "<index>:<update frequency>" form.  For example, "1-10:200ms".
+    ///
+    /// Can be repeated, to shape the load like the real traffic: a few fast feeds, and many slow
+    /// ones.  Feeds in the specified ranges are updated with their own frequency, and are not
+    /// affected by the `--phase`s.  All the other feeds use the `--update-frequency`, or the
+    /// current `--phase` frequency.  When ranges overlap, the first one is used.
+    ///
+    /// Feeds that are due at the same time are combined into the same transactions.
+    #[arg(long, value_parser = feed_frequency_parser, action = ArgAction::Append)]
+    pub feed_update_frequency: Vec<FeedFrequency>,
+
+    /// Pace the publishers to send this many transactions per second in total, instead of using
+    /// the `--update-frequency`.
+    ///
+    /// The initial update frequency is derived from the number of publishers, the price feeds
+    /// each one updates, and the `--price-updates-per-tx`.  While the benchmark is running, it is
+    /// adjusted every few seconds, based on the rate of successful transactions, so that
+    /// publishers that fall behind, and occasional failed transactions, are compensated for.  While
+    /// more than 10% of the transactions fail, publishers are only ever slowed down, so that an
+    /// overloaded cluster does not receive even more load.  Payers are funded for the initial
+    /// update frequency.
+    #[arg(long, conflicts_with_all = ["phase", "feed_update_frequency"])]
+    pub target_tps: Option<u32>,
+
+    /// A YAML file that assigns each publisher its own subset of the price feeds, instead of all
+    /// the publishers updating the same feeds.  Subsets can overlap, so the number of publishers
+    /// per feed can vary.  For example:
+    ///
+    ///   6YbK...QGx: [1-100]
+    ///   9dLp...7Tw: [50-150, 200]
+    ///
+    /// Publishers are identified by their pubkeys.  Publishers that are not listed update all the
+    /// feeds.  Feeds outside of the `--price-feed-index-start` and `--price-feed-index-end` range
+    /// are ignored.
+    #[arg(long)]
+    pub publisher_feeds: Option<PathBuf>,
+
+    /// Simulates a publisher that lags behind the chain, in the "<publisher pubkey>:<lag>" form.
+    /// For example, "6YbK...QGx:800ms".
+    ///
+    /// Can be repeated, to set a lag for more than one publisher.  A lagging publisher builds its
+    /// updates on schedule, but sends each one only after the lag has passed.  Its updates are
+    /// signed with a blockhash that is older by the lag, and land that much later than they would
+    /// otherwise, while the update frequency stays the same.  This reproduces a publisher with a
+    /// slow view of the chain, and exercises the Price Store and the Oracle staleness handling.
+    ///
+    /// Price Store updates do not carry a publisher supplied slot or timestamp: updates are
+    /// attributed to the slot they are processed in.  So a publisher that leads the chain can not
+    /// be reproduced.
+    #[arg(long, value_parser = publisher_lag_parser, action = ArgAction::Append)]
+    pub publisher_lag: Vec<PublisherLag>,
+
+    /// Percentage of the price feeds that are updated with the Oracle `UpdPrice` instruction,
+    /// rather than with the Price Store `SubmitPrices`.
+    ///
+    /// Both write paths are active at the same time, as they are while publishers are migrating
+    /// to the Price Store.  Feeds are split evenly across the price feed range, and every
+    /// publisher uses the same path for the same feed.  Requires `--oracle-program-id`, and the
+    /// publishers need to be added to the Oracle price accounts with `oracle add-publisher`.
+    /// Feeds that do not have an Oracle price account are always updated via the Price Store.
+    ///
+    /// Range: [0, 100]
+    #[arg(long, default_value_t = 0, value_parser = value_parser!(u8).range(0..=100))]
+    pub oracle_update_percent: u8,
+
+    /// Number of `UpdPrice` instructions to put into the same transaction, with
+    /// `--oracle-update-percent`.
+    ///
+    /// Range: [1, 20]
+    #[arg(long, default_value_t = 5, value_parser = value_parser!(u8).range(1..=20))]
+    pub oracle_updates_per_tx: u8,
+
+    /// How transactions are sent.
+    #[arg(long, value_enum, default_value_t = SendMode::Rpc)]
+    pub send_mode: SendMode,
+
+    /// Split the publishers into two groups, one sending via RPC, and the other directly to the
+    /// leaders, and report results of the groups side by side.  Overrides `--send-mode`.
+    ///
+    /// Publishers alternate between the groups, so the groups differ by at most one publisher, and
+    /// generate the same load.  Landing rate and landing latency are measured for both groups, as
+    /// with `--report-costs`, showing how much the direct send path buys on the cluster.
+    #[arg(long)]
+    pub compare_send_modes: bool,
+
     /// Prices will fluctuate around this point.
     ///
     /// Each publisher will have their own value of the price, for each of the price feeds, but they
@@ -109,8 +228,10 @@ pub struct Benchmark1Args {
     /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
     ///
     /// https://docs.rs/humantime/latest/humantime/
+    ///
+    /// Required, unless `--phase` is specified.
     #[arg(long)]
-    pub duration: Duration,
+    pub duration: Option<Duration>,
 
     /// An interval for reporting transaction stats.
     ///
@@ -119,6 +240,283 @@ pub struct Benchmark1Args {
     /// https://docs.rs/humantime/latest/humantime/
     #[arg(long, default_value_t = StdDuration::from_secs(60).into())]
     pub stats_update_interval: Duration,
+
+    /// After the benchmark, fetch metadata of a sample of the sent transactions, and report the
+    /// compute units they consumed and the fees they paid.
+    ///
+    /// Shows the on-chain cost of the `--price-updates-per-tx` choice.
+    #[arg(long)]
+    pub report_costs: bool,
+
+    /// Maximum number of transactions, per cluster, to fetch metadata for, with `--report-costs`.
+    ///
+    /// Transactions are sampled uniformly over the whole run.  With `--compare-send-modes`, every
+    /// group gets a sample of this size.
+    #[arg(long, default_value_t = 200, requires = "report_costs")]
+    pub cost_sample_size: usize,
+
+    /// After the benchmark, fetch the leader schedule and the produced blocks for the slots the
+    /// benchmark was sending in, and report, for every leader, how many of its slots were skipped,
+    /// together with the send failures and the landing rate of the transactions sent in its slots.
+    ///
+    /// Shows whether failure or expiry spikes come from specific leaders that skip their slots.
+    /// Landing rate is measured on a sample of transactions, as with `--report-costs`.
+    #[arg(long)]
+    pub leader_stats: bool,
+
+    /// Snapshot the Oracle price accounts and the price buffers the benchmark updates, before and
+    /// after the run, and report how they changed.
+    ///
+    /// Shows what the cluster has actually processed: how many aggregates were updated, and how
+    /// far the `pub_slot` of each publisher has advanced.  Requires `--oracle-program-id`.
+    #[arg(long)]
+    pub state_diff: bool,
+
+    /// Poll the Oracle price accounts the benchmark updates during the run, and report what
+    /// fraction of slots produced an aggregate for them.
+    ///
+    /// This is the Oracle side success metric: the send counts show what was delivered, while this
+    /// shows how often the prices actually got aggregated.  Requires `--oracle-program-id`.
+    #[arg(long)]
+    pub track_aggregation: bool,
+
+    /// How often to poll the price accounts, with `--track-aggregation`.
+    ///
+    /// Price accounts only remember the last two aggregation slots, so polls that are further
+    /// apart than two slots miss some of the aggregations.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(
+        long,
+        default_value_t = StdDuration::from_millis(400).into(),
+        requires = "track_aggregation",
+    )]
+    pub aggregation_poll_interval: Duration,
+
+    /// Address of the Oracle program that owns the price accounts for the `--state-diff`, the
+    /// `--track-aggregation` and the `--oracle-update-percent`.
+    #[arg(long, env = "HEISENBERG_ORACLE_PROGRAM_ID")]
+    pub oracle_program_id: Option<Pubkey>,
+
+    /// Save a summary of the run into this directory, for a later `benchmark compare`.
+    ///
+    /// Records the throughput and the success rate of every cluster, and the landing rate and the
+    /// median landing latency, with `--report-costs`.
+    #[arg(long, env = "HEISENBERG_RESULTS_DIR")]
+    pub results_dir: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub fault_injection: FaultInjectionArgs,
+
+    #[command(flatten)]
+    pub canary: CanaryArgs,
+
+    #[command(flatten)]
+    pub distributed: DistributedArgs,
+
+    #[command(flatten)]
+    pub abort: AbortArgs,
+
+    /// Stats are pushed every `--stats-update-interval`, as the `benchmark1` measurement.
+    #[command(flatten)]
+    pub metrics: MetricsArgs,
+}
+
+/// A second cluster to run the same load against, at the same time.  Useful for comparing a
+/// cluster running a new software version against a baseline cluster.
+///
+/// The same payer, publisher, and price buffer accounts are used on both clusters.
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Canary cluster")]
+pub struct CanaryArgs {
+    /// An HTTP address of a canary cluster node.  When specified, the benchmark runs against both
+    /// the `--rpc-url` cluster, as a baseline, and this cluster, and reports results side by side.
+    #[arg(long, value_name = "URL")]
+    pub canary_rpc_url: Option<Url>,
+
+    /// A WebSocket address of the canary cluster node.
+    ///
+    /// Defaults to an address derived from the `--canary-rpc-url`.
+    #[arg(long, value_name = "URL", requires = "canary_rpc_url")]
+    pub canary_websocket_url: Option<Url>,
+
+    /// Address of the Price Store program on the canary cluster.
+    ///
+    /// Defaults to the `--program-id`.
+    #[arg(long, requires = "canary_rpc_url")]
+    pub canary_program_id: Option<Pubkey>,
+}
+
+/// Spreads the publishers over multiple `benchmark1-worker` instances, running on different
+/// machines.  A single host can only generate as much load as its CPU and network allow.
+///
+/// This instance becomes a coordinator: it assigns publishers to workers, synchronizes the start,
+/// and aggregates the stats the workers report.  Keypair files are read by the workers, so they
+/// need to be present at the same paths on all the worker machines.
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Distributed mode")]
+pub struct DistributedArgs {
+    /// Wait for this many workers to connect, and spread the publishers between them.
+    #[arg(long)]
+    pub workers: Option<usize>,
+
+    /// An address to listen on for the worker connections.
+    #[arg(long, default_value = "0.0.0.0:7400", requires = "workers")]
+    pub coordinator_listen: SocketAddr,
+
+    /// Time between the assignments being sent to the workers, and the synchronized benchmark
+    /// start.  Workers need this time to read keypairs and to connect to the cluster.
+    ///
+    /// Worker clocks are expected to be synchronized, for example, via NTP.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(5).into(), requires = "workers")]
+    pub start_delay: Duration,
+}
+
+/// Faults injected into the publisher send path, to see how the cluster and the program handle
+/// them.  Sends affected by a fault are counted separately from the regular ones in the stats.
+#[derive(Args, Debug)]
+#[command(next_help_heading = "Fault injection")]
+pub struct FaultInjectionArgs {
+    /// Percentage of transactions that are silently not sent.
+    #[arg(long, default_value_t = 0.0, value_parser = percent_parser)]
+    pub fault_drop_percent: f64,
+
+    /// Percentage of transactions that are sent with an artificial delay.  See `--fault-delay`.
+    #[arg(long, default_value_t = 0.0, value_parser = percent_parser)]
+    pub fault_delay_percent: f64,
+
+    /// Maximum artificial delay.  Delayed transactions wait for a random time up to this value.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_millis(400).into())]
+    pub fault_delay: Duration,
+
+    /// Percentage of transactions that are sent twice.
+    #[arg(long, default_value_t = 0.0, value_parser = percent_parser)]
+    pub fault_duplicate_percent: f64,
+
+    /// Percentage of transactions that are sent with a corrupted signature.
+    ///
+    /// These transactions are expected to be rejected by the cluster.
+    #[arg(long, default_value_t = 0.0, value_parser = percent_parser)]
+    pub fault_malformed_percent: f64,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SendMode {
+    /// `sendTransaction` requests to the `--rpc-url` node.
+    Rpc,
+    /// UDP packets to the TPU ports of the upcoming leaders.  See `--fanout-slots`.
+    ///
+    /// Injected delays and duplicates only apply to the `rpc` mode.
+    Udp,
+}
+
+/// A period of the benchmark with its own update frequency.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LoadPhase {
+    pub duration: StdDuration,
+    pub update_frequency: StdDuration,
+}
+
+/// An update frequency of a range of price feeds.  See `--feed-update-frequency`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedFrequency {
+    pub feeds: RangeInclusive<u32>,
+    pub update_frequency: StdDuration,
+}
+
+fn feed_frequency_parser(input: &str) -> Result<FeedFrequency, String> {
+    let Some((feeds, update_frequency)) = input.split_once(':') else {
+        return Err(format!(
+            "`--feed-update-frequency` value should be in the \"<start>-<end>:<update frequency>\" \
+             form, got: {input}"
+        ));
+    };
+    let feeds = feed_range_parser(feeds).map_err(|err| format!("{input}: {err}"))?;
+    let update_frequency = humantime::parse_duration(update_frequency)
+        .map_err(|err| format!("{input}: update frequency part: {err}"))?;
+    if update_frequency.is_zero() {
+        return Err(format!("{input}: update frequency should be above zero"));
+    }
+    Ok(FeedFrequency {
+        feeds,
+        update_frequency,
+    })
+}
+
+/// Parses a price feed index range, in the "<start>-<end>" or the "<index>" form.
+pub fn feed_range_parser(input: &str) -> Result<RangeInclusive<u32>, String> {
+    let parse_index = |index: &str| {
+        index
+            .trim()
+            .parse::<u32>()
+            .map_err(|err| format!("feed index \"{}\": {err}", index.trim()))
+    };
+    let feeds = match input.split_once('-') {
+        Some((start, end)) => parse_index(start)?..=parse_index(end)?,
+        None => {
+            let index = parse_index(input)?;
+            index..=index
+        }
+    };
+    if feeds.is_empty() {
+        return Err(format!("feed range starts after it ends: {input}"));
+    }
+    Ok(feeds)
+}
+
+/// A publisher that sends its updates late.  See `--publisher-lag`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PublisherLag {
+    pub publisher: Pubkey,
+    pub lag: StdDuration,
+}
+
+fn publisher_lag_parser(input: &str) -> Result<PublisherLag, String> {
+    let Some((publisher, lag)) = input.split_once(':') else {
+        return Err(format!(
+            "`--publisher-lag` value should be in the \"<publisher pubkey>:<lag>\" form, got: \
+             {input}"
+        ));
+    };
+    let publisher = publisher
+        .parse::<Pubkey>()
+        .map_err(|err| format!("{input}: publisher part: {err}"))?;
+    let lag = humantime::parse_duration(lag).map_err(|err| format!("{input}: lag part: {err}"))?;
+    Ok(PublisherLag { publisher, lag })
+}
+
+fn load_phase_parser(input: &str) -> Result<LoadPhase, String> {
+    let Some((duration, update_frequency)) = input.split_once(':') else {
+        return Err(format!(
+            "`--phase` value should be in the \"<duration>:<update frequency>\" form, got: {input}"
+        ));
+    };
+    let duration = humantime::parse_duration(duration)
+        .map_err(|err| format!("{input}: duration part: {err}"))?;
+    let update_frequency = humantime::parse_duration(update_frequency)
+        .map_err(|err| format!("{input}: update frequency part: {err}"))?;
+    Ok(LoadPhase {
+        duration,
+        update_frequency,
+    })
+}
+
+fn percent_parser(value: &str) -> Result<f64, String> {
+    let value = value.parse::<f64>().map_err(|err| err.to_string())?;
+    if !(0.0..=100.0).contains(&value) {
+        return Err(format!("Expected a value between 0 and 100, got: {value}"));
+    }
+    Ok(value)
 }
 
 /// Additional validation of the [`SubmitPricesArgs`] instances.
@@ -129,13 +527,71 @@ impl Benchmark1Args {
             price_buffer_pubkey,
             price_feed_index_start,
             price_feed_index_end,
+            phase,
+            feed_update_frequency,
+            target_tps,
+            duration,
+            canary,
+            distributed,
+            abort,
+            report_costs,
+            results_dir,
+            state_diff,
+            track_aggregation,
+            aggregation_poll_interval,
+            oracle_update_percent,
+            oracle_program_id,
+            compare_send_modes,
+            leader_stats,
             ..
         } = self;
 
+        if *state_diff && oracle_program_id.is_none() {
+            bail!("--state-diff requires --oracle-program-id");
+        }
+
+        if *track_aggregation && oracle_program_id.is_none() {
+            bail!("--track-aggregation requires --oracle-program-id");
+        }
+
+        if *oracle_update_percent != 0 && oracle_program_id.is_none() {
+            bail!("--oracle-update-percent requires --oracle-program-id");
+        }
+
+        if StdDuration::from(*aggregation_poll_interval).is_zero() {
+            bail!("--aggregation-poll-interval should be above zero");
+        }
+
+        if *target_tps == Some(0) {
+            bail!("--target-tps should be above zero");
+        }
+
+        // Not expressed via `required_unless_present`, as `clap` does not count scenario
+        // defaults as present.
+        if duration.is_none() && phase.is_empty() {
+            bail!("You need to specify either --duration or at least one --phase");
+        }
+
         if price_feed_index_start > price_feed_index_end {
             bail!("--price-feed-index-start must be at or below --price-feed-index-end");
         }
 
+        let price_feed_indices = *price_feed_index_start..=*price_feed_index_end;
+        for FeedFrequency { feeds, .. } in feed_update_frequency {
+            if !price_feed_indices.contains(feeds.start())
+                || !price_feed_indices.contains(feeds.end())
+            {
+                bail!(
+                    "--feed-update-frequency range {}-{} is outside of the \
+                     --price-feed-index-start and --price-feed-index-end range: {}-{}",
+                    feeds.start(),
+                    feeds.end(),
+                    price_feed_index_start,
+                    price_feed_index_end,
+                );
+            }
+        }
+
         if publisher_keypair.is_empty() {
             bail!("You need to specify at least one publisher with --publisher-keypair");
         }
@@ -151,6 +607,62 @@ impl Benchmark1Args {
             );
         }
 
+        if *compare_send_modes {
+            if publisher_keypair.len() < 2 {
+                bail!("--compare-send-modes needs at least two publishers, one for each group");
+            }
+            if canary.canary_rpc_url.is_some() {
+                bail!("--compare-send-modes is not supported together with --canary-rpc-url");
+            }
+            if distributed.workers.is_some() {
+                bail!("--compare-send-modes is not supported together with --workers");
+            }
+            // Pacing would give each group its own update frequency.
+            if target_tps.is_some() {
+                bail!("--compare-send-modes is not supported together with --target-tps");
+            }
+            // Both groups run against the same cluster, so cluster wide reports would be
+            // duplicated.
+            if *state_diff {
+                bail!("--compare-send-modes is not supported together with --state-diff");
+            }
+            if *track_aggregation {
+                bail!("--compare-send-modes is not supported together with --track-aggregation");
+            }
+        }
+
+        if let Some(workers) = distributed.workers {
+            if workers == 0 {
+                bail!("--workers must be at least 1");
+            }
+            if workers > publisher_keypair.len() {
+                bail!(
+                    "Every worker needs at least one publisher.\n\
+                     Got --workers: {workers}\n\
+                     Got --publisher-keypair: {}",
+                    publisher_keypair.len(),
+                );
+            }
+            if canary.canary_rpc_url.is_some() {
+                bail!("--canary-rpc-url is not supported together with --workers");
+            }
+            if *report_costs {
+                bail!("--report-costs is not supported together with --workers");
+            }
+            if *leader_stats {
+                bail!("--leader-stats is not supported together with --workers");
+            }
+            if results_dir.is_some() {
+                bail!("--results-dir is not supported together with --workers");
+            }
+            if abort.abort_on_failure_rate.is_some() {
+                bail!("--abort-on-failure-rate is not supported together with --workers");
+            }
+            if target_tps.is_some() {
+                bail!("--target-tps is not supported together with --workers");
+            }
+        }
+
         Ok(())
     }
 }
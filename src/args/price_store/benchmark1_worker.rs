@@ -0,0 +1,22 @@
+use clap::Args;
+use reqwest::Url;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct Benchmark1WorkerArgs {
+    /// The RPC node this worker uses.  Workers on different machines can use different nodes of
+    /// the same cluster.
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// A WebSocket address of a Pythnet node.
+    ///
+    /// Defaults to an address derived from the `--rpc-url`.
+    #[arg(long, value_name = "URL")]
+    pub websocket_url: Option<Url>,
+
+    /// Address of a `benchmark1` instance running with `--workers`, in the "host:port" form.
+    #[arg(long)]
+    pub coordinator: String,
+}
@@ -12,14 +12,14 @@ pub struct InitializePublisherArgs {
     pub json_rpc_url: JsonRpcUrlArgs,
 
     /// Address of the Price Store program.
-    #[arg(long)]
+    #[arg(long, env = "HEISENBERG_PRICE_STORE_PROGRAM_ID")]
     pub program_id: Pubkey,
 
     /// A keypair file for the account that would pay for the publisher config account.
     ///
     /// Price Store program uses a config account, which is a PDA.  It needs to be constructed as
     /// the program initialization step.
-    #[arg(long)]
+    #[arg(long, env = "HEISENBERG_PAYER_KEYPAIR")]
     pub payer_keypair: PathBuf,
 
     /// An account that can add new publishers.
@@ -0,0 +1,46 @@
+use std::{ops::RangeInclusive, path::PathBuf};
+
+use clap::{ArgAction, Args};
+use solana_program::pubkey::Pubkey;
+
+use crate::args::{JsonRpcUrlArgs, price_store::benchmark1::feed_range_parser};
+
+#[derive(Args, Debug)]
+pub struct StalePricesArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// Address of the Price Store program.
+    #[arg(long, env = "HEISENBERG_PRICE_STORE_PROGRAM_ID")]
+    pub program_id: Pubkey,
+
+    /// A publisher whose price buffer is checked.
+    ///
+    /// Can be repeated.  Defaults to all the publishers configured in the program.
+    #[arg(long, action = ArgAction::Append)]
+    pub publisher: Vec<Pubkey>,
+
+    /// Prices last updated more than this many slots before the current slot are stale.
+    #[arg(long, default_value_t = 25)]
+    pub max_lag_slots: u64,
+
+    /// Price feeds every publisher is expected to update, in the "<start>-<end>" or the "<index>"
+    /// form.
+    ///
+    /// Expected feeds that are missing from a publisher buffer are reported as stale.  A buffer
+    /// only holds the feeds updated in the last slot the publisher submitted in, so this check
+    /// assumes publishers update all their feeds in every slot they submit in.
+    ///
+    /// By default, only the buffer update slot is checked.
+    #[arg(long, value_parser = feed_range_parser)]
+    pub feeds: Option<RangeInclusive<u32>>,
+
+    /// A YAML file with the price feeds each publisher is expected to update, in the same format as
+    /// the `benchmark1 --publisher-feeds`.  Overrides the `--feeds` for the listed publishers.
+    #[arg(long)]
+    pub publisher_feeds: Option<PathBuf>,
+
+    /// Only report publishers with stale prices.
+    #[arg(long)]
+    pub stale_only: bool,
+}
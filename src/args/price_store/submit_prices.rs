@@ -1,12 +1,12 @@
 use std::{convert::TryFrom, path::PathBuf, str::FromStr as _};
 
 use clap::{ArgAction, Args};
+use pythnet_heisenberg::price_store::instructions::submit_prices::{
+    BufferedPrice, FEED_INDEX_MAX, TradingStatus,
+};
 use solana_program::pubkey::Pubkey;
 
-use crate::{
-    args::JsonRpcUrlArgs,
-    price_store::instructions::submit_prices::{BufferedPrice, FEED_INDEX_MAX, TradingStatus},
-};
+use crate::args::JsonRpcUrlArgs;
 
 #[derive(Args, Debug)]
 pub struct SubmitPricesArgs {
@@ -14,11 +14,11 @@ pub struct SubmitPricesArgs {
     pub json_rpc_url: JsonRpcUrlArgs,
 
     /// Address of the Price Store program.
-    #[arg(long)]
+    #[arg(long, env = "HEISENBERG_PRICE_STORE_PROGRAM_ID")]
     pub program_id: Pubkey,
 
     /// A keypair file for the account that would pay for the transaction.
-    #[arg(long)]
+    #[arg(long, env = "HEISENBERG_PAYER_KEYPAIR")]
     pub payer_keypair: PathBuf,
 
     /// An address of the publisher publishing this price update.
@@ -54,6 +54,11 @@ pub struct SubmitPricesArgs {
     /// You can add up to about 50 prices in one transaction.
     #[arg(long, value_parser = price_update_parser, action = ArgAction::Append)]
     pub price: Vec<BufferedPrice>,
+
+    /// After the transaction is executed, fetch its metadata, and report the compute units it
+    /// consumed and the fee it paid.
+    #[arg(long)]
+    pub report_costs: bool,
 }
 
 fn price_update_parser(input: &str) -> Result<BufferedPrice, String> {
@@ -0,0 +1,105 @@
+use std::{path::PathBuf, time::Duration as StdDuration};
+
+use anyhow::{Result, bail};
+use clap::{ArgAction, Args, value_parser};
+use humantime::Duration;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::JsonRpcUrlArgs;
+
+/// Most arguments match the `benchmark1` arguments of the same name, so that the same values can
+/// be used for both commands.
+#[derive(Args, Debug)]
+pub struct VerifySetupArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// Address of the Price Store program.
+    #[arg(long, env = "HEISENBERG_PRICE_STORE_PROGRAM_ID")]
+    pub program_id: Pubkey,
+
+    /// Address of the Oracle program.
+    ///
+    /// When specified, price feed indices are checked to be assigned to Oracle price accounts.
+    #[arg(long, env = "HEISENBERG_ORACLE_PROGRAM_ID")]
+    pub oracle_program_id: Option<Pubkey>,
+
+    /// A keypair file for an account that would pay for the benchmark transactions.
+    ///
+    /// Each publisher uses the payer at the same position.
+    #[arg(long, action = ArgAction::Append)]
+    pub payer_keypair: Vec<PathBuf>,
+
+    /// A keypair file of a publisher that will send price updates.
+    #[arg(long, action = ArgAction::Append)]
+    pub publisher_keypair: Vec<PathBuf>,
+
+    /// An account that holds price updates from the publisher at the same position.
+    #[arg(long, action = ArgAction::Append)]
+    pub price_buffer_pubkey: Vec<Pubkey>,
+
+    /// First price feed index the benchmark will update.
+    #[arg(long, default_value_t = 1)]
+    pub price_feed_index_start: u32,
+
+    /// Last price feed index the benchmark will update.
+    #[arg(long)]
+    pub price_feed_index_end: u32,
+
+    /// Number of price feed updates the benchmark will put into the same transaction.
+    ///
+    /// Range: [1, 50]
+    #[arg(long, default_value_t = 10, value_parser = value_parser!(u8).range(1..50))]
+    pub price_updates_per_tx: u8,
+
+    /// Delay between consecutive updates from the same publisher.
+    ///
+    /// Determines how many price updates each buffer needs to hold within a single slot, and how
+    /// many transactions each payer pays for.
+    #[arg(long, default_value_t = StdDuration::from_millis(400).into())]
+    pub update_frequency: Duration,
+
+    /// How long the benchmark will run.  Payers need to be able to pay for all the transactions
+    /// sent during this time.
+    #[arg(long)]
+    pub duration: Duration,
+}
+
+/// Additional validation of the [`VerifySetupArgs`] instances.
+impl VerifySetupArgs {
+    pub fn check_are_valid(&self) -> Result<()> {
+        let Self {
+            publisher_keypair,
+            price_buffer_pubkey,
+            price_feed_index_start,
+            price_feed_index_end,
+            update_frequency,
+            ..
+        } = self;
+
+        if price_feed_index_start > price_feed_index_end {
+            bail!("--price-feed-index-start must be at or below --price-feed-index-end");
+        }
+
+        if publisher_keypair.is_empty() {
+            bail!("You need to specify at least one publisher with --publisher-keypair");
+        }
+
+        if publisher_keypair.len() != price_buffer_pubkey.len() {
+            bail!(
+                "You have to specify the same number of --publisher-keypair and \
+                 --price-buffer-pubkey arguments.\n\
+                 Got --publisher-keypair: {}\n\
+                 Got --price-buffer-pubkey: {}",
+                publisher_keypair.len(),
+                price_buffer_pubkey.len(),
+            );
+        }
+
+        if update_frequency.is_zero() {
+            bail!("--update-frequency must be above zero");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+use clap::Args;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct WhoseBufferArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// Address of the Price Store program.
+    #[arg(long, env = "HEISENBERG_PRICE_STORE_PROGRAM_ID")]
+    pub program_id: Pubkey,
+
+    /// Price buffer account to look up.
+    pub buffer: Pubkey,
+}
@@ -2,6 +2,7 @@ use clap::Subcommand;
 
 pub mod feature;
 pub mod loader_v3;
+pub mod validators;
 
 #[derive(Subcommand, Debug)]
 #[command(name = "primordial-accounts")]
@@ -13,4 +14,12 @@ pub enum Command {
     /// Output accounts that match deployment of a program with loader v3, aka
     /// `BPFLoaderUpgradeab1e11111111111111111111111`.
     LoaderV3(loader_v3::LoaderV3Args),
+
+    /// Output accounts for a set of validators: a funded identity, a vote account, and a stake
+    /// account delegated to the vote account, for every validator identity.
+    ///
+    /// Vote and stake account addresses are derived from the identity, with the "vote" and the
+    /// "stake" seeds, and are listed on stderr.  Start each validator with its vote account
+    /// address as the `--vote-account`.
+    Validators(validators::ValidatorsArgs),
 }
@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use clap::{ArgAction, Args};
+use solana_program::pubkey::Pubkey;
+
+use crate::args::{u64_nice_parser, u64_nice_printer};
+
+#[derive(Args, Debug)]
+pub struct ValidatorsArgs {
+    /// A keypair file for a validator identity.  Only the pubkey is used.
+    ///
+    /// Can be repeated, and can be combined with the identities specified on the command line.
+    #[arg(long, action = ArgAction::Append)]
+    pub identity_keypair: Vec<PathBuf>,
+
+    /// Balance of every identity account, in lamports.  Validators pay for their votes from it.
+    #[arg(
+        long,
+        value_parser = u64_nice_parser,
+        default_value = u64_nice_printer(500_000_000_000),
+    )]
+    pub identity_lamports: u64,
+
+    /// Balance of every stake account, in lamports, including the rent exempt reserve.  The rest
+    /// is delegated to the validator vote account, and is active from the first epoch.
+    #[arg(
+        long,
+        value_parser = u64_nice_parser,
+        default_value = u64_nice_printer(1_000_000_000_000),
+    )]
+    pub stake_lamports: u64,
+
+    /// Commission of every vote account, in percent.
+    #[arg(long, default_value_t = 100)]
+    pub commission: u8,
+
+    /// Withdraw authority for the vote and the stake accounts.
+    ///
+    /// Defaults to the identity of each validator.
+    #[arg(long)]
+    pub authorized_withdrawer: Option<Pubkey>,
+
+    /// Validator identities.
+    pub identities: Vec<Pubkey>,
+}
+
+/// Additional validation of the [`ValidatorsArgs`] instances.
+impl ValidatorsArgs {
+    pub fn check_are_valid(&self) -> Result<()> {
+        let Self {
+            identity_keypair,
+            commission,
+            identities,
+            ..
+        } = self;
+
+        if identity_keypair.is_empty() && identities.is_empty() {
+            bail!("Specify at least one validator identity");
+        }
+
+        if *commission > 100 {
+            bail!("--commission should be at most 100");
+        }
+
+        Ok(())
+    }
+}
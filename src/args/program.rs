@@ -0,0 +1,22 @@
+use clap::Subcommand;
+
+pub mod close_buffers;
+pub mod deploy;
+
+#[derive(Subcommand, Debug)]
+#[command(name = "program")]
+pub enum Command {
+    /// Deploys or upgrades a program owned by the upgradeable BPF loader.
+    ///
+    /// Program data is written into a buffer account by many transactions sent in parallel,
+    /// directly to the upcoming leaders.  This is a lot faster than `solana program deploy` when
+    /// the RPC node is far away.
+    Deploy(deploy::DeployArgs),
+
+    /// Closes buffer accounts of the upgradeable BPF loader, that are owned by the specified
+    /// authorities.
+    ///
+    /// Failed or abandoned deployments leave buffers behind, holding lamports.  This command
+    /// reclaims them.
+    CloseBuffers(close_buffers::CloseBuffersArgs),
+}
@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+
+use clap::{ArgAction, Args};
+use solana_program::pubkey::Pubkey;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct CloseBuffersArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// A keypair file for a buffer authority.  All buffers with this authority are closed.
+    ///
+    /// Repeat to close buffers of multiple authorities at once.
+    #[arg(long, required = true, action = ArgAction::Append)]
+    pub authority_keypair: Vec<PathBuf>,
+
+    /// An account that receives the lamports held by the closed buffers.
+    ///
+    /// Defaults to the authority of each buffer.
+    #[arg(long)]
+    pub recipient: Option<Pubkey>,
+
+    /// A keypair file for the account that pays for the transactions.
+    ///
+    /// Defaults to the first `--authority-keypair`.
+    #[arg(long)]
+    pub payer_keypair: Option<PathBuf>,
+
+    /// Only list the buffers, without closing them.
+    #[arg(long)]
+    pub list_only: bool,
+
+    /// Do not ask for a confirmation before closing the buffers.
+    #[arg(long)]
+    pub yes: bool,
+}
@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+
+use clap::{ArgGroup, Args};
+use reqwest::Url;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("program").required(true).args(["program_keypair", "program_id"])))]
+pub struct DeployArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// A WebSocket address of a Pythnet node.  Used to track the leader schedule.
+    ///
+    /// Defaults to an address derived from `--rpc-url`, the same way the `solana` CLI does it.
+    #[arg(long, value_name = "URL")]
+    pub websocket_url: Option<Url>,
+
+    /// Send each buffer write to validators that cover this many slots in the future.
+    #[arg(long, default_value_t = 4)]
+    pub fanout_slots: u8,
+
+    /// Send buffer writes via the RPC node, rather than directly to the leaders.
+    #[arg(long)]
+    pub via_rpc: bool,
+
+    /// The program ELF file.
+    #[arg(long)]
+    pub program_so: PathBuf,
+
+    /// A keypair file for the program account.
+    ///
+    /// Required for the initial deployment.  An upgrade only needs the program address, so
+    /// `--program-id` can be used instead.
+    #[arg(long)]
+    pub program_keypair: Option<PathBuf>,
+
+    /// Address of an already deployed program to upgrade.
+    #[arg(long)]
+    pub program_id: Option<Pubkey>,
+
+    /// A keypair file for the account that pays for the transactions, and funds the buffer and the
+    /// program accounts.
+    #[arg(long)]
+    pub payer_keypair: PathBuf,
+
+    /// A keypair file for the program upgrade authority.  Also used as the buffer authority.
+    ///
+    /// Defaults to the `--payer-keypair`.
+    #[arg(long)]
+    pub upgrade_authority_keypair: Option<PathBuf>,
+
+    /// A keypair file for the buffer account.
+    ///
+    /// If the buffer already exists, only the parts that differ from the ELF file are written,
+    /// allowing an interrupted deployment to be resumed.  When not specified, a new buffer is
+    /// created.  In case of a failure, the address of the buffer is printed, so that the lamports
+    /// can be recovered.
+    #[arg(long)]
+    pub buffer_keypair: Option<PathBuf>,
+
+    /// Maximum size of the program data, in bytes, for the initial deployment.
+    ///
+    /// Upgrades can not make the program larger than this.  Defaults to the size of the ELF file.
+    #[arg(long)]
+    pub max_len: Option<usize>,
+
+    /// Do not ask for a confirmation before deploying.
+    #[arg(long)]
+    pub yes: bool,
+}
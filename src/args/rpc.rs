@@ -0,0 +1,28 @@
+use clap::Subcommand;
+
+pub mod benchmark;
+pub mod compare;
+pub mod pubsub_benchmark;
+
+#[derive(Subcommand, Debug)]
+#[command(name = "rpc")]
+pub enum Command {
+    /// Sends a mix of read requests to an RPC node at a target rate, and reports latency and error
+    /// rates for each method.
+    ///
+    /// Shows if the RPC node can serve the price consumers load, while the publishers are writing.
+    Benchmark(benchmark::BenchmarkArgs),
+
+    /// Opens many `accountSubscribe` and `programSubscribe` subscriptions, and reports notification
+    /// latency and drop rate.
+    ///
+    /// Price consumers rely on these notifications, so they need to keep up with the publishers
+    /// load.
+    PubsubBenchmark(pubsub_benchmark::PubsubBenchmarkArgs),
+
+    /// Sends the same queries to multiple RPC nodes, and reports where their answers diverge.
+    ///
+    /// Compares the slot, the blockhash, and the state of the selected accounts.  Detects an RPC
+    /// node that fell behind or desynced, before it affects a benchmark run.
+    Compare(compare::CompareArgs),
+}
@@ -0,0 +1,115 @@
+use std::{path::PathBuf, time::Duration as StdDuration};
+
+use clap::{ArgAction, Args, ValueEnum};
+use humantime::Duration;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct BenchmarkArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// Address of the Oracle program.
+    ///
+    /// Price accounts of this program are the targets of the `getAccountInfo` and
+    /// `getMultipleAccounts` requests, unless `--account` is specified.  `getProgramAccounts`
+    /// requests fetch all the price accounts of this program.
+    #[arg(
+        long,
+        env = "HEISENBERG_ORACLE_PROGRAM_ID",
+        required_unless_present = "account"
+    )]
+    pub oracle_program_id: Option<Pubkey>,
+
+    /// Read this account with the `getAccountInfo` and `getMultipleAccounts` requests.
+    ///
+    /// Can be repeated.  Replaces the Oracle price accounts.
+    #[arg(long, action = ArgAction::Append)]
+    pub account: Vec<Pubkey>,
+
+    /// Relative weight of an RPC method in the request mix, in the "<method>:<weight>" form.
+    ///
+    /// Can be repeated.  Methods that are not listed are not used.  Defaults to
+    /// "account-info:80", "multiple-accounts:19", and "program-accounts:1".
+    ///
+    /// Supported methods are: "account-info", "multiple-accounts", and
+    /// "program-accounts".
+    #[arg(long, value_parser = method_weight_parser, action = ArgAction::Append)]
+    pub mix: Vec<(ReadMethod, u32)>,
+
+    /// Target number of requests per second, across all the methods.
+    #[arg(long, default_value_t = 100.0)]
+    pub rate: f64,
+
+    /// Number of accounts to request in every `getMultipleAccounts` call.
+    ///
+    /// Consecutive accounts, starting from a random one, are requested.
+    #[arg(long, default_value_t = 100)]
+    pub multiple_accounts_batch: usize,
+
+    /// Maximum number of requests waiting for a response.
+    ///
+    /// When the RPC node is slower than the target rate, requests above this limit are not sent,
+    /// and are reported as skipped.
+    #[arg(long, default_value_t = 1000)]
+    pub max_in_flight: usize,
+
+    /// The benchmark will run for this long.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(60).into())]
+    pub duration: Duration,
+
+    /// An interval for reporting request stats.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(10).into())]
+    pub stats_update_interval: Duration,
+
+    /// Save a summary of the run into this directory, for a later `benchmark compare`.
+    ///
+    /// Records the request rate, and the latency percentiles and the error rate of every method.
+    #[arg(long, env = "HEISENBERG_RESULTS_DIR")]
+    pub results_dir: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReadMethod {
+    /// `getAccountInfo` for a random account.
+    AccountInfo,
+    /// `getMultipleAccounts` for a batch of accounts.
+    MultipleAccounts,
+    /// `getProgramAccounts` for all the Oracle price accounts.
+    ProgramAccounts,
+}
+
+impl ReadMethod {
+    /// Name of the JSON RPC method.
+    pub fn rpc_name(self) -> &'static str {
+        match self {
+            ReadMethod::AccountInfo => "getAccountInfo",
+            ReadMethod::MultipleAccounts => "getMultipleAccounts",
+            ReadMethod::ProgramAccounts => "getProgramAccounts",
+        }
+    }
+}
+
+fn method_weight_parser(input: &str) -> Result<(ReadMethod, u32), String> {
+    let Some((method, weight)) = input.split_once(':') else {
+        return Err(format!(
+            "`--mix` value should be in the \"<method>:<weight>\" form, got: {input}"
+        ));
+    };
+    let method =
+        ReadMethod::from_str(method, true).map_err(|err| format!("{input}: method part: {err}"))?;
+    let weight = weight
+        .parse::<u32>()
+        .map_err(|err| format!("{input}: weight part: not a u32: {err}"))?;
+    Ok((method, weight))
+}
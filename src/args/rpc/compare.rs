@@ -0,0 +1,31 @@
+use clap::{ArgAction, Args};
+use reqwest::Url;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::clock::Slot;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct CompareArgs {
+    /// The `--rpc-url` endpoint is the reference the other endpoints are compared against.
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// An HTTP address of another RPC node to compare with the `--rpc-url` one.
+    ///
+    /// Can be repeated.  All the other RPC settings, like the commitment and the timeout, are the
+    /// same for all the endpoints.
+    #[arg(long, value_name = "URL", action = ArgAction::Append, required = true)]
+    pub other_rpc_url: Vec<Url>,
+
+    /// Compare the state of this account between the endpoints.
+    ///
+    /// Can be repeated.  Using the "finalized" commitment makes it more likely for all the
+    /// endpoints to return the state as of the same slot.
+    #[arg(long, action = ArgAction::Append)]
+    pub account: Vec<Pubkey>,
+
+    /// Fail if any endpoint is behind the most advanced one by more than this many slots.
+    #[arg(long, default_value_t = 10)]
+    pub max_slot_lag: Slot,
+}
@@ -0,0 +1,80 @@
+use std::time::Duration as StdDuration;
+
+use clap::{ArgAction, Args};
+use humantime::Duration;
+use reqwest::Url;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct PubsubBenchmarkArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// A WebSocket address of a Pythnet node.
+    ///
+    /// Defaults to an address derived from the `--rpc-url`, the same way the `solana` CLI does it:
+    /// "http" becomes "ws", "https" becomes "wss", and the port, if specified, is incremented by
+    /// one.
+    #[arg(long, value_name = "URL")]
+    pub websocket_url: Option<Url>,
+
+    /// Address of the Oracle program.
+    ///
+    /// `accountSubscribe` subscriptions are spread over the price accounts of this program, unless
+    /// `--account` is specified.  `programSubscribe` subscriptions receive updates of all the price
+    /// accounts of this program.
+    #[arg(
+        long,
+        env = "HEISENBERG_ORACLE_PROGRAM_ID",
+        required_unless_present = "account"
+    )]
+    pub oracle_program_id: Option<Pubkey>,
+
+    /// Subscribe to this account with `accountSubscribe`.
+    ///
+    /// Can be repeated.  Replaces the Oracle price accounts.
+    #[arg(long, action = ArgAction::Append)]
+    pub account: Vec<Pubkey>,
+
+    /// Number of `accountSubscribe` subscriptions to open.
+    ///
+    /// Subscriptions are assigned to accounts round robin, so the same account can have more than
+    /// one subscriber, the same way independent consumers would subscribe to it.
+    #[arg(long, default_value_t = 100)]
+    pub account_subscriptions: usize,
+
+    /// Number of `programSubscribe` subscriptions to the Oracle price accounts to open.
+    #[arg(long, default_value_t = 0)]
+    pub program_subscriptions: usize,
+
+    /// Number of WebSocket connections to spread the subscriptions over.
+    #[arg(long, default_value_t = 1)]
+    pub connections: usize,
+
+    /// How long to wait for all the subscribers of an account to receive an update, before the
+    /// missing notifications are counted as dropped.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(5).into())]
+    pub drop_timeout: Duration,
+
+    /// The benchmark will run for this long.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(60).into())]
+    pub duration: Duration,
+
+    /// An interval for reporting notification stats.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(10).into())]
+    pub stats_update_interval: Duration,
+}
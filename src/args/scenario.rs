@@ -0,0 +1,135 @@
+//! Scenario files, that specify benchmark arguments, instead of a long command line.
+//!
+//! A scenario file is a YAML file that names the command it is for, and provides values for the
+//! command arguments, using the long argument names:
+//!
+//!   command: price-store benchmark1
+//!   args:
+//!     program-id: 3m6sv6HGqEbuyLV84mD7rJn4MAC9LhUa1y1AUNVqcPfr
+//!     publisher-keypair:
+//!       - keys/publisher-1.json
+//!       - keys/publisher-2.json
+//!     price-feed-index-end: 500
+//!     phase:
+//!       - 1m:1s
+//!       - 5m:200ms
+//!
+//! Scenario values become argument defaults, the same way cluster alias values do.  Values
+//! specified on the command line take precedence.  Relative paths are resolved relative to the
+//! current directory, the same as for the command line arguments.  Keypair arguments accept
+//! `dir://` sources, same as on the command line.
+
+use std::{ffi::OsString, fs, path::Path};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use clap::Command;
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+
+use crate::args::{cluster_config::find_arg_value, keypair_dirs};
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// Subcommand path, without the binary name.  For example, "price-store benchmark1".
+    pub command: String,
+    #[serde(default)]
+    pub args: Mapping,
+}
+
+impl Scenario {
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scenario file: {}", path.to_string_lossy()))?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse scenario file: {}", path.to_string_lossy()))
+    }
+}
+
+/// If a `--scenario` file is specified in `args`, updates default values of all the arguments of
+/// the command the scenario is for.
+pub fn apply_scenario(command: Command, args: &[OsString]) -> Result<Command> {
+    let Some(path) = find_arg_value(args, "--scenario") else {
+        return Ok(command);
+    };
+    let path = Path::new(&path);
+    let scenario = Scenario::read(path)?;
+
+    let names = scenario.command.split_whitespace().collect::<Vec<_>>();
+    apply_to_subcommand(command, &names, &scenario.args)
+        .with_context(|| format!("Failed to apply scenario file: {}", path.to_string_lossy()))
+}
+
+fn apply_to_subcommand(command: Command, names: &[&str], args: &Mapping) -> Result<Command> {
+    let Some((name, rest)) = names.split_first() else {
+        return apply_defaults(command, args);
+    };
+
+    if command.find_subcommand(name).is_none() {
+        bail!("Unknown command: {name}");
+    }
+    let mut res = Ok(());
+    let command = command.mut_subcommand(name, |subcommand| {
+        match apply_to_subcommand(subcommand.clone(), rest, args) {
+            Ok(subcommand) => subcommand,
+            Err(err) => {
+                res = Err(err);
+                subcommand
+            }
+        }
+    });
+    res.map(|()| command)
+}
+
+fn apply_defaults(mut command: Command, args: &Mapping) -> Result<Command> {
+    if !command
+        .get_arguments()
+        .any(|arg| arg.get_id() == "scenario")
+    {
+        bail!(
+            "Command \"{}\" does not accept scenario files",
+            command.get_name()
+        );
+    }
+
+    for (name, value) in args {
+        let name = name
+            .as_str()
+            .ok_or_else(|| anyhow!("Argument names should be strings, got: {name:?}"))?;
+        let Some(id) = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(name))
+            .map(|arg| arg.get_id().clone())
+        else {
+            bail!("Unknown argument: {name}");
+        };
+        let values = arg_values(value)
+            .and_then(|values| keypair_dirs::expand_values(name, values))
+            .with_context(|| format!("Argument: {name}"))?;
+        if values.is_empty() {
+            continue;
+        }
+        command = command.mut_arg(id, |arg| arg.default_values(values).required(false));
+    }
+
+    Ok(command)
+}
+
+/// Values are converted to strings, as they are parsed by `clap`, together with all the other
+/// arguments.
+fn arg_values(value: &Value) -> Result<Vec<String>> {
+    match value {
+        Value::Null => Ok(vec![]),
+        Value::Sequence(values) => values.iter().map(scalar_value).collect(),
+        value => Ok(vec![scalar_value(value)?]),
+    }
+}
+
+fn scalar_value(value: &Value) -> Result<String> {
+    match value {
+        Value::Bool(value) => Ok(value.to_string()),
+        Value::Number(value) => Ok(value.to_string()),
+        Value::String(value) => Ok(value.clone()),
+        value => bail!("Expected a string, a number, or a boolean, got: {value:?}"),
+    }
+}
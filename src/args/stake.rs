@@ -0,0 +1,14 @@
+use clap::Subcommand;
+
+pub mod delegate;
+
+#[derive(Subcommand, Debug)]
+#[command(name = "stake")]
+pub enum Command {
+    /// Creates stake accounts, or splits them off an existing one, and delegates them to multiple
+    /// vote accounts in parallel, according to a weights file.
+    ///
+    /// Stake distribution drives the leader schedule, so this sets up the leader schedule the
+    /// benchmarks will run against.  Stake becomes active at the start of the next epoch.
+    Delegate(delegate::DelegateArgs),
+}
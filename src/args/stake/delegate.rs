@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use clap::Args;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::{JsonRpcUrlArgs, u64_nice_parser};
+
+#[derive(Args, Debug)]
+pub struct DelegateArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// A keypair file for the account that funds the new stake accounts and pays for the
+    /// transactions.
+    ///
+    /// It is also the base for the stake account addresses, and the stake and the withdraw
+    /// authority of the new stake accounts.
+    #[arg(long, env = "HEISENBERG_PAYER_KEYPAIR")]
+    pub funding_keypair: PathBuf,
+
+    /// A CSV file with the vote accounts to delegate to, and their weights.
+    ///
+    /// Each line holds a vote account address and a weight, separated by a comma:
+    ///
+    ///   "[vote pubkey],[weight]"
+    ///
+    /// Empty lines and lines starting with '#' are ignored.  Every vote account receives a share
+    /// of the `--total-stake` proportional to its weight.
+    #[arg(long)]
+    pub weights_file: PathBuf,
+
+    /// Total amount to delegate across all the vote accounts, in lamports.
+    ///
+    /// Includes the rent exempt reserve of every stake account.
+    #[arg(long, value_parser = u64_nice_parser)]
+    pub total_stake: u64,
+
+    /// An existing stake account to split the stake off, instead of funding new stake accounts
+    /// from the `--funding-keypair` balance.
+    ///
+    /// It should not be delegated, and its stake authority should be the `--funding-keypair`.
+    #[arg(long)]
+    pub split_from: Option<Pubkey>,
+
+    /// Stake account addresses are derived from the `--funding-keypair` pubkey and a seed made of
+    /// this prefix and the vote account position in the `--weights-file`.
+    ///
+    /// Stake accounts that already exist are left as is, so a failed run can be repeated.  Use a
+    /// different prefix to add more stake on top of a previous run.
+    #[arg(long, default_value = "heisenberg-stake-")]
+    pub seed_prefix: String,
+}
+
+/// Additional validation of the [`DelegateArgs`] instances.
+impl DelegateArgs {
+    pub fn check_are_valid(&self) -> Result<()> {
+        let Self {
+            total_stake,
+            seed_prefix,
+            ..
+        } = self;
+
+        if *total_stake == 0 {
+            bail!("--total-stake must be positive");
+        }
+
+        // Seeds are limited to 32 bytes, and the position takes a few more.
+        if seed_prefix.len() > 26 {
+            bail!("--seed-prefix should be at most 26 bytes long");
+        }
+
+        Ok(())
+    }
+}
@@ -1,10 +1,19 @@
 use clap::Subcommand;
 
+pub mod get;
 pub mod set_parameters;
+pub mod transfer_authority;
 
 #[derive(Subcommand, Debug)]
 #[command(name = "stake-cap-parameters")]
 pub enum Command {
     /// Initialize or update the cluster stake cap parameters account.
     SetParameters(set_parameters::SetParametersArgs),
+
+    /// Reads and prints the current cluster stake cap parameters.
+    Get(get::GetArgs),
+
+    /// Hands control over the parameters account to a different authority, keeping the parameter
+    /// values unchanged.
+    TransferAuthority(transfer_authority::TransferAuthorityArgs),
 }
@@ -0,0 +1,21 @@
+use clap::Args;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct GetArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// An address of the stake_caps_parameters program.
+    #[arg(long, env = "HEISENBERG_STAKE_CAPS_PARAMETERS_PROGRAM_ID")]
+    pub program_id: Pubkey,
+
+    /// An address of the parameters account from the stake_caps_parameters program.
+    ///
+    /// Defaults to the program derived address with a "parameters" seed, same as the
+    /// `set-parameters` command.
+    #[arg(long)]
+    pub parameters_account: Option<Pubkey>,
+}
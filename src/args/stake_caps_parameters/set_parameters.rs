@@ -11,11 +11,13 @@ pub struct SetParametersArgs {
     pub json_rpc_url: JsonRpcUrlArgs,
 
     /// A keypair file for the signer of the update transaction.
+    ///
+    /// Can also be a hardware wallet, specified as `usb://ledger?key=0`.
     #[arg(long)]
     pub signer_keypair: PathBuf,
 
     /// An address of the stake_caps_parameters program.
-    #[arg(long)]
+    #[arg(long, env = "HEISENBERG_STAKE_CAPS_PARAMETERS_PROGRAM_ID")]
     pub program_id: Pubkey,
 
     /// An address of the parameters account from the stake_caps_parameters program.
@@ -44,4 +46,12 @@ pub struct SetParametersArgs {
     /// Defaults to the `--signer-keypair`, if not specified.
     #[arg(long)]
     pub update_authority: Option<Pubkey>,
+
+    /// Send the update transaction without running a preflight simulation.
+    #[arg(long)]
+    pub skip_preflight: bool,
+
+    /// Do not ask for a confirmation before sending the transaction.
+    #[arg(long)]
+    pub yes: bool,
 }
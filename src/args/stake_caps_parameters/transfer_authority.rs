@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct TransferAuthorityArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// A keypair file for the current authority of the parameters account.  It also pays for the
+    /// transaction.
+    ///
+    /// Can also be a hardware wallet, specified as `usb://ledger?key=0`.
+    #[arg(long)]
+    pub signer_keypair: PathBuf,
+
+    /// An address of the stake_caps_parameters program.
+    #[arg(long, env = "HEISENBERG_STAKE_CAPS_PARAMETERS_PROGRAM_ID")]
+    pub program_id: Pubkey,
+
+    /// An address of the parameters account from the stake_caps_parameters program.
+    ///
+    /// Defaults to the program derived address with a "parameters" seed, same as the
+    /// `set-parameters` command.
+    #[arg(long)]
+    pub parameters_account: Option<Pubkey>,
+
+    /// An authority that would be able to make changes to the parameters after this transaction.
+    ///
+    /// The `--signer-keypair` loses control over the parameters account once the transaction is
+    /// executed.
+    #[arg(long)]
+    pub new_authority: Pubkey,
+
+    /// Do not ask for a confirmation before sending the transaction.
+    #[arg(long)]
+    pub yes: bool,
+}
@@ -1,10 +1,48 @@
 use clap::Subcommand;
 
+pub mod airdrop;
+pub mod close_accounts;
+pub mod create_nonce_accounts;
 pub mod fill_up_to;
+pub mod fund_vote_accounts;
+pub mod watch_and_fill;
+pub mod withdraw_from_vote;
 
 #[derive(Subcommand, Debug)]
 #[command(name = "transfer")]
 pub enum Command {
     /// Makes sure that the specified accounts have at least a certain balance.
     FillUpTo(fill_up_to::FillUpToArgs),
+
+    /// Creates and funds multiple durable nonce accounts in parallel.
+    ///
+    /// Produces a file with addresses of all the created accounts.
+    CreateNonceAccounts(create_nonce_accounts::CreateNonceAccountsArgs),
+
+    /// Requests SOL from the cluster faucet for multiple accounts in parallel.
+    ///
+    /// An alternative to `fill-up-to` on clusters that run a faucet, when a funded account is not
+    /// available.
+    Airdrop(airdrop::AirdropArgs),
+
+    /// Continuously watches the specified accounts, topping them up whenever their balance drops
+    /// below a threshold.
+    ///
+    /// Runs until an INT or a TERM signal is received.
+    WatchAndFill(watch_and_fill::WatchAndFillArgs),
+
+    /// Tops up vote accounts to a target balance, in parallel.
+    FundVoteAccounts(fund_vote_accounts::FundVoteAccountsArgs),
+
+    /// Withdraws the balance above a floor from vote accounts, in parallel, using their withdraw
+    /// authority keypairs.
+    ///
+    /// Vote accounts are never brought below the rent exempt minimum.
+    WithdrawFromVote(withdraw_from_vote::WithdrawFromVoteArgs),
+
+    /// Closes system accounts, in parallel, by transferring out their whole balance.
+    ///
+    /// The teardown counterpart of the commands that create accounts in bulk, like
+    /// `create-nonce-accounts`.  Prints the amount reclaimed from every account.
+    CloseAccounts(close_accounts::CloseAccountsArgs),
 }
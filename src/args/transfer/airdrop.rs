@@ -0,0 +1,81 @@
+use std::{path::PathBuf, time::Duration as StdDuration};
+
+use anyhow::{Result, bail};
+use clap::Args;
+use humantime::Duration;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::{JsonRpcUrlArgs, u64_nice_parser};
+
+#[derive(Args, Debug)]
+pub struct AirdropArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// Address of a standalone faucet, as "host:port".
+    ///
+    /// By default, airdrops are requested via the `requestAirdrop` RPC call, that is forwarded to
+    /// the faucet configured on the RPC node.  Use this argument when the RPC node does not have a
+    /// faucet configured, but the faucet itself is reachable.  The standard faucet port is 9900.
+    #[arg(long, value_name = "HOST:PORT")]
+    pub faucet: Option<String>,
+
+    /// Amount to airdrop to every recipient, in lamports.
+    #[arg(long, value_parser = u64_nice_parser)]
+    pub lamports: u64,
+
+    /// A file with recipient addresses, one per line.
+    ///
+    /// Empty lines and lines starting with '#' are ignored.  Can be combined with the recipients
+    /// specified on the command line.
+    #[arg(long)]
+    pub recipients_file: Option<PathBuf>,
+
+    /// Maximum number of airdrops in flight at the same time.
+    ///
+    /// Faucets limit the request rate per IP, so a high value might cause more retries.
+    #[arg(long, default_value_t = 8)]
+    pub max_parallel: usize,
+
+    /// How many times to retry a failed airdrop, before giving up on a recipient.
+    #[arg(long, default_value_t = 5)]
+    pub retries: usize,
+
+    /// Delay before the first retry.  Every subsequent retry waits twice as long.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(1).into())]
+    pub retry_delay: Duration,
+
+    /// Accounts to airdrop to.  These accounts do not need to exist.
+    pub recipients: Vec<Pubkey>,
+}
+
+/// Additional validation of the [`AirdropArgs`] instances.
+impl AirdropArgs {
+    pub fn check_are_valid(&self) -> Result<()> {
+        let Self {
+            lamports,
+            recipients_file,
+            max_parallel,
+            recipients,
+            ..
+        } = self;
+
+        if recipients.is_empty() && recipients_file.is_none() {
+            bail!("Specify at least one recipient, or a --recipients-file");
+        }
+
+        if *lamports == 0 {
+            bail!("--lamports must be positive");
+        }
+
+        if *max_parallel == 0 {
+            bail!("--max-parallel must be positive");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use clap::{ArgAction, Args};
+use solana_program::pubkey::Pubkey;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct CloseAccountsArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// A keypair file for an account to close.
+    ///
+    /// Can be repeated.  `dir://` sources select all the matching keypair files in a directory.
+    /// For example, this closes the nonce accounts created by `create-nonce-accounts`:
+    ///
+    ///   --account-keypair dir://nonces/nonce-*.json
+    ///
+    /// Accounts need to be owned by the system program.  Accounts without data are closed by
+    /// transferring out their whole balance, signed by the account keypair.  Nonce accounts are
+    /// closed by withdrawing their whole balance, signed by the nonce authority.
+    ///
+    /// Accounts that do not exist are skipped, so that an interrupted run can be repeated.
+    #[arg(long, action = ArgAction::Append, required = true)]
+    pub account_keypair: Vec<PathBuf>,
+
+    /// A keypair file for the account that would pay for the transactions.
+    ///
+    /// The payer covers the fees, so that the closed accounts can transfer out their whole
+    /// balance.  It can not be one of the closed accounts.
+    #[arg(long, env = "HEISENBERG_PAYER_KEYPAIR")]
+    pub payer_keypair: PathBuf,
+
+    /// A keypair file for the authority of the nonce accounts to close.
+    ///
+    /// Defaults to the `--payer-keypair`, same as in `create-nonce-accounts`.
+    #[arg(long)]
+    pub nonce_authority_keypair: Option<PathBuf>,
+
+    /// An account to receive the reclaimed SOL.
+    ///
+    /// Defaults to the `--payer-keypair`.
+    #[arg(long)]
+    pub to: Option<Pubkey>,
+
+    /// After all the accounts are closed, write a CSV report into this file.
+    ///
+    /// Each line describes one closed account:
+    ///
+    ///   "[account],[lamports],[signature],[error]"
+    ///
+    /// For successfully closed accounts the error column is empty.  For failed ones the signature
+    /// column is empty.
+    #[arg(long)]
+    pub report_file: Option<PathBuf>,
+}
@@ -0,0 +1,56 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::{JsonRpcUrlArgs, u64_nice_parser};
+
+#[derive(Args, Debug)]
+pub struct CreateNonceAccountsArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// A keypair file for the account that would pay for the transactions and fund the new nonce
+    /// accounts.
+    #[arg(long, env = "HEISENBERG_PAYER_KEYPAIR")]
+    pub payer_keypair: PathBuf,
+
+    /// An account that would be able to advance, authorize, and withdraw from the created nonce
+    /// accounts.
+    ///
+    /// Defaults to the `--payer-keypair`.
+    #[arg(long)]
+    pub nonce_authority: Option<Pubkey>,
+
+    /// Number of nonce accounts to create.
+    ///
+    /// Accounts that already exist as nonce accounts with the `--nonce-authority` are skipped, so a
+    /// failed run can be repeated to create the rest.  Any other existing account is an error.
+    #[arg(long)]
+    pub count: usize,
+
+    /// A directory to hold keypairs of the nonce accounts.
+    ///
+    /// Keypairs are named `nonce-{i}.json`, where `i` goes from 0 to `--count` - 1.  If a keypair
+    /// file already exists, it is reused.  Otherwise, a new keypair is generated and written to
+    /// this directory.
+    ///
+    /// The directory is created, if it does not exist.  No keypairs are generated with
+    /// `--dry-run`.
+    #[arg(long)]
+    pub keypair_dir: PathBuf,
+
+    /// Balance of each created nonce account, in lamports.
+    ///
+    /// Defaults to the rent exempt minimum for a nonce account.
+    #[arg(long, value_parser = u64_nice_parser)]
+    pub lamports: Option<u64>,
+
+    /// A file to write the addresses of the nonce accounts to, one per line.
+    ///
+    /// Only lists accounts that already existed or were created successfully.
+    ///
+    /// Defaults to `nonce-accounts.txt` inside the `--keypair-dir`.
+    #[arg(long)]
+    pub accounts_file: Option<PathBuf>,
+}
@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
-use clap::Args;
+use anyhow::{Result, bail};
+use clap::{ArgAction, Args, ValueEnum};
 use solana_program::pubkey::Pubkey;
 
 use crate::args::{JsonRpcUrlArgs, u64_nice_parser};
@@ -17,27 +18,144 @@ pub struct FillUpToArgs {
     /// A keypair file for the account that would pay for the transaction.
     ///
     /// Defaults to the `--signer-keypair`.
-    #[arg(long)]
+    #[arg(long, env = "HEISENBERG_PAYER_KEYPAIR")]
     pub payer_keypair: Option<PathBuf>,
 
     /// An account to transfer SOL from.
     ///
+    /// Can be repeated, to draw funds from multiple accounts, when a single account can not cover
+    /// all the transfers.  See `--funding-order` for how the funds are drawn.
+    ///
+    /// A from account is either drained completely, or left with at least the rent exempt minimum.
+    /// When the payer is also a from account, it keeps enough to pay the fees for all the
+    /// transactions.
+    ///
     /// Defaults to the `--payer-keypair`.
-    #[arg(long)]
-    pub from_keypair: Option<PathBuf>,
+    #[arg(long, action = ArgAction::Append)]
+    pub from_keypair: Vec<PathBuf>,
+
+    /// When multiple `--from-keypair` accounts are specified, controls how the transferred amounts
+    /// are split between them.
+    #[arg(long, value_enum, default_value_t = FundingOrder::InOrder)]
+    pub funding_order: FundingOrder,
 
-    /// A balance that we want to see on all the specified target accounts, in lamports.
-    #[arg(long, value_parser = u64_nice_parser)]
-    pub target_balance: u64,
+    /// A balance that we want to see on all the specified target accounts.
+    ///
+    /// Either an amount in lamports, or `rent-exempt+<lamports>`, for the rent exempt minimum of
+    /// each account, plus the specified amount.  The rent exempt minimum depends on the size of the
+    /// account data, so accounts of different sizes get different target balances.  Accounts that
+    /// do not exist yet are targeted as accounts with no data.  `rent-exempt` is the same as
+    /// `rent-exempt+0`.
+    ///
+    /// Required when target accounts are specified on the command line.  Entries in the
+    /// `--targets-file` carry their own target balances.
+    #[arg(long, alias = "target", value_parser = target_balance_parser)]
+    pub target_balance: Option<TargetBalance>,
+
+    /// A CSV file with per-account target balances.
+    ///
+    /// Each line holds an account address and a target balance for this account, separated by a
+    /// comma:
+    ///
+    ///   "[pubkey],[target lamports]"
+    ///
+    /// Target balances use the same format as the `--target-balance` values, so
+    /// `rent-exempt+<lamports>` targets are supported as well.
+    ///
+    /// Empty lines and lines starting with '#' are ignored.
+    ///
+    /// This allows different classes of accounts (validators, payers, publishers) to be topped up
+    /// to different levels in one run.  Can be combined with the target accounts specified on the
+    /// command line, that use the `--target-balance` value.  An account may only be listed once,
+    /// across both the command line and the file.
+    #[arg(long)]
+    pub targets_file: Option<PathBuf>,
 
     /// Print expected balance increments for all the accounts that are going to receive balance
     /// transfers.
     #[arg(long)]
     pub print_target_increments: bool,
 
+    /// Attach a memo instruction with this text to every transfer transaction.
+    ///
+    /// Use a run id or a label, to make it possible to find out who funded an account, when
+    /// multiple people are funding accounts on a shared cluster.
+    #[arg(long)]
+    pub memo: Option<String>,
+
+    /// After all the transfers are executed, write a CSV report into this file.
+    ///
+    /// Each line describes one recipient:
+    ///
+    ///   "[recipient],[lamports],[signature],[error]"
+    ///
+    /// For successful transfers the error column is empty.  For failed transfers the signature
+    /// column is empty.
+    #[arg(long)]
+    pub report_file: Option<PathBuf>,
+
     /// Target accounts, that after successful execution should all have a balance equal to
     /// `--target-balance`.
     ///
     /// These accounts do not need to exist.
     pub recepients: Vec<Pubkey>,
 }
+
+/// Balance to top up a target account to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetBalance {
+    Lamports(u64),
+    /// Rent exempt minimum for the account data size, plus this many lamports.
+    RentExemptPlus(u64),
+}
+
+const RENT_EXEMPT_PREFIX: &str = "rent-exempt";
+
+pub fn target_balance_parser(value: &str) -> Result<TargetBalance, String> {
+    let Some(extra) = value.strip_prefix(RENT_EXEMPT_PREFIX) else {
+        return u64_nice_parser(value).map(TargetBalance::Lamports);
+    };
+
+    if extra.is_empty() {
+        return Ok(TargetBalance::RentExemptPlus(0));
+    }
+
+    match extra.strip_prefix('+') {
+        Some(lamports) => u64_nice_parser(lamports).map(TargetBalance::RentExemptPlus),
+        None => Err(format!(
+            "expected a lamports amount, or \"{RENT_EXEMPT_PREFIX}+<lamports>\", got: {value}"
+        )),
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FundingOrder {
+    /// Draw from the first `--from-keypair` account until its balance is exhausted, then move on
+    /// to the next one, in the order they are specified.
+    InOrder,
+    /// Draw from all the `--from-keypair` accounts, proportionally to their current balances,
+    /// less the amounts they need to keep.
+    Proportional,
+}
+
+/// Additional validation of the [`FillUpToArgs`] instances.
+impl FillUpToArgs {
+    pub fn check_are_valid(&self) -> Result<()> {
+        let Self {
+            target_balance,
+            targets_file,
+            recepients,
+            ..
+        } = self;
+
+        if recepients.is_empty() && targets_file.is_none() {
+            bail!("Specify at least one target account, or a --targets-file");
+        }
+
+        if !recepients.is_empty() && target_balance.is_none() {
+            bail!("--target-balance is required when target accounts are specified");
+        }
+
+        Ok(())
+    }
+}
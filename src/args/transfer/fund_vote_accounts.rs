@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use clap::Args;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::{JsonRpcUrlArgs, u64_nice_parser};
+
+#[derive(Args, Debug)]
+pub struct FundVoteAccountsArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// A keypair file for the account to transfer SOL from.  It also pays for the transactions.
+    #[arg(long, env = "HEISENBERG_PAYER_KEYPAIR")]
+    pub from_keypair: PathBuf,
+
+    /// A balance that we want to see on all the vote accounts, in lamports.
+    ///
+    /// Should be at or above the rent exempt minimum for a vote account.
+    #[arg(long, value_parser = u64_nice_parser)]
+    pub target_balance: u64,
+
+    /// Fund all the vote accounts of the cluster, as reported by `getVoteAccounts`, including the
+    /// delinquent ones.
+    #[arg(long)]
+    pub all_vote_accounts: bool,
+
+    /// Vote accounts to fund.  These accounts need to exist.
+    pub vote_accounts: Vec<Pubkey>,
+}
+
+/// Additional validation of the [`FundVoteAccountsArgs`] instances.
+impl FundVoteAccountsArgs {
+    pub fn check_are_valid(&self) -> Result<()> {
+        let Self {
+            all_vote_accounts,
+            vote_accounts,
+            ..
+        } = self;
+
+        if vote_accounts.is_empty() && !all_vote_accounts {
+            bail!("Specify at least one vote account, or --all-vote-accounts");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use std::{path::PathBuf, time::Duration as StdDuration};
+
+use clap::Args;
+use humantime::Duration;
+use solana_program::pubkey::Pubkey;
+
+use crate::args::{JsonRpcUrlArgs, u64_nice_parser};
+
+#[derive(Args, Debug)]
+pub struct WatchAndFillArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// A keypair file for the account that would pay for the transfer transactions.
+    #[arg(long, env = "HEISENBERG_PAYER_KEYPAIR")]
+    pub payer_keypair: PathBuf,
+
+    /// An account to transfer SOL from.
+    ///
+    /// Defaults to the `--payer-keypair`.
+    #[arg(long)]
+    pub from_keypair: Option<PathBuf>,
+
+    /// A balance that watched accounts are topped up to, in lamports.
+    #[arg(long, value_parser = u64_nice_parser)]
+    pub target_balance: u64,
+
+    /// When a watched account balance drops below this value, in lamports, it is topped up back
+    /// to the `--target-balance`.
+    ///
+    /// Defaults to the `--target-balance`, meaning that any spending is replenished on the next
+    /// check.
+    #[arg(long, value_parser = u64_nice_parser)]
+    pub threshold: Option<u64>,
+
+    /// Delay between consecutive balance checks.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(30).into())]
+    pub poll_interval: Duration,
+
+    /// Accounts to watch.
+    ///
+    /// These accounts do not need to exist.  Missing accounts are created with the
+    /// `--target-balance`.
+    #[arg(required = true)]
+    pub recepients: Vec<Pubkey>,
+}
@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+use clap::{ArgAction, Args};
+use solana_program::pubkey::Pubkey;
+
+use crate::args::{JsonRpcUrlArgs, u64_nice_parser};
+
+#[derive(Args, Debug)]
+pub struct WithdrawFromVoteArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// A keypair file for the withdraw authority of one or more vote accounts.
+    ///
+    /// Can be repeated.  Every vote account is withdrawn from with the keypair that matches its
+    /// authorized withdrawer.
+    #[arg(long, action = ArgAction::Append, required = true)]
+    pub withdraw_authority_keypair: Vec<PathBuf>,
+
+    /// A keypair file for the account that would pay for the transactions.
+    ///
+    /// Defaults to the first `--withdraw-authority-keypair`.
+    #[arg(long, env = "HEISENBERG_PAYER_KEYPAIR")]
+    pub payer_keypair: Option<PathBuf>,
+
+    /// An account to receive the withdrawn SOL.
+    #[arg(long)]
+    pub to: Pubkey,
+
+    /// A balance to leave on every vote account, in lamports.
+    ///
+    /// Vote accounts are never brought below the rent exempt minimum, so that they stay open.
+    /// Without this argument, everything above the rent exempt minimum is withdrawn.
+    #[arg(long, value_parser = u64_nice_parser)]
+    pub keep_balance: Option<u64>,
+
+    /// Withdraw from all the vote accounts of the cluster, as reported by `getVoteAccounts`, that
+    /// any of the `--withdraw-authority-keypair` keypairs can withdraw from.
+    #[arg(long)]
+    pub all_vote_accounts: bool,
+
+    /// Vote accounts to withdraw from.
+    pub vote_accounts: Vec<Pubkey>,
+}
+
+/// Additional validation of the [`WithdrawFromVoteArgs`] instances.
+impl WithdrawFromVoteArgs {
+    pub fn check_are_valid(&self) -> Result<()> {
+        let Self {
+            all_vote_accounts,
+            vote_accounts,
+            ..
+        } = self;
+
+        if vote_accounts.is_empty() && !all_vote_accounts {
+            bail!("Specify at least one vote account, or --all-vote-accounts");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use clap::Subcommand;
+
+pub mod build_and_send;
+pub mod landing_report;
+pub mod load;
+
+#[derive(Subcommand, Debug)]
+#[command(name = "tx")]
+pub enum Command {
+    /// Analyzes where and when a set of transactions landed.
+    ///
+    /// Reads a file of transaction signatures, fetches their statuses, and reports how the
+    /// transactions are distributed across slots, how long it took them to land, and why some of
+    /// them failed.
+    LandingReport(landing_report::LandingReportArgs),
+
+    /// Builds transactions from a YAML template, and sends them.
+    ///
+    /// Meant for one-off instructions against test programs, that do not have a dedicated command.
+    BuildAndSend(build_and_send::BuildAndSendArgs),
+
+    /// Sends transactions built from a YAML template at a fixed rate.
+    ///
+    /// Loads a cluster with non-Oracle traffic, for example alongside a `price-store benchmark1`
+    /// run.  Template placeholders make every transaction unique.
+    Load(load::LoadArgs),
+}
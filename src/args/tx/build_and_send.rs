@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct BuildAndSendArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// A YAML file that describes the transactions to send.  For example:
+    ///
+    ///   signers:
+    ///     - name: payer
+    ///       keypair: payer.json
+    ///     - name: authority
+    ///       keypair: usb://ledger?key=0
+    ///   # Defaults to the first signer.
+    ///   fee_payer: payer
+    ///   transactions:
+    ///     - instructions:
+    ///         - program_id: 11111111111111111111111111111111
+    ///           accounts:
+    ///             - signer: payer
+    ///               writable: true
+    ///             - pubkey: 9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin
+    ///               writable: true
+    ///           data:
+    ///             fields:
+    ///               - u32: 2
+    ///               - u64: 1000000
+    ///
+    /// Instruction data is one of `base64: ...`, `hex: ...`, or `fields: [...]`.  Fields are
+    /// encoded in order, little endian, and each is one of: `u8`, `u16`, `u32`, `u64`, `i8`,
+    /// `i16`, `i32`, `i64`, `bool`, `pubkey`, `hex`, or `string` (a `u32` length followed by the
+    /// UTF-8 bytes, as Borsh does it).
+    ///
+    /// Placeholders use the transaction position in the template, starting from 0, as an index.  A
+    /// signer with `keypairs: [...]`, rather than a `keypair`, is a pool, and transactions use the
+    /// pool keypairs in turn.  An `index: <width>` field, where the width is one of `u8`, `u16`,
+    /// `u32`, or `u64`, encodes the index itself.
+    ///
+    /// Keypair paths are relative to the template file location.
+    ///
+    /// Transactions are sent in parallel.
+    #[arg(long)]
+    pub template: PathBuf,
+
+    /// Send transactions without running a preflight simulation.
+    #[arg(long)]
+    pub skip_preflight: bool,
+
+    /// After the transactions are executed, fetch their metadata, and report the compute units
+    /// they consumed and the fees they paid.
+    #[arg(long)]
+    pub report_costs: bool,
+
+    /// Do not ask for a confirmation before sending the transactions.
+    #[arg(long)]
+    pub yes: bool,
+}
@@ -0,0 +1,26 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use crate::args::JsonRpcUrlArgs;
+
+#[derive(Args, Debug)]
+pub struct LandingReportArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// A file with one transaction signature per line.
+    ///
+    /// A line may also contain a slot the transaction was sent at, separated by a comma:
+    ///
+    ///   "[signature],[sent slot]"
+    ///
+    /// When the sent slot is known, the report includes inclusion delay percentiles.  Empty lines
+    /// and lines starting with `#` are ignored.
+    #[arg(long)]
+    pub signatures_file: PathBuf,
+
+    /// Do not print the per slot landing distribution, only the summary.
+    #[arg(long)]
+    pub no_per_slot: bool,
+}
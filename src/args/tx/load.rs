@@ -0,0 +1,107 @@
+use std::{path::PathBuf, time::Duration as StdDuration};
+
+use anyhow::{Result, bail};
+use clap::Args;
+use humantime::Duration;
+use reqwest::Url;
+
+use crate::args::{JsonRpcUrlArgs, abort_args::AbortArgs, price_store::benchmark1::SendMode};
+
+#[derive(Args, Debug)]
+pub struct LoadArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    #[arg(long, value_name = "URL", default_value = "ws://localhost:8900")]
+    /// A WebSocket address of a Pythnet node.
+    pub websocket_url: Url,
+
+    /// A YAML file that describes the transactions to send, in the same format as the
+    /// `tx build-and-send` template.
+    ///
+    /// Transactions are numbered from `--start-index` up, and cycle through the template
+    /// transactions.  The transaction number is used as an index for the template placeholders:
+    /// signers with `keypairs: [...]` pools and the `index: <width>` data fields.  For example:
+    ///
+    ///   signers:
+    ///     - name: payer
+    ///       keypairs: [payer-1.json, payer-2.json, payer-3.json]
+    ///   transactions:
+    ///     - instructions:
+    ///         - program_id: MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr
+    ///           accounts:
+    ///             - signer: payer
+    ///           data:
+    ///             fields:
+    ///               - index: u64
+    ///
+    /// Transactions that are identical within a blockhash lifetime are dropped by the cluster as
+    /// duplicates.  Use placeholders to make every transaction unique.
+    #[arg(long)]
+    pub template: PathBuf,
+
+    /// Number of transactions to send every second.
+    #[arg(long, default_value_t = 100)]
+    pub rate: u32,
+
+    /// Send transactions for this long.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long)]
+    pub duration: Duration,
+
+    /// Index of the first transaction.
+    ///
+    /// Runs started within a blockhash lifetime of each other produce identical transactions for
+    /// the same indices, so they should use different index ranges.
+    #[arg(long, default_value_t = 0)]
+    pub start_index: u64,
+
+    /// How to deliver the transactions to the cluster.
+    #[arg(long, value_enum, default_value_t = SendMode::Udp)]
+    pub send_mode: SendMode,
+
+    #[arg(long, default_value_t = 4)]
+    /// In the `udp` send mode, send each transaction to validators that cover this many slots in
+    /// the future.
+    pub fanout_slots: u8,
+
+    /// In the `rpc` send mode, maximum number of `sendTransaction` requests waiting for a
+    /// response.  When the RPC node can not keep up, sending is slowed down, rather than the
+    /// requests piling up.
+    #[arg(long, default_value_t = 256)]
+    pub max_in_flight: usize,
+
+    /// An interval for reporting send stats.
+    ///
+    /// This accepts any formats that the `humantime` library can parse, for the `Duration` values:
+    ///
+    /// https://docs.rs/humantime/latest/humantime/
+    #[arg(long, default_value_t = StdDuration::from_secs(10).into())]
+    pub stats_update_interval: Duration,
+
+    /// Write signatures of all the sent transactions into this file, along with the slot they
+    /// were sent at, for a later `tx landing-report`.
+    #[arg(long)]
+    pub signatures_file: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub abort: AbortArgs,
+}
+
+impl LoadArgs {
+    pub fn check_are_valid(&self) -> Result<()> {
+        if self.rate == 0 {
+            bail!("--rate should be above zero");
+        }
+        if self.max_in_flight == 0 {
+            bail!("--max-in-flight should be above zero");
+        }
+        if StdDuration::from(self.stats_update_interval).is_zero() {
+            bail!("--stats-update-interval should be above zero");
+        }
+        Ok(())
+    }
+}
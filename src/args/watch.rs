@@ -0,0 +1,10 @@
+use clap::Subcommand;
+
+pub mod accounts;
+
+#[derive(Subcommand, Debug)]
+#[command(name = "watch")]
+pub enum Command {
+    /// Prints every change to a set of accounts, or to all accounts of a program, as they happen.
+    Accounts(accounts::AccountsArgs),
+}
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use anyhow::{Result, bail};
+
+use clap::{ArgAction, ArgGroup, Args, ValueEnum};
+use reqwest::Url;
+use solana_program::pubkey::Pubkey;
+
+#[cfg(feature = "geyser")]
+use crate::args::geyser_args::GeyserArgs;
+use crate::args::{JsonRpcUrlArgs, metrics_args::MetricsArgs};
+
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("target").required(true).args(["account", "program"])))]
+pub struct AccountsArgs {
+    #[command(flatten)]
+    pub json_rpc_url: JsonRpcUrlArgs,
+
+    /// A WebSocket address of a Pythnet node.
+    ///
+    /// Defaults to an address derived from the `--rpc-url`, the same way the `solana` CLI does it:
+    /// "http" becomes "ws", "https" becomes "wss", and the port, if specified, is incremented by
+    /// one.
+    #[arg(long, value_name = "URL")]
+    pub websocket_url: Option<Url>,
+
+    #[cfg(feature = "geyser")]
+    #[command(flatten)]
+    pub geyser: GeyserArgs,
+
+    /// An account to watch.  Can be repeated.
+    #[arg(long, action = ArgAction::Append)]
+    pub account: Vec<Pubkey>,
+
+    /// Watch all the accounts owned by this program.
+    #[arg(long)]
+    pub program: Option<Pubkey>,
+
+    /// Include a hex dump of the account data into every change report.
+    #[arg(long)]
+    pub hexdump: bool,
+
+    /// Decode account data using one of the known account layouts.
+    #[arg(long, value_enum)]
+    pub decode: Option<AccountDecoder>,
+
+    /// A file to append every change into, as a single line JSON object.
+    ///
+    /// Records are appended, so the same file can be used across multiple runs.
+    #[arg(long)]
+    pub record_file: Option<PathBuf>,
+
+    /// A CSV file to append the prices held by the watched price buffers into, for offline
+    /// analysis of the update coverage.
+    ///
+    /// Every slot, the last state of every price buffer is written as one row per price: slot,
+    /// publisher, feed index, trading status, price, and confidence.  Requires
+    /// `--decode price-buffer`.
+    #[arg(long)]
+    pub samples_file: Option<PathBuf>,
+
+    /// Every change is pushed as an `account_change` measurement.
+    #[command(flatten)]
+    pub metrics: MetricsArgs,
+}
+
+impl AccountsArgs {
+    pub fn check_are_valid(&self) -> Result<()> {
+        if self.samples_file.is_some() && self.decode != Some(AccountDecoder::PriceBuffer) {
+            bail!("--samples-file requires --decode price-buffer");
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountDecoder {
+    /// An Oracle program price account.
+    OraclePrice,
+    /// A Price Store program price buffer account.
+    PriceBuffer,
+    /// A stake caps parameters program parameters account.
+    StakeCapsParameters,
+}
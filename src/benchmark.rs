@@ -0,0 +1,12 @@
+use anyhow::Result;
+
+use crate::args::benchmark::Command;
+
+mod compare;
+pub mod results;
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Compare(args) => compare::run(args).await,
+    }
+}
@@ -0,0 +1,146 @@
+//! Compares the metrics of two benchmark runs.
+
+use std::collections::BTreeSet;
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use pythnet_heisenberg::output;
+use serde_json::json;
+
+use crate::{
+    args::benchmark::compare::CompareArgs,
+    benchmark::results::{Better, Metric, RunSummary},
+    exit_code::ValidationFailed,
+};
+
+pub async fn run(
+    CompareArgs {
+        results_dir,
+        threshold,
+        baseline,
+        candidate,
+    }: CompareArgs,
+) -> Result<()> {
+    let baseline = RunSummary::load(&baseline, results_dir.as_deref())?;
+    let candidate = RunSummary::load(&candidate, results_dir.as_deref())?;
+
+    if baseline.benchmark != candidate.benchmark {
+        bail!(
+            "Runs are from different benchmarks: {} and {}",
+            baseline.benchmark,
+            candidate.benchmark
+        );
+    }
+
+    let scenario_hash =
+        |run: &RunSummary| run.scenario.as_ref().map(|scenario| scenario.hash.clone());
+    if scenario_hash(&baseline) != scenario_hash(&candidate) {
+        output::notice(
+            "Runs used different scenario files, so the differences might come from the \
+             different load",
+        );
+    }
+
+    output::notice(format!(
+        "Comparing {} runs: baseline started at {}, candidate started at {}",
+        baseline.benchmark, baseline.started_at, candidate.started_at
+    ));
+
+    let names = baseline
+        .metrics
+        .keys()
+        .chain(candidate.metrics.keys())
+        .collect::<BTreeSet<_>>();
+
+    let mut regressions = vec![];
+    for name in names {
+        let (baseline, candidate) = match (baseline.metrics.get(name), candidate.metrics.get(name))
+        {
+            (Some(baseline), Some(candidate)) => (baseline, candidate),
+            (Some(Metric { value, .. }), None) => {
+                output::result(
+                    format!("  {name}: {value:.2} -> missing"),
+                    json!({ "metric": name, "baseline": value, "candidate": null }),
+                );
+                continue;
+            }
+            (None, Some(Metric { value, .. })) => {
+                output::result(
+                    format!("  {name}: missing -> {value:.2}"),
+                    json!({ "metric": name, "baseline": null, "candidate": value }),
+                );
+                continue;
+            }
+            (None, None) => unreachable!("`name` comes from one of the runs"),
+        };
+
+        let change_percent = change_percent(baseline.value, candidate.value);
+        let regressed = is_regression(baseline, candidate, change_percent, threshold);
+        if regressed {
+            regressions.push(name.clone());
+        }
+
+        let change_text = match change_percent {
+            Some(change) => format!("{change:+.2}%"),
+            None => "n/a".to_owned(),
+        };
+        let flag = if regressed { "  REGRESSION" } else { "" };
+        output::result(
+            format!(
+                "  {name}: {:.2} -> {:.2} ({change_text}){flag}",
+                baseline.value, candidate.value
+            ),
+            json!({
+                "metric": name,
+                "baseline": baseline.value,
+                "candidate": candidate.value,
+                "change_percent": change_percent,
+                "better": baseline.better,
+                "regression": regressed,
+            }),
+        );
+    }
+
+    if !regressions.is_empty() {
+        return Err(anyhow!(
+            "{} metrics regressed by more than {threshold}%: {}",
+            regressions.len(),
+            regressions.join(", ")
+        ))
+        .context(ValidationFailed);
+    }
+
+    output::notice(format!("No metrics regressed by more than {threshold}%"));
+
+    Ok(())
+}
+
+/// Relative change from the `baseline` to the `candidate`.  Undefined when the baseline is zero,
+/// unless both are zero.
+fn change_percent(baseline: f64, candidate: f64) -> Option<f64> {
+    if baseline == 0.0 {
+        return (candidate == 0.0).then_some(0.0);
+    }
+    Some((candidate - baseline) / baseline.abs() * 100.0)
+}
+
+/// A metric regressed if it moved in the worse direction by more than the `threshold` percent.  A
+/// metric that moves away from a zero baseline in the worse direction, like an error rate, is
+/// always a regression.
+fn is_regression(
+    baseline: &Metric,
+    candidate: &Metric,
+    change_percent: Option<f64>,
+    threshold: f64,
+) -> bool {
+    let worse_change = match baseline.better {
+        Better::Higher => change_percent.map(|change| -change),
+        Better::Lower => change_percent,
+    };
+    match worse_change {
+        Some(worse_change) => worse_change > threshold,
+        None => match baseline.better {
+            Better::Higher => candidate.value < baseline.value,
+            Better::Lower => candidate.value > baseline.value,
+        },
+    }
+}
@@ -0,0 +1,148 @@
+//! Summaries of benchmark runs, stored as JSON files in a results directory, so that runs can be
+//! compared later with `benchmark compare`.
+//!
+//! Every benchmark records its own set of metrics.  Each metric carries the direction in which it
+//! is better, so that a comparison does not need to know anything about the benchmark that
+//! produced it.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result, bail};
+use chrono::{DateTime, Local};
+use pythnet_heisenberg::output;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_sdk::hash::hash;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Better {
+    Higher,
+    Lower,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Metric {
+    pub value: f64,
+    pub better: Better,
+}
+
+/// The scenario file a run was started with.  Runs with the same `hash` used the same arguments,
+/// except for the ones given on the command line.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScenarioInfo {
+    pub path: String,
+    /// SHA-256 of the scenario file content.
+    pub hash: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RunSummary {
+    /// Command that produced the run, like "price-store benchmark1".
+    pub benchmark: String,
+    /// RFC 3339 timestamps.
+    pub started_at: String,
+    pub ended_at: String,
+    pub scenario: Option<ScenarioInfo>,
+    pub metrics: BTreeMap<String, Metric>,
+    /// Made of the start time and the benchmark name, so that runs sort chronologically.  Only
+    /// known for new runs.
+    #[serde(skip)]
+    id: String,
+}
+
+impl RunSummary {
+    pub fn new(
+        benchmark: &str,
+        started_at: DateTime<Local>,
+        scenario: Option<&Path>,
+    ) -> Result<Self> {
+        let scenario = scenario
+            .map(|path| -> Result<ScenarioInfo> {
+                let content = fs::read(path).with_context(|| {
+                    format!("Failed to read scenario file: {}", path.to_string_lossy())
+                })?;
+                Ok(ScenarioInfo {
+                    path: path.to_string_lossy().into_owned(),
+                    hash: hash(&content).to_string(),
+                })
+            })
+            .transpose()?;
+
+        let id = format!(
+            "{}-{}",
+            started_at.format("%Y%m%d-%H%M%S"),
+            benchmark.replace(' ', "-")
+        );
+
+        Ok(Self {
+            benchmark: benchmark.to_owned(),
+            started_at: started_at.to_rfc3339(),
+            ended_at: String::new(),
+            scenario,
+            metrics: BTreeMap::new(),
+            id,
+        })
+    }
+
+    pub fn add(&mut self, name: impl Into<String>, value: f64, better: Better) {
+        self.metrics.insert(name.into(), Metric { value, better });
+    }
+
+    /// Stores the summary as `<run id>.json` in the `dir`, creating the directory if necessary.
+    pub fn save(mut self, dir: &Path, ended_at: DateTime<Local>) -> Result<()> {
+        self.ended_at = ended_at.to_rfc3339();
+
+        let id = &self.id;
+        let path = dir.join(format!("{id}.json"));
+
+        fs::create_dir_all(dir).with_context(|| {
+            format!(
+                "Failed to create results directory: {}",
+                dir.to_string_lossy()
+            )
+        })?;
+        let content =
+            serde_json::to_string_pretty(&self).expect("Run summary serializes into JSON");
+        fs::write(&path, content)
+            .with_context(|| format!("Failed to write results file: {}", path.to_string_lossy()))?;
+
+        output::result(
+            format!("Results saved as {id}: {}", path.to_string_lossy()),
+            json!({
+                "results": {
+                    "id": id,
+                    "path": path.to_string_lossy(),
+                },
+            }),
+        );
+
+        Ok(())
+    }
+
+    /// Reads a run summary from a file at `run`, or, if there is no such file, from a file for a
+    /// run with this id in the `results_dir`.
+    pub fn load(run: &str, results_dir: Option<&Path>) -> Result<Self> {
+        let path = PathBuf::from(run);
+        let path = if path.is_file() {
+            path
+        } else {
+            match results_dir {
+                Some(dir) => dir.join(format!("{run}.json")),
+                None => bail!(
+                    "{run} is not a results file, and no --results-dir is specified to look up a \
+                     run with this id"
+                ),
+            }
+        };
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read results file: {}", path.to_string_lossy()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse results file: {}", path.to_string_lossy()))
+    }
+}
@@ -0,0 +1,11 @@
+use anyhow::Result;
+
+use crate::args::block::Command;
+
+mod inspect;
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Inspect(args) => inspect::run(args).await,
+    }
+}
@@ -0,0 +1,388 @@
+//! Fetches a block and describes transactions that invoke the programs of interest.
+
+use std::{mem::size_of, str::FromStr as _};
+
+use anyhow::{Context as _, Result};
+use pythnet_heisenberg::{
+    output,
+    price_store::instructions::submit_prices::{BufferedPrice, SubmitPricesArgsHeader},
+};
+use serde_json::json;
+use solana_rpc_client_api::config::RpcBlockConfig;
+use solana_sdk::{
+    clock::Slot, commitment_config::CommitmentConfig, compute_budget, pubkey::Pubkey,
+    system_program, vote,
+};
+use solana_transaction_status::{
+    EncodedTransactionWithStatusMeta, TransactionDetails, UiLoadedAddresses, UiTransactionEncoding,
+};
+
+use crate::args::{block::inspect::InspectArgs, json_rpc_url_args::get_rpc_client};
+
+pub async fn run(
+    InspectArgs {
+        json_rpc_url,
+        slot,
+        oracle_program_id,
+        price_store_program_id,
+        program_id: program_ids,
+        all,
+        include_votes,
+    }: InspectArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    // `getBlock` does not support the `processed` commitment.
+    let commitment = if rpc_client.commitment() == CommitmentConfig::processed() {
+        CommitmentConfig::confirmed()
+    } else {
+        rpc_client.commitment()
+    };
+    let block = rpc_client
+        .get_block_with_config(
+            slot,
+            RpcBlockConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                transaction_details: Some(TransactionDetails::Full),
+                rewards: Some(false),
+                commitment: Some(commitment),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await
+        .with_context(|| format!("Failed to fetch block at slot {slot}"))?;
+
+    let programs = Programs {
+        oracle: oracle_program_id,
+        price_store: price_store_program_id,
+        others: program_ids,
+    };
+
+    let transactions = block.transactions.unwrap_or_default();
+    let total = transactions.len();
+    let summaries = transactions
+        .iter()
+        .filter_map(|tx| TxSummary::new(tx, &programs))
+        .filter(|summary| {
+            if all {
+                include_votes || !summary.is_vote
+            } else {
+                summary.matches
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let block_time = block
+        .block_time
+        .and_then(|time| chrono::DateTime::from_timestamp(time, 0).map(|time| time.to_rfc3339()));
+    output::result(
+        format!(
+            "Slot {slot}: {} of {total} transactions shown\n  \
+               Blockhash: {}\n  \
+               Parent slot: {}\n  \
+               Block time: {}",
+            summaries.len(),
+            block.blockhash,
+            block.parent_slot,
+            block_time.as_deref().unwrap_or("n/a"),
+        ),
+        json!({
+            "slot": slot,
+            "blockhash": block.blockhash,
+            "parent_slot": block.parent_slot,
+            "block_time": block_time,
+            "total_tx": total,
+            "shown_tx": summaries.len(),
+        }),
+    );
+
+    for summary in &summaries {
+        summary.print(slot);
+    }
+
+    Ok(())
+}
+
+/// Programs that determine which transactions are shown.  Oracle and Price Store instructions are
+/// also decoded.
+struct Programs {
+    oracle: Option<Pubkey>,
+    price_store: Option<Pubkey>,
+    others: Vec<Pubkey>,
+}
+
+impl Programs {
+    fn is_of_interest(&self, program_id: &Pubkey) -> bool {
+        self.oracle.as_ref() == Some(program_id)
+            || self.price_store.as_ref() == Some(program_id)
+            || self.others.contains(program_id)
+    }
+
+    fn name(&self, program_id: &Pubkey) -> Option<&'static str> {
+        if self.oracle.as_ref() == Some(program_id) {
+            Some("Oracle")
+        } else if self.price_store.as_ref() == Some(program_id) {
+            Some("Price Store")
+        } else if *program_id == system_program::id() {
+            Some("System")
+        } else if *program_id == compute_budget::id() {
+            Some("Compute Budget")
+        } else if *program_id == vote::program::id() {
+            Some("Vote")
+        } else {
+            None
+        }
+    }
+
+    /// Short description of the instruction `data`, for the programs we know.
+    fn describe(&self, program_id: &Pubkey, data: &[u8]) -> String {
+        if self.oracle.as_ref() == Some(program_id) {
+            describe_oracle(data)
+        } else if self.price_store.as_ref() == Some(program_id) {
+            describe_price_store(data)
+        } else if *program_id == compute_budget::id() {
+            describe_compute_budget(data)
+        } else {
+            format!("{} bytes", data.len())
+        }
+    }
+}
+
+struct InstructionSummary {
+    program_id: Pubkey,
+    program_name: Option<&'static str>,
+    description: String,
+}
+
+struct TxSummary {
+    signature: String,
+    signers: Vec<Pubkey>,
+    error: Option<String>,
+    fee: Option<u64>,
+    compute_units: Option<u64>,
+    instructions: Vec<InstructionSummary>,
+    /// At least one instruction invokes one of the programs of interest.
+    matches: bool,
+    is_vote: bool,
+}
+
+impl TxSummary {
+    /// Returns `None` for transactions that can not be decoded.
+    fn new(
+        EncodedTransactionWithStatusMeta {
+            transaction, meta, ..
+        }: &EncodedTransactionWithStatusMeta,
+        programs: &Programs,
+    ) -> Option<Self> {
+        let transaction = transaction.decode()?;
+        let message = &transaction.message;
+
+        // Address lookup tables add accounts after the static ones: writable first, then
+        // read-only.
+        let mut account_keys = message.static_account_keys().to_vec();
+        if let Some(loaded) = meta
+            .as_ref()
+            .and_then(|meta| Option::<&UiLoadedAddresses>::from(meta.loaded_addresses.as_ref()))
+        {
+            account_keys.extend(
+                loaded
+                    .writable
+                    .iter()
+                    .chain(&loaded.readonly)
+                    .filter_map(|address| Pubkey::from_str(address).ok()),
+            );
+        }
+
+        let instructions = message
+            .instructions()
+            .iter()
+            .map(|instruction| {
+                let program_id = account_keys
+                    .get(usize::from(instruction.program_id_index))
+                    .copied()
+                    .unwrap_or_default();
+                InstructionSummary {
+                    program_id,
+                    program_name: programs.name(&program_id),
+                    description: programs.describe(&program_id, &instruction.data),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let matches = instructions
+            .iter()
+            .any(|instruction| programs.is_of_interest(&instruction.program_id));
+        let is_vote = instructions
+            .iter()
+            .any(|instruction| instruction.program_id == vote::program::id());
+
+        let signer_count = usize::from(message.header().num_required_signatures);
+        let signers = message
+            .static_account_keys()
+            .iter()
+            .take(signer_count)
+            .copied()
+            .collect();
+
+        Some(Self {
+            signature: transaction
+                .signatures
+                .first()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            signers,
+            error: meta
+                .as_ref()
+                .and_then(|meta| meta.err.as_ref())
+                .map(ToString::to_string),
+            fee: meta.as_ref().map(|meta| meta.fee),
+            compute_units: meta
+                .as_ref()
+                .and_then(|meta| Option::from(meta.compute_units_consumed.clone())),
+            instructions,
+            matches,
+            is_vote,
+        })
+    }
+
+    fn print(&self, slot: Slot) {
+        let Self {
+            signature,
+            signers,
+            error,
+            fee,
+            compute_units,
+            instructions,
+            ..
+        } = self;
+
+        let outcome = match error {
+            None => "Success".to_owned(),
+            Some(error) => format!("Failed: {error}"),
+        };
+        let optional = |value: &Option<u64>| match value {
+            Some(value) => value.to_string(),
+            None => "n/a".to_owned(),
+        };
+        let mut text = format!(
+            "{signature}: {outcome}\n  \
+               Signers: {}\n  \
+               Fee: {} lamports / Compute units: {}",
+            signers
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+            optional(fee),
+            optional(compute_units),
+        );
+        for (
+            index,
+            InstructionSummary {
+                program_id,
+                program_name,
+                description,
+            },
+        ) in instructions.iter().enumerate()
+        {
+            let program = program_name
+                .map(str::to_owned)
+                .unwrap_or_else(|| program_id.to_string());
+            text.push_str(&format!("\n  {}. {program}: {description}", index + 1));
+        }
+
+        output::result(
+            text,
+            json!({
+                "slot": slot,
+                "signature": signature,
+                "signers": signers.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                "success": error.is_none(),
+                "error": error,
+                "fee": fee,
+                "compute_units": compute_units,
+                "instructions": instructions
+                    .iter()
+                    .map(|instruction| json!({
+                        "program_id": instruction.program_id.to_string(),
+                        "program": instruction.program_name,
+                        "instruction": instruction.description,
+                    }))
+                    .collect::<Vec<_>>(),
+            }),
+        );
+    }
+}
+
+/// Oracle instructions start with a `CommandHeader`: a `u32` version, followed by an `i32` command.
+fn describe_oracle(data: &[u8]) -> String {
+    let Some(command) = data
+        .get(4..8)
+        .map(|command| i32::from_le_bytes(command.try_into().expect("Slice is 4 bytes long")))
+    else {
+        return format!("Malformed: {} bytes", data.len());
+    };
+
+    // Values of the `OracleCommand` enum in `pyth-oracle`.
+    let name = match command {
+        0 => "InitMapping",
+        1 => "AddMapping",
+        2 => "AddProduct",
+        3 => "UpdProduct",
+        4 => "AddPrice",
+        5 => "AddPublisher",
+        6 => "DelPublisher",
+        7 => "UpdPrice",
+        8 => "AggPrice",
+        9 => "InitPrice",
+        12 => "SetMinPub",
+        13 => "UpdPriceNoFailOnError",
+        14 => "ResizePriceAccount",
+        15 => "DelPrice",
+        16 => "DelProduct",
+        17 => "UpdPermissions",
+        18 => "SetMaxLatency",
+        19 => "InitPriceFeedIndex",
+        20 => "ResizeMapping",
+        command => return format!("Command {command}"),
+    };
+    name.to_owned()
+}
+
+/// Price Store instructions start with a `u8` instruction id.
+fn describe_price_store(data: &[u8]) -> String {
+    match data.first() {
+        Some(0) => "Initialize".to_owned(),
+        Some(1) => {
+            let prices = data
+                .len()
+                .saturating_sub(size_of::<SubmitPricesArgsHeader>())
+                / size_of::<BufferedPrice>();
+            format!("SubmitPrices ({prices} prices)")
+        }
+        Some(2) => "InitializePublisher".to_owned(),
+        Some(id) => format!("Instruction {id}"),
+        None => "Empty".to_owned(),
+    }
+}
+
+/// Compute Budget instructions are Borsh encoded, with a `u8` variant index.
+fn describe_compute_budget(data: &[u8]) -> String {
+    let u32_arg = || {
+        data.get(1..5)
+            .map(|arg| u32::from_le_bytes(arg.try_into().expect("Slice is 4 bytes long")))
+    };
+    let u64_arg = || {
+        data.get(1..9)
+            .map(|arg| u64::from_le_bytes(arg.try_into().expect("Slice is 8 bytes long")))
+    };
+
+    let described = match data.first() {
+        Some(1) => u32_arg().map(|bytes| format!("RequestHeapFrame {bytes}")),
+        Some(2) => u32_arg().map(|units| format!("SetComputeUnitLimit {units}")),
+        Some(3) => u64_arg().map(|price| format!("SetComputeUnitPrice {price}")),
+        Some(4) => u32_arg().map(|bytes| format!("SetLoadedAccountsDataSizeLimit {bytes}")),
+        _ => None,
+    };
+    described.unwrap_or_else(|| format!("{} bytes", data.len()))
+}
@@ -9,11 +9,12 @@ use std::{
 use anyhow::{Context as _, Result};
 use log::warn;
 use parking_lot::Mutex;
-use solana_rpc_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::hash::Hash;
 use tokio::{select, time::sleep};
 use tokio_util::sync::CancellationToken;
 
+use crate::cluster_rpc::ClusterRpc;
+
 pub mod runner;
 
 /// A convenient way to use a [`BlockhashCache`] in your code.  [`with_blockhash`] uses a builder
@@ -36,7 +37,7 @@ impl BlockhashCache {
     }
 
     /// Repeatedly calls `self.refresh()` until we get a non-default value.
-    pub async fn init(&self, rpc_client: &RpcClient) {
+    pub async fn init<Rpc: ClusterRpc + ?Sized>(&self, rpc_client: &Rpc) {
         loop {
             let res = self.refresh(rpc_client).await;
             if let Err(err) = res {
@@ -51,11 +52,11 @@ impl BlockhashCache {
         }
     }
 
-    pub async fn refresh(&self, rpc_client: &RpcClient) -> Result<()> {
+    pub async fn refresh<Rpc: ClusterRpc + ?Sized>(&self, rpc_client: &Rpc) -> Result<()> {
         let blockhash = rpc_client
-            .get_latest_blockhash()
+            .latest_blockhash_for_tx()
             .await
-            .context("get_latest_blockhash() failed")?;
+            .context("latest_blockhash_for_tx() failed")?;
         let mut last_hash = self.last_hash.lock();
         if *last_hash == blockhash {
             // There are two probable cases why you might be seeing this warning:
@@ -71,9 +72,9 @@ impl BlockhashCache {
         Ok(())
     }
 
-    pub async fn run_refresh_loop(
+    pub async fn run_refresh_loop<Rpc: ClusterRpc + ?Sized>(
         &self,
-        rpc_client: &RpcClient,
+        rpc_client: &Rpc,
         min_loop_duration: Duration,
         exit: CancellationToken,
     ) {
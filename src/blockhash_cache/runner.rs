@@ -7,6 +7,8 @@ use solana_rpc_client::nonblocking::rpc_client::RpcClient;
 use tokio::{pin, select};
 use tokio_util::sync::CancellationToken;
 
+use crate::session;
+
 use super::BlockhashCache;
 
 /// Prepares an asynchronous operation with an access to a [`BlockhashCache`] instance, that is kept
@@ -36,6 +38,9 @@ impl<'rpc_client> RunWithBlockhashArgs<'rpc_client> {
 
     /// Runs the specified asynchronous operation with an access to a [`BlockhashCache`] instance,
     /// that is kept up to date.
+    ///
+    /// Inside a `shell` session, the session [`BlockhashCache`] is used, and it is kept running
+    /// after the operation completes.
     pub async fn run<'context, T, Op>(self, op: Op) -> T
     where
         Op: AsyncFnOnce(&BlockhashCache) -> T + 'rpc_client + 'context,
@@ -46,6 +51,18 @@ impl<'rpc_client> RunWithBlockhashArgs<'rpc_client> {
             shutdown,
         } = self;
 
+        let session_blockhash_cache = match session::get() {
+            Some(session) => session.blockhash_cache(rpc_client).await,
+            None => None,
+        };
+        if let Some(blockhash_cache) = session_blockhash_cache {
+            let op_res = op(&blockhash_cache).await;
+            if let Some(shutdown) = shutdown {
+                shutdown.cancel();
+            }
+            return op_res;
+        }
+
         let shutdown = shutdown.unwrap_or_else(CancellationToken::new);
 
         let blockhash_cache = BlockhashCache::uninitialized();
@@ -0,0 +1,14 @@
+use anyhow::{Context as _, Result};
+
+use crate::{args::bootstrap::Command, exit_code::ValidationFailed};
+
+pub mod genesis;
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Genesis(args) => {
+            args.check_are_valid().context(ValidationFailed)?;
+            genesis::run(args).await
+        }
+    }
+}
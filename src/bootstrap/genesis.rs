@@ -0,0 +1,315 @@
+//! Builds a complete primordial accounts file for a Pythnet test cluster, using the same account
+//! generators as the `primordial-accounts` commands.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    process,
+};
+
+use anchor_lang::AccountSerialize as _;
+use anyhow::{Context as _, Result, bail};
+use base64::{self, Engine as _};
+use pythnet_heisenberg::output;
+use serde::Deserialize;
+use serde_json::json;
+use solana_genesis::Base64Account;
+use solana_sdk::{pubkey::Pubkey, system_program, sysvar::rent::Rent};
+use stake_caps_parameters as stake_caps_program;
+
+use crate::{
+    args::bootstrap::genesis::GenesisArgs,
+    primordial_accounts::{feature::feature_account, loader_v3::program_accounts},
+    serde_pubkey,
+    stake_caps_parameters::default_parameters_account,
+};
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisConfig {
+    oracle: Option<ProgramConfig>,
+    price_store: Option<ProgramConfig>,
+    stake_caps_parameters: Option<StakeCapsParametersConfig>,
+    #[serde(default)]
+    programs: Vec<ProgramConfig>,
+    #[serde(default)]
+    features: Vec<FeatureConfig>,
+    #[serde(default)]
+    funded_accounts: Vec<FundedAccountConfig>,
+    #[serde(default)]
+    primordial_accounts_files: Vec<PathBuf>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ProgramConfig {
+    #[serde(deserialize_with = "serde_pubkey::from_str")]
+    program_id: Pubkey,
+    program_data: PathBuf,
+    #[serde(default, deserialize_with = "serde_pubkey::optional_from_str")]
+    upgrade_authority: Option<Pubkey>,
+    #[serde(default)]
+    last_modified_slot: u64,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct StakeCapsParametersConfig {
+    #[serde(flatten)]
+    program: ProgramConfig,
+    parameters: Option<StakeCapsParameters>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct StakeCapsParameters {
+    m: u64,
+    z: u64,
+    #[serde(deserialize_with = "serde_pubkey::from_str")]
+    authority: Pubkey,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct FeatureConfig {
+    #[serde(deserialize_with = "serde_pubkey::from_str")]
+    address: Pubkey,
+    #[serde(default)]
+    not_active: bool,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct FundedAccountConfig {
+    #[serde(deserialize_with = "serde_pubkey::from_str")]
+    address: Pubkey,
+    lamports: u64,
+}
+
+pub async fn run(
+    GenesisArgs {
+        genesis_config,
+        primordial_accounts_file,
+        ledger,
+        solana_genesis,
+        solana_genesis_args,
+    }: GenesisArgs,
+) -> Result<()> {
+    let config = read_config(&genesis_config)?;
+    let base_dir = genesis_config.parent().unwrap_or(Path::new("."));
+
+    let accounts = build_accounts(base_dir, config)?;
+
+    match &primordial_accounts_file {
+        Some(path) => {
+            let file = File::create(path).with_context(|| {
+                format!(
+                    "Failed to create primordial accounts file: {}",
+                    path.to_string_lossy()
+                )
+            })?;
+            serde_yaml::to_writer(file, &accounts).context("Constructing final YAML")?;
+            output::result(
+                format!(
+                    "Wrote {} primordial accounts into {}",
+                    accounts.len(),
+                    path.to_string_lossy()
+                ),
+                json!({
+                    "primordial_accounts_file": path,
+                    "accounts": accounts.len(),
+                }),
+            );
+        }
+        None => {
+            serde_yaml::to_writer(io::stdout().lock(), &accounts)
+                .context("Constructing final YAML")?;
+        }
+    }
+
+    if let Some(ledger) = ledger {
+        let primordial_accounts_file = primordial_accounts_file
+            .expect("`check_are_valid()` requires `--primordial-accounts-file` with `--ledger`");
+        run_solana_genesis(
+            &solana_genesis,
+            &ledger,
+            &primordial_accounts_file,
+            &solana_genesis_args,
+        )?;
+        output::result(
+            format!("Created genesis in {}", ledger.to_string_lossy()),
+            json!({ "ledger": ledger }),
+        );
+    }
+
+    Ok(())
+}
+
+pub fn read_config(path: &Path) -> Result<GenesisConfig> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read genesis config: {}", path.to_string_lossy()))?;
+    serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse genesis config: {}", path.to_string_lossy()))
+}
+
+/// Generates all the accounts described by the `config`.  Fails if the same address is produced
+/// more than once, as `solana-genesis` would silently use only one of them.
+pub fn build_accounts(
+    base_dir: &Path,
+    GenesisConfig {
+        oracle,
+        price_store,
+        stake_caps_parameters,
+        programs,
+        features,
+        funded_accounts,
+        primordial_accounts_files,
+    }: GenesisConfig,
+) -> Result<BTreeMap<String, Base64Account>> {
+    let mut accounts = BTreeMap::new();
+    let mut add = |address: String, account: Base64Account, source: &str| -> Result<()> {
+        if accounts.insert(address.clone(), account).is_some() {
+            bail!("{source}: account {address} is already defined");
+        }
+        Ok(())
+    };
+
+    let named_programs = [
+        ("oracle", oracle.as_ref()),
+        ("price_store", price_store.as_ref()),
+        (
+            "stake_caps_parameters",
+            stake_caps_parameters.as_ref().map(|config| &config.program),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(source, program)| program.map(|program| (source, program)));
+    let other_programs = programs.iter().map(|program| ("programs", program));
+    for (source, program) in named_programs.chain(other_programs) {
+        for (address, account) in build_program_accounts(base_dir, program)? {
+            add(address.to_string(), account, source)?;
+        }
+    }
+
+    if let Some(StakeCapsParametersConfig {
+        program,
+        parameters: Some(parameters),
+    }) = &stake_caps_parameters
+    {
+        let address = default_parameters_account(&program.program_id);
+        let account = stake_caps_parameters_account(&program.program_id, parameters)?;
+        add(
+            address.to_string(),
+            account,
+            "stake_caps_parameters.parameters",
+        )?;
+    }
+
+    for FeatureConfig {
+        address,
+        not_active,
+    } in features
+    {
+        add(
+            address.to_string(),
+            feature_account(not_active)?,
+            "features",
+        )?;
+    }
+
+    for FundedAccountConfig { address, lamports } in funded_accounts {
+        let account = Base64Account {
+            balance: lamports,
+            owner: system_program::id().to_string(),
+            data: String::new(),
+            executable: false,
+        };
+        add(address.to_string(), account, "funded_accounts")?;
+    }
+
+    for path in primordial_accounts_files {
+        let path = base_dir.join(path);
+        let source = path.to_string_lossy();
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read primordial accounts file: {source}"))?;
+        let file_accounts: BTreeMap<String, Base64Account> = serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse primordial accounts file: {source}"))?;
+        for (address, account) in file_accounts {
+            add(address, account, &source)?;
+        }
+    }
+
+    Ok(accounts)
+}
+
+fn build_program_accounts(
+    base_dir: &Path,
+    ProgramConfig {
+        program_id,
+        program_data,
+        upgrade_authority,
+        last_modified_slot,
+    }: &ProgramConfig,
+) -> Result<[(Pubkey, Base64Account); 2]> {
+    let program_data = base_dir.join(program_data);
+    let program_so_data = fs::read(&program_data).with_context(|| {
+        format!(
+            "Failed to read program data for {program_id}: {}",
+            program_data.to_string_lossy()
+        )
+    })?;
+
+    program_accounts(
+        *program_id,
+        *last_modified_slot,
+        &program_so_data,
+        *upgrade_authority,
+    )
+}
+
+fn stake_caps_parameters_account(
+    program_id: &Pubkey,
+    StakeCapsParameters { m, z, authority }: &StakeCapsParameters,
+) -> Result<Base64Account> {
+    let parameters = stake_caps_program::Parameters {
+        current_authority: *authority,
+        m: *m,
+        z: *z,
+    };
+
+    let mut data = vec![];
+    parameters
+        .try_serialize(&mut data)
+        .context("Encoding stake caps parameters")?;
+
+    Ok(Base64Account {
+        balance: Rent::default().minimum_balance(data.len()),
+        owner: program_id.to_string(),
+        data: base64::engine::general_purpose::STANDARD.encode(data),
+        executable: false,
+    })
+}
+
+fn run_solana_genesis(
+    solana_genesis: &Path,
+    ledger: &Path,
+    primordial_accounts_file: &Path,
+    extra_args: &[String],
+) -> Result<()> {
+    let status = process::Command::new(solana_genesis)
+        .arg("--ledger")
+        .arg(ledger)
+        .arg("--primordial-accounts-file")
+        .arg(primordial_accounts_file)
+        .args(extra_args)
+        .status()
+        .with_context(|| format!("Failed to start {}", solana_genesis.to_string_lossy()))?;
+
+    if !status.success() {
+        bail!("{} failed: {status}", solana_genesis.to_string_lossy());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use anyhow::Result;
+
+use crate::args::cluster::Command;
+
+mod commitment_lag;
+mod fees;
+mod health;
+mod validators;
+mod wait_for_epoch;
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Health(args) => health::run(args).await,
+        Command::Validators(args) => validators::run(args).await,
+        Command::WaitForEpoch(args) => wait_for_epoch::run(args).await,
+        Command::Fees(args) => fees::run(args).await,
+        Command::CommitmentLag(args) => commitment_lag::run(args).await,
+    }
+}
@@ -0,0 +1,349 @@
+//! Polls the processed, confirmed, and finalized slots, and reports how far behind each other they
+//! are.
+//!
+//! A cluster can keep producing slots while it fails to confirm or to finalize them, for example
+//! when votes do not land.  This looks healthy in the slot production stats, so stalls are reported
+//! as soon as they start, in addition to the per window lag stats.
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Context as _, Result};
+use futures::{StreamExt as _, stream::select_all, try_join};
+use pythnet_heisenberg::output;
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{clock::Slot, commitment_config::CommitmentConfig};
+use tokio::{
+    select,
+    signal::unix::{SignalKind, signal},
+    time::sleep,
+};
+use tokio_stream::wrappers::SignalStream;
+
+use crate::args::{cluster::commitment_lag::CommitmentLagArgs, json_rpc_url_args::get_rpc_client};
+
+pub async fn run(
+    CommitmentLagArgs {
+        json_rpc_url,
+        interval,
+        window,
+        max_confirmation_lag,
+        max_finalization_lag,
+        count,
+    }: CommitmentLagArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let interval: Duration = interval.into();
+    let window: Duration = window.into();
+
+    let stop_signals = select_all([
+        SignalStream::new(signal(SignalKind::interrupt()).expect("Can install a SIGINT handler")),
+        SignalStream::new(signal(SignalKind::terminate()).expect("Can install a SIGTERM handler")),
+    ]);
+    tokio::pin!(stop_signals);
+
+    let mut confirmation = Stall::new(
+        "confirmation",
+        "confirmed",
+        "processed",
+        max_confirmation_lag,
+    );
+    let mut finality = Stall::new("finality", "finalized", "confirmed", max_finalization_lag);
+
+    let mut last = Heads::take(&rpc_client).await?;
+    let mut report = Report::new(&last);
+    let mut reports = 0;
+    while count.is_none_or(|count| reports < count) {
+        select! {
+            () = sleep(interval) => (),
+            _ = stop_signals.next() => break,
+        }
+
+        let heads = Heads::take(&rpc_client).await?;
+        let since_last = heads.at.duration_since(last.at);
+
+        confirmation.update(since_last, heads.confirmed, heads.processed);
+        finality.update(since_last, heads.finalized, heads.confirmed);
+        report.add(&heads);
+
+        if heads.at.duration_since(report.start.at) >= window {
+            report.print(&mut confirmation, &mut finality);
+            report = Report::new(&heads);
+            reports += 1;
+        }
+
+        last = heads;
+    }
+
+    for stall in [&confirmation, &finality] {
+        stall.print_ongoing();
+    }
+
+    Ok(())
+}
+
+/// Latest slots the RPC node has seen at each commitment level.
+#[derive(Clone, Copy)]
+struct Heads {
+    at: Instant,
+    processed: Slot,
+    confirmed: Slot,
+    finalized: Slot,
+}
+
+impl Heads {
+    /// All three slots are requested at once, so that they describe the same moment as closely as
+    /// possible.
+    async fn take(rpc_client: &RpcClient) -> Result<Self> {
+        let at = Instant::now();
+        let (processed, confirmed, finalized) = try_join!(
+            async {
+                rpc_client
+                    .get_slot_with_commitment(CommitmentConfig::processed())
+                    .await
+                    .context("Failed to get the processed slot")
+            },
+            async {
+                rpc_client
+                    .get_slot_with_commitment(CommitmentConfig::confirmed())
+                    .await
+                    .context("Failed to get the confirmed slot")
+            },
+            async {
+                rpc_client
+                    .get_slot_with_commitment(CommitmentConfig::finalized())
+                    .await
+                    .context("Failed to get the finalized slot")
+            },
+        )?;
+
+        Ok(Self {
+            at,
+            processed,
+            confirmed,
+            finalized,
+        })
+    }
+
+    /// How far the confirmed slot is behind the processed slot.  Requests may complete out of
+    /// order, so a lagging slot can occasionally be observed ahead.
+    fn confirmation_lag(&self) -> u64 {
+        self.processed.saturating_sub(self.confirmed)
+    }
+
+    /// How far the finalized slot is behind the confirmed slot.
+    fn finalization_lag(&self) -> u64 {
+        self.confirmed.saturating_sub(self.finalized)
+    }
+}
+
+/// Tracks periods when one commitment level is too far behind another one.
+struct Stall {
+    name: &'static str,
+    lagging: &'static str,
+    leading: &'static str,
+    max_lag: u64,
+    /// When the current stall has started, and the largest lag observed during it.
+    current: Option<(Instant, u64)>,
+    /// Time spent stalled since the last report.
+    stalled_in_window: Duration,
+}
+
+impl Stall {
+    fn new(name: &'static str, lagging: &'static str, leading: &'static str, max_lag: u64) -> Self {
+        Self {
+            name,
+            lagging,
+            leading,
+            max_lag,
+            current: None,
+            stalled_in_window: Duration::ZERO,
+        }
+    }
+
+    /// Reports the start and the end of a stall, based on the latest `lagging_slot` and
+    /// `leading_slot`.  `since_last` is the time since the previous update.
+    fn update(&mut self, since_last: Duration, lagging_slot: Slot, leading_slot: Slot) {
+        let Self {
+            name,
+            lagging,
+            leading,
+            max_lag,
+            current,
+            stalled_in_window,
+        } = self;
+
+        if current.is_some() {
+            *stalled_in_window += since_last;
+        }
+
+        let lag = leading_slot.saturating_sub(lagging_slot);
+        match current {
+            None if lag > *max_lag => {
+                *current = Some((Instant::now(), lag));
+                output::result(
+                    format!(
+                        "Stall started: {lagging} slot {lagging_slot} is {lag} slots behind the \
+                         {leading} slot {leading_slot}"
+                    ),
+                    json!({
+                        "stall": *name,
+                        "state": "started",
+                        "lag": lag,
+                        "lagging_slot": lagging_slot,
+                        "leading_slot": leading_slot,
+                    }),
+                );
+            }
+            None => (),
+            Some((_started, max_observed)) if lag > *max_lag => {
+                *max_observed = (*max_observed).max(lag);
+            }
+            Some((started, max_observed)) => {
+                let duration = started.elapsed();
+                output::result(
+                    format!(
+                        "Stall ended: {lagging} slot is within {max_lag} slots of the {leading} \
+                         slot again, after {:.1} s, with a lag of up to {max_observed} slots",
+                        duration.as_secs_f64()
+                    ),
+                    json!({
+                        "stall": *name,
+                        "state": "ended",
+                        "duration_ms": duration.as_millis() as u64,
+                        "max_lag": *max_observed,
+                        "lagging_slot": lagging_slot,
+                        "leading_slot": leading_slot,
+                    }),
+                );
+                *current = None;
+            }
+        }
+    }
+
+    /// Time spent stalled since the previous call.
+    fn take_stalled_in_window(&mut self) -> Duration {
+        std::mem::take(&mut self.stalled_in_window)
+    }
+
+    /// A stall that did not end before the command stopped is still worth mentioning.
+    fn print_ongoing(&self) {
+        let Self { name, current, .. } = self;
+        if let Some((started, max_observed)) = current {
+            output::notice(format!(
+                "Stopped during a {name} stall, that lasted {:.1} s so far, with a lag of up to \
+                 {max_observed} slots",
+                started.elapsed().as_secs_f64()
+            ));
+        }
+    }
+}
+
+/// Minimum, average, and maximum of the lag samples within a window.
+#[derive(Default)]
+struct LagStats {
+    min: Option<u64>,
+    max: u64,
+    sum: u64,
+    samples: u64,
+}
+
+impl LagStats {
+    fn add(&mut self, lag: u64) {
+        self.min = Some(self.min.map_or(lag, |min| min.min(lag)));
+        self.max = self.max.max(lag);
+        self.sum += lag;
+        self.samples += 1;
+    }
+
+    fn average(&self) -> f64 {
+        if self.samples == 0 {
+            return 0.0;
+        }
+        self.sum as f64 / self.samples as f64
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "min {}, avg {:.1}, max {} slots",
+            self.min.unwrap_or(0),
+            self.average(),
+            self.max
+        )
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "min": self.min.unwrap_or(0),
+            "avg": self.average(),
+            "max": self.max,
+        })
+    }
+}
+
+struct Report {
+    start: Heads,
+    end: Option<Heads>,
+    confirmation_lag: LagStats,
+    finalization_lag: LagStats,
+}
+
+impl Report {
+    fn new(start: &Heads) -> Self {
+        Self {
+            start: *start,
+            end: None,
+            confirmation_lag: LagStats::default(),
+            finalization_lag: LagStats::default(),
+        }
+    }
+
+    fn add(&mut self, heads: &Heads) {
+        self.confirmation_lag.add(heads.confirmation_lag());
+        self.finalization_lag.add(heads.finalization_lag());
+        self.end = Some(*heads);
+    }
+
+    fn print(&self, confirmation: &mut Stall, finality: &mut Stall) {
+        let Self {
+            start,
+            end,
+            confirmation_lag,
+            finalization_lag,
+        } = self;
+        let end = end.as_ref().unwrap_or(start);
+
+        let processed = end.processed.saturating_sub(start.processed);
+        let confirmed = end.confirmed.saturating_sub(start.confirmed);
+        let finalized = end.finalized.saturating_sub(start.finalized);
+        let confirmation_stalled = confirmation.take_stalled_in_window();
+        let finality_stalled = finality.take_stalled_in_window();
+
+        output::result(
+            format!(
+                "Slots {}..{}\n  \
+                   Advanced: processed {processed}, confirmed {confirmed}, finalized {finalized}\n  \
+                   Confirmation lag: {}\n  \
+                   Finalization lag: {}\n  \
+                   Stalled: confirmation {:.1} s, finality {:.1} s",
+                start.processed,
+                end.processed,
+                confirmation_lag.describe(),
+                finalization_lag.describe(),
+                confirmation_stalled.as_secs_f64(),
+                finality_stalled.as_secs_f64(),
+            ),
+            json!({
+                "window_start_slot": start.processed,
+                "window_end_slot": end.processed,
+                "processed_slots": processed,
+                "confirmed_slots": confirmed,
+                "finalized_slots": finalized,
+                "confirmation_lag": confirmation_lag.to_json(),
+                "finalization_lag": finalization_lag.to_json(),
+                "confirmation_stalled_ms": confirmation_stalled.as_millis() as u64,
+                "finality_stalled_ms": finality_stalled.as_millis() as u64,
+            }),
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Polls the prioritization fees the RPC node has seen in recent slots, and reports their
+//! distribution for the slots that were not reported yet.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write as _},
+    path::Path,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result};
+use futures::{StreamExt as _, stream::select_all};
+use pythnet_heisenberg::{output, rpc_client_ext::RpcClientExt as _};
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::response::RpcPrioritizationFee;
+use solana_sdk::{clock::Slot, pubkey::Pubkey, transaction::MAX_TX_ACCOUNT_LOCKS};
+use tokio::{
+    select,
+    signal::unix::{SignalKind, signal},
+    time::sleep,
+};
+use tokio_stream::wrappers::SignalStream;
+
+use crate::args::{cluster::fees::FeesArgs, json_rpc_url_args::get_rpc_client};
+
+pub async fn run(
+    FeesArgs {
+        json_rpc_url,
+        account: accounts,
+        accounts_of_program: programs,
+        interval,
+        count,
+        csv,
+    }: FeesArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let interval: Duration = interval.into();
+
+    let accounts = filter_accounts(&rpc_client, accounts, &programs).await?;
+
+    let mut csv = csv.as_deref().map(CsvWriter::create).transpose()?;
+
+    let stop_signals = select_all([
+        SignalStream::new(signal(SignalKind::interrupt()).expect("Can install a SIGINT handler")),
+        SignalStream::new(signal(SignalKind::terminate()).expect("Can install a SIGTERM handler")),
+    ]);
+    tokio::pin!(stop_signals);
+
+    let mut last_reported_slot = None;
+    let mut reports = 0;
+    loop {
+        let mut fees = rpc_client
+            .get_recent_prioritization_fees(&accounts)
+            .await
+            .context("Failed to get recent prioritization fees")?;
+        fees.sort_by_key(|fee| fee.slot);
+        fees.retain(|fee| last_reported_slot.is_none_or(|last| fee.slot > last));
+
+        if let Some(report) = Report::new(&fees) {
+            report.print();
+            last_reported_slot = Some(report.last_slot);
+        }
+        if let Some(csv) = &mut csv {
+            csv.append(&fees)?;
+        }
+        reports += 1;
+
+        if count.is_some_and(|count| reports >= count) {
+            break;
+        }
+        select! {
+            () = sleep(interval) => (),
+            _ = stop_signals.next() => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Combines explicitly specified accounts with the accounts owned by the specified programs.
+/// Trims the list to the maximum the RPC node accepts.
+async fn filter_accounts(
+    rpc_client: &RpcClient,
+    mut accounts: Vec<Pubkey>,
+    programs: &[Pubkey],
+) -> Result<Vec<Pubkey>> {
+    for program_id in programs {
+        // Only the addresses are needed.
+        let program_accounts = rpc_client
+            .get_program_accounts_typed::<()>(program_id, vec![], 0)
+            .await?;
+        accounts.extend(
+            program_accounts
+                .into_iter()
+                .map(|(address, _account)| address),
+        );
+    }
+
+    if accounts.len() > MAX_TX_ACCOUNT_LOCKS {
+        output::notice(format!(
+            "Only the first {MAX_TX_ACCOUNT_LOCKS} out of {} accounts are used to filter fees",
+            accounts.len()
+        ));
+        accounts.truncate(MAX_TX_ACCOUNT_LOCKS);
+    }
+
+    Ok(accounts)
+}
+
+/// Fee distribution over a range of slots.
+struct Report {
+    first_slot: Slot,
+    last_slot: Slot,
+    slots: usize,
+    /// Slots with a non-zero minimum prioritization fee.
+    slots_with_fee: usize,
+    min: u64,
+    median: u64,
+    p75: u64,
+    p90: u64,
+    max: u64,
+}
+
+impl Report {
+    /// `fees` are expected to be sorted by slot.  Returns `None` if there are no fees.
+    fn new(fees: &[RpcPrioritizationFee]) -> Option<Self> {
+        let first_slot = fees.first()?.slot;
+        let last_slot = fees.last()?.slot;
+
+        let mut sorted = fees
+            .iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect::<Vec<_>>();
+        sorted.sort_unstable();
+
+        Some(Self {
+            first_slot,
+            last_slot,
+            slots: sorted.len(),
+            slots_with_fee: sorted.iter().filter(|fee| **fee > 0).count(),
+            min: sorted[0],
+            median: percentile_of(&sorted, 50),
+            p75: percentile_of(&sorted, 75),
+            p90: percentile_of(&sorted, 90),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+
+    fn print(&self) {
+        let Self {
+            first_slot,
+            last_slot,
+            slots,
+            slots_with_fee,
+            min,
+            median,
+            p75,
+            p90,
+            max,
+        } = self;
+
+        output::result(
+            format!(
+                "Slots {first_slot}..{last_slot}: {slots_with_fee} of {slots} with a fee\n  \
+                   Micro-lamports per CU: min {min} / p50 {median} / p75 {p75} / p90 {p90} / \
+                   max {max}"
+            ),
+            json!({
+                "first_slot": first_slot,
+                "last_slot": last_slot,
+                "slots": slots,
+                "slots_with_fee": slots_with_fee,
+                "min": min,
+                "median": median,
+                "p75": p75,
+                "p90": p90,
+                "max": max,
+            }),
+        );
+    }
+}
+
+/// Nearest rank percentile of a non-empty sorted list of values.
+fn percentile_of(sorted: &[u64], percentile: usize) -> u64 {
+    let rank = (percentile * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Writes "[slot],[prioritization fee]" lines, as new slots are observed.
+struct CsvWriter {
+    out: BufWriter<File>,
+}
+
+impl CsvWriter {
+    fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create CSV file: {}", path.to_string_lossy()))?;
+        let mut out = BufWriter::new(file);
+        writeln!(out, "# slot,prioritization_fee").context("Writing CSV header")?;
+        Ok(Self { out })
+    }
+
+    fn append(&mut self, fees: &[RpcPrioritizationFee]) -> Result<()> {
+        for RpcPrioritizationFee {
+            slot,
+            prioritization_fee,
+        } in fees
+        {
+            writeln!(self.out, "{slot},{prioritization_fee}").context("Writing CSV line")?;
+        }
+        // Flush after every poll, so that the file is usable while the command is still running.
+        self.out.flush().context("Flushing CSV file")
+    }
+}
@@ -0,0 +1,193 @@
+//! Samples cluster state at the start and at the end of a window, and reports how the cluster
+//! progressed during this window.
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Context as _, Result};
+use futures::{StreamExt as _, stream::select_all};
+use pythnet_heisenberg::output;
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{clock::Slot, commitment_config::CommitmentConfig};
+use tokio::{
+    select,
+    signal::unix::{SignalKind, signal},
+    time::sleep,
+};
+use tokio_stream::wrappers::SignalStream;
+
+use crate::args::{cluster::health::HealthArgs, json_rpc_url_args::get_rpc_client};
+
+pub async fn run(
+    HealthArgs {
+        json_rpc_url,
+        window,
+        count,
+    }: HealthArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let window: Duration = window.into();
+
+    let stop_signals = select_all([
+        SignalStream::new(signal(SignalKind::interrupt()).expect("Can install a SIGINT handler")),
+        SignalStream::new(signal(SignalKind::terminate()).expect("Can install a SIGTERM handler")),
+    ]);
+    tokio::pin!(stop_signals);
+
+    let mut start = Sample::take(&rpc_client).await?;
+    let mut reports = 0;
+    while count.is_none_or(|count| reports < count) {
+        select! {
+            () = sleep(window) => (),
+            _ = stop_signals.next() => break,
+        }
+
+        let end = Sample::take(&rpc_client).await?;
+        let report = Report::new(&rpc_client, &start, &end).await?;
+        report.print();
+
+        start = end;
+        reports += 1;
+    }
+
+    Ok(())
+}
+
+/// Cluster state at a specific point in time.
+struct Sample {
+    at: Instant,
+    /// Latest slot this RPC node has processed.
+    processed_slot: Slot,
+    /// Skipped slots are only known for confirmed slots.
+    confirmed_slot: Slot,
+    transaction_count: u64,
+}
+
+impl Sample {
+    async fn take(rpc_client: &RpcClient) -> Result<Self> {
+        let at = Instant::now();
+        let processed_slot = rpc_client
+            .get_slot_with_commitment(CommitmentConfig::processed())
+            .await
+            .context("Failed to get the processed slot")?;
+        let confirmed_slot = rpc_client
+            .get_slot_with_commitment(CommitmentConfig::confirmed())
+            .await
+            .context("Failed to get the confirmed slot")?;
+        let transaction_count = rpc_client
+            .get_transaction_count_with_commitment(CommitmentConfig::processed())
+            .await
+            .context("Failed to get the transaction count")?;
+
+        Ok(Self {
+            at,
+            processed_slot,
+            confirmed_slot,
+            transaction_count,
+        })
+    }
+}
+
+struct Report {
+    window_start_slot: Slot,
+    window_end_slot: Slot,
+    slots_per_second: f64,
+    skipped_slots: u64,
+    skipped_slots_percent: f64,
+    average_block_time: Option<Duration>,
+    transactions_per_second: f64,
+    /// How far behind the highest slot seen by this RPC node, is the slot it has processed.
+    rpc_slot_lag: u64,
+}
+
+impl Report {
+    async fn new(rpc_client: &RpcClient, start: &Sample, end: &Sample) -> Result<Self> {
+        let elapsed = end.at.duration_since(start.at).as_secs_f64();
+
+        let slots_per_second =
+            end.processed_slot.saturating_sub(start.processed_slot) as f64 / elapsed;
+
+        let (skipped_slots, skipped_slots_percent, average_block_time) =
+            if end.confirmed_slot > start.confirmed_slot {
+                let first = start.confirmed_slot + 1;
+                let last = end.confirmed_slot;
+                let blocks = rpc_client
+                    .get_blocks_with_commitment(first, Some(last), CommitmentConfig::confirmed())
+                    .await
+                    .with_context(|| format!("Failed to get blocks in [{first}, {last}]"))?;
+                let total_slots = last - first + 1;
+                let produced = u64::try_from(blocks.len()).expect("Block count fits into a u64");
+                let skipped = total_slots.saturating_sub(produced);
+                let average_block_time =
+                    (produced > 0).then(|| Duration::from_secs_f64(elapsed / produced as f64));
+                (
+                    skipped,
+                    skipped as f64 * 100.0 / total_slots as f64,
+                    average_block_time,
+                )
+            } else {
+                (0, 0.0, None)
+            };
+
+        let transactions_per_second = end
+            .transaction_count
+            .saturating_sub(start.transaction_count) as f64
+            / elapsed;
+
+        let max_shred_insert_slot = rpc_client
+            .get_max_shred_insert_slot()
+            .await
+            .context("Failed to get the max shred insert slot")?;
+        let rpc_slot_lag = max_shred_insert_slot.saturating_sub(end.processed_slot);
+
+        Ok(Self {
+            window_start_slot: start.processed_slot,
+            window_end_slot: end.processed_slot,
+            slots_per_second,
+            skipped_slots,
+            skipped_slots_percent,
+            average_block_time,
+            transactions_per_second,
+            rpc_slot_lag,
+        })
+    }
+
+    fn print(&self) {
+        let Self {
+            window_start_slot,
+            window_end_slot,
+            slots_per_second,
+            skipped_slots,
+            skipped_slots_percent,
+            average_block_time,
+            transactions_per_second,
+            rpc_slot_lag,
+        } = self;
+
+        let average_block_time_text = match average_block_time {
+            Some(block_time) => format!("{} ms", block_time.as_millis()),
+            None => "n/a".to_owned(),
+        };
+
+        output::result(
+            format!(
+                "Slots {window_start_slot}..{window_end_slot}\n  \
+                   Slot rate: {slots_per_second:.2} slots/s\n  \
+                   Skipped slots: {skipped_slots} ({skipped_slots_percent:.1}%)\n  \
+                   Average block time: {average_block_time_text}\n  \
+                   TPS: {transactions_per_second:.1}\n  \
+                   RPC slot lag: {rpc_slot_lag}"
+            ),
+            json!({
+                "window_start_slot": window_start_slot,
+                "window_end_slot": window_end_slot,
+                "slots_per_second": slots_per_second,
+                "skipped_slots": skipped_slots,
+                "skipped_slots_percent": skipped_slots_percent,
+                "average_block_time_ms": average_block_time.map(|time| time.as_millis() as u64),
+                "transactions_per_second": transactions_per_second,
+                "rpc_slot_lag": rpc_slot_lag,
+            }),
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! Merges the `getVoteAccounts` and the `getClusterNodes` responses into a single view of the
+//! validator set.
+
+use std::{collections::HashMap, fmt::Write as _, net::SocketAddr};
+
+use anyhow::{Context as _, Result};
+use pythnet_heisenberg::output;
+use serde_json::json;
+use solana_rpc_client_api::response::{RpcContactInfo, RpcVoteAccountInfo};
+use solana_sdk::native_token::lamports_to_sol;
+
+use crate::args::{cluster::validators::ValidatorsArgs, json_rpc_url_args::get_rpc_client};
+
+pub async fn run(
+    ValidatorsArgs {
+        json_rpc_url,
+        delinquent_only,
+    }: ValidatorsArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let vote_accounts = rpc_client
+        .get_vote_accounts()
+        .await
+        .context("Failed to get vote accounts")?;
+    let nodes = rpc_client
+        .get_cluster_nodes()
+        .await
+        .context("Failed to get cluster nodes")?;
+
+    let total_stake: u64 = vote_accounts
+        .current
+        .iter()
+        .chain(&vote_accounts.delinquent)
+        .map(|vote_account| vote_account.activated_stake)
+        .sum();
+
+    let mut nodes: HashMap<String, RpcContactInfo> = nodes
+        .into_iter()
+        .map(|node| (node.pubkey.clone(), node))
+        .collect();
+
+    let mut validators = vote_accounts
+        .current
+        .into_iter()
+        .map(|vote_account| (vote_account, false))
+        .chain(
+            vote_accounts
+                .delinquent
+                .into_iter()
+                .map(|vote_account| (vote_account, true)),
+        )
+        .map(|(vote_account, delinquent)| {
+            let node = nodes.remove(&vote_account.node_pubkey);
+            Validator::new(
+                vote_account.node_pubkey.clone(),
+                Some(vote_account),
+                node,
+                delinquent,
+            )
+        })
+        .collect::<Vec<_>>();
+    // Whatever is left in gossip does not vote.
+    validators.extend(
+        nodes
+            .into_values()
+            .map(|node| Validator::new(node.pubkey.clone(), None, Some(node), false)),
+    );
+
+    validators.retain(|validator| !delinquent_only || validator.delinquent);
+    validators.sort_by(|a, b| {
+        b.stake
+            .cmp(&a.stake)
+            .then_with(|| a.identity.cmp(&b.identity))
+    });
+
+    let text = format_table(&validators, total_stake);
+    let json = json!({
+        "total_stake": total_stake,
+        "validators": validators
+            .iter()
+            .map(|validator| validator.to_json(total_stake))
+            .collect::<Vec<_>>(),
+    });
+    output::result(text, json);
+
+    Ok(())
+}
+
+struct Validator {
+    identity: String,
+    vote_account: Option<String>,
+    /// `None` for nodes without a vote account.
+    stake: Option<u64>,
+    commission: Option<u8>,
+    last_vote: Option<u64>,
+    gossip: Option<SocketAddr>,
+    tpu: Option<SocketAddr>,
+    tpu_quic: Option<SocketAddr>,
+    rpc: Option<SocketAddr>,
+    version: Option<String>,
+    /// `false` for nodes that are not in gossip.
+    in_gossip: bool,
+    delinquent: bool,
+}
+
+impl Validator {
+    fn new(
+        identity: String,
+        vote_account: Option<RpcVoteAccountInfo>,
+        node: Option<RpcContactInfo>,
+        delinquent: bool,
+    ) -> Self {
+        Self {
+            identity,
+            vote_account: vote_account
+                .as_ref()
+                .map(|vote_account| vote_account.vote_pubkey.clone()),
+            stake: vote_account
+                .as_ref()
+                .map(|vote_account| vote_account.activated_stake),
+            commission: vote_account
+                .as_ref()
+                .map(|vote_account| vote_account.commission),
+            last_vote: vote_account
+                .as_ref()
+                .map(|vote_account| vote_account.last_vote),
+            gossip: node.as_ref().and_then(|node| node.gossip),
+            tpu: node.as_ref().and_then(|node| node.tpu),
+            tpu_quic: node.as_ref().and_then(|node| node.tpu_quic),
+            rpc: node.as_ref().and_then(|node| node.rpc),
+            in_gossip: node.is_some(),
+            version: node.and_then(|node| node.version),
+            delinquent,
+        }
+    }
+
+    fn stake_percent(&self, total_stake: u64) -> Option<f64> {
+        self.stake
+            .filter(|_| total_stake != 0)
+            .map(|stake| stake as f64 * 100.0 / total_stake as f64)
+    }
+
+    fn to_json(&self, total_stake: u64) -> serde_json::Value {
+        let Self {
+            identity,
+            vote_account,
+            stake,
+            commission,
+            last_vote,
+            gossip,
+            tpu,
+            tpu_quic,
+            rpc,
+            version,
+            in_gossip,
+            delinquent,
+        } = self;
+
+        json!({
+            "identity": identity,
+            "vote_account": vote_account,
+            "stake": stake,
+            "stake_percent": self.stake_percent(total_stake),
+            "commission": commission,
+            "last_vote": last_vote,
+            "gossip": gossip.map(|addr| addr.to_string()),
+            "tpu": tpu.map(|addr| addr.to_string()),
+            "tpu_quic": tpu_quic.map(|addr| addr.to_string()),
+            "rpc": rpc.map(|addr| addr.to_string()),
+            "version": version,
+            "in_gossip": in_gossip,
+            "delinquent": delinquent,
+        })
+    }
+}
+
+fn format_table(validators: &[Validator], total_stake: u64) -> String {
+    const HEADER: [&str; 9] = [
+        "Identity",
+        "Vote account",
+        "Stake (SOL)",
+        "Stake %",
+        "Commission",
+        "Gossip",
+        "TPU",
+        "Version",
+        "Status",
+    ];
+
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_owned());
+    let rows = validators
+        .iter()
+        .map(|validator| {
+            let status = if validator.delinquent {
+                "delinquent"
+            } else if !validator.in_gossip {
+                "not in gossip"
+            } else if validator.vote_account.is_none() {
+                "non-voting"
+            } else {
+                "ok"
+            };
+            [
+                validator.identity.clone(),
+                or_dash(validator.vote_account.clone()),
+                or_dash(
+                    validator
+                        .stake
+                        .map(|stake| format!("{:.2}", lamports_to_sol(stake))),
+                ),
+                or_dash(
+                    validator
+                        .stake_percent(total_stake)
+                        .map(|percent| format!("{percent:.2}")),
+                ),
+                or_dash(
+                    validator
+                        .commission
+                        .map(|commission| format!("{commission}%")),
+                ),
+                or_dash(validator.gossip.map(|addr| addr.to_string())),
+                or_dash(validator.tpu.map(|addr| addr.to_string())),
+                or_dash(validator.version.clone()),
+                status.to_owned(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let mut widths = HEADER.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut text = String::new();
+    let mut push_row = |cells: &mut dyn Iterator<Item = &str>| {
+        let line = cells
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        writeln!(text, "{}", line.trim_end()).expect("Writing into a String never fails");
+    };
+    push_row(&mut HEADER.into_iter());
+    for row in &rows {
+        push_row(&mut row.iter().map(String::as_str));
+    }
+
+    let delinquent = validators
+        .iter()
+        .filter(|validator| validator.delinquent)
+        .count();
+    write!(
+        text,
+        "Validators: {}, delinquent: {delinquent}, total stake: {:.2} SOL",
+        validators.len(),
+        lamports_to_sol(total_stake),
+    )
+    .expect("Writing into a String never fails");
+
+    text
+}
@@ -0,0 +1,107 @@
+//! Blocks until the cluster reaches a specific epoch or slot.
+
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use indicatif::{ProgressBar, ProgressStyle};
+use pythnet_heisenberg::{
+    output,
+    rpc_client_ext::{RpcClientExt as _, WaitTarget},
+};
+use serde_json::json;
+use solana_sdk::epoch_info::EpochInfo;
+use tokio::time::timeout as with_timeout;
+
+use crate::args::{cluster::wait_for_epoch::WaitForEpochArgs, json_rpc_url_args::get_rpc_client};
+
+pub async fn run(
+    WaitForEpochArgs {
+        json_rpc_url,
+        epoch,
+        slot,
+        poll_interval,
+        timeout,
+    }: WaitForEpochArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let target = match (epoch, slot) {
+        (Some(epoch), _) => WaitTarget::Epoch(epoch),
+        (None, Some(slot)) => WaitTarget::Slot(slot),
+        (None, None) => unreachable!("Clap requires either `--epoch` or `--slot`"),
+    };
+
+    let progress_bar = ProgressBar::new_spinner();
+    progress_bar.set_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.green} [{elapsed}] {wide_msg}")
+            .expect("ProgressStyle::template direct input to be correct"),
+    );
+    progress_bar.enable_steady_tick(Duration::from_millis(100));
+
+    let wait = rpc_client.wait_for(target, poll_interval.into(), |epoch_info| {
+        progress_bar.set_message(progress_message(target, epoch_info));
+    });
+    let res = match timeout {
+        Some(timeout) => match with_timeout(timeout.into(), wait).await {
+            Ok(res) => res,
+            Err(_) => {
+                progress_bar.abandon();
+                bail!("{} was not reached within {timeout}", describe(target));
+            }
+        },
+        None => wait.await,
+    };
+    progress_bar.finish_and_clear();
+    let epoch_info = res?;
+
+    output::result(
+        format!(
+            "Reached {}: epoch {}, slot {}",
+            describe(target),
+            epoch_info.epoch,
+            epoch_info.absolute_slot,
+        ),
+        json!({
+            "epoch": epoch_info.epoch,
+            "slot": epoch_info.absolute_slot,
+            "slot_index": epoch_info.slot_index,
+            "slots_in_epoch": epoch_info.slots_in_epoch,
+        }),
+    );
+
+    Ok(())
+}
+
+fn describe(target: WaitTarget) -> String {
+    match target {
+        WaitTarget::Epoch(epoch) => format!("epoch {epoch}"),
+        WaitTarget::Slot(slot) => format!("slot {slot}"),
+    }
+}
+
+fn progress_message(target: WaitTarget, epoch_info: &EpochInfo) -> String {
+    let EpochInfo {
+        epoch,
+        slot_index,
+        slots_in_epoch,
+        absolute_slot,
+        ..
+    } = *epoch_info;
+
+    // Assumes all the remaining epochs are of the same length, which holds after the warmup.
+    let slots_left = match target {
+        WaitTarget::Epoch(target_epoch) => target_epoch
+            .saturating_sub(epoch)
+            .saturating_sub(1)
+            .saturating_mul(slots_in_epoch)
+            .saturating_add(slots_in_epoch.saturating_sub(slot_index)),
+        WaitTarget::Slot(target_slot) => target_slot.saturating_sub(absolute_slot),
+    };
+
+    format!(
+        "Epoch {epoch}, slot {absolute_slot} ({slot_index}/{slots_in_epoch}).  \
+         Waiting for {}, ~{slots_left} slots left",
+        describe(target),
+    )
+}
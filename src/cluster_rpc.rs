@@ -0,0 +1,207 @@
+//! The RPC operations [`BlockhashCache`](crate::blockhash_cache::BlockhashCache),
+//! [`NodeAddressService`](crate::node_address_service::NodeAddressService), and
+//! [`tx_sheppard`](crate::tx_sheppard) depend on.
+//!
+//! [`RpcClient`] is the implementation used against a real cluster.  Tests can use a mock instead,
+//! that controls when transactions land and what errors the node reports, to exercise the retry and
+//! confirmation logic deterministically.
+
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use solana_program::pubkey::Pubkey;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::{
+    client_error::Result as ClientResult,
+    config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+    response::{RpcContactInfo, RpcSimulateTransactionResult},
+};
+use solana_sdk::{
+    clock::Slot,
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    epoch_info::EpochInfo,
+    hash::Hash,
+    signature::Signature,
+    transaction::Transaction,
+};
+use solana_transaction_status::TransactionStatus;
+
+#[cfg(test)]
+pub mod mock;
+
+/// Method names and semantics match the [`RpcClient`] methods with the same names.
+#[async_trait]
+pub trait ClusterRpc: Send + Sync {
+    /// Commitment level used when a method does not specify one.
+    fn commitment(&self) -> CommitmentConfig;
+
+    async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)>;
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature>;
+
+    async fn simulate_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<RpcSimulateTransactionResult>;
+
+    /// Statuses of the `signatures`, in the same order.  `None` for transactions the node does not
+    /// know about.
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> ClientResult<Vec<Option<TransactionStatus>>>;
+
+    async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<Slot>;
+
+    async fn get_epoch_info(&self) -> ClientResult<EpochInfo>;
+
+    async fn get_slot_leaders(&self, start_slot: Slot, limit: u64) -> ClientResult<Vec<Pubkey>>;
+
+    async fn get_cluster_nodes(&self) -> ClientResult<Vec<RpcContactInfo>>;
+
+    /// Latest blockhash to build a transaction with.
+    ///
+    /// Uses the client commitment, but never goes below `confirmed`.  Blockhashes of processed
+    /// blocks might end up on a fork that is dropped, causing "Blockhash not found" errors.
+    async fn latest_blockhash_for_tx(&self) -> Result<Hash> {
+        let commitment = match self.commitment().commitment {
+            CommitmentLevel::Processed => CommitmentConfig::confirmed(),
+            _ => self.commitment(),
+        };
+
+        let (latest_blockhash, _) = self
+            .get_latest_blockhash_with_commitment(commitment)
+            .await
+            .context("Getting a blockhash from the cluster")?;
+
+        Ok(latest_blockhash)
+    }
+}
+
+#[async_trait]
+impl ClusterRpc for RpcClient {
+    fn commitment(&self) -> CommitmentConfig {
+        RpcClient::commitment(self)
+    }
+
+    async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        RpcClient::get_latest_blockhash_with_commitment(self, commitment).await
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        RpcClient::send_transaction_with_config(self, transaction, config).await
+    }
+
+    async fn simulate_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<RpcSimulateTransactionResult> {
+        Ok(
+            RpcClient::simulate_transaction_with_config(self, transaction, config)
+                .await?
+                .value,
+        )
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> ClientResult<Vec<Option<TransactionStatus>>> {
+        Ok(RpcClient::get_signature_statuses(self, signatures)
+            .await?
+            .value)
+    }
+
+    async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<Slot> {
+        RpcClient::get_slot_with_commitment(self, commitment).await
+    }
+
+    async fn get_epoch_info(&self) -> ClientResult<EpochInfo> {
+        RpcClient::get_epoch_info(self).await
+    }
+
+    async fn get_slot_leaders(&self, start_slot: Slot, limit: u64) -> ClientResult<Vec<Pubkey>> {
+        RpcClient::get_slot_leaders(self, start_slot, limit).await
+    }
+
+    async fn get_cluster_nodes(&self) -> ClientResult<Vec<RpcContactInfo>> {
+        RpcClient::get_cluster_nodes(self).await
+    }
+}
+
+/// Commands usually share an `Arc<RpcClient>`.
+#[async_trait]
+impl<Rpc: ClusterRpc + ?Sized> ClusterRpc for Arc<Rpc> {
+    fn commitment(&self) -> CommitmentConfig {
+        (**self).commitment()
+    }
+
+    async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        (**self)
+            .get_latest_blockhash_with_commitment(commitment)
+            .await
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        (**self)
+            .send_transaction_with_config(transaction, config)
+            .await
+    }
+
+    async fn simulate_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<RpcSimulateTransactionResult> {
+        (**self)
+            .simulate_transaction_with_config(transaction, config)
+            .await
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> ClientResult<Vec<Option<TransactionStatus>>> {
+        (**self).get_signature_statuses(signatures).await
+    }
+
+    async fn get_slot_with_commitment(&self, commitment: CommitmentConfig) -> ClientResult<Slot> {
+        (**self).get_slot_with_commitment(commitment).await
+    }
+
+    async fn get_epoch_info(&self) -> ClientResult<EpochInfo> {
+        (**self).get_epoch_info().await
+    }
+
+    async fn get_slot_leaders(&self, start_slot: Slot, limit: u64) -> ClientResult<Vec<Pubkey>> {
+        (**self).get_slot_leaders(start_slot, limit).await
+    }
+
+    async fn get_cluster_nodes(&self) -> ClientResult<Vec<RpcContactInfo>> {
+        (**self).get_cluster_nodes().await
+    }
+}
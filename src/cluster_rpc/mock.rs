@@ -0,0 +1,307 @@
+//! A simulated cluster, for testing the code that sends transactions.
+//!
+//! Slots advance every [`SLOT_DURATION`] of the tokio clock, so tests that run with a paused clock
+//! are deterministic, and do not actually wait.  Every slot has a new blockhash.
+//!
+//! Transactions land one slot after they are sent, unless a [`SendBehavior`] queued with
+//! [`MockRpc::on_next_sends()`] says otherwise.  A landed transaction is confirmed in the next
+//! slot, and finalized [`FINALIZATION_SLOTS`] slots after it landed.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use solana_program::pubkey::Pubkey;
+use solana_rpc_client_api::{
+    client_error::{ErrorKind as ClientErrorKind, Result as ClientResult},
+    config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig},
+    custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+    request::{RpcError, RpcResponseErrorData},
+    response::{RpcContactInfo, RpcSimulateTransactionResult},
+};
+use solana_sdk::{
+    clock::{MAX_PROCESSING_AGE, Slot},
+    commitment_config::CommitmentConfig,
+    epoch_info::EpochInfo,
+    hash::{Hash, hashv},
+    signature::Signature,
+    transaction::{Transaction, TransactionError},
+};
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use tokio::time::Instant;
+
+use super::ClusterRpc;
+
+pub const SLOT_DURATION: Duration = Duration::from_millis(400);
+
+/// Number of slots after which a landed transaction is considered finalized.
+pub const FINALIZATION_SLOTS: u64 = 32;
+
+const SLOTS_IN_EPOCH: u64 = 432_000;
+
+/// What happens to a transaction sent to the [`MockRpc`].
+#[derive(Debug, Clone)]
+pub enum SendBehavior {
+    /// Accepted, and executed successfully in the next slot.
+    Land,
+    /// Accepted, but never makes it into a block.
+    Drop,
+    /// Accepted, and executed with this error in the next slot.
+    FailExecution(TransactionError),
+    /// Rejected by the node.
+    Reject(SendError),
+}
+
+/// Errors the node can reject a transaction with.
+#[derive(Debug, Clone)]
+pub enum SendError {
+    BlockhashNotFound,
+    NodeUnhealthy,
+    /// A preflight simulation failed with this error.
+    Preflight(TransactionError),
+    /// Anything else, like a transport error.
+    Other(String),
+}
+
+impl SendError {
+    fn into_client_error(self) -> ClientErrorKind {
+        match self {
+            SendError::BlockhashNotFound => {
+                ClientErrorKind::TransactionError(TransactionError::BlockhashNotFound)
+            }
+            SendError::NodeUnhealthy => ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                code: JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+                message: "Node is unhealthy".to_owned(),
+                data: RpcResponseErrorData::NodeUnhealthy {
+                    num_slots_behind: None,
+                },
+            }),
+            SendError::Preflight(error) => ClientErrorKind::TransactionError(error),
+            SendError::Other(message) => ClientErrorKind::Custom(message),
+        }
+    }
+}
+
+/// A transaction that reached the [`MockRpc`], whether it was accepted or not.
+#[derive(Debug, Clone)]
+pub struct SentTx {
+    pub signature: Signature,
+    pub blockhash: Hash,
+    pub slot: Slot,
+    pub at: Instant,
+    pub accepted: bool,
+}
+
+struct Landed {
+    slot: Slot,
+    err: Option<TransactionError>,
+}
+
+struct State {
+    /// Behavior of the upcoming sends.  Sends beyond the queue [`SendBehavior::Land`].
+    next_sends: VecDeque<SendBehavior>,
+    sent: Vec<SentTx>,
+    landed: HashMap<Signature, Landed>,
+}
+
+pub struct MockRpc {
+    start: Instant,
+    commitment: CommitmentConfig,
+    state: Mutex<State>,
+}
+
+impl MockRpc {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            commitment: CommitmentConfig::confirmed(),
+            state: Mutex::new(State {
+                next_sends: VecDeque::new(),
+                sent: vec![],
+                landed: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Queues behaviors for the next sends, in the order the sends arrive.
+    pub fn on_next_sends(&self, behaviors: impl IntoIterator<Item = SendBehavior>) {
+        self.lock().next_sends.extend(behaviors);
+    }
+
+    /// All the sends so far, in the order they arrived.
+    pub fn sent(&self) -> Vec<SentTx> {
+        self.lock().sent.clone()
+    }
+
+    pub fn current_slot(&self) -> Slot {
+        let elapsed = self.start.elapsed();
+        (elapsed.as_millis() / SLOT_DURATION.as_millis()) as Slot
+    }
+
+    pub fn blockhash_for(slot: Slot) -> Hash {
+        hashv(&[b"mock blockhash", &slot.to_le_bytes()])
+    }
+
+    /// Blockhashes older than [`MAX_PROCESSING_AGE`] slots are rejected, same as on a real node.
+    fn is_blockhash_valid(&self, blockhash: &Hash) -> bool {
+        let current_slot = self.current_slot();
+        (current_slot.saturating_sub(MAX_PROCESSING_AGE as Slot)..=current_slot)
+            .any(|slot| Self::blockhash_for(slot) == *blockhash)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("Mock RPC lock is not poisoned")
+    }
+}
+
+impl Default for MockRpc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ClusterRpc for MockRpc {
+    fn commitment(&self) -> CommitmentConfig {
+        self.commitment
+    }
+
+    async fn get_latest_blockhash_with_commitment(
+        &self,
+        _commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        let slot = self.current_slot();
+        Ok((Self::blockhash_for(slot), slot + MAX_PROCESSING_AGE as Slot))
+    }
+
+    async fn send_transaction_with_config(
+        &self,
+        transaction: &Transaction,
+        _config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        let signature = transaction.signatures[0];
+        let blockhash = transaction.message.recent_blockhash;
+        let slot = self.current_slot();
+        let blockhash_valid = self.is_blockhash_valid(&blockhash);
+
+        let mut state = self.lock();
+        let behavior = state.next_sends.pop_front().unwrap_or(SendBehavior::Land);
+        let behavior = match behavior {
+            SendBehavior::Land | SendBehavior::FailExecution(_) if !blockhash_valid => {
+                SendBehavior::Reject(SendError::BlockhashNotFound)
+            }
+            behavior => behavior,
+        };
+
+        state.sent.push(SentTx {
+            signature,
+            blockhash,
+            slot,
+            at: Instant::now(),
+            accepted: !matches!(behavior, SendBehavior::Reject(_)),
+        });
+
+        match behavior {
+            SendBehavior::Land => {
+                state.landed.entry(signature).or_insert(Landed {
+                    slot: slot + 1,
+                    err: None,
+                });
+            }
+            SendBehavior::Drop => (),
+            SendBehavior::FailExecution(err) => {
+                state.landed.entry(signature).or_insert(Landed {
+                    slot: slot + 1,
+                    err: Some(err),
+                });
+            }
+            SendBehavior::Reject(error) => return Err(error.into_client_error().into()),
+        }
+
+        Ok(signature)
+    }
+
+    /// Every transaction simulates successfully.  Simulation does not affect the sent transactions.
+    async fn simulate_transaction_with_config(
+        &self,
+        _transaction: &Transaction,
+        _config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<RpcSimulateTransactionResult> {
+        Ok(RpcSimulateTransactionResult {
+            err: None,
+            logs: Some(vec![]),
+            accounts: None,
+            units_consumed: Some(0),
+            return_data: None,
+            inner_instructions: None,
+        })
+    }
+
+    async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> ClientResult<Vec<Option<TransactionStatus>>> {
+        let current_slot = self.current_slot();
+        let state = self.lock();
+
+        let statuses = signatures
+            .iter()
+            .map(|signature| {
+                let Landed { slot, err } = state.landed.get(signature)?;
+                if *slot > current_slot {
+                    return None;
+                }
+
+                let confirmations = current_slot - slot;
+                let (confirmations, confirmation_status) = match confirmations {
+                    0 => (Some(0), TransactionConfirmationStatus::Processed),
+                    confirmations if confirmations < FINALIZATION_SLOTS => (
+                        Some(confirmations as usize),
+                        TransactionConfirmationStatus::Confirmed,
+                    ),
+                    _ => (None, TransactionConfirmationStatus::Finalized),
+                };
+                Some(TransactionStatus {
+                    slot: *slot,
+                    confirmations,
+                    status: match err {
+                        None => Ok(()),
+                        Some(err) => Err(err.clone()),
+                    },
+                    err: err.clone(),
+                    confirmation_status: Some(confirmation_status),
+                })
+            })
+            .collect();
+
+        Ok(statuses)
+    }
+
+    async fn get_slot_with_commitment(&self, _commitment: CommitmentConfig) -> ClientResult<Slot> {
+        Ok(self.current_slot())
+    }
+
+    async fn get_epoch_info(&self) -> ClientResult<EpochInfo> {
+        let slot = self.current_slot();
+        Ok(EpochInfo {
+            epoch: slot / SLOTS_IN_EPOCH,
+            slot_index: slot % SLOTS_IN_EPOCH,
+            slots_in_epoch: SLOTS_IN_EPOCH,
+            absolute_slot: slot,
+            block_height: slot,
+            transaction_count: None,
+        })
+    }
+
+    /// A single leader, that does not advertise a TPU address.
+    async fn get_slot_leaders(&self, _start_slot: Slot, limit: u64) -> ClientResult<Vec<Pubkey>> {
+        Ok(vec![Pubkey::default(); limit as usize])
+    }
+
+    async fn get_cluster_nodes(&self) -> ClientResult<Vec<RpcContactInfo>> {
+        Ok(vec![])
+    }
+}
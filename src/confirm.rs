@@ -0,0 +1,38 @@
+//! Interactive confirmation for commands that make changes that are hard to revert, like changing
+//! authorities.
+
+use std::{
+    fmt::Display,
+    io::{self, BufRead as _, Write as _},
+};
+
+use anyhow::{Context as _, Result, bail};
+
+/// Shows a `summary` of the operation that is about to be performed on stderr, and asks the user
+/// to confirm it.  Returns an error if the user does not confirm.
+///
+/// When `yes` is set, the summary is still shown, but no question is asked.  It is expected to come
+/// from a `--yes` argument.
+pub fn confirm_or_abort(summary: impl Display, yes: bool) -> Result<()> {
+    eprintln!("{summary}");
+
+    if !yes && !confirm("Continue?")? {
+        bail!("Aborted");
+    }
+
+    Ok(())
+}
+
+/// Asks the user a yes/no question on the terminal.  Anything other than "y" or "yes" is a "no".
+fn confirm(question: &str) -> Result<bool> {
+    eprint!("{question} [y/N] ");
+    io::stderr().flush().context("Flushing stderr")?;
+
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .context("Reading confirmation from stdin")?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
@@ -0,0 +1,11 @@
+use anyhow::Result;
+
+use crate::args::dev::Command;
+
+mod up;
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Up(args) => up::run(args).await,
+    }
+}
@@ -0,0 +1,309 @@
+//! Runs `solana-test-validator` as a child process, and drives the program initialization against
+//! it, using the same code paths as the individual commands.
+
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs, iter,
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result, bail};
+use base64::{self, Engine as _};
+use clap::CommandFactory as _;
+use futures::{StreamExt as _, stream::select_all};
+use pythnet_heisenberg::{keypair_ext::read_keypair_file, output};
+use serde_json::json;
+use solana_genesis::Base64Account;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signer::Signer as _};
+use tokio::{
+    process::{Child, Command},
+    select,
+    signal::unix::{SignalKind, signal},
+    time::{Instant, sleep},
+};
+use tokio_stream::wrappers::SignalStream;
+
+use crate::{
+    args::{self, Args, dev::up::UpArgs},
+    bootstrap::genesis::{build_accounts, read_config},
+};
+
+/// How often to check if the validator is healthy, during the startup.
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub async fn run(
+    UpArgs {
+        validator,
+        ledger,
+        rpc_port,
+        genesis_config,
+        payer_keypair,
+        oracle_program_id,
+        price_store_program_id,
+        startup_timeout,
+        exit_after_init,
+        validator_args,
+    }: UpArgs,
+) -> Result<()> {
+    let payer = read_keypair_file(&payer_keypair)?;
+    let rpc_url = format!("http://127.0.0.1:{rpc_port}");
+
+    let account_dir = match &genesis_config {
+        Some(genesis_config) => Some(write_account_dir(genesis_config, &ledger)?),
+        None => None,
+    };
+
+    let mut command = Command::new(&validator);
+    command
+        .arg("--ledger")
+        .arg(&ledger)
+        .arg("--reset")
+        .arg("--quiet")
+        .arg("--rpc-port")
+        .arg(rpc_port.to_string())
+        .arg("--mint")
+        .arg(payer.pubkey().to_string());
+    if let Some(account_dir) = &account_dir {
+        command.arg("--account-dir").arg(account_dir);
+    }
+    command
+        .args(&validator_args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .kill_on_drop(true);
+
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("Failed to start {}", validator.to_string_lossy()))?;
+    output::notice(format!(
+        "Started {} with the ledger in {}",
+        validator.to_string_lossy(),
+        ledger.to_string_lossy()
+    ));
+
+    let res = init_and_wait(
+        &mut child,
+        &rpc_url,
+        &ledger,
+        &payer_keypair,
+        payer.pubkey(),
+        oracle_program_id,
+        price_store_program_id,
+        startup_timeout.into(),
+        exit_after_init,
+    )
+    .await;
+
+    output::notice("Stopping the validator");
+    child.kill().await.context("Failed to stop the validator")?;
+
+    res
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn init_and_wait(
+    child: &mut Child,
+    rpc_url: &str,
+    ledger: &Path,
+    payer_keypair: &Path,
+    payer: Pubkey,
+    oracle_program_id: Option<Pubkey>,
+    price_store_program_id: Option<Pubkey>,
+    startup_timeout: Duration,
+    exit_after_init: bool,
+) -> Result<()> {
+    wait_for_health(child, rpc_url, ledger, startup_timeout).await?;
+
+    let payer = payer.to_string();
+    let common = |area: &str, command: &str| -> Vec<OsString> {
+        [
+            area,
+            command,
+            "--rpc-url",
+            rpc_url,
+            "--commitment",
+            "confirmed",
+        ]
+        .into_iter()
+        .map(OsString::from)
+        .collect()
+    };
+
+    if let Some(program_id) = oracle_program_id {
+        let program_id = program_id.to_string();
+
+        let mut update_permissions = common("oracle", "update-permissions");
+        update_permissions.extend(
+            [
+                "--program-id",
+                &program_id,
+                "--master-authority",
+                &payer,
+                "--data-curation-authority",
+                &payer,
+                "--security-authority",
+                &payer,
+                "--yes",
+            ]
+            .map(OsString::from),
+        );
+        update_permissions.extend(["--funding-keypair".into(), payer_keypair.into()]);
+        run_subcommand(update_permissions).await?;
+
+        let mut init_mapping = common("oracle", "init-mapping");
+        init_mapping.extend(["--program-id", &program_id].map(OsString::from));
+        init_mapping.extend([
+            "--funding-keypair".into(),
+            payer_keypair.into(),
+            "--mapping-keypair".into(),
+            ledger.join("mapping-keypair.json").into(),
+        ]);
+        run_subcommand(init_mapping).await?;
+    }
+
+    if let Some(program_id) = price_store_program_id {
+        let mut initialize = common("price-store", "initialize");
+        initialize.extend(
+            [
+                "--program-id",
+                &program_id.to_string(),
+                "--authority",
+                &payer,
+            ]
+            .map(OsString::from),
+        );
+        initialize.extend(["--payer-keypair".into(), payer_keypair.into()]);
+        run_subcommand(initialize).await?;
+    }
+
+    output::result(
+        format!("Cluster is ready.  RPC: {rpc_url}"),
+        json!({ "rpc_url": rpc_url, "ledger": ledger }),
+    );
+
+    if exit_after_init {
+        return Ok(());
+    }
+
+    let stop_signals = select_all([
+        SignalStream::new(signal(SignalKind::interrupt()).expect("Can install a SIGINT handler")),
+        SignalStream::new(signal(SignalKind::terminate()).expect("Can install a SIGTERM handler")),
+    ]);
+    tokio::pin!(stop_signals);
+
+    output::notice("Press Ctrl+C to stop the validator.");
+
+    select! {
+        status = child.wait() => {
+            let status = status.context("Failed to wait for the validator")?;
+            bail!("Validator exited unexpectedly: {status}.  See the logs in {}", ledger.to_string_lossy());
+        }
+        _ = stop_signals.next() => Ok(()),
+    }
+}
+
+/// Writes all the accounts from the `bootstrap genesis` config as JSON files, in the format that
+/// `solana-test-validator --account-dir` accepts.  Returns the directory path.
+fn write_account_dir(genesis_config: &Path, ledger: &Path) -> Result<PathBuf> {
+    let config = read_config(genesis_config)?;
+    let base_dir = genesis_config.parent().unwrap_or(Path::new("."));
+    let accounts: BTreeMap<String, Base64Account> = build_accounts(base_dir, config)?;
+
+    let mut account_dir = ledger.as_os_str().to_owned();
+    account_dir.push("-accounts");
+    let account_dir = PathBuf::from(account_dir);
+
+    if account_dir.exists() {
+        fs::remove_dir_all(&account_dir).with_context(|| {
+            format!(
+                "Failed to clear the account dir: {}",
+                account_dir.to_string_lossy()
+            )
+        })?;
+    }
+    fs::create_dir_all(&account_dir).with_context(|| {
+        format!(
+            "Failed to create the account dir: {}",
+            account_dir.to_string_lossy()
+        )
+    })?;
+
+    for (address, account) in accounts {
+        let Base64Account {
+            balance,
+            owner,
+            data,
+            executable,
+        } = account;
+        // The `solana account --output json` format.
+        let space = base64::engine::general_purpose::STANDARD
+            .decode(&data)
+            .with_context(|| format!("Invalid account data for {address}"))?
+            .len();
+        let content = json!({
+            "pubkey": address,
+            "account": {
+                "lamports": balance,
+                "data": [data, "base64"],
+                "owner": owner,
+                "executable": executable,
+                "rentEpoch": 0,
+                "space": space,
+            },
+        });
+
+        let path = account_dir.join(format!("{address}.json"));
+        fs::write(&path, content.to_string())
+            .with_context(|| format!("Failed to write: {}", path.to_string_lossy()))?;
+    }
+
+    Ok(account_dir)
+}
+
+/// Waits until the RPC node reports it is healthy.  Fails if the validator process exits first.
+async fn wait_for_health(
+    child: &mut Child,
+    rpc_url: &str,
+    ledger: &Path,
+    startup_timeout: Duration,
+) -> Result<()> {
+    let rpc_client = RpcClient::new(rpc_url.to_owned());
+    let deadline = Instant::now() + startup_timeout;
+
+    output::notice(format!("Waiting for {rpc_url} to become healthy ..."));
+    loop {
+        if let Some(status) = child
+            .try_wait()
+            .context("Failed to check the validator status")?
+        {
+            bail!(
+                "Validator exited during the startup: {status}.  See the logs in {}",
+                ledger.to_string_lossy()
+            );
+        }
+
+        if rpc_client.get_health().await.is_ok() {
+            return Ok(());
+        }
+
+        if Instant::now() >= deadline {
+            bail!("Validator did not become healthy within {startup_timeout:?}");
+        }
+
+        sleep(HEALTH_POLL_INTERVAL).await;
+    }
+}
+
+/// Runs another command, as if it was specified on the command line.
+async fn run_subcommand(words: Vec<OsString>) -> Result<()> {
+    let command = Args::command();
+    let bin_name = OsString::from(command.get_name());
+    let args = args::try_parse_from(command, iter::once(bin_name).chain(words))
+        .context("Failed to construct an initialization command")?;
+
+    Box::pin(crate::run_command(args.command)).await
+}
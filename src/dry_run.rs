@@ -0,0 +1,243 @@
+//! `--dry-run`: commands build their transactions as usual, but instead of sending them, describe
+//! what the transactions would do, and stop.
+//!
+//! [`tx_sheppard`](crate::tx_sheppard) and the [`rpc_client_ext`](crate::rpc_client_ext) send
+//! helpers check [`is_enabled()`] before sending anything.  In the dry run mode they call
+//! [`describe()`], and return the [`Stopped`] error, so that the command ends right there, without
+//! reading the state the transactions were expected to produce.
+//!
+//! Commands that can provide a more specific summary, like the expected fees, check
+//! [`is_enabled()`] themselves, and return before any transactions are built.
+//!
+//! Dry run is disabled by default.
+
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    fmt::{self, Display},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use anyhow::{Result, bail};
+use itertools::Itertools as _;
+use serde_json::json;
+use solana_program::{
+    program_utils::limited_deserialize, system_instruction::SystemInstruction, system_program,
+};
+use solana_rpc_client_api::config::RpcSimulateTransactionConfig;
+use solana_sdk::{native_token::Sol, packet::PACKET_DATA_SIZE, transaction::Transaction};
+
+use crate::{cluster_rpc::ClusterRpc, output};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static SIMULATE: AtomicBool = AtomicBool::new(false);
+
+/// Returned by the transaction sending code in the dry run mode, after the transactions are
+/// described.  Not a failure, as far as the process exit code is concerned.
+#[derive(Debug)]
+pub struct Stopped;
+
+impl Display for Stopped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Dry run.  No transactions were sent")
+    }
+}
+
+impl Error for Stopped {}
+
+/// Switches to the dry run mode.  When `simulate` is set, [`describe()`] also runs every
+/// transaction through `simulateTransaction`.
+pub fn enable(simulate: bool) {
+    SIMULATE.store(simulate, Ordering::Relaxed);
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn disable() {
+    ENABLED.store(false, Ordering::Relaxed);
+    SIMULATE.store(false, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Fails in the dry run mode.  For the commands that generate load, where describing every
+/// transaction is not useful.
+pub fn check_not_enabled(command: &str) -> Result<()> {
+    if is_enabled() {
+        bail!("`{command}` generates load, and does not support `--dry-run`");
+    }
+    Ok(())
+}
+
+/// Prints a summary of every transaction: the fee payer, the signers, the invoked programs, the
+/// accounts written, and the lamports moved by the system program instructions.  Simulates the
+/// transactions, if requested in [`enable()`].
+///
+/// `dependencies` are the same as in
+/// [`RunWithTxSheppardArgs::dependencies()`](crate::tx_sheppard::RunWithTxSheppardArgs::dependencies).
+/// Transactions that depend on other transactions are not simulated, as the state they expect
+/// does not exist.
+///
+/// Always returns [`Stopped`], for the caller to return as an error.
+pub async fn describe<Rpc: ClusterRpc + ?Sized>(
+    rpc_client: &Rpc,
+    transactions: &[Transaction],
+    labels: Option<&[String]>,
+    dependencies: &[Vec<usize>],
+) -> Stopped {
+    let simulate = SIMULATE.load(Ordering::Relaxed);
+
+    let mut total_lamports = 0;
+    for (index, transaction) in transactions.iter().enumerate() {
+        let label = labels.and_then(|labels| labels.get(index));
+        let depends_on = dependencies
+            .get(index)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let message = &transaction.message;
+        let signers = message
+            .account_keys
+            .iter()
+            .take(usize::from(message.header.num_required_signatures))
+            .collect_vec();
+        let fee_payer = message.account_keys.first().copied().unwrap_or_default();
+        let programs = message
+            .instructions
+            .iter()
+            .map(|instruction| message.account_keys[usize::from(instruction.program_id_index)])
+            .collect::<BTreeSet<_>>();
+        let writable = message
+            .account_keys
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| message.is_writable(*i))
+            .map(|(_, key)| key)
+            .collect_vec();
+        let lamports = system_lamports_moved(transaction);
+        total_lamports += lamports;
+
+        let mut text = match label {
+            Some(label) => format!("Transaction #{index}: {label}\n"),
+            None => format!("Transaction #{index}\n"),
+        };
+        text.push_str(&format!("  Fee payer: {fee_payer}\n"));
+        text.push_str(&format!("  Signers: {}\n", signers.iter().join(", ")));
+        text.push_str(&format!("  Programs: {}\n", programs.iter().join(", ")));
+        text.push_str(&format!(
+            "  Writable accounts: {}\n",
+            writable.iter().join(", ")
+        ));
+        text.push_str(&format!("  Lamports moved: {}", Sol(lamports)));
+        if !depends_on.is_empty() {
+            text.push_str(&format!(
+                "\n  Depends on: {}",
+                depends_on.iter().map(|i| format!("#{i}")).join(", ")
+            ));
+        }
+
+        let simulation = if !simulate {
+            None
+        } else if !depends_on.is_empty() {
+            text.push_str("\n  Simulation: skipped, depends on other transactions");
+            Some(json!({ "skipped": true }))
+        } else {
+            Some(simulate_transaction(rpc_client, transaction, &mut text).await)
+        };
+
+        output::result(
+            text,
+            json!({
+                "index": index,
+                "label": label,
+                "fee_payer": fee_payer.to_string(),
+                "signers": signers.iter().map(ToString::to_string).collect_vec(),
+                "programs": programs.iter().map(ToString::to_string).collect_vec(),
+                "writable": writable.iter().map(ToString::to_string).collect_vec(),
+                "lamports_moved": lamports,
+                "depends_on": depends_on,
+                "simulation": simulation,
+            }),
+        );
+    }
+
+    output::notice(format!(
+        "Transactions: {}, lamports moved: {}",
+        transactions.len(),
+        Sol(total_lamports)
+    ));
+
+    Stopped
+}
+
+/// Sum of the lamports moved by the system program instructions of the `transaction`.
+fn system_lamports_moved(transaction: &Transaction) -> u64 {
+    let message = &transaction.message;
+    message
+        .instructions
+        .iter()
+        .filter(|instruction| {
+            message.account_keys[usize::from(instruction.program_id_index)] == system_program::id()
+        })
+        .filter_map(|instruction| {
+            limited_deserialize::<SystemInstruction>(&instruction.data, PACKET_DATA_SIZE as u64)
+                .ok()
+        })
+        .map(|instruction| match instruction {
+            SystemInstruction::CreateAccount { lamports, .. }
+            | SystemInstruction::CreateAccountWithSeed { lamports, .. }
+            | SystemInstruction::Transfer { lamports }
+            | SystemInstruction::TransferWithSeed { lamports, .. }
+            | SystemInstruction::WithdrawNonceAccount(lamports) => lamports,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Simulates the `transaction`, appending the outcome to the `text` summary, and returning it in
+/// the JSON form.
+async fn simulate_transaction<Rpc: ClusterRpc + ?Sized>(
+    rpc_client: &Rpc,
+    transaction: &Transaction,
+    text: &mut String,
+) -> serde_json::Value {
+    // Transactions are built with a placeholder blockhash in the dry run mode.
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(rpc_client.commitment()),
+        ..RpcSimulateTransactionConfig::default()
+    };
+
+    let res = match rpc_client
+        .simulate_transaction_with_config(transaction, config)
+        .await
+    {
+        Ok(res) => res,
+        Err(err) => {
+            text.push_str(&format!("\n  Simulation: request failed: {err}"));
+            return json!({ "error": err.to_string() });
+        }
+    };
+
+    match &res.err {
+        None => text.push_str(&format!(
+            "\n  Simulation: succeeded, compute units: {}",
+            res.units_consumed.unwrap_or_default()
+        )),
+        Some(err) => {
+            text.push_str(&format!("\n  Simulation: failed: {err}"));
+            for log in res.logs.iter().flatten() {
+                text.push_str(&format!("\n    {log}"));
+            }
+        }
+    }
+
+    json!({
+        "error": res.err.as_ref().map(ToString::to_string),
+        "units_consumed": res.units_consumed,
+        "logs": res.logs,
+    })
+}
@@ -0,0 +1,112 @@
+//! Process exit codes, allowing automation to tell different kinds of failures apart:
+//!
+//! * 0 - Everything succeeded.
+//! * 1 - Any other error.
+//! * 2 - Command line arguments could not be parsed.  This one is reported by `clap`.
+//! * 3 - Some of the transactions failed.  Others might have succeeded.
+//! * 4 - Transaction preflight check failed, or command inputs did not pass validation.
+//! * 5 - RPC node is unreachable.
+//!
+//! Commands report the specific kinds of failures by including [`TransactionsFailed`] or
+//! [`ValidationFailed`] into the returned error chain.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    process::ExitCode,
+};
+
+use pythnet_heisenberg::tx_sheppard::TxOutcome;
+use solana_rpc_client_api::{
+    client_error::{Error as ClientError, ErrorKind as ClientErrorKind},
+    request::{RpcError, RpcResponseErrorData},
+};
+
+const OTHER_ERROR: u8 = 1;
+const TRANSACTIONS_FAILED: u8 = 3;
+const VALIDATION_FAILED: u8 = 4;
+const RPC_UNREACHABLE: u8 = 5;
+
+/// Some of the transactions sent by a command failed.
+#[derive(Debug)]
+pub struct TransactionsFailed {
+    pub failed: usize,
+    pub total: usize,
+}
+
+impl Display for TransactionsFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { failed, total } = self;
+        write!(f, "{failed} out of {total} transactions failed")
+    }
+}
+
+impl Error for TransactionsFailed {}
+
+/// Command inputs did not pass validation.  Meant to be used as an error context:
+///
+///   args.check_are_valid().context(ValidationFailed)?;
+#[derive(Debug)]
+pub struct ValidationFailed;
+
+impl Display for ValidationFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Validation failed")
+    }
+}
+
+/// Returns `Ok(())` when `failed` is zero, otherwise returns a [`TransactionsFailed`] error.
+pub fn check_transactions(failed: usize, total: usize) -> Result<(), TransactionsFailed> {
+    if failed == 0 {
+        Ok(())
+    } else {
+        Err(TransactionsFailed { failed, total })
+    }
+}
+
+/// Returns `Ok(())` when all the `outcomes` are successful, otherwise returns a
+/// [`TransactionsFailed`] error.
+pub fn check_outcomes(outcomes: &[TxOutcome]) -> Result<(), TransactionsFailed> {
+    let failed = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome, TxOutcome::Failed(_)))
+        .count();
+    check_transactions(failed, outcomes.len())
+}
+
+/// Selects an exit code for a command that failed with `err`.
+pub fn for_error(err: &anyhow::Error) -> ExitCode {
+    // `anyhow::Error::downcast_ref()` also finds types used as a context, while `chain()` only
+    // sees errors that are part of the `source()` chain.
+    let code = if err.downcast_ref::<TransactionsFailed>().is_some() {
+        TRANSACTIONS_FAILED
+    } else if err.downcast_ref::<ValidationFailed>().is_some() {
+        VALIDATION_FAILED
+    } else {
+        err.chain()
+            .find_map(|err| err.downcast_ref::<ClientError>().and_then(for_client_error))
+            .unwrap_or(OTHER_ERROR)
+    };
+
+    ExitCode::from(code)
+}
+
+fn for_client_error(err: &ClientError) -> Option<u8> {
+    match err.kind() {
+        ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => Some(RPC_UNREACHABLE),
+        // `RpcClient` queries the node version before some requests, and converts any failure
+        // into a string.  It is the first request such commands send, and the most likely reason
+        // for it to fail is the node being unreachable.
+        ClientErrorKind::RpcError(RpcError::RpcRequestError(message))
+            if message.starts_with("cluster version query failed") =>
+        {
+            Some(RPC_UNREACHABLE)
+        }
+        ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(_),
+            ..
+        }) => Some(VALIDATION_FAILED),
+        ClientErrorKind::TransactionError(_) => Some(TRANSACTIONS_FAILED),
+        _ => None,
+    }
+}
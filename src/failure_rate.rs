@@ -0,0 +1,116 @@
+//! Stops long runs early, when most of what they send fails.
+//!
+//! A benchmark against an unhealthy cluster, or an overloaded RPC node, keeps producing numbers
+//! that say nothing about the system under test.  [`FailureRateGuard`] watches the failure rate
+//! over a sliding window, so that such runs can be stopped, rather than consuming the scheduled
+//! test time.
+
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+/// Tracks cumulative success and failure counts, and reports when the failure rate over the
+/// last `window` reaches a threshold.
+pub struct FailureRateGuard {
+    /// Percentage of failures that trips the guard.
+    threshold: f64,
+    window: Duration,
+    start: Instant,
+    /// Cumulative counts, with the moment they were observed, oldest first.  The first sample is
+    /// at or before the window start, once the run is longer than the window.
+    samples: VecDeque<Sample>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    at: Instant,
+    succeeded: u64,
+    failed: u64,
+}
+
+/// Failure rate that tripped a [`FailureRateGuard`].
+#[derive(Debug, Clone, Copy)]
+pub struct FailureRate {
+    /// Percentage of failures.
+    pub percent: f64,
+    pub failed: u64,
+    pub total: u64,
+    pub window: Duration,
+}
+
+impl Display for FailureRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            percent,
+            failed,
+            total,
+            window,
+        } = self;
+        write!(
+            f,
+            "{failed} out of {total} transactions ({percent:.1}%) failed over the last {}",
+            humantime::format_duration(*window)
+        )
+    }
+}
+
+impl FailureRateGuard {
+    /// `threshold` is a percentage of failures.
+    pub fn new(threshold: f64, window: Duration) -> Self {
+        Self {
+            threshold,
+            window,
+            start: Instant::now(),
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// Records the cumulative `succeeded` and `failed` counts, as of `now`.  Returns the failure
+    /// rate over the last window, if it is at or above the threshold.
+    ///
+    /// The guard does not trip until the run is at least as long as the window, so that a slow
+    /// start does not abort it.  Windows without any transactions are not considered failed.
+    pub fn check(&mut self, now: Instant, succeeded: u64, failed: u64) -> Option<FailureRate> {
+        self.samples.push_back(Sample {
+            at: now,
+            succeeded,
+            failed,
+        });
+
+        let window_start = now.checked_sub(self.window)?;
+        while self
+            .samples
+            .get(1)
+            .is_some_and(|sample| sample.at <= window_start)
+        {
+            self.samples.pop_front();
+        }
+
+        if now.duration_since(self.start) < self.window {
+            return None;
+        }
+
+        let baseline = self.samples.front()?;
+        let failed = failed.saturating_sub(baseline.failed);
+        let total = failed + succeeded.saturating_sub(baseline.succeeded);
+        if total == 0 {
+            return None;
+        }
+
+        let percent = failed as f64 / total as f64 * 100.0;
+        (percent >= self.threshold).then_some(FailureRate {
+            percent,
+            failed,
+            total,
+            window: self.window,
+        })
+    }
+}
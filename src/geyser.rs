@@ -0,0 +1,160 @@
+//! Subscriptions over the Yellowstone gRPC interface.
+//!
+//! Nodes that run the Yellowstone Geyser plugin stream account updates directly from the
+//! validator.  Compared to the RPC WebSocket subscriptions, updates arrive sooner, and a single
+//! connection can carry an arbitrary number of filters.
+//!
+//! Every update is converted into a plain type defined here, so the callers do not need to depend
+//! on the protobuf definitions.
+
+use std::collections::HashMap;
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use futures::{Stream, StreamExt as _, future, stream::BoxStream};
+use log::warn;
+use reqwest::Url;
+use solana_sdk::{
+    account::Account,
+    clock::Slot,
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    pubkey::Pubkey,
+};
+use yellowstone_grpc_client::GeyserGrpcClient;
+use yellowstone_grpc_proto::prelude::{
+    CommitmentLevel as GeyserCommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts,
+    SubscribeUpdate, SubscribeUpdateAccount, subscribe_update::UpdateOneof,
+};
+
+/// Name of the filter used in every subscription request.  We only ever use one filter, so the
+/// name is not important.
+const FILTER_NAME: &str = "heisenberg";
+
+/// Location of a Yellowstone gRPC endpoint.
+#[derive(Debug, Clone)]
+pub struct GeyserConfig {
+    pub endpoint: Url,
+    /// Access token, sent in the `x-token` header.  Most public providers require one.
+    pub x_token: Option<String>,
+}
+
+/// A new state of an account.
+#[derive(Debug, Clone)]
+pub struct AccountUpdate {
+    pub pubkey: Pubkey,
+    pub slot: Slot,
+    pub account: Account,
+}
+
+/// Subscribes to changes of the specified `accounts`, as well as all the accounts owned by any of
+/// the `owners`.
+pub async fn subscribe_accounts(
+    config: &GeyserConfig,
+    accounts: &[Pubkey],
+    owners: &[Pubkey],
+    commitment: CommitmentLevel,
+) -> Result<BoxStream<'static, Result<AccountUpdate>>> {
+    let request = SubscribeRequest {
+        accounts: HashMap::from([(
+            FILTER_NAME.to_owned(),
+            SubscribeRequestFilterAccounts {
+                account: accounts.iter().map(Pubkey::to_string).collect(),
+                owner: owners.iter().map(Pubkey::to_string).collect(),
+                ..SubscribeRequestFilterAccounts::default()
+            },
+        )]),
+        commitment: Some(geyser_commitment(commitment) as i32),
+        ..SubscribeRequest::default()
+    };
+
+    let updates = subscribe(config, request).await?;
+    Ok(updates
+        .filter_map(|update| {
+            future::ready(match update {
+                Ok(UpdateOneof::Account(update)) => Some(account_update(update)),
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+        })
+        .boxed())
+}
+
+/// Connects to the endpoint and sends a single subscription request.  Pings are answered by the
+/// server side of the stream, so they are dropped here.
+async fn subscribe(
+    GeyserConfig { endpoint, x_token }: &GeyserConfig,
+    request: SubscribeRequest,
+) -> Result<impl Stream<Item = Result<UpdateOneof>>> {
+    let mut client = GeyserGrpcClient::build_from_shared(endpoint.to_string())
+        .with_context(|| format!("Invalid Yellowstone gRPC endpoint: {endpoint}"))?
+        .x_token(x_token.clone())
+        .context("Invalid Yellowstone gRPC access token")?
+        .connect()
+        .await
+        .with_context(|| format!("Failed to connect to {endpoint}"))?;
+
+    let updates = client
+        .subscribe_once(request)
+        .await
+        .with_context(|| format!("Failed to subscribe at {endpoint}"))?;
+
+    Ok(updates.filter_map(|update| {
+        future::ready(match update {
+            Ok(SubscribeUpdate {
+                update_oneof: Some(UpdateOneof::Ping(_) | UpdateOneof::Pong(_)),
+                ..
+            }) => None,
+            Ok(SubscribeUpdate {
+                update_oneof: Some(update),
+                ..
+            }) => Some(Ok(update)),
+            Ok(SubscribeUpdate {
+                update_oneof: None, ..
+            }) => {
+                warn!("Yellowstone gRPC update without any content");
+                None
+            }
+            Err(status) => Some(Err(
+                anyhow!(status).context("Yellowstone gRPC stream failed")
+            )),
+        })
+    }))
+}
+
+fn geyser_commitment(commitment: CommitmentLevel) -> GeyserCommitmentLevel {
+    // `CommitmentLevel` still contains deprecated variants, so we match through the helpers.
+    let commitment = CommitmentConfig { commitment };
+    if commitment.is_finalized() {
+        GeyserCommitmentLevel::Finalized
+    } else if commitment.is_confirmed() {
+        GeyserCommitmentLevel::Confirmed
+    } else {
+        GeyserCommitmentLevel::Processed
+    }
+}
+
+fn account_update(
+    SubscribeUpdateAccount { account, slot, .. }: SubscribeUpdateAccount,
+) -> Result<AccountUpdate> {
+    let Some(account) = account else {
+        bail!("Account update for slot {slot} does not contain an account");
+    };
+
+    let pubkey = pubkey_from_bytes(&account.pubkey).context("Account update `pubkey`")?;
+    let owner = pubkey_from_bytes(&account.owner).context("Account update `owner`")?;
+
+    Ok(AccountUpdate {
+        pubkey,
+        slot,
+        account: Account {
+            lamports: account.lamports,
+            data: account.data,
+            owner,
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+        },
+    })
+}
+
+fn pubkey_from_bytes(bytes: &[u8]) -> Result<Pubkey> {
+    Pubkey::try_from(bytes).map_err(|_| anyhow!("Expected 32 bytes, got {}", bytes.len()))
+}
@@ -1,13 +1,64 @@
 //! Helpers for dealing with `Keypair`s.
 
-use std::path::Path;
+use std::{
+    cmp::Ordering,
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Write as _},
+    mem::ManuallyDrop,
+    os::{
+        fd::{FromRawFd as _, RawFd},
+        unix::fs::OpenOptionsExt as _,
+    },
+    path::{Path, PathBuf},
+};
 
-use anyhow::{Context as _, Result, anyhow};
+use anyhow::{Context as _, Result, anyhow, bail};
 use rand_0_7::rngs::OsRng;
-use solana_sdk::{signature::Keypair, signer::EncodableKey};
+use solana_clap_utils::keypair::keypair_from_seed_phrase;
+use solana_remote_wallet::{
+    locator::Locator, remote_keypair::generate_remote_keypair, remote_wallet::maybe_wallet_manager,
+};
+use solana_sdk::{
+    derivation_path::DerivationPath,
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, read_keypair},
+    signer::{EncodableKey, Signer, SignerError, signers::Signers},
+    transaction::Transaction,
+};
+use uriparse::URIReference;
 
+/// Reads a keypair from a file.
+///
+/// Same as the `solana` CLI, also accepts `prompt://[?key=<account>[/<change>]]` and `ASK`, in
+/// which case a BIP-39 seed phrase and an optional passphrase are read from the terminal.  The
+/// `prompt://` form uses the specified derivation path, or `m/44'/501'` if none is given, while
+/// `ASK` derives the keypair from the seed directly.
+///
+/// In order to pass secrets from a secret manager without writing them to disk, `-` reads a keypair
+/// from stdin, and `fd:N` reads a keypair from an already open file descriptor `N`.  Keypairs use
+/// the same JSON format as the keypair files.  Stdin can only be used for one keypair.
 pub fn read_keypair_file(path: impl AsRef<Path>) -> Result<Keypair> {
     let path = path.as_ref();
+
+    match path.to_str() {
+        Some("-") => return read_keypair_from_reader(&mut io::stdin().lock(), "stdin"),
+        Some(source) if source.starts_with("fd:") => return read_keypair_from_fd(source),
+        Some(source @ "ASK") => return read_keypair_from_seed_phrase(source, None, true),
+        Some(source) if source.starts_with("prompt:") => {
+            let derivation_path = URIReference::try_from(source)
+                .map_err(|err| anyhow!(err.to_string()))
+                .and_then(|uri| {
+                    DerivationPath::from_uri_any_query(&uri).map_err(|err| anyhow!(err.to_string()))
+                })
+                .with_context(|| format!("Parsing a derivation path from: {source}"))?;
+            return read_keypair_from_seed_phrase(source, derivation_path, false);
+        }
+        _ => (),
+    }
+
     Keypair::read_from_file(path)
         // It is a bit strange, but `Box<dyn Error>` does not implement `Error` for some reason.
         // And `anyhow::Context::with_context` fails.  So I need to construct a new `Error`
@@ -16,17 +67,378 @@ pub fn read_keypair_file(path: impl AsRef<Path>) -> Result<Keypair> {
         .with_context(|| format!("Error reading a keypair from: {}", path.to_string_lossy()))
 }
 
+/// Returns `true` for all the keypair sources that are not files.  See [`read_keypair_file()`].
+fn is_non_file_source(path: &Path) -> bool {
+    path.to_str().is_some_and(|source| {
+        source == "-"
+            || source == "ASK"
+            || source.starts_with("fd:")
+            || source.starts_with("prompt:")
+    })
+}
+
+fn read_keypair_from_reader(reader: &mut impl Read, source: &str) -> Result<Keypair> {
+    read_keypair(reader)
+        .map_err(|err| anyhow!(err.to_string()))
+        .with_context(|| format!("Error reading a keypair from: {source}"))
+}
+
+/// Reads a keypair from an `fd:N` source.  The file descriptor is not closed, as it is owned by
+/// whoever started this process.
+fn read_keypair_from_fd(source: &str) -> Result<Keypair> {
+    let fd = source["fd:".len()..]
+        .parse::<RawFd>()
+        .with_context(|| format!("Expected a file descriptor number in: {source}"))?;
+
+    // SAFETY: The file descriptor is provided by the user, who is responsible for it being open.
+    // `ManuallyDrop` makes sure we do not close a descriptor we do not own.
+    let mut file = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+    read_keypair_from_reader(&mut *file, source)
+}
+
+/// Asks the user for a seed phrase, validating it, and an optional passphrase.
+fn read_keypair_from_seed_phrase(
+    source: &str,
+    derivation_path: Option<DerivationPath>,
+    legacy: bool,
+) -> Result<Keypair> {
+    keypair_from_seed_phrase(
+        source,
+        /* skip_validation: */ false,
+        /* confirm_pubkey: */ false,
+        derivation_path,
+        legacy,
+    )
+    .map_err(|err| anyhow!(err.to_string()))
+    .with_context(|| format!("Error reading a keypair from a seed phrase for: {source}"))
+}
+
+/// Reads a signer that is either a keypair file, or a hardware wallet.
+///
+/// Hardware wallets are specified using the same URIs the `solana` CLI accepts:
+///
+///   usb://ledger[/<wallet pubkey>][?key=<account>[/<change>]]
+///
+/// Hardware wallet support requires the `ledger` feature.  `keypair_name` is shown when asking the
+/// user to pick a device, if more than one is connected.
+pub fn read_signer(source: impl AsRef<Path>, keypair_name: &str) -> Result<Box<dyn Signer>> {
+    let source = source.as_ref();
+
+    let Some(uri) = source
+        .to_str()
+        .filter(|source| source.starts_with("usb://"))
+    else {
+        return Ok(Box::new(read_keypair_file(source)?));
+    };
+
+    let context = || format!("Hardware wallet: {uri}");
+
+    let locator = Locator::new_from_path(uri).with_context(context)?;
+    let derivation_path = URIReference::try_from(uri)
+        .map_err(|err| anyhow!(err.to_string()))
+        .and_then(|uri| {
+            DerivationPath::from_uri_key_query(&uri).map_err(|err| anyhow!(err.to_string()))
+        })
+        .with_context(context)?
+        .unwrap_or_default();
+
+    let wallet_manager = maybe_wallet_manager()
+        .with_context(context)?
+        .ok_or_else(|| anyhow!("No hardware wallets found"))
+        .with_context(context)?;
+
+    let keypair = generate_remote_keypair(
+        locator,
+        derivation_path,
+        &wallet_manager,
+        /* confirm_key: */ false,
+        keypair_name,
+    )
+    .with_context(context)?;
+
+    Ok(Box::new(keypair))
+}
+
+/// Same as [`Transaction::new_signed_with_payer()`], but returns signing errors rather than
+/// panicking.  Use it with signers returned by [`read_signer()`], as hardware wallets can fail to
+/// sign, for example, when the user rejects the transaction.
+pub fn try_new_signed_with_payer<SigningKeypairs: Signers + ?Sized>(
+    instructions: &[Instruction],
+    payer: Option<&Pubkey>,
+    signing_keypairs: &SigningKeypairs,
+    recent_blockhash: Hash,
+) -> Result<Transaction, SignerError> {
+    let mut transaction = Transaction::new_with_payer(instructions, payer);
+    transaction.try_sign(signing_keypairs, recent_blockhash)?;
+    Ok(transaction)
+}
+
+/// Reads a keypair from the `path`, generating a new keypair, and writing it into the `path`, if
+/// there is no file there yet.
+///
+/// An existing file is never overwritten, even if it does not hold a keypair.  See
+/// [`write_new_keypair_file()`] for how the new file is written.
 pub fn read_or_generate_keypair_file(path: impl AsRef<Path>) -> Result<Keypair> {
     let path = path.as_ref();
 
-    if path.exists() {
+    if is_non_file_source(path) {
         return read_keypair_file(path);
     }
+    if path.exists() {
+        return read_existing_keypair_file(path);
+    }
 
     let key = Keypair::generate(&mut OsRng);
-    key.write_to_file(path)
-        .map_err(|err| anyhow!(err.to_string()))
-        .with_context(|| format!("Error reading a keypair from: {}", path.to_string_lossy()))?;
+    match write_new_keypair_file(&key, path) {
+        Ok(()) => Ok(key),
+        // Someone else generated a keypair at the same path first, for example, a parallel run.
+        Err(err) if is_already_exists(&err) => read_existing_keypair_file(path),
+        Err(err) => Err(err),
+    }
+}
+
+/// Writes the `keypair` into a new file at the `path`.  Fails if the file already exists.
+///
+/// The keypair is written into a temporary file next to the `path` first, readable only by the
+/// owner, and is then linked into place.  So the `path` either does not exist, or holds the
+/// complete keypair, even if the process is interrupted.
+pub fn write_new_keypair_file(keypair: &Keypair, path: &Path) -> Result<()> {
+    let temp_path = write_temp_keypair_file(keypair, path)?;
+
+    let res = fs::hard_link(&temp_path, path);
+    let _ = fs::remove_file(&temp_path);
+    res.with_context(|| format!("Failed to create keypair file: {}", path.to_string_lossy()))
+}
+
+/// Replaces the keypair file at the `path` with the `keypair`, the same way as
+/// [`write_new_keypair_file()`] does.  The existing file is kept as `<path>.bak`, or as
+/// `<path>.bak.<N>`, if there are backups already.  Returns the backup path, if there was an
+/// existing file.
+///
+/// Only keypair files are replaced.  Anything else at the `path` is likely a mistake in the path.
+pub fn replace_keypair_file(keypair: &Keypair, path: &Path) -> Result<Option<PathBuf>> {
+    if !path.exists() {
+        write_new_keypair_file(keypair, path)?;
+        return Ok(None);
+    }
+
+    read_existing_keypair_file(path)?;
+
+    let backup_path = link_backup(path)?;
+
+    let temp_path = write_temp_keypair_file(keypair, path)?;
+    if let Err(err) = fs::rename(&temp_path, path) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(err).with_context(|| {
+            format!("Failed to replace keypair file: {}", path.to_string_lossy())
+        });
+    }
+
+    Ok(Some(backup_path))
+}
+
+fn read_existing_keypair_file(path: &Path) -> Result<Keypair> {
+    read_keypair_file(path).with_context(|| {
+        format!(
+            "{} exists, but does not hold a keypair.  Not overwriting it",
+            path.to_string_lossy()
+        )
+    })
+}
+
+/// Writes the `keypair` into a new file in the same directory as the `path`, so that it can be
+/// moved into the `path` atomically.  The file is only accessible by the owner.
+fn write_temp_keypair_file(keypair: &Keypair, path: &Path) -> Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("Not a file path: {}", path.to_string_lossy()))?;
+    let mut temp_name = OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{:08x}.tmp", rand::random::<u32>()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let content = serde_json::to_string(&keypair.to_bytes().to_vec())
+        .expect("Keypair bytes serialize into JSON");
+
+    let res = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(content.as_bytes())?;
+            file.sync_all()
+        });
+    if let Err(err) = res {
+        let _ = fs::remove_file(&temp_path);
+        return Err(err).with_context(|| {
+            format!(
+                "Failed to write keypair file: {}",
+                temp_path.to_string_lossy()
+            )
+        });
+    }
+
+    Ok(temp_path)
+}
+
+/// Links the file at the `path` as the first available `<path>.bak` or `<path>.bak.<N>`.
+fn link_backup(path: &Path) -> Result<PathBuf> {
+    let mut backup_path = path.as_os_str().to_owned();
+    backup_path.push(".bak");
+
+    let mut n = 0;
+    loop {
+        let candidate = if n == 0 {
+            PathBuf::from(&backup_path)
+        } else {
+            let mut candidate = backup_path.clone();
+            candidate.push(format!(".{n}"));
+            PathBuf::from(candidate)
+        };
+
+        match fs::hard_link(path, &candidate) {
+            Ok(()) => return Ok(candidate),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => n += 1,
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!(
+                        "Failed to back up keypair file {} as {}",
+                        path.to_string_lossy(),
+                        candidate.to_string_lossy()
+                    )
+                });
+            }
+        }
+    }
+}
+
+fn is_already_exists(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<io::Error>()
+        .is_some_and(|err| err.kind() == io::ErrorKind::AlreadyExists)
+}
+
+/// Prefix of a keypair source that names a group of keypair files.  See [`expand_keypair_dir()`].
+pub const KEYPAIR_DIR_PREFIX: &str = "dir://";
+
+/// Expands a `dir://<dir>` or a `dir://<dir>/<pattern>` keypair source into a list of keypair
+/// files.  Sources without the [`KEYPAIR_DIR_PREFIX`] are returned as is.
+///
+/// A directory expands into all the `.json` files inside it.  A pattern is matched against the
+/// file names, with `*` matching any number of characters, and `?` matching any single character.
+/// Only the last path component can be a pattern.  For example:
+///
+///   dir://keys/publisher-*.json
+///
+/// Files are ordered by name, with numbers compared by value, so `publisher-2.json` comes before
+/// `publisher-10.json`.  This way, lists with the same numbering, like publishers and their price
+/// buffers, stay in the same order.
+pub fn expand_keypair_dir(source: &str) -> Result<Vec<PathBuf>> {
+    let Some(pattern) = source.strip_prefix(KEYPAIR_DIR_PREFIX) else {
+        return Ok(vec![source.into()]);
+    };
+
+    let pattern = Path::new(pattern);
+    let (dir, name_pattern) = if pattern.is_dir() {
+        (pattern, "*.json")
+    } else {
+        let name_pattern = pattern
+            .file_name()
+            .and_then(|name| name.to_str())
+            .with_context(|| format!("Expected a directory or a file name pattern in: {source}"))?;
+        let dir = match pattern.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        (dir, name_pattern)
+    };
+
+    let entries = fs::read_dir(dir).with_context(|| {
+        format!(
+            "Failed to read keypair directory: {}",
+            dir.to_string_lossy()
+        )
+    })?;
+
+    let mut names = vec![];
+    for entry in entries {
+        let entry = entry.with_context(|| {
+            format!(
+                "Failed to read keypair directory: {}",
+                dir.to_string_lossy()
+            )
+        })?;
+        if !entry.path().is_file() {
+            continue;
+        }
+        let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+            continue;
+        };
+        if matches_pattern(name_pattern.as_bytes(), name.as_bytes()) {
+            names.push(name);
+        }
+    }
+
+    if names.is_empty() {
+        bail!("No keypair files match: {source}");
+    }
+
+    names.sort_by(|a, b| natural_cmp(a, b));
+    Ok(names.into_iter().map(|name| dir.join(name)).collect())
+}
+
+/// Matches a file `name` against a `pattern` with `*` and `?` wildcards.
+fn matches_pattern(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| matches_pattern(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && matches_pattern(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && matches_pattern(rest, &name[1..]),
+    }
+}
+
+/// Compares strings, treating runs of digits as numbers.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.as_bytes();
+    let mut b = b.as_bytes();
+    loop {
+        match (a.first(), b.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (a_digits, a_rest) = split_digits(a);
+                let (b_digits, b_rest) = split_digits(b);
+                let a_number = trim_leading_zeros(a_digits);
+                let b_number = trim_leading_zeros(b_digits);
+                let order = a_number
+                    .len()
+                    .cmp(&b_number.len())
+                    .then_with(|| a_number.cmp(b_number))
+                    .then_with(|| a_digits.len().cmp(&b_digits.len()));
+                if order != Ordering::Equal {
+                    return order;
+                }
+                a = a_rest;
+                b = b_rest;
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(y);
+                }
+                a = &a[1..];
+                b = &b[1..];
+            }
+        }
+    }
+}
+
+fn split_digits(s: &[u8]) -> (&[u8], &[u8]) {
+    let len = s.iter().take_while(|c| c.is_ascii_digit()).count();
+    s.split_at(len)
+}
 
-    Ok(key)
+fn trim_leading_zeros(digits: &[u8]) -> &[u8] {
+    let zeros = digits.iter().take_while(|c| **c == b'0').count();
+    &digits[zeros..]
 }
@@ -0,0 +1,11 @@
+use anyhow::Result;
+
+use crate::args::keys::Command;
+
+mod generate;
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Generate(args) => generate::run(args).await,
+    }
+}
@@ -0,0 +1,82 @@
+use std::fs;
+
+use anyhow::{Context as _, Result};
+use pythnet_heisenberg::{
+    keypair_ext::{read_or_generate_keypair_file, replace_keypair_file},
+    output,
+};
+use rand_0_7::rngs::OsRng;
+use serde_json::json;
+use solana_sdk::{signature::Keypair, signer::Signer as _};
+
+use crate::args::keys::generate::GenerateArgs;
+
+pub async fn run(
+    GenerateArgs {
+        dir,
+        count,
+        prefix: prefixes,
+        start_index,
+        replace_existing,
+        manifest_file,
+    }: GenerateArgs,
+) -> Result<()> {
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create directory: {}", dir.to_string_lossy()))?;
+
+    let mut manifest = "# prefix,index,pubkey,path\n".to_owned();
+    let mut generated = 0;
+    let mut replaced = 0;
+    for prefix in &prefixes {
+        for index in start_index..start_index + count {
+            let path = dir.join(format!("{prefix}-{index}.json"));
+            let existed = path.exists();
+            let (pubkey, backup) = if replace_existing {
+                let keypair = Keypair::generate(&mut OsRng);
+                let backup = replace_keypair_file(&keypair, &path)?;
+                (keypair.pubkey(), backup)
+            } else {
+                (read_or_generate_keypair_file(&path)?.pubkey(), None)
+            };
+            if !existed {
+                generated += 1;
+            } else if backup.is_some() {
+                replaced += 1;
+            }
+
+            let path = path.to_string_lossy();
+            manifest.push_str(&format!("{prefix},{index},{pubkey},{path}\n"));
+            output::result(
+                format!("{prefix},{index},{pubkey},{path}"),
+                json!({
+                    "prefix": prefix,
+                    "index": index,
+                    "pubkey": pubkey.to_string(),
+                    "path": path,
+                    "generated": !existed || backup.is_some(),
+                    "backup": backup.map(|backup| backup.to_string_lossy().into_owned()),
+                }),
+            );
+        }
+    }
+
+    if replace_existing {
+        eprintln!("Generated {generated} new keypairs, replaced {replaced} existing ones");
+    } else {
+        eprintln!(
+            "Generated {generated} new keypairs, reused {} existing ones",
+            prefixes.len() * count - generated,
+        );
+    }
+
+    if let Some(manifest_file) = manifest_file {
+        fs::write(&manifest_file, manifest).with_context(|| {
+            format!(
+                "Failed to write manifest into: {}",
+                manifest_file.to_string_lossy()
+            )
+        })?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+//! Building blocks for testing a Pythnet cluster.
+//!
+//! The `pythnet-heisenberg` binary is a command line interface on top of this library.  Test
+//! harnesses can use the same functionality directly, instead of running the binary:
+//!
+//! * [`tx_sheppard`] sends a set of transactions in parallel, resending them until they are
+//!   executed.
+//! * [`blockhash_cache`] and [`node_address_service`] keep track of the latest blockhash and the
+//!   upcoming leaders, for the code that sends transactions directly to the leaders.
+//! * [`cluster_rpc`] is the subset of the RPC interface the above depend on, so that they can run
+//!   against a mock in tests.
+//! * [`oracle::instructions`] and [`price_store::instructions`] construct instructions for the
+//!   Oracle and the Price Store programs, while [`oracle::accounts`] and
+//!   [`price_store::accounts`] decode their accounts, and [`oracle::messages`] decodes the
+//!   messages the Oracle puts into the accumulator.
+//! * `geyser`, behind the `geyser` feature, subscribes to account updates over the Yellowstone
+//!   gRPC interface, for nodes that run the Yellowstone Geyser plugin.
+//! * [`slot_clock`] maps wall clock time to slots, so that latencies can be reported both in
+//!   milliseconds and in slots.
+//! * [`metrics_sink`] pushes measurements to an InfluxDB line protocol endpoint.
+//! * [`failure_rate`] detects runs where most of the transactions fail, so that they can be
+//!   stopped early.
+//! * [`keypair_ext`] and [`rpc_client_ext`] are helpers for reading keypairs and working with an
+//!   RPC client.
+//! * [`retrying_rpc_sender`] and [`rpc_telemetry`] wrap the RPC client transport, to retry failed
+//!   requests and to count the requests sent.
+//! * [`tx_records`] writes every transaction attempt into a CSV file, for offline analysis.
+//! * [`dry_run`] describes the transactions a command would send, instead of sending them.
+
+pub mod blockhash_cache;
+pub mod cluster_rpc;
+pub mod dry_run;
+pub mod failure_rate;
+#[cfg(feature = "geyser")]
+pub mod geyser;
+pub mod keypair_ext;
+pub mod metrics_sink;
+pub mod node_address_service;
+pub mod output;
+pub mod retrying_rpc_sender;
+pub mod rpc_client_ext;
+pub mod rpc_telemetry;
+pub mod session;
+pub mod slot_clock;
+pub mod tx_records;
+pub mod tx_sheppard;
+
+/// Interaction with the Oracle program.
+pub mod oracle {
+    pub mod accounts;
+    pub mod instructions;
+    pub mod messages;
+}
+
+/// Interaction with the Price Store program.
+pub mod price_store {
+    pub mod accounts;
+    pub mod instructions;
+}
@@ -1,27 +1,107 @@
+use std::process::ExitCode;
+
 use anyhow::Result;
-use clap::Parser as _;
+use pythnet_heisenberg::{dry_run, output, rpc_telemetry, tx_records};
 
+mod account;
 mod args;
-pub mod blockhash_cache;
-pub(crate) mod keypair_ext;
-pub mod node_address_service;
+mod benchmark;
+mod block;
+mod bootstrap;
+mod cluster;
+mod confirm;
+mod dev;
+mod exit_code;
+mod keys;
 mod oracle;
 mod price_store;
 mod primordial_accounts;
-pub(crate) mod rpc_client_ext;
+mod program;
+mod rpc;
+mod serde_pubkey;
+mod shell;
+mod stake;
 mod stake_caps_parameters;
 mod transfer;
-mod tx_sheppard;
+mod tx;
+mod tx_cost;
+mod watch;
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let args::Args { command } = args::Args::parse();
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        // Not a failure.  Transactions were described instead of being sent.
+        Err(err) if err.is::<dry_run::Stopped>() => {
+            eprintln!("{}", dry_run::Stopped);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            // Same format as the one used by `Termination` for `Result<(), anyhow::Error>`.
+            eprintln!("Error: {err:?}");
+            exit_code::for_error(&err)
+        }
+    }
+}
+
+async fn run() -> Result<()> {
+    let args::Args {
+        output,
+        cluster: _,
+        config: _,
+        rpc_telemetry,
+        tx_records_file,
+        dry_run,
+        simulate,
+        command,
+    } = args::parse()?;
+
+    output::init(output);
+
+    if rpc_telemetry {
+        rpc_telemetry::enable();
+    }
+    if let Some(path) = &tx_records_file {
+        tx_records::open(path)?;
+    }
+    if dry_run {
+        dry_run::enable(simulate);
+    }
+
+    let res = run_command(command).await;
+
+    tx_records::close();
+
+    // The shell prints a summary after every command.
+    if rpc_telemetry::is_enabled() {
+        rpc_telemetry::print_summary();
+    }
+
+    res
+}
+
+async fn run_command(command: args::Command) -> Result<()> {
+    // Reads cached by an earlier command, in a `shell` session, or in `dev up`, are stale.
+    oracle::price_accounts::clear_cache();
 
     match command {
         args::Command::PrimordialAccounts(command) => primordial_accounts::run(command).await,
+        args::Command::Bootstrap(command) => bootstrap::run(command).await,
+        args::Command::Dev(command) => dev::run(command).await,
         args::Command::Transfer(command) => transfer::run(command).await,
+        args::Command::Stake(command) => stake::run(command).await,
         args::Command::StakeCapsParameters(command) => stake_caps_parameters::run(command).await,
         args::Command::Oracle(command) => oracle::run(command).await,
         args::Command::PriceStore(command) => price_store::run(command).await,
+        args::Command::Program(command) => program::run(command).await,
+        args::Command::Keys(command) => keys::run(command).await,
+        args::Command::Watch(command) => watch::run(command).await,
+        args::Command::Account(command) => account::run(command).await,
+        args::Command::Block(command) => block::run(command).await,
+        args::Command::Cluster(command) => cluster::run(command).await,
+        args::Command::Rpc(command) => rpc::run(command).await,
+        args::Command::Tx(command) => tx::run(command).await,
+        args::Command::Benchmark(command) => benchmark::run(command).await,
+        args::Command::Shell => shell::run().await,
     }
 }
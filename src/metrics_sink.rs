@@ -0,0 +1,289 @@
+//! Pushes measurements to an endpoint that accepts the InfluxDB line protocol, such as InfluxDB
+//! itself, or VictoriaMetrics.  Lets long running commands be graphed without a separate scraping
+//! setup.
+//!
+//! Points are batched, and sent from a background task.  Failures to send are logged, but do not
+//! affect the command that produces the points.
+
+use std::{
+    fmt::Write as _,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use log::warn;
+use reqwest::{Client, Url};
+use tokio::{
+    select,
+    sync::mpsc,
+    task::JoinHandle,
+    time::{MissedTickBehavior, interval},
+};
+
+/// Points accumulated while the endpoint is not accepting writes are dropped after this limit.
+const MAX_BUFFERED_LINES: usize = 100_000;
+
+/// A batch is sent before the flush interval elapses, if it gets this large.
+const MAX_BATCH_LINES: usize = 5_000;
+
+#[derive(Debug, Clone)]
+pub struct MetricsConfig {
+    /// Full write URL, including any database, bucket, or organization query parameters.  For
+    /// example:
+    ///
+    ///   http://localhost:8086/api/v2/write?org=pyth&bucket=soak&precision=ns
+    ///   http://localhost:8428/write
+    pub url: Url,
+    /// Sent as `Authorization: Token <token>`.
+    pub token: Option<String>,
+    /// Tags added to every point.
+    pub tags: Vec<(String, String)>,
+    pub flush_interval: Duration,
+}
+
+/// Accepts points, and sends them to the configured endpoint in the background.  Call
+/// [`close()`](Self::close) to send the remaining points before exiting.
+pub struct MetricsSink {
+    lines: mpsc::UnboundedSender<String>,
+    tags: String,
+    flush_task: JoinHandle<()>,
+}
+
+/// A single measurement, in the line protocol format.  Constructed via [`MetricsSink::point()`].
+pub struct Point {
+    measurement: String,
+    tags: String,
+    fields: String,
+    timestamp: u128,
+}
+
+impl MetricsSink {
+    /// Starts the background task that sends the points.  Must be called inside a Tokio runtime.
+    pub fn start(
+        MetricsConfig {
+            url,
+            token,
+            tags,
+            flush_interval,
+        }: MetricsConfig,
+    ) -> Self {
+        let mut tags_text = String::new();
+        for (key, value) in &tags {
+            write!(tags_text, ",{}={}", escape_key(key), escape_key(value))
+                .expect("Writing into a String never fails");
+        }
+
+        let (lines_tx, lines_rx) = mpsc::unbounded_channel();
+        let flush_task = tokio::spawn(run_flush_loop(url, token, flush_interval, lines_rx));
+
+        Self {
+            lines: lines_tx,
+            tags: tags_text,
+            flush_task,
+        }
+    }
+
+    /// Starts a new point, timestamped now.  Pass it to [`submit()`](Self::submit) once all the
+    /// tags and fields are added.
+    pub fn point(&self, measurement: &str) -> Point {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_nanos());
+
+        Point {
+            measurement: escape_measurement(measurement),
+            tags: self.tags.clone(),
+            fields: String::new(),
+            timestamp,
+        }
+    }
+
+    /// Points without any fields are not valid in the line protocol, and are ignored.
+    pub fn submit(&self, point: Point) {
+        let Point {
+            measurement,
+            tags,
+            fields,
+            timestamp,
+        } = point;
+
+        if fields.is_empty() {
+            return;
+        }
+
+        // The flush task only stops after all the senders are gone.
+        let _ = self
+            .lines
+            .send(format!("{measurement}{tags} {fields} {timestamp}"));
+    }
+
+    /// Sends all the remaining points, and stops the background task.
+    pub async fn close(self) {
+        let Self {
+            lines,
+            tags: _,
+            flush_task,
+        } = self;
+
+        drop(lines);
+        if let Err(err) = flush_task.await {
+            warn!("Metrics flush task failed: {err}");
+        }
+    }
+}
+
+impl Point {
+    pub fn tag(mut self, key: &str, value: impl ToString) -> Self {
+        write!(
+            self.tags,
+            ",{}={}",
+            escape_key(key),
+            escape_key(&value.to_string())
+        )
+        .expect("Writing into a String never fails");
+        self
+    }
+
+    pub fn field_i64(self, key: &str, value: i64) -> Self {
+        self.field(key, format_args!("{value}i"))
+    }
+
+    /// Values that do not fit into an `i64` are sent as floats, as not all the line protocol
+    /// implementations support unsigned integers.
+    pub fn field_u64(self, key: &str, value: u64) -> Self {
+        match i64::try_from(value) {
+            Ok(value) => self.field_i64(key, value),
+            Err(_) => self.field_f64(key, value as f64),
+        }
+    }
+
+    /// Non finite values can not be represented, and are skipped.
+    pub fn field_f64(self, key: &str, value: f64) -> Self {
+        if !value.is_finite() {
+            return self;
+        }
+        self.field(key, format_args!("{value:?}"))
+    }
+
+    pub fn field_bool(self, key: &str, value: bool) -> Self {
+        self.field(key, format_args!("{value}"))
+    }
+
+    pub fn field_str(self, key: &str, value: &str) -> Self {
+        let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+        self.field(key, format_args!("\"{value}\""))
+    }
+
+    fn field(mut self, key: &str, value: std::fmt::Arguments<'_>) -> Self {
+        if !self.fields.is_empty() {
+            self.fields.push(',');
+        }
+        write!(self.fields, "{}={value}", escape_key(key))
+            .expect("Writing into a String never fails");
+        self
+    }
+}
+
+async fn run_flush_loop(
+    url: Url,
+    token: Option<String>,
+    flush_interval: Duration,
+    mut lines_rx: mpsc::UnboundedReceiver<String>,
+) {
+    let client = Client::new();
+    let mut buffer: Vec<String> = vec![];
+    let mut dropped = 0;
+
+    let mut flush_timer = interval(flush_interval);
+    flush_timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        let closed = select! {
+            line = lines_rx.recv() => match line {
+                Some(line) => {
+                    if buffer.len() >= MAX_BUFFERED_LINES {
+                        dropped += 1;
+                    } else {
+                        buffer.push(line);
+                    }
+                    if buffer.len() < MAX_BATCH_LINES {
+                        continue;
+                    }
+                    false
+                }
+                None => true,
+            },
+            _at = flush_timer.tick() => false,
+        };
+
+        if dropped != 0 {
+            warn!("Metrics endpoint is not keeping up, dropped {dropped} points");
+            dropped = 0;
+        }
+
+        if !buffer.is_empty() && send_batch(&client, &url, token.as_deref(), &buffer).await {
+            buffer.clear();
+        }
+
+        if closed {
+            if !buffer.is_empty() {
+                warn!(
+                    "Failed to send the last {} points to the metrics endpoint",
+                    buffer.len()
+                );
+            }
+            return;
+        }
+    }
+}
+
+/// Returns `true` if the batch was accepted.
+async fn send_batch(client: &Client, url: &Url, token: Option<&str>, lines: &[String]) -> bool {
+    let mut request = client.post(url.clone()).body(lines.join("\n"));
+    if let Some(token) = token {
+        request = request.header("Authorization", format!("Token {token}"));
+    }
+
+    match request.send().await {
+        Ok(response) if response.status().is_success() => true,
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            warn!(
+                "Metrics endpoint rejected {} points: {status}: {body}",
+                lines.len()
+            );
+            // Retrying a batch the endpoint does not accept would only block the following ones.
+            status.is_client_error()
+        }
+        Err(err) => {
+            warn!(
+                "Failed to send {} points to the metrics endpoint: {err}",
+                lines.len()
+            );
+            false
+        }
+    }
+}
+
+fn escape_measurement(value: &str) -> String {
+    let mut res = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | ' ') {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+    res
+}
+
+/// Tag keys, tag values, and field keys share the same escaping rules.
+fn escape_key(value: &str) -> String {
+    let mut res = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | '=' | ' ') {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+    res
+}
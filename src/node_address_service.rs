@@ -9,6 +9,7 @@
 
 use std::{
     collections::{HashMap, VecDeque},
+    fmt,
     net::SocketAddr,
     str::FromStr as _,
     sync::{Arc, RwLock},
@@ -20,7 +21,6 @@ use futures::StreamExt as _;
 use log::{trace, warn};
 use solana_program::pubkey::Pubkey;
 use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
-use solana_rpc_client::nonblocking::rpc_client::RpcClient;
 use solana_rpc_client_api::{
     client_error::Result as ClientResult,
     response::{RpcContactInfo, SlotUpdate},
@@ -33,6 +33,8 @@ use tokio::{
 };
 use tokio_util::sync::CancellationToken;
 
+use crate::cluster_rpc::ClusterRpc;
+
 pub mod runner;
 
 /// A convenient way to use a [`NodeAddressService`] in your code.  [`with_node_address_service`]
@@ -41,16 +43,37 @@ pub mod runner;
 /// the [`BlockhashCache`] and [`NodeAddressService`] available for consumption.
 pub use runner::with_node_address_service;
 
+/// Leader of a slot, according to the leader schedule cached by the [`NodeAddressService`].
+#[derive(Debug, Clone, Copy)]
+pub struct SlotLeader {
+    pub slot: Slot,
+    pub identity: Pubkey,
+    /// `None` when the leader does not advertise a TPU address in gossip.  Transactions sent
+    /// directly to the leaders do not reach it.
+    pub tpu: Option<SocketAddr>,
+}
+
+impl fmt::Display for SlotLeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.identity)?;
+        if self.tpu.is_none() {
+            write!(f, " (no TPU address)")?;
+        }
+        Ok(())
+    }
+}
+
 /// Service that tracks upcoming leaders and maintains an up-to-date mapping of leader id to their
 /// TPU socket address.
+#[derive(Clone)]
 pub struct NodeAddressService {
     recent_slots: RecentLeaderSlots,
     leader_tpu_cache: Arc<RwLock<LeaderTpuCache>>,
 }
 
 impl NodeAddressService {
-    pub async fn init(
-        rpc_client: Arc<RpcClient>,
+    pub async fn init<Rpc: ClusterRpc + ?Sized + 'static>(
+        rpc_client: Arc<Rpc>,
         websocket_url: &str,
         exit: CancellationToken,
     ) -> Result<(Self, JoinHandle<Result<()>>)> {
@@ -102,6 +125,16 @@ impl NodeAddressService {
         self.recent_slots.estimated_current_slot()
     }
 
+    /// Leader of the [`estimated_current_slot()`](Self::estimated_current_slot), if the cached
+    /// leader schedule covers it.
+    pub fn current_leader(&self) -> Option<SlotLeader> {
+        let current_slot = self.recent_slots.estimated_current_slot();
+        self.leader_tpu_cache
+            .read()
+            .unwrap()
+            .get_slot_leader_info(current_slot)
+    }
+
     pub fn get_tpu_for_next_in_schedule(&self, out: &mut Vec<SocketAddr>, fanout_slots: u64) {
         let current_slot = self.recent_slots.estimated_current_slot();
         self.leader_tpu_cache
@@ -110,8 +143,8 @@ impl NodeAddressService {
             .get_leader_sockets(out, current_slot, fanout_slots);
     }
 
-    async fn run(
-        rpc_client: Arc<RpcClient>,
+    async fn run<Rpc: ClusterRpc + ?Sized>(
+        rpc_client: Arc<Rpc>,
         recent_slots: RecentLeaderSlots,
         leader_tpu_cache: Arc<RwLock<LeaderTpuCache>>,
         pubsub_client: Option<PubsubClient>,
@@ -213,10 +246,10 @@ impl LeaderTpuCacheUpdateInfo {
     }
 }
 
-async fn maybe_fetch_cache_info(
+async fn maybe_fetch_cache_info<Rpc: ClusterRpc + ?Sized>(
     leader_tpu_cache: &Arc<RwLock<LeaderTpuCache>>,
     last_cluster_refresh: Instant,
-    rpc_client: &RpcClient,
+    rpc_client: &Rpc,
     recent_slots: &RecentLeaderSlots,
 ) -> LeaderTpuCacheUpdateInfo {
     let estimated_current_slot = recent_slots.estimated_current_slot();
@@ -344,6 +377,15 @@ impl LeaderTpuCache {
         }
     }
 
+    fn get_slot_leader_info(&self, slot: Slot) -> Option<SlotLeader> {
+        let identity = *self.get_slot_leader(slot)?;
+        Some(SlotLeader {
+            slot,
+            identity,
+            tpu: self.leader_tpu_map.get(&identity).copied(),
+        })
+    }
+
     fn extract_cluster_tpu_sockets(
         cluster_contact_info: Vec<RpcContactInfo>,
     ) -> HashMap<Pubkey, SocketAddr> {
@@ -11,7 +11,7 @@ use solana_rpc_client::nonblocking::rpc_client::RpcClient;
 use tokio::{pin, select};
 use tokio_util::sync::CancellationToken;
 
-use crate::blockhash_cache::BlockhashCache;
+use crate::{blockhash_cache::BlockhashCache, session};
 
 use super::NodeAddressService;
 
@@ -45,6 +45,9 @@ impl<'websocket_url> RunWithNodeAddressServiceArgs<'websocket_url> {
 
     /// Runs the specified asynchronous operation with an access to a [`BlockhashCache`] instance,
     /// that is kept up to date.
+    ///
+    /// Inside a `shell` session, the session [`BlockhashCache`] and [`NodeAddressService`] are
+    /// used, and they are kept running after the operation completes.
     pub async fn run<'context, T, Op>(self, op: Op) -> Result<T>
     where
         Op: AsyncFnOnce(&BlockhashCache, NodeAddressService) -> T + 'websocket_url + 'context,
@@ -56,6 +59,22 @@ impl<'websocket_url> RunWithNodeAddressServiceArgs<'websocket_url> {
             shutdown,
         } = self;
 
+        if let Some(session) = session::get() {
+            let blockhash_cache = session.blockhash_cache(&rpc_client).await;
+            let node_address_service = session
+                .node_address_service(&rpc_client, websocket_url)
+                .await?;
+            if let (Some(blockhash_cache), Some(node_address_service)) =
+                (blockhash_cache, node_address_service)
+            {
+                let op_res = op(&blockhash_cache, node_address_service).await;
+                if let Some(shutdown) = shutdown {
+                    shutdown.cancel();
+                }
+                return Ok(op_res);
+            }
+        }
+
         let shutdown = shutdown.unwrap_or_else(CancellationToken::new);
 
         let blockhash_cache = BlockhashCache::uninitialized();
@@ -1,32 +1,46 @@
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 
-use crate::args::oracle::Command;
+use crate::{args::oracle::Command, exit_code::ValidationFailed};
 
-pub mod accounts;
+mod accumulator;
+mod accumulator_status;
 mod add_price;
 mod add_product;
 mod add_publisher;
+mod addresses;
+pub mod feed_index;
 mod get_price_feed_index;
 mod init_mapping;
-pub mod instructions;
+pub mod price_accounts;
+mod publisher_matrix;
+mod stale_aggregates;
+mod sync_parameters;
 mod update_permissions;
+mod verify_accumulator;
 
 pub async fn run(command: Command) -> Result<()> {
     match command {
         Command::UpdatePermissions(args) => update_permissions::run(args).await,
         Command::InitMapping(args) => init_mapping::run(args).await,
         Command::AddProduct(args) => {
-            args.check_are_valid()?;
+            args.check_are_valid().context(ValidationFailed)?;
             add_product::run(args).await
         }
         Command::AddPrice(args) => {
-            args.check_are_valid()?;
+            args.check_are_valid().context(ValidationFailed)?;
             add_price::run(args).await
         }
         Command::AddPublisher(args) => {
-            args.check_are_valid()?;
+            args.check_are_valid().context(ValidationFailed)?;
             add_publisher::run(args).await
         }
         Command::GetPriceFeedIndex(args) => get_price_feed_index::run(args).await,
+        Command::AccumulatorStatus(args) => accumulator_status::run(args).await,
+        Command::SyncParameters(args) => sync_parameters::run(args).await,
+        Command::VerifyAccumulator(args) => verify_accumulator::run(args).await,
+        Command::PublisherMatrix(args) => publisher_matrix::run(args).await,
+        Command::StaleAggregates(args) => stale_aggregates::run(args).await,
+        Command::Addresses(args) => addresses::run(args).await,
+        Command::FeedIndex(command) => feed_index::run(command).await,
     }
 }
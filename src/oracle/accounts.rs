@@ -2,7 +2,15 @@
 
 use bytemuck::{Pod, Zeroable};
 
+pub mod message_buffer;
 pub mod price;
+pub mod product;
+
+/// Value of [`AccountHeader::magic_number`] in all Oracle accounts.
+pub const PC_MAGIC: u32 = 0xa1b2c3d4;
+
+/// Value of [`AccountHeader::account_type`] in price accounts.
+pub const PC_ACCTYPE_PRICE: u32 = 3;
 
 #[repr(C)]
 #[derive(Copy, Clone, Zeroable, Pod)]
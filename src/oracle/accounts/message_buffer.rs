@@ -0,0 +1,145 @@
+//! Describes a message buffer account of the Message Buffer program.
+//!
+//! Before [`PriceAccountFlags::ACCUMULATOR_V2`], the Oracle program wrote a message for every
+//! price update into a message buffer account, via a CPI into the Message Buffer program.  The
+//! validator then collected messages from all the buffers into the accumulator.  With
+//! `ACCUMULATOR_V2`, the validator generates messages from the price accounts directly, and the
+//! Oracle program clears the buffer once, setting [`PriceAccountFlags::MESSAGE_BUFFER_CLEARED`].
+//!
+//! The layout is based on the
+//! `target_chains/solana/programs/message_buffer/src/state/message_buffer.rs` file from the
+//! `https://github.com/pyth-network/pyth-crosschain.git` repository.
+//!
+//! [`PriceAccountFlags::ACCUMULATOR_V2`]: super::price::PriceAccountFlags::ACCUMULATOR_V2
+//! [`PriceAccountFlags::MESSAGE_BUFFER_CLEARED`]:
+//!     super::price::PriceAccountFlags::MESSAGE_BUFFER_CLEARED
+
+use std::fmt::{self, Display};
+
+use solana_program::{pubkey, pubkey::Pubkey};
+
+/// Message Buffer program address on Pythnet.
+pub const MESSAGE_BUFFER_PROGRAM_ID: Pubkey =
+    pubkey!("7Vbmv1jt4vyuqBZcpYPpnVhrqVe5e6ZPb6JxDcffRHUM");
+
+/// Seed of the Oracle program PDA, that the Message Buffer program accepts as a CPI caller.
+pub const UPD_PRICE_WRITE_SEED: &[u8] = b"upd_price_write";
+
+/// Seed of the message buffer account PDA.
+pub const MESSAGE_SEED: &[u8] = b"message";
+
+/// Maximum number of messages in one buffer.
+pub const MAX_MESSAGES: usize = 255;
+
+/// Size of the Anchor account discriminator, that precedes the header.
+const DISCRIMINATOR_SIZE: usize = 8;
+
+/// `bump`, `version`, `header_len`, and `end_offsets`.
+const HEADER_SIZE: usize = 1 + 1 + 2 + 2 * MAX_MESSAGES;
+
+/// Address of the message buffer account for the `price_account`.
+pub fn message_buffer_address(
+    oracle_program_id: &Pubkey,
+    message_buffer_program_id: &Pubkey,
+    price_account: &Pubkey,
+) -> Pubkey {
+    let (cpi_caller_auth, _bump) =
+        cpi_caller_authority(oracle_program_id, message_buffer_program_id);
+    let (address, _bump) = Pubkey::find_program_address(
+        &[
+            MESSAGE_SEED,
+            cpi_caller_auth.as_ref(),
+            price_account.as_ref(),
+        ],
+        message_buffer_program_id,
+    );
+    address
+}
+
+/// Authority the Oracle program signs with, when it writes into the message buffer accounts, and
+/// its bump seed.  Message buffer accounts are derived from it.
+pub fn cpi_caller_authority(
+    oracle_program_id: &Pubkey,
+    message_buffer_program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[UPD_PRICE_WRITE_SEED, message_buffer_program_id.as_ref()],
+        oracle_program_id,
+    )
+}
+
+#[derive(Debug)]
+pub struct MessageBuffer<'data> {
+    pub bump: u8,
+    pub version: u8,
+    /// Messages, in the order they were written by the Oracle program.
+    pub messages: Vec<&'data [u8]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageBufferError {
+    TooShort { len: usize },
+    InvalidHeaderLen { header_len: u16 },
+    InvalidEndOffset { index: usize, end_offset: u16 },
+}
+
+impl Display for MessageBufferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooShort { len } => write!(
+                f,
+                "Account is too short for a message buffer: {len} bytes, need at least {}",
+                DISCRIMINATOR_SIZE + HEADER_SIZE
+            ),
+            Self::InvalidHeaderLen { header_len } => {
+                write!(f, "Header length is outside of the account: {header_len}")
+            }
+            Self::InvalidEndOffset { index, end_offset } => write!(
+                f,
+                "Message {index} end offset is outside of the account: {end_offset}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MessageBufferError {}
+
+impl<'data> MessageBuffer<'data> {
+    /// Decodes the account data, including the Anchor discriminator.
+    pub fn decode(data: &'data [u8]) -> Result<Self, MessageBufferError> {
+        let Some(header) = data.get(DISCRIMINATOR_SIZE..DISCRIMINATOR_SIZE + HEADER_SIZE) else {
+            return Err(MessageBufferError::TooShort { len: data.len() });
+        };
+
+        let bump = header[0];
+        let version = header[1];
+        let header_len = u16::from_le_bytes([header[2], header[3]]);
+
+        let messages_start = DISCRIMINATOR_SIZE + usize::from(header_len);
+        let Some(messages_data) = data.get(messages_start..) else {
+            return Err(MessageBufferError::InvalidHeaderLen { header_len });
+        };
+
+        // Unused entries at the end of `end_offsets` are zero.
+        let mut messages = vec![];
+        let mut start = 0;
+        for (index, end_offset) in header[4..].chunks_exact(2).enumerate() {
+            let end_offset = u16::from_le_bytes([end_offset[0], end_offset[1]]);
+            let end = usize::from(end_offset);
+            if end <= start {
+                break;
+            }
+            let Some(message) = messages_data.get(start..end) else {
+                return Err(MessageBufferError::InvalidEndOffset { index, end_offset });
+            };
+            messages.push(message);
+            start = end;
+        }
+
+        Ok(Self {
+            bump,
+            version,
+            messages,
+        })
+    }
+}
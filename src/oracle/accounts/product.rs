@@ -0,0 +1,63 @@
+//! Describes a `product` account of the Oracle program.
+
+use std::mem::size_of;
+
+use anyhow::{Result, bail};
+use bytemuck::{Pod, Zeroable, pod_read_unaligned};
+use solana_program::pubkey::Pubkey;
+
+use super::AccountHeader;
+
+/// Fixed part of a product account.  It is followed by the product metadata, up to the
+/// [`AccountHeader::size`] bytes of the account.
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct ProductAccount {
+    pub header: AccountHeader,
+    /// First price account in the list of the product prices.
+    pub first_price_account: Pubkey,
+}
+
+impl ProductAccount {
+    /// Decodes the product metadata: key and value pairs, such as `symbol`.  Every key and every
+    /// value is stored as a length byte, followed by the string bytes.
+    pub fn metadata(data: &[u8]) -> Result<Vec<(String, String)>> {
+        let Some(fixed) = data.get(..size_of::<ProductAccount>()) else {
+            bail!(
+                "Account is too small for a product account.  Expected at least {} bytes, got {}",
+                size_of::<ProductAccount>(),
+                data.len()
+            );
+        };
+        let product: ProductAccount = pod_read_unaligned(fixed);
+
+        let end = usize::try_from(product.header.size)
+            .unwrap_or(usize::MAX)
+            .min(data.len());
+        let mut rest = data
+            .get(size_of::<ProductAccount>()..end)
+            .unwrap_or_default();
+
+        let mut metadata = vec![];
+        while !rest.is_empty() {
+            let key = read_string(&mut rest)?;
+            let value = read_string(&mut rest)?;
+            metadata.push((key, value));
+        }
+
+        Ok(metadata)
+    }
+}
+
+fn read_string(data: &mut &[u8]) -> Result<String> {
+    let Some((len, tail)) = data.split_first() else {
+        bail!("Product metadata ends in the middle of a key and value pair");
+    };
+    let len = usize::from(*len);
+    if tail.len() < len {
+        bail!("Product metadata string is cut short");
+    }
+    let (string, tail) = tail.split_at(len);
+    *data = tail;
+    Ok(String::from_utf8_lossy(string).into_owned())
+}
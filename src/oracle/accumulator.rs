@@ -0,0 +1,342 @@
+//! Reads the state involved in getting Oracle price updates into the accumulator, and checks that
+//! it is consistent.  Downstream Wormhole consumers only see prices that made it into the
+//! accumulator.
+//!
+//! Price accounts with [`PriceAccountFlags::ACCUMULATOR_V2`] are accumulated by the validator
+//! directly, and need a feed index.  For other price accounts, the Oracle program writes a message
+//! into a message buffer account on every update, and the message needs to match the price
+//! account.
+
+use std::{fmt::Write as _, mem::size_of};
+
+use anyhow::{Context as _, Result, bail};
+use bytemuck::pod_read_unaligned;
+use itertools::izip;
+use pythnet_heisenberg::oracle::{
+    accounts::{
+        message_buffer::{MessageBuffer, message_buffer_address},
+        price::{PriceAccount, PriceAccountFlags},
+    },
+    messages::{Message, PriceFeedMessage},
+};
+use serde_json::{Value, json};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::request::MAX_MULTIPLE_ACCOUNTS;
+use solana_sdk::{clock::Slot, pubkey::Pubkey};
+
+use crate::args::oracle::accumulator_status::AccumulatorArgs;
+
+/// State of a single price account, as seen at one slot.
+pub struct AccumulatorState {
+    pub price_pubkey: Pubkey,
+    pub slot: Slot,
+    pub flags: PriceAccountFlags,
+    pub feed_index: u32,
+    /// Slot of the last aggregation.
+    pub pub_slot: u64,
+    /// The message the Oracle program should have written for the current price.
+    pub expected: PriceFeedMessage,
+    pub message_buffer: Pubkey,
+    pub buffer: BufferState,
+}
+
+pub enum BufferState {
+    Missing,
+    Invalid(String),
+    /// `None` for messages that could not be decoded.
+    Messages(Vec<Option<Message>>),
+}
+
+/// Reads all the price accounts, and their message buffer accounts.  A price account and its
+/// message buffer are always read in the same request, so they are from the same slot.
+pub async fn fetch_states(
+    rpc_client: &RpcClient,
+    AccumulatorArgs {
+        program_id,
+        message_buffer_program_id,
+        price_pubkey: price_pubkeys,
+    }: &AccumulatorArgs,
+) -> Result<Vec<AccumulatorState>> {
+    let mut states = Vec::with_capacity(price_pubkeys.len());
+
+    for chunk in price_pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS / 2) {
+        let message_buffers = chunk
+            .iter()
+            .map(|price_pubkey| {
+                message_buffer_address(program_id, message_buffer_program_id, price_pubkey)
+            })
+            .collect::<Vec<_>>();
+        let addresses = chunk
+            .iter()
+            .chain(&message_buffers)
+            .copied()
+            .collect::<Vec<_>>();
+
+        let response = rpc_client
+            .get_multiple_accounts_with_commitment(&addresses, rpc_client.commitment())
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to fetch {} price accounts, starting with {}",
+                    chunk.len(),
+                    chunk[0]
+                )
+            })?;
+        let slot = response.context.slot;
+        let (price_accounts, buffer_accounts) = response.value.split_at(chunk.len());
+
+        for (price_pubkey, price_account, message_buffer, buffer_account) in
+            izip!(chunk, price_accounts, message_buffers, buffer_accounts)
+        {
+            let Some(price_account) = price_account else {
+                bail!("Price account {price_pubkey} does not exist");
+            };
+            let Some(price_data) = price_account.data.get(..size_of::<PriceAccount>()) else {
+                bail!(
+                    "Account {price_pubkey} is too short for a price account: {} bytes",
+                    price_account.data.len()
+                );
+            };
+            let price_account: PriceAccount = pod_read_unaligned(price_data);
+
+            let buffer = match buffer_account {
+                None => BufferState::Missing,
+                Some(account) => match MessageBuffer::decode(&account.data) {
+                    Ok(MessageBuffer { messages, .. }) => {
+                        BufferState::Messages(messages.into_iter().map(Message::decode).collect())
+                    }
+                    Err(err) => BufferState::Invalid(err.to_string()),
+                },
+            };
+
+            states.push(AccumulatorState {
+                price_pubkey: *price_pubkey,
+                slot,
+                flags: price_account.flags,
+                feed_index: price_account.feed_index,
+                pub_slot: price_account.agg.pub_slot,
+                expected: PriceFeedMessage::for_price_account(price_pubkey, &price_account),
+                message_buffer,
+                buffer,
+            });
+        }
+    }
+
+    Ok(states)
+}
+
+impl AccumulatorState {
+    /// Describes all the reasons this price would not reach the accumulator correctly.  Empty if
+    /// everything is consistent.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = vec![];
+
+        if self.flags.contains(PriceAccountFlags::ACCUMULATOR_V2) {
+            if self.feed_index == 0 {
+                problems.push(
+                    "ACCUMULATOR_V2 is set, but there is no feed index, so the validator will not \
+                     accumulate this price"
+                        .to_owned(),
+                );
+            }
+            if self
+                .flags
+                .contains(PriceAccountFlags::MESSAGE_BUFFER_CLEARED)
+                && matches!(&self.buffer, BufferState::Messages(messages) if !messages.is_empty())
+            {
+                problems.push(
+                    "MESSAGE_BUFFER_CLEARED is set, but the message buffer still has messages"
+                        .to_owned(),
+                );
+            }
+            return problems;
+        }
+
+        // No messages are written until the first aggregation.
+        if self.pub_slot == 0 {
+            return problems;
+        }
+
+        let messages = match &self.buffer {
+            BufferState::Missing => {
+                problems.push(format!(
+                    "Message buffer account {} does not exist",
+                    self.message_buffer
+                ));
+                return problems;
+            }
+            BufferState::Invalid(err) => {
+                problems.push(format!("Message buffer is invalid: {err}"));
+                return problems;
+            }
+            BufferState::Messages(messages) => messages,
+        };
+
+        let price_feed = messages.iter().find_map(|message| match message {
+            Some(Message::PriceFeed(price_feed)) if price_feed.feed_id == self.price_pubkey => {
+                Some(price_feed)
+            }
+            _ => None,
+        });
+        match price_feed {
+            None => problems.push("Message buffer has no price feed message".to_owned()),
+            Some(price_feed) if *price_feed != self.expected => problems.push(format!(
+                "Price feed message does not match the price account: published at {}, price \
+                 {} ± {}, while the price account has {}, price {} ± {}",
+                price_feed.publish_time,
+                price_feed.price,
+                price_feed.conf,
+                self.expected.publish_time,
+                self.expected.price,
+                self.expected.conf,
+            )),
+            Some(_) => (),
+        }
+
+        problems
+    }
+
+    pub fn text(&self) -> String {
+        let mut text = format!("{}:\n", self.price_pubkey);
+        let flags = self
+            .flags
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        let flags = if flags.is_empty() {
+            "none".to_owned()
+        } else {
+            flags.join(" | ")
+        };
+
+        writeln!(text, "  Slot: {}", self.slot).expect("Writing into a String never fails");
+        writeln!(text, "  Flags: {flags}").expect("Writing into a String never fails");
+        writeln!(text, "  Feed index: {}", self.feed_index)
+            .expect("Writing into a String never fails");
+        writeln!(text, "  Last aggregation slot: {}", self.pub_slot)
+            .expect("Writing into a String never fails");
+        match &self.buffer {
+            BufferState::Missing => {
+                writeln!(text, "  Message buffer {}: missing", self.message_buffer)
+                    .expect("Writing into a String never fails");
+            }
+            BufferState::Invalid(err) => {
+                writeln!(text, "  Message buffer {}: {err}", self.message_buffer)
+                    .expect("Writing into a String never fails");
+            }
+            BufferState::Messages(messages) => {
+                writeln!(
+                    text,
+                    "  Message buffer {}: {} messages",
+                    self.message_buffer,
+                    messages.len()
+                )
+                .expect("Writing into a String never fails");
+                for message in messages {
+                    writeln!(text, "    {}", message_text(message.as_ref()))
+                        .expect("Writing into a String never fails");
+                }
+            }
+        }
+
+        let problems = self.problems();
+        if problems.is_empty() {
+            text.push_str("  Status: OK");
+        } else {
+            text.push_str("  Status: inconsistent");
+            for problem in problems {
+                write!(text, "\n    {problem}").expect("Writing into a String never fails");
+            }
+        }
+
+        text
+    }
+
+    pub fn json(&self) -> Value {
+        let flags = self
+            .flags
+            .iter_names()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        let buffer = match &self.buffer {
+            BufferState::Missing => Value::Null,
+            BufferState::Invalid(err) => json!({ "error": err }),
+            BufferState::Messages(messages) => json!({
+                "messages": messages
+                    .iter()
+                    .map(|message| message_json(message.as_ref()))
+                    .collect::<Vec<_>>(),
+            }),
+        };
+
+        json!({
+            "price": self.price_pubkey.to_string(),
+            "slot": self.slot,
+            "flags": flags,
+            "feed_index": self.feed_index,
+            "pub_slot": self.pub_slot,
+            "message_buffer": self.message_buffer.to_string(),
+            "buffer": buffer,
+            "problems": self.problems(),
+        })
+    }
+}
+
+fn message_text(message: Option<&Message>) -> String {
+    match message {
+        Some(Message::PriceFeed(PriceFeedMessage {
+            feed_id,
+            price,
+            conf,
+            exponent,
+            publish_time,
+            ..
+        })) => format!(
+            "Price feed {feed_id}: {price} ± {conf} × 10^{exponent}, published at {publish_time}"
+        ),
+        Some(Message::Twap(twap)) => format!(
+            "TWAP {}: published at slot {}",
+            twap.feed_id, twap.publish_slot
+        ),
+        Some(Message::Other { tag }) => format!("Unknown message type: {tag}"),
+        None => "Message could not be decoded".to_owned(),
+    }
+}
+
+fn message_json(message: Option<&Message>) -> Value {
+    match message {
+        Some(Message::PriceFeed(PriceFeedMessage {
+            feed_id,
+            price,
+            conf,
+            exponent,
+            publish_time,
+            prev_publish_time,
+            ema_price,
+            ema_conf,
+        })) => json!({
+            "type": "price_feed",
+            "feed_id": feed_id.to_string(),
+            "price": price,
+            "conf": conf,
+            "exponent": exponent,
+            "publish_time": publish_time,
+            "prev_publish_time": prev_publish_time,
+            "ema_price": ema_price,
+            "ema_conf": ema_conf,
+        }),
+        Some(Message::Twap(twap)) => json!({
+            "type": "twap",
+            "feed_id": twap.feed_id.to_string(),
+            "cumulative_price": twap.cumulative_price.to_string(),
+            "cumulative_conf": twap.cumulative_conf.to_string(),
+            "num_down_slots": twap.num_down_slots,
+            "exponent": twap.exponent,
+            "publish_time": twap.publish_time,
+            "prev_publish_time": twap.prev_publish_time,
+            "publish_slot": twap.publish_slot,
+        }),
+        Some(Message::Other { tag }) => json!({ "type": "unknown", "tag": tag }),
+        None => json!({ "type": "invalid" }),
+    }
+}
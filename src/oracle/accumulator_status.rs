@@ -0,0 +1,22 @@
+use anyhow::Result;
+use pythnet_heisenberg::output;
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, oracle::accumulator_status::AccumulatorStatusArgs},
+    oracle::accumulator::fetch_states,
+};
+
+pub async fn run(
+    AccumulatorStatusArgs {
+        json_rpc_url,
+        accumulator,
+    }: AccumulatorStatusArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    for state in fetch_states(&rpc_client, &accumulator).await? {
+        output::result(state.text(), state.json());
+    }
+
+    Ok(())
+}
@@ -1,18 +1,33 @@
-use anyhow::{Context as _, Result};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result, bail};
 use futures::{StreamExt as _, stream::FuturesUnordered};
 use itertools::izip;
+use pythnet_heisenberg::{
+    blockhash_cache::{BlockhashCache, with_blockhash},
+    dry_run,
+    keypair_ext::{read_keypair_file, read_or_generate_keypair_file},
+    oracle::{
+        accounts::price::PriceAccount,
+        instructions::add_price::{self, ACCOUNT_MIN_SIZE},
+    },
+    output,
+    rpc_client_ext::{RpcClientExt as _, TypedAccount},
+};
+use serde_json::json;
 use solana_program::{pubkey::Pubkey, system_instruction};
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{rent::Rent, signature::Keypair, signer::Signer as _, transaction::Transaction};
+use solana_sdk::{rent::Rent, signature::Keypair, signer::Signer as _};
 
 use crate::{
     args::{json_rpc_url_args::get_rpc_client, oracle::add_price::AddPriceArgs},
-    blockhash_cache::{BlockhashCache, with_blockhash},
-    keypair_ext::{read_keypair_file, read_or_generate_keypair_file},
+    exit_code::check_transactions,
 };
 
-use super::instructions::add_price::{self, ACCOUNT_MIN_SIZE};
-
 pub async fn run(
     AddPriceArgs {
         json_rpc_url,
@@ -21,6 +36,8 @@ pub async fn run(
         funding_keypair,
         product_pubkey: product_pubkeys,
         price_keypair: price_keypairs,
+        price_keypair_dir,
+        count,
         exponent: exponents,
     }: AddPriceArgs,
 ) -> Result<()> {
@@ -30,25 +47,74 @@ pub async fn run(
     let funding = read_keypair_file(&funding_keypair)?;
     let funding_pubkey = funding.pubkey();
 
+    let (product_pubkeys, price_keypairs, exponents) = match &price_keypair_dir {
+        Some(dir) => generated_price_keypairs(
+            dir,
+            count.expect("`--count` is required with `--price-keypair-dir`"),
+            &product_pubkeys,
+            &exponents,
+        )?,
+        None => (product_pubkeys, price_keypairs, exponents),
+    };
+
     let prices = price_keypairs
         .into_iter()
         .map(|keypair| read_or_generate_keypair_file(&keypair))
         .collect::<Result<Vec<_>>>()?;
 
+    // Price accounts that exist already were added by an earlier run, that was interrupted or had
+    // some of its transactions fail.
+    let existing = rpc_client
+        .get_accounts_chunked::<PriceAccount>(
+            &prices
+                .iter()
+                .map(|price| price.pubkey())
+                .collect::<Vec<_>>(),
+        )
+        .await
+        .context("Failed to check for existing price accounts")?;
+    let mut to_add = vec![];
+    for (product_pubkey, price, exponent, account) in
+        izip!(&product_pubkeys, &prices, &exponents, existing)
+    {
+        match account {
+            None => to_add.push((*product_pubkey, price, *exponent)),
+            Some(TypedAccount { owner, data, .. })
+                if owner == program_id && data.product_account == *product_pubkey => {}
+            Some(_) => bail!(
+                "Price account {} already exists, but it is not a price of product \
+                 {product_pubkey} in {program_id}",
+                price.pubkey()
+            ),
+        }
+    }
+    if to_add.len() < prices.len() {
+        output::notice(format!(
+            "{} prices already exist, skipping them",
+            prices.len() - to_add.len()
+        ));
+    }
+
     let account_size = ACCOUNT_MIN_SIZE;
     let account_lamports = Rent::default()
         .minimum_balance(usize::try_from(account_size).expect("Account size fits into a usize"));
 
-    let total_additions = prices.len();
+    let total_additions = to_add.len();
+    let new_prices = to_add
+        .iter()
+        .map(|(_product, price, _exponent)| price.pubkey())
+        .collect::<HashSet<_>>();
 
     let mut successful_tx = 0;
     let mut failed_tx = 0;
 
-    println!("Adding {} prices in parallel...", total_additions);
+    output::notice(format!("Adding {total_additions} prices in parallel..."));
 
-    with_blockhash(rpc_client)
+    let (failed_tx, added) = with_blockhash(rpc_client)
         .run(async move |blockhash_cache: &BlockhashCache| {
-            let mut add_ops = izip!(&product_pubkeys, &prices, &exponents)
+            let mut added = HashSet::new();
+            let mut add_ops = to_add
+                .iter()
                 .map(|(product_pubkey, price, exponent)| {
                     add_one_price(
                         rpc_client,
@@ -70,31 +136,127 @@ pub async fn run(
                 match add_res {
                     Ok(AddDetails { product, price }) => {
                         successful_tx += 1;
-                        println!(
-                            "Add {} of {}: Success for product {} price {}",
-                            successful_tx + failed_tx,
-                            total_additions,
-                            product,
-                            price,
+                        output::result(
+                            format!(
+                                "Add {} of {}: Success for product {} price {}",
+                                successful_tx + failed_tx,
+                                total_additions,
+                                product,
+                                price,
+                            ),
+                            json!({
+                                "product": product.to_string(),
+                                "price": price.to_string(),
+                            }),
                         );
+                        added.insert(price);
                     }
+                    // Transactions were only described.
+                    Err(err) if err.is::<dry_run::Stopped>() => (),
                     Err(err) => {
                         failed_tx += 1;
-                        println!(
-                            "Add {} of {}: Error: {}",
-                            successful_tx + failed_tx,
-                            total_additions,
-                            err,
+                        output::result(
+                            format!(
+                                "Add {} of {}: Error: {}",
+                                successful_tx + failed_tx,
+                                total_additions,
+                                err,
+                            ),
+                            json!({ "error": format!("{err:#}") }),
                         );
                     }
                 }
             }
+
+            (failed_tx, added)
         })
         .await;
 
+    if dry_run::is_enabled() {
+        return Err(dry_run::Stopped.into());
+    }
+
+    if let Some(dir) = &price_keypair_dir {
+        // Prices added by this run, as well as by the earlier runs, in the keypair order.
+        let manifest = izip!(&product_pubkeys, &prices)
+            .map(|(product, price)| AddDetails {
+                product: *product,
+                price: price.pubkey(),
+            })
+            .filter(|AddDetails { price, .. }| !new_prices.contains(price) || added.contains(price))
+            .collect::<Vec<_>>();
+        write_manifest(rpc_client, dir, &manifest).await?;
+    }
+
+    check_transactions(failed_tx, total_additions)?;
+
     Ok(())
 }
 
+/// Paths of `count` price keypairs for every product, in the `dir`, with the product and the
+/// exponent lists extended to match.
+fn generated_price_keypairs(
+    dir: &Path,
+    count: usize,
+    product_pubkeys: &[Pubkey],
+    exponents: &[i32],
+) -> Result<(Vec<Pubkey>, Vec<PathBuf>, Vec<i32>)> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create directory: {}", dir.to_string_lossy()))?;
+
+    let mut products = vec![];
+    let mut keypairs = vec![];
+    let mut price_exponents = vec![];
+    for (product_pubkey, exponent) in product_pubkeys.iter().zip(exponents) {
+        for _ in 0..count {
+            keypairs.push(dir.join(format!("price-{}.json", keypairs.len())));
+            products.push(*product_pubkey);
+            price_exponents.push(*exponent);
+        }
+    }
+
+    Ok((products, keypairs, price_exponents))
+}
+
+/// Prints feed indices assigned to the `added` prices, and writes them into `manifest.csv` in the
+/// `dir`.  `added` includes the prices added by the earlier runs.
+async fn write_manifest(rpc_client: &RpcClient, dir: &Path, added: &[AddDetails]) -> Result<()> {
+    let prices = added
+        .iter()
+        .map(|details| details.price)
+        .collect::<Vec<_>>();
+    let accounts = rpc_client
+        .get_accounts_chunked::<PriceAccount>(&prices)
+        .await
+        .context("Failed to fetch feed indices of the added prices")?;
+
+    let mut manifest = "# product,price,feed_index\n".to_owned();
+    for (AddDetails { product, price }, account) in added.iter().zip(accounts) {
+        let Some(account) = account else {
+            bail!("Price account {price} does not exist after it was added");
+        };
+        let feed_index = account.data.feed_index;
+
+        manifest.push_str(&format!("{product},{price},{feed_index}\n"));
+        output::result(
+            format!("{product},{price},{feed_index}"),
+            json!({
+                "product": product.to_string(),
+                "price": price.to_string(),
+                "feed_index": feed_index,
+            }),
+        );
+    }
+
+    let manifest_file = dir.join("manifest.csv");
+    fs::write(&manifest_file, manifest).with_context(|| {
+        format!(
+            "Failed to write manifest into: {}",
+            manifest_file.to_string_lossy()
+        )
+    })
+}
+
 struct AddDetails {
     product: Pubkey,
     price: Pubkey,
@@ -116,33 +278,30 @@ async fn add_one_price(
 ) -> Result<AddDetails> {
     let price_pubkey = price_keypair.pubkey();
 
-    let transaction = Transaction::new_signed_with_payer(
-        &[
-            system_instruction::create_account(
-                &funding_pubkey,
-                &price_pubkey,
-                account_lamports,
-                account_size,
-                &program_id,
-            ),
-            add_price::instruction(
-                program_id,
-                funding_pubkey,
-                product_pubkey,
-                price_pubkey,
-                permissions_account,
-                exponent,
-            ),
-        ],
-        Some(&funding_pubkey),
-        &[&funding_keypair, &price_keypair],
-        blockhash_cache.get(),
-    );
-
     let _signature = rpc_client
-        .send_and_confirm_transaction(&transaction)
-        .await
-        .context("Transaction execution failed")?;
+        .send_with_payer_cached_blockhash(
+            &[
+                system_instruction::create_account(
+                    &funding_pubkey,
+                    &price_pubkey,
+                    account_lamports,
+                    account_size,
+                    &program_id,
+                ),
+                add_price::instruction(
+                    program_id,
+                    funding_pubkey,
+                    product_pubkey,
+                    price_pubkey,
+                    permissions_account,
+                    exponent,
+                ),
+            ],
+            Some(&funding_pubkey),
+            &[&funding_keypair, &price_keypair],
+            blockhash_cache,
+        )
+        .await?;
 
     Ok(AddDetails {
         product: product_pubkey,
@@ -1,21 +1,27 @@
-use anyhow::{Context as _, Result};
+use anyhow::Result;
 use futures::{StreamExt as _, stream::FuturesUnordered};
 use itertools::izip;
+use pythnet_heisenberg::{
+    blockhash_cache::{BlockhashCache, with_blockhash},
+    dry_run,
+    keypair_ext::{read_keypair_file, read_or_generate_keypair_file},
+    oracle::instructions::add_product::{self, ACCOUNT_MIN_SIZE},
+    output,
+    rpc_client_ext::RpcClientExt as _,
+};
+use serde_json::json;
 use solana_program::{pubkey::Pubkey, system_instruction};
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{rent::Rent, signature::Keypair, signer::Signer as _, transaction::Transaction};
+use solana_sdk::{rent::Rent, signature::Keypair, signer::Signer as _};
 
 use crate::{
     args::{
         json_rpc_url_args::get_rpc_client,
         oracle::add_product::{AddProductArgs, per_product_metadata},
     },
-    blockhash_cache::{BlockhashCache, with_blockhash},
-    keypair_ext::{read_keypair_file, read_or_generate_keypair_file},
+    exit_code::check_transactions,
 };
 
-use super::instructions::add_product::{self, ACCOUNT_MIN_SIZE};
-
 pub async fn run(
     AddProductArgs {
         json_rpc_url,
@@ -52,9 +58,9 @@ pub async fn run(
     let mut successful_tx = 0;
     let mut failed_tx = 0;
 
-    println!("Adding {} products in parallel...", total_additions);
+    output::notice(format!("Adding {total_additions} products in parallel..."));
 
-    with_blockhash(rpc_client)
+    let failed_tx = with_blockhash(rpc_client)
         .run(async move |blockhash_cache: &BlockhashCache| {
             let mut add_ops = izip!(&products, &metadata)
                 .map(|(product, metadata)| {
@@ -79,27 +85,43 @@ pub async fn run(
                 match add_res {
                     Ok(product_pubkey) => {
                         successful_tx += 1;
-                        println!(
-                            "Add {} of {}: Success for product {}",
-                            successful_tx + failed_tx,
-                            total_additions,
-                            product_pubkey,
+                        output::result(
+                            format!(
+                                "Add {} of {}: Success for product {}",
+                                successful_tx + failed_tx,
+                                total_additions,
+                                product_pubkey,
+                            ),
+                            json!({ "product": product_pubkey.to_string() }),
                         );
                     }
+                    // Transactions were only described.
+                    Err(err) if err.is::<dry_run::Stopped>() => (),
                     Err(err) => {
                         failed_tx += 1;
-                        println!(
-                            "Add {} of {}: Error: {}",
-                            successful_tx + failed_tx,
-                            total_additions,
-                            err,
+                        output::result(
+                            format!(
+                                "Add {} of {}: Error: {}",
+                                successful_tx + failed_tx,
+                                total_additions,
+                                err,
+                            ),
+                            json!({ "error": format!("{err:#}") }),
                         );
                     }
                 }
             }
+
+            failed_tx
         })
         .await;
 
+    if dry_run::is_enabled() {
+        return Err(dry_run::Stopped.into());
+    }
+
+    check_transactions(failed_tx, total_additions)?;
+
     Ok(())
 }
 
@@ -120,33 +142,30 @@ async fn add_one_product(
 ) -> Result<Pubkey> {
     let product_pubkey = product_keypair.pubkey();
 
-    let transaction = Transaction::new_signed_with_payer(
-        &[
-            system_instruction::create_account(
-                &funding_pubkey,
-                &product_pubkey,
-                account_lamports,
-                account_size,
-                &program_id,
-            ),
-            add_product::instruction(
-                program_id,
-                funding_pubkey,
-                mapping_pubkey,
-                product_pubkey,
-                permissions_account,
-                metadata,
-            ),
-        ],
-        Some(&funding_pubkey),
-        &[&funding_keypair, &mapping_keypair, &product_keypair],
-        blockhash_cache.get(),
-    );
-
     let _signature = rpc_client
-        .send_and_confirm_transaction(&transaction)
-        .await
-        .context("Transaction execution failed")?;
+        .send_with_payer_cached_blockhash(
+            &[
+                system_instruction::create_account(
+                    &funding_pubkey,
+                    &product_pubkey,
+                    account_lamports,
+                    account_size,
+                    &program_id,
+                ),
+                add_product::instruction(
+                    program_id,
+                    funding_pubkey,
+                    mapping_pubkey,
+                    product_pubkey,
+                    permissions_account,
+                    metadata,
+                ),
+            ],
+            Some(&funding_pubkey),
+            &[&funding_keypair, &mapping_keypair, &product_keypair],
+            blockhash_cache,
+        )
+        .await?;
 
     Ok(product_pubkey)
 }
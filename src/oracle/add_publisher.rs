@@ -1,18 +1,24 @@
-use anyhow::{Context as _, Result};
+use anyhow::Result;
 use futures::{StreamExt as _, stream::FuturesUnordered};
 use itertools::izip;
+use pythnet_heisenberg::{
+    blockhash_cache::{BlockhashCache, with_blockhash},
+    dry_run,
+    keypair_ext::read_keypair_file,
+    oracle::instructions::add_publisher,
+    output,
+    rpc_client_ext::RpcClientExt as _,
+};
+use serde_json::json;
 use solana_program::pubkey::Pubkey;
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{signature::Keypair, signer::Signer as _, transaction::Transaction};
+use solana_sdk::{signature::Keypair, signer::Signer as _};
 
 use crate::{
     args::{json_rpc_url_args::get_rpc_client, oracle::add_publisher::AddPublisherArgs},
-    blockhash_cache::{BlockhashCache, with_blockhash},
-    keypair_ext::read_keypair_file,
+    exit_code::check_transactions,
 };
 
-use super::instructions::add_publisher;
-
 pub async fn run(
     AddPublisherArgs {
         json_rpc_url,
@@ -39,9 +45,11 @@ pub async fn run(
     let mut successful_tx = 0;
     let mut failed_tx = 0;
 
-    println!("Adding {} publishers in parallel...", total_additions);
+    output::notice(format!(
+        "Adding {total_additions} publishers in parallel..."
+    ));
 
-    with_blockhash(rpc_client)
+    let failed_tx = with_blockhash(rpc_client)
         .run(async move |blockhash_cache: &BlockhashCache| {
             let mut add_ops = izip!(&prices, &publisher_pubkeys)
                 .map(|(price, publisher_pubkey)| {
@@ -62,28 +70,47 @@ pub async fn run(
                 match add_res {
                     Ok(AddDetails { price, publisher }) => {
                         successful_tx += 1;
-                        println!(
-                            "Add {} of {}: Success for price {} publisher {}",
-                            successful_tx + failed_tx,
-                            total_additions,
-                            price,
-                            publisher,
+                        output::result(
+                            format!(
+                                "Add {} of {}: Success for price {} publisher {}",
+                                successful_tx + failed_tx,
+                                total_additions,
+                                price,
+                                publisher,
+                            ),
+                            json!({
+                                "price": price.to_string(),
+                                "publisher": publisher.to_string(),
+                            }),
                         );
                     }
+                    // Transactions were only described.
+                    Err(err) if err.is::<dry_run::Stopped>() => (),
                     Err(err) => {
                         failed_tx += 1;
-                        println!(
-                            "Add {} of {}: Error: {}",
-                            successful_tx + failed_tx,
-                            total_additions,
-                            err,
+                        output::result(
+                            format!(
+                                "Add {} of {}: Error: {}",
+                                successful_tx + failed_tx,
+                                total_additions,
+                                err,
+                            ),
+                            json!({ "error": format!("{err:#}") }),
                         );
                     }
                 }
             }
+
+            failed_tx
         })
         .await;
 
+    if dry_run::is_enabled() {
+        return Err(dry_run::Stopped.into());
+    }
+
+    check_transactions(failed_tx, total_additions)?;
+
     Ok(())
 }
 
@@ -105,23 +132,20 @@ async fn add_one_publisher(
 ) -> Result<AddDetails> {
     let price_pubkey = price_keypair.pubkey();
 
-    let transaction = Transaction::new_signed_with_payer(
-        &[add_publisher::instruction(
-            program_id,
-            funding_pubkey,
-            price_pubkey,
-            permissions_account,
-            publisher_pubkey,
-        )],
-        Some(&funding_pubkey),
-        &[&funding_keypair, &price_keypair],
-        blockhash_cache.get(),
-    );
-
     let _signature = rpc_client
-        .send_and_confirm_transaction(&transaction)
-        .await
-        .context("Transaction execution failed")?;
+        .send_with_payer_cached_blockhash(
+            &[add_publisher::instruction(
+                program_id,
+                funding_pubkey,
+                price_pubkey,
+                permissions_account,
+                publisher_pubkey,
+            )],
+            Some(&funding_pubkey),
+            &[&funding_keypair, &price_keypair],
+            blockhash_cache,
+        )
+        .await?;
 
     Ok(AddDetails {
         price: price_pubkey,
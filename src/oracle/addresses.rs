@@ -0,0 +1,61 @@
+use anyhow::Result;
+use pythnet_heisenberg::{
+    oracle::{
+        accounts::message_buffer::{cpi_caller_authority, message_buffer_address},
+        instructions::default_permissions_account,
+    },
+    output,
+};
+use serde_json::json;
+use solana_program::{bpf_loader_upgradeable, pubkey::Pubkey};
+
+use crate::args::oracle::addresses::AddressesArgs;
+
+pub async fn run(
+    AddressesArgs {
+        program_id,
+        message_buffer_program_id,
+        price_pubkey,
+    }: AddressesArgs,
+) -> Result<()> {
+    let (permissions, permissions_bump) = default_permissions_account(program_id);
+    print_address("permissions", permissions, permissions_bump);
+
+    let (program_data, program_data_bump) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    print_address("program_data", program_data, program_data_bump);
+
+    let (cpi_caller_auth, cpi_caller_auth_bump) =
+        cpi_caller_authority(&program_id, &message_buffer_program_id);
+    print_address(
+        "upd_price_write_authority",
+        cpi_caller_auth,
+        cpi_caller_auth_bump,
+    );
+
+    for price in price_pubkey {
+        let message_buffer =
+            message_buffer_address(&program_id, &message_buffer_program_id, &price);
+        output::result(
+            format!("message_buffer for {price}: {message_buffer}"),
+            json!({
+                "name": "message_buffer",
+                "price": price.to_string(),
+                "address": message_buffer.to_string(),
+            }),
+        );
+    }
+
+    Ok(())
+}
+
+fn print_address(name: &str, address: Pubkey, bump: u8) {
+    output::result(
+        format!("{name}: {address} (bump {bump})"),
+        json!({
+            "name": name,
+            "address": address.to_string(),
+            "bump": bump,
+        }),
+    );
+}
@@ -0,0 +1,21 @@
+use anyhow::{Context as _, Result};
+
+use crate::{args::oracle::feed_index::Command, exit_code::ValidationFailed};
+
+mod allocate;
+mod check;
+mod gaps;
+mod list;
+pub mod usage;
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::List(args) => list::run(args).await,
+        Command::Gaps(args) => gaps::run(args).await,
+        Command::Allocate(args) => {
+            args.check_are_valid().context(ValidationFailed)?;
+            allocate::run(args).await
+        }
+        Command::Check(args) => check::run(args).await,
+    }
+}
@@ -0,0 +1,58 @@
+use anyhow::{Result, bail};
+use pythnet_heisenberg::output;
+use serde_json::json;
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, oracle::feed_index::allocate::AllocateArgs},
+    oracle::feed_index::usage::{FeedIndexUsage, Registry, Reservation},
+};
+
+pub async fn run(
+    AllocateArgs {
+        json_rpc_url,
+        source,
+        name,
+        count,
+        min_index,
+    }: AllocateArgs,
+) -> Result<()> {
+    let registry_path = source
+        .registry
+        .clone()
+        .expect("`check_are_valid()` requires `--registry`");
+
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let usage = FeedIndexUsage::load(&rpc_client, &source).await?;
+
+    let mut registry = Registry::read(&registry_path)?;
+    if registry
+        .reservations
+        .iter()
+        .any(|reservation| reservation.name == name)
+    {
+        bail!(
+            "Reservation \"{name}\" already exists in {}",
+            registry_path.to_string_lossy()
+        );
+    }
+
+    let range = usage.find_free(min_index, count)?;
+    let (start, end) = (*range.start(), *range.end());
+    registry.reservations.push(Reservation {
+        name: name.clone(),
+        start,
+        end,
+    });
+    registry.write(&registry_path)?;
+
+    output::result(
+        format!("Reserved feed indices {start}-{end} for \"{name}\""),
+        json!({
+            "name": name,
+            "start": start,
+            "end": end,
+        }),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use anyhow::{Result, bail};
+use pythnet_heisenberg::output;
+use serde_json::json;
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, oracle::feed_index::check::CheckArgs},
+    oracle::feed_index::usage::{Collision, FeedIndexUsage, Owner},
+};
+
+pub async fn run(
+    CheckArgs {
+        json_rpc_url,
+        source,
+        reservation: reservations,
+    }: CheckArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let mut usage = FeedIndexUsage::load(&rpc_client, &source).await?;
+    for (name, range) in reservations {
+        usage.add(range, Owner::Reservation(name));
+    }
+
+    let collisions = usage.collisions();
+    for Collision {
+        first,
+        second,
+        overlap,
+    } in &collisions
+    {
+        output::result(
+            format!(
+                "{}-{}: used by both {first} and {second}",
+                overlap.start(),
+                overlap.end()
+            ),
+            json!({
+                "start": overlap.start(),
+                "end": overlap.end(),
+                "first": first.to_string(),
+                "second": second.to_string(),
+            }),
+        );
+    }
+
+    if !collisions.is_empty() {
+        bail!("Found {} feed index collisions", collisions.len());
+    }
+
+    output::notice("No feed index collisions");
+    Ok(())
+}
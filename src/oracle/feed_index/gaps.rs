@@ -0,0 +1,34 @@
+use anyhow::Result;
+use pythnet_heisenberg::output;
+use serde_json::json;
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, oracle::feed_index::gaps::GapsArgs},
+    oracle::feed_index::usage::FeedIndexUsage,
+};
+
+pub async fn run(
+    GapsArgs {
+        json_rpc_url,
+        source,
+    }: GapsArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let usage = FeedIndexUsage::load(&rpc_client, &source).await?;
+
+    // Feed index 0 means "no feed index".
+    for gap in usage.gaps(1) {
+        let (start, end) = (*gap.start(), *gap.end());
+        output::result(
+            format!("{start}-{end} ({} indices)", end - start + 1),
+            json!({ "start": start, "end": end }),
+        );
+    }
+
+    let next_free = usage.max_used().map_or(1, |max_used| max_used + 1);
+    output::notice(format!(
+        "All feed indices starting with {next_free} are free"
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use anyhow::Result;
+use pythnet_heisenberg::output;
+use serde_json::json;
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, oracle::feed_index::list::ListArgs},
+    oracle::feed_index::usage::{FeedIndexUsage, Owner},
+};
+
+pub async fn run(
+    ListArgs {
+        json_rpc_url,
+        source,
+    }: ListArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let usage = FeedIndexUsage::load(&rpc_client, &source).await?;
+
+    for (range, owner) in &usage.ranges {
+        let (start, end) = (*range.start(), *range.end());
+        let text_range = if start == end {
+            start.to_string()
+        } else {
+            format!("{start}-{end}")
+        };
+        let (kind, id) = match owner {
+            Owner::PriceAccount(pubkey) => ("price_account", pubkey.to_string()),
+            Owner::Reservation(name) => ("reservation", name.clone()),
+        };
+        output::result(
+            format!("{text_range}: {owner}"),
+            json!({
+                "start": start,
+                "end": end,
+                "kind": kind,
+                "owner": id,
+            }),
+        );
+    }
+
+    output::notice(format!(
+        "{} entries, largest used feed index: {}",
+        usage.ranges.len(),
+        usage
+            .max_used()
+            .map_or_else(|| "none".to_owned(), |max_used| max_used.to_string())
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,233 @@
+//! Collects feed indices used on chain, by the Oracle price accounts, and reserved in a registry
+//! file, by test setups.
+
+use std::{
+    fmt::{self, Display},
+    fs,
+    ops::RangeInclusive,
+    path::Path,
+};
+
+use anyhow::{Context as _, Result, bail};
+use pythnet_heisenberg::price_store::instructions::submit_prices::FEED_INDEX_MAX;
+use serde::{Deserialize, Serialize};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{args::oracle::feed_index::FeedIndexSourceArgs, oracle::price_accounts};
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Registry {
+    #[serde(default)]
+    pub reservations: Vec<Reservation>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Reservation {
+    pub name: String,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl Registry {
+    /// A missing file is treated as an empty registry, so that the first `allocate` can create it.
+    pub fn read(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path).with_context(|| {
+            format!(
+                "Failed to read feed index registry: {}",
+                path.to_string_lossy()
+            )
+        })?;
+        let registry: Self = serde_yaml::from_str(&content).with_context(|| {
+            format!(
+                "Failed to parse feed index registry: {}",
+                path.to_string_lossy()
+            )
+        })?;
+
+        for Reservation { name, start, end } in &registry.reservations {
+            if start > end {
+                bail!(
+                    "{}: reservation \"{name}\" starts after it ends: {start}-{end}",
+                    path.to_string_lossy()
+                );
+            }
+        }
+
+        Ok(registry)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let content = serde_yaml::to_string(self).context("Constructing registry YAML")?;
+        fs::write(path, content).with_context(|| {
+            format!(
+                "Failed to write feed index registry: {}",
+                path.to_string_lossy()
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Owner {
+    PriceAccount(Pubkey),
+    Reservation(String),
+}
+
+impl Display for Owner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PriceAccount(pubkey) => write!(f, "price account {pubkey}"),
+            Self::Reservation(name) => write!(f, "reservation \"{name}\""),
+        }
+    }
+}
+
+/// All used feed index ranges, sorted by the range start.  Price accounts occupy single index
+/// ranges.
+#[derive(Debug, Default)]
+pub struct FeedIndexUsage {
+    pub ranges: Vec<(RangeInclusive<u32>, Owner)>,
+}
+
+/// Two owners that use the same feed indices.
+#[derive(Debug)]
+pub struct Collision<'usage> {
+    pub first: &'usage Owner,
+    pub second: &'usage Owner,
+    pub overlap: RangeInclusive<u32>,
+}
+
+impl FeedIndexUsage {
+    /// Loads usage from all the sources specified in the `source`.
+    pub async fn load(rpc_client: &RpcClient, source: &FeedIndexSourceArgs) -> Result<Self> {
+        let FeedIndexSourceArgs {
+            program_id,
+            registry,
+        } = source;
+
+        if program_id.is_none() && registry.is_none() {
+            bail!("Specify the Oracle --program-id, or a --registry, or both");
+        }
+
+        let mut usage = Self::default();
+        if let Some(program_id) = program_id {
+            for (pubkey, feed_index) in price_feed_indices(rpc_client, program_id).await? {
+                usage.add(feed_index..=feed_index, Owner::PriceAccount(pubkey));
+            }
+        }
+        if let Some(registry) = registry {
+            usage.add_registry(&Registry::read(registry)?);
+        }
+
+        Ok(usage)
+    }
+
+    pub fn add_registry(&mut self, registry: &Registry) {
+        for Reservation { name, start, end } in &registry.reservations {
+            self.add(*start..=*end, Owner::Reservation(name.clone()));
+        }
+    }
+
+    pub fn add(&mut self, range: RangeInclusive<u32>, owner: Owner) {
+        let pos = self
+            .ranges
+            .partition_point(|(existing, _)| existing.start() <= range.start());
+        self.ranges.insert(pos, (range, owner));
+    }
+
+    /// Largest used feed index, if any are used.
+    pub fn max_used(&self) -> Option<u32> {
+        self.ranges.iter().map(|(range, _)| *range.end()).max()
+    }
+
+    /// Unused ranges starting at `min_index`, up to the largest used index.
+    pub fn gaps(&self, min_index: u32) -> Vec<RangeInclusive<u32>> {
+        let mut gaps = vec![];
+        // Next index that is not known to be used.
+        let mut next = min_index;
+        for (range, _) in &self.ranges {
+            if *range.start() > next {
+                gaps.push(next..=range.start() - 1);
+            }
+            next = next.max(range.end().saturating_add(1));
+        }
+        gaps
+    }
+
+    /// First unused range of `count` indices, starting at `min_index` or above.
+    pub fn find_free(&self, min_index: u32, count: u32) -> Result<RangeInclusive<u32>> {
+        let fits =
+            |start: u32, end: u32| end.checked_sub(start).is_some_and(|len| len >= count - 1);
+
+        let first_fit = self
+            .gaps(min_index)
+            .into_iter()
+            .find(|gap| fits(*gap.start(), *gap.end()));
+        let start = match first_fit {
+            Some(gap) => *gap.start(),
+            None => self.max_used().map_or(min_index, |max_used| {
+                max_used.saturating_add(1).max(min_index)
+            }),
+        };
+
+        let end = start.saturating_add(count - 1);
+        if end > FEED_INDEX_MAX {
+            bail!("There is no free range of {count} feed indices below {FEED_INDEX_MAX}");
+        }
+        Ok(start..=end)
+    }
+
+    /// Price accounts that share feed indices with other price accounts, and reservations that
+    /// overlap other reservations.  A reservation may cover existing price accounts, as test
+    /// setups publish prices for existing feeds.
+    pub fn collisions(&self) -> Vec<Collision<'_>> {
+        let mut collisions = vec![];
+        for (i, (first_range, first)) in self.ranges.iter().enumerate() {
+            for (second_range, second) in &self.ranges[i + 1..] {
+                // Ranges are sorted by start, so no later range can overlap.
+                if second_range.start() > first_range.end() {
+                    break;
+                }
+                if std::mem::discriminant(first) != std::mem::discriminant(second) {
+                    continue;
+                }
+
+                let overlap = *second_range.start()..=*first_range.end().min(second_range.end());
+                collisions.push(Collision {
+                    first,
+                    second,
+                    overlap,
+                });
+            }
+        }
+        collisions
+    }
+}
+
+/// Feed indices of all the price accounts of the Oracle program.  Price accounts that did not get
+/// a feed index yet are skipped.
+pub async fn price_feed_indices(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<Vec<(Pubkey, u32)>> {
+    // Feed indices do not change once assigned, so any earlier read will do.
+    let accounts = price_accounts::fetch(
+        rpc_client,
+        program_id,
+        price_accounts::FEED_INDEX_DATA_LEN,
+        None,
+    )
+    .await?;
+
+    Ok(accounts
+        .with_feed_indices(&(1..=u32::MAX))
+        .map(|(pubkey, feed_index, _data)| (pubkey, feed_index))
+        .collect())
+}
@@ -1,11 +1,10 @@
 use anyhow::{Context as _, Result};
 use bytemuck::from_bytes;
+use pythnet_heisenberg::{oracle::accounts::price::PriceAccount, output};
+use serde_json::json;
 
-use crate::{
-    args::{
-        json_rpc_url_args::get_rpc_client, oracle::get_price_feed_index::GetPriceFeedIndexArgs,
-    },
-    oracle::accounts::price::PriceAccount,
+use crate::args::{
+    json_rpc_url_args::get_rpc_client, oracle::get_price_feed_index::GetPriceFeedIndexArgs,
 };
 
 pub async fn run(
@@ -23,7 +22,13 @@ pub async fn run(
 
     let price_account: &PriceAccount = from_bytes(&account.data);
 
-    println!("{}", price_account.feed_index);
+    output::result(
+        price_account.feed_index,
+        json!({
+            "price": price_pubkey.to_string(),
+            "feed_index": price_account.feed_index,
+        }),
+    );
 
     Ok(())
 }
@@ -1,14 +1,16 @@
 use anyhow::{Context as _, Result};
-use solana_program::system_instruction;
-use solana_sdk::{rent::Rent, signer::Signer as _};
-
-use crate::{
-    args::{json_rpc_url_args::get_rpc_client, oracle::init_mapping::InitMappingArgs},
+use pythnet_heisenberg::{
+    blockhash_cache::{BlockhashCache, with_blockhash},
     keypair_ext::{read_keypair_file, read_or_generate_keypair_file},
+    oracle::instructions::init_mapping::{self, ACCOUNT_MIN_SIZE},
+    output,
     rpc_client_ext::RpcClientExt as _,
 };
+use serde_json::json;
+use solana_program::system_instruction;
+use solana_sdk::{rent::Rent, signer::Signer as _};
 
-use super::instructions::init_mapping::{self, ACCOUNT_MIN_SIZE};
+use crate::args::{json_rpc_url_args::get_rpc_client, oracle::init_mapping::InitMappingArgs};
 
 pub async fn run(
     InitMappingArgs {
@@ -31,30 +33,41 @@ pub async fn run(
     let account_lamports = Rent::default()
         .minimum_balance(usize::try_from(account_size).expect("Account size fits into a usize"));
 
-    let signature = rpc_client
-        .send_with_payer_latest_blockhash_with_spinner(
-            &[
-                system_instruction::create_account(
-                    &funding_pubkey,
-                    &mapping_pubkey,
-                    account_lamports,
-                    account_size,
-                    &program_id,
-                ),
-                init_mapping::instruction(
-                    program_id,
-                    funding_pubkey,
-                    mapping_pubkey,
-                    permissions_account,
-                ),
-            ],
-            Some(&funding_pubkey),
-            &[&funding, &mapping],
-        )
+    let signature = with_blockhash(&rpc_client)
+        .run(async |blockhash_cache: &BlockhashCache| {
+            rpc_client
+                .send_with_payer_cached_blockhash(
+                    &[
+                        system_instruction::create_account(
+                            &funding_pubkey,
+                            &mapping_pubkey,
+                            account_lamports,
+                            account_size,
+                            &program_id,
+                        ),
+                        init_mapping::instruction(
+                            program_id,
+                            funding_pubkey,
+                            mapping_pubkey,
+                            permissions_account,
+                        ),
+                    ],
+                    Some(&funding_pubkey),
+                    &[&funding, &mapping],
+                    blockhash_cache,
+                )
+                .await
+        })
         .await
         .context("Transaction execution failed")?;
 
-    println!("Init mapping tx: {signature}");
+    output::result(
+        format!("Init mapping tx: {signature}"),
+        json!({
+            "signature": signature.to_string(),
+            "mapping": mapping_pubkey.to_string(),
+        }),
+    );
 
     Ok(())
 }
@@ -13,6 +13,10 @@ pub mod add_price;
 pub mod add_product;
 pub mod add_publisher;
 pub mod init_mapping;
+pub mod init_price;
+pub mod set_max_latency;
+pub mod set_min_pub;
+pub mod upd_price;
 pub mod update_permissions;
 
 pub const PC_VERSION: u32 = 2;
@@ -45,12 +49,39 @@ pub enum OracleCommand {
     // account[1] price account         [signer writable]
     // account[2] permissions account   []
     AddPublisher = 5,
+    /// Publish component price
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [writable]
+    // account[2] sysvar_clock account  []
+    #[allow(dead_code)]
+    UpdPrice = 7,
+    /// (Re)initialize price account
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    InitPrice = 9,
+    /// Set min publishers
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    SetMinPub = 12,
+    /// The same as `UpdPrice`, except that the transaction does not fail when the update is
+    /// rejected.  Publishers use this one.
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [writable]
+    // account[2] sysvar_clock account  []
+    UpdPriceNoFailOnError = 13,
     /// Update authorities
     // key[0] upgrade authority         [signer writable]
     // key[1] programdata account       []
     // key[2] permissions account       [writable]
     // key[3] system program            []
     UpdPermissions = 17,
+    /// Set max latency
+    // account[0] funding account       [signer writable]
+    // account[1] price account         [signer writable]
+    // account[2] permissions account   []
+    SetMaxLatency = 18,
 }
 
 #[repr(C)]
@@ -69,7 +100,16 @@ impl CommandHeader {
     }
 }
 
-fn compute_permissions_account(program_id: Pubkey, permissions_account: Option<Pubkey>) -> Pubkey {
-    permissions_account
-        .unwrap_or_else(|| Pubkey::find_program_address(&[b"permissions"], &program_id).0)
+/// Returns `permissions_account`, if specified, or the default permissions account address for
+/// the `program_id`.
+pub fn compute_permissions_account(
+    program_id: Pubkey,
+    permissions_account: Option<Pubkey>,
+) -> Pubkey {
+    permissions_account.unwrap_or_else(|| default_permissions_account(program_id).0)
+}
+
+/// The default permissions account address for the `program_id`, and its bump seed.
+pub fn default_permissions_account(program_id: Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"permissions"], &program_id)
 }
@@ -53,3 +53,9 @@ impl InitMappingArgs {
         }
     }
 }
+
+impl Default for InitMappingArgs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,50 @@
+use bytemuck::{Pod, Zeroable, bytes_of};
+use solana_program::{instruction::AccountMeta, instruction::Instruction, pubkey::Pubkey};
+
+use super::{CommandHeader, OracleCommand, compute_permissions_account};
+
+/// Sets the exponent of a price account.  The program also resets the aggregate and all the
+/// publisher components, so the price is unavailable until the publishers update it again.
+///
+/// The price account does not need to sign, when the `funding_account` is the `master_authority`
+/// from the permissions account.
+pub fn instruction(
+    program_id: Pubkey,
+    funding_account: Pubkey,
+    price_account: Pubkey,
+    permissions_account: Option<Pubkey>,
+    exponent: i32,
+    price_type: u32,
+) -> Instruction {
+    let permissions_account = compute_permissions_account(program_id, permissions_account);
+
+    let accounts = vec![
+        AccountMeta::new(funding_account, true),
+        AccountMeta::new(price_account, false),
+        AccountMeta::new_readonly(permissions_account, false),
+    ];
+
+    Instruction {
+        program_id,
+        accounts,
+        data: bytes_of(&InitPriceArgs::new(exponent, price_type)).to_owned(),
+    }
+}
+
+#[repr(C)]
+#[derive(Zeroable, Pod, Copy, Clone)]
+pub struct InitPriceArgs {
+    pub header: CommandHeader,
+    pub exponent: i32,
+    pub price_type: u32,
+}
+
+impl InitPriceArgs {
+    pub fn new(exponent: i32, price_type: u32) -> Self {
+        Self {
+            header: CommandHeader::new(OracleCommand::InitPrice),
+            exponent,
+            price_type,
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use bytemuck::{Pod, Zeroable, bytes_of};
+use solana_program::{instruction::AccountMeta, instruction::Instruction, pubkey::Pubkey};
+
+use super::{CommandHeader, OracleCommand, compute_permissions_account};
+
+/// Sets the maximum number of slots between a publisher update and an aggregation, for the update
+/// to be included into the aggregate.  Zero means the program default.
+///
+/// The price account does not need to sign, when the `funding_account` is the `master_authority`
+/// from the permissions account.
+pub fn instruction(
+    program_id: Pubkey,
+    funding_account: Pubkey,
+    price_account: Pubkey,
+    permissions_account: Option<Pubkey>,
+    max_latency: u8,
+) -> Instruction {
+    let permissions_account = compute_permissions_account(program_id, permissions_account);
+
+    let accounts = vec![
+        AccountMeta::new(funding_account, true),
+        AccountMeta::new(price_account, false),
+        AccountMeta::new_readonly(permissions_account, false),
+    ];
+
+    Instruction {
+        program_id,
+        accounts,
+        data: bytes_of(&SetMaxLatencyArgs::new(max_latency)).to_owned(),
+    }
+}
+
+#[repr(C)]
+#[derive(Zeroable, Pod, Copy, Clone)]
+pub struct SetMaxLatencyArgs {
+    pub header: CommandHeader,
+    pub max_latency: u8,
+    pub unused_: [u8; 3],
+}
+
+impl SetMaxLatencyArgs {
+    pub fn new(max_latency: u8) -> Self {
+        Self {
+            header: CommandHeader::new(OracleCommand::SetMaxLatency),
+            max_latency,
+            unused_: [0; 3],
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use bytemuck::{Pod, Zeroable, bytes_of};
+use solana_program::{instruction::AccountMeta, instruction::Instruction, pubkey::Pubkey};
+
+use super::{CommandHeader, OracleCommand, compute_permissions_account};
+
+/// Sets the minimum number of publishers with valid quotes, for a successful aggregation.
+///
+/// The price account does not need to sign, when the `funding_account` is the `master_authority`
+/// from the permissions account.
+pub fn instruction(
+    program_id: Pubkey,
+    funding_account: Pubkey,
+    price_account: Pubkey,
+    permissions_account: Option<Pubkey>,
+    minimum_publishers: u8,
+) -> Instruction {
+    let permissions_account = compute_permissions_account(program_id, permissions_account);
+
+    let accounts = vec![
+        AccountMeta::new(funding_account, true),
+        AccountMeta::new(price_account, false),
+        AccountMeta::new_readonly(permissions_account, false),
+    ];
+
+    Instruction {
+        program_id,
+        accounts,
+        data: bytes_of(&SetMinPubArgs::new(minimum_publishers)).to_owned(),
+    }
+}
+
+#[repr(C)]
+#[derive(Zeroable, Pod, Copy, Clone)]
+pub struct SetMinPubArgs {
+    pub header: CommandHeader,
+    pub minimum_publishers: u8,
+    pub unused_: [u8; 3],
+}
+
+impl SetMinPubArgs {
+    pub fn new(minimum_publishers: u8) -> Self {
+        Self {
+            header: CommandHeader::new(OracleCommand::SetMinPub),
+            minimum_publishers,
+            unused_: [0; 3],
+        }
+    }
+}
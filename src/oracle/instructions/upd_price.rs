@@ -0,0 +1,64 @@
+use bytemuck::{Pod, Zeroable, bytes_of};
+use solana_program::{
+    clock::Slot, instruction::AccountMeta, instruction::Instruction, pubkey::Pubkey, sysvar,
+};
+
+use super::{CommandHeader, OracleCommand};
+
+// `PC_STATUS_TRADING` in the Oracle code.
+pub const PC_STATUS_TRADING: u32 = 1;
+
+/// Updates the `publisher` component of the `price_account`.  Uses `UpdPriceNoFailOnError`, the
+/// same way the publishers do, so an update that is rejected, for example, because the
+/// `publishing_slot` is not newer than the last one, does not fail the whole transaction.
+pub fn instruction(
+    program_id: Pubkey,
+    publisher: Pubkey,
+    price_account: Pubkey,
+    status: u32,
+    price: i64,
+    confidence: u64,
+    publishing_slot: Slot,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(publisher, true),
+        AccountMeta::new(price_account, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+
+    Instruction {
+        program_id,
+        accounts,
+        data: bytes_of(&UpdPriceArgs::new(
+            status,
+            price,
+            confidence,
+            publishing_slot,
+        ))
+        .to_owned(),
+    }
+}
+
+#[repr(C)]
+#[derive(Zeroable, Pod, Copy, Clone)]
+pub struct UpdPriceArgs {
+    pub header: CommandHeader,
+    pub status: u32,
+    pub unused_: u32,
+    pub price: i64,
+    pub confidence: u64,
+    pub publishing_slot: u64,
+}
+
+impl UpdPriceArgs {
+    pub fn new(status: u32, price: i64, confidence: u64, publishing_slot: Slot) -> Self {
+        Self {
+            header: CommandHeader::new(OracleCommand::UpdPriceNoFailOnError),
+            status,
+            unused_: 0,
+            price,
+            confidence,
+            publishing_slot,
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Messages the Oracle program and the validator put into the accumulator.  Downstream consumers
+//! receive these messages via Wormhole.
+//!
+//! The layout is based on the `pythnet_sdk/src/messages.rs` file from the
+//! `https://github.com/pyth-network/pyth-crosschain.git` repository.  Messages use the big endian
+//! `pythnet_sdk` wire format, prefixed with a one byte variant tag.
+
+use solana_program::pubkey::Pubkey;
+
+use crate::oracle::accounts::price::PriceAccount;
+
+/// `PC_STATUS_TRADING` in the Oracle program.
+const STATUS_TRADING: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    PriceFeed(PriceFeedMessage),
+    Twap(TwapMessage),
+    /// A message variant this tool does not know how to decode.
+    Other {
+        tag: u8,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceFeedMessage {
+    /// Address of the price account.
+    pub feed_id: Pubkey,
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TwapMessage {
+    /// Address of the price account.
+    pub feed_id: Pubkey,
+    pub cumulative_price: i128,
+    pub cumulative_conf: u128,
+    pub num_down_slots: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub publish_slot: u64,
+}
+
+impl Message {
+    /// Returns `None` if `data` is too short for the message variant.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let (&tag, data) = data.split_first()?;
+        let mut reader = Reader(data);
+        let message = match tag {
+            0 => Self::PriceFeed(PriceFeedMessage {
+                feed_id: reader.pubkey()?,
+                price: i64::from_be_bytes(reader.bytes()?),
+                conf: u64::from_be_bytes(reader.bytes()?),
+                exponent: i32::from_be_bytes(reader.bytes()?),
+                publish_time: i64::from_be_bytes(reader.bytes()?),
+                prev_publish_time: i64::from_be_bytes(reader.bytes()?),
+                ema_price: i64::from_be_bytes(reader.bytes()?),
+                ema_conf: u64::from_be_bytes(reader.bytes()?),
+            }),
+            1 => Self::Twap(TwapMessage {
+                feed_id: reader.pubkey()?,
+                cumulative_price: i128::from_be_bytes(reader.bytes()?),
+                cumulative_conf: u128::from_be_bytes(reader.bytes()?),
+                num_down_slots: u64::from_be_bytes(reader.bytes()?),
+                exponent: i32::from_be_bytes(reader.bytes()?),
+                publish_time: i64::from_be_bytes(reader.bytes()?),
+                prev_publish_time: i64::from_be_bytes(reader.bytes()?),
+                publish_slot: u64::from_be_bytes(reader.bytes()?),
+            }),
+            tag => Self::Other { tag },
+        };
+        Some(message)
+    }
+}
+
+impl PriceFeedMessage {
+    /// The message the Oracle program generates for the current state of the `price_account`.
+    /// When the last aggregation did not produce a trading price, the previous price is used.
+    pub fn for_price_account(price_pubkey: &Pubkey, price_account: &PriceAccount) -> Self {
+        let (price, conf, publish_time) = if price_account.agg.status == STATUS_TRADING {
+            (
+                price_account.agg.price,
+                price_account.agg.conf,
+                price_account.timestamp,
+            )
+        } else {
+            (
+                price_account.prev_price,
+                price_account.prev_conf,
+                price_account.prev_timestamp,
+            )
+        };
+
+        Self {
+            feed_id: *price_pubkey,
+            price,
+            conf,
+            exponent: price_account.exponent,
+            publish_time,
+            prev_publish_time: price_account.prev_timestamp,
+            ema_price: price_account.twap.val,
+            ema_conf: price_account.twac.val as u64,
+        }
+    }
+}
+
+struct Reader<'data>(&'data [u8]);
+
+impl Reader<'_> {
+    fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (head, tail) = self.0.split_first_chunk::<N>()?;
+        self.0 = tail;
+        Some(*head)
+    }
+
+    fn pubkey(&mut self) -> Option<Pubkey> {
+        self.bytes().map(Pubkey::new_from_array)
+    }
+}
@@ -0,0 +1,162 @@
+//! Reads all the price accounts of an Oracle program at once, for commands that look at hundreds of
+//! them.
+//!
+//! A single `getProgramAccounts` request, filtered by the price account size and header, replaces
+//! a `getMultipleAccounts` request for every 100 accounts, and only transfers the part of the
+//! account data the caller needs.  Reads are cached by slot, for the duration of a command, so that
+//! a command that needs the same price accounts in several places only reads them once.  The cache
+//! is cleared before every command, so that commands that run in the same process, in a `shell`
+//! session, or as a part of `dev up`, never see each other's reads.
+
+use std::{
+    collections::BTreeMap,
+    mem::{offset_of, size_of},
+    ops::RangeInclusive,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{Context as _, Result};
+use bytemuck::{Pod, pod_read_unaligned};
+use pythnet_heisenberg::{
+    oracle::{
+        accounts::{AccountHeader, PC_ACCTYPE_PRICE, PC_MAGIC, price::PriceAccount},
+        instructions::add_price::ACCOUNT_MIN_SIZE,
+    },
+    rpc_client_ext::RpcClientExt as _,
+};
+use solana_account_decoder::UiDataSliceConfig;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use solana_sdk::{clock::Slot, pubkey::Pubkey};
+
+/// Data length that covers the price account fields up to, and including, the feed index.
+pub const FEED_INDEX_DATA_LEN: usize = offset_of!(PriceAccount, feed_index) + size_of::<u32>();
+
+/// Data length that covers the whole [`PriceAccount`].
+pub const FULL_DATA_LEN: usize = size_of::<PriceAccount>();
+
+/// Price accounts of one Oracle program, as seen at one slot.
+pub struct PriceAccounts {
+    pub slot: Slot,
+    /// Every account holds this many bytes of data, starting at the beginning of the account.
+    pub data_len: usize,
+    pub accounts: BTreeMap<Pubkey, Vec<u8>>,
+}
+
+impl PriceAccounts {
+    /// Price accounts with feed indices in the `range`, along with their feed indices.  Price
+    /// accounts that did not get a feed index yet have a feed index of 0.
+    pub fn with_feed_indices(
+        &self,
+        range: &RangeInclusive<u32>,
+    ) -> impl Iterator<Item = (Pubkey, u32, &[u8])> {
+        self.accounts.iter().filter_map(move |(pubkey, data)| {
+            let feed_index = read::<u32>(data, offset_of!(PriceAccount, feed_index));
+            range
+                .contains(&feed_index)
+                .then_some((*pubkey, feed_index, data.as_slice()))
+        })
+    }
+
+    /// Decodes the account `data`.  Only works for reads of the [`FULL_DATA_LEN`].
+    pub fn decode(data: &[u8]) -> PriceAccount {
+        read(data, 0)
+    }
+}
+
+static CACHE: Mutex<Vec<(String, Pubkey, Arc<PriceAccounts>)>> = Mutex::new(Vec::new());
+
+/// Forgets all the earlier reads.  Called before every command.
+pub fn clear_cache() {
+    CACHE
+        .lock()
+        .expect("Price accounts cache lock is not poisoned")
+        .clear();
+}
+
+/// Reads the first `data_len` bytes of all the price accounts of the `program_id`, at the
+/// `min_slot` or later.  Without a `min_slot`, any earlier read of the same accounts, done by the
+/// same command, is reused.
+///
+/// An earlier read is also reused when it was done at the `min_slot` or later, with at least
+/// `data_len` bytes.
+pub async fn fetch(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    data_len: usize,
+    min_slot: Option<Slot>,
+) -> Result<Arc<PriceAccounts>> {
+    let url = rpc_client.url();
+    let cached = |cache: &[(String, Pubkey, Arc<PriceAccounts>)]| {
+        cache
+            .iter()
+            .find(|(cached_url, cached_program_id, _accounts)| {
+                *cached_url == url && cached_program_id == program_id
+            })
+            .map(|(_url, _program_id, accounts)| accounts.clone())
+    };
+
+    let reusable = cached(
+        &CACHE
+            .lock()
+            .expect("Price accounts cache lock is not poisoned"),
+    )
+    .filter(|accounts| {
+        accounts.data_len >= data_len && min_slot.is_none_or(|min_slot| accounts.slot >= min_slot)
+    });
+    if let Some(accounts) = reusable {
+        return Ok(accounts);
+    }
+
+    let (slot, accounts) = rpc_client
+        .get_program_accounts_with_slot(
+            program_id,
+            vec![
+                RpcFilterType::DataSize(ACCOUNT_MIN_SIZE),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    offset_of!(AccountHeader, magic_number),
+                    PC_MAGIC.to_le_bytes().to_vec(),
+                )),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    offset_of!(AccountHeader, account_type),
+                    PC_ACCTYPE_PRICE.to_le_bytes().to_vec(),
+                )),
+            ],
+            UiDataSliceConfig {
+                offset: 0,
+                length: data_len,
+            },
+            min_slot,
+        )
+        .await
+        .with_context(|| format!("Failed to fetch price accounts of {program_id}"))?;
+
+    let accounts = Arc::new(PriceAccounts {
+        slot,
+        data_len,
+        accounts: accounts
+            .into_iter()
+            .map(|(pubkey, account)| (pubkey, account.data))
+            .collect(),
+    });
+
+    let mut cache = CACHE
+        .lock()
+        .expect("Price accounts cache lock is not poisoned");
+    // A concurrent read might have stored a newer or a longer read in the meantime.
+    let replace = cached(&cache).is_none_or(|existing| {
+        existing.slot <= accounts.slot && existing.data_len <= accounts.data_len
+    });
+    if replace {
+        cache.retain(|(cached_url, cached_program_id, _accounts)| {
+            *cached_url != url || cached_program_id != program_id
+        });
+        cache.push((url, *program_id, accounts.clone()));
+    }
+
+    Ok(accounts)
+}
+
+fn read<T: Pod>(data: &[u8], offset: usize) -> T {
+    pod_read_unaligned(&data[offset..offset + size_of::<T>()])
+}
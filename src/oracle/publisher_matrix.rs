@@ -0,0 +1,160 @@
+//! Shows which publishers are authorized on which price accounts, by decoding the publisher
+//! components of all the price accounts of an Oracle program.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use itertools::Itertools as _;
+use pythnet_heisenberg::output;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, oracle::publisher_matrix::PublisherMatrixArgs},
+    oracle::{
+        price_accounts::{self, PriceAccounts},
+        sync_parameters::product_symbols,
+    },
+};
+
+/// One row of the matrix.
+struct Feed {
+    price: Pubkey,
+    product: Pubkey,
+    symbol: Option<String>,
+    publishers: BTreeSet<Pubkey>,
+}
+
+pub async fn run(
+    PublisherMatrixArgs {
+        json_rpc_url,
+        program_id,
+        publisher,
+        missing_only,
+    }: PublisherMatrixArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let prices = price_accounts::fetch(
+        &rpc_client,
+        &program_id,
+        price_accounts::FULL_DATA_LEN,
+        None,
+    )
+    .await?;
+
+    let mut feeds = prices
+        .accounts
+        .iter()
+        .map(|(price, data)| {
+            let account = PriceAccounts::decode(data);
+            let num = usize::try_from(account.num)
+                .unwrap_or(usize::MAX)
+                .min(account.comp.len());
+            Feed {
+                price: *price,
+                product: account.product_account,
+                symbol: None,
+                publishers: account.comp[..num]
+                    .iter()
+                    .map(|component| component.pub_)
+                    .collect(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let products = feeds
+        .iter()
+        .map(|Feed { product, .. }| *product)
+        .unique()
+        .collect::<Vec<_>>();
+    let symbols = product_symbols(&rpc_client, &products).await?;
+    for feed in &mut feeds {
+        feed.symbol = symbols.get(&feed.product).cloned();
+    }
+    feeds.sort_by(|a, b| (&a.symbol, a.price).cmp(&(&b.symbol, b.price)));
+
+    let columns = if publisher.is_empty() {
+        feeds
+            .iter()
+            .flat_map(|Feed { publishers, .. }| publishers)
+            .copied()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>()
+    } else {
+        publisher.into_iter().unique().collect()
+    };
+
+    if missing_only {
+        feeds.retain(|Feed { publishers, .. }| {
+            columns.iter().any(|column| !publishers.contains(column))
+        });
+    }
+
+    // Text output is a CSV table, so that it can be loaded into a spreadsheet as is.  The header
+    // is not needed in the JSON mode.
+    output::notice(format!("price,product,symbol,{}", columns.iter().join(",")));
+
+    let mut missing = BTreeMap::<Pubkey, usize>::new();
+    for Feed {
+        price,
+        product,
+        symbol,
+        publishers,
+    } in &feeds
+    {
+        let cells = columns
+            .iter()
+            .map(|column| {
+                let authorized = publishers.contains(column);
+                if !authorized {
+                    *missing.entry(*column).or_default() += 1;
+                }
+                authorized
+            })
+            .collect::<Vec<_>>();
+
+        let symbol_csv = symbol.as_deref().map(csv_field).unwrap_or_default();
+        output::result(
+            format!(
+                "{price},{product},{symbol_csv},{}",
+                cells.iter().map(|cell| u8::from(*cell)).join(",")
+            ),
+            json!({
+                "price": price.to_string(),
+                "product": product.to_string(),
+                "symbol": symbol,
+                "publishers": publishers.iter().map(ToString::to_string).collect_vec(),
+                "missing": columns
+                    .iter()
+                    .zip(&cells)
+                    .filter(|(_column, authorized)| !**authorized)
+                    .map(|(column, _authorized)| column.to_string())
+                    .collect_vec(),
+            }),
+        );
+    }
+
+    // Goes to stderr, so that the text output stays a valid CSV.
+    eprintln!(
+        "{} price accounts, {} publishers, at slot {}",
+        feeds.len(),
+        columns.len(),
+        prices.slot
+    );
+    for (publisher, count) in &missing {
+        eprintln!("{publisher} is not authorized on {count} price accounts");
+    }
+
+    Ok(())
+}
+
+/// Symbols are free form text, so make sure they do not break the CSV structure.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
@@ -0,0 +1,189 @@
+//! Finds feeds whose aggregate price stopped updating, the health view to check after an incident.
+//!
+//! The Oracle only moves the aggregate `last_slot` forward when an aggregation succeeds, that is,
+//! when at least `min_pub` publishers provided a recent trading quote.  A feed is stale when its
+//! `last_slot` lags the slot the price accounts were read at by more than the threshold.  For every
+//! feed, the publishers whose latest quote is trading and recent are counted, as the usual reason
+//! for a stale aggregate is that too few of them are still quoting.
+
+use std::cmp::Reverse;
+
+use anyhow::{Result, bail};
+use itertools::Itertools as _;
+use pythnet_heisenberg::{
+    oracle::instructions::upd_price::PC_STATUS_TRADING, output,
+    price_store::instructions::submit_prices::TradingStatus,
+};
+use serde_json::json;
+use solana_sdk::{clock::Slot, pubkey::Pubkey};
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, oracle::stale_aggregates::StaleAggregatesArgs},
+    oracle::{
+        price_accounts::{self, PriceAccounts},
+        sync_parameters::product_symbols,
+    },
+};
+
+/// Aggregate state of one price account.
+struct Feed {
+    price: Pubkey,
+    product: Pubkey,
+    symbol: Option<String>,
+    feed_index: u32,
+    /// `None` for feeds that were never successfully aggregated.
+    last_slot: Option<Slot>,
+    status: u32,
+    min_pub: u8,
+    /// Authorized publishers.
+    publishers: usize,
+    /// Publishers with a recent trading quote.
+    quoting: usize,
+}
+
+pub async fn run(
+    StaleAggregatesArgs {
+        json_rpc_url,
+        program_id,
+        max_lag_slots,
+        stale_only,
+    }: StaleAggregatesArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let prices = price_accounts::fetch(
+        &rpc_client,
+        &program_id,
+        price_accounts::FULL_DATA_LEN,
+        None,
+    )
+    .await?;
+    let current_slot = prices.slot;
+
+    let mut feeds = prices
+        .accounts
+        .iter()
+        .map(|(price, data)| {
+            let account = PriceAccounts::decode(data);
+            let num = usize::try_from(account.num)
+                .unwrap_or(usize::MAX)
+                .min(account.comp.len());
+            let quoting = account.comp[..num]
+                .iter()
+                .filter(|component| {
+                    component.latest.status == PC_STATUS_TRADING
+                        && current_slot.saturating_sub(component.latest.pub_slot) <= max_lag_slots
+                })
+                .count();
+            Feed {
+                price: *price,
+                product: account.product_account,
+                symbol: None,
+                feed_index: account.feed_index,
+                last_slot: (account.last_slot != 0).then_some(account.last_slot),
+                status: account.agg.status,
+                min_pub: account.min_pub,
+                publishers: num,
+                quoting,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let products = feeds
+        .iter()
+        .map(|Feed { product, .. }| *product)
+        .unique()
+        .collect::<Vec<_>>();
+    let symbols = product_symbols(&rpc_client, &products).await?;
+    for feed in &mut feeds {
+        feed.symbol = symbols.get(&feed.product).cloned();
+    }
+
+    let lag_of = |feed: &Feed| feed.last_slot.map(|slot| current_slot.saturating_sub(slot));
+    let is_stale = |feed: &Feed| lag_of(feed).is_none_or(|lag| lag > max_lag_slots);
+
+    // The most stale feeds first, never aggregated ones at the very top.
+    feeds.sort_by(|a, b| (&a.symbol, a.price).cmp(&(&b.symbol, b.price)));
+    feeds.sort_by_key(|feed| lag_of(feed).map(Reverse));
+
+    for feed in &feeds {
+        let stale = is_stale(feed);
+        if stale_only && !stale {
+            continue;
+        }
+        let Feed {
+            price,
+            product,
+            symbol,
+            feed_index,
+            last_slot,
+            status,
+            min_pub,
+            publishers,
+            quoting,
+        } = feed;
+
+        let who = match symbol {
+            Some(symbol) => format!("{symbol} ({price})"),
+            None => price.to_string(),
+        };
+        let health = if stale { "stale" } else { "ok" };
+        let aggregated = match (last_slot, lag_of(feed)) {
+            (Some(slot), Some(lag)) => format!("aggregated in slot {slot}, {lag} slots ago"),
+            _ => "never aggregated".to_owned(),
+        };
+        output::result(
+            format!(
+                "{who}: {health}, {aggregated}, status: {}, {quoting} of {publishers} publishers \
+                 quoting, min_pub: {min_pub}",
+                status_name(*status)
+            ),
+            json!({
+                "price": price.to_string(),
+                "product": product.to_string(),
+                "symbol": symbol,
+                "feed_index": feed_index,
+                "last_slot": last_slot,
+                "lag_slots": lag_of(feed),
+                "stale": stale,
+                "status": status_name(*status),
+                "publishers": publishers,
+                "quoting": quoting,
+                "min_pub": min_pub,
+            }),
+        );
+    }
+
+    let stale = feeds.iter().filter(|feed| is_stale(feed)).count();
+    output::result(
+        format!(
+            "Current slot: {current_slot}, feeds: {}, stale: {stale}, over {max_lag_slots} slots",
+            feeds.len()
+        ),
+        json!({
+            "current_slot": current_slot,
+            "feeds": feeds.len(),
+            "stale": stale,
+            "max_lag_slots": max_lag_slots,
+        }),
+    );
+
+    if stale != 0 {
+        bail!(
+            "{stale} out of {} feeds were not aggregated in the last {max_lag_slots} slots",
+            feeds.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Name of the Oracle trading `status`, or the raw value for the statuses the Oracle does not
+/// define.
+fn status_name(status: u32) -> String {
+    u8::try_from(status)
+        .ok()
+        .and_then(|status| TradingStatus::try_from(status).ok())
+        .map(|status| format!("{status:?}").to_lowercase())
+        .unwrap_or_else(|| status.to_string())
+}
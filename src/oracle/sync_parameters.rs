@@ -0,0 +1,314 @@
+//! Makes the aggregation parameters of the feeds on a test cluster match a reference cluster,
+//! usually production, so that the test cluster aggregates prices the same way.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Context as _, Result};
+use itertools::Itertools as _;
+use pythnet_heisenberg::{
+    blockhash_cache::BlockhashCache,
+    dry_run,
+    keypair_ext::read_keypair_file,
+    oracle::{
+        accounts::product::ProductAccount,
+        instructions::{init_price, set_max_latency, set_min_pub},
+    },
+    output,
+    tx_sheppard::{TxOutcome, with_sheppard},
+};
+use serde_json::json;
+use solana_program::instruction::Instruction;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::request::MAX_MULTIPLE_ACCOUNTS;
+use solana_sdk::{pubkey::Pubkey, signer::Signer as _, transaction::Transaction};
+
+use crate::{
+    args::{
+        JsonRpcUrlArgs, json_rpc_url_args::get_rpc_client,
+        oracle::sync_parameters::SyncParametersArgs,
+    },
+    exit_code::check_outcomes,
+    oracle::price_accounts::{self, PriceAccounts},
+};
+
+/// Parameters of a single feed that are copied from the reference cluster.
+#[derive(Debug, Clone, Copy)]
+struct FeedParameters {
+    price_account: Pubkey,
+    price_type: u32,
+    exponent: i32,
+    min_pub: u8,
+    max_latency: u8,
+}
+
+struct Update {
+    symbol: String,
+    price_account: Pubkey,
+    /// Human readable descriptions of the changes, like "min_pub: 3 -> 5".
+    changes: Vec<String>,
+    instructions: Vec<Instruction>,
+}
+
+pub async fn run(
+    SyncParametersArgs {
+        json_rpc_url,
+        program_id,
+        permissions_account,
+        funding_keypair,
+        reference_rpc_url,
+        reference_program_id,
+        sync_exponent,
+    }: SyncParametersArgs,
+) -> Result<()> {
+    let dry_run = dry_run::is_enabled();
+    let reference_rpc_client = get_rpc_client(JsonRpcUrlArgs {
+        rpc_url: reference_rpc_url,
+        ..json_rpc_url.clone()
+    });
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let rpc_client = &rpc_client;
+
+    let funding = read_keypair_file(&funding_keypair)?;
+    let funding_pubkey = funding.pubkey();
+
+    let reference = read_feeds(
+        &reference_rpc_client,
+        &reference_program_id.unwrap_or(program_id),
+    )
+    .await
+    .context("Reading the reference cluster feeds")?;
+    let target = read_feeds(rpc_client, &program_id)
+        .await
+        .context("Reading the target cluster feeds")?;
+
+    let mut updates = vec![];
+    let mut not_in_reference = 0;
+    for (symbol, target) in &target {
+        let Some(reference) = reference.get(symbol) else {
+            not_in_reference += 1;
+            continue;
+        };
+
+        let mut changes = vec![];
+        let mut instructions = vec![];
+        // Re-initialization resets the price, so it goes before the other changes.
+        if sync_exponent && target.exponent != reference.exponent {
+            changes.push(format!(
+                "exponent: {} -> {}",
+                target.exponent, reference.exponent
+            ));
+            instructions.push(init_price::instruction(
+                program_id,
+                funding_pubkey,
+                target.price_account,
+                permissions_account,
+                reference.exponent,
+                target.price_type,
+            ));
+        }
+        if target.min_pub != reference.min_pub {
+            changes.push(format!(
+                "min_pub: {} -> {}",
+                target.min_pub, reference.min_pub
+            ));
+            instructions.push(set_min_pub::instruction(
+                program_id,
+                funding_pubkey,
+                target.price_account,
+                permissions_account,
+                reference.min_pub,
+            ));
+        }
+        if target.max_latency != reference.max_latency {
+            changes.push(format!(
+                "max_latency: {} -> {}",
+                target.max_latency, reference.max_latency
+            ));
+            instructions.push(set_max_latency::instruction(
+                program_id,
+                funding_pubkey,
+                target.price_account,
+                permissions_account,
+                reference.max_latency,
+            ));
+        }
+
+        if !instructions.is_empty() {
+            updates.push(Update {
+                symbol: symbol.clone(),
+                price_account: target.price_account,
+                changes,
+                instructions,
+            });
+        }
+    }
+
+    if not_in_reference != 0 {
+        output::notice(format!(
+            "{not_in_reference} of {} target feeds do not have a matching feed on the reference \
+             cluster, and are left as is",
+            target.len()
+        ));
+    }
+
+    for Update {
+        symbol,
+        price_account,
+        changes,
+        ..
+    } in &updates
+    {
+        eprintln!("{symbol} ({price_account}): {}", changes.join(", "));
+    }
+
+    if updates.is_empty() {
+        output::notice("All the matching feeds already have the reference parameters");
+        return Ok(());
+    }
+
+    if dry_run {
+        output::notice(format!(
+            "Dry run.  {} feeds would be updated.  No transactions were sent.",
+            updates.len()
+        ));
+        return Ok(());
+    }
+
+    let outcomes = with_sheppard(rpc_client)
+        .labels(updates.iter().map(|Update { symbol, .. }| symbol))
+        .run(updates.iter().map(|Update { instructions, .. }| {
+            let funding = &funding;
+            move |blockhash_cache: &BlockhashCache| {
+                Transaction::new_signed_with_payer(
+                    instructions,
+                    Some(&funding_pubkey),
+                    &[funding],
+                    blockhash_cache.get(),
+                )
+            }
+        }))
+        .await
+        .context("Running parameter update transactions")?;
+
+    for (
+        Update {
+            symbol,
+            price_account,
+            changes,
+            ..
+        },
+        outcome,
+    ) in updates.iter().zip(&outcomes)
+    {
+        let mut result = json!({
+            "symbol": symbol,
+            "price_account": price_account.to_string(),
+            "changes": changes,
+        });
+        match outcome {
+            TxOutcome::Success(signature) => result["signature"] = json!(signature.to_string()),
+            TxOutcome::Failed(error) => result["error"] = json!(error),
+        }
+        output::json_result(result);
+    }
+
+    check_outcomes(&outcomes)?;
+
+    output::notice(format!("Updated {} feeds", updates.len()));
+
+    Ok(())
+}
+
+/// Reads the parameters of all the feeds of the Oracle `program_id`, keyed by the product symbol.
+/// Products with more than one price account can not be matched unambiguously, and are skipped.
+async fn read_feeds(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<BTreeMap<String, FeedParameters>> {
+    let prices =
+        price_accounts::fetch(rpc_client, program_id, price_accounts::FULL_DATA_LEN, None).await?;
+
+    let prices = prices
+        .accounts
+        .iter()
+        .map(|(price_account, data)| {
+            let price = PriceAccounts::decode(data);
+            (
+                price.product_account,
+                FeedParameters {
+                    price_account: *price_account,
+                    price_type: price.price_type,
+                    exponent: price.exponent,
+                    min_pub: price.min_pub,
+                    max_latency: price.max_latency,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let products = prices
+        .iter()
+        .map(|(product, _parameters)| *product)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let symbols = product_symbols(rpc_client, &products).await?;
+
+    let mut feeds = BTreeMap::new();
+    let mut ambiguous = BTreeSet::new();
+    let by_product = prices.into_iter().into_group_map();
+    for (product, parameters) in by_product {
+        let Some(symbol) = symbols.get(&product) else {
+            continue;
+        };
+        let [parameters] = parameters[..] else {
+            ambiguous.insert(symbol.clone());
+            continue;
+        };
+        if feeds.insert(symbol.clone(), parameters).is_some() {
+            ambiguous.insert(symbol.clone());
+        }
+    }
+    for symbol in &ambiguous {
+        feeds.remove(symbol);
+    }
+
+    if !ambiguous.is_empty() {
+        output::notice(format!(
+            "Skipping symbols with more than one price account on {}: {}",
+            rpc_client.url(),
+            ambiguous.iter().join(", ")
+        ));
+    }
+
+    Ok(feeds)
+}
+
+/// Reads the `symbol` from the metadata of the `products`.  Missing products, and products without
+/// a symbol are skipped.
+pub(super) async fn product_symbols(
+    rpc_client: &RpcClient,
+    products: &[Pubkey],
+) -> Result<BTreeMap<Pubkey, String>> {
+    let mut symbols = BTreeMap::new();
+    for chunk in products.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = rpc_client
+            .get_multiple_accounts_with_commitment(chunk, rpc_client.commitment())
+            .await
+            .context("Failed to fetch product accounts")?
+            .value;
+
+        for (product, account) in chunk.iter().zip(accounts) {
+            let Some(account) = account else {
+                continue;
+            };
+            let metadata = ProductAccount::metadata(&account.data)
+                .with_context(|| format!("Failed to decode product account {product}"))?;
+            if let Some((_key, symbol)) = metadata.into_iter().find(|(key, _value)| key == "symbol")
+            {
+                symbols.insert(*product, symbol);
+            }
+        }
+    }
+    Ok(symbols)
+}
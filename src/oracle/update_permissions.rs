@@ -1,14 +1,18 @@
 use anyhow::{Context as _, Result};
-use solana_sdk::signer::Signer as _;
+use pythnet_heisenberg::{
+    blockhash_cache::{BlockhashCache, with_blockhash},
+    keypair_ext::read_signer,
+    oracle::instructions::{compute_permissions_account, update_permissions},
+    output,
+    rpc_client_ext::RpcClientExt as _,
+};
+use serde_json::json;
 
 use crate::{
     args::{json_rpc_url_args::get_rpc_client, oracle::update_permissions::UpdatePermissionsArgs},
-    keypair_ext::read_keypair_file,
-    rpc_client_ext::RpcClientExt as _,
+    confirm::confirm_or_abort,
 };
 
-use super::instructions::update_permissions;
-
 pub async fn run(
     UpdatePermissionsArgs {
         json_rpc_url,
@@ -18,30 +22,52 @@ pub async fn run(
         master_authority,
         data_curation_authority,
         security_authority,
+        yes,
     }: UpdatePermissionsArgs,
 ) -> Result<()> {
     let rpc_client = get_rpc_client(json_rpc_url);
 
-    let funding = read_keypair_file(&funding_keypair)?;
+    let funding = read_signer(&funding_keypair, "funding")?;
     let funding_pubkey = funding.pubkey();
 
-    let signature = rpc_client
-        .send_with_payer_latest_blockhash_with_spinner(
-            &[update_permissions::instruction(
-                program_id,
-                funding_pubkey,
-                permissions_account,
-                master_authority,
-                data_curation_authority,
-                security_authority,
-            )],
-            Some(&funding_pubkey),
-            &[&funding],
-        )
+    confirm_or_abort(
+        format!(
+            "Updating permissions of the Oracle program {program_id}\n\
+             Permissions account: {}\n\
+             Signed by the program upgrade authority: {funding_pubkey}\n\
+             Master authority: {master_authority}\n\
+             Data curation authority: {data_curation_authority}\n\
+             Security authority: {security_authority}",
+            compute_permissions_account(program_id, permissions_account),
+        ),
+        yes,
+    )?;
+
+    let signature = with_blockhash(&rpc_client)
+        .run(async |blockhash_cache: &BlockhashCache| {
+            rpc_client
+                .send_with_payer_cached_blockhash(
+                    &[update_permissions::instruction(
+                        program_id,
+                        funding_pubkey,
+                        permissions_account,
+                        master_authority,
+                        data_curation_authority,
+                        security_authority,
+                    )],
+                    Some(&funding_pubkey),
+                    &[funding.as_ref()],
+                    blockhash_cache,
+                )
+                .await
+        })
         .await
         .context("Transaction execution failed")?;
 
-    println!("Oracle permissions update tx: {signature}");
+    output::result(
+        format!("Oracle permissions update tx: {signature}"),
+        json!({ "signature": signature.to_string() }),
+    );
 
     Ok(())
 }
@@ -0,0 +1,134 @@
+//! Repeatedly checks that price updates are reflected into the accumulator.
+
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::{Result, bail};
+use indicatif::{ProgressBar, ProgressStyle};
+use pythnet_heisenberg::output;
+use serde_json::json;
+use solana_sdk::pubkey::Pubkey;
+use tokio::time::{Instant, MissedTickBehavior, interval};
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, oracle::verify_accumulator::VerifyAccumulatorArgs},
+    oracle::accumulator::fetch_states,
+};
+
+#[derive(Default)]
+struct PriceStats {
+    checks: u64,
+    failed_checks: u64,
+    /// Number of times a new aggregation was observed.
+    updates: u64,
+    last_pub_slot: Option<u64>,
+    last_problems: Vec<String>,
+}
+
+pub async fn run(
+    VerifyAccumulatorArgs {
+        json_rpc_url,
+        accumulator,
+        duration,
+        poll_interval,
+        require_updates,
+    }: VerifyAccumulatorArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let duration: Duration = duration.into();
+
+    let mut stats: HashMap<Pubkey, PriceStats> = accumulator
+        .price_pubkey
+        .iter()
+        .map(|price_pubkey| (*price_pubkey, PriceStats::default()))
+        .collect();
+
+    let progress_bar = ProgressBar::new_spinner();
+    progress_bar.set_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.green} [{elapsed}] {wide_msg}")
+            .expect("ProgressStyle::template direct input to be correct"),
+    );
+
+    let deadline = Instant::now() + duration;
+    let mut poll = interval(poll_interval.into());
+    poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut rounds = 0;
+    while Instant::now() < deadline {
+        poll.tick().await;
+
+        for state in fetch_states(&rpc_client, &accumulator).await? {
+            let price_stats = stats
+                .get_mut(&state.price_pubkey)
+                .expect("`fetch_states()` only returns requested prices");
+
+            price_stats.checks += 1;
+            if price_stats
+                .last_pub_slot
+                .is_some_and(|last_pub_slot| last_pub_slot != state.pub_slot)
+            {
+                price_stats.updates += 1;
+            }
+            price_stats.last_pub_slot = Some(state.pub_slot);
+
+            let problems = state.problems();
+            if !problems.is_empty() {
+                price_stats.failed_checks += 1;
+                if problems != price_stats.last_problems {
+                    progress_bar.suspend(|| {
+                        output::notice(format!(
+                            "{} at slot {}: {}",
+                            state.price_pubkey,
+                            state.slot,
+                            problems.join("; ")
+                        ));
+                    });
+                }
+            }
+            price_stats.last_problems = problems;
+        }
+
+        rounds += 1;
+        progress_bar.set_message(format!(
+            "Checked {} price accounts {rounds} times",
+            stats.len()
+        ));
+        progress_bar.tick();
+    }
+    progress_bar.finish_and_clear();
+
+    let mut failed = 0;
+    for price_pubkey in &accumulator.price_pubkey {
+        let PriceStats {
+            checks,
+            failed_checks,
+            updates,
+            ..
+        } = &stats[price_pubkey];
+
+        let not_updated = require_updates && *updates == 0;
+        if *failed_checks != 0 || not_updated {
+            failed += 1;
+        }
+
+        output::result(
+            format!(
+                "{price_pubkey}: {checks} checks, {failed_checks} inconsistent, {updates} updates"
+            ),
+            json!({
+                "price": price_pubkey.to_string(),
+                "checks": checks,
+                "failed_checks": failed_checks,
+                "updates": updates,
+            }),
+        );
+    }
+
+    if failed != 0 {
+        bail!(
+            "Accumulator verification failed for {failed} out of {} price accounts",
+            accumulator.price_pubkey.len()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+//! Commands report their results either as human readable text, or as JSON, depending on the
+//! global `--output` argument.
+//!
+//! In the JSON mode every result is printed as a single line JSON object on stdout, while all the
+//! progress messages go to stderr.  This way scripts can consume the output line by line, without
+//! scraping text.
+
+use std::{fmt::Display, sync::OnceLock};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human readable text.
+    Text,
+    /// One JSON object per line, for each result.  Progress messages are printed on stderr.
+    Json,
+}
+
+static OUTPUT_FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+/// Sets the output format for the whole process.  Should be called once, before any command
+/// starts producing output.
+pub fn init(format: OutputFormat) {
+    OUTPUT_FORMAT
+        .set(format)
+        .expect("`output::init()` is called only once");
+}
+
+pub fn format() -> OutputFormat {
+    OUTPUT_FORMAT.get().copied().unwrap_or(OutputFormat::Text)
+}
+
+/// Prints a command result.  `text` is used in the text mode, and `json` is used in the JSON mode.
+pub fn result(text: impl Display, json: impl Serialize) {
+    match format() {
+        OutputFormat::Text => println!("{text}"),
+        OutputFormat::Json => print_json(json),
+    }
+}
+
+/// Prints a command result that only has a JSON representation.  In the text mode the same
+/// information is expected to be shown by some other means, like a progress bar.
+pub fn json_result(json: impl Serialize) {
+    match format() {
+        OutputFormat::Text => (),
+        OutputFormat::Json => print_json(json),
+    }
+}
+
+/// Prints a progress or an informational message.  It goes to stdout in the text mode, and to
+/// stderr in the JSON mode, in order to keep stdout parsable.
+pub fn notice(text: impl Display) {
+    match format() {
+        OutputFormat::Text => println!("{text}"),
+        OutputFormat::Json => eprintln!("{text}"),
+    }
+}
+
+fn print_json(json: impl Serialize) {
+    let line = serde_json::to_string(&json).expect("Command results serialize into JSON");
+    println!("{line}");
+}
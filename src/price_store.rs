@@ -1,24 +1,41 @@
-use anyhow::Result;
+use anyhow::{Context as _, Result};
+use pythnet_heisenberg::dry_run;
 
-use crate::args::price_store::Command;
+use crate::{args::price_store::Command, exit_code::ValidationFailed};
 
+mod addresses;
 mod benchmark1;
 mod initialize;
 mod initialize_publisher;
-pub mod instructions;
+mod stale_prices;
 mod submit_prices;
+mod verify_setup;
+mod whose_buffer;
 
 pub async fn run(command: Command) -> Result<()> {
     match command {
         Command::Initialize(args) => initialize::run(args).await,
         Command::InitializePublisher(args) => {
-            args.check_are_valid()?;
+            args.check_are_valid().context(ValidationFailed)?;
             initialize_publisher::run(args).await
         }
         Command::SubmitPrices(args) => submit_prices::run(args).await,
         Command::Benchmark1(args) => {
-            args.check_are_valid()?;
-            benchmark1::run(args).await
+            dry_run::check_not_enabled("price-store benchmark1").context(ValidationFailed)?;
+            args.check_are_valid().context(ValidationFailed)?;
+            benchmark1::run(*args).await
         }
+        Command::Benchmark1Worker(args) => {
+            dry_run::check_not_enabled("price-store benchmark1-worker")
+                .context(ValidationFailed)?;
+            benchmark1::run_worker(args).await
+        }
+        Command::VerifySetup(args) => {
+            args.check_are_valid().context(ValidationFailed)?;
+            verify_setup::run(args).await
+        }
+        Command::WhoseBuffer(args) => whose_buffer::run(args).await,
+        Command::Addresses(args) => addresses::run(args).await,
+        Command::StalePrices(args) => stale_prices::run(args).await,
     }
 }
@@ -0,0 +1,34 @@
+//! Describes accounts of the Price Store program.
+//!
+//! Copied from the same `pyth-price-store` version as the [`instructions`](super::instructions).
+
+use bytemuck::{Pod, Zeroable};
+
+/// Header of a price buffer account.  It is followed by `num_prices` entries of
+/// [`BufferedPrice`](super::instructions::submit_prices::BufferedPrice).
+///
+/// The program clears the buffer when it receives the first update in a new slot, so the header
+/// describes the updates received in the `slot`.
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+pub struct BufferHeader {
+    /// Account format, to distinguish buffers from other accounts of the program.
+    pub format: u32,
+    /// Publisher this buffer belongs to.
+    pub publisher: [u8; 32],
+    /// Slot of the last update.
+    pub slot: u64,
+    /// Number of prices written in the `slot`.
+    pub num_prices: u32,
+}
+
+/// Publisher config account, a PDA derived from the publisher address with
+/// [`compute_publisher_config_account()`](super::instructions::compute_publisher_config_account).
+#[repr(C, packed)]
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+pub struct PublisherConfig {
+    /// Account format, to distinguish publisher configs from other accounts of the program.
+    pub format: u32,
+    /// Price buffer of the publisher.
+    pub buffer_account: [u8; 32],
+}
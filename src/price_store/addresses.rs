@@ -0,0 +1,40 @@
+use anyhow::Result;
+use pythnet_heisenberg::{
+    output,
+    price_store::instructions::{compute_config_account, compute_publisher_config_account},
+};
+use serde_json::json;
+
+use crate::args::price_store::addresses::AddressesArgs;
+
+pub async fn run(
+    AddressesArgs {
+        program_id,
+        publisher,
+    }: AddressesArgs,
+) -> Result<()> {
+    let (config, config_bump) = compute_config_account(program_id);
+    output::result(
+        format!("config: {config} (bump {config_bump})"),
+        json!({
+            "name": "config",
+            "address": config.to_string(),
+            "bump": config_bump,
+        }),
+    );
+
+    for publisher in publisher {
+        let (publisher_config, bump) = compute_publisher_config_account(program_id, publisher);
+        output::result(
+            format!("publisher_config for {publisher}: {publisher_config} (bump {bump})"),
+            json!({
+                "name": "publisher_config",
+                "publisher": publisher.to_string(),
+                "address": publisher_config.to_string(),
+                "bump": bump,
+            }),
+        );
+    }
+
+    Ok(())
+}
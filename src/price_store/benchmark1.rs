@@ -1,88 +1,722 @@
 //! Benchmark that sends price updates to the Price Store.
 //!
 //! It is sending updates in parallel on behalf of each know publisher, for as many prices in each
-//! update as specified.  Updates are sent directly to the UDP port of the current leader.
+//! update as specified.  Updates are sent via RPC, or directly to the UDP ports of the upcoming
+//! leaders, or half of the publishers use each path, to compare them.  Some of the feeds can be
+//! updated via the Oracle `UpdPrice` instead, to have both write paths active at the same time.
 //!
 //! Initially price for each product starts at the same specified value, but it drifts over time
 //! randomly to make it a bit closer to the actual production cluster behavior.  This part most
 //! likely does not matter.
 
-use std::sync::Arc;
+use std::{
+    collections::BTreeMap,
+    fs, iter,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
-use anyhow::Result;
+use aggregation::AggregationProgress;
+use anyhow::{Context as _, Result, bail};
 use derive_more::{Add, AddAssign};
+use fault_injection::{FaultInjection, FaultStats, InjectedFault};
 use futures::{
     StreamExt as _,
+    future::try_join_all,
     stream::{FuturesUnordered, select_all},
 };
-use itertools::izip;
+use itertools::{Itertools as _, izip};
+use leader_stats::SlotSends;
 use log::warn;
+use oracle_updates::OracleUpdates;
+use pacing::Pacing;
 use price_publisher::run_publisher;
+use publisher_feeds::PublisherFeeds;
+use pythnet_heisenberg::{
+    blockhash_cache::BlockhashCache,
+    failure_rate::{FailureRate, FailureRateGuard},
+    keypair_ext::read_keypair_file,
+    metrics_sink::MetricsSink,
+    node_address_service::{NodeAddressService, with_node_address_service},
+    output,
+    rpc_client_ext::RpcClientExt as _,
+};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    clock::Slot,
+    native_token::Sol,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signature},
+    signer::Signer as _,
+};
+use state_diff::StateSnapshot;
 use tokio::{
     select,
     signal::unix::{SignalKind, signal},
     sync::mpsc,
-    time::{Instant, interval_at, sleep},
+    task::JoinHandle,
+    time::{Instant, interval, interval_at, sleep},
 };
 use tokio_stream::wrappers::SignalStream;
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    args::{json_rpc_url_args::get_rpc_client, price_store::benchmark1::Benchmark1Args},
-    blockhash_cache::BlockhashCache,
-    keypair_ext::read_keypair_file,
-    node_address_service::{NodeAddressService, with_node_address_service},
+    args::{
+        json_rpc_url_args::{JsonRpcUrlArgs, get_rpc_client, websocket_url_for},
+        price_store::benchmark1::{
+            Benchmark1Args, CanaryArgs, DistributedArgs, FeedFrequency, LoadPhase, PublisherLag,
+            SendMode,
+        },
+    },
+    benchmark::results::{Better, RunSummary},
+    transfer::top_up,
+    tx_cost::{CostSummary, SignatureSample},
 };
 
+mod aggregation;
+mod distributed;
+mod fault_injection;
+mod leader_stats;
+mod oracle_updates;
+mod pacing;
 mod price_publisher;
 mod price_source;
+pub(super) mod publisher_feeds;
+mod state_diff;
+
+pub use distributed::run_worker;
+
+/// How often the `--abort-on-failure-rate` condition is checked.
+const ABORT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the update frequency is adjusted, in the `--target-tps` mode.
+const PACING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A cluster the benchmark is running against.
+///
+/// With `--compare-send-modes`, each group of publishers runs as a separate cluster, with the same
+/// node.
+struct Cluster {
+    /// Marks the stats output, when the benchmark is running against more than one cluster.
+    label: Option<&'static str>,
+    rpc_client: Arc<RpcClient>,
+    websocket_url: Url,
+    program_id: Pubkey,
+    /// Publishers that run against this cluster, when they are split by the send mode.
+    publisher_group: Option<PublisherGroup>,
+}
+
+/// One of the two `--compare-send-modes` groups.  Publishers alternate between the groups.
+#[derive(Clone, Copy)]
+struct PublisherGroup {
+    index: usize,
+    send_mode: SendMode,
+}
+
+impl PublisherGroup {
+    const ALL: [Self; 2] = [
+        Self {
+            index: 0,
+            send_mode: SendMode::Rpc,
+        },
+        Self {
+            index: 1,
+            send_mode: SendMode::Udp,
+        },
+    ];
+
+    fn label(&self) -> &'static str {
+        match self.send_mode {
+            SendMode::Rpc => "rpc",
+            SendMode::Udp => "udp",
+        }
+    }
+
+    /// Items of this group, out of the per publisher `items`.
+    fn select<T>(&self, items: impl IntoIterator<Item = T>) -> Vec<T> {
+        items
+            .into_iter()
+            .skip(self.index)
+            .step_by(Self::ALL.len())
+            .collect()
+    }
+}
+
+/// Publishers and the load they generate.  A coordinator splits it between the workers.
+#[derive(Serialize, Deserialize)]
+struct Plan {
+    program_id: Pubkey,
+    payer_keypairs: Vec<PathBuf>,
+    publisher_keypairs: Vec<PathBuf>,
+    price_buffer_pubkeys: Vec<Pubkey>,
+    load: Load,
+    duration: Duration,
+    stats_update_interval: Duration,
+}
+
+/// Load generated by each publisher.  Identical for all the clusters.
+#[derive(Clone, Serialize, Deserialize)]
+struct Load {
+    price_feed_indices: RangeInclusive<u32>,
+    price_updates_per_tx: u8,
+    update_frequency: Duration,
+    /// When not empty, overrides `update_frequency` for the specified time since the start.
+    phases: Vec<LoadPhase>,
+    /// Feed ranges that are updated with their own frequency, ignoring the `phases`.
+    feed_frequencies: Vec<FeedFrequency>,
+    publisher_lags: Vec<PublisherLag>,
+    /// Publishers that update only some of the `price_feed_indices`.
+    publisher_feeds: Vec<PublisherFeeds>,
+    price_mean: i64,
+    price_range: u64,
+    confidence_mean: u64,
+    confidence_range: u64,
+    fanout_slots: u8,
+    send_mode: SendMode,
+    fault_injection: FaultInjection,
+    /// Feeds that are updated via the Oracle, rather than the Price Store.
+    oracle_updates: Option<OracleUpdates>,
+    /// In the `--target-tps` mode, overrides `update_frequency`.  Every cluster has its own.
+    #[serde(skip)]
+    pacing: Option<Arc<Pacing>>,
+}
+
+impl Load {
+    /// Update frequency `elapsed` time after the start.  After the last phase, publishers keep
+    /// updating with the `update_frequency`.
+    fn update_frequency_at(&self, elapsed: Duration) -> Duration {
+        if let Some(pacing) = &self.pacing {
+            return pacing.update_frequency();
+        }
+        let mut phase_end = Duration::ZERO;
+        for phase in &self.phases {
+            phase_end += phase.duration;
+            if elapsed < phase_end {
+                return phase.update_frequency;
+            }
+        }
+        self.update_frequency
+    }
+
+    /// Price feeds the `publisher` updates.
+    fn publisher_feed_indices(&self, publisher: &Pubkey) -> Vec<u32> {
+        let all_feeds = self.price_feed_indices.clone();
+        match self
+            .publisher_feeds
+            .iter()
+            .find(|assignment| assignment.publisher == *publisher)
+        {
+            Some(PublisherFeeds { feeds, .. }) => all_feeds
+                .filter(|feed_index| feeds.iter().any(|feeds| feeds.contains(feed_index)))
+                .collect(),
+            None => all_feeds.collect(),
+        }
+    }
+
+    /// Splits the `feed_indices` by their update frequency.  Feeds that follow the
+    /// `update_frequency` and the `phases` come first.  Groups without any feeds are omitted.
+    fn feed_groups(&self, feed_indices: impl IntoIterator<Item = u32>) -> Vec<FeedGroup> {
+        let mut groups = iter::once(None)
+            .chain(
+                self.feed_frequencies
+                    .iter()
+                    .map(|feed_frequency| Some(feed_frequency.update_frequency)),
+            )
+            .map(|update_frequency| FeedGroup {
+                feeds: vec![],
+                update_frequency,
+            })
+            .collect::<Vec<_>>();
+        for feed_index in feed_indices {
+            let group = self
+                .feed_frequencies
+                .iter()
+                .position(|feed_frequency| feed_frequency.feeds.contains(&feed_index))
+                .map_or(0, |position| position + 1);
+            groups[group].feeds.push(feed_index);
+        }
+        groups.retain(|group| !group.feeds.is_empty());
+        groups
+    }
+
+    /// Number of updates each publisher sends within `duration`, for the feeds that follow the
+    /// `update_frequency` and the `phases`, if none of them are late.
+    fn updates_within(&self, duration: Duration) -> u64 {
+        let mut total = 0u64;
+        let mut left = duration;
+        for phase in &self.phases {
+            let phase_duration = phase.duration.min(left);
+            total = total.saturating_add(updates(phase_duration, phase.update_frequency));
+            left -= phase_duration;
+        }
+        total.saturating_add(updates(left, self.update_frequency))
+    }
+
+    /// Oracle price account of the `feed_index`, if the feed is updated via the Oracle.
+    fn oracle_price_account(&self, feed_index: u32) -> Option<Pubkey> {
+        self.oracle_updates
+            .as_ref()?
+            .price_accounts
+            .get(&feed_index)
+            .copied()
+    }
+
+    /// Number of transactions a single update of the `feeds` is sent in.
+    fn txs_per_update(&self, feeds: &[u32]) -> u64 {
+        let txs =
+            |feeds: usize, updates_per_tx: u8| (feeds as u64).div_ceil(u64::from(updates_per_tx));
+        let oracle_feeds = feeds
+            .iter()
+            .filter(|feed_index| self.oracle_price_account(**feed_index).is_some())
+            .count();
+        let oracle_txs = self.oracle_updates.as_ref().map_or(0, |oracle_updates| {
+            txs(oracle_feeds, oracle_updates.updates_per_tx)
+        });
+        txs(feeds.len() - oracle_feeds, self.price_updates_per_tx) + oracle_txs
+    }
+
+    /// Number of transactions `publishers` send for one update of all their feeds.
+    fn txs_per_round(&self, publishers: usize) -> u64 {
+        let all_feeds = self.price_feed_indices.clone().collect::<Vec<_>>();
+        let unassigned = publishers.saturating_sub(self.publisher_feeds.len()) as u64;
+        self.publisher_feeds
+            .iter()
+            .map(|PublisherFeeds { publisher, .. }| {
+                self.txs_per_update(&self.publisher_feed_indices(publisher))
+            })
+            .fold(
+                unassigned.saturating_mul(self.txs_per_update(&all_feeds)),
+                u64::saturating_add,
+            )
+    }
+
+    /// Upper bound on the fees paid for the transactions of one publisher within `duration`.
+    /// Publishers with a subset of the feeds pay less.
+    fn fees_per_publisher(&self, duration: Duration, lamports_per_signature: u64) -> u64 {
+        let txs = self
+            .feed_groups(self.price_feed_indices.clone())
+            .iter()
+            .map(
+                |FeedGroup {
+                     feeds,
+                     update_frequency,
+                 }| {
+                    let txs_per_update = self.txs_per_update(feeds);
+                    let updates = match update_frequency {
+                        Some(update_frequency) => updates(duration, *update_frequency),
+                        None => self.updates_within(duration),
+                    };
+                    updates.saturating_mul(txs_per_update)
+                },
+            )
+            .fold(0u64, u64::saturating_add);
+        // Every transaction is signed by the payer and by the publisher.
+        txs.saturating_mul(2 * lamports_per_signature)
+    }
+}
+
+/// Price feeds that are updated together.
+struct FeedGroup {
+    feeds: Vec<u32>,
+    /// `None` for the feeds that follow the [`Load`] `update_frequency` and `phases`.
+    update_frequency: Option<Duration>,
+}
+
+/// Number of updates within `duration`, sent every `update_frequency`.
+fn updates(duration: Duration, update_frequency: Duration) -> u64 {
+    let updates = duration
+        .as_nanos()
+        .div_ceil(update_frequency.as_nanos().max(1));
+    u64::try_from(updates).unwrap_or(u64::MAX)
+}
 
 pub async fn run(
     Benchmark1Args {
         json_rpc_url,
+        scenario,
         websocket_url,
         fanout_slots,
         program_id,
         payer_keypair: payer_keypairs,
+        treasury_keypair,
         publisher_keypair: publisher_keypairs,
         price_buffer_pubkey: price_buffer_pubkeys,
         price_feed_index_start,
         price_feed_index_end,
         price_updates_per_tx,
         update_frequency,
+        phase: phases,
+        feed_update_frequency: feed_frequencies,
+        target_tps,
+        publisher_lag: publisher_lags,
+        publisher_feeds: publisher_feeds_file,
+        oracle_update_percent,
+        oracle_updates_per_tx,
+        send_mode,
+        compare_send_modes,
         price_mean,
         price_range,
         confidence_mean,
         confidence_range,
         duration,
         stats_update_interval,
+        report_costs,
+        cost_sample_size,
+        leader_stats,
+        results_dir,
+        state_diff,
+        track_aggregation,
+        aggregation_poll_interval,
+        oracle_program_id,
+        fault_injection,
+        canary:
+            CanaryArgs {
+                canary_rpc_url,
+                canary_websocket_url,
+                canary_program_id,
+            },
+        distributed:
+            DistributedArgs {
+                workers,
+                coordinator_listen,
+                start_delay,
+            },
+        abort,
+        metrics,
     }: Benchmark1Args,
 ) -> Result<()> {
-    let rpc_client = Arc::new(get_rpc_client(json_rpc_url));
+    if let Some(scenario) = &scenario {
+        print_scenario(scenario)?;
+    }
+
+    // Without an explicit duration, the benchmark runs for all the load phases.
+    let duration = duration
+        .map(Into::into)
+        .unwrap_or_else(|| phases.iter().map(|phase| phase.duration).sum());
+
+    let publisher_feeds = publisher_feeds_file
+        .map(|path| publisher_feeds::read(&path))
+        .transpose()?
+        .unwrap_or_default();
+    if !publisher_feeds.is_empty() {
+        publisher_feeds::print_coverage(
+            &publisher_feeds,
+            &(price_feed_index_start..=price_feed_index_end),
+            publisher_keypairs.len(),
+        );
+    }
+
+    // `check_are_valid()` makes sure `--oracle-program-id` is present with
+    // `--oracle-update-percent`.
+    let oracle_update_program = oracle_program_id.filter(|_| oracle_update_percent != 0);
+    let price_feed_indices = price_feed_index_start..=price_feed_index_end;
+    // Price accounts of the canary cluster are fetched separately, before the run.
+    let oracle_updates = match oracle_update_program {
+        Some(oracle_program_id) => Some(
+            oracle_updates::fetch(
+                &get_rpc_client(json_rpc_url.clone()),
+                canary_rpc_url.as_ref().map(|_| "baseline"),
+                oracle_program_id,
+                oracle_update_percent,
+                oracle_updates_per_tx,
+                &price_feed_indices,
+            )
+            .await?,
+        ),
+        None => None,
+    };
+
+    let mut load = Load {
+        price_feed_indices,
+        price_updates_per_tx,
+        update_frequency: update_frequency.into(),
+        phases,
+        feed_frequencies,
+        publisher_lags,
+        publisher_feeds,
+        price_mean,
+        price_range,
+        confidence_mean,
+        confidence_range,
+        fanout_slots,
+        send_mode,
+        fault_injection: FaultInjection::new(fault_injection),
+        oracle_updates,
+        pacing: None,
+    };
+    if let Some(target_tps) = target_tps {
+        let txs_per_round = load.txs_per_round(publisher_keypairs.len());
+        load.update_frequency = Pacing::initial_update_frequency(target_tps, txs_per_round);
+        output::result(
+            format!(
+                "Target TPS: {target_tps}, {txs_per_round} txs per update, initial update \
+                 frequency: {:?}",
+                load.update_frequency,
+            ),
+            json!({
+                "target_tps": target_tps,
+                "txs_per_update": txs_per_round,
+                "initial_update_frequency_ms": load.update_frequency.as_millis(),
+            }),
+        );
+    }
+
+    let treasury = treasury_keypair.map(read_keypair_file).transpose()?;
+    // `check_are_valid()` makes sure `--oracle-program-id` is present with `--state-diff`.
+    let state_diff_oracle = oracle_program_id.filter(|_| state_diff);
+    let aggregation_oracle = oracle_program_id.filter(|_| track_aggregation);
+    let aggregation_poll_interval: Duration = aggregation_poll_interval.into();
+
+    if let Some(workers) = workers {
+        let rpc_client = get_rpc_client(json_rpc_url);
+        if let Some(treasury) = &treasury {
+            fund_payers(
+                &rpc_client,
+                treasury,
+                &payer_keypairs,
+                publisher_keypairs.len(),
+                &load,
+                duration,
+            )
+            .await?;
+        }
+
+        let state_before = match &state_diff_oracle {
+            Some(oracle_program_id) => Some(
+                take_state_snapshots(
+                    oracle_program_id,
+                    &[&rpc_client],
+                    &load.price_feed_indices,
+                    &price_buffer_pubkeys,
+                    None,
+                )
+                .await?,
+            ),
+            None => None,
+        };
+        let price_feed_indices = load.price_feed_indices.clone();
+
+        let aggregation_shutdown = CancellationToken::new();
+        let aggregation_tracker = aggregation_oracle.map(|oracle_program_id| {
+            tokio::spawn(aggregation::track(
+                rpc_client.clone(),
+                oracle_program_id,
+                price_feed_indices.clone(),
+                aggregation_poll_interval,
+                aggregation_shutdown.clone(),
+            ))
+        });
+
+        let metrics = metrics.start_sink();
+        let res = distributed::run_coordinator(
+            coordinator_listen,
+            workers,
+            start_delay.into(),
+            Plan {
+                program_id,
+                payer_keypairs,
+                publisher_keypairs: publisher_keypairs.clone(),
+                price_buffer_pubkeys: price_buffer_pubkeys.clone(),
+                load,
+                duration,
+                stats_update_interval: stats_update_interval.into(),
+            },
+            metrics.as_ref(),
+        )
+        .await;
+        if let Some(metrics) = metrics {
+            metrics.close().await;
+        }
+        aggregation_shutdown.cancel();
+        res?;
+
+        if let Some(aggregation_tracker) = aggregation_tracker {
+            print_aggregation_progress(vec![(None, aggregation_tracker)]).await?;
+        }
+
+        if let (Some(oracle_program_id), Some(state_before)) = (&state_diff_oracle, state_before) {
+            print_state_diffs(
+                oracle_program_id,
+                &[(None, &rpc_client)],
+                state_before,
+                &price_feed_indices,
+                &publisher_keypairs,
+                &price_buffer_pubkeys,
+            )
+            .await?;
+        }
+
+        return Ok(());
+    }
+
+    let mut clusters = vec![Cluster {
+        label: None,
+        rpc_client: get_rpc_client(json_rpc_url.clone()),
+        websocket_url,
+        program_id,
+        publisher_group: None,
+    }];
+    if let Some(canary_rpc_url) = canary_rpc_url {
+        clusters[0].label = Some("baseline");
+        let websocket_url =
+            canary_websocket_url.unwrap_or_else(|| websocket_url_for(&canary_rpc_url));
+        clusters.push(Cluster {
+            label: Some("canary"),
+            rpc_client: get_rpc_client(JsonRpcUrlArgs {
+                rpc_url: canary_rpc_url,
+                ..json_rpc_url
+            }),
+            websocket_url,
+            program_id: canary_program_id.unwrap_or(program_id),
+            publisher_group: None,
+        });
+    }
+
+    if let Some(treasury) = &treasury {
+        for Cluster { rpc_client, .. } in &clusters {
+            fund_payers(
+                rpc_client,
+                treasury,
+                &payer_keypairs,
+                publisher_keypairs.len(),
+                &load,
+                duration,
+            )
+            .await?;
+        }
+    }
+
+    // `check_are_valid()` makes sure there is no canary with `--compare-send-modes`.
+    if compare_send_modes {
+        let Cluster {
+            rpc_client,
+            websocket_url,
+            program_id,
+            ..
+        } = clusters.remove(0);
+        clusters = PublisherGroup::ALL
+            .map(|group| Cluster {
+                label: Some(group.label()),
+                rpc_client: rpc_client.clone(),
+                websocket_url: websocket_url.clone(),
+                program_id,
+                publisher_group: Some(group),
+            })
+            .into();
+    }
 
     let publishers_shutdown = CancellationToken::new();
+    let metrics = metrics.start_sink();
 
-    let payers = payer_keypairs
-        .into_iter()
-        .map(|keypair_file| read_keypair_file(&keypair_file))
-        .collect::<Result<Vec<_>>>()?;
+    // `Keypair` is not `Clone`, so every cluster gets its own copy, read from the same files.
+    let mut cluster_keypairs = vec![];
+    for _ in &clusters {
+        let payers = payer_keypairs
+            .iter()
+            .map(read_keypair_file)
+            .collect::<Result<Vec<_>>>()?;
 
-    let publishers = publisher_keypairs
-        .into_iter()
-        .map(|keypair_file| read_keypair_file(&keypair_file))
-        .collect::<Result<Vec<_>>>()?;
+        let publishers = publisher_keypairs
+            .iter()
+            .map(read_keypair_file)
+            .collect::<Result<Vec<_>>>()?;
 
-    let price_feed_indices = price_feed_index_start..=price_feed_index_end;
+        cluster_keypairs.push((payers, publishers));
+    }
 
-    let benchmark_start = chrono::Local::now();
-    let benchmark_end_timer = sleep(duration.into());
-    tokio::pin!(benchmark_end_timer);
+    if let Some((_payers, publishers)) = cluster_keypairs.first() {
+        let is_publisher =
+            |pubkey: &Pubkey| publishers.iter().any(|keypair| keypair.pubkey() == *pubkey);
+        for PublisherLag { publisher, .. } in &load.publisher_lags {
+            if !is_publisher(publisher) {
+                bail!(
+                    "--publisher-lag: {publisher} is not one of the --publisher-keypair publishers"
+                );
+            }
+        }
+        for PublisherFeeds { publisher, .. } in &load.publisher_feeds {
+            if !is_publisher(publisher) {
+                bail!(
+                    "--publisher-feeds: {publisher} is not one of the --publisher-keypair \
+                     publishers"
+                );
+            }
+        }
+    }
+
+    let cluster_keypairs = izip!(&clusters, cluster_keypairs)
+        .map(
+            |(
+                Cluster {
+                    publisher_group, ..
+                },
+                (payers, publishers),
+            )| match publisher_group {
+                Some(group) => (group.select(payers), group.select(publishers)),
+                None => (payers, publishers),
+            },
+        )
+        .collect::<Vec<_>>();
 
-    let stats_update_interval = {
-        let update_interval = stats_update_interval.into();
-        interval_at(Instant::now() + update_interval, update_interval)
+    let cluster_rpc_clients = clusters
+        .iter()
+        .map(|Cluster { rpc_client, .. }| &**rpc_client)
+        .collect::<Vec<_>>();
+    let state_before = match &state_diff_oracle {
+        Some(oracle_program_id) => Some(
+            take_state_snapshots(
+                oracle_program_id,
+                &cluster_rpc_clients,
+                &load.price_feed_indices,
+                &price_buffer_pubkeys,
+                None,
+            )
+            .await?,
+        ),
+        None => None,
     };
-    tokio::pin!(stats_update_interval);
+
+    let aggregation_trackers = match aggregation_oracle {
+        Some(oracle_program_id) => clusters
+            .iter()
+            .map(
+                |Cluster {
+                     label, rpc_client, ..
+                 }| {
+                    let tracker = tokio::spawn(aggregation::track(
+                        rpc_client.clone(),
+                        oracle_program_id,
+                        load.price_feed_indices.clone(),
+                        aggregation_poll_interval,
+                        publishers_shutdown.clone(),
+                    ));
+                    (*label, tracker)
+                },
+            )
+            .collect(),
+        None => vec![],
+    };
+
+    let benchmark_start = chrono::Local::now();
+    let run_start = Instant::now();
+    let mut run_summary = results_dir
+        .as_ref()
+        .map(|_| {
+            RunSummary::new(
+                "price-store benchmark1",
+                benchmark_start,
+                scenario.as_deref(),
+            )
+        })
+        .transpose()?;
+    let benchmark_end_timer = sleep(duration);
+    tokio::pin!(benchmark_end_timer);
 
     let stop_signals = select_all([
         SignalStream::new(signal(SignalKind::interrupt()).expect("Can install a SIGINT handler")),
@@ -90,33 +724,444 @@ pub async fn run(
     ]);
     tokio::pin!(stop_signals);
 
-    println!("Benchmark start time: {}", benchmark_start);
+    output::result(
+        format!("Benchmark start time: {benchmark_start}"),
+        json!({ "benchmark_start": benchmark_start.to_rfc3339() }),
+    );
+
+    // Clusters may process transactions at different rates, so each one is paced separately.
+    let mut cluster_loads = vec![];
+    for (
+        index,
+        Cluster {
+            label,
+            rpc_client,
+            publisher_group,
+            ..
+        },
+    ) in clusters.iter().enumerate()
+    {
+        let mut cluster_load = Load {
+            send_mode: publisher_group.map_or(load.send_mode, |group| group.send_mode),
+            pacing: target_tps
+                .map(|target_tps| Arc::new(Pacing::new(target_tps, load.update_frequency))),
+            ..load.clone()
+        };
+        // The `load` already has the Oracle price accounts of the first cluster.  Publisher groups
+        // all run against the first cluster.
+        if let Some(oracle_program_id) =
+            oracle_update_program.filter(|_| index != 0 && publisher_group.is_none())
+        {
+            cluster_load.oracle_updates = Some(
+                oracle_updates::fetch(
+                    rpc_client,
+                    *label,
+                    oracle_program_id,
+                    oracle_update_percent,
+                    oracle_updates_per_tx,
+                    &load.price_feed_indices,
+                )
+                .await?,
+            );
+        }
+        cluster_loads.push(cluster_load);
+    }
+
+    // Cluster runs borrow `metrics`, so they need to be gone before it is closed.
+    let cluster_stats = {
+        let cluster_runs = try_join_all(izip!(&clusters, cluster_keypairs, &cluster_loads).map(
+            |(cluster, keypairs, load)| {
+                let price_buffer_pubkeys = match &cluster.publisher_group {
+                    Some(group) => group.select(price_buffer_pubkeys.iter().copied()),
+                    None => price_buffer_pubkeys.clone(),
+                };
+                run_cluster(
+                    cluster,
+                    keypairs,
+                    price_buffer_pubkeys,
+                    load,
+                    stats_update_interval.into(),
+                    metrics.as_ref(),
+                    (report_costs || compare_send_modes || leader_stats)
+                        .then(|| SignatureSample::new(cost_sample_size)),
+                    leader_stats.then(SlotSends::default),
+                    publishers_shutdown.clone(),
+                    None,
+                    abort.failure_rate_guard(),
+                )
+            },
+        ));
+        tokio::pin!(cluster_runs);
 
+        loop {
+            select! {
+                cluster_stats = &mut cluster_runs => break cluster_stats?,
+                () = &mut benchmark_end_timer, if !benchmark_end_timer.is_elapsed() => {
+                    publishers_shutdown.cancel();
+                }
+                stop_res = stop_signals.next() => match stop_res {
+                    Some(()) => publishers_shutdown.cancel(),
+                    None => panic!("`stop_signals` stream show never complete"),
+                },
+            }
+        }
+    };
+
+    // Publishers should not exit by themselves, but it does not hurt to make sure all the exit
+    // flags are set at this point.
+    publishers_shutdown.cancel();
+
+    let run_time = run_start.elapsed();
+
+    let (cluster_stats, cost_samples, slot_sends, aborted): (Vec<_>, Vec<_>, Vec<_>, Vec<_>) =
+        cluster_stats.into_iter().multiunzip();
+
+    for (Cluster { label, .. }, stats, load) in izip!(&clusters, &cluster_stats, &cluster_loads) {
+        print_stats(*label, stats);
+        push_stats(metrics.as_ref(), *label, stats);
+        if let Some(run_summary) = &mut run_summary {
+            record_stats(run_summary, *label, stats, run_time);
+        }
+        if let Some(pacing) = &load.pacing {
+            print_pacing(*label, pacing);
+        }
+    }
+    if let ([baseline, other], [baseline_stats, other_stats]) =
+        (clusters.as_slice(), cluster_stats.as_slice())
+    {
+        print_comparison(
+            (baseline.label.unwrap_or_default(), baseline_stats),
+            (other.label.unwrap_or_default(), other_stats),
+        );
+    }
+
+    let aborted = izip!(&clusters, aborted)
+        .filter_map(|(Cluster { label, .. }, aborted)| Some((*label, aborted?)))
+        .collect::<Vec<_>>();
+    for (label, failure_rate) in &aborted {
+        let prefix = label.map(|label| format!("[{label}] ")).unwrap_or_default();
+        output::result(
+            format!("  {prefix}Aborted: {failure_rate}"),
+            json!({
+                "label": label,
+                "aborted": {
+                    "failure_rate": failure_rate.percent,
+                    "failed_tx": failure_rate.failed,
+                    "total_tx": failure_rate.total,
+                    "window_ms": failure_rate.window.as_millis(),
+                },
+            }),
+        );
+    }
+
+    print_aggregation_progress(aggregation_trackers).await?;
+
+    if let (Some(oracle_program_id), Some(state_before)) = (&state_diff_oracle, state_before) {
+        let clusters = clusters
+            .iter()
+            .map(
+                |Cluster {
+                     label, rpc_client, ..
+                 }| (*label, &**rpc_client),
+            )
+            .collect::<Vec<_>>();
+        print_state_diffs(
+            oracle_program_id,
+            &clusters,
+            state_before,
+            &load.price_feed_indices,
+            &publisher_keypairs,
+            &price_buffer_pubkeys,
+        )
+        .await?;
+    }
+
+    let mut cluster_costs = vec![];
+    for (
+        Cluster {
+            label, rpc_client, ..
+        },
+        cost_sample,
+    ) in izip!(&clusters, cost_samples)
+    {
+        let Some(cost_sample) = cost_sample else {
+            cluster_costs.push(None);
+            continue;
+        };
+        output::notice(format!(
+            "Fetching metadata for {} transactions...",
+            cost_sample.len()
+        ));
+        let costs = CostSummary::collect_sample(rpc_client, &cost_sample).await;
+        costs.print(
+            *label,
+            Some((u64::from(price_updates_per_tx), "price update")),
+        );
+        if let Some(run_summary) = &mut run_summary {
+            let prefix = label.map(|label| format!("{label}.")).unwrap_or_default();
+            if let Some(landing_rate) = costs.landing_rate() {
+                run_summary.add(
+                    format!("{prefix}landing_rate_percent"),
+                    landing_rate,
+                    Better::Higher,
+                );
+            }
+            if let Some(latency) = costs.median_landing_latency() {
+                run_summary.add(
+                    format!("{prefix}landing_latency_p50_slots"),
+                    latency as f64,
+                    Better::Lower,
+                );
+            }
+        }
+        cluster_costs.push(Some(costs));
+    }
+    if let ([baseline, other], [Some(baseline_costs), Some(other_costs)]) =
+        (clusters.as_slice(), cluster_costs.as_slice())
+    {
+        print_landing_comparison(
+            (baseline.label.unwrap_or_default(), baseline_costs),
+            (other.label.unwrap_or_default(), other_costs),
+        );
+    }
+
+    for (
+        Cluster {
+            label, rpc_client, ..
+        },
+        slot_sends,
+        costs,
+    ) in izip!(&clusters, slot_sends, &cluster_costs)
+    {
+        let Some(slot_sends) = slot_sends else {
+            continue;
+        };
+        // Leader stats are secondary, so the run results are still reported if they fail.
+        match leader_stats::collect(rpc_client, &slot_sends, costs.as_ref()).await {
+            Ok(Some(leader_stats)) => leader_stats.print(*label),
+            Ok(None) => (),
+            Err(err) => output::notice(format!("Failed to collect leader stats: {err:#}")),
+        }
+    }
+
+    if let Some(metrics) = metrics {
+        metrics.close().await;
+    }
+
+    let benchmark_end = chrono::Local::now();
+    output::result(
+        format!("Benchmark end time:   {benchmark_end}"),
+        json!({ "benchmark_end": benchmark_end.to_rfc3339() }),
+    );
+
+    if let (Some(run_summary), Some(results_dir)) = (run_summary, &results_dir) {
+        run_summary.save(results_dir, benchmark_end)?;
+    }
+
+    if let Some((_label, failure_rate)) = aborted.first() {
+        bail!("Benchmark aborted: {failure_rate}");
+    }
+
+    Ok(())
+}
+
+/// Tops up payers from the `treasury`, so that each one can pay for all the transactions the
+/// benchmark could send on its behalf.
+async fn fund_payers(
+    rpc_client: &RpcClient,
+    treasury: &Keypair,
+    payer_keypairs: &[PathBuf],
+    publishers: usize,
+    load: &Load,
+    duration: Duration,
+) -> Result<()> {
+    let lamports_per_signature = rpc_client
+        .get_lamports_per_signature()
+        .await
+        .context("Estimating transaction fees")?;
+    let fees = load.fees_per_publisher(duration, lamports_per_signature);
+
+    // Publishers without a payer do not run, and the same payer may be used by more than one
+    // publisher.
+    let mut targets = BTreeMap::<Pubkey, u64>::new();
+    for path in payer_keypairs.iter().take(publishers) {
+        let payer = read_keypair_file(path)?.pubkey();
+        *targets.entry(payer).or_default() += fees;
+    }
+    // New accounts need to be rent exempt.
+    let minimum_balance = Rent::default().minimum_balance(0);
+    let targets = targets
+        .into_iter()
+        .map(|(payer, target)| (payer, target.max(minimum_balance)))
+        .collect::<Vec<_>>();
+
+    output::notice(format!(
+        "Funding {} payers from {}, to cover up to {} in fees per publisher ...",
+        targets.len(),
+        treasury.pubkey(),
+        Sol(fees),
+    ));
+    top_up(rpc_client, treasury, &targets)
+        .await
+        .context("Failed to fund the payers")
+}
+
+/// Snapshots the accounts the benchmark updates, on every cluster, for the `--state-diff`.
+async fn take_state_snapshots(
+    oracle_program_id: &Pubkey,
+    rpc_clients: &[&RpcClient],
+    price_feed_indices: &RangeInclusive<u32>,
+    price_buffers: &[Pubkey],
+    state_before: Option<&[StateSnapshot]>,
+) -> Result<Vec<StateSnapshot>> {
+    try_join_all(rpc_clients.iter().enumerate().map(|(index, rpc_client)| {
+        // The after snapshots should not reuse the price accounts read for the before snapshots.
+        let min_slot = state_before.map(|state_before| state_before[index].slot() + 1);
+        StateSnapshot::take(
+            rpc_client,
+            oracle_program_id,
+            price_feed_indices,
+            price_buffers,
+            min_slot,
+        )
+    }))
+    .await
+    .context("Failed to take a state snapshot")
+}
+
+/// Takes the after snapshots, and prints how the state changed on every cluster, compared to the
+/// `state_before`.
+async fn print_state_diffs(
+    oracle_program_id: &Pubkey,
+    clusters: &[(Option<&str>, &RpcClient)],
+    state_before: Vec<StateSnapshot>,
+    price_feed_indices: &RangeInclusive<u32>,
+    publisher_keypairs: &[PathBuf],
+    price_buffers: &[Pubkey],
+) -> Result<()> {
+    let rpc_clients = clusters
+        .iter()
+        .map(|(_label, rpc_client)| *rpc_client)
+        .collect::<Vec<_>>();
+    let state_after = take_state_snapshots(
+        oracle_program_id,
+        &rpc_clients,
+        price_feed_indices,
+        price_buffers,
+        Some(&state_before),
+    )
+    .await?;
+
+    let publishers = publisher_keypairs
+        .iter()
+        .map(|path| read_keypair_file(path).map(|keypair| keypair.pubkey()))
+        .collect::<Result<Vec<_>>>()?;
+
+    for ((label, _rpc_client), before, after) in izip!(clusters, &state_before, &state_after) {
+        state_diff::print_diff(*label, before, after, &publishers, price_buffers);
+    }
+
+    Ok(())
+}
+
+/// Waits for the `--track-aggregation` trackers to finish, and prints their results.
+async fn print_aggregation_progress(
+    trackers: Vec<(Option<&str>, JoinHandle<Result<AggregationProgress>>)>,
+) -> Result<()> {
+    for (label, tracker) in trackers {
+        let progress = tracker
+            .await
+            .context("Aggregation tracker panicked")?
+            .context("Aggregation tracking failed")?;
+        progress.print(label);
+    }
+    Ok(())
+}
+
+/// Includes the scenario file into the output, so that the run can be reproduced from the output
+/// alone.
+fn print_scenario(path: &Path) -> Result<()> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read scenario file: {}", path.to_string_lossy()))?;
+    let parsed = serde_yaml::from_str::<serde_json::Value>(&content)
+        .with_context(|| format!("Failed to parse scenario file: {}", path.to_string_lossy()))?;
+    output::result(
+        format!(
+            "Scenario: {}\n{}",
+            path.to_string_lossy(),
+            content.trim_end()
+        ),
+        json!({
+            "scenario": {
+                "path": path.to_string_lossy(),
+                "content": parsed,
+            },
+        }),
+    );
+    Ok(())
+}
+
+/// Runs all the publishers against one cluster, until `publishers_shutdown` is cancelled.
+///
+/// Signatures of the successfully sent transactions are added to the `cost_sample`, if provided.
+/// Outcomes of all the sends are counted per slot in the `slot_sends`, if provided.  A copy of the
+/// stats is sent into `stats_updates` every `stats_update_interval`, if provided.
+#[allow(clippy::too_many_arguments)]
+async fn run_cluster(
+    Cluster {
+        label,
+        rpc_client,
+        websocket_url,
+        program_id,
+        publisher_group: _,
+    }: &Cluster,
+    (payers, publishers): (Vec<Keypair>, Vec<Keypair>),
+    price_buffer_pubkeys: Vec<Pubkey>,
+    load: &Load,
+    stats_update_interval: Duration,
+    metrics: Option<&MetricsSink>,
+    mut cost_sample: Option<SignatureSample>,
+    mut slot_sends: Option<SlotSends>,
+    publishers_shutdown: CancellationToken,
+    stats_updates: Option<&mpsc::UnboundedSender<RunStats>>,
+    mut failure_rate_guard: Option<FailureRateGuard>,
+) -> Result<(
+    RunStats,
+    Option<SignatureSample>,
+    Option<SlotSends>,
+    Option<FailureRate>,
+)> {
     let (update_results_tx, mut update_results_rx) = mpsc::channel(1000);
     let mut stats = RunStats::default();
 
+    let mut stats_update_interval = interval_at(
+        Instant::now() + stats_update_interval,
+        stats_update_interval,
+    );
+
+    let mut abort_check_interval = interval(ABORT_CHECK_INTERVAL);
+    let mut aborted = None;
+
+    let mut pacing_interval = interval_at(Instant::now() + PACING_INTERVAL, PACING_INTERVAL);
+    let mut last_pacing_check = (Instant::now(), 0u64, 0u64);
+
     let publishers_task = {
-        let rpc_client = rpc_client.clone();
         let stats = &mut stats;
+        let cost_sample = &mut cost_sample;
+        let slot_sends = &mut slot_sends;
+        let aborted = &mut aborted;
         async move |blockhash_cache: &BlockhashCache, node_address_service: NodeAddressService| {
             let mut publishers = izip!(payers, publishers, price_buffer_pubkeys)
                 .map(|(payer, publisher, price_buffer)| {
                     run_publisher(
-                        &rpc_client,
-                        program_id,
+                        rpc_client,
+                        *program_id,
                         payer,
                         publisher,
                         price_buffer,
-                        price_feed_indices.clone(),
-                        price_updates_per_tx,
-                        update_frequency.into(),
-                        price_mean,
-                        price_range,
-                        confidence_mean,
-                        confidence_range,
+                        load,
                         blockhash_cache,
                         &node_address_service,
-                        fanout_slots,
                         update_results_tx.clone(),
                         publishers_shutdown.clone(),
                     )
@@ -140,82 +1185,383 @@ pub async fn run(
                     update_result_res = update_results_rx.recv(),
                         if !update_results_rx.is_closed() =>
                     if let Some(update_result) = update_result_res {
+                        if let (
+                            Some(cost_sample),
+                            PriceUpdateResult::Success(Some((signature, sent_slot))),
+                        ) = (cost_sample.as_mut(), &update_result)
+                        {
+                            cost_sample.add(*signature, *sent_slot);
+                        }
+                        if let Some(slot_sends) = slot_sends.as_mut() {
+                            slot_sends.include(
+                                &update_result,
+                                node_address_service.estimated_current_slot(),
+                            );
+                        }
                         stats.include(update_result);
                     },
                     _at = stats_update_interval.tick() => {
-                        print_stats(stats);
+                        print_stats(*label, stats);
+                        push_stats(metrics, *label, stats);
+                        if let Some(stats_updates) = stats_updates {
+                            // The receiver only goes away when the worker is shutting down.
+                            let _ = stats_updates.send(stats.clone());
+                        }
+                        if let Some(pacing) = &load.pacing {
+                            print_pacing(*label, pacing);
+                        }
+                        print_leader(*label, &node_address_service);
                     }
-                    () = &mut benchmark_end_timer, if !benchmark_end_timer.is_elapsed() => {
-                        publishers_shutdown.cancel();
+                    at = pacing_interval.tick(), if load.pacing.is_some() => {
+                        let pacing = load.pacing.as_ref().expect("Checked in the guard");
+                        let (last_at, last_successful_tx, last_failed_tx) = last_pacing_check;
+                        pacing.adjust(
+                            stats.successful_tx.saturating_sub(last_successful_tx),
+                            stats.failed_tx.saturating_sub(last_failed_tx),
+                            at.saturating_duration_since(last_at),
+                        );
+                        last_pacing_check = (at, stats.successful_tx, stats.failed_tx);
+                    }
+                    at = abort_check_interval.tick(),
+                        if failure_rate_guard.is_some() && aborted.is_none() =>
+                    {
+                        let guard = failure_rate_guard.as_mut().expect("Checked in the guard");
+                        if let Some(failure_rate) =
+                            guard.check(at, stats.successful_tx, stats.failed_tx)
+                        {
+                            let prefix =
+                                label.map(|label| format!("[{label}] ")).unwrap_or_default();
+                            output::notice(format!(
+                                "{prefix}Stopping the benchmark: {failure_rate}, at or above \
+                                 the --abort-on-failure-rate of {}%",
+                                guard.threshold(),
+                            ));
+                            *aborted = Some(failure_rate);
+                            publishers_shutdown.cancel();
+                        }
                     }
-                    stop_res = stop_signals.next() => match stop_res {
-                        Some(()) => publishers_shutdown.cancel(),
-                        None => panic!("`stop_signals` stream show never complete"),
-                    },
                 }
             }
-
-            // Publishers should not exit by themselves, but it does not hurt to make sure
-            // all the exit flags are set at this point.
-            publishers_shutdown.cancel();
         }
     };
 
-    with_node_address_service(rpc_client, websocket_url.as_str())
+    with_node_address_service(rpc_client.clone(), websocket_url.as_str())
         .run(publishers_task)
         .await?;
 
-    print_stats(&stats);
-    println!("Benchmark end time:   {}", chrono::Local::now());
-
-    Ok(())
+    Ok((stats, cost_sample, slot_sends, aborted))
 }
 
 fn print_stats(
+    label: Option<&str>,
     RunStats {
         successful_tx,
         failed_tx,
+        faults,
     }: &RunStats,
 ) {
-    println!("  Txs: {successful_tx} successful / {failed_tx} failed");
+    let prefix = label.map(|label| format!("[{label}] ")).unwrap_or_default();
+    let mut text = format!("  {prefix}Txs: {successful_tx} successful / {failed_tx} failed");
+    let mut json = json!({
+        "successful_tx": successful_tx,
+        "failed_tx": failed_tx,
+    });
+
+    if !faults.is_empty() {
+        text = format!("{text}\n{}", faults.text());
+        json["injected_faults"] = faults.json();
+    }
+    if let Some(label) = label {
+        json["cluster"] = json!(label);
+    }
+
+    output::result(text, json);
+}
+
+fn print_pacing(label: Option<&str>, pacing: &Pacing) {
+    let prefix = label.map(|label| format!("[{label}] ")).unwrap_or_default();
+    let target_tps = pacing.target_tps();
+    let achieved_tps = pacing.achieved_tps();
+    let update_frequency = pacing.update_frequency();
+    let held_back = pacing.held_back();
+    let achieved_text = match achieved_tps {
+        Some(achieved_tps) => format!("{achieved_tps:.1}"),
+        None => "n/a".to_owned(),
+    };
+    let mut json = json!({
+        "pacing": {
+            "target_tps": target_tps,
+            "achieved_tps": achieved_tps,
+            "update_frequency_ms": update_frequency.as_millis(),
+            "held_back": held_back,
+        },
+    });
+    if let Some(label) = label {
+        json["cluster"] = json!(label);
+    }
+
+    output::result(
+        format!(
+            "  {prefix}Pacing: {achieved_text} tx/s achieved, {target_tps} tx/s target, update \
+             frequency: {update_frequency:?}{}",
+            if held_back {
+                ", not sped up, as too many transactions fail"
+            } else {
+                ""
+            },
+        ),
+        json,
+    );
+}
+
+/// Publishers send directly to the leaders, so a stall is often caused by a single leader.
+fn print_leader(label: Option<&str>, node_address_service: &NodeAddressService) {
+    let prefix = label.map(|label| format!("[{label}] ")).unwrap_or_default();
+    let slot = node_address_service.estimated_current_slot();
+    let leader = node_address_service.current_leader();
+
+    let text = match &leader {
+        Some(leader) => format!("  {prefix}Slot {slot}, leader: {leader}"),
+        None => format!("  {prefix}Slot {slot}, leader: unknown"),
+    };
+    let mut json = json!({
+        "slot": slot,
+        "leader": leader.map(|leader| json!({
+            "identity": leader.identity.to_string(),
+            "tpu": leader.tpu.map(|tpu| tpu.to_string()),
+        })),
+    });
+    if let Some(label) = label {
+        json["cluster"] = json!(label);
+    }
+
+    output::result(text, json);
+}
+
+/// Pushes the cumulative `stats` as a `benchmark1` measurement, if a metrics sink is configured.
+fn push_stats(metrics: Option<&MetricsSink>, label: Option<&str>, stats: &RunStats) {
+    let Some(metrics) = metrics else {
+        return;
+    };
+    let RunStats {
+        successful_tx,
+        failed_tx,
+        faults,
+    } = stats;
+
+    let mut point = metrics.point("benchmark1");
+    if let Some(label) = label {
+        point = point.tag("cluster", label);
+    }
+    point = point
+        .field_u64("successful_tx", *successful_tx)
+        .field_u64("failed_tx", *failed_tx);
+    if !faults.is_empty() {
+        point = faults.add_fields(point);
+    }
+    metrics.submit(point);
+}
+
+/// Adds the `stats` of one cluster to the `--results-dir` summary.  With a canary cluster, metric
+/// names are prefixed with the cluster label.
+fn record_stats(
+    run_summary: &mut RunSummary,
+    label: Option<&str>,
+    stats: &RunStats,
+    run_time: Duration,
+) {
+    let prefix = label.map(|label| format!("{label}.")).unwrap_or_default();
+    run_summary.add(
+        format!("{prefix}throughput_tps"),
+        stats.successful_tx as f64 / run_time.as_secs_f64().max(f64::EPSILON),
+        Better::Higher,
+    );
+    if let Some(success_rate) = stats.success_rate() {
+        run_summary.add(
+            format!("{prefix}success_rate_percent"),
+            success_rate,
+            Better::Higher,
+        );
+    }
+}
+
+/// Reports results of the `other` cluster relative to the `baseline` one.  The canary cluster
+/// relative to the baseline, or the direct send path relative to RPC, with `--compare-send-modes`.
+fn print_comparison(
+    (baseline_label, baseline): (&str, &RunStats),
+    (other_label, other): (&str, &RunStats),
+) {
+    let successful_tx_delta = other.successful_tx as i64 - baseline.successful_tx as i64;
+    let failed_tx_delta = other.failed_tx as i64 - baseline.failed_tx as i64;
+
+    let baseline_rate = baseline.success_rate();
+    let other_rate = other.success_rate();
+
+    let mut comparison = json!({
+        "successful_tx_delta": successful_tx_delta,
+        "failed_tx_delta": failed_tx_delta,
+    });
+    for (label, stats, rate) in [
+        (baseline_label, baseline, baseline_rate),
+        (other_label, other, other_rate),
+    ] {
+        comparison[label] = json!({
+            "successful_tx": stats.successful_tx,
+            "failed_tx": stats.failed_tx,
+            "success_rate": rate,
+        });
+    }
+
+    output::result(
+        format!(
+            "Comparison, {other_label} vs {baseline_label}:\n  \
+             Successful txs: {} vs {} ({successful_tx_delta:+})\n  \
+             Failed txs:     {} vs {} ({failed_tx_delta:+})\n  \
+             Success rate:   {} vs {}",
+            other.successful_tx,
+            baseline.successful_tx,
+            other.failed_tx,
+            baseline.failed_tx,
+            percent_text(other_rate),
+            percent_text(baseline_rate),
+        ),
+        json!({ "comparison": comparison }),
+    );
+}
+
+/// Reports the landing rate and the landing latency of the `other` cluster relative to the
+/// `baseline` one, from the sampled transactions.
+fn print_landing_comparison(
+    (baseline_label, baseline): (&str, &CostSummary),
+    (other_label, other): (&str, &CostSummary),
+) {
+    let baseline_rate = baseline.landing_rate();
+    let other_rate = other.landing_rate();
+    let baseline_latency = baseline.median_landing_latency();
+    let other_latency = other.median_landing_latency();
+
+    let rate_delta = baseline_rate
+        .zip(other_rate)
+        .map(|(baseline, other)| other - baseline);
+    let latency_delta = baseline_latency
+        .zip(other_latency)
+        .map(|(baseline, other)| other as i64 - baseline as i64);
+
+    let latency_text = |latency: Option<u64>| match latency {
+        Some(latency) => format!("{latency} slots"),
+        None => "n/a".to_owned(),
+    };
+    let rate_delta_text = rate_delta
+        .map(|delta| format!(" ({delta:+.2}%)"))
+        .unwrap_or_default();
+    let latency_delta_text = latency_delta
+        .map(|delta| format!(" ({delta:+})"))
+        .unwrap_or_default();
+
+    let mut comparison = json!({
+        "landing_rate_delta": rate_delta,
+        "median_landing_latency_delta_slots": latency_delta,
+    });
+    for (label, rate, latency) in [
+        (baseline_label, baseline_rate, baseline_latency),
+        (other_label, other_rate, other_latency),
+    ] {
+        comparison[label] = json!({
+            "landing_rate": rate,
+            "median_landing_latency_slots": latency,
+        });
+    }
+
+    output::result(
+        format!(
+            "Landing, {other_label} vs {baseline_label}:\n  \
+             Landing rate:   {} vs {}{rate_delta_text}\n  \
+             Median latency: {} vs {}{latency_delta_text}",
+            percent_text(other_rate),
+            percent_text(baseline_rate),
+            latency_text(other_latency),
+            latency_text(baseline_latency),
+        ),
+        json!({ "landing_comparison": comparison }),
+    );
+}
+
+fn percent_text(percent: Option<f64>) -> String {
+    match percent {
+        Some(percent) => format!("{percent:.2}%"),
+        None => "n/a".to_owned(),
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum PriceUpdateResult {
-    Success,
+    /// Holds the transaction signature, and the estimated slot it was sent in, when they are known.
+    Success(Option<(Signature, Slot)>),
     Fail,
+    /// A send affected by an injected fault.  These are not counted as regular successes or
+    /// failures.
+    Faulty {
+        fault: InjectedFault,
+        success: bool,
+    },
 }
 
 impl PriceUpdateResult {
-    pub fn from_result<T, E>(result: Result<T, E>) -> Self {
+    pub fn from_result<E>(result: Result<Signature, E>, sent_slot: Slot) -> Self {
         match result {
-            Ok(_) => Self::Success,
+            Ok(signature) => Self::Success(Some((signature, sent_slot))),
             Err(_) => Self::Fail,
         }
     }
 }
 
 trait ResultIntoPriceUpdateResult {
-    fn into_price_update_result(self) -> PriceUpdateResult;
+    fn into_price_update_result(self, sent_slot: Slot) -> PriceUpdateResult;
 }
 
-impl<T, E> ResultIntoPriceUpdateResult for Result<T, E> {
-    fn into_price_update_result(self) -> PriceUpdateResult {
-        PriceUpdateResult::from_result(self)
+impl<E> ResultIntoPriceUpdateResult for Result<Signature, E> {
+    fn into_price_update_result(self, sent_slot: Slot) -> PriceUpdateResult {
+        PriceUpdateResult::from_result(self, sent_slot)
     }
 }
 
-#[derive(Debug, Clone, Default, Add, AddAssign)]
+impl PriceUpdateResult {
+    /// Attributes this result to the `fault`, if there is one.
+    pub fn with_fault(self, fault: Option<InjectedFault>) -> Self {
+        match (fault, self) {
+            (None, res) => res,
+            (Some(fault), PriceUpdateResult::Success(_)) => PriceUpdateResult::Faulty {
+                fault,
+                success: true,
+            },
+            (Some(fault), PriceUpdateResult::Fail) => PriceUpdateResult::Faulty {
+                fault,
+                success: false,
+            },
+            (Some(_), res @ PriceUpdateResult::Faulty { .. }) => res,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Add, AddAssign, Serialize, Deserialize)]
 pub struct RunStats {
     successful_tx: u64,
     failed_tx: u64,
+    faults: FaultStats,
 }
 
 impl RunStats {
+    /// Percentage of successful transactions, if any were sent.
+    fn success_rate(&self) -> Option<f64> {
+        let total = self.successful_tx + self.failed_tx;
+        (total != 0).then(|| self.successful_tx as f64 / total as f64 * 100.0)
+    }
+
     fn include(&mut self, result: PriceUpdateResult) {
         match result {
-            PriceUpdateResult::Success => self.successful_tx += 1,
+            PriceUpdateResult::Success(_) => self.successful_tx += 1,
             PriceUpdateResult::Fail => self.failed_tx += 1,
+            PriceUpdateResult::Faulty { fault, success } => self.faults.include(fault, success),
         }
     }
 }
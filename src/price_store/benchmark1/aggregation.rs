@@ -0,0 +1,210 @@
+//! Tracks how the Oracle aggregates progress while a benchmark is running.
+//!
+//! Send counts show what the benchmark has delivered, and the state diff shows where the price
+//! accounts ended up.  Neither shows how often the Oracle actually produced an aggregate.  Every
+//! price account remembers the last two slots an aggregation was attempted in (`agg.pub_slot` and
+//! `valid_slot`), and the last two slots an aggregation succeeded in (`last_slot` and
+//! `prev_slot`).  Polling these often enough reconstructs the slots with a successful aggregation,
+//! which are then compared to the number of slots the run covered.
+
+use std::{
+    collections::BTreeSet,
+    mem::{offset_of, size_of},
+    ops::RangeInclusive,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result};
+use bytemuck::{Pod, pod_read_unaligned};
+use pythnet_heisenberg::{
+    oracle::accounts::price::{PriceAccount, PriceInfo},
+    output,
+};
+use serde_json::json;
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::{config::RpcAccountInfoConfig, request::MAX_MULTIPLE_ACCOUNTS};
+use solana_sdk::{clock::Slot, pubkey::Pubkey};
+use tokio::{
+    select,
+    time::{MissedTickBehavior, interval},
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::oracle::feed_index::usage::price_feed_indices;
+
+/// Only the part of a price account before the publisher components is fetched.
+const DATA_LEN: usize = offset_of!(PriceAccount, comp);
+
+/// Aggregation slots observed for the price accounts the benchmark updates.
+pub struct AggregationProgress {
+    /// Slot of the first poll.  Aggregations at or before this slot are not counted.
+    first_slot: Option<Slot>,
+    /// Slot of the last poll.
+    last_slot: Option<Slot>,
+    prices: Vec<PriceProgress>,
+}
+
+#[derive(Default)]
+struct PriceProgress {
+    attempted: BTreeSet<Slot>,
+    succeeded: BTreeSet<Slot>,
+}
+
+/// Polls the price accounts with feed indices in the `price_feed_indices` range every
+/// `poll_interval`, until the `exit` is cancelled.
+pub async fn track(
+    rpc_client: Arc<RpcClient>,
+    oracle_program_id: Pubkey,
+    price_feed_indices_range: RangeInclusive<u32>,
+    poll_interval: Duration,
+    exit: CancellationToken,
+) -> Result<AggregationProgress> {
+    let addresses = price_feed_indices(&rpc_client, &oracle_program_id)
+        .await?
+        .into_iter()
+        .filter(|(_pubkey, feed_index)| price_feed_indices_range.contains(feed_index))
+        .map(|(pubkey, _feed_index)| pubkey)
+        .collect::<Vec<_>>();
+
+    let mut progress = AggregationProgress {
+        first_slot: None,
+        last_slot: None,
+        prices: addresses.iter().map(|_| PriceProgress::default()).collect(),
+    };
+
+    let mut poll_interval = interval(poll_interval);
+    poll_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        select! {
+            _at = poll_interval.tick() => (),
+            () = exit.cancelled() => break,
+        }
+
+        // A failed poll only reduces the accuracy, so the tracking continues.
+        if let Err(err) = progress.poll(&rpc_client, &addresses).await {
+            output::notice(format!("Aggregation tracking poll failed: {err:#}"));
+        }
+    }
+
+    // Aggregations in the slots after the last poll, up to the moment the publishers stopped.
+    if let Err(err) = progress.poll(&rpc_client, &addresses).await {
+        output::notice(format!("Aggregation tracking poll failed: {err:#}"));
+    }
+
+    Ok(progress)
+}
+
+impl AggregationProgress {
+    async fn poll(&mut self, rpc_client: &RpcClient, addresses: &[Pubkey]) -> Result<()> {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig {
+                offset: 0,
+                length: DATA_LEN,
+            }),
+            commitment: Some(rpc_client.commitment()),
+            ..RpcAccountInfoConfig::default()
+        };
+
+        for (chunk_index, chunk) in addresses.chunks(MAX_MULTIPLE_ACCOUNTS).enumerate() {
+            let response = rpc_client
+                .get_multiple_accounts_with_config(chunk, config.clone())
+                .await
+                .context("Failed to fetch price accounts")?;
+
+            let slot = response.context.slot;
+            self.first_slot.get_or_insert(slot);
+            self.last_slot = self.last_slot.max(Some(slot));
+
+            let prices = &mut self.prices[chunk_index * MAX_MULTIPLE_ACCOUNTS..];
+            for (price, account) in prices.iter_mut().zip(response.value) {
+                // Accounts could have been closed after the feed indices were fetched.
+                let Some(account) = account else {
+                    continue;
+                };
+                let data = &account.data;
+                if data.len() < DATA_LEN {
+                    continue;
+                }
+
+                let agg_pub_slot = read::<Slot>(
+                    data,
+                    offset_of!(PriceAccount, agg) + offset_of!(PriceInfo, pub_slot),
+                );
+                let valid_slot = read::<Slot>(data, offset_of!(PriceAccount, valid_slot));
+                let last_slot = read::<Slot>(data, offset_of!(PriceAccount, last_slot));
+                let prev_slot = read::<Slot>(data, offset_of!(PriceAccount, prev_slot));
+
+                price.attempted.extend([agg_pub_slot, valid_slot]);
+                price.succeeded.extend([last_slot, prev_slot]);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prints the fraction of slots with an attempted and a successful aggregation, over all the
+    /// slots between the first and the last poll.
+    pub fn print(&self, label: Option<&str>) {
+        let prefix = label.map(|label| format!("[{label}] ")).unwrap_or_default();
+
+        let (Some(first_slot), Some(last_slot)) = (self.first_slot, self.last_slot) else {
+            output::result(
+                format!("  {prefix}Aggregation: no price account polls succeeded"),
+                json!({ "label": label, "aggregation": null }),
+            );
+            return;
+        };
+        let slots = last_slot.saturating_sub(first_slot);
+        let window = first_slot + 1..=last_slot;
+
+        let fractions = |slots_of: fn(&PriceProgress) -> &BTreeSet<Slot>| {
+            self.prices
+                .iter()
+                .map(|price| {
+                    let count = slots_of(price).range(window.clone()).count();
+                    count as f64 / slots.max(1) as f64
+                })
+                .collect::<Vec<_>>()
+        };
+        let attempted = fractions(|price| &price.attempted);
+        let succeeded = fractions(|price| &price.succeeded);
+
+        let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len().max(1) as f64;
+        let min = |values: &[f64]| values.iter().copied().reduce(f64::min).unwrap_or(0.);
+        let never_succeeded = succeeded.iter().filter(|fraction| **fraction == 0.).count();
+
+        output::result(
+            format!(
+                "  {prefix}Aggregation, slots {first_slot} to {last_slot}:\n    \
+                     Price accounts: {}\n    \
+                     Slots with an attempted aggregation: {:.1}% on average\n    \
+                     Slots with a successful aggregation: {:.1}% on average, {:.1}% minimum\n    \
+                     Price accounts without a successful aggregation: {never_succeeded}",
+                self.prices.len(),
+                mean(&attempted) * 100.,
+                mean(&succeeded) * 100.,
+                min(&succeeded) * 100.,
+            ),
+            json!({
+                "label": label,
+                "aggregation": {
+                    "first_slot": first_slot,
+                    "last_slot": last_slot,
+                    "price_accounts": self.prices.len(),
+                    "attempted_fraction_mean": mean(&attempted),
+                    "succeeded_fraction_mean": mean(&succeeded),
+                    "succeeded_fraction_min": min(&succeeded),
+                    "never_succeeded": never_succeeded,
+                },
+            }),
+        );
+    }
+}
+
+fn read<T: Pod>(data: &[u8], offset: usize) -> T {
+    pod_read_unaligned(&data[offset..offset + size_of::<T>()])
+}
@@ -0,0 +1,463 @@
+//! Runs `benchmark1` publishers on multiple machines.
+//!
+//! A coordinator waits for the workers to connect, splits the publishers between them, and sends
+//! every worker its part of the [`Plan`], together with a common start time.  Workers send their
+//! stats back every stats update interval, and the coordinator reports the totals.
+//!
+//! The control channel is a TCP connection carrying one JSON message per line.  It is not
+//! authenticated, and is only meant to be used inside a test network.
+
+use std::{
+    net::SocketAddr,
+    ops::Range,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use futures::{StreamExt as _, stream::select_all};
+use log::warn;
+use pythnet_heisenberg::{keypair_ext::read_keypair_file, metrics_sink::MetricsSink, output};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::json;
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader, Lines},
+    net::{
+        TcpListener, TcpStream,
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+    },
+    select,
+    signal::unix::{SignalKind, signal},
+    sync::mpsc,
+    time::{Instant, interval_at, sleep},
+};
+use tokio_stream::wrappers::SignalStream;
+use tokio_util::sync::CancellationToken;
+
+use crate::args::{
+    json_rpc_url_args::{get_rpc_client, websocket_url_for},
+    price_store::benchmark1_worker::Benchmark1WorkerArgs,
+};
+
+use super::{Cluster, Plan, RunStats, print_stats, push_stats, run_cluster};
+
+#[derive(Serialize, Deserialize)]
+enum CoordinatorMessage {
+    /// Publishers this worker runs, and when to start them.
+    Assign {
+        plan: Box<Plan>,
+        start_at: SystemTime,
+    },
+    /// Stop the benchmark before the planned duration has elapsed.
+    Stop,
+}
+
+#[derive(Serialize, Deserialize)]
+enum WorkerMessage {
+    /// Cumulative stats since the start.
+    Stats(RunStats),
+    /// The benchmark is over, with these final stats.
+    Finished(RunStats),
+    Failed(String),
+}
+
+impl Plan {
+    /// Splits the publishers into `parts` contiguous groups of about the same size.  Payers are
+    /// paired with the publishers by position, the same way a single instance pairs them.
+    fn split(self, parts: usize) -> Vec<Plan> {
+        let Plan {
+            program_id,
+            payer_keypairs,
+            publisher_keypairs,
+            price_buffer_pubkeys,
+            load,
+            duration,
+            stats_update_interval,
+        } = self;
+
+        let publishers = publisher_keypairs.len();
+        (0..parts)
+            .map(|part| {
+                let start = part * publishers / parts;
+                let end = (part + 1) * publishers / parts;
+                Plan {
+                    program_id,
+                    payer_keypairs: clipped(&payer_keypairs, start..end),
+                    publisher_keypairs: publisher_keypairs[start..end].to_vec(),
+                    price_buffer_pubkeys: clipped(&price_buffer_pubkeys, start..end),
+                    load: load.clone(),
+                    duration,
+                    stats_update_interval,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Items of the `range`, that is clipped to the `items` length.
+fn clipped<T: Clone>(items: &[T], range: Range<usize>) -> Vec<T> {
+    let len = items.len();
+    items[range.start.min(len)..range.end.min(len)].to_vec()
+}
+
+/// Waits for `workers` connections on `listen`, assigns publishers to them, and reports their
+/// stats until all the workers are done.
+pub(super) async fn run_coordinator(
+    listen: SocketAddr,
+    workers: usize,
+    start_delay: Duration,
+    plan: Plan,
+    metrics: Option<&MetricsSink>,
+) -> Result<()> {
+    let stats_update_interval = plan.stats_update_interval;
+
+    let listener = TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to listen on {listen}"))?;
+    output::notice(format!(
+        "Waiting for {workers} workers to connect to {listen}"
+    ));
+
+    let mut connections = vec![];
+    while connections.len() < workers {
+        let (stream, address) = listener
+            .accept()
+            .await
+            .context("Failed to accept a worker connection")?;
+        output::notice(format!(
+            "Worker {} connected from {address}",
+            connections.len() + 1
+        ));
+        connections.push((address, stream));
+    }
+
+    let start_at = SystemTime::now() + start_delay;
+    let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+    let mut writers = vec![];
+    let mut labels = vec![];
+    for (index, ((address, stream), plan)) in
+        connections.into_iter().zip(plan.split(workers)).enumerate()
+    {
+        let (reader, mut writer) = stream.into_split();
+        output::notice(format!(
+            "Worker {} at {address}: {} publishers",
+            index + 1,
+            plan.publisher_keypairs.len()
+        ));
+        send_message(
+            &mut writer,
+            &CoordinatorMessage::Assign {
+                plan: Box::new(plan),
+                start_at,
+            },
+        )
+        .await
+        .with_context(|| format!("Failed to send an assignment to {address}"))?;
+        tokio::spawn(forward_worker_messages(index, reader, events_tx.clone()));
+        writers.push(writer);
+        labels.push(format!("worker {} {address}", index + 1));
+    }
+    drop(events_tx);
+
+    let benchmark_start = chrono::DateTime::<chrono::Local>::from(start_at);
+    output::result(
+        format!("Benchmark start time: {benchmark_start}"),
+        json!({ "benchmark_start": benchmark_start.to_rfc3339() }),
+    );
+
+    let stop_signals = select_all([
+        SignalStream::new(signal(SignalKind::interrupt()).expect("Can install a SIGINT handler")),
+        SignalStream::new(signal(SignalKind::terminate()).expect("Can install a SIGTERM handler")),
+    ]);
+    tokio::pin!(stop_signals);
+
+    let mut stats_update_ticker = interval_at(
+        Instant::now() + start_delay + stats_update_interval,
+        stats_update_interval,
+    );
+
+    let mut latest_stats = vec![RunStats::default(); workers];
+    let mut outcomes: Vec<Option<Result<(), String>>> = vec![None; workers];
+    while outcomes.iter().any(Option::is_none) {
+        select! {
+            event = events_rx.recv() => {
+                let Some((index, event)) = event else {
+                    break;
+                };
+                match event {
+                    Ok(WorkerMessage::Stats(stats)) => latest_stats[index] = stats,
+                    Ok(WorkerMessage::Finished(stats)) => {
+                        latest_stats[index] = stats;
+                        outcomes[index] = Some(Ok(()));
+                    }
+                    Ok(WorkerMessage::Failed(err)) => outcomes[index] = Some(Err(err)),
+                    Err(err) => {
+                        if outcomes[index].is_none() {
+                            outcomes[index] = Some(Err(format!("{err:#}")));
+                        }
+                    }
+                }
+            }
+            _at = stats_update_ticker.tick() => {
+                let total = total_stats(&latest_stats);
+                print_stats(None, &total);
+                push_stats(metrics, None, &total);
+            }
+            _ = stop_signals.next() => {
+                output::notice("Stopping the workers");
+                for writer in &mut writers {
+                    if let Err(err) = send_message(writer, &CoordinatorMessage::Stop).await {
+                        warn!("Failed to stop a worker: {err:#}");
+                    }
+                }
+            }
+        }
+    }
+
+    let mut failed = 0;
+    for ((label, stats), outcome) in labels.iter().zip(&latest_stats).zip(&outcomes) {
+        match outcome {
+            Some(Ok(())) => print_stats(Some(label), stats),
+            Some(Err(err)) => {
+                failed += 1;
+                output::result(
+                    format!("  [{label}] Failed: {err}"),
+                    json!({
+                        "worker": label,
+                        "error": err,
+                    }),
+                );
+            }
+            None => {
+                failed += 1;
+                output::result(
+                    format!("  [{label}] Did not report the final stats"),
+                    json!({
+                        "worker": label,
+                        "error": "Did not report the final stats",
+                    }),
+                );
+            }
+        }
+    }
+
+    let total = total_stats(&latest_stats);
+    print_stats(None, &total);
+    push_stats(metrics, None, &total);
+
+    let benchmark_end = chrono::Local::now();
+    output::result(
+        format!("Benchmark end time:   {benchmark_end}"),
+        json!({ "benchmark_end": benchmark_end.to_rfc3339() }),
+    );
+
+    if failed > 0 {
+        bail!("{failed} out of {workers} workers failed");
+    }
+    Ok(())
+}
+
+fn total_stats(stats: &[RunStats]) -> RunStats {
+    stats
+        .iter()
+        .cloned()
+        .fold(RunStats::default(), |total, stats| total + stats)
+}
+
+/// Reads messages from a worker connection, until it is closed.  A connection that is closed
+/// without a final message produces an error event.
+async fn forward_worker_messages(
+    index: usize,
+    reader: OwnedReadHalf,
+    events: mpsc::UnboundedSender<(usize, Result<WorkerMessage>)>,
+) {
+    let mut lines = BufReader::new(reader).lines();
+    loop {
+        let event = match receive_message::<WorkerMessage>(&mut lines).await {
+            Ok(Some(message)) => Ok(message),
+            Ok(None) => Err(anyhow!("Worker disconnected")),
+            Err(err) => Err(err),
+        };
+        let done = event.is_err();
+        if events.send((index, event)).is_err() || done {
+            return;
+        }
+    }
+}
+
+/// Connects to a coordinator, and runs the publishers it assigns.
+pub async fn run_worker(
+    Benchmark1WorkerArgs {
+        json_rpc_url,
+        websocket_url,
+        coordinator,
+    }: Benchmark1WorkerArgs,
+) -> Result<()> {
+    let websocket_url = websocket_url.unwrap_or_else(|| websocket_url_for(&json_rpc_url.rpc_url));
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let stream = TcpStream::connect(&coordinator)
+        .await
+        .with_context(|| format!("Failed to connect to the coordinator at {coordinator}"))?;
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    output::notice(format!(
+        "Connected to {coordinator}, waiting for an assignment"
+    ));
+
+    let (plan, start_at) = match receive_message(&mut lines).await? {
+        Some(CoordinatorMessage::Assign { plan, start_at }) => (*plan, start_at),
+        Some(CoordinatorMessage::Stop) => {
+            output::notice("Coordinator stopped the benchmark before it started");
+            return Ok(());
+        }
+        None => bail!("Coordinator disconnected before sending an assignment"),
+    };
+
+    let res = run_assignment(
+        Cluster {
+            label: None,
+            rpc_client,
+            websocket_url,
+            program_id: plan.program_id,
+            publisher_group: None,
+        },
+        plan,
+        start_at,
+        &mut lines,
+        &mut writer,
+    )
+    .await;
+
+    let message = match &res {
+        Ok(stats) => WorkerMessage::Finished(stats.clone()),
+        Err(err) => WorkerMessage::Failed(format!("{err:#}")),
+    };
+    send_message(&mut writer, &message)
+        .await
+        .context("Failed to send the final stats to the coordinator")?;
+
+    res.map(|stats| print_stats(None, &stats))
+}
+
+async fn run_assignment(
+    cluster: Cluster,
+    Plan {
+        program_id: _,
+        payer_keypairs,
+        publisher_keypairs,
+        price_buffer_pubkeys,
+        load,
+        duration,
+        stats_update_interval,
+    }: Plan,
+    start_at: SystemTime,
+    coordinator_lines: &mut Lines<BufReader<OwnedReadHalf>>,
+    coordinator_writer: &mut OwnedWriteHalf,
+) -> Result<RunStats> {
+    let payers = payer_keypairs
+        .iter()
+        .map(read_keypair_file)
+        .collect::<Result<Vec<_>>>()?;
+    let publishers = publisher_keypairs
+        .iter()
+        .map(read_keypair_file)
+        .collect::<Result<Vec<_>>>()?;
+
+    let start_delay = start_at
+        .duration_since(SystemTime::now())
+        .unwrap_or_default();
+    output::notice(format!(
+        "Running {} publishers, starting in {:.1}s",
+        publishers.len(),
+        start_delay.as_secs_f64()
+    ));
+    sleep(start_delay).await;
+
+    let stop_signals = select_all([
+        SignalStream::new(signal(SignalKind::interrupt()).expect("Can install a SIGINT handler")),
+        SignalStream::new(signal(SignalKind::terminate()).expect("Can install a SIGTERM handler")),
+    ]);
+    tokio::pin!(stop_signals);
+
+    let benchmark_end_timer = sleep(duration);
+    tokio::pin!(benchmark_end_timer);
+
+    let publishers_shutdown = CancellationToken::new();
+    let (stats_updates_tx, mut stats_updates_rx) = mpsc::unbounded_channel();
+
+    let cluster_run = run_cluster(
+        &cluster,
+        (payers, publishers),
+        price_buffer_pubkeys,
+        &load,
+        stats_update_interval,
+        None,
+        None,
+        None,
+        publishers_shutdown.clone(),
+        Some(&stats_updates_tx),
+        None,
+    );
+    tokio::pin!(cluster_run);
+
+    let mut coordinator_connected = true;
+    let (stats, _cost_sample, _slot_sends, _aborted) = loop {
+        select! {
+            res = &mut cluster_run => break res?,
+            Some(stats) = stats_updates_rx.recv() => {
+                if let Err(err) =
+                    send_message(coordinator_writer, &WorkerMessage::Stats(stats)).await
+                {
+                    warn!("Failed to send stats to the coordinator: {err:#}");
+                }
+            }
+            message = receive_message(coordinator_lines), if coordinator_connected => {
+                match message {
+                    Ok(Some(CoordinatorMessage::Stop)) => {
+                        output::notice("Coordinator stopped the benchmark");
+                        publishers_shutdown.cancel();
+                    }
+                    Ok(Some(CoordinatorMessage::Assign { .. })) => {
+                        warn!("Ignoring a second assignment from the coordinator");
+                    }
+                    Ok(None) | Err(_) => {
+                        warn!("Lost connection to the coordinator, stopping");
+                        coordinator_connected = false;
+                        publishers_shutdown.cancel();
+                    }
+                }
+            }
+            () = &mut benchmark_end_timer, if !benchmark_end_timer.is_elapsed() => {
+                publishers_shutdown.cancel();
+            }
+            _ = stop_signals.next() => publishers_shutdown.cancel(),
+        }
+    };
+
+    Ok(stats)
+}
+
+async fn send_message(writer: &mut OwnedWriteHalf, message: &impl Serialize) -> Result<()> {
+    let mut line = serde_json::to_string(message).context("Serializing a control message")?;
+    line.push('\n');
+    writer
+        .write_all(line.as_bytes())
+        .await
+        .context("Writing a control message")
+}
+
+/// Returns `None` when the connection is closed.
+async fn receive_message<T: DeserializeOwned>(
+    lines: &mut Lines<BufReader<OwnedReadHalf>>,
+) -> Result<Option<T>> {
+    let Some(line) = lines
+        .next_line()
+        .await
+        .context("Reading a control message")?
+    else {
+        return Ok(None);
+    };
+    serde_json::from_str(&line)
+        .map(Some)
+        .with_context(|| format!("Malformed control message: {line}"))
+}
@@ -0,0 +1,208 @@
+//! Faults that the benchmark can inject into the publisher send path.
+//!
+//! A decision about which faults affect a given transaction is made once, when the transaction is
+//! constructed, and applies to all the sends of this transaction.
+
+use std::{fmt::Display, time::Duration};
+
+use derive_more::{Add, AddAssign};
+use pythnet_heisenberg::metrics_sink::Point;
+use rand::{Rng as _, rng};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use solana_sdk::{signature::Signature, transaction::Transaction};
+
+use crate::args::price_store::benchmark1::FaultInjectionArgs;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FaultInjection {
+    drop_probability: f64,
+    delay_probability: f64,
+    max_delay: Duration,
+    duplicate_probability: f64,
+    malformed_probability: f64,
+}
+
+/// Faults selected for a single transaction.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FaultPlan {
+    pub drop: bool,
+    pub delay: Option<Duration>,
+    pub duplicate: bool,
+    pub malformed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InjectedFault {
+    Dropped,
+    Delayed,
+    Duplicated,
+    Malformed,
+}
+
+impl FaultInjection {
+    pub fn new(
+        FaultInjectionArgs {
+            fault_drop_percent,
+            fault_delay_percent,
+            fault_delay,
+            fault_duplicate_percent,
+            fault_malformed_percent,
+        }: FaultInjectionArgs,
+    ) -> Self {
+        Self {
+            drop_probability: fault_drop_percent / 100.0,
+            delay_probability: fault_delay_percent / 100.0,
+            max_delay: fault_delay.into(),
+            duplicate_probability: fault_duplicate_percent / 100.0,
+            malformed_probability: fault_malformed_percent / 100.0,
+        }
+    }
+
+    pub fn plan(&self) -> FaultPlan {
+        let Self {
+            drop_probability,
+            delay_probability,
+            max_delay,
+            duplicate_probability,
+            malformed_probability,
+        } = *self;
+
+        let mut rng = rng();
+        if rng.random_bool(drop_probability) {
+            return FaultPlan {
+                drop: true,
+                ..FaultPlan::default()
+            };
+        }
+
+        FaultPlan {
+            drop: false,
+            delay: rng
+                .random_bool(delay_probability)
+                .then(|| max_delay.mul_f64(rng.random::<f64>())),
+            duplicate: rng.random_bool(duplicate_probability),
+            malformed: rng.random_bool(malformed_probability),
+        }
+    }
+}
+
+impl FaultPlan {
+    /// The fault a send of the original transaction is attributed to.  A transaction that is both
+    /// malformed and delayed is counted as malformed.
+    pub fn primary_fault(&self) -> Option<InjectedFault> {
+        if self.drop {
+            Some(InjectedFault::Dropped)
+        } else if self.malformed {
+            Some(InjectedFault::Malformed)
+        } else if self.delay.is_some() {
+            Some(InjectedFault::Delayed)
+        } else {
+            None
+        }
+    }
+}
+
+/// Replaces the fee payer signature with random bytes.  The transaction still deserializes, but
+/// fails signature verification.
+pub fn malform(transaction: &mut Transaction) {
+    let bytes: [u8; 64] = std::array::from_fn(|_| rand::random());
+    transaction.signatures[0] = Signature::from(bytes);
+}
+
+/// Sends affected by injected faults.  For every kind of fault, `*_ok` counts sends that were
+/// still accepted by the node.
+#[derive(Debug, Clone, Default, Add, AddAssign, Serialize, Deserialize)]
+pub struct FaultStats {
+    pub dropped: u64,
+    pub delayed: u64,
+    pub delayed_ok: u64,
+    pub duplicated: u64,
+    pub duplicated_ok: u64,
+    pub malformed: u64,
+    pub malformed_ok: u64,
+}
+
+impl FaultStats {
+    pub fn include(&mut self, fault: InjectedFault, success: bool) {
+        let ok = u64::from(success);
+        match fault {
+            InjectedFault::Dropped => self.dropped += 1,
+            InjectedFault::Delayed => {
+                self.delayed += 1;
+                self.delayed_ok += ok;
+            }
+            InjectedFault::Duplicated => {
+                self.duplicated += 1;
+                self.duplicated_ok += ok;
+            }
+            InjectedFault::Malformed => {
+                self.malformed += 1;
+                self.malformed_ok += ok;
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dropped == 0 && self.delayed == 0 && self.duplicated == 0 && self.malformed == 0
+    }
+
+    pub fn text(&self) -> impl Display {
+        let Self {
+            dropped,
+            delayed,
+            delayed_ok,
+            duplicated,
+            duplicated_ok,
+            malformed,
+            malformed_ok,
+        } = self;
+        format!(
+            "  Injected faults: {dropped} dropped / {delayed} delayed ({delayed_ok} accepted) / \
+             {duplicated} duplicated ({duplicated_ok} accepted) / \
+             {malformed} malformed ({malformed_ok} accepted)"
+        )
+    }
+
+    /// Adds all the counters as `fault_*` fields.
+    pub fn add_fields(&self, point: Point) -> Point {
+        let Self {
+            dropped,
+            delayed,
+            delayed_ok,
+            duplicated,
+            duplicated_ok,
+            malformed,
+            malformed_ok,
+        } = self;
+        point
+            .field_u64("fault_dropped", *dropped)
+            .field_u64("fault_delayed", *delayed)
+            .field_u64("fault_delayed_ok", *delayed_ok)
+            .field_u64("fault_duplicated", *duplicated)
+            .field_u64("fault_duplicated_ok", *duplicated_ok)
+            .field_u64("fault_malformed", *malformed)
+            .field_u64("fault_malformed_ok", *malformed_ok)
+    }
+
+    pub fn json(&self) -> Value {
+        let Self {
+            dropped,
+            delayed,
+            delayed_ok,
+            duplicated,
+            duplicated_ok,
+            malformed,
+            malformed_ok,
+        } = self;
+        json!({
+            "dropped": dropped,
+            "delayed": delayed,
+            "delayed_ok": delayed_ok,
+            "duplicated": duplicated,
+            "duplicated_ok": duplicated_ok,
+            "malformed": malformed,
+            "malformed_ok": malformed_ok,
+        })
+    }
+}
@@ -0,0 +1,331 @@
+//! Correlates benchmark failures with the leaders that were expected to process the transactions.
+//!
+//! Failure and expiry spikes are often caused by a leader that skips its slots, rather than by the
+//! cluster as a whole.  While the benchmark is running, send outcomes are counted per slot they
+//! were sent in.  After the run, the leader schedule and the produced blocks for the same slots
+//! are fetched, and the send outcomes, as well as the landing of the sampled transactions, are
+//! attributed to the leader of the slot each transaction was sent in.
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashSet},
+};
+
+use anyhow::{Context as _, Result};
+use itertools::Itertools as _;
+use pythnet_heisenberg::output;
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::request::{MAX_GET_CONFIRMED_BLOCKS_RANGE, MAX_GET_SLOT_LEADERS};
+use solana_sdk::{clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+use super::PriceUpdateResult;
+use crate::tx_cost::CostSummary;
+
+/// Send outcomes, by the slot the sends happened in.
+#[derive(Default)]
+pub struct SlotSends {
+    slots: BTreeMap<Slot, SendCounts>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct SendCounts {
+    successful: u64,
+    failed: u64,
+}
+
+impl SendCounts {
+    fn include(&mut self, other: SendCounts) {
+        self.successful += other.successful;
+        self.failed += other.failed;
+    }
+}
+
+/// Sampled transactions and how many of them landed.
+#[derive(Debug, Default, Clone, Copy)]
+struct Landings {
+    sampled: u64,
+    landed: u64,
+}
+
+impl Landings {
+    fn rate(&self) -> Option<f64> {
+        (self.sampled != 0).then(|| self.landed as f64 / self.sampled as f64 * 100.0)
+    }
+}
+
+impl SlotSends {
+    /// Counts the `result` in the slot it was sent in, when known, or in the `current_slot`.
+    /// Sends affected by injected faults are not counted.
+    pub fn include(&mut self, result: &PriceUpdateResult, current_slot: Slot) {
+        let (slot, success) = match result {
+            PriceUpdateResult::Success(sent) => (
+                sent.map_or(current_slot, |(_signature, sent_slot)| sent_slot),
+                true,
+            ),
+            PriceUpdateResult::Fail => (current_slot, false),
+            PriceUpdateResult::Faulty { .. } => return,
+        };
+        let counts = self.slots.entry(slot).or_default();
+        if success {
+            counts.successful += 1;
+        } else {
+            counts.failed += 1;
+        }
+    }
+}
+
+/// Per leader stats, for the slots the benchmark was sending in.
+pub struct LeaderStats {
+    first_slot: Slot,
+    last_slot: Slot,
+    leaders: Vec<LeaderRow>,
+    /// Sends and landings in the slots that were skipped, and in the slots that were produced.
+    skipped: (SendCounts, Landings),
+    produced: (SendCounts, Landings),
+}
+
+struct LeaderRow {
+    leader: Pubkey,
+    scheduled_slots: u64,
+    skipped_slots: u64,
+    sends: SendCounts,
+    landings: Landings,
+}
+
+impl LeaderRow {
+    fn new(leader: Pubkey) -> Self {
+        Self {
+            leader,
+            scheduled_slots: 0,
+            skipped_slots: 0,
+            sends: SendCounts::default(),
+            landings: Landings::default(),
+        }
+    }
+}
+
+/// Fetches the leader schedule and the produced blocks for the slots in the `slot_sends`, and
+/// attributes the sends, as well as the landings in the `costs` sample, if any, to the leaders.
+///
+/// Returns `None` if nothing was sent.
+pub async fn collect(
+    rpc_client: &RpcClient,
+    slot_sends: &SlotSends,
+    costs: Option<&CostSummary>,
+) -> Result<Option<LeaderStats>> {
+    let landings = costs.map(CostSummary::landings).unwrap_or_default();
+    let sent_slots = slot_sends
+        .slots
+        .keys()
+        .copied()
+        .chain(landings.iter().map(|(sent_slot, _landed_slot)| *sent_slot));
+    let Some((first_slot, last_slot)) = sent_slots.minmax().into_option() else {
+        return Ok(None);
+    };
+
+    // Blocks are only known up to the confirmed slot, later slots would look skipped.
+    let confirmed_slot = rpc_client
+        .get_slot_with_commitment(CommitmentConfig::confirmed())
+        .await
+        .context("Failed to get the confirmed slot")?;
+    let last_slot = last_slot.min(confirmed_slot);
+    if last_slot < first_slot {
+        return Ok(None);
+    }
+
+    let produced_slots = get_produced_slots(rpc_client, first_slot, last_slot).await?;
+    let slot_leaders = get_slot_leaders(rpc_client, first_slot, last_slot).await?;
+
+    let mut rows = BTreeMap::<Pubkey, LeaderRow>::new();
+    let mut skipped = (SendCounts::default(), Landings::default());
+    let mut produced = (SendCounts::default(), Landings::default());
+    for (slot, leader) in (first_slot..=last_slot).zip(&slot_leaders) {
+        let is_produced = produced_slots.contains(&slot);
+        let sends = slot_sends.slots.get(&slot).copied().unwrap_or_default();
+
+        let row = rows
+            .entry(*leader)
+            .or_insert_with(|| LeaderRow::new(*leader));
+        row.scheduled_slots += 1;
+        if !is_produced {
+            row.skipped_slots += 1;
+        }
+        row.sends.include(sends);
+
+        let (totals, _) = if is_produced {
+            &mut produced
+        } else {
+            &mut skipped
+        };
+        totals.include(sends);
+    }
+
+    for (sent_slot, landed_slot) in landings {
+        let Some(leader) = sent_slot
+            .checked_sub(first_slot)
+            .and_then(|offset| slot_leaders.get(usize::try_from(offset).ok()?))
+        else {
+            continue;
+        };
+        let Some(row) = rows.get_mut(leader) else {
+            continue;
+        };
+        let (_, totals) = if produced_slots.contains(sent_slot) {
+            &mut produced
+        } else {
+            &mut skipped
+        };
+        for landings in [&mut row.landings, totals] {
+            landings.sampled += 1;
+            if landed_slot.is_some() {
+                landings.landed += 1;
+            }
+        }
+    }
+
+    Ok(Some(LeaderStats {
+        first_slot,
+        last_slot,
+        leaders: rows.into_values().collect(),
+        skipped,
+        produced,
+    }))
+}
+
+impl LeaderStats {
+    /// Prints the totals for the skipped and the produced slots, followed by the leaders, starting
+    /// with the ones that skipped the most slots.
+    pub fn print(&self, label: Option<&str>) {
+        let prefix = label.map(|label| format!("[{label}] ")).unwrap_or_default();
+        let Self {
+            first_slot,
+            last_slot,
+            leaders,
+            skipped,
+            produced,
+        } = self;
+
+        let total_slots = last_slot - first_slot + 1;
+        let skipped_slots = leaders.iter().map(|row| row.skipped_slots).sum::<u64>();
+
+        let mut text = format!(
+            "  {prefix}Leaders, slots {first_slot} to {last_slot}: {skipped_slots} of \
+             {total_slots} slots skipped\n    \
+             Sent in produced slots: {}\n    \
+             Sent in skipped slots:  {}",
+            outcome_text(produced),
+            outcome_text(skipped),
+        );
+
+        let leaders = leaders
+            .iter()
+            .sorted_by_key(|row| {
+                (
+                    Reverse(row.skipped_slots),
+                    Reverse(row.sends.failed),
+                    row.leader,
+                )
+            })
+            .collect::<Vec<_>>();
+        for row in &leaders {
+            text.push_str(&format!(
+                "\n    {}: {} of {} slots skipped, {}",
+                row.leader,
+                row.skipped_slots,
+                row.scheduled_slots,
+                outcome_text(&(row.sends, row.landings)),
+            ));
+        }
+
+        let outcome_json = |(sends, landings): &(SendCounts, Landings)| {
+            json!({
+                "successful_sends": sends.successful,
+                "failed_sends": sends.failed,
+                "sampled_tx": landings.sampled,
+                "landed_tx": landings.landed,
+                "landing_rate": landings.rate(),
+            })
+        };
+        output::result(
+            text,
+            json!({
+                "label": label,
+                "leaders": {
+                    "first_slot": first_slot,
+                    "last_slot": last_slot,
+                    "skipped_slots": skipped_slots,
+                    "produced": outcome_json(produced),
+                    "skipped": outcome_json(skipped),
+                    "leaders": leaders
+                        .iter()
+                        .map(|row| {
+                            let mut json = outcome_json(&(row.sends, row.landings));
+                            json["leader"] = json!(row.leader.to_string());
+                            json["scheduled_slots"] = json!(row.scheduled_slots);
+                            json["skipped_slots"] = json!(row.skipped_slots);
+                            json
+                        })
+                        .collect::<Vec<_>>(),
+                },
+            }),
+        );
+    }
+}
+
+fn outcome_text((sends, landings): &(SendCounts, Landings)) -> String {
+    let landed = match landings.rate() {
+        Some(rate) => format!(
+            "{} of {} sampled landed ({rate:.1}%)",
+            landings.landed, landings.sampled
+        ),
+        None => "no sampled txs".to_owned(),
+    };
+    format!(
+        "{} successful / {} failed sends, {landed}",
+        sends.successful, sends.failed
+    )
+}
+
+async fn get_produced_slots(
+    rpc_client: &RpcClient,
+    first_slot: Slot,
+    last_slot: Slot,
+) -> Result<HashSet<Slot>> {
+    let mut produced = HashSet::new();
+    let mut start = first_slot;
+    while start <= last_slot {
+        let end = last_slot.min(start + MAX_GET_CONFIRMED_BLOCKS_RANGE - 1);
+        produced.extend(
+            rpc_client
+                .get_blocks_with_commitment(start, Some(end), CommitmentConfig::confirmed())
+                .await
+                .with_context(|| format!("Failed to get blocks in [{start}, {end}]"))?,
+        );
+        start = end + 1;
+    }
+    Ok(produced)
+}
+
+/// Leader of every slot in the `[first_slot, last_slot]` range.
+async fn get_slot_leaders(
+    rpc_client: &RpcClient,
+    first_slot: Slot,
+    last_slot: Slot,
+) -> Result<Vec<Pubkey>> {
+    let mut leaders = vec![];
+    let mut start = first_slot;
+    while start <= last_slot {
+        let limit = (last_slot - start + 1).min(MAX_GET_SLOT_LEADERS as u64);
+        let chunk = rpc_client
+            .get_slot_leaders(start, limit)
+            .await
+            .with_context(|| format!("Failed to get slot leaders starting at {start}"))?;
+        if chunk.is_empty() {
+            break;
+        }
+        start += chunk.len() as u64;
+        leaders.extend(chunk);
+    }
+    Ok(leaders)
+}
@@ -0,0 +1,89 @@
+//! `--oracle-update-percent`: some of the price feeds are updated with the Oracle `UpdPrice`
+//! instruction, the way publishers did before the Price Store, while the rest go through the Price
+//! Store `SubmitPrices`.
+//!
+//! Feeds are split by their index, so that every publisher uses the same path for the same feed,
+//! and the selected feeds are spread evenly across the price feed range.
+
+use std::{collections::BTreeMap, ops::RangeInclusive};
+
+use anyhow::Result;
+use pythnet_heisenberg::output;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::oracle::feed_index::usage::price_feed_indices;
+
+/// Price feeds updated via the Oracle.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OracleUpdates {
+    pub program_id: Pubkey,
+    pub updates_per_tx: u8,
+    /// Oracle price accounts of the feeds that are updated via `UpdPrice`.
+    pub price_accounts: BTreeMap<u32, Pubkey>,
+}
+
+/// Selects `percent` of the feeds in the `price_feed_indices` range, and looks up their Oracle
+/// price accounts.  Selected feeds without a price account stay with the Price Store.
+pub async fn fetch(
+    rpc_client: &RpcClient,
+    label: Option<&str>,
+    program_id: Pubkey,
+    percent: u8,
+    updates_per_tx: u8,
+    price_feed_indices_range: &RangeInclusive<u32>,
+) -> Result<OracleUpdates> {
+    let addresses = price_feed_indices(rpc_client, &program_id)
+        .await?
+        .into_iter()
+        .map(|(pubkey, feed_index)| (feed_index, pubkey))
+        .collect::<BTreeMap<_, _>>();
+
+    let selected = price_feed_indices_range
+        .clone()
+        .filter(|feed_index| is_selected(feed_index - price_feed_indices_range.start(), percent))
+        .collect::<Vec<_>>();
+    let price_accounts = selected
+        .iter()
+        .filter_map(|feed_index| Some((*feed_index, *addresses.get(feed_index)?)))
+        .collect::<BTreeMap<_, _>>();
+
+    let total = price_feed_indices_range.clone().count();
+    let missing = selected.len() - price_accounts.len();
+    let prefix = label.map(|label| format!("[{label}] ")).unwrap_or_default();
+    let mut text = format!(
+        "{prefix}Oracle updates: {} of {total} feeds are updated via UpdPrice",
+        price_accounts.len(),
+    );
+    if missing != 0 {
+        text.push_str(&format!(
+            ", {missing} more do not have an Oracle price account and use the Price Store"
+        ));
+    }
+    let mut json = json!({
+        "oracle_updates": {
+            "feeds": price_accounts.len(),
+            "total_feeds": total,
+            "missing_price_accounts": missing,
+        },
+    });
+    if let Some(label) = label {
+        json["cluster"] = json!(label);
+    }
+    output::result(text, json);
+
+    Ok(OracleUpdates {
+        program_id,
+        updates_per_tx,
+        price_accounts,
+    })
+}
+
+/// Spreads the selected feeds evenly: a feed at the `offset` from the start of the range is
+/// selected when the running share of the selected feeds reaches the next whole feed.
+fn is_selected(offset: u32, percent: u8) -> bool {
+    let share = |feeds: u64| feeds * u64::from(percent) / 100;
+    share(u64::from(offset) + 1) > share(u64::from(offset))
+}
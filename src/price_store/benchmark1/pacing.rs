@@ -0,0 +1,113 @@
+//! `--target-tps` mode: publishers are paced to produce a target transaction rate for the whole
+//! run.
+//!
+//! The initial update frequency is derived from the number of transactions all the publishers send
+//! per update round.  Publishers can fall behind it, when sending takes longer than the update
+//! frequency, and failed sends do not count towards the target.  So the update frequency is
+//! adjusted periodically, based on the rate of successful transactions actually observed.
+//!
+//! Failed transactions are not compensated for while many of them fail.  Failures usually mean
+//! the cluster is overloaded, and sending more would only make it worse, so in this case the update
+//! frequency is kept, or increased, but never reduced.
+
+use std::{
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Update frequency is never set below this value.
+const MIN_UPDATE_FREQUENCY: Duration = Duration::from_millis(1);
+
+/// A single adjustment changes the update frequency by at most this factor, in either direction,
+/// so that a noisy measurement does not throw the publishers far off.
+const MAX_ADJUSTMENT: f64 = 2.;
+
+/// Publishers are not sped up while more than this fraction of the sent transactions fail.
+const MAX_FAILURE_RATE: f64 = 0.1;
+
+/// Update frequency shared by all the publishers of a cluster.
+pub struct Pacing {
+    target_tps: u32,
+    state: Mutex<PacingState>,
+}
+
+struct PacingState {
+    update_frequency: Duration,
+    /// Rate observed during the last adjustment.
+    achieved_tps: Option<f64>,
+    /// The last adjustment did not speed the publishers up, as too many transactions failed.
+    held_back: bool,
+}
+
+impl Pacing {
+    pub fn new(target_tps: u32, update_frequency: Duration) -> Self {
+        Self {
+            target_tps,
+            state: Mutex::new(PacingState {
+                update_frequency: update_frequency.max(MIN_UPDATE_FREQUENCY),
+                achieved_tps: None,
+                held_back: false,
+            }),
+        }
+    }
+
+    /// Update frequency that produces `target_tps`, when all the publishers together send
+    /// `txs_per_round` transactions per update.
+    pub fn initial_update_frequency(target_tps: u32, txs_per_round: u64) -> Duration {
+        Duration::from_secs_f64(txs_per_round as f64 / f64::from(target_tps.max(1)))
+            .max(MIN_UPDATE_FREQUENCY)
+    }
+
+    pub fn target_tps(&self) -> u32 {
+        self.target_tps
+    }
+
+    pub fn update_frequency(&self) -> Duration {
+        self.lock().update_frequency
+    }
+
+    /// Rate observed during the last [`adjust()`](Self::adjust) call, if any.
+    pub fn achieved_tps(&self) -> Option<f64> {
+        self.lock().achieved_tps
+    }
+
+    /// Whether the last [`adjust()`](Self::adjust) call did not speed the publishers up, because
+    /// too many transactions failed.
+    pub fn held_back(&self) -> bool {
+        self.lock().held_back
+    }
+
+    /// Scales the update frequency by how far the `successful_tx` sent within `elapsed` were from
+    /// the target rate.  While more than [`MAX_FAILURE_RATE`] of all the sent transactions fail,
+    /// the update frequency is not reduced.
+    pub fn adjust(&self, successful_tx: u64, failed_tx: u64, elapsed: Duration) {
+        if elapsed.is_zero() {
+            return;
+        }
+
+        let achieved_tps = successful_tx as f64 / elapsed.as_secs_f64();
+        let mut ratio =
+            (achieved_tps / f64::from(self.target_tps)).clamp(1. / MAX_ADJUSTMENT, MAX_ADJUSTMENT);
+
+        let sent_tx = successful_tx + failed_tx;
+        let held_back = sent_tx != 0 && failed_tx as f64 / sent_tx as f64 > MAX_FAILURE_RATE;
+        if held_back {
+            ratio = ratio.max(1.);
+        }
+
+        let mut state = self.lock();
+        state.update_frequency = state
+            .update_frequency
+            .mul_f64(ratio)
+            .max(MIN_UPDATE_FREQUENCY);
+        state.achieved_tps = Some(achieved_tps);
+        state.held_back = held_back;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, PacingState> {
+        self.state.lock().expect("Pacing lock is not poisoned")
+    }
+}
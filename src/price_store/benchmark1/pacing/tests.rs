@@ -0,0 +1,64 @@
+//! Checks how the update frequency follows the observed transaction rate.
+
+use std::time::Duration;
+
+use super::{MAX_ADJUSTMENT, Pacing};
+
+const TARGET_TPS: u32 = 1000;
+const UPDATE_FREQUENCY: Duration = Duration::from_millis(100);
+const ELAPSED: Duration = Duration::from_secs(1);
+
+#[test]
+fn speeds_up_when_behind_the_target() {
+    let pacing = Pacing::new(TARGET_TPS, UPDATE_FREQUENCY);
+
+    pacing.adjust(800, 0, ELAPSED);
+
+    assert_eq!(pacing.update_frequency(), UPDATE_FREQUENCY.mul_f64(0.8));
+    assert!(!pacing.held_back());
+}
+
+#[test]
+fn slows_down_when_ahead_of_the_target() {
+    let pacing = Pacing::new(TARGET_TPS, UPDATE_FREQUENCY);
+
+    pacing.adjust(10_000, 0, ELAPSED);
+
+    assert_eq!(
+        pacing.update_frequency(),
+        UPDATE_FREQUENCY.mul_f64(MAX_ADJUSTMENT)
+    );
+}
+
+#[test]
+fn tolerates_a_few_failures() {
+    let pacing = Pacing::new(TARGET_TPS, UPDATE_FREQUENCY);
+
+    pacing.adjust(950, 50, ELAPSED);
+
+    assert_eq!(pacing.update_frequency(), UPDATE_FREQUENCY.mul_f64(0.95));
+    assert!(!pacing.held_back());
+}
+
+#[test]
+fn does_not_speed_up_while_most_transactions_fail() {
+    let pacing = Pacing::new(TARGET_TPS, UPDATE_FREQUENCY);
+
+    for _ in 0..20 {
+        pacing.adjust(0, 1000, ELAPSED);
+    }
+
+    assert_eq!(pacing.update_frequency(), UPDATE_FREQUENCY);
+    assert!(pacing.held_back());
+}
+
+#[test]
+fn resumes_once_failures_stop() {
+    let pacing = Pacing::new(TARGET_TPS, UPDATE_FREQUENCY);
+
+    pacing.adjust(100, 900, ELAPSED);
+    pacing.adjust(500, 0, ELAPSED);
+
+    assert_eq!(pacing.update_frequency(), UPDATE_FREQUENCY.mul_f64(0.5));
+    assert!(!pacing.held_back());
+}
@@ -1,7 +1,10 @@
 use std::{
     net::{Ipv4Addr, SocketAddr},
-    ops::RangeInclusive,
-    time::{Duration, Instant},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant, SystemTime},
 };
 
 use anyhow::{Context as _, Result};
@@ -11,25 +14,32 @@ use futures::{
     stream::{FuturesUnordered, StreamExt as _},
 };
 use log::warn;
+use pythnet_heisenberg::{
+    blockhash_cache::BlockhashCache,
+    node_address_service::NodeAddressService,
+    oracle::instructions::upd_price::{self, PC_STATUS_TRADING},
+    price_store::instructions::submit_prices::{self, BufferedPrice, TradingStatus},
+    tx_records::{self, TxRecord},
+};
 use solana_program::{hash::Hash, pubkey::Pubkey};
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
-    clock::NUM_CONSECUTIVE_LEADER_SLOTS, signature::Keypair, signer::Signer as _,
+    clock::{NUM_CONSECUTIVE_LEADER_SLOTS, Slot},
+    signature::Keypair,
+    signer::Signer as _,
     transaction::Transaction,
 };
 use tokio::{net::UdpSocket, select, sync::mpsc, time::sleep};
 use tokio_util::sync::CancellationToken;
 
-use crate::{
-    blockhash_cache::BlockhashCache,
-    node_address_service::NodeAddressService,
-    price_store::{
-        benchmark1::ResultIntoPriceUpdateResult as _,
-        instructions::submit_prices::{self, BufferedPrice, TradingStatus},
-    },
-};
+use crate::price_store::benchmark1::ResultIntoPriceUpdateResult as _;
 
-use super::{PriceUpdateResult, price_source::PriceSource};
+use super::{
+    FeedGroup, Load, PriceUpdateResult, SendMode,
+    fault_injection::{FaultInjection, InjectedFault, malform},
+    oracle_updates::OracleUpdates,
+    price_source::PriceSource,
+};
 
 #[allow(clippy::too_many_arguments)]
 pub async fn run_publisher(
@@ -38,36 +48,69 @@ pub async fn run_publisher(
     payer: Keypair,
     publisher: Keypair,
     price_buffer: Pubkey,
-    price_feed_indices: RangeInclusive<u32>,
-    price_updates_per_tx: u8,
-    update_frequency: Duration,
-    price_mean: i64,
-    price_range: u64,
-    confidence_mean: u64,
-    confidence_range: u64,
+    load: &Load,
     blockhash_cache: &BlockhashCache,
     node_address_service: &NodeAddressService,
-    fanout_slots: u8,
     update_results_consumer: mpsc::Sender<PriceUpdateResult>,
     exit: CancellationToken,
 ) -> Result<()> {
+    let Load {
+        price_feed_indices: _,
+        price_updates_per_tx,
+        update_frequency: _,
+        phases: _,
+        feed_frequencies: _,
+        publisher_lags,
+        publisher_feeds: _,
+        price_mean,
+        price_range,
+        confidence_mean,
+        confidence_range,
+        fanout_slots,
+        send_mode,
+        fault_injection,
+        oracle_updates,
+        pacing: _,
+    } = load;
+    let (price_updates_per_tx, fanout_slots) = (*price_updates_per_tx, *fanout_slots);
+
     let payer_pubkey = payer.pubkey();
     let publisher_pubkey = publisher.pubkey();
 
-    let price_sources = price_feed_indices
-        .map(|price_feed_index| {
-            PriceSource::new(
-                price_feed_index,
-                price_mean,
-                price_range,
-                confidence_mean,
-                confidence_range,
-            )
-        })
-        .collect::<Vec<_>>();
+    let lag = publisher_lags
+        .iter()
+        .find(|publisher_lag| publisher_lag.publisher == publisher_pubkey)
+        .map(|publisher_lag| publisher_lag.lag)
+        .unwrap_or_default();
 
     let start_time = Instant::now();
 
+    let mut groups = load
+        .feed_groups(load.publisher_feed_indices(&publisher_pubkey))
+        .into_iter()
+        .map(
+            |FeedGroup {
+                 feeds,
+                 update_frequency,
+             }| SourceGroup {
+                sources: feeds
+                    .into_iter()
+                    .map(|price_feed_index| {
+                        PriceSource::new(
+                            price_feed_index,
+                            *price_mean,
+                            *price_range,
+                            *confidence_mean,
+                            *confidence_range,
+                        )
+                    })
+                    .collect(),
+                update_frequency,
+                next_update: start_time,
+            },
+        )
+        .collect::<Vec<_>>();
+
     // This socket will be used by all the publisher requests.
     //
     // Socket will be bound to a specific interface on the first `send_to()` call.  And we then
@@ -78,6 +121,9 @@ pub async fn run_publisher(
         .context("Creation of a UDP socket")?;
 
     let mut pending_price_updates = PriceUpdateFutures::new();
+    // Updates of a lagging publisher are held back for the lag.  The next iteration does not wait
+    // for them, so that the update frequency is not affected.
+    let mut lagging_price_updates = PriceUpdateFutures::new();
     // We should not see more than 2 nodes as our send target, as we are going to query leaders for
     // the next 4 slots only.
     let mut target_nodes = Vec::with_capacity(
@@ -88,14 +134,27 @@ pub async fn run_publisher(
     'publishing_all: loop {
         let iteration_start_time = Instant::now();
 
+        let due_sources = groups
+            .iter()
+            .filter(|group| group.next_update <= iteration_start_time)
+            .flat_map(|group| &group.sources)
+            .collect::<Vec<_>>();
+
         let latest_blockhash = blockhash_cache.get();
+        let current_slot = node_address_service.estimated_current_slot();
         target_nodes.clear();
         node_address_service.get_tpu_for_next_in_schedule(&mut target_nodes, fanout_slots.into());
 
         start_all_price_updates(
             rpc_client,
-            &mut pending_price_updates,
+            if lag.is_zero() {
+                &mut pending_price_updates
+            } else {
+                &mut lagging_price_updates
+            },
+            lag,
             &send_socket,
+            node_address_service,
             latest_blockhash,
             &target_nodes,
             (iteration_start_time - start_time).as_secs_f64(),
@@ -106,7 +165,11 @@ pub async fn run_publisher(
             publisher_pubkey,
             price_buffer,
             price_updates_per_tx,
-            &price_sources,
+            &due_sources,
+            oracle_updates.as_ref(),
+            current_slot,
+            fault_injection,
+            *send_mode,
         )
         .context("start_all_price_updates()")?;
 
@@ -126,15 +189,43 @@ pub async fn run_publisher(
                         break 'all_iteration_updates;
                     }
                 },
+                Some(send_result) = lagging_price_updates.next() => {
+                    if update_results_consumer.send(send_result).await.is_err() {
+                        break 'publishing_all;
+                    }
+                }
                 _ = exit.cancelled() => break 'publishing_all,
             }
         }
 
-        let iteration_time_left = update_frequency.saturating_sub(iteration_start_time.elapsed());
+        for group in &mut groups {
+            if group.next_update <= iteration_start_time {
+                let update_frequency = group
+                    .update_frequency
+                    .unwrap_or_else(|| load.update_frequency_at(iteration_start_time - start_time));
+                group.next_update = iteration_start_time + update_frequency;
+            }
+        }
+
+        let next_update = groups
+            .iter()
+            .map(|group| group.next_update)
+            .min()
+            .unwrap_or(iteration_start_time);
+        let iteration_time_left = next_update.saturating_duration_since(Instant::now());
         if !iteration_time_left.is_zero() {
-            select! {
-                _ = sleep(iteration_time_left) => (),
-                _ = exit.cancelled() => break 'publishing_all,
+            let iteration_end = sleep(iteration_time_left);
+            tokio::pin!(iteration_end);
+            loop {
+                select! {
+                    _ = &mut iteration_end => break,
+                    Some(send_result) = lagging_price_updates.next() => {
+                        if update_results_consumer.send(send_result).await.is_err() {
+                            break 'publishing_all;
+                        }
+                    }
+                    _ = exit.cancelled() => break 'publishing_all,
+                }
             }
         }
     }
@@ -142,13 +233,31 @@ pub async fn run_publisher(
     Ok(())
 }
 
+/// Price sources that are updated together.
+struct SourceGroup {
+    sources: Vec<PriceSource>,
+    /// `None` when following the [`Load`] update frequency and phases.
+    update_frequency: Option<Duration>,
+    next_update: Instant,
+}
+
 type PriceUpdateFutures<'env> = FuturesUnordered<BoxFuture<'env, PriceUpdateResult>>;
 
+/// Numbers the price update transactions of all the publishers, for the transaction records.
+static NEXT_TX_INDEX: AtomicU64 = AtomicU64::new(0);
+
 #[allow(clippy::too_many_arguments)]
-fn start_all_price_updates<'update_deps, 'rpc_client: 'update_deps, 'socket: 'update_deps>(
+fn start_all_price_updates<
+    'update_deps,
+    'rpc_client: 'update_deps,
+    'socket: 'update_deps,
+    'service: 'update_deps,
+>(
     rpc_client: &'rpc_client RpcClient,
     price_updates: &mut PriceUpdateFutures<'update_deps>,
+    lag: Duration,
     socket: &'socket UdpSocket,
+    node_address_service: &'service NodeAddressService,
     latest_blockhash: Hash,
     target_nodes: &[SocketAddr],
     time: f64,
@@ -159,98 +268,165 @@ fn start_all_price_updates<'update_deps, 'rpc_client: 'update_deps, 'socket: 'up
     publisher_pubkey: Pubkey,
     price_buffer_pubkey: Pubkey,
     price_updates_per_tx: u8,
-    price_sources: &[PriceSource],
+    price_sources: &[&PriceSource],
+    oracle_updates: Option<&OracleUpdates>,
+    current_slot: Slot,
+    fault_injection: &FaultInjection,
+    send_mode: SendMode,
 ) -> Result<()> {
-    let prices = price_sources
-        .iter()
-        .map(|price_source| {
-            let (price, confidence) = price_source.get(time);
+    let mut prices = vec![];
+    let mut oracle_instructions = vec![];
+    for price_source in price_sources {
+        let (price, confidence) = price_source.get(time);
+        let price_feed_index = price_source.price_feed_index;
 
-            BufferedPrice::new(
+        let oracle_price_account = oracle_updates.and_then(|oracle_updates| {
+            let price_account = oracle_updates.price_accounts.get(&price_feed_index)?;
+            Some((oracle_updates.program_id, *price_account))
+        });
+        match oracle_price_account {
+            Some((oracle_program_id, price_account)) => {
+                oracle_instructions.push(upd_price::instruction(
+                    oracle_program_id,
+                    publisher_pubkey,
+                    price_account,
+                    PC_STATUS_TRADING,
+                    price,
+                    confidence,
+                    current_slot,
+                ))
+            }
+            None => prices.push(BufferedPrice::new(
                 TradingStatus::Trading,
-                price_source.price_feed_index,
+                price_feed_index,
                 price,
                 confidence,
-            )
+            )),
+        }
+    }
+
+    // Both kinds of transactions are signed by the payer and by the publisher.
+    let price_store_transactions = prices.chunks(price_updates_per_tx.into()).map(|prices| {
+        vec![submit_prices::instruction(
+            program_id,
+            publisher_pubkey,
+            price_buffer_pubkey,
+            prices,
+        )]
+    });
+    let oracle_transactions = oracle_updates
+        .map(|oracle_updates| {
+            oracle_instructions
+                .chunks(oracle_updates.updates_per_tx.into())
+                .map(<[_]>::to_vec)
+                .collect::<Vec<_>>()
         })
-        .collect::<Vec<_>>();
+        .unwrap_or_default();
 
-    for prices in prices.chunks(price_updates_per_tx.into()) {
-        let transaction = Transaction::new_signed_with_payer(
-            &[submit_prices::instruction(
-                program_id,
-                publisher_pubkey,
-                price_buffer_pubkey,
-                prices,
-            )],
+    for instructions in price_store_transactions.chain(oracle_transactions) {
+        let mut transaction = Transaction::new_signed_with_payer(
+            &instructions,
             Some(&payer_pubkey),
             &[&payer, &publisher_keypair],
             latest_blockhash,
         );
 
-        //- println!(
-        //-     "D.start_all_price_updates.1: starting task to rpc_send() from {}",
-        //-     publisher_pubkey
-        //- );
-        price_updates.push({
-            let transaction = transaction.clone();
-            Box::pin(async move {
-                // let rpc_result = rpc_client.send_transaction(&transaction).await;
-                debug_rpc_send(rpc_client, &transaction)
-                    .await
-                    .into_price_update_result()
-            })
-        });
-
-        const SEND_OVER_UDP: bool = false;
-        if !SEND_OVER_UDP {
+        let fault_plan = fault_injection.plan();
+        if fault_plan.drop {
+            price_updates.push(Box::pin(async {
+                PriceUpdateResult::Faulty {
+                    fault: InjectedFault::Dropped,
+                    success: false,
+                }
+            }));
             continue;
         }
+        if fault_plan.malformed {
+            malform(&mut transaction);
+        }
+        let delay = lag + fault_plan.delay.unwrap_or_default();
 
-        let buf = encode_to_vec(transaction, bincode::config::legacy())
-            .context("Serialization of the submit prices transaction")?;
-        for node_address in target_nodes.iter().copied() {
-            //- println!(
-            //-     "D.start_all_price_updates.2: starting task to send_to({}) to {}",
-            //-     buf.len(),
-            //-     node_address
-            //- );
-            price_updates.push({
-                let buf = buf.clone();
-                Box::pin(async move {
-                    //- println!(
-                    //-     "D.start_all_price_updates.2.1: Socket local address pre send_to(): {:?}",
-                    //-     socket.local_addr(),
-                    //- );
-                    // let update_result = match socket.send_to(&buf, node_address).await {
-                    match socket.send_to(&buf, node_address).await {
-                        Ok(sent) => {
-                            if sent != buf.len() {
-                                warn!("Failed to send a submit price transaction in one packet");
-                                //- println!(
-                                //-     "D.start_all_price_updates.2.2: send_to() cut from {} to {} bytes",
-                                //-     buf.len(),
-                                //-     sent
-                                //- );
+        // All the sends of a transaction share the same serialized copy.
+        let signature = transaction.signatures[0];
+        let tx_index = NEXT_TX_INDEX.fetch_add(1, Ordering::Relaxed);
+        let serialized: Arc<[u8]> = encode_to_vec(&transaction, bincode::config::legacy())
+            .context("Serialization of the price update transaction")?
+            .into();
+
+        if send_mode == SendMode::Udp {
+            for node_address in target_nodes.iter().copied() {
+                price_updates.push({
+                    let buf = serialized.clone();
+                    let fault = fault_plan.primary_fault();
+                    Box::pin(async move {
+                        if !lag.is_zero() {
+                            sleep(lag).await;
+                        }
+                        let sent_at = SystemTime::now();
+                        let sent_slot = node_address_service.estimated_current_slot();
+                        match socket.send_to(&buf, node_address).await {
+                            Ok(sent) if sent == buf.len() => {
+                                record_send(tx_index, publisher_pubkey, signature, sent_at, None);
+                                PriceUpdateResult::Success(Some((signature, sent_slot)))
+                            }
+                            Ok(_sent) => {
+                                warn!("Failed to send a price update transaction in one packet");
+                                record_send(
+                                    tx_index,
+                                    publisher_pubkey,
+                                    signature,
+                                    sent_at,
+                                    Some("Transaction did not fit into one packet"),
+                                );
                                 PriceUpdateResult::Fail
-                            } else {
-                                //- println!("D.start_all_price_updates.2.3: send_to() sent {sent} bytes");
-                                PriceUpdateResult::Success
                             }
-                        }
-                        Err(_err) => {
                             // We do not care if the send fails.  We are not going to retry it.
-                            //- println!("D.start_all_price_updates.2.4: send_to() failed: {err:?}");
-                            PriceUpdateResult::Fail
+                            Err(err) => {
+                                record_send(
+                                    tx_index,
+                                    publisher_pubkey,
+                                    signature,
+                                    sent_at,
+                                    Some(&err.to_string()),
+                                );
+                                PriceUpdateResult::Fail
+                            }
                         }
-                    }
-
-                    //- println!(
-                    //-     "D.start_all_price_updates.2.5: Socket local address post send_to(): {:?}",
-                    //-     socket.local_addr(),
-                    //- );
+                        .with_fault(fault)
+                    })
+                });
+            }
+            continue;
+        }
 
-                    // update_result
+        //- println!(
+        //-     "D.start_all_price_updates.1: starting task to rpc_send() from {}",
+        //-     publisher_pubkey
+        //- );
+        // Each send is attributed to a fault, if any.  A duplicate is an extra send.
+        let mut sends = vec![fault_plan.primary_fault()];
+        if fault_plan.duplicate {
+            sends.push(Some(InjectedFault::Duplicated));
+        }
+        for fault in sends {
+            price_updates.push({
+                let serialized = serialized.clone();
+                Box::pin(async move {
+                    if !delay.is_zero() {
+                        sleep(delay).await;
+                    }
+                    // let rpc_result = rpc_client.send_transaction(&transaction).await;
+                    let sent_at = SystemTime::now();
+                    let sent_slot = node_address_service.estimated_current_slot();
+                    let res = debug_rpc_send(rpc_client, &serialized, signature).await;
+                    record_send(
+                        tx_index,
+                        publisher_pubkey,
+                        signature,
+                        sent_at,
+                        res.as_ref().err().map(|err| err.to_string()).as_deref(),
+                    );
+                    res.into_price_update_result(sent_slot).with_fault(fault)
                 })
             });
         }
@@ -259,9 +435,33 @@ fn start_all_price_updates<'update_deps, 'rpc_client: 'update_deps, 'socket: 'up
     Ok(())
 }
 
+/// Price updates are sent once, and are not confirmed, so only the send is recorded.  Every
+/// destination of a transaction gets its own row.
+fn record_send(
+    tx_index: u64,
+    publisher: Pubkey,
+    signature: Signature,
+    sent_at: SystemTime,
+    error: Option<&str>,
+) {
+    if !tx_records::is_enabled() {
+        return;
+    }
+
+    tx_records::write(&TxRecord {
+        index: tx_index,
+        label: Some(&publisher.to_string()),
+        signature,
+        sent_at,
+        confirmed_at: None,
+        slot: None,
+        retries: 0,
+        error,
+    });
+}
+
 use base64::{Engine, prelude::BASE64_STANDARD};
 use serde_json::json;
-use solana_rpc_client::rpc_client::SerializableTransaction;
 use solana_rpc_client_api::{
     client_error::{ErrorKind as ClientErrorKind, Result as ClientResult},
     config::RpcSendTransactionConfig,
@@ -273,7 +473,8 @@ use solana_transaction_status::UiTransactionEncoding;
 
 async fn debug_rpc_send(
     rpc_client: &RpcClient,
-    transaction: &Transaction,
+    serialized: &[u8],
+    signature: Signature,
 ) -> ClientResult<Signature> {
     let config = RpcSendTransactionConfig {
         encoding: Some(UiTransactionEncoding::Base64),
@@ -281,12 +482,8 @@ async fn debug_rpc_send(
         ..RpcSendTransactionConfig::default()
     };
 
-    let serialized_encoded = {
-        let serialized = encode_to_vec(transaction, bincode::config::legacy())
-            .expect("Transaction serialization failed");
-        //- println!("D.debug_rpc_send. tx byte len: {}", serialized.len());
-        BASE64_STANDARD.encode(serialized)
-    };
+    //- println!("D.debug_rpc_send. tx byte len: {}", serialized.len());
+    let serialized_encoded = BASE64_STANDARD.encode(serialized);
 
     let _signature_base58_str: String = match rpc_client
         .send(
@@ -323,5 +520,5 @@ async fn debug_rpc_send(
 
     //- println!("D.debug_rpc_send: Tx RPC signature: {signature_base58_str}");
 
-    Ok(*transaction.get_signature())
+    Ok(signature)
 }
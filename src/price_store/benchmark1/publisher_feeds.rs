@@ -0,0 +1,105 @@
+//! Assignment of price feeds to individual publishers, for the `--publisher-feeds` of the
+//! `benchmark1` and the `stale-prices` commands.
+
+use std::{collections::BTreeMap, fs, ops::RangeInclusive, path::Path};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use pythnet_heisenberg::output;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use serde_yaml::Value;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::args::price_store::benchmark1::feed_range_parser;
+
+/// Price feeds a publisher updates, instead of the whole benchmark range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublisherFeeds {
+    pub publisher: Pubkey,
+    pub feeds: Vec<RangeInclusive<u32>>,
+}
+
+pub fn read(path: &Path) -> Result<Vec<PublisherFeeds>> {
+    let content = fs::read_to_string(path).with_context(|| {
+        format!(
+            "Failed to read publisher feeds file: {}",
+            path.to_string_lossy()
+        )
+    })?;
+    let parsed: BTreeMap<String, Vec<Value>> =
+        serde_yaml::from_str(&content).with_context(|| {
+            format!(
+                "Failed to parse publisher feeds file: {}",
+                path.to_string_lossy()
+            )
+        })?;
+
+    parsed
+        .into_iter()
+        .map(|(publisher, ranges)| {
+            let context = || format!("{}: publisher {publisher}", path.to_string_lossy());
+            let publisher = publisher
+                .parse::<Pubkey>()
+                .map_err(|err| anyhow!("Invalid pubkey: {err}"))
+                .with_context(context)?;
+            let feeds = ranges
+                .iter()
+                .map(|range| {
+                    let range = match range {
+                        Value::Number(index) => index.to_string(),
+                        Value::String(range) => range.clone(),
+                        range => bail!("Expected a feed index or a range, got: {range:?}"),
+                    };
+                    feed_range_parser(&range).map_err(|err| anyhow!(err))
+                })
+                .collect::<Result<Vec<_>>>()
+                .with_context(context)?;
+            Ok(PublisherFeeds { publisher, feeds })
+        })
+        .collect()
+}
+
+/// Prints how many of the `publishers` update each of the `price_feed_indices`.  Publishers
+/// without an assignment update all the feeds.
+pub fn print_coverage(
+    assignments: &[PublisherFeeds],
+    price_feed_indices: &RangeInclusive<u32>,
+    publishers: usize,
+) {
+    let unassigned = publishers.saturating_sub(assignments.len());
+    let counts = price_feed_indices
+        .clone()
+        .map(|feed_index| {
+            unassigned
+                + assignments
+                    .iter()
+                    .filter(|assignment| {
+                        assignment
+                            .feeds
+                            .iter()
+                            .any(|feeds| feeds.contains(&feed_index))
+                    })
+                    .count()
+        })
+        .collect::<Vec<_>>();
+
+    let min = counts.iter().copied().min().unwrap_or(0);
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let mean = counts.iter().sum::<usize>() as f64 / counts.len().max(1) as f64;
+    let uncovered = counts.iter().filter(|count| **count == 0).count();
+
+    output::result(
+        format!(
+            "Publishers per feed: {mean:.1} on average, {min} minimum, {max} maximum, \
+             {uncovered} feeds without publishers"
+        ),
+        json!({
+            "publishers_per_feed": {
+                "mean": mean,
+                "min": min,
+                "max": max,
+                "feeds_without_publishers": uncovered,
+            },
+        }),
+    );
+}
@@ -0,0 +1,216 @@
+//! On-chain state of the accounts a benchmark updates, captured before and after the run.
+//!
+//! Transaction stats show what the benchmark has sent.  Comparing the Oracle price accounts and
+//! the price buffers before and after the run shows what the cluster has actually processed.
+
+use std::{collections::HashMap, mem::size_of, ops::RangeInclusive};
+
+use anyhow::{Context as _, Result};
+use bytemuck::pod_read_unaligned;
+use itertools::izip;
+use pythnet_heisenberg::{
+    output, price_store::accounts::BufferHeader, rpc_client_ext::RpcClientExt as _,
+};
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{clock::Slot, pubkey::Pubkey};
+
+use crate::oracle::price_accounts::{self, PriceAccounts};
+
+pub struct StateSnapshot {
+    /// Slot the account states were reported for.
+    slot: Slot,
+    /// Price accounts with feed indices the benchmark updates.
+    prices: HashMap<Pubkey, PriceState>,
+    /// Headers of the price buffers, in the order of the benchmark `--price-buffer-pubkey`
+    /// arguments.  `None` for missing or invalid accounts.
+    buffers: Vec<Option<BufferHeader>>,
+}
+
+struct PriceState {
+    agg_price: i64,
+    agg_pub_slot: Slot,
+    /// `pub_slot` of the latest price from each of the component publishers.
+    publisher_slots: HashMap<Pubkey, Slot>,
+}
+
+impl StateSnapshot {
+    pub fn slot(&self) -> Slot {
+        self.slot
+    }
+
+    /// Reads the state at the `min_slot` or later.  Price accounts read earlier, by this process,
+    /// are reused without a `min_slot`.
+    pub async fn take(
+        rpc_client: &RpcClient,
+        oracle_program_id: &Pubkey,
+        price_feed_indices_range: &RangeInclusive<u32>,
+        price_buffers: &[Pubkey],
+        min_slot: Option<Slot>,
+    ) -> Result<Self> {
+        let price_accounts = price_accounts::fetch(
+            rpc_client,
+            oracle_program_id,
+            price_accounts::FULL_DATA_LEN,
+            min_slot,
+        )
+        .await?;
+        let (buffers_slot, buffer_accounts) = rpc_client
+            .get_multiple_accounts_chunked(price_buffers)
+            .await
+            .context("Failed to fetch price buffer accounts")?;
+
+        let prices = price_accounts
+            .with_feed_indices(price_feed_indices_range)
+            .map(|(address, _feed_index, data)| {
+                let price = PriceAccounts::decode(data);
+                let components = usize::try_from(price.num)
+                    .unwrap_or(usize::MAX)
+                    .min(price.comp.len());
+                (
+                    address,
+                    PriceState {
+                        agg_price: price.agg.price,
+                        agg_pub_slot: price.agg.pub_slot,
+                        publisher_slots: price.comp[..components]
+                            .iter()
+                            .map(|component| (component.pub_, component.latest.pub_slot))
+                            .collect(),
+                    },
+                )
+            })
+            .collect();
+
+        let buffers = buffer_accounts
+            .into_iter()
+            .map(|account| {
+                let data = account?.data;
+                let data = data.get(..size_of::<BufferHeader>())?;
+                Some(pod_read_unaligned::<BufferHeader>(data))
+            })
+            .collect();
+
+        Ok(Self {
+            slot: buffers_slot.map_or(price_accounts.slot, |buffers_slot| {
+                buffers_slot.max(price_accounts.slot)
+            }),
+            prices,
+            buffers,
+        })
+    }
+}
+
+/// Prints how the price accounts, and the price buffers of each publisher, changed between the
+/// `before` and the `after` snapshots.
+pub fn print_diff(
+    label: Option<&str>,
+    before: &StateSnapshot,
+    after: &StateSnapshot,
+    publishers: &[Pubkey],
+    price_buffers: &[Pubkey],
+) {
+    let prefix = label.map(|label| format!("[{label}] ")).unwrap_or_default();
+
+    let mut aggregates_updated = 0;
+    let mut prices_moved = 0;
+    let mut max_agg_advance = 0;
+    for (address, after_price) in &after.prices {
+        let Some(before_price) = before.prices.get(address) else {
+            continue;
+        };
+        let advance = after_price
+            .agg_pub_slot
+            .saturating_sub(before_price.agg_pub_slot);
+        if advance > 0 {
+            aggregates_updated += 1;
+            max_agg_advance = max_agg_advance.max(advance);
+        }
+        if after_price.agg_price != before_price.agg_price {
+            prices_moved += 1;
+        }
+    }
+
+    output::result(
+        format!(
+            "  {prefix}State diff, slots {} to {}:\n    \
+                 Price accounts: {}\n    \
+                 Aggregates updated: {aggregates_updated}, by up to {max_agg_advance} slots\n    \
+                 Aggregate prices changed: {prices_moved}",
+            before.slot,
+            after.slot,
+            after.prices.len(),
+        ),
+        json!({
+            "label": label,
+            "state_diff": {
+                "slot_before": before.slot,
+                "slot_after": after.slot,
+                "price_accounts": after.prices.len(),
+                "aggregates_updated": aggregates_updated,
+                "max_aggregate_slot_advance": max_agg_advance,
+                "aggregate_prices_changed": prices_moved,
+            },
+        }),
+    );
+
+    for (publisher, buffer, buffer_before, buffer_after) in
+        izip!(publishers, price_buffers, &before.buffers, &after.buffers)
+    {
+        // Price accounts this publisher is a component of.
+        let mut feeds = 0;
+        let mut advanced = 0;
+        let mut last_pub_slot = None;
+        for (address, after_price) in &after.prices {
+            let Some(after_slot) = after_price.publisher_slots.get(publisher) else {
+                continue;
+            };
+            feeds += 1;
+            let before_slot = before
+                .prices
+                .get(address)
+                .and_then(|before_price| before_price.publisher_slots.get(publisher));
+            if before_slot.is_none_or(|before_slot| after_slot > before_slot) {
+                advanced += 1;
+            }
+            last_pub_slot = last_pub_slot.max(Some(*after_slot));
+        }
+
+        let buffer_slot_before = buffer_before.map(|header| header.slot);
+        let buffer_slot_after = buffer_after.map(|header| header.slot);
+        let buffer_prices = buffer_after.map(|header| header.num_prices);
+
+        let text = if feeds == 0 {
+            format!(
+                "    {prefix}Publisher {publisher}: not a component of any of the price accounts"
+            )
+        } else {
+            format!(
+                "    {prefix}Publisher {publisher}: pub_slot advanced in {advanced} of {feeds} \
+                 price accounts, latest: {}\n      \
+                   Buffer {buffer}: last update slot {} to {}, with {} prices",
+                display_option(last_pub_slot),
+                display_option(buffer_slot_before),
+                display_option(buffer_slot_after),
+                display_option(buffer_prices),
+            )
+        };
+        output::result(
+            text,
+            json!({
+                "label": label,
+                "publisher": publisher.to_string(),
+                "price_accounts": feeds,
+                "pub_slot_advanced": advanced,
+                "last_pub_slot": last_pub_slot,
+                "price_buffer": buffer.to_string(),
+                "buffer_slot_before": buffer_slot_before,
+                "buffer_slot_after": buffer_slot_after,
+                "buffer_prices": buffer_prices,
+            }),
+        );
+    }
+}
+
+fn display_option<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "-".to_owned(), |value| value.to_string())
+}
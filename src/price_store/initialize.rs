@@ -1,13 +1,15 @@
 use anyhow::{Context as _, Result};
-use solana_sdk::signer::Signer as _;
-
-use crate::{
-    args::{json_rpc_url_args::get_rpc_client, price_store::initialize::InitializeArgs},
+use pythnet_heisenberg::{
+    blockhash_cache::{BlockhashCache, with_blockhash},
     keypair_ext::read_keypair_file,
+    output,
+    price_store::instructions::initialize,
     rpc_client_ext::RpcClientExt as _,
 };
+use serde_json::json;
+use solana_sdk::signer::Signer as _;
 
-use super::instructions::initialize;
+use crate::args::{json_rpc_url_args::get_rpc_client, price_store::initialize::InitializeArgs};
 
 pub async fn run(
     InitializeArgs {
@@ -22,16 +24,24 @@ pub async fn run(
     let payer = read_keypair_file(&payer_keypair)?;
     let payer_pubkey = payer.pubkey();
 
-    let signature = rpc_client
-        .send_with_payer_latest_blockhash_with_spinner(
-            &[initialize::instruction(program_id, payer_pubkey, authority)],
-            Some(&payer_pubkey),
-            &[&payer],
-        )
+    let signature = with_blockhash(&rpc_client)
+        .run(async |blockhash_cache: &BlockhashCache| {
+            rpc_client
+                .send_with_payer_cached_blockhash(
+                    &[initialize::instruction(program_id, payer_pubkey, authority)],
+                    Some(&payer_pubkey),
+                    &[&payer],
+                    blockhash_cache,
+                )
+                .await
+        })
         .await
         .context("Transaction execution failed")?;
 
-    println!("Price Store initialization tx: {signature}");
+    output::result(
+        format!("Price Store initialization tx: {signature}"),
+        json!({ "signature": signature.to_string() }),
+    );
 
     Ok(())
 }
@@ -1,23 +1,29 @@
 use std::iter;
 
-use anyhow::{Context as _, Result};
+use anyhow::Result;
 use futures::{StreamExt as _, stream::FuturesUnordered};
 use itertools::izip;
+use pythnet_heisenberg::{
+    blockhash_cache::{BlockhashCache, with_blockhash},
+    dry_run,
+    keypair_ext::{read_keypair_file, read_or_generate_keypair_file},
+    output,
+    price_store::instructions::{buffer_account_size, initialize_publisher},
+    rpc_client_ext::RpcClientExt as _,
+};
+use serde_json::json;
 use solana_program::{pubkey::Pubkey, system_instruction};
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::{rent::Rent, signature::Keypair, signer::Signer as _, transaction::Transaction};
+use solana_sdk::{rent::Rent, signature::Keypair, signer::Signer as _};
 
 use crate::{
     args::{
         json_rpc_url_args::get_rpc_client,
         price_store::initialize_publisher::InitializePublisherArgs,
     },
-    blockhash_cache::{BlockhashCache, with_blockhash},
-    keypair_ext::{read_keypair_file, read_or_generate_keypair_file},
+    exit_code::check_transactions,
 };
 
-use super::instructions::{buffer_account_size, initialize_publisher};
-
 pub async fn run(
     InitializePublisherArgs {
         json_rpc_url,
@@ -51,12 +57,11 @@ pub async fn run(
     let mut successful_tx = 0;
     let mut failed_tx = 0;
 
-    println!(
-        "Initializing {} publishers in parallel...",
-        total_initializations
-    );
+    output::notice(format!(
+        "Initializing {total_initializations} publishers in parallel..."
+    ));
 
-    with_blockhash(rpc_client)
+    let failed_tx = with_blockhash(rpc_client)
         .run(async move |blockhash_cache: &BlockhashCache| {
             let mut init_ops = izip!(&publisher_pubkeys, &price_buffers, max_prices,)
                 .map(|(publisher_pubkey, price_buffer, max_prices)| {
@@ -82,28 +87,48 @@ pub async fn run(
                         price_buffer,
                     }) => {
                         successful_tx += 1;
-                        println!(
-                            "Initialization {} of {}: Success for publisher {} price_buffer {}",
-                            successful_tx + failed_tx,
-                            total_initializations,
-                            publisher,
-                            price_buffer,
+                        output::result(
+                            format!(
+                                "Initialization {} of {}: Success for publisher {} \
+                                 price_buffer {}",
+                                successful_tx + failed_tx,
+                                total_initializations,
+                                publisher,
+                                price_buffer,
+                            ),
+                            json!({
+                                "publisher": publisher.to_string(),
+                                "price_buffer": price_buffer.to_string(),
+                            }),
                         );
                     }
+                    // Transactions were only described.
+                    Err(err) if err.is::<dry_run::Stopped>() => (),
                     Err(err) => {
                         failed_tx += 1;
-                        println!(
-                            "Initialization {} of {}: Error: {}",
-                            successful_tx + failed_tx,
-                            total_initializations,
-                            err,
+                        output::result(
+                            format!(
+                                "Initialization {} of {}: Error: {}",
+                                successful_tx + failed_tx,
+                                total_initializations,
+                                err,
+                            ),
+                            json!({ "error": format!("{err:#}") }),
                         );
                     }
                 }
             }
+
+            failed_tx
         })
         .await;
 
+    if dry_run::is_enabled() {
+        return Err(dry_run::Stopped.into());
+    }
+
+    check_transactions(failed_tx, total_initializations)?;
+
     Ok(())
 }
 
@@ -132,31 +157,28 @@ async fn initialize_one_publisher(
         usize::try_from(price_buffer_size).expect("Account size fits into a usize"),
     );
 
-    let transaction = Transaction::new_signed_with_payer(
-        &[
-            system_instruction::create_account(
-                &payer_pubkey,
-                &price_buffer_pubkey,
-                price_buffer_lamports,
-                price_buffer_size,
-                &program_id,
-            ),
-            initialize_publisher::instruction(
-                program_id,
-                authority_pubkey,
-                publisher_pubkey,
-                price_buffer_pubkey,
-            ),
-        ],
-        Some(&payer_pubkey),
-        &[&payer, &price_buffer, &authority],
-        blockhash_cache.get(),
-    );
-
     let _signature = rpc_client
-        .send_and_confirm_transaction(&transaction)
-        .await
-        .context("Transaction execution failed")?;
+        .send_with_payer_cached_blockhash(
+            &[
+                system_instruction::create_account(
+                    &payer_pubkey,
+                    &price_buffer_pubkey,
+                    price_buffer_lamports,
+                    price_buffer_size,
+                    &program_id,
+                ),
+                initialize_publisher::instruction(
+                    program_id,
+                    authority_pubkey,
+                    publisher_pubkey,
+                    price_buffer_pubkey,
+                ),
+            ],
+            Some(&payer_pubkey),
+            &[&payer, &price_buffer, &authority],
+            blockhash_cache,
+        )
+        .await?;
 
     Ok(InitDetails {
         publisher: publisher_pubkey,
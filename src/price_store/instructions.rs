@@ -46,24 +46,31 @@ pub enum InstructionId {
 }
 
 /// Address of the Price Store config account.
-fn compute_config_account(program_id: Pubkey) -> (Pubkey, u8) {
+pub fn compute_config_account(program_id: Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[CONFIG_SEED.as_bytes()], &program_id)
 }
 
 /// Address of the Price Store config account for a given publisher.
-fn compute_publisher_config_account(program_id: Pubkey, publisher: Pubkey) -> (Pubkey, u8) {
+pub fn compute_publisher_config_account(program_id: Pubkey, publisher: Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(
         &[PUBLISHER_CONFIG_SEED.as_bytes(), &publisher.to_bytes()],
         &program_id,
     )
 }
 
+// use pyth_price_store::accounts::buffer::{BufferHeader, BufferedPrice};
+// size_of::<BufferHeader>()
+const BUFFER_HEADER_SIZE: u64 = 48;
+// size_of::<BufferedPrice>()
+const BUFFER_ENTRY_SIZE: u64 = 20;
+
 /// Size of a buffer account that can hold this many price updates.
 pub fn buffer_account_size(max_prices: u64) -> u64 {
-    const HEADER_SIZE: u64 = 48;
-    const ENTRY_SIZE: u64 = 20;
+    BUFFER_HEADER_SIZE + max_prices * BUFFER_ENTRY_SIZE
+}
 
-    // use pyth_price_store::accounts::buffer::{BufferHeader, BufferedPrice};
-    // size_of::<BufferHeader>() + max_prices * size_of::<BufferedPrice>()
-    HEADER_SIZE + max_prices * ENTRY_SIZE
+/// Number of price updates a buffer account of this size can hold.  An inverse of
+/// [`buffer_account_size()`].
+pub fn buffer_account_capacity(size: u64) -> u64 {
+    size.saturating_sub(BUFFER_HEADER_SIZE) / BUFFER_ENTRY_SIZE
 }
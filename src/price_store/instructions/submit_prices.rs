@@ -100,4 +100,13 @@ impl BufferedPrice {
             confidence,
         }
     }
+
+    pub fn feed_index(&self) -> u32 {
+        self.trading_status_and_feed_index & FEED_INDEX_MAX
+    }
+
+    /// Raw trading status value.  Values the program does not know about are preserved.
+    pub fn trading_status(&self) -> u32 {
+        self.trading_status_and_feed_index >> 28
+    }
 }
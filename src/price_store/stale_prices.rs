@@ -0,0 +1,280 @@
+//! Detects publishers that silently stopped submitting prices, for example, in the middle of a soak
+//! test.
+//!
+//! The program clears a price buffer when the first update of a new slot arrives, so a buffer
+//! holds the prices from the last slot the publisher has submitted in.  The buffer header `slot` is
+//! the latest update slot of all the feeds in the buffer.  Buffers are found via the publisher
+//! config accounts, so only the buffers publishers currently use are checked.
+//!
+//! When the feeds each publisher is expected to update are known, a publisher is also stale if
+//! any of these feeds is missing from its buffer, as publishers may keep submitting some of their
+//! prices while silently dropping others.
+
+use std::{cmp::Reverse, collections::BTreeMap, mem::size_of, ops::RangeInclusive};
+
+use anyhow::{Context as _, Result, bail};
+use itertools::{Itertools as _, izip};
+use pythnet_heisenberg::{
+    output,
+    price_store::{accounts::PublisherConfig, instructions::compute_publisher_config_account},
+    rpc_client_ext::RpcClientExt as _,
+};
+use serde_json::json;
+use solana_rpc_client_api::filter::RpcFilterType;
+use solana_sdk::{account::Account, clock::Slot, pubkey::Pubkey};
+
+use super::{
+    benchmark1::publisher_feeds::{self, PublisherFeeds},
+    verify_setup::format_range,
+};
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, price_store::stale_prices::StalePricesArgs},
+    watch::decode::read_price_buffer,
+};
+
+/// Latest update of one publisher.
+struct PublisherState {
+    /// `None` when the buffer header does not name the publisher of the config.
+    publisher: Option<Pubkey>,
+    publisher_config: Pubkey,
+    buffer: Pubkey,
+    /// `None` for buffers that were never written to.
+    slot: Option<Slot>,
+    feeds: Vec<u32>,
+    /// Expected feeds that are not in the buffer.  Empty when the expected feeds are not known.
+    missing_feeds: Vec<u32>,
+}
+
+pub async fn run(
+    StalePricesArgs {
+        json_rpc_url,
+        program_id,
+        publisher: publishers,
+        max_lag_slots,
+        feeds: expected_feeds,
+        publisher_feeds: publisher_feeds_file,
+        stale_only,
+    }: StalePricesArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let publisher_feeds = publisher_feeds_file
+        .map(|path| publisher_feeds::read(&path))
+        .transpose()?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|PublisherFeeds { publisher, feeds }| (publisher, feeds))
+        .collect::<BTreeMap<_, _>>();
+    let expected_feeds_of = |publisher: Option<Pubkey>| -> Vec<RangeInclusive<u32>> {
+        publisher
+            .and_then(|publisher| publisher_feeds.get(&publisher).cloned())
+            .unwrap_or_else(|| expected_feeds.iter().cloned().collect())
+    };
+
+    let configs = if publishers.is_empty() {
+        rpc_client
+            .get_program_accounts_typed::<PublisherConfig>(
+                &program_id,
+                vec![RpcFilterType::DataSize(size_of::<PublisherConfig>() as u64)],
+                0,
+            )
+            .await
+            .context("Failed to fetch publisher config accounts")?
+            .into_iter()
+            .map(|(config, account)| (config, Pubkey::new_from_array(account.data.buffer_account)))
+            .collect::<Vec<_>>()
+    } else {
+        let addresses = publishers
+            .iter()
+            .map(|publisher| compute_publisher_config_account(program_id, *publisher).0)
+            .collect::<Vec<_>>();
+        let accounts = rpc_client
+            .get_accounts_chunked::<PublisherConfig>(&addresses)
+            .await
+            .context("Failed to fetch publisher config accounts")?;
+        let mut configs = vec![];
+        for (publisher, config, account) in izip!(&publishers, addresses, accounts) {
+            match account {
+                Some(account) if account.owner == program_id => {
+                    configs.push((config, Pubkey::new_from_array(account.data.buffer_account)))
+                }
+                _ => output::notice(format!(
+                    "{publisher} is not configured in {program_id}: publisher config {config} \
+                     does not exist"
+                )),
+            }
+        }
+        configs
+    };
+
+    let buffer_addresses = configs
+        .iter()
+        .map(|(_config, buffer)| *buffer)
+        .collect::<Vec<_>>();
+    let (_slot, buffers) = rpc_client
+        .get_multiple_accounts_chunked(&buffer_addresses)
+        .await
+        .context("Failed to fetch price buffer accounts")?;
+    // Read after the buffers, so that the lag is not underestimated.
+    let current_slot = rpc_client
+        .get_slot()
+        .await
+        .context("Failed to get the current slot")?;
+
+    let mut states = vec![];
+    for ((publisher_config, buffer), account) in configs.into_iter().zip(buffers) {
+        let Some(Account { data, .. }) = account.filter(|account| account.owner == program_id)
+        else {
+            output::notice(format!(
+                "Price buffer {buffer} of publisher config {publisher_config} does not exist, or \
+                 is not owned by {program_id}"
+            ));
+            continue;
+        };
+        let (header, prices) = match read_price_buffer(&data) {
+            Ok(buffer) => buffer,
+            Err(err) => {
+                output::notice(format!("Price buffer {buffer}: {err:#}"));
+                continue;
+            }
+        };
+
+        // The config address is a PDA of the publisher, so the publisher named in the buffer header
+        // is only reported if it derives to this config.
+        let publisher = Some(Pubkey::new_from_array(header.publisher)).filter(|publisher| {
+            compute_publisher_config_account(program_id, *publisher).0 == publisher_config
+        });
+        let slot = header.slot;
+        let feeds = prices
+            .iter()
+            .map(|price| price.feed_index())
+            .sorted_unstable()
+            .dedup()
+            .collect::<Vec<_>>();
+        let missing_feeds = expected_feeds_of(publisher)
+            .into_iter()
+            .flatten()
+            .filter(|feed| feeds.binary_search(feed).is_err())
+            .sorted_unstable()
+            .dedup()
+            .collect();
+        states.push(PublisherState {
+            publisher,
+            publisher_config,
+            buffer,
+            slot: (slot != 0).then_some(slot),
+            feeds,
+            missing_feeds,
+        });
+    }
+
+    for publisher in publisher_feeds.keys() {
+        if !states
+            .iter()
+            .any(|state| state.publisher == Some(*publisher))
+        {
+            output::notice(format!(
+                "{publisher} is listed in the --publisher-feeds, but its price buffer was not \
+                 checked"
+            ));
+        }
+    }
+
+    let lag_of = |state: &PublisherState| state.slot.map(|slot| current_slot.saturating_sub(slot));
+    let is_stale = |state: &PublisherState| {
+        lag_of(state).is_none_or(|lag| lag > max_lag_slots) || !state.missing_feeds.is_empty()
+    };
+
+    // The most stale publishers first.
+    states.sort_by_key(|state| (lag_of(state).map(Reverse), state.publisher_config));
+
+    for state in &states {
+        let stale = is_stale(state);
+        if stale_only && !stale {
+            continue;
+        }
+        let PublisherState {
+            publisher,
+            publisher_config,
+            buffer,
+            slot,
+            feeds,
+            missing_feeds,
+        } = state;
+
+        let who = match publisher {
+            Some(publisher) => publisher.to_string(),
+            None => format!("publisher config {publisher_config}"),
+        };
+        let status = if stale { "stale" } else { "ok" };
+        let mut text = match (slot, lag_of(state)) {
+            (Some(slot), Some(lag)) => format!(
+                "{who}: {status}, last update in slot {slot}, {lag} slots ago, feeds: {}",
+                feed_ranges(feeds)
+            ),
+            _ => format!("{who}: {status}, never updated"),
+        };
+        if !missing_feeds.is_empty() {
+            text += &format!(", missing feeds: {}", feed_ranges(missing_feeds));
+        }
+        output::result(
+            text,
+            json!({
+                "publisher": publisher.map(|publisher| publisher.to_string()),
+                "publisher_config": publisher_config.to_string(),
+                "buffer": buffer.to_string(),
+                "last_update_slot": slot,
+                "lag_slots": lag_of(state),
+                "stale": stale,
+                "feeds": feeds,
+                "missing_feeds": missing_feeds,
+            }),
+        );
+    }
+
+    let stale = states.iter().filter(|state| is_stale(state)).count();
+    output::result(
+        format!(
+            "Current slot: {current_slot}, publishers: {}, stale: {stale}, over {max_lag_slots} \
+             slots",
+            states.len()
+        ),
+        json!({
+            "current_slot": current_slot,
+            "publishers": states.len(),
+            "stale": stale,
+            "max_lag_slots": max_lag_slots,
+        }),
+    );
+
+    if stale != 0 {
+        bail!(
+            "{stale} out of {} publishers have not updated all their prices in the last \
+             {max_lag_slots} slots",
+            states.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Sorted `feeds`, with consecutive feed indices joined into ranges.
+fn feed_ranges(feeds: &[u32]) -> String {
+    if feeds.is_empty() {
+        return "none".to_owned();
+    }
+    let mut ranges = vec![];
+    let mut start = feeds[0];
+    let mut end = feeds[0];
+    for feed in feeds.iter().copied().skip(1) {
+        if feed == end + 1 {
+            end = feed;
+        } else {
+            ranges.push(format_range(start, end));
+            start = feed;
+            end = feed;
+        }
+    }
+    ranges.push(format_range(start, end));
+    ranges.join(", ")
+}
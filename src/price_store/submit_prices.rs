@@ -1,14 +1,19 @@
 use anyhow::{Context as _, Result};
+use pythnet_heisenberg::{
+    blockhash_cache::{BlockhashCache, with_blockhash},
+    keypair_ext::read_keypair_file,
+    output,
+    price_store::instructions::submit_prices,
+    rpc_client_ext::RpcClientExt as _,
+};
+use serde_json::json;
 use solana_sdk::signer::Signer as _;
 
 use crate::{
     args::{json_rpc_url_args::get_rpc_client, price_store::submit_prices::SubmitPricesArgs},
-    keypair_ext::read_keypair_file,
-    rpc_client_ext::RpcClientExt as _,
+    tx_cost::CostSummary,
 };
 
-use super::instructions::submit_prices;
-
 pub async fn run(
     SubmitPricesArgs {
         json_rpc_url,
@@ -17,6 +22,7 @@ pub async fn run(
         publisher_keypair,
         price_buffer_pubkey,
         price: prices,
+        report_costs,
     }: SubmitPricesArgs,
 ) -> Result<()> {
     let rpc_client = get_rpc_client(json_rpc_url);
@@ -27,21 +33,36 @@ pub async fn run(
     let publisher = read_keypair_file(&publisher_keypair)?;
     let publisher_pubkey = publisher.pubkey();
 
-    let signature = rpc_client
-        .send_with_payer_latest_blockhash_with_spinner(
-            &[submit_prices::instruction(
-                program_id,
-                publisher_pubkey,
-                price_buffer_pubkey,
-                &prices,
-            )],
-            Some(&payer_pubkey),
-            &[&payer, &publisher],
-        )
+    let signature = with_blockhash(&rpc_client)
+        .run(async |blockhash_cache: &BlockhashCache| {
+            rpc_client
+                .send_with_payer_cached_blockhash(
+                    &[submit_prices::instruction(
+                        program_id,
+                        publisher_pubkey,
+                        price_buffer_pubkey,
+                        &prices,
+                    )],
+                    Some(&payer_pubkey),
+                    &[&payer, &publisher],
+                    blockhash_cache,
+                )
+                .await
+        })
         .await
         .context("Transaction execution failed")?;
 
-    println!("Price Store submit price tx: {signature}");
+    output::result(
+        format!("Price Store submit price tx: {signature}"),
+        json!({ "signature": signature.to_string() }),
+    );
+
+    if report_costs {
+        let price_count = u64::try_from(prices.len()).expect("Price count fits into a u64");
+        CostSummary::collect(&rpc_client, &[signature])
+            .await
+            .print(None, Some((price_count, "price update")));
+    }
 
     Ok(())
 }
@@ -0,0 +1,294 @@
+//! Checks that the accounts a `benchmark1` run depends on are in place, so that a misconfigured
+//! setup is detected before the benchmark starts, rather than as a stream of failed transactions.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use itertools::izip;
+use pythnet_heisenberg::{
+    keypair_ext::read_keypair_file,
+    output,
+    price_store::instructions::{buffer_account_capacity, compute_publisher_config_account},
+    rpc_client_ext::RpcClientExt as _,
+};
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    clock::DEFAULT_MS_PER_SLOT, native_token::Sol, pubkey::Pubkey, signer::Signer as _,
+};
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, price_store::verify_setup::VerifySetupArgs},
+    exit_code::ValidationFailed,
+    oracle::feed_index::usage::price_feed_indices,
+};
+
+pub async fn run(
+    VerifySetupArgs {
+        json_rpc_url,
+        program_id,
+        oracle_program_id,
+        payer_keypair: payer_keypairs,
+        publisher_keypair: publisher_keypairs,
+        price_buffer_pubkey: price_buffer_pubkeys,
+        price_feed_index_start,
+        price_feed_index_end,
+        price_updates_per_tx,
+        update_frequency,
+        duration,
+    }: VerifySetupArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let payers = payer_keypairs
+        .iter()
+        .map(|path| read_keypair_file(path).map(|keypair| keypair.pubkey()))
+        .collect::<Result<Vec<_>>>()?;
+    let publishers = publisher_keypairs
+        .iter()
+        .map(|path| read_keypair_file(path).map(|keypair| keypair.pubkey()))
+        .collect::<Result<Vec<_>>>()?;
+
+    let update_frequency: Duration = update_frequency.into();
+    let duration: Duration = duration.into();
+
+    let feeds = u64::from(price_feed_index_end - price_feed_index_start + 1);
+    // Buffers are cleared at the start of every slot, so they need to hold all the updates a
+    // publisher sends within one slot.
+    let updates_per_slot = u64::try_from(
+        u128::from(DEFAULT_MS_PER_SLOT).div_ceil(update_frequency.as_millis().max(1)),
+    )
+    .unwrap_or(u64::MAX);
+    let required_capacity = feeds.saturating_mul(updates_per_slot);
+
+    let lamports_per_signature = rpc_client
+        .get_lamports_per_signature()
+        .await
+        .context("Estimating transaction fees")?;
+    let updates = u64::try_from(
+        duration
+            .as_millis()
+            .div_ceil(update_frequency.as_millis().max(1)),
+    )
+    .unwrap_or(u64::MAX);
+    // Every transaction is signed by the payer and by the publisher.
+    let fees_per_publisher = updates
+        .saturating_mul(feeds.div_ceil(u64::from(price_updates_per_tx)))
+        .saturating_mul(2 * lamports_per_signature);
+
+    let mut problems = vec![];
+
+    problems.extend(
+        check_publishers(
+            &rpc_client,
+            program_id,
+            &publishers,
+            &price_buffer_pubkeys,
+            required_capacity,
+        )
+        .await?,
+    );
+
+    problems.extend(check_payers(&rpc_client, &payers, &publishers, fees_per_publisher).await?);
+
+    if let Some(oracle_program_id) = oracle_program_id {
+        problems.extend(
+            check_feed_indices(
+                &rpc_client,
+                oracle_program_id,
+                price_feed_index_start,
+                price_feed_index_end,
+            )
+            .await?,
+        );
+    }
+
+    output::result(
+        format!(
+            "Publishers: {}\n  \
+               Buffer capacity needed: {required_capacity} prices\n  \
+               Fees per publisher, up to: {}\n  \
+               Problems: {}",
+            publishers.len(),
+            Sol(fees_per_publisher),
+            problems.len(),
+        ),
+        json!({
+            "publishers": publishers.len(),
+            "required_buffer_capacity": required_capacity,
+            "fees_per_publisher": fees_per_publisher,
+            "problems": problems,
+        }),
+    );
+
+    if !problems.is_empty() {
+        return Err(anyhow!("Setup is not ready:\n  {}", problems.join("\n  ")))
+            .context(ValidationFailed);
+    }
+
+    Ok(())
+}
+
+/// Publisher config accounts need to exist, and price buffers need to be owned by the program and
+/// be large enough to hold `required_capacity` prices.
+async fn check_publishers(
+    rpc_client: &RpcClient,
+    program_id: Pubkey,
+    publishers: &[Pubkey],
+    price_buffers: &[Pubkey],
+    required_capacity: u64,
+) -> Result<Vec<String>> {
+    let publisher_configs = publishers
+        .iter()
+        .map(|publisher| compute_publisher_config_account(program_id, *publisher).0)
+        .collect::<Vec<_>>();
+    let (_slot, configs) = rpc_client
+        .get_multiple_accounts_chunked(&publisher_configs)
+        .await
+        .context("Failed to fetch publisher config accounts")?;
+    let (_slot, buffers) = rpc_client
+        .get_multiple_accounts_chunked(price_buffers)
+        .await
+        .context("Failed to fetch price buffer accounts")?;
+
+    let mut problems = vec![];
+    for (publisher, config_address, config, buffer_address, buffer) in izip!(
+        publishers,
+        &publisher_configs,
+        configs,
+        price_buffers,
+        buffers
+    ) {
+        match config {
+            None => problems.push(format!(
+                "Publisher {publisher} is not initialized: publisher config {config_address} \
+                 does not exist.  Add it with `price-store initialize-publisher`."
+            )),
+            Some(config) if config.owner != program_id => problems.push(format!(
+                "Publisher config {config_address} of publisher {publisher} is owned by {}, \
+                 rather than by {program_id}.  Check the --program-id.",
+                config.owner
+            )),
+            Some(_) => (),
+        }
+
+        match buffer {
+            None => problems.push(format!(
+                "Price buffer {buffer_address} of publisher {publisher} does not exist.  Create \
+                 it with `price-store initialize-publisher`."
+            )),
+            Some(buffer) if buffer.owner != program_id => problems.push(format!(
+                "Price buffer {buffer_address} of publisher {publisher} is owned by {}, rather \
+                 than by {program_id}.  Check the --program-id, and the --price-buffer-pubkey \
+                 order.",
+                buffer.owner
+            )),
+            Some(buffer) => {
+                let capacity = buffer_account_capacity(buffer.data.len() as u64);
+                if capacity < required_capacity {
+                    problems.push(format!(
+                        "Price buffer {buffer_address} of publisher {publisher} holds {capacity} \
+                         prices, while up to {required_capacity} could be sent within a slot.  \
+                         Increase the --update-frequency, reduce the number of price feeds, or \
+                         create a new buffer with `--max-prices {required_capacity}`."
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Every publisher needs a payer, and every payer needs to be able to pay the fees for all of the
+/// publishers it is used for.
+async fn check_payers(
+    rpc_client: &RpcClient,
+    payers: &[Pubkey],
+    publishers: &[Pubkey],
+    fees_per_publisher: u64,
+) -> Result<Vec<String>> {
+    let mut problems = vec![];
+
+    if payers.len() < publishers.len() {
+        problems.push(format!(
+            "{} publishers do not have a payer, and would not send any updates.  Specify a \
+             --payer-keypair for every --publisher-keypair.",
+            publishers.len() - payers.len()
+        ));
+    }
+
+    // The same payer may be specified for more than one publisher.
+    let mut required = BTreeMap::<Pubkey, u64>::new();
+    for payer in payers.iter().take(publishers.len()) {
+        *required.entry(*payer).or_default() += fees_per_publisher;
+    }
+
+    for (payer, required) in required {
+        let balance = rpc_client
+            .get_balance(&payer)
+            .await
+            .with_context(|| format!("Failed to get the balance of payer {payer}"))?;
+        if balance < required {
+            problems.push(format!(
+                "Payer {payer} has {}, while the benchmark could spend up to {} on fees.  Fund it \
+                 with `transfer fill-up-to`.",
+                Sol(balance),
+                Sol(required),
+            ));
+        }
+    }
+
+    Ok(problems)
+}
+
+/// All the feed indices in the `start..=end` range need to be assigned to Oracle price accounts.
+async fn check_feed_indices(
+    rpc_client: &RpcClient,
+    oracle_program_id: Pubkey,
+    start: u32,
+    end: u32,
+) -> Result<Vec<String>> {
+    let assigned = price_feed_indices(rpc_client, &oracle_program_id)
+        .await?
+        .into_iter()
+        .map(|(_pubkey, feed_index)| feed_index)
+        .collect::<HashSet<_>>();
+
+    let mut missing = vec![];
+    let mut gap_start = None;
+    for feed_index in start..=end {
+        match (assigned.contains(&feed_index), gap_start) {
+            (false, None) => gap_start = Some(feed_index),
+            (true, Some(first)) => {
+                missing.push(format_range(first, feed_index - 1));
+                gap_start = None;
+            }
+            _ => (),
+        }
+    }
+    if let Some(first) = gap_start {
+        missing.push(format_range(first, end));
+    }
+
+    if missing.is_empty() {
+        return Ok(vec![]);
+    }
+    Ok(vec![format!(
+        "Feed indices {} are not assigned to any price account of {oracle_program_id}.  Add \
+         price accounts with `oracle add-price`, or change the --price-feed-index-start and \
+         --price-feed-index-end.",
+        missing.join(", ")
+    )])
+}
+
+pub(super) fn format_range(start: u32, end: u32) -> String {
+    if start == end {
+        start.to_string()
+    } else {
+        format!("{start}-{end}")
+    }
+}
@@ -0,0 +1,104 @@
+//! Finds the publisher of a price buffer.
+//!
+//! Buffer addresses show up in transaction logs and explorers, but nothing on chain links a buffer
+//! back to its publisher directly.  Publisher config accounts point to the buffers, but they are
+//! PDAs, so the publisher can not be recovered from the config address.  The buffer header names
+//! the publisher it was created for, and the config address derived from that publisher confirms
+//! that the publisher still uses this buffer.
+
+use std::mem::{offset_of, size_of};
+
+use anyhow::{Context as _, Result, bail};
+use bytemuck::pod_read_unaligned;
+use pythnet_heisenberg::{
+    output,
+    price_store::{
+        accounts::{BufferHeader, PublisherConfig},
+        instructions::compute_publisher_config_account,
+    },
+    rpc_client_ext::RpcClientExt as _,
+};
+use serde_json::json;
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::args::{json_rpc_url_args::get_rpc_client, price_store::whose_buffer::WhoseBufferArgs};
+
+pub async fn run(
+    WhoseBufferArgs {
+        json_rpc_url,
+        program_id,
+        buffer,
+    }: WhoseBufferArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    // Only the addresses are needed.
+    let configs = rpc_client
+        .get_program_accounts_typed::<()>(
+            &program_id,
+            vec![
+                RpcFilterType::DataSize(size_of::<PublisherConfig>() as u64),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    offset_of!(PublisherConfig, buffer_account),
+                    buffer.to_bytes().to_vec(),
+                )),
+            ],
+            0,
+        )
+        .await
+        .context("Failed to fetch publisher config accounts")?;
+
+    let header_publisher = rpc_client
+        .get_account_with_commitment(&buffer, rpc_client.commitment())
+        .await
+        .with_context(|| format!("Failed to fetch price buffer {buffer}"))?
+        .value
+        .filter(|account| account.owner == program_id)
+        .and_then(|account| {
+            let header = account.data.get(..size_of::<BufferHeader>())?;
+            let BufferHeader { publisher, .. } = pod_read_unaligned(header);
+            Some(Pubkey::new_from_array(publisher))
+        });
+
+    if configs.is_empty() {
+        match header_publisher {
+            Some(publisher) => bail!(
+                "No publisher config of {program_id} points to {buffer}.  The buffer header names \
+                 {publisher} as the publisher, but that publisher uses a different buffer now."
+            ),
+            None => bail!(
+                "No publisher config of {program_id} points to {buffer}, and it is not a price \
+                 buffer of this program."
+            ),
+        }
+    }
+
+    for (config, _account) in configs {
+        // The config address is a PDA of the publisher, so the publisher named in the buffer header
+        // is only reported if it derives to this config.
+        let publisher = header_publisher.filter(|publisher| {
+            compute_publisher_config_account(program_id, *publisher).0 == config
+        });
+
+        let text = match publisher {
+            Some(publisher) => {
+                format!("Buffer {buffer} belongs to publisher {publisher} (config {config})")
+            }
+            None => format!(
+                "Buffer {buffer} is used by publisher config {config}, but the buffer header does \
+                 not name the publisher of this config"
+            ),
+        };
+        output::result(
+            text,
+            json!({
+                "buffer": buffer.to_string(),
+                "publisher": publisher.map(|publisher| publisher.to_string()),
+                "publisher_config": config.to_string(),
+            }),
+        );
+    }
+
+    Ok(())
+}
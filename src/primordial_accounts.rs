@@ -1,13 +1,18 @@
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 
-use crate::args::primordial_accounts::Command;
+use crate::{args::primordial_accounts::Command, exit_code::ValidationFailed};
 
-mod feature;
-mod loader_v3;
+pub mod feature;
+pub mod loader_v3;
+pub mod validators;
 
 pub async fn run(command: Command) -> Result<()> {
     match command {
         Command::Feature(args) => feature::run(args).await,
         Command::LoaderV3(args) => loader_v3::run(args).await,
+        Command::Validators(args) => {
+            args.check_are_valid().context(ValidationFailed)?;
+            validators::run(args).await
+        }
     }
 }
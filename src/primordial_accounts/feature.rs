@@ -17,27 +17,7 @@ pub async fn run(
         not_active,
     }: FeatureArgs,
 ) -> Result<()> {
-    let rent = Rent::default();
-
-    let feature_account = {
-        let data = Feature {
-            activated_at: if not_active { None } else { Some(0) },
-        };
-        let target_len = Feature::size_of();
-        let mut data = encode_to_vec(data, bincode::config::legacy())
-            .context("Encoding program data with `bincode`")?;
-        if data.len() < target_len {
-            data.resize(target_len, 0);
-        }
-        assert_eq!(data.len(), target_len);
-
-        Base64Account {
-            balance: rent.minimum_balance(data.len()),
-            data: base64::engine::general_purpose::STANDARD.encode(data),
-            executable: false,
-            owner: feature::id().to_string(),
-        }
-    };
+    let feature_account = feature_account(not_active)?;
 
     serde_yaml::to_writer(
         io::stdout().lock(),
@@ -47,3 +27,26 @@ pub async fn run(
 
     Ok(())
 }
+
+/// Constructs a feature account, to be placed at the feature address.
+pub fn feature_account(not_active: bool) -> Result<Base64Account> {
+    let rent = Rent::default();
+
+    let data = Feature {
+        activated_at: if not_active { None } else { Some(0) },
+    };
+    let target_len = Feature::size_of();
+    let mut data = encode_to_vec(data, bincode::config::legacy())
+        .context("Encoding program data with `bincode`")?;
+    if data.len() < target_len {
+        data.resize(target_len, 0);
+    }
+    assert_eq!(data.len(), target_len);
+
+    Ok(Base64Account {
+        balance: rent.minimum_balance(data.len()),
+        data: base64::engine::general_purpose::STANDARD.encode(data),
+        executable: false,
+        owner: feature::id().to_string(),
+    })
+}
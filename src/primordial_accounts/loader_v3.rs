@@ -23,8 +23,6 @@ pub async fn run(
         upgrade_authority,
     }: LoaderV3Args,
 ) -> Result<()> {
-    let rent = Rent::default();
-
     let program_so_data = fs::read(&program_data).with_context(|| {
         format!(
             "Failed to read the --program-data file: {}",
@@ -32,6 +30,38 @@ pub async fn run(
         )
     })?;
 
+    let [
+        (program_id, program_account),
+        (program_data_address, program_data_account),
+    ] = program_accounts(
+        program_id,
+        last_modified_slot,
+        &program_so_data,
+        upgrade_authority,
+    )?;
+
+    serde_yaml::to_writer(
+        io::stdout().lock(),
+        &HashMap::<String, Base64Account>::from([
+            (program_id.to_string(), program_account),
+            (program_data_address.to_string(), program_data_account),
+        ]),
+    )
+    .context("Constructing final YAML")?;
+
+    Ok(())
+}
+
+/// Constructs the program and the program data accounts of a program deployed with the upgradeable
+/// loader.  Returns `(address, account)` pairs for both accounts.
+pub fn program_accounts(
+    program_id: Pubkey,
+    last_modified_slot: u64,
+    program_so_data: &[u8],
+    upgrade_authority: Option<Pubkey>,
+) -> Result<[(Pubkey, Base64Account); 2]> {
+    let rent = Rent::default();
+
     let (program_data_address, _) =
         Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
 
@@ -68,13 +98,12 @@ pub async fn run(
                 bincode::config::legacy(),
             )
             .context("Encoding program data header with `bincode`")?;
-            assert_eq!(
-                encoded_header_size,
-                UpgradeableLoaderState::size_of_programdata_metadata()
-            );
+            // Metadata size accounts for an upgrade authority.  Without one, the header is shorter,
+            // and the rest of the metadata area stays zeroed.
+            assert!(encoded_header_size <= UpgradeableLoaderState::size_of_programdata_metadata());
 
             buf[UpgradeableLoaderState::size_of_programdata_metadata()..]
-                .copy_from_slice(&program_so_data);
+                .copy_from_slice(program_so_data);
 
             buf
         };
@@ -90,14 +119,8 @@ pub async fn run(
         }
     };
 
-    serde_yaml::to_writer(
-        io::stdout().lock(),
-        &HashMap::<String, Base64Account>::from([
-            (program_id.to_string(), program_account),
-            (program_data_address.to_string(), program_data_account),
-        ]),
-    )
-    .context("Constructing final YAML")?;
-
-    Ok(())
+    Ok([
+        (program_id, program_account),
+        (program_data_address, program_data_account),
+    ])
 }
@@ -0,0 +1,170 @@
+use std::{collections::HashMap, io};
+
+use anyhow::{Context as _, Result, anyhow};
+use base64::{self, Engine as _};
+use bincode::{self, serde::encode_to_vec};
+use itertools::Itertools as _;
+use pythnet_heisenberg::keypair_ext::read_keypair_file;
+use solana_genesis::Base64Account;
+use solana_sdk::{
+    clock::{Clock, Epoch},
+    pubkey::Pubkey,
+    signer::Signer as _,
+    stake::{
+        self,
+        stake_flags::StakeFlags,
+        state::{Authorized, Delegation, Lockup, Meta, Stake, StakeStateV2},
+    },
+    system_program,
+    sysvar::rent::Rent,
+    vote::{
+        self,
+        state::{VoteInit, VoteState, VoteStateVersions},
+    },
+};
+
+use crate::args::primordial_accounts::validators::ValidatorsArgs;
+
+/// Seed for the vote account address, derived from the validator identity.
+pub const VOTE_ACCOUNT_SEED: &str = "vote";
+
+/// Seed for the stake account address, derived from the validator identity.
+pub const STAKE_ACCOUNT_SEED: &str = "stake";
+
+pub async fn run(
+    ValidatorsArgs {
+        identity_keypair,
+        identity_lamports,
+        stake_lamports,
+        commission,
+        authorized_withdrawer,
+        identities,
+    }: ValidatorsArgs,
+) -> Result<()> {
+    let identities = identity_keypair
+        .iter()
+        .map(|path| read_keypair_file(path).map(|keypair| keypair.pubkey()))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .chain(identities)
+        .unique()
+        .collect::<Vec<_>>();
+
+    let mut accounts = HashMap::<String, Base64Account>::new();
+    for identity in identities {
+        let [
+            (identity, identity_account),
+            (vote_address, vote_account),
+            (stake_address, stake_account),
+        ] = validator_accounts(
+            identity,
+            identity_lamports,
+            stake_lamports,
+            commission,
+            authorized_withdrawer.unwrap_or(identity),
+        )?;
+
+        eprintln!(
+            "Validator {identity}: vote account {vote_address}, stake account {stake_address}"
+        );
+
+        accounts.insert(identity.to_string(), identity_account);
+        accounts.insert(vote_address.to_string(), vote_account);
+        accounts.insert(stake_address.to_string(), stake_account);
+    }
+
+    serde_yaml::to_writer(io::stdout().lock(), &accounts).context("Constructing final YAML")?;
+
+    Ok(())
+}
+
+/// Constructs the identity, the vote, and the stake accounts of a validator.  Stake is delegated
+/// as a bootstrap stake, so it is fully active from the first epoch.  Returns `(address, account)`
+/// pairs for all three accounts.
+pub fn validator_accounts(
+    identity: Pubkey,
+    identity_lamports: u64,
+    stake_lamports: u64,
+    commission: u8,
+    authorized_withdrawer: Pubkey,
+) -> Result<[(Pubkey, Base64Account); 3]> {
+    let rent = Rent::default();
+
+    let vote_address = Pubkey::create_with_seed(&identity, VOTE_ACCOUNT_SEED, &vote::program::id())
+        .context("Deriving the vote account address")?;
+    let stake_address =
+        Pubkey::create_with_seed(&identity, STAKE_ACCOUNT_SEED, &stake::program::id())
+            .context("Deriving the stake account address")?;
+
+    let identity_account = Base64Account {
+        balance: identity_lamports,
+        data: String::new(),
+        executable: false,
+        owner: system_program::id().to_string(),
+    };
+
+    let vote_account = {
+        let vote_state = VoteState::new(
+            &VoteInit {
+                node_pubkey: identity,
+                authorized_voter: identity,
+                authorized_withdrawer,
+                commission,
+            },
+            &Clock::default(),
+        );
+
+        let mut data = vec![0; VoteState::size_of()];
+        VoteState::serialize(&VoteStateVersions::new_current(vote_state), &mut data)
+            .map_err(|err| anyhow!("Encoding the vote state: {err}"))?;
+
+        Base64Account {
+            balance: rent.minimum_balance(data.len()),
+            data: base64::engine::general_purpose::STANDARD.encode(data),
+            executable: false,
+            owner: vote::program::id().to_string(),
+        }
+    };
+
+    let stake_account = {
+        let rent_exempt_reserve = rent.minimum_balance(StakeStateV2::size_of());
+        let data = StakeStateV2::Stake(
+            Meta {
+                rent_exempt_reserve,
+                authorized: Authorized::auto(&authorized_withdrawer),
+                lockup: Lockup::default(),
+            },
+            Stake {
+                // `Epoch::MAX` marks a bootstrap stake, that is active from the start.
+                delegation: Delegation::new(
+                    &vote_address,
+                    stake_lamports.saturating_sub(rent_exempt_reserve),
+                    Epoch::MAX,
+                ),
+                credits_observed: 0,
+            },
+            StakeFlags::empty(),
+        );
+
+        let target_len = StakeStateV2::size_of();
+        let mut data = encode_to_vec(data, bincode::config::legacy())
+            .context("Encoding stake state with `bincode`")?;
+        if data.len() < target_len {
+            data.resize(target_len, 0);
+        }
+        assert_eq!(data.len(), target_len);
+
+        Base64Account {
+            balance: stake_lamports.max(rent_exempt_reserve),
+            data: base64::engine::general_purpose::STANDARD.encode(data),
+            executable: false,
+            owner: stake::program::id().to_string(),
+        }
+    };
+
+    Ok([
+        (identity, identity_account),
+        (vote_address, vote_account),
+        (stake_address, stake_account),
+    ])
+}
@@ -0,0 +1,13 @@
+use anyhow::Result;
+
+use crate::args::program::Command;
+
+mod close_buffers;
+mod deploy;
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Deploy(args) => deploy::run(args).await,
+        Command::CloseBuffers(args) => close_buffers::run(args).await,
+    }
+}
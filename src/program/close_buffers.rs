@@ -0,0 +1,191 @@
+use anyhow::{Context as _, Result};
+use pythnet_heisenberg::{
+    blockhash_cache::BlockhashCache,
+    keypair_ext::read_keypair_file,
+    output,
+    rpc_client_ext::RpcClientExt as _,
+    tx_sheppard::{TxOutcome, with_sheppard},
+};
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use solana_sdk::{
+    bpf_loader_upgradeable, native_token::lamports_to_sol, pubkey::Pubkey, signature::Keypair,
+    signer::Signer as _, transaction::Transaction,
+};
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, program::close_buffers::CloseBuffersArgs},
+    confirm::confirm_or_abort,
+    exit_code::check_outcomes,
+};
+
+pub async fn run(
+    CloseBuffersArgs {
+        json_rpc_url,
+        authority_keypair: authority_keypairs,
+        recipient,
+        payer_keypair,
+        list_only,
+        yes,
+    }: CloseBuffersArgs,
+) -> Result<()> {
+    let authorities = authority_keypairs
+        .iter()
+        .map(read_keypair_file)
+        .collect::<Result<Vec<_>>>()?;
+    let payer = payer_keypair.map(read_keypair_file).transpose()?;
+    let payer = payer.as_ref().unwrap_or(&authorities[0]);
+
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let mut buffers = vec![];
+    for authority in &authorities {
+        for (address, lamports) in authority_buffers(&rpc_client, authority.pubkey()).await? {
+            buffers.push(Buffer {
+                address,
+                lamports,
+                authority,
+            });
+        }
+    }
+
+    for Buffer {
+        address,
+        lamports,
+        authority,
+    } in &buffers
+    {
+        output::result(
+            format!(
+                "Buffer {address}: authority {}, {} SOL",
+                authority.pubkey(),
+                lamports_to_sol(*lamports)
+            ),
+            json!({
+                "buffer": address.to_string(),
+                "authority": authority.pubkey().to_string(),
+                "lamports": lamports,
+            }),
+        );
+    }
+
+    if buffers.is_empty() {
+        output::notice("No buffers found");
+        return Ok(());
+    }
+    if list_only {
+        return Ok(());
+    }
+
+    let total_lamports = buffers.iter().map(|buffer| buffer.lamports).sum::<u64>();
+    confirm_or_abort(
+        format!(
+            "Closing {} buffers, reclaiming {} SOL",
+            buffers.len(),
+            lamports_to_sol(total_lamports)
+        ),
+        yes,
+    )?;
+
+    let outcomes = with_sheppard(&rpc_client)
+        .labels(buffers.iter().map(|Buffer { address, .. }| address))
+        .run(buffers.iter().map(
+            |Buffer {
+                 address, authority, ..
+             }| {
+                let recipient = recipient.unwrap_or_else(|| authority.pubkey());
+                move |blockhash_cache: &BlockhashCache| {
+                    let instruction =
+                        bpf_loader_upgradeable::close(address, &recipient, &authority.pubkey());
+                    if payer.pubkey() == authority.pubkey() {
+                        Transaction::new_signed_with_payer(
+                            &[instruction],
+                            Some(&payer.pubkey()),
+                            &[payer],
+                            blockhash_cache.get(),
+                        )
+                    } else {
+                        Transaction::new_signed_with_payer(
+                            &[instruction],
+                            Some(&payer.pubkey()),
+                            &[payer, *authority],
+                            blockhash_cache.get(),
+                        )
+                    }
+                }
+            },
+        ))
+        .await
+        .context("Closing buffers")?;
+
+    let mut reclaimed = 0;
+    for (
+        Buffer {
+            address, lamports, ..
+        },
+        outcome,
+    ) in buffers.iter().zip(&outcomes)
+    {
+        match outcome {
+            TxOutcome::Success(signature) => {
+                reclaimed += lamports;
+                output::result(
+                    format!("Closed {address}: {signature}"),
+                    json!({
+                        "buffer": address.to_string(),
+                        "signature": signature.to_string(),
+                    }),
+                );
+            }
+            TxOutcome::Failed(error) => output::result(
+                format!("Failed to close {address}: {error}"),
+                json!({
+                    "buffer": address.to_string(),
+                    "error": error,
+                }),
+            ),
+        }
+    }
+    output::notice(format!("Reclaimed {} SOL", lamports_to_sol(reclaimed)));
+
+    check_outcomes(&outcomes)?;
+
+    Ok(())
+}
+
+struct Buffer<'authority> {
+    address: Pubkey,
+    lamports: u64,
+    authority: &'authority Keypair,
+}
+
+/// Finds all buffers that have the specified `authority`, returning their addresses and balances.
+async fn authority_buffers(
+    rpc_client: &RpcClient,
+    authority: Pubkey,
+) -> Result<Vec<(Pubkey, u64)>> {
+    // `UpgradeableLoaderState::Buffer { authority_address: Some(authority) }`, as encoded by
+    // `bincode`: a `u32` variant index, followed by an `Option<Pubkey>`.
+    let mut buffer_prefix = 1u32.to_le_bytes().to_vec();
+    buffer_prefix.push(1);
+    buffer_prefix.extend_from_slice(authority.as_ref());
+
+    // Only the balance is needed.
+    let accounts = rpc_client
+        .get_program_accounts_typed::<()>(
+            &bpf_loader_upgradeable::id(),
+            vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                buffer_prefix,
+            ))],
+            0,
+        )
+        .await
+        .with_context(|| format!("Failed to fetch buffers of {authority}"))?;
+
+    Ok(accounts
+        .into_iter()
+        .map(|(address, account)| (address, account.lamports))
+        .collect())
+}
@@ -0,0 +1,524 @@
+use std::{fmt::Write as _, fs, sync::Arc};
+
+use anyhow::{Context as _, Result, bail};
+use bincode::{self, serde::encode_to_vec};
+use pythnet_heisenberg::{
+    blockhash_cache::BlockhashCache,
+    keypair_ext::read_keypair_file,
+    node_address_service::{NodeAddressService, with_node_address_service},
+    output,
+    tx_sheppard::{TxOutcome, with_sheppard},
+};
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    instruction::Instruction,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer as _,
+    transaction::Transaction,
+};
+
+use crate::{
+    args::{
+        json_rpc_url_args::{get_rpc_client, websocket_url_for},
+        program::deploy::DeployArgs,
+    },
+    confirm::confirm_or_abort,
+    exit_code::check_outcomes,
+};
+
+#[cfg(test)]
+mod tests;
+
+pub async fn run(
+    DeployArgs {
+        json_rpc_url,
+        websocket_url,
+        fanout_slots,
+        via_rpc,
+        program_so,
+        program_keypair,
+        program_id,
+        payer_keypair,
+        upgrade_authority_keypair,
+        buffer_keypair,
+        max_len,
+        yes,
+    }: DeployArgs,
+) -> Result<()> {
+    let program_data = fs::read(&program_so).with_context(|| {
+        format!(
+            "Failed to read the --program-so file: {}",
+            program_so.to_string_lossy()
+        )
+    })?;
+
+    let program_keypair = program_keypair.map(read_keypair_file).transpose()?;
+    let program_id = match (&program_keypair, program_id) {
+        (Some(keypair), _) => keypair.pubkey(),
+        (None, Some(program_id)) => program_id,
+        (None, None) => unreachable!("`program` argument group requires one of the arguments"),
+    };
+    let payer = read_keypair_file(&payer_keypair)?;
+    let upgrade_authority = upgrade_authority_keypair
+        .map(read_keypair_file)
+        .transpose()?;
+    let upgrade_authority = upgrade_authority.as_ref().unwrap_or(&payer);
+    let buffer = match &buffer_keypair {
+        Some(path) => read_keypair_file(path)?,
+        None => Keypair::new(),
+    };
+    let buffer_pubkey = buffer.pubkey();
+
+    let websocket_url = websocket_url.unwrap_or_else(|| websocket_url_for(&json_rpc_url.rpc_url));
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let mode = deploy_mode(&rpc_client, program_id, &program_data).await?;
+    if mode == DeployMode::Deploy && program_keypair.is_none() {
+        bail!(
+            "Program {program_id} does not exist yet.  The initial deployment requires \
+             --program-keypair"
+        );
+    }
+
+    let existing_buffer = existing_buffer(
+        &rpc_client,
+        buffer_pubkey,
+        upgrade_authority.pubkey(),
+        &program_data,
+    )
+    .await?;
+
+    let chunk_size = write_chunk_size(&payer, upgrade_authority, buffer_pubkey);
+    let chunks = program_data
+        .chunks(chunk_size)
+        .enumerate()
+        .map(|(index, chunk)| (index * chunk_size, chunk))
+        .filter(|(offset, chunk)| match &existing_buffer {
+            None => true,
+            Some(written) => written.get(*offset..*offset + chunk.len()) != Some(*chunk),
+        })
+        .collect::<Vec<_>>();
+
+    confirm_or_abort(
+        summary(
+            mode,
+            program_id,
+            program_data.len(),
+            buffer_pubkey,
+            existing_buffer.is_some(),
+            chunks.len(),
+            payer.pubkey(),
+            upgrade_authority.pubkey(),
+        ),
+        yes,
+    )?;
+
+    let buffer_write_res = async {
+        if existing_buffer.is_none() {
+            create_buffer(
+                &rpc_client,
+                &payer,
+                &buffer,
+                upgrade_authority,
+                &program_data,
+            )
+            .await?;
+        }
+
+        write_buffer(
+            &rpc_client,
+            websocket_url.as_str(),
+            (!via_rpc).then_some(u64::from(fanout_slots)),
+            &payer,
+            upgrade_authority,
+            buffer_pubkey,
+            &chunks,
+        )
+        .await?;
+
+        verify_buffer(&rpc_client, buffer_pubkey, &program_data).await
+    }
+    .await;
+    if let Err(err) = buffer_write_res {
+        output::notice(format!(
+            "Buffer {buffer_pubkey} holds lamports that can be recovered with `program \
+             close-buffers`.  Or, use --buffer-keypair to retry writing into the same buffer."
+        ));
+        if buffer_keypair.is_none() {
+            output::notice(format!(
+                "Buffer keypair, as a JSON array: {:?}",
+                buffer.to_bytes()
+            ));
+        }
+        return Err(err);
+    }
+
+    let instructions = match mode {
+        DeployMode::Deploy => {
+            let program_lamports = rpc_client
+                .get_minimum_balance_for_rent_exemption(UpgradeableLoaderState::size_of_program())
+                .await
+                .context("Failed to get the program account rent")?;
+            bpf_loader_upgradeable::deploy_with_max_program_len(
+                &payer.pubkey(),
+                &program_id,
+                &buffer_pubkey,
+                &upgrade_authority.pubkey(),
+                program_lamports,
+                max_len.unwrap_or(program_data.len()),
+            )
+            .context("Constructing deploy instructions")?
+        }
+        DeployMode::Upgrade => vec![bpf_loader_upgradeable::upgrade(
+            &program_id,
+            &buffer_pubkey,
+            &upgrade_authority.pubkey(),
+            &payer.pubkey(),
+        )],
+    };
+
+    let mut signers = vec![&payer, upgrade_authority];
+    if mode == DeployMode::Deploy {
+        signers.extend(program_keypair.as_ref());
+    }
+    let signature = send_one(&rpc_client, &instructions, &payer, signers)
+        .await
+        .context("Deploying from the buffer")?;
+
+    output::result(
+        format!(
+            "{} program {program_id}\nSignature: {signature}",
+            match mode {
+                DeployMode::Deploy => "Deployed",
+                DeployMode::Upgrade => "Upgraded",
+            }
+        ),
+        json!({
+            "program_id": program_id.to_string(),
+            "mode": match mode {
+                DeployMode::Deploy => "deploy",
+                DeployMode::Upgrade => "upgrade",
+            },
+            "buffer": buffer_pubkey.to_string(),
+            "signature": signature.to_string(),
+        }),
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeployMode {
+    Deploy,
+    Upgrade,
+}
+
+/// Checks if the program is already deployed, and if the existing program data account has enough
+/// space for the new program.
+async fn deploy_mode(
+    rpc_client: &RpcClient,
+    program_id: Pubkey,
+    program_data: &[u8],
+) -> Result<DeployMode> {
+    let Some(program_account) = get_account(rpc_client, program_id).await? else {
+        return Ok(DeployMode::Deploy);
+    };
+
+    if program_account.owner != bpf_loader_upgradeable::id() {
+        bail!(
+            "Account {program_id} already exists, and is owned by {}, rather than the upgradeable \
+             loader",
+            program_account.owner
+        );
+    }
+    let Ok(UpgradeableLoaderState::Program {
+        programdata_address,
+    }) = bincode::serde::decode_from_slice(&program_account.data, bincode::config::legacy())
+        .map(|(state, _len)| state)
+    else {
+        bail!("Account {program_id} is not an upgradeable program");
+    };
+
+    let Some(programdata_account) = get_account(rpc_client, programdata_address).await? else {
+        bail!("Program data account {programdata_address} of program {program_id} does not exist");
+    };
+    let required = UpgradeableLoaderState::size_of_programdata(program_data.len());
+    if programdata_account.data.len() < required {
+        bail!(
+            "Program data account {programdata_address} is {} bytes long, while the new program \
+             needs {required} bytes.  Extend the program first",
+            programdata_account.data.len()
+        );
+    }
+
+    Ok(DeployMode::Upgrade)
+}
+
+/// When the buffer account already exists, checks that it can be reused for this program, and
+/// returns the data it currently holds.
+async fn existing_buffer(
+    rpc_client: &RpcClient,
+    buffer: Pubkey,
+    authority: Pubkey,
+    program_data: &[u8],
+) -> Result<Option<Vec<u8>>> {
+    let Some(account) = get_account(rpc_client, buffer).await? else {
+        return Ok(None);
+    };
+
+    if account.owner != bpf_loader_upgradeable::id() {
+        bail!(
+            "Buffer account {buffer} is owned by {}, rather than the upgradeable loader",
+            account.owner
+        );
+    }
+    let state = bincode::serde::decode_from_slice(&account.data, bincode::config::legacy())
+        .map(|(state, _len)| state);
+    let Ok(UpgradeableLoaderState::Buffer { authority_address }) = state else {
+        bail!("Account {buffer} is not a buffer");
+    };
+    if authority_address != Some(authority) {
+        bail!(
+            "Buffer {buffer} authority is {}, while the upgrade authority is {authority}",
+            authority_address.map_or_else(|| "not set".to_owned(), |address| address.to_string())
+        );
+    }
+    if account.data.len() != UpgradeableLoaderState::size_of_buffer(program_data.len()) {
+        bail!(
+            "Buffer {buffer} is {} bytes long, while the program needs a {} bytes long buffer",
+            account.data.len(),
+            UpgradeableLoaderState::size_of_buffer(program_data.len())
+        );
+    }
+
+    let mut data = account.data;
+    data.drain(..UpgradeableLoaderState::size_of_buffer_metadata());
+    Ok(Some(data))
+}
+
+async fn get_account(rpc_client: &RpcClient, address: Pubkey) -> Result<Option<Account>> {
+    rpc_client
+        .get_account_with_commitment(&address, rpc_client.commitment())
+        .await
+        .map(|response| response.value)
+        .with_context(|| format!("Failed to fetch account {address}"))
+}
+
+/// Largest number of program bytes that fit into a single write transaction.
+///
+/// The instruction data length is encoded as a compact-u16, that takes 2 bytes, rather than 1, once
+/// the data is over 127 bytes long.  The empty write is measured with a 1 byte length, so one more
+/// byte is reserved, same as the `solana` CLI does.
+fn write_chunk_size(payer: &Keypair, authority: &Keypair, buffer: Pubkey) -> usize {
+    let empty_write = Transaction::new_with_payer(
+        &[bpf_loader_upgradeable::write(
+            &buffer,
+            &authority.pubkey(),
+            0,
+            vec![],
+        )],
+        Some(&payer.pubkey()),
+    );
+    let empty_write_size = encode_to_vec(&empty_write, bincode::config::legacy())
+        .expect("Transaction serialization never fails")
+        .len();
+    PACKET_DATA_SIZE - empty_write_size - 1
+}
+
+#[allow(clippy::too_many_arguments)]
+fn summary(
+    mode: DeployMode,
+    program_id: Pubkey,
+    program_len: usize,
+    buffer: Pubkey,
+    buffer_exists: bool,
+    write_count: usize,
+    payer: Pubkey,
+    upgrade_authority: Pubkey,
+) -> String {
+    let mut res = String::new();
+    let action = match mode {
+        DeployMode::Deploy => "Deploying",
+        DeployMode::Upgrade => "Upgrading",
+    };
+    writeln!(res, "{action} program {program_id}: {program_len} bytes")
+        .expect("Writing into a String never fails");
+    writeln!(
+        res,
+        "Buffer: {buffer} ({})",
+        if buffer_exists { "existing" } else { "new" }
+    )
+    .expect("Writing into a String never fails");
+    writeln!(res, "Write transactions: {write_count}").expect("Writing into a String never fails");
+    writeln!(res, "Payer: {payer}").expect("Writing into a String never fails");
+    write!(res, "Upgrade authority: {upgrade_authority}")
+        .expect("Writing into a String never fails");
+    res
+}
+
+async fn create_buffer(
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+    buffer: &Keypair,
+    authority: &Keypair,
+    program_data: &[u8],
+) -> Result<()> {
+    let lamports = rpc_client
+        .get_minimum_balance_for_rent_exemption(UpgradeableLoaderState::size_of_buffer(
+            program_data.len(),
+        ))
+        .await
+        .context("Failed to get the buffer account rent")?;
+
+    let instructions = bpf_loader_upgradeable::create_buffer(
+        &payer.pubkey(),
+        &buffer.pubkey(),
+        &authority.pubkey(),
+        lamports,
+        program_data.len(),
+    )
+    .context("Constructing buffer creation instructions")?;
+
+    send_one(rpc_client, &instructions, payer, vec![payer, buffer])
+        .await
+        .context("Creating the buffer account")?;
+
+    Ok(())
+}
+
+/// Writes all the `chunks` into the buffer in parallel.  When `fanout_slots` is set, transactions
+/// are sent directly to the leaders.
+async fn write_buffer(
+    rpc_client: &Arc<RpcClient>,
+    websocket_url: &str,
+    fanout_slots: Option<u64>,
+    payer: &Keypair,
+    authority: &Keypair,
+    buffer: Pubkey,
+    chunks: &[(usize, &[u8])],
+) -> Result<()> {
+    if chunks.is_empty() {
+        output::notice("Buffer already holds the program");
+        return Ok(());
+    }
+
+    output::notice(format!(
+        "Writing {} chunks into buffer {buffer}...",
+        chunks.len()
+    ));
+
+    let write_all = async |node_address_service: Option<(NodeAddressService, u64)>| {
+        let sheppard = with_sheppard(rpc_client).skip_preflight(true);
+        let sheppard = match node_address_service {
+            Some((node_address_service, fanout_slots)) => {
+                sheppard.send_to_leaders(node_address_service, fanout_slots)
+            }
+            None => sheppard,
+        };
+
+        sheppard
+            .labels(
+                chunks
+                    .iter()
+                    .map(|(offset, chunk)| format!("{} bytes at offset {offset}", chunk.len())),
+            )
+            .run(chunks.iter().map(|(offset, chunk)| {
+                let offset = u32::try_from(*offset).expect("Program size fits into a u32");
+                let signers = dedup_signers(vec![payer, authority]);
+                move |blockhash_cache: &BlockhashCache| {
+                    Transaction::new_signed_with_payer(
+                        &[bpf_loader_upgradeable::write(
+                            &buffer,
+                            &authority.pubkey(),
+                            offset,
+                            chunk.to_vec(),
+                        )],
+                        Some(&payer.pubkey()),
+                        &signers,
+                        blockhash_cache.get(),
+                    )
+                }
+            }))
+            .await
+    };
+
+    let outcomes = match fanout_slots {
+        Some(fanout_slots) => {
+            // The sheppard runs its own `BlockhashCache`, so the one provided here is not used.
+            with_node_address_service(rpc_client.clone(), websocket_url)
+                .run(
+                    async |_blockhash_cache: &BlockhashCache, node_address_service| {
+                        write_all(Some((node_address_service, fanout_slots))).await
+                    },
+                )
+                .await??
+        }
+        None => write_all(None).await?,
+    };
+
+    check_outcomes(&outcomes)?;
+
+    Ok(())
+}
+
+/// Makes sure the buffer holds exactly the program data.  Write transactions may succeed, while
+/// still writing stale data, if the same buffer was used concurrently.
+async fn verify_buffer(rpc_client: &RpcClient, buffer: Pubkey, program_data: &[u8]) -> Result<()> {
+    let Some(account) = get_account(rpc_client, buffer).await? else {
+        bail!("Buffer account {buffer} does not exist");
+    };
+
+    let written = account
+        .data
+        .get(UpgradeableLoaderState::size_of_buffer_metadata()..)
+        .unwrap_or_default();
+    if written != program_data {
+        bail!("Buffer {buffer} content does not match the program data after all the writes");
+    }
+
+    Ok(())
+}
+
+/// Sends a single transaction, waiting for it to be executed.
+async fn send_one(
+    rpc_client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Keypair,
+    signers: Vec<&Keypair>,
+) -> Result<Signature> {
+    let signers = dedup_signers(signers);
+    let outcomes = with_sheppard(rpc_client)
+        .run(std::iter::once(|blockhash_cache: &BlockhashCache| {
+            Transaction::new_signed_with_payer(
+                instructions,
+                Some(&payer.pubkey()),
+                &signers,
+                blockhash_cache.get(),
+            )
+        }))
+        .await?;
+    check_outcomes(&outcomes)?;
+
+    match &outcomes[..] {
+        [TxOutcome::Success(signature)] => Ok(*signature),
+        _ => unreachable!("`check_outcomes()` checks that the only transaction succeeded"),
+    }
+}
+
+/// The payer and the upgrade authority are often the same keypair, and a transaction needs each
+/// signer to be listed only once.
+fn dedup_signers(mut signers: Vec<&Keypair>) -> Vec<&Keypair> {
+    let mut seen = vec![];
+    signers.retain(|signer| {
+        let pubkey = signer.pubkey();
+        let is_new = !seen.contains(&pubkey);
+        seen.push(pubkey);
+        is_new
+    });
+    signers
+}
@@ -0,0 +1,55 @@
+//! Checks that the write transactions built for a full chunk fit into a single packet.
+
+use bincode::serde::encode_to_vec;
+use solana_sdk::{
+    bpf_loader_upgradeable, hash::Hash, packet::PACKET_DATA_SIZE, pubkey::Pubkey,
+    signature::Keypair, signer::Signer as _, transaction::Transaction,
+};
+
+use super::write_chunk_size;
+
+/// Size of a signed write transaction that carries a full chunk.
+fn full_write_size(payer: &Keypair, authority: &Keypair) -> usize {
+    let buffer = Pubkey::new_unique();
+    let chunk_size = write_chunk_size(payer, authority, buffer);
+
+    let mut signers = vec![payer];
+    if authority.pubkey() != payer.pubkey() {
+        signers.push(authority);
+    }
+    let write = Transaction::new_signed_with_payer(
+        &[bpf_loader_upgradeable::write(
+            &buffer,
+            &authority.pubkey(),
+            u32::MAX,
+            vec![0xff; chunk_size],
+        )],
+        Some(&payer.pubkey()),
+        &signers,
+        Hash::new_unique(),
+    );
+    encode_to_vec(&write, bincode::config::legacy())
+        .expect("Transaction serialization never fails")
+        .len()
+}
+
+#[test]
+fn full_chunk_fits_into_a_packet() {
+    let payer = Keypair::new();
+    let size = full_write_size(&payer, &payer);
+    assert!(
+        size <= PACKET_DATA_SIZE,
+        "Write transaction is {size} bytes, over the {PACKET_DATA_SIZE} bytes limit"
+    );
+}
+
+#[test]
+fn full_chunk_with_a_separate_authority_fits_into_a_packet() {
+    let payer = Keypair::new();
+    let authority = Keypair::new();
+    let size = full_write_size(&payer, &authority);
+    assert!(
+        size <= PACKET_DATA_SIZE,
+        "Write transaction is {size} bytes, over the {PACKET_DATA_SIZE} bytes limit"
+    );
+}
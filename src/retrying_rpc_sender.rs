@@ -0,0 +1,69 @@
+//! [`RpcClient`] only retries requests that were rate limited by the server.  Overloaded test
+//! RPC nodes often drop connections or time out, and for most of our requests it is safe to just
+//! try again.
+//!
+//! [`RpcClient`]: solana_rpc_client::nonblocking::rpc_client::RpcClient
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use log::warn;
+use solana_rpc_client::rpc_sender::{RpcSender, RpcTransportStats};
+use solana_rpc_client_api::{
+    client_error::{ErrorKind, Result},
+    request::RpcRequest,
+};
+use tokio::time::sleep;
+
+/// Wraps another [`RpcSender`], retrying requests that failed due to transport errors.
+///
+/// Errors reported by the RPC node itself, like preflight failures, are not retried, as they are
+/// not going to change on a retry.
+pub struct RetryingRpcSender<Sender> {
+    inner: Sender,
+    retries: usize,
+    retry_delay: Duration,
+}
+
+impl<Sender> RetryingRpcSender<Sender> {
+    pub fn new(inner: Sender, retries: usize, retry_delay: Duration) -> Self {
+        Self {
+            inner,
+            retries,
+            retry_delay,
+        }
+    }
+}
+
+#[async_trait]
+impl<Sender: RpcSender + Send + Sync> RpcSender for RetryingRpcSender<Sender> {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        let mut retries_left = self.retries;
+        loop {
+            let res = self.inner.send(request, params.clone()).await;
+            match res {
+                Err(err)
+                    if retries_left > 0
+                        && matches!(err.kind(), ErrorKind::Io(_) | ErrorKind::Reqwest(_)) =>
+                {
+                    warn!("RPC request {request} failed, will retry: {err}");
+                    retries_left -= 1;
+                    sleep(self.retry_delay).await;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
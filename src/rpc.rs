@@ -0,0 +1,15 @@
+use anyhow::Result;
+
+use crate::args::rpc::Command;
+
+mod benchmark;
+mod compare;
+mod pubsub_benchmark;
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Benchmark(args) => benchmark::run(args).await,
+        Command::PubsubBenchmark(args) => pubsub_benchmark::run(args).await,
+        Command::Compare(args) => compare::run(args).await,
+    }
+}
@@ -0,0 +1,485 @@
+//! Sends read requests to an RPC node at a fixed rate, measuring how long each one takes.
+//!
+//! Requests are sent on schedule, regardless of how fast the previous ones complete, the same way
+//! independent price consumers would.  This way a slow RPC node shows up as growing latency, rather
+//! than as a lower request rate.
+
+use std::{collections::BTreeMap, mem::offset_of, time::Duration};
+
+use anyhow::{Context as _, Result, bail};
+use futures::{
+    StreamExt as _,
+    stream::{FuturesUnordered, select_all},
+};
+use pythnet_heisenberg::{
+    oracle::accounts::{AccountHeader, PC_ACCTYPE_PRICE, PC_MAGIC},
+    output,
+};
+use rand::{Rng as _, rng};
+use serde_json::{Map, Value, json};
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::{
+    config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use tokio::{
+    select,
+    signal::unix::{SignalKind, signal},
+    time::{Instant, interval, interval_at, sleep_until},
+};
+use tokio_stream::wrappers::SignalStream;
+
+use crate::{
+    args::{
+        json_rpc_url_args::get_rpc_client,
+        rpc::benchmark::{BenchmarkArgs, ReadMethod},
+    },
+    benchmark::results::{Better, RunSummary},
+};
+
+/// Used when no `--mix` is specified.
+const DEFAULT_MIX: [(ReadMethod, u32); 3] = [
+    (ReadMethod::AccountInfo, 80),
+    (ReadMethod::MultipleAccounts, 19),
+    (ReadMethod::ProgramAccounts, 1),
+];
+
+pub async fn run(
+    BenchmarkArgs {
+        json_rpc_url,
+        oracle_program_id,
+        account: accounts,
+        mix,
+        rate,
+        multiple_accounts_batch,
+        max_in_flight,
+        duration,
+        stats_update_interval,
+        results_dir,
+    }: BenchmarkArgs,
+) -> Result<()> {
+    let duration: Duration = duration.into();
+    let stats_update_interval: Duration = stats_update_interval.into();
+
+    let mix = if mix.is_empty() {
+        DEFAULT_MIX.to_vec()
+    } else {
+        mix
+    };
+    let mix = mix
+        .into_iter()
+        .filter(|(_method, weight)| *weight > 0)
+        .collect::<Vec<_>>();
+    if mix.is_empty() {
+        bail!("All the `--mix` weights are zero");
+    }
+    if !(rate.is_finite() && rate > 0.0) {
+        bail!("`--rate` must be a positive number, got: {rate}");
+    }
+    if multiple_accounts_batch == 0 {
+        bail!("`--multiple-accounts-batch` must be at least 1");
+    }
+
+    let uses = |method: ReadMethod| mix.iter().any(|(used, _weight)| *used == method);
+    if uses(ReadMethod::ProgramAccounts) && oracle_program_id.is_none() {
+        bail!("`program-accounts` requests need an `--oracle-program-id`");
+    }
+    let reads_accounts = uses(ReadMethod::AccountInfo) || uses(ReadMethod::MultipleAccounts);
+
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let commitment = rpc_client.commitment();
+
+    let accounts = if accounts.is_empty() && reads_accounts {
+        let program_id = oracle_program_id
+            .context("Either `--account` or `--oracle-program-id` must be specified")?;
+        let accounts = price_accounts(&rpc_client, &program_id).await?;
+        if accounts.is_empty() {
+            bail!("Oracle program {program_id} has no price accounts");
+        }
+        output::notice(format!(
+            "Reading {} price accounts of {program_id}",
+            accounts.len()
+        ));
+        accounts
+    } else {
+        accounts
+    };
+
+    let picker = RequestPicker {
+        mix: &mix,
+        total_weight: mix.iter().map(|(_method, weight)| weight).sum(),
+        accounts: &accounts,
+        batch: multiple_accounts_batch.min(accounts.len()),
+        program_id: oracle_program_id.unwrap_or_default(),
+    };
+    let program_accounts_config = price_accounts_config(None, commitment);
+
+    let stop_signals = select_all([
+        SignalStream::new(signal(SignalKind::interrupt()).expect("Can install a SIGINT handler")),
+        SignalStream::new(signal(SignalKind::terminate()).expect("Can install a SIGTERM handler")),
+    ]);
+    tokio::pin!(stop_signals);
+
+    let run_summary = results_dir
+        .as_ref()
+        .map(|_| RunSummary::new("rpc benchmark", chrono::Local::now(), None))
+        .transpose()?;
+    let start = Instant::now();
+    let end = sleep_until(start + duration);
+    tokio::pin!(end);
+
+    // The default `MissedTickBehavior::Burst` sends requests that were missed as soon as possible.
+    // This keeps the average rate on target, even when it is above the timer resolution.
+    let mut request_ticker = interval(Duration::from_secs_f64(1.0 / rate));
+    let mut stats_ticker = interval_at(start + stats_update_interval, stats_update_interval);
+
+    let mut in_flight = FuturesUnordered::new();
+    let mut period_stats = Stats::default();
+    let mut period_start = start;
+    let mut total_stats = Stats::default();
+
+    loop {
+        select! {
+            _ = request_ticker.tick() => {
+                if in_flight.len() >= max_in_flight {
+                    period_stats.skipped += 1;
+                    continue;
+                }
+                in_flight.push(send(&rpc_client, picker.pick(), &program_accounts_config));
+            }
+            Some((method, result)) = in_flight.next() => period_stats.record(method, result),
+            _ = stats_ticker.tick() => {
+                let now = Instant::now();
+                period_stats.print("Last period", now - period_start);
+                total_stats.merge(std::mem::take(&mut period_stats));
+                period_start = now;
+            }
+            () = &mut end => break,
+            _ = stop_signals.next() => break,
+        }
+    }
+
+    // Requests that were already sent are still counted.
+    while let Some((method, result)) = in_flight.next().await {
+        period_stats.record(method, result);
+    }
+    total_stats.merge(period_stats);
+    let elapsed = start.elapsed();
+    total_stats.print("Total", elapsed);
+
+    if let (Some(mut run_summary), Some(results_dir)) = (run_summary, &results_dir) {
+        total_stats.add_to_summary(&mut run_summary, elapsed);
+        run_summary.save(results_dir, chrono::Local::now())?;
+    }
+
+    Ok(())
+}
+
+/// Filters that select the Oracle price accounts.
+pub fn price_accounts_config(
+    data_slice: Option<UiDataSliceConfig>,
+    commitment: CommitmentConfig,
+) -> RpcProgramAccountsConfig {
+    RpcProgramAccountsConfig {
+        filters: Some(vec![
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                offset_of!(AccountHeader, magic_number),
+                PC_MAGIC.to_le_bytes().to_vec(),
+            )),
+            RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                offset_of!(AccountHeader, account_type),
+                PC_ACCTYPE_PRICE.to_le_bytes().to_vec(),
+            )),
+        ]),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice,
+            commitment: Some(commitment),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    }
+}
+
+/// Addresses of all the price accounts of the Oracle program.
+pub async fn price_accounts(rpc_client: &RpcClient, program_id: &Pubkey) -> Result<Vec<Pubkey>> {
+    // Only the addresses are needed.
+    let config = price_accounts_config(
+        Some(UiDataSliceConfig {
+            offset: 0,
+            length: 0,
+        }),
+        rpc_client.commitment(),
+    );
+    let accounts = rpc_client
+        .get_program_accounts_with_config(program_id, config)
+        .await
+        .with_context(|| format!("Failed to fetch price accounts of {program_id}"))?;
+    Ok(accounts
+        .into_iter()
+        .map(|(address, _account)| address)
+        .collect())
+}
+
+enum Request {
+    AccountInfo(Pubkey),
+    MultipleAccounts(Vec<Pubkey>),
+    ProgramAccounts(Pubkey),
+}
+
+/// Chooses methods according to their weights in the mix, and accounts uniformly.
+struct RequestPicker<'a> {
+    mix: &'a [(ReadMethod, u32)],
+    total_weight: u32,
+    accounts: &'a [Pubkey],
+    batch: usize,
+    program_id: Pubkey,
+}
+
+impl RequestPicker<'_> {
+    fn pick(&self) -> Request {
+        let mut point = rng().random_range(0..self.total_weight);
+        let method = self
+            .mix
+            .iter()
+            .find_map(|(method, weight)| {
+                if point < *weight {
+                    Some(*method)
+                } else {
+                    point -= weight;
+                    None
+                }
+            })
+            .expect("`point` is below the total weight");
+
+        match method {
+            ReadMethod::AccountInfo => {
+                let index = rng().random_range(0..self.accounts.len());
+                Request::AccountInfo(self.accounts[index])
+            }
+            ReadMethod::MultipleAccounts => {
+                let start = rng().random_range(0..self.accounts.len());
+                Request::MultipleAccounts(
+                    self.accounts
+                        .iter()
+                        .cycle()
+                        .skip(start)
+                        .take(self.batch)
+                        .copied()
+                        .collect(),
+                )
+            }
+            ReadMethod::ProgramAccounts => Request::ProgramAccounts(self.program_id),
+        }
+    }
+}
+
+/// Sends one request, returning how long it took to get a response.
+async fn send(
+    rpc_client: &RpcClient,
+    request: Request,
+    program_accounts_config: &RpcProgramAccountsConfig,
+) -> (ReadMethod, Result<Duration, String>) {
+    let commitment = rpc_client.commitment();
+    let start = Instant::now();
+    let (method, result) = match request {
+        Request::AccountInfo(address) => (
+            ReadMethod::AccountInfo,
+            rpc_client
+                .get_account_with_commitment(&address, commitment)
+                .await
+                .map(drop),
+        ),
+        Request::MultipleAccounts(addresses) => (
+            ReadMethod::MultipleAccounts,
+            rpc_client
+                .get_multiple_accounts_with_commitment(&addresses, commitment)
+                .await
+                .map(drop),
+        ),
+        Request::ProgramAccounts(program_id) => (
+            ReadMethod::ProgramAccounts,
+            rpc_client
+                .get_program_accounts_with_config(&program_id, program_accounts_config.clone())
+                .await
+                .map(drop),
+        ),
+    };
+    (
+        method,
+        result
+            .map(|()| start.elapsed())
+            .map_err(|err| err.to_string()),
+    )
+}
+
+#[derive(Default)]
+struct Stats {
+    methods: BTreeMap<ReadMethod, MethodStats>,
+    /// Requests that were not sent, as there were too many requests in flight.
+    skipped: u64,
+}
+
+#[derive(Default)]
+struct MethodStats {
+    /// Latency of every successful request, in microseconds.
+    latencies: Vec<u64>,
+    errors: u64,
+    last_error: Option<String>,
+}
+
+impl Stats {
+    fn record(&mut self, method: ReadMethod, result: Result<Duration, String>) {
+        let stats = self.methods.entry(method).or_default();
+        match result {
+            Ok(latency) => stats
+                .latencies
+                .push(u64::try_from(latency.as_micros()).unwrap_or(u64::MAX)),
+            Err(err) => {
+                stats.errors += 1;
+                stats.last_error = Some(err);
+            }
+        }
+    }
+
+    fn merge(&mut self, other: Stats) {
+        let Stats { methods, skipped } = other;
+        for (method, other) in methods {
+            let stats = self.methods.entry(method).or_default();
+            stats.latencies.extend(other.latencies);
+            stats.errors += other.errors;
+            if other.last_error.is_some() {
+                stats.last_error = other.last_error;
+            }
+        }
+        self.skipped += skipped;
+    }
+
+    fn print(&mut self, period: &str, elapsed: Duration) {
+        let Self { methods, skipped } = self;
+
+        let completed = methods
+            .values()
+            .map(|stats| stats.latencies.len() as u64 + stats.errors)
+            .sum::<u64>();
+        let elapsed_secs = elapsed.as_secs_f64();
+        let achieved_rate = completed as f64 / elapsed_secs.max(f64::EPSILON);
+
+        let mut text = format!(
+            "{period}: {completed} requests in {elapsed_secs:.1}s, {achieved_rate:.1} per \
+             second, {skipped} skipped"
+        );
+        let mut methods_json = Map::new();
+
+        for (method, stats) in methods.iter_mut() {
+            let MethodStats {
+                latencies,
+                errors,
+                last_error,
+            } = stats;
+            latencies.sort_unstable();
+
+            let requests = latencies.len() as u64 + *errors;
+            let error_rate = *errors as f64 * 100.0 / requests.max(1) as f64;
+            let latency_ms = |percentile: usize| {
+                percentile_of(latencies, percentile).map(|micros| micros as f64 / 1000.0)
+            };
+            let (p50, p90, p99) = (latency_ms(50), latency_ms(90), latency_ms(99));
+            let max = latencies.last().map(|micros| *micros as f64 / 1000.0);
+
+            let optional = |value: Option<f64>| match value {
+                Some(value) => format!("{value:.1}"),
+                None => "n/a".to_owned(),
+            };
+            text.push_str(&format!(
+                "\n  {}: {requests} requests / {errors} errors ({error_rate:.2}%) / latency ms: \
+                 p50 {} / p90 {} / p99 {} / max {}",
+                method.rpc_name(),
+                optional(p50),
+                optional(p90),
+                optional(p99),
+                optional(max),
+            ));
+            if let Some(last_error) = last_error {
+                text.push_str(&format!("\n    Last error: {last_error}"));
+            }
+
+            methods_json.insert(
+                method.rpc_name().to_owned(),
+                json!({
+                    "requests": requests,
+                    "errors": errors,
+                    "error_rate_percent": error_rate,
+                    "latency_ms": {
+                        "p50": p50,
+                        "p90": p90,
+                        "p99": p99,
+                        "max": max,
+                    },
+                    "last_error": last_error,
+                }),
+            );
+        }
+
+        output::result(
+            text,
+            json!({
+                "period": period,
+                "elapsed_secs": elapsed_secs,
+                "requests": completed,
+                "requests_per_second": achieved_rate,
+                "skipped": skipped,
+                "methods": Value::Object(methods_json),
+            }),
+        );
+    }
+
+    /// Adds the metrics of the whole run to the `--results-dir` summary.  Expects the latencies to
+    /// be sorted by a preceding [`Stats::print()`].
+    fn add_to_summary(&self, run_summary: &mut RunSummary, elapsed: Duration) {
+        let Self { methods, .. } = self;
+
+        let completed = methods
+            .values()
+            .map(|stats| stats.latencies.len() as u64 + stats.errors)
+            .sum::<u64>();
+        run_summary.add(
+            "requests_per_second",
+            completed as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+            Better::Higher,
+        );
+
+        for (
+            method,
+            MethodStats {
+                latencies, errors, ..
+            },
+        ) in methods
+        {
+            let name = method.rpc_name();
+            let requests = latencies.len() as u64 + *errors;
+            run_summary.add(
+                format!("{name}.error_rate_percent"),
+                *errors as f64 * 100.0 / requests.max(1) as f64,
+                Better::Lower,
+            );
+            for percentile in [50, 90, 99] {
+                if let Some(micros) = percentile_of(latencies, percentile) {
+                    run_summary.add(
+                        format!("{name}.latency_p{percentile}_ms"),
+                        micros as f64 / 1000.0,
+                        Better::Lower,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Nearest rank percentile of a sorted list of values.  Returns `None` for an empty list.
+pub fn percentile_of(sorted: &[u64], percentile: usize) -> Option<u64> {
+    let rank = (percentile * sorted.len()).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied()
+}
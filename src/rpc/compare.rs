@@ -0,0 +1,294 @@
+//! Sends the same queries to multiple RPC nodes, and compares the answers against the first one.
+//!
+//! Queries to different nodes run in parallel, but they are not atomic.  Nodes that process slots
+//! at slightly different times may return different account states, which is why account
+//! differences are only considered a divergence when both nodes report the state for the same
+//! slot.
+
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result, bail};
+use futures::future::join_all;
+use pythnet_heisenberg::output;
+use reqwest::Url;
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::request::MAX_MULTIPLE_ACCOUNTS;
+use solana_sdk::{account::Account, clock::Slot, hash::Hash, pubkey::Pubkey};
+
+use crate::args::{
+    json_rpc_url_args::{JsonRpcUrlArgs, get_rpc_client},
+    rpc::compare::CompareArgs,
+};
+
+pub async fn run(
+    CompareArgs {
+        json_rpc_url,
+        other_rpc_url: other_rpc_urls,
+        account: accounts,
+        max_slot_lag,
+    }: CompareArgs,
+) -> Result<()> {
+    let mut endpoints = vec![(
+        json_rpc_url.rpc_url.clone(),
+        get_rpc_client(json_rpc_url.clone()),
+    )];
+    for rpc_url in other_rpc_urls {
+        let rpc_client = get_rpc_client(JsonRpcUrlArgs {
+            rpc_url: rpc_url.clone(),
+            ..json_rpc_url.clone()
+        });
+        endpoints.push((rpc_url, rpc_client));
+    }
+
+    let snapshots = join_all(
+        endpoints
+            .iter()
+            .map(|(_rpc_url, rpc_client)| Snapshot::take(rpc_client, &accounts)),
+    )
+    .await;
+
+    let highest_slot = snapshots
+        .iter()
+        .filter_map(|snapshot| snapshot.as_ref().ok())
+        .map(|snapshot| snapshot.slot)
+        .max();
+    let reference = snapshots[0].as_ref().ok();
+
+    let mut problems = vec![];
+    for ((rpc_url, _rpc_client), snapshot) in endpoints.iter().zip(&snapshots) {
+        let snapshot = match snapshot {
+            Ok(snapshot) => snapshot,
+            Err(err) => {
+                output::result(
+                    format!("{rpc_url}: Failed: {err:#}"),
+                    json!({
+                        "rpc_url": rpc_url.as_str(),
+                        "error": format!("{err:#}"),
+                    }),
+                );
+                problems.push(format!("{rpc_url} failed to answer"));
+                continue;
+            }
+        };
+
+        let lag = highest_slot.unwrap_or(snapshot.slot) - snapshot.slot;
+        if lag > max_slot_lag {
+            problems.push(format!("{rpc_url} is {lag} slots behind"));
+        }
+
+        let blockhash_matches = reference.and_then(|reference| {
+            (reference.block_height == snapshot.block_height)
+                .then(|| reference.blockhash == snapshot.blockhash)
+        });
+        if blockhash_matches == Some(false) {
+            problems.push(format!(
+                "{rpc_url} has a different blockhash at block height {}",
+                snapshot.block_height
+            ));
+        }
+
+        let differences = reference
+            .map(|reference| AccountDifference::find(&accounts, reference, snapshot))
+            .unwrap_or_default();
+        let diverged = differences
+            .iter()
+            .filter(|difference| difference.same_slot)
+            .count();
+        if diverged > 0 {
+            problems.push(format!(
+                "{rpc_url} has {diverged} accounts that differ at the same slot"
+            ));
+        }
+
+        snapshot.print(rpc_url, lag, blockhash_matches, &differences);
+    }
+
+    if !problems.is_empty() {
+        bail!("Endpoints diverged:\n  {}", problems.join("\n  "));
+    }
+
+    Ok(())
+}
+
+/// Answers of one RPC node.
+struct Snapshot {
+    slot: Slot,
+    block_height: u64,
+    blockhash: Hash,
+    /// Slot the account states were reported for, by the last `getMultipleAccounts` request.
+    accounts_slot: Option<Slot>,
+    accounts: Vec<Option<Account>>,
+}
+
+impl Snapshot {
+    async fn take(rpc_client: &Arc<RpcClient>, addresses: &[Pubkey]) -> Result<Self> {
+        let commitment = rpc_client.commitment();
+        let slot = rpc_client
+            .get_slot_with_commitment(commitment)
+            .await
+            .context("Failed to get the slot")?;
+        let block_height = rpc_client
+            .get_block_height_with_commitment(commitment)
+            .await
+            .context("Failed to get the block height")?;
+        let (blockhash, _last_valid_block_height) = rpc_client
+            .get_latest_blockhash_with_commitment(commitment)
+            .await
+            .context("Failed to get the latest blockhash")?;
+
+        let mut accounts_slot = None;
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let response = rpc_client
+                .get_multiple_accounts_with_commitment(chunk, commitment)
+                .await
+                .context("Failed to get the accounts")?;
+            accounts_slot = Some(response.context.slot);
+            accounts.extend(response.value);
+        }
+
+        Ok(Self {
+            slot,
+            block_height,
+            blockhash,
+            accounts_slot,
+            accounts,
+        })
+    }
+
+    fn print(
+        &self,
+        rpc_url: &Url,
+        lag: Slot,
+        blockhash_matches: Option<bool>,
+        differences: &[AccountDifference],
+    ) {
+        let Self {
+            slot,
+            block_height,
+            blockhash,
+            accounts_slot,
+            accounts,
+        } = self;
+
+        let blockhash_status = match blockhash_matches {
+            Some(true) => "matches",
+            Some(false) => "DIFFERS",
+            None => "at a different block height",
+        };
+        let mut text = format!(
+            "{rpc_url}: slot {slot}, {lag} slots behind\n  \
+               Block height: {block_height}\n  \
+               Blockhash: {blockhash} ({blockhash_status})\n  \
+               Accounts: {} of {} differ",
+            differences.len(),
+            accounts.len(),
+        );
+        if let Some(accounts_slot) = accounts_slot {
+            text.push_str(&format!(", as of slot {accounts_slot}"));
+        }
+        for difference in differences {
+            text.push_str(&format!("\n    {}", difference.describe()));
+        }
+
+        output::result(
+            text,
+            json!({
+                "rpc_url": rpc_url.as_str(),
+                "slot": slot,
+                "slot_lag": lag,
+                "block_height": block_height,
+                "blockhash": blockhash.to_string(),
+                "blockhash_matches": blockhash_matches,
+                "accounts_slot": accounts_slot,
+                "differing_accounts": differences
+                    .iter()
+                    .map(|difference| json!({
+                        "address": difference.address.to_string(),
+                        "same_slot": difference.same_slot,
+                        "difference": difference.describe(),
+                    }))
+                    .collect::<Vec<_>>(),
+            }),
+        );
+    }
+}
+
+/// An account that has a different state on an endpoint, compared to the reference endpoint.
+struct AccountDifference {
+    address: Pubkey,
+    reference: Option<Account>,
+    other: Option<Account>,
+    /// Both endpoints reported the state as of the same slot, so the difference is a divergence,
+    /// rather than a lag.
+    same_slot: bool,
+}
+
+impl AccountDifference {
+    fn find(addresses: &[Pubkey], reference: &Snapshot, other: &Snapshot) -> Vec<Self> {
+        let same_slot = reference.accounts_slot == other.accounts_slot;
+        addresses
+            .iter()
+            .zip(reference.accounts.iter().zip(&other.accounts))
+            .filter(|(_address, (reference, other))| reference != other)
+            .map(|(address, (reference, other))| Self {
+                address: *address,
+                reference: reference.clone(),
+                other: other.clone(),
+                same_slot,
+            })
+            .collect()
+    }
+
+    fn describe(&self) -> String {
+        let Self {
+            address,
+            reference,
+            other,
+            same_slot,
+        } = self;
+        let what = match (reference, other) {
+            (Some(_), None) => "missing".to_owned(),
+            (None, Some(_)) => "exists, while missing on the reference endpoint".to_owned(),
+            (None, None) => "missing on both endpoints".to_owned(),
+            (Some(reference), Some(other)) => {
+                let mut fields = vec![];
+                if reference.lamports != other.lamports {
+                    fields.push(format!(
+                        "lamports {} vs {}",
+                        other.lamports, reference.lamports
+                    ));
+                }
+                if reference.owner != other.owner {
+                    fields.push(format!("owner {} vs {}", other.owner, reference.owner));
+                }
+                if reference.data != other.data {
+                    let first_difference = reference
+                        .data
+                        .iter()
+                        .zip(&other.data)
+                        .position(|(reference, other)| reference != other)
+                        .unwrap_or(reference.data.len().min(other.data.len()));
+                    fields.push(format!(
+                        "data differs starting at byte {first_difference}, {} vs {} bytes",
+                        other.data.len(),
+                        reference.data.len()
+                    ));
+                }
+                if reference.executable != other.executable
+                    || reference.rent_epoch != other.rent_epoch
+                {
+                    fields.push("metadata differs".to_owned());
+                }
+                fields.join(", ")
+            }
+        };
+        let when = if *same_slot {
+            "at the same slot"
+        } else {
+            "at a different slot"
+        };
+        format!("{address}: {what} ({when})")
+    }
+}
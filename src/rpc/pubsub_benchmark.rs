@@ -0,0 +1,479 @@
+//! Opens many subscriptions to the price accounts, and measures how the pubsub endpoint delivers
+//! their notifications.
+//!
+//! Latency is measured from the moment the `slotSubscribe` notification for the slot of an update
+//! arrives, so it includes the time it takes for the slot to reach the requested commitment.
+//! Latencies are reported in milliseconds and in slots, using the observed slot duration.  An
+//! update is a new value of an account in a slot.  Every subscriber of the account is expected to
+//! receive it.  Subscribers that did not receive an update within the `--drop-timeout` of the first
+//! subscriber that did, count as dropped notifications.
+
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr as _,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use futures::{
+    Stream, StreamExt as _, future,
+    future::try_join_all,
+    stream::{BoxStream, select_all},
+};
+use log::warn;
+use pythnet_heisenberg::{output, slot_clock::SlotClock};
+use serde_json::{Value, json};
+use solana_account_decoder::UiAccountEncoding;
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+use solana_rpc_client_api::config::RpcAccountInfoConfig;
+use solana_sdk::{clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use tokio::{
+    select,
+    signal::unix::{SignalKind, signal},
+    time::{Instant, interval_at, sleep_until},
+};
+use tokio_stream::wrappers::SignalStream;
+
+use crate::args::{
+    json_rpc_url_args::{get_rpc_client, websocket_url_for},
+    rpc::pubsub_benchmark::PubsubBenchmarkArgs,
+};
+
+use super::benchmark::{percentile_of, price_accounts, price_accounts_config};
+
+pub async fn run(
+    PubsubBenchmarkArgs {
+        json_rpc_url,
+        websocket_url,
+        oracle_program_id,
+        account: accounts,
+        account_subscriptions,
+        program_subscriptions,
+        connections,
+        drop_timeout,
+        duration,
+        stats_update_interval,
+    }: PubsubBenchmarkArgs,
+) -> Result<()> {
+    let drop_timeout: Duration = drop_timeout.into();
+    let duration: Duration = duration.into();
+    let stats_update_interval: Duration = stats_update_interval.into();
+
+    if account_subscriptions == 0 && program_subscriptions == 0 {
+        bail!("At least one of `--account-subscriptions` or `--program-subscriptions` must be set");
+    }
+    if connections == 0 {
+        bail!("`--connections` must be at least 1");
+    }
+    if program_subscriptions > 0 && oracle_program_id.is_none() {
+        bail!("`--program-subscriptions` need an `--oracle-program-id`");
+    }
+
+    let websocket_url = websocket_url.unwrap_or_else(|| websocket_url_for(&json_rpc_url.rpc_url));
+    let commitment = CommitmentConfig {
+        commitment: json_rpc_url.commitment,
+    };
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let accounts = if accounts.is_empty() && account_subscriptions > 0 {
+        let program_id = oracle_program_id
+            .context("Either `--account` or `--oracle-program-id` must be specified")?;
+        let accounts = price_accounts(&rpc_client, &program_id).await?;
+        if accounts.is_empty() {
+            bail!("Oracle program {program_id} has no price accounts");
+        }
+        accounts
+    } else {
+        accounts
+    };
+
+    let clients = try_join_all((0..connections).map(|_| PubsubClient::new(websocket_url.as_str())))
+        .await
+        .with_context(|| format!("Failed to connect to {websocket_url}"))?;
+
+    let account_config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(commitment),
+        ..RpcAccountInfoConfig::default()
+    };
+
+    let mut streams: Vec<BoxStream<'_, Notification>> = vec![];
+    let mut unsubscribes = vec![];
+    let mut subscribers = HashMap::<Pubkey, usize>::new();
+    let mut failed = 0;
+
+    for subscription in 0..account_subscriptions {
+        let account = accounts[subscription % accounts.len()];
+        let client = &clients[subscription % connections];
+        match client
+            .account_subscribe(&account, Some(account_config.clone()))
+            .await
+        {
+            Ok((stream, unsubscribe)) => {
+                streams.push(
+                    stream
+                        .map(move |response| Notification {
+                            subscription,
+                            account,
+                            slot: response.context.slot,
+                        })
+                        .boxed(),
+                );
+                unsubscribes.push(unsubscribe);
+                *subscribers.entry(account).or_default() += 1;
+            }
+            Err(err) => {
+                warn!("Failed to subscribe to {account}: {err}");
+                failed += 1;
+            }
+        }
+    }
+
+    let mut program_subscribers = 0;
+    if let Some(program_id) = oracle_program_id {
+        for index in 0..program_subscriptions {
+            let subscription = account_subscriptions + index;
+            let client = &clients[subscription % connections];
+            let mut config = price_accounts_config(None, commitment);
+            config.account_config = account_config.clone();
+            match client.program_subscribe(&program_id, Some(config)).await {
+                Ok((stream, unsubscribe)) => {
+                    streams.push(
+                        stream
+                            .filter_map(move |response| {
+                                let slot = response.context.slot;
+                                let res = match Pubkey::from_str(&response.value.pubkey) {
+                                    Ok(account) => Some(Notification {
+                                        subscription,
+                                        account,
+                                        slot,
+                                    }),
+                                    Err(err) => {
+                                        warn!("Notification for an invalid pubkey: {err}");
+                                        None
+                                    }
+                                };
+                                future::ready(res)
+                            })
+                            .boxed(),
+                    );
+                    unsubscribes.push(unsubscribe);
+                    program_subscribers += 1;
+                }
+                Err(err) => {
+                    warn!("Failed to subscribe to accounts of {program_id}: {err}");
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    let opened = unsubscribes.len();
+    if opened == 0 {
+        bail!("Failed to open any subscriptions");
+    }
+    output::notice(format!(
+        "Opened {opened} out of {} subscriptions over {connections} connections",
+        opened + failed
+    ));
+
+    let (slots, slots_unsubscribe) = clients[0]
+        .slot_subscribe()
+        .await
+        .context("Failed to subscribe to slot notifications")?;
+
+    let mut tracker = Tracker::new(subscribers, program_subscribers);
+    let res = track_until_stopped(
+        select_all(streams),
+        slots.map(|slot_info| slot_info.slot),
+        &mut tracker,
+        drop_timeout,
+        duration,
+        stats_update_interval,
+    )
+    .await;
+
+    slots_unsubscribe().await;
+    for unsubscribe in unsubscribes {
+        unsubscribe().await;
+    }
+    for client in clients {
+        if let Err(err) = client.shutdown().await {
+            warn!("Failed to disconnect the pubsub client: {err}");
+        }
+    }
+
+    res
+}
+
+/// An account notification received by one of the subscriptions.
+struct Notification {
+    subscription: usize,
+    account: Pubkey,
+    slot: Slot,
+}
+
+async fn track_until_stopped(
+    notifications: impl Stream<Item = Notification>,
+    slots: impl Stream<Item = Slot>,
+    tracker: &mut Tracker,
+    drop_timeout: Duration,
+    duration: Duration,
+    stats_update_interval: Duration,
+) -> Result<()> {
+    tokio::pin!(notifications);
+    tokio::pin!(slots);
+
+    let stop_signals = select_all([
+        SignalStream::new(signal(SignalKind::interrupt()).expect("Can install a SIGINT handler")),
+        SignalStream::new(signal(SignalKind::terminate()).expect("Can install a SIGTERM handler")),
+    ]);
+    tokio::pin!(stop_signals);
+
+    let start = Instant::now();
+    let end = sleep_until(start + duration);
+    tokio::pin!(end);
+    let mut stats_ticker = interval_at(start + stats_update_interval, stats_update_interval);
+    let mut period_start = start;
+    let mut total = PeriodStats::default();
+
+    let res = loop {
+        select! {
+            notification = notifications.next() => {
+                let Some(notification) = notification else {
+                    break Err(anyhow!("Server closed all the subscriptions"));
+                };
+                tracker.record(notification, Instant::now());
+            }
+            slot = slots.next() => {
+                let Some(slot) = slot else {
+                    break Err(anyhow!("Server closed the slot subscription"));
+                };
+                tracker.slot_clock.record(slot, Instant::now());
+            }
+            _ = stats_ticker.tick() => {
+                let now = Instant::now();
+                tracker.finalize(now, drop_timeout);
+                let mut period = std::mem::take(&mut tracker.stats);
+                period.print("Last period", now - period_start, &tracker.slot_clock);
+                total.merge(period);
+                period_start = now;
+            }
+            () = &mut end => break Ok(()),
+            _ = stop_signals.next() => break Ok(()),
+        }
+    };
+
+    // Updates that are more recent than the `drop_timeout` are not counted, as their
+    // notifications might still be in flight.
+    tracker.finalize(Instant::now(), drop_timeout);
+    total.merge(std::mem::take(&mut tracker.stats));
+    total.print("Total", start.elapsed(), &tracker.slot_clock);
+
+    res
+}
+
+/// Matches notifications of the same update received by different subscriptions.
+struct Tracker {
+    /// Number of `accountSubscribe` subscriptions for each account.
+    subscribers: HashMap<Pubkey, usize>,
+    /// `programSubscribe` subscriptions receive updates of every price account.
+    program_subscribers: usize,
+    slot_clock: SlotClock,
+    pending: HashMap<(Pubkey, Slot), PendingUpdate>,
+    stats: PeriodStats,
+}
+
+struct PendingUpdate {
+    first_seen: Instant,
+    received_by: HashSet<usize>,
+}
+
+impl Tracker {
+    fn new(subscribers: HashMap<Pubkey, usize>, program_subscribers: usize) -> Self {
+        Self {
+            subscribers,
+            program_subscribers,
+            slot_clock: SlotClock::new(),
+            pending: HashMap::new(),
+            stats: PeriodStats::default(),
+        }
+    }
+
+    fn record(
+        &mut self,
+        Notification {
+            subscription,
+            account,
+            slot,
+        }: Notification,
+        now: Instant,
+    ) {
+        let stats = &mut self.stats;
+        stats.notifications += 1;
+
+        if let Some(slot_start) = self.slot_clock.slot_start(slot) {
+            stats.latencies.push(micros(now - slot_start));
+        }
+
+        let update = self
+            .pending
+            .entry((account, slot))
+            .or_insert_with(|| PendingUpdate {
+                first_seen: now,
+                received_by: HashSet::new(),
+            });
+        if !update.received_by.insert(subscription) {
+            stats.repeated += 1;
+        }
+        stats.delays.push(micros(now - update.first_seen));
+    }
+
+    /// Counts dropped notifications for all the updates first seen more than `drop_timeout` ago.
+    fn finalize(&mut self, now: Instant, drop_timeout: Duration) {
+        let Self {
+            subscribers,
+            program_subscribers,
+            pending,
+            stats,
+            ..
+        } = self;
+
+        pending.retain(|(account, _slot), update| {
+            if now - update.first_seen < drop_timeout {
+                return true;
+            }
+            let expected = subscribers.get(account).copied().unwrap_or(0) + *program_subscribers;
+            let received = update.received_by.len();
+            stats.updates += 1;
+            stats.expected += expected as u64;
+            stats.dropped += expected.saturating_sub(received) as u64;
+            false
+        });
+    }
+}
+
+fn micros(duration: Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
+}
+
+#[derive(Default)]
+struct PeriodStats {
+    notifications: u64,
+    /// Time from the slot notification to the account notification, in microseconds.
+    latencies: Vec<u64>,
+    /// Time from the first notification of the same update, in microseconds.
+    delays: Vec<u64>,
+    /// Notifications for an update that the subscription has already received.
+    repeated: u64,
+    /// Updates that were finalized.
+    updates: u64,
+    /// Notifications expected for the finalized updates.
+    expected: u64,
+    /// Expected notifications that were not received.
+    dropped: u64,
+}
+
+impl PeriodStats {
+    fn merge(&mut self, other: PeriodStats) {
+        let PeriodStats {
+            notifications,
+            latencies,
+            delays,
+            repeated,
+            updates,
+            expected,
+            dropped,
+        } = other;
+        self.notifications += notifications;
+        self.latencies.extend(latencies);
+        self.delays.extend(delays);
+        self.repeated += repeated;
+        self.updates += updates;
+        self.expected += expected;
+        self.dropped += dropped;
+    }
+
+    fn print(&mut self, period: &str, elapsed: Duration, slot_clock: &SlotClock) {
+        let Self {
+            notifications,
+            latencies,
+            delays,
+            repeated,
+            updates,
+            expected,
+            dropped,
+        } = self;
+        latencies.sort_unstable();
+        delays.sort_unstable();
+
+        let elapsed_secs = elapsed.as_secs_f64();
+        let rate = *notifications as f64 / elapsed_secs.max(f64::EPSILON);
+        let drop_rate = *dropped as f64 * 100.0 / (*expected).max(1) as f64;
+
+        let (latency_text, latency_json, latency_slots_json) = distribution(latencies, slot_clock);
+        let (delay_text, delay_json, delay_slots_json) = distribution(delays, slot_clock);
+
+        output::result(
+            format!(
+                "{period}: {notifications} notifications in {elapsed_secs:.1}s, {rate:.1} per \
+                 second\n  \
+                   Latency after the slot notification: {latency_text}\n  \
+                   Delay behind the first subscriber: {delay_text}\n  \
+                   Updates: {updates} / expected notifications: {expected} / dropped: {dropped} \
+                   ({drop_rate:.2}%) / repeated: {repeated}"
+            ),
+            json!({
+                "period": period,
+                "elapsed_secs": elapsed_secs,
+                "notifications": notifications,
+                "notifications_per_second": rate,
+                "latency_ms": latency_json,
+                "latency_slots": latency_slots_json,
+                "delay_ms": delay_json,
+                "delay_slots": delay_slots_json,
+                "updates": updates,
+                "expected": expected,
+                "dropped": dropped,
+                "drop_rate_percent": drop_rate,
+                "repeated": repeated,
+            }),
+        );
+    }
+}
+
+/// Text and JSON descriptions of a sorted list of durations, in microseconds.  JSON describes
+/// them in milliseconds, and in slots, once the `slot_clock` knows the slot duration.
+fn distribution(sorted: &[u64], slot_clock: &SlotClock) -> (String, Value, Value) {
+    let at = |percentile: usize| percentile_of(sorted, percentile).map(Duration::from_micros);
+    let (p50, p90, p99) = (at(50), at(90), at(99));
+    let max = sorted.last().copied().map(Duration::from_micros);
+
+    let describe = |value: Option<Duration>| match value {
+        Some(value) => slot_clock.describe(value),
+        None => "n/a".to_owned(),
+    };
+    let ms = |value: Option<Duration>| value.map(|value| value.as_secs_f64() * 1000.0);
+    let slots = |value: Option<Duration>| value.and_then(|value| slot_clock.to_slots(value));
+    (
+        format!(
+            "p50 {} / p90 {} / p99 {} / max {}",
+            describe(p50),
+            describe(p90),
+            describe(p99),
+            describe(max),
+        ),
+        json!({
+            "p50": ms(p50),
+            "p90": ms(p90),
+            "p99": ms(p99),
+            "max": ms(max),
+        }),
+        json!({
+            "p50": slots(p50),
+            "p90": slots(p90),
+            "p99": slots(p99),
+            "max": slots(max),
+        }),
+    )
+}
@@ -1,14 +1,65 @@
 //! Commonly used functionality related to the `rpc_client`.
 
-use anyhow::{Context as _, Result};
+use std::{mem::size_of, str::FromStr as _, time::Duration};
+
+use anyhow::{Context as _, Result, bail};
+use bytemuck::{Pod, pod_read_unaligned};
+use futures::future::try_join_all;
+use serde_json::json;
+use solana_account_decoder::{UiAccount, UiAccountEncoding, UiDataSliceConfig};
 use solana_program::pubkey::Pubkey;
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
-use solana_rpc_client_api::config::RpcSendTransactionConfig;
+use solana_rpc_client_api::{
+    config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig},
+    filter::RpcFilterType,
+    request::{MAX_MULTIPLE_ACCOUNTS, RpcRequest},
+    response::{OptionalContext, Response, RpcKeyedAccount},
+};
 use solana_sdk::{
-    commitment_config::CommitmentConfig, instruction::Instruction, signature::Signature,
-    signer::signers::Signers, transaction::Transaction,
+    account::Account,
+    clock::{Epoch, Slot},
+    epoch_info::EpochInfo,
+    hash::Hash,
+    instruction::Instruction,
+    message::Message,
+    signature::Signature,
+    signer::signers::Signers,
+};
+use tokio::time::sleep;
+
+use crate::{
+    blockhash_cache::BlockhashCache, cluster_rpc::ClusterRpc, dry_run,
+    keypair_ext::try_new_signed_with_payer,
 };
 
+/// A point in the cluster history to wait for, using [`RpcClientExt::wait_for()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitTarget {
+    /// First slot of the epoch has been reached.
+    Epoch(Epoch),
+    Slot(Slot),
+}
+
+impl WaitTarget {
+    pub fn is_reached(&self, epoch_info: &EpochInfo) -> bool {
+        match *self {
+            WaitTarget::Epoch(epoch) => epoch_info.epoch >= epoch,
+            WaitTarget::Slot(slot) => epoch_info.absolute_slot >= slot,
+        }
+    }
+}
+
+/// An account fetched by [`RpcClientExt::get_accounts_chunked()`] or
+/// [`RpcClientExt::get_program_accounts_typed()`], with a part of its data decoded as a `T`.
+#[derive(Debug, Clone, Copy)]
+pub struct TypedAccount<T> {
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub data: T,
+}
+
+// Callers `.await` these futures directly, so there is no need to require them to be `Send`.
+#[allow(async_fn_in_trait)]
 pub trait RpcClientExt {
     async fn send_with_payer_latest_blockhash_with_spinner<SigningKeyparis: Signers + ?Sized>(
         &self,
@@ -26,6 +77,103 @@ pub trait RpcClientExt {
         signing_keypairs: &SigningKeyparis,
         config: RpcSendTransactionConfig,
     ) -> Result<Signature>;
+
+    /// Signs a transaction with a blockhash from the `blockhash_cache`, sends it, and waits for it
+    /// to reach the client commitment level.
+    ///
+    /// Unlike [`send_with_payer_latest_blockhash_with_spinner()`], does not fetch a blockhash, and
+    /// does not show a spinner, so it is suitable for sending transactions in parallel.
+    ///
+    /// [`send_with_payer_latest_blockhash_with_spinner()`]:
+    ///     RpcClientExt::send_with_payer_latest_blockhash_with_spinner
+    async fn send_with_payer_cached_blockhash<SigningKeyparis: Signers + ?Sized>(
+        &self,
+        instructions: &[Instruction],
+        payer: Option<&Pubkey>,
+        signing_keypairs: &SigningKeyparis,
+        blockhash_cache: &BlockhashCache,
+    ) -> Result<Signature>;
+
+    /// Fee the cluster currently charges for every transaction signature, in lamports.
+    ///
+    /// Useful for estimating costs of a batch of transactions, without building any of them.
+    async fn get_lamports_per_signature(&self) -> Result<u64>;
+
+    /// Latest blockhash to be used in new transactions.
+    ///
+    /// Uses the client commitment, but never goes below `confirmed`.  Blockhashes of processed
+    /// blocks might end up on a fork that is dropped, causing "Blockhash not found" errors.
+    async fn get_latest_blockhash_for_tx(&self) -> Result<Hash>;
+
+    /// Fetches `addresses` with as many `getMultipleAccounts` requests as necessary, decoding the
+    /// first `size_of::<T>()` bytes of every account data as a `T`.  Only these bytes are
+    /// transferred, so use `()` when only the balances are needed.
+    ///
+    /// Returns an entry for every address, in the same order, with `None` for missing accounts.
+    /// Accounts with less data than a `T` needs are reported as errors.
+    async fn get_accounts_chunked<T: Pod>(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<Option<TypedAccount<T>>>>;
+
+    /// Fetches `addresses` with as many `getMultipleAccounts` requests as necessary, transferring
+    /// the full account data.  Use [`get_accounts_chunked()`] when only a prefix of the data is
+    /// needed.
+    ///
+    /// Returns an entry for every address, in the same order, with `None` for missing accounts,
+    /// along with the latest slot the chunks were read at.  The slot is `None` when there are no
+    /// `addresses`.
+    ///
+    /// [`get_accounts_chunked()`]: RpcClientExt::get_accounts_chunked
+    async fn get_multiple_accounts_chunked(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<(Option<Slot>, Vec<Option<Account>>)>;
+
+    /// Data sizes of the `addresses` accounts, in bytes, with as many `getMultipleAccounts` requests
+    /// as necessary.  Returns an entry for every address, in the same order, with `None` for
+    /// missing accounts.
+    ///
+    /// Account data is not transferred, unless the node does not report account sizes, which older
+    /// nodes do not.
+    async fn get_account_data_lens(&self, addresses: &[Pubkey]) -> Result<Vec<Option<usize>>>;
+
+    /// Fetches all the accounts owned by the `program_id` that match all of the `filters`, with a
+    /// single `getProgramAccounts` request.  `size_of::<T>()` bytes of every account data,
+    /// starting at the `data_offset`, are decoded as a `T`.  Only these bytes are transferred, so
+    /// use `()` when only the addresses or the balances are needed.
+    ///
+    /// Accounts with less data than a `T` needs are reported as errors, so use a
+    /// [`RpcFilterType::DataSize`] filter if the program owns accounts of different types.
+    async fn get_program_accounts_typed<T: Pod>(
+        &self,
+        program_id: &Pubkey,
+        filters: Vec<RpcFilterType>,
+        data_offset: usize,
+    ) -> Result<Vec<(Pubkey, TypedAccount<T>)>>;
+
+    /// Fetches all the accounts owned by the `program_id` that match all of the `filters`, with a
+    /// single `getProgramAccounts` request, and returns them along with the slot they were read
+    /// at.  Only the `data_slice` of every account data is transferred.
+    ///
+    /// With a `min_context_slot`, the request fails if the node has not reached that slot yet.
+    async fn get_program_accounts_with_slot(
+        &self,
+        program_id: &Pubkey,
+        filters: Vec<RpcFilterType>,
+        data_slice: UiDataSliceConfig,
+        min_context_slot: Option<Slot>,
+    ) -> Result<(Slot, Vec<(Pubkey, Account)>)>;
+
+    /// Polls the cluster every `poll_interval` until the `target` is reached, using the client
+    /// commitment.  `on_progress` is called with every [`EpochInfo`] received, including the last
+    /// one, that is also returned.
+    async fn wait_for(
+        &self,
+        target: WaitTarget,
+        poll_interval: Duration,
+        on_progress: impl FnMut(&EpochInfo),
+    ) -> Result<EpochInfo>;
 }
 
 impl RpcClientExt for RpcClient {
@@ -53,20 +201,20 @@ impl RpcClientExt for RpcClient {
         signing_keypairs: &SigningKeyparis,
         config: RpcSendTransactionConfig,
     ) -> Result<Signature> {
-        // When the RpcClient is configured with a commitment that is not Finalized, I often see
-        // "Blockhash not found" errors.  Considering that we run transactions right away, there
-        // seems to be no downside in using slightly older block hashes as reference points.
-        let (latest_blockhash, _) = self
-            .get_latest_blockhash_with_commitment(CommitmentConfig::finalized())
-            .await
-            .context("Getting a blockhash from the cluster")?;
+        if dry_run::is_enabled() {
+            let transaction =
+                try_new_signed_with_payer(instructions, payer, signing_keypairs, Hash::default())
+                    .context("Failed to sign the transaction")?;
+            return Err(dry_run::describe(self, &[transaction], None, &[])
+                .await
+                .into());
+        }
 
-        let transaction = Transaction::new_signed_with_payer(
-            instructions,
-            payer,
-            signing_keypairs,
-            latest_blockhash,
-        );
+        let latest_blockhash = self.get_latest_blockhash_for_tx().await?;
+
+        let transaction =
+            try_new_signed_with_payer(instructions, payer, signing_keypairs, latest_blockhash)
+                .context("Failed to sign the transaction")?;
 
         self.send_and_confirm_transaction_with_spinner_and_config(
             &transaction,
@@ -76,4 +224,306 @@ impl RpcClientExt for RpcClient {
         .await
         .context("Transaction execution failed")
     }
+
+    async fn send_with_payer_cached_blockhash<SigningKeyparis: Signers + ?Sized>(
+        &self,
+        instructions: &[Instruction],
+        payer: Option<&Pubkey>,
+        signing_keypairs: &SigningKeyparis,
+        blockhash_cache: &BlockhashCache,
+    ) -> Result<Signature> {
+        let transaction =
+            try_new_signed_with_payer(instructions, payer, signing_keypairs, blockhash_cache.get())
+                .context("Failed to sign the transaction")?;
+
+        if dry_run::is_enabled() {
+            return Err(dry_run::describe(self, &[transaction], None, &[])
+                .await
+                .into());
+        }
+
+        self.send_and_confirm_transaction(&transaction)
+            .await
+            .context("Transaction execution failed")
+    }
+
+    async fn get_lamports_per_signature(&self) -> Result<u64> {
+        let latest_blockhash = self
+            .get_latest_blockhash()
+            .await
+            .context("Getting a blockhash from the cluster")?;
+
+        // A message without any instructions, with just a fee payer signature.
+        let message = Message::new_with_blockhash(&[], Some(&Pubkey::default()), &latest_blockhash);
+
+        self.get_fee_for_message(&message)
+            .await
+            .context("Getting a fee for a single signature message")
+    }
+
+    async fn get_latest_blockhash_for_tx(&self) -> Result<Hash> {
+        ClusterRpc::latest_blockhash_for_tx(self).await
+    }
+
+    async fn get_accounts_chunked<T: Pod>(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<Option<TypedAccount<T>>>> {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig {
+                offset: 0,
+                length: size_of::<T>(),
+            }),
+            ..RpcAccountInfoConfig::default()
+        };
+
+        let chunks = try_join_all(addresses.chunks(MAX_MULTIPLE_ACCOUNTS).map(|chunk| {
+            let config = config.clone();
+            async move {
+                self.get_multiple_accounts_with_config(chunk, config)
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "Reading {} accounts, starting with {}",
+                            chunk.len(),
+                            chunk[0]
+                        )
+                    })
+                    .map(|response| response.value)
+            }
+        }))
+        .await?;
+
+        addresses
+            .iter()
+            .zip(chunks.into_iter().flatten())
+            .map(|(address, account)| {
+                let Some(account) = account else {
+                    return Ok(None);
+                };
+                let Some(data) = account.data.get(..size_of::<T>()) else {
+                    bail!(
+                        "Account {address} holds {} bytes of data, while at least {} are expected",
+                        account.data.len(),
+                        size_of::<T>(),
+                    );
+                };
+                Ok(Some(TypedAccount {
+                    lamports: account.lamports,
+                    owner: account.owner,
+                    data: pod_read_unaligned(data),
+                }))
+            })
+            .collect()
+    }
+
+    async fn get_multiple_accounts_chunked(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<(Option<Slot>, Vec<Option<Account>>)> {
+        let chunks = try_join_all(addresses.chunks(MAX_MULTIPLE_ACCOUNTS).map(
+            |chunk| async move {
+                self.get_multiple_accounts_with_commitment(chunk, self.commitment())
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "Reading {} accounts, starting with {}",
+                            chunk.len(),
+                            chunk[0]
+                        )
+                    })
+            },
+        ))
+        .await?;
+
+        let slot = chunks.iter().map(|response| response.context.slot).max();
+        let accounts = chunks
+            .into_iter()
+            .flat_map(|response| response.value)
+            .collect();
+        Ok((slot, accounts))
+    }
+
+    async fn get_account_data_lens(&self, addresses: &[Pubkey]) -> Result<Vec<Option<usize>>> {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(UiDataSliceConfig {
+                offset: 0,
+                length: 0,
+            }),
+            commitment: Some(self.commitment()),
+            ..RpcAccountInfoConfig::default()
+        };
+
+        let chunks = try_join_all(addresses.chunks(MAX_MULTIPLE_ACCOUNTS).map(|chunk| {
+            let config = config.clone();
+            async move {
+                let context = || {
+                    format!(
+                        "Reading sizes of {} accounts, starting with {}",
+                        chunk.len(),
+                        chunk[0]
+                    )
+                };
+
+                // `get_multiple_accounts_with_config()` drops the account sizes, so the request is
+                // sent directly.
+                let addresses = chunk.iter().map(Pubkey::to_string).collect::<Vec<_>>();
+                let accounts = self
+                    .send::<Response<Vec<Option<UiAccount>>>>(
+                        RpcRequest::GetMultipleAccounts,
+                        json!([addresses, config]),
+                    )
+                    .await
+                    .with_context(context)?
+                    .value;
+
+                if accounts
+                    .iter()
+                    .flatten()
+                    .all(|account| account.space.is_some())
+                {
+                    return Ok(accounts
+                        .into_iter()
+                        .map(|account| {
+                            account.and_then(|account| account.space).map(|space| {
+                                usize::try_from(space).expect("Account size fits into a usize")
+                            })
+                        })
+                        .collect::<Vec<_>>());
+                }
+
+                // Older nodes do not report account sizes, so the data needs to be fetched.
+                let accounts = self
+                    .get_multiple_accounts(chunk)
+                    .await
+                    .with_context(context)?;
+                Ok::<_, anyhow::Error>(
+                    accounts
+                        .into_iter()
+                        .map(|account| account.map(|account| account.data.len()))
+                        .collect(),
+                )
+            }
+        }))
+        .await?;
+
+        Ok(chunks.into_iter().flatten().collect())
+    }
+
+    async fn get_program_accounts_typed<T: Pod>(
+        &self,
+        program_id: &Pubkey,
+        filters: Vec<RpcFilterType>,
+        data_offset: usize,
+    ) -> Result<Vec<(Pubkey, TypedAccount<T>)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: (!filters.is_empty()).then_some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: Some(UiDataSliceConfig {
+                    offset: data_offset,
+                    length: size_of::<T>(),
+                }),
+                commitment: Some(self.commitment()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+
+        let accounts = self
+            .get_program_accounts_with_config(program_id, config)
+            .await
+            .with_context(|| format!("Failed to fetch accounts of {program_id}"))?;
+
+        accounts
+            .into_iter()
+            .map(|(address, account)| {
+                let Some(data) = account.data.get(..size_of::<T>()) else {
+                    bail!(
+                        "Account {address} holds {} bytes of data at offset {data_offset}, while \
+                         {} are expected",
+                        account.data.len(),
+                        size_of::<T>(),
+                    );
+                };
+                Ok((
+                    address,
+                    TypedAccount {
+                        lamports: account.lamports,
+                        owner: account.owner,
+                        data: pod_read_unaligned(data),
+                    },
+                ))
+            })
+            .collect()
+    }
+
+    async fn get_program_accounts_with_slot(
+        &self,
+        program_id: &Pubkey,
+        filters: Vec<RpcFilterType>,
+        data_slice: UiDataSliceConfig,
+        min_context_slot: Option<Slot>,
+    ) -> Result<(Slot, Vec<(Pubkey, Account)>)> {
+        let config = RpcProgramAccountsConfig {
+            filters: (!filters.is_empty()).then_some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: Some(data_slice),
+                commitment: Some(self.commitment()),
+                min_context_slot,
+            },
+            // `get_program_accounts_with_config()` drops the context, so the request is sent
+            // directly.
+            with_context: Some(true),
+        };
+
+        let response = self
+            .send::<OptionalContext<Vec<RpcKeyedAccount>>>(
+                RpcRequest::GetProgramAccounts,
+                json!([program_id.to_string(), config]),
+            )
+            .await
+            .with_context(|| format!("Failed to fetch accounts of {program_id}"))?;
+        let OptionalContext::Context(Response { context, value }) = response else {
+            bail!("Node did not return the slot for the accounts of {program_id}");
+        };
+
+        let accounts = value
+            .into_iter()
+            .map(|RpcKeyedAccount { pubkey, account }| {
+                let address = Pubkey::from_str(&pubkey)
+                    .with_context(|| format!("Node returned an invalid address: {pubkey}"))?;
+                let account = account
+                    .decode::<Account>()
+                    .with_context(|| format!("Failed to decode account {address}"))?;
+                Ok((address, account))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok((context.slot, accounts))
+    }
+
+    async fn wait_for(
+        &self,
+        target: WaitTarget,
+        poll_interval: Duration,
+        mut on_progress: impl FnMut(&EpochInfo),
+    ) -> Result<EpochInfo> {
+        loop {
+            let epoch_info = self
+                .get_epoch_info()
+                .await
+                .context("Getting the current epoch info")?;
+            on_progress(&epoch_info);
+
+            if target.is_reached(&epoch_info) {
+                return Ok(epoch_info);
+            }
+
+            sleep(poll_interval).await;
+        }
+    }
 }
@@ -0,0 +1,188 @@
+//! Counts RPC requests sent by the process, per method, so that commands can report how much RPC
+//! traffic they generate.  Helps to see which commands are RPC heavy, and if a node is likely to
+//! rate limit them.
+//!
+//! Collection is disabled by default, as measuring the payload sizes requires serializing every
+//! request and response one more time.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use serde_json::json;
+use solana_rpc_client::rpc_sender::{RpcSender, RpcTransportStats};
+use solana_rpc_client_api::{client_error::Result, request::RpcRequest};
+
+use crate::output;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static STATS: Mutex<BTreeMap<String, MethodStats>> = Mutex::new(BTreeMap::new());
+
+/// Requests sent for one RPC method.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MethodStats {
+    pub requests: u64,
+    /// Requests that failed, either due to a transport error, or with an error reported by the
+    /// node.
+    pub errors: u64,
+    /// Size of the JSON request bodies.  HTTP headers are not included.
+    pub request_bytes: u64,
+    /// Size of the JSON results.  The response envelope and HTTP headers are not included.
+    pub response_bytes: u64,
+    /// Combined time spent waiting for the responses.
+    pub elapsed: Duration,
+}
+
+/// Starts collecting stats for all the RPC clients that use a [`TelemetryRpcSender`].
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Returns stats collected since the last call, and starts collecting from scratch.
+pub fn take() -> BTreeMap<String, MethodStats> {
+    std::mem::take(&mut *STATS.lock().expect("RPC telemetry lock is not poisoned"))
+}
+
+/// Prints stats collected since the last [`take()`] call, if any requests were sent.
+pub fn print_summary() {
+    let stats = take();
+    if stats.is_empty() {
+        return;
+    }
+
+    let total = stats
+        .values()
+        .fold(MethodStats::default(), |total, method| MethodStats {
+            requests: total.requests + method.requests,
+            errors: total.errors + method.errors,
+            request_bytes: total.request_bytes + method.request_bytes,
+            response_bytes: total.response_bytes + method.response_bytes,
+            elapsed: total.elapsed + method.elapsed,
+        });
+
+    let mut text = format!("RPC requests: {}", describe(&total));
+    for (method, stats) in &stats {
+        text.push_str(&format!("\n  {method}: {}", describe(stats)));
+    }
+
+    output::result(
+        text,
+        json!({
+            "rpc_telemetry": stats
+                .iter()
+                .map(|(method, stats)| (method.clone(), stats_json(stats)))
+                .collect::<serde_json::Map<_, _>>(),
+            "rpc_telemetry_total": stats_json(&total),
+        }),
+    );
+}
+
+fn describe(
+    MethodStats {
+        requests,
+        errors,
+        request_bytes,
+        response_bytes,
+        elapsed,
+    }: &MethodStats,
+) -> String {
+    let error_rate = if *requests == 0 {
+        0.
+    } else {
+        *errors as f64 / *requests as f64 * 100.
+    };
+    let average = elapsed
+        .checked_div(u32::try_from(*requests).unwrap_or(u32::MAX))
+        .unwrap_or_default();
+    format!(
+        "{requests} requests, {errors} failed ({error_rate:.1}%), {request_bytes} bytes sent, \
+         {response_bytes} bytes received, {average:.1?} average latency"
+    )
+}
+
+fn stats_json(
+    MethodStats {
+        requests,
+        errors,
+        request_bytes,
+        response_bytes,
+        elapsed,
+    }: &MethodStats,
+) -> serde_json::Value {
+    json!({
+        "requests": requests,
+        "errors": errors,
+        "request_bytes": request_bytes,
+        "response_bytes": response_bytes,
+        "elapsed_ms": elapsed.as_millis(),
+    })
+}
+
+/// Wraps another [`RpcSender`], recording every request into the process wide stats, when
+/// [`enable()`]d.
+pub struct TelemetryRpcSender<Sender> {
+    inner: Sender,
+}
+
+impl<Sender> TelemetryRpcSender<Sender> {
+    pub fn new(inner: Sender) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl<Sender: RpcSender + Send + Sync> RpcSender for TelemetryRpcSender<Sender> {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        if !is_enabled() {
+            return self.inner.send(request, params).await;
+        }
+
+        // Request id does not affect the body size much.
+        let request_bytes = request
+            .build_request_json(0, params.clone())
+            .to_string()
+            .len();
+
+        let start = Instant::now();
+        let res = self.inner.send(request, params).await;
+        let elapsed = start.elapsed();
+
+        let response_bytes = match &res {
+            Ok(value) => value.to_string().len(),
+            Err(_) => 0,
+        };
+
+        let mut stats = STATS.lock().expect("RPC telemetry lock is not poisoned");
+        let stats = stats.entry(request.to_string()).or_default();
+        stats.requests += 1;
+        stats.errors += u64::from(res.is_err());
+        stats.request_bytes += request_bytes as u64;
+        stats.response_bytes += response_bytes as u64;
+        stats.elapsed += elapsed;
+
+        res
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
@@ -0,0 +1,27 @@
+//! `Pubkey` deserializes from an array of bytes, while config files and templates use base58
+//! strings.  Use these via `#[serde(deserialize_with = "...")]`.
+
+use std::str::FromStr as _;
+
+use serde::{Deserialize as _, Deserializer, de::Error as _};
+use solana_sdk::pubkey::Pubkey;
+
+pub fn from_str<'de, D>(deserializer: D) -> Result<Pubkey, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    Pubkey::from_str(&value).map_err(|err| D::Error::custom(format!("{value}: {err}")))
+}
+
+pub fn optional_from_str<'de, D>(deserializer: D) -> Result<Option<Pubkey>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(value) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    Pubkey::from_str(&value)
+        .map(Some)
+        .map_err(|err| D::Error::custom(format!("{value}: {err}")))
+}
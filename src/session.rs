@@ -0,0 +1,151 @@
+//! Inside an interactive `shell`, RPC clients, [`BlockhashCache`]s, and [`NodeAddressService`]s
+//! are kept alive between commands.  This saves the startup cost every command would otherwise
+//! pay, constructing and initializing all of them.
+//!
+//! Outside of a `shell` there is no session, and every command creates its own instances.
+
+use std::{collections::HashMap, sync::Arc, sync::OnceLock, time::Duration};
+
+use anyhow::{Context as _, Result};
+use parking_lot::Mutex;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use tokio::sync::{Mutex as AsyncMutex, OnceCell};
+use tokio_util::sync::CancellationToken;
+
+use crate::{blockhash_cache::BlockhashCache, node_address_service::NodeAddressService};
+
+static SESSION: OnceLock<Session> = OnceLock::new();
+
+pub struct Session {
+    /// Stops all the background tasks, started by the session.
+    shutdown: CancellationToken,
+    clusters: Mutex<Vec<Arc<ClusterState>>>,
+}
+
+/// State shared by all the commands that use the same RPC configuration.
+struct ClusterState {
+    /// Identifies the RPC configuration `rpc_client` was constructed with.
+    config: String,
+    rpc_client: Arc<RpcClient>,
+    blockhash_cache: OnceCell<BlockhashCache>,
+    /// Indexed by the WebSocket URL.
+    node_address_services: AsyncMutex<HashMap<String, NodeAddressService>>,
+}
+
+/// Starts a new session.  Should only be called once, by the `shell` command.
+pub fn start() -> &'static Session {
+    let session = Session {
+        shutdown: CancellationToken::new(),
+        clusters: Mutex::default(),
+    };
+    if SESSION.set(session).is_err() {
+        panic!("`session::start()` should only be called once");
+    }
+    get().expect("Session was just set")
+}
+
+/// Returns the current session, if one was started.
+pub fn get() -> Option<&'static Session> {
+    SESSION.get()
+}
+
+impl Session {
+    /// Returns an RPC client for the given configuration, constructing it, via `new_client`, if
+    /// this configuration was not seen before.
+    ///
+    /// `config` should uniquely identify all the parameters `new_client` uses.
+    pub fn rpc_client(
+        &self,
+        config: String,
+        new_client: impl FnOnce() -> RpcClient,
+    ) -> Arc<RpcClient> {
+        let mut clusters = self.clusters.lock();
+        if let Some(cluster) = clusters.iter().find(|cluster| cluster.config == config) {
+            return cluster.rpc_client.clone();
+        }
+
+        let rpc_client = Arc::new(new_client());
+        clusters.push(Arc::new(ClusterState {
+            config,
+            rpc_client: rpc_client.clone(),
+            blockhash_cache: OnceCell::new(),
+            node_address_services: AsyncMutex::default(),
+        }));
+        rpc_client
+    }
+
+    /// Returns a [`BlockhashCache`] that is kept up to date in the background, as long as the
+    /// session is active.
+    ///
+    /// Returns `None` if `rpc_client` was not constructed by this session.
+    pub async fn blockhash_cache(&self, rpc_client: &RpcClient) -> Option<BlockhashCache> {
+        let cluster = self.find_cluster(rpc_client)?;
+
+        let blockhash_cache = cluster
+            .blockhash_cache
+            .get_or_init(async || {
+                let blockhash_cache = BlockhashCache::uninitialized();
+                blockhash_cache.init(&cluster.rpc_client).await;
+
+                tokio::spawn({
+                    let blockhash_cache = blockhash_cache.clone();
+                    let rpc_client = cluster.rpc_client.clone();
+                    let shutdown = self.shutdown.clone();
+                    async move {
+                        blockhash_cache
+                            .run_refresh_loop(&rpc_client, Duration::from_millis(400), shutdown)
+                            .await
+                    }
+                });
+
+                blockhash_cache
+            })
+            .await;
+
+        Some(blockhash_cache.clone())
+    }
+
+    /// Returns a [`NodeAddressService`] that is kept up to date in the background, as long as the
+    /// session is active.
+    ///
+    /// Returns `None` if `rpc_client` was not constructed by this session.
+    pub async fn node_address_service(
+        &self,
+        rpc_client: &RpcClient,
+        websocket_url: &str,
+    ) -> Result<Option<NodeAddressService>> {
+        let Some(cluster) = self.find_cluster(rpc_client) else {
+            return Ok(None);
+        };
+
+        let mut node_address_services = cluster.node_address_services.lock().await;
+        if let Some(node_address_service) = node_address_services.get(websocket_url) {
+            return Ok(Some(node_address_service.clone()));
+        }
+
+        // The background task runs until the session is shutdown, so we do not need the handle.
+        let (node_address_service, _handle) = NodeAddressService::init(
+            cluster.rpc_client.clone(),
+            websocket_url,
+            self.shutdown.clone(),
+        )
+        .await
+        .context("NodeAddressService construction failed")?;
+
+        node_address_services.insert(websocket_url.to_owned(), node_address_service.clone());
+        Ok(Some(node_address_service))
+    }
+
+    /// Stops all the background tasks.
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+
+    fn find_cluster(&self, rpc_client: &RpcClient) -> Option<Arc<ClusterState>> {
+        self.clusters
+            .lock()
+            .iter()
+            .find(|cluster| std::ptr::eq(Arc::as_ptr(&cluster.rpc_client), rpc_client))
+            .cloned()
+    }
+}
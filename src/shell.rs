@@ -0,0 +1,124 @@
+//! An interactive shell, that executes commands one after another, sharing state between them.
+//!
+//! See [`pythnet_heisenberg::session`] for the state that is shared.
+
+use std::{env, ffi::OsString, io::Write as _, iter};
+
+use anyhow::{Context as _, Result};
+use clap::CommandFactory as _;
+use pythnet_heisenberg::{dry_run, rpc_telemetry, session, tx_records};
+use tokio::io::{AsyncBufReadExt as _, BufReader, stdin};
+
+use crate::args::{self, Args, arg_files, cluster_config, keypair_dirs, scenario};
+
+const PROMPT: &str = "heisenberg> ";
+
+pub async fn run() -> Result<()> {
+    let session = session::start();
+
+    // Global arguments from the shell invocation, like `--cluster` or `--config`, are used when
+    // resolving cluster aliases for every command.
+    let shell_args = env::args_os().skip(1).collect::<Vec<_>>();
+
+    let mut lines = BufReader::new(stdin()).lines();
+    loop {
+        eprint!("{PROMPT}");
+        std::io::stderr()
+            .flush()
+            .context("Failed to flush stderr")?;
+
+        let Some(line) = lines.next_line().await.context("Failed to read stdin")? else {
+            // EOF.  Make sure the next shell prompt starts on a new line.
+            eprintln!();
+            break;
+        };
+
+        let Some(words) = shlex::split(&line) else {
+            eprintln!("Error: Unbalanced quotes");
+            continue;
+        };
+
+        match words.first().map(String::as_str) {
+            None => continue,
+            Some("exit" | "quit") => break,
+            Some(_) => (),
+        }
+
+        match run_line(words, &shell_args).await {
+            Ok(()) => (),
+            Err(err) if err.is::<dry_run::Stopped>() => eprintln!("{}", dry_run::Stopped),
+            Err(err) => eprintln!("Error: {err:?}"),
+        }
+    }
+
+    session.shutdown();
+
+    Ok(())
+}
+
+async fn run_line(words: Vec<String>, shell_args: &[OsString]) -> Result<()> {
+    let alias_args = words
+        .iter()
+        .map(OsString::from)
+        .chain(shell_args.iter().cloned())
+        .collect::<Vec<_>>();
+    let command = cluster_config::apply_cluster_alias(Args::command(), &alias_args)?;
+    let command = scenario::apply_scenario(command, &alias_args)?;
+
+    let bin_name = command.get_name().to_owned();
+    let words = arg_files::expand_args(&command, words.into_iter().map(OsString::from).collect())?;
+    let words = keypair_dirs::expand_args(words)?;
+    let args = match args::try_parse_from(command, iter::once(bin_name.into()).chain(words)) {
+        Ok(args) => args,
+        Err(err) => {
+            // This also covers `--help`, and `help`.
+            err.print().context("Failed to print a parse error")?;
+            return Ok(());
+        }
+    };
+
+    let args::Args {
+        output: _,
+        cluster: _,
+        config: _,
+        rpc_telemetry,
+        tx_records_file,
+        dry_run,
+        simulate,
+        command,
+    } = args;
+
+    if let args::Command::Shell = command {
+        eprintln!("Already running inside a shell");
+        return Ok(());
+    }
+
+    if rpc_telemetry {
+        rpc_telemetry::enable();
+    }
+    if let Some(path) = &tx_records_file {
+        tx_records::open(path)?;
+    }
+    // A dry run requested for the shell itself applies to all the commands.
+    let line_dry_run = dry_run && !dry_run::is_enabled();
+    if line_dry_run {
+        dry_run::enable(simulate);
+    }
+
+    let res = Box::pin(crate::run_command(command)).await;
+
+    if line_dry_run {
+        dry_run::disable();
+    }
+
+    // A file given to the shell itself stays open until the shell exits.
+    if tx_records_file.is_some() {
+        tx_records::close();
+    }
+
+    if rpc_telemetry::is_enabled() {
+        rpc_telemetry::print_summary();
+    }
+
+    res
+}
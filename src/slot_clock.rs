@@ -0,0 +1,148 @@
+//! Maps wall clock time to slots.
+//!
+//! Latencies measured in milliseconds are easier to relate to the cluster behavior when they are
+//! also expressed in slots.  [`SlotClock`] remembers when each slot started, based on the
+//! `slotSubscribe` notifications, and converts between the two using these observations.  All
+//! the commands that report latencies use the same conversion, so their numbers are consistent.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use futures::StreamExt as _;
+use log::warn;
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+use solana_sdk::clock::Slot;
+use tokio::{select, task::JoinHandle, time::Instant};
+use tokio_util::sync::CancellationToken;
+
+/// Start times of this many most recent slots are remembered.
+const SLOT_HISTORY: usize = 1000;
+
+/// Shared record of the slot start times.  Clones refer to the same record.
+#[derive(Clone, Default)]
+pub struct SlotClock {
+    slot_starts: Arc<RwLock<BTreeMap<Slot, Instant>>>,
+}
+
+impl SlotClock {
+    /// Creates a clock without any observations.  Use [`record()`](Self::record) to feed it, when
+    /// the caller already has a slot subscription.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes to the slot notifications of the node at `websocket_url`, and records them,
+    /// until `exit` is cancelled.
+    pub async fn start(
+        websocket_url: &str,
+        exit: CancellationToken,
+    ) -> Result<(Self, JoinHandle<Result<()>>)> {
+        let pubsub_client = PubsubClient::new(websocket_url)
+            .await
+            .with_context(|| format!("Failed to connect to {websocket_url}"))?;
+
+        let clock = Self::new();
+        let handle = {
+            let clock = clock.clone();
+            tokio::spawn(async move {
+                let (mut slots, unsubscribe) = pubsub_client
+                    .slot_subscribe()
+                    .await
+                    .context("Failed to subscribe to slot notifications")?;
+
+                let res = loop {
+                    select! {
+                        slot_info = slots.next() => match slot_info {
+                            Some(slot_info) => clock.record(slot_info.slot, Instant::now()),
+                            None => break Err(anyhow!("Server closed the slot subscription")),
+                        },
+                        () = exit.cancelled() => break Ok(()),
+                    }
+                };
+
+                unsubscribe().await;
+                // `slots` borrows the `pubsub_client`.
+                drop(slots);
+                if let Err(err) = pubsub_client.shutdown().await {
+                    warn!("Failed to disconnect the pubsub client: {err}");
+                }
+
+                res
+            })
+        };
+
+        Ok((clock, handle))
+    }
+
+    /// Records the moment the `slot` was seen.  Only the first observation of a slot is kept.
+    pub fn record(&self, slot: Slot, at: Instant) {
+        let mut slot_starts = self.slot_starts.write().unwrap();
+        slot_starts.entry(slot).or_insert(at);
+        while slot_starts.len() > SLOT_HISTORY {
+            slot_starts.pop_first();
+        }
+    }
+
+    /// When the `slot` was first seen, if it is among the recent observations.
+    pub fn slot_start(&self, slot: Slot) -> Option<Instant> {
+        self.slot_starts.read().unwrap().get(&slot).copied()
+    }
+
+    /// Average slot duration over the recent observations.  Skipped slots are accounted for, as
+    /// they advance the slot number without a notification.
+    pub fn slot_duration(&self) -> Option<Duration> {
+        let slot_starts = self.slot_starts.read().unwrap();
+        let (first_slot, first_start) = slot_starts.first_key_value()?;
+        let (last_slot, last_start) = slot_starts.last_key_value()?;
+        let slots = u32::try_from(last_slot.checked_sub(*first_slot)?).ok()?;
+        if slots == 0 {
+            return None;
+        }
+        last_start
+            .checked_duration_since(*first_start)?
+            .checked_div(slots)
+    }
+
+    /// Slot that was current at the `at` moment, with a fraction of the slot that has elapsed.
+    /// Moments after the last observation are extrapolated using the average slot duration.
+    pub fn slot_at(&self, at: Instant) -> Option<f64> {
+        let slot_duration = self.slot_duration()?.as_secs_f64();
+        let slot_starts = self.slot_starts.read().unwrap();
+        // Slot start times increase with the slot number, except for some notification jitter.
+        let (slot, start) = slot_starts
+            .iter()
+            .rev()
+            .find(|(_slot, start)| **start <= at)
+            .or_else(|| slot_starts.first_key_value())?;
+        let offset = if at >= *start {
+            (at - *start).as_secs_f64()
+        } else {
+            -(*start - at).as_secs_f64()
+        };
+        Some(*slot as f64 + offset / slot_duration)
+    }
+
+    /// The current slot, with a fraction of the slot that has elapsed.
+    pub fn current_slot(&self) -> Option<f64> {
+        self.slot_at(Instant::now())
+    }
+
+    /// Converts a `duration` into a number of slots, using the average slot duration.
+    pub fn to_slots(&self, duration: Duration) -> Option<f64> {
+        let slot_duration = self.slot_duration()?;
+        Some(duration.as_secs_f64() / slot_duration.as_secs_f64())
+    }
+
+    /// Describes a `duration` in milliseconds and, once the slot duration is known, in slots.
+    pub fn describe(&self, duration: Duration) -> String {
+        let ms = duration.as_secs_f64() * 1000.0;
+        match self.to_slots(duration) {
+            Some(slots) => format!("{ms:.1}ms ({slots:.2} slots)"),
+            None => format!("{ms:.1}ms"),
+        }
+    }
+}
@@ -0,0 +1,14 @@
+use anyhow::{Context as _, Result};
+
+use crate::{args::stake::Command, exit_code::ValidationFailed};
+
+mod delegate;
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Delegate(args) => {
+            args.check_are_valid().context(ValidationFailed)?;
+            delegate::run(args).await
+        }
+    }
+}
@@ -0,0 +1,328 @@
+//! Delegates stake to multiple vote accounts, in proportion to their weights.
+
+use std::{fs, path::Path, str::FromStr as _};
+
+use anyhow::{Context as _, Result, bail};
+use itertools::izip;
+use pythnet_heisenberg::{
+    blockhash_cache::BlockhashCache,
+    dry_run,
+    keypair_ext::read_keypair_file,
+    output,
+    rpc_client_ext::RpcClientExt as _,
+    tx_sheppard::{TxOutcome, with_sheppard},
+};
+use serde_json::json;
+use solana_sdk::{
+    native_token::Sol,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Keypair,
+    signer::Signer as _,
+    stake::{
+        self, instruction as stake_instruction,
+        state::{Authorized, Lockup, StakeStateV2},
+    },
+    transaction::Transaction,
+};
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, stake::delegate::DelegateArgs},
+    exit_code::check_outcomes,
+};
+
+struct Delegation {
+    vote_account: Pubkey,
+    stake_account: Pubkey,
+    seed: String,
+    /// Including the rent exempt reserve.
+    lamports: u64,
+}
+
+pub async fn run(
+    DelegateArgs {
+        json_rpc_url,
+        funding_keypair,
+        weights_file,
+        total_stake,
+        split_from,
+        seed_prefix,
+    }: DelegateArgs,
+) -> Result<()> {
+    let dry_run = dry_run::is_enabled();
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let rpc_client = &rpc_client;
+
+    let funding = read_keypair_file(&funding_keypair)?;
+    let funding_pubkey = funding.pubkey();
+
+    let weights = read_weights_file(&weights_file)?;
+    if weights.is_empty() {
+        bail!(
+            "Weights file does not list any vote accounts: {}",
+            weights_file.to_string_lossy()
+        );
+    }
+
+    let rent_exempt_reserve = Rent::default().minimum_balance(StakeStateV2::size_of());
+    let minimum_delegation = rpc_client
+        .get_stake_minimum_delegation()
+        .await
+        .context("Failed to get the minimum stake delegation")?;
+    let minimum_stake = rent_exempt_reserve + minimum_delegation;
+
+    let amounts = split_by_weight(total_stake, weights.iter().map(|(_, weight)| *weight));
+    let mut delegations = vec![];
+    for (position, ((vote_account, _weight), lamports)) in izip!(&weights, amounts).enumerate() {
+        if lamports < minimum_stake {
+            bail!(
+                "Stake for {vote_account} would be {}, below the minimum of {}: the rent exempt \
+                 reserve plus the minimum delegation.  Increase --total-stake.",
+                Sol(lamports),
+                Sol(minimum_stake),
+            );
+        }
+
+        let seed = format!("{seed_prefix}{position}");
+        let stake_account = Pubkey::create_with_seed(&funding_pubkey, &seed, &stake::program::id())
+            .with_context(|| format!("Failed to derive a stake account address for {seed}"))?;
+        delegations.push(Delegation {
+            vote_account: *vote_account,
+            stake_account,
+            seed,
+            lamports,
+        });
+    }
+
+    // Stake accounts left over from a previous run are not touched.
+    let existing = rpc_client
+        .get_accounts_chunked::<()>(
+            &delegations
+                .iter()
+                .map(|Delegation { stake_account, .. }| *stake_account)
+                .collect::<Vec<_>>(),
+        )
+        .await
+        .context("Checking for existing stake accounts")?;
+    let delegations = izip!(delegations, existing)
+        .filter_map(|(delegation, existing)| {
+            if existing.is_some() {
+                eprintln!(
+                    "Stake account {} for {} already exists, skipping ...",
+                    delegation.stake_account, delegation.vote_account,
+                );
+                return None;
+            }
+            Some(delegation)
+        })
+        .collect::<Vec<_>>();
+
+    if delegations.is_empty() {
+        output::notice("All the stake accounts already exist");
+        return Ok(());
+    }
+
+    for Delegation {
+        vote_account,
+        stake_account,
+        lamports,
+        ..
+    } in &delegations
+    {
+        eprintln!(
+            "Delegating {} to {vote_account} via {stake_account} ...",
+            Sol(*lamports)
+        );
+    }
+    let total = delegations
+        .iter()
+        .map(|Delegation { lamports, .. }| *lamports)
+        .sum::<u64>();
+
+    if dry_run {
+        let lamports_per_signature = rpc_client
+            .get_lamports_per_signature()
+            .await
+            .context("Estimating transaction fees")?;
+        // The funding account is the only signer.
+        eprintln!(
+            "Dry run.  No transactions were sent.\n\
+             Transactions: {}\n\
+             Total to delegate: {}\n\
+             Estimated fees: {}",
+            delegations.len(),
+            Sol(total),
+            Sol(delegations.len() as u64 * lamports_per_signature),
+        );
+        return Ok(());
+    }
+
+    let source = split_from.unwrap_or(funding_pubkey);
+    let source_balance = rpc_client
+        .get_balance(&source)
+        .await
+        .with_context(|| format!("Failed to get the {source} balance"))?;
+    // A stake account being split needs to keep its own rent exempt reserve.
+    let required = match split_from {
+        Some(_) => total + rent_exempt_reserve,
+        None => total,
+    };
+    if source_balance < required {
+        bail!(
+            "{source} does not have enough funds for all the stake accounts.\n\
+             Current balance: {}\n\
+             Required: {}",
+            Sol(source_balance),
+            Sol(required),
+        );
+    }
+
+    let outcomes = with_sheppard(rpc_client)
+        .labels(
+            delegations
+                .iter()
+                .map(|Delegation { vote_account, .. }| vote_account),
+        )
+        .run(
+            delegations
+                .iter()
+                .map(|delegation| delegate_tx(&funding, funding_pubkey, split_from, delegation)),
+        )
+        .await
+        .context("Running delegation transactions")?;
+
+    for (
+        Delegation {
+            vote_account,
+            stake_account,
+            lamports,
+            ..
+        },
+        outcome,
+    ) in izip!(&delegations, &outcomes)
+    {
+        let mut result = json!({
+            "vote_account": vote_account.to_string(),
+            "stake_account": stake_account.to_string(),
+            "lamports": lamports,
+        });
+        match outcome {
+            TxOutcome::Success(signature) => result["signature"] = json!(signature.to_string()),
+            TxOutcome::Failed(error) => result["error"] = json!(error),
+        }
+        output::json_result(result);
+    }
+
+    check_outcomes(&outcomes)?;
+
+    output::notice("Stake becomes active at the start of the next epoch");
+
+    Ok(())
+}
+
+/// Reads a CSV file with "[vote pubkey],[weight]" lines.
+fn read_weights_file(path: &Path) -> Result<Vec<(Pubkey, u64)>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read weights file: {}", path.to_string_lossy()))?;
+
+    let mut weights = vec![];
+    for (line_no, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let context = || format!("{}:{}", path.to_string_lossy(), line_no + 1);
+
+        let Some((vote_account, weight)) = line.split_once(',') else {
+            bail!(
+                "{}: expected \"[vote pubkey],[weight]\", got: {line}",
+                context()
+            );
+        };
+
+        let vote_account = Pubkey::from_str(vote_account.trim())
+            .with_context(|| format!("{}: invalid pubkey: {}", context(), vote_account.trim()))?;
+        let weight = weight
+            .trim()
+            .parse::<u64>()
+            .with_context(|| format!("{}: invalid weight: {}", context(), weight.trim()))?;
+        if weight == 0 {
+            bail!("{}: weight must be positive", context());
+        }
+
+        weights.push((vote_account, weight));
+    }
+
+    Ok(weights)
+}
+
+/// Splits `total` proportionally to the `weights`.  Lamports left over after rounding down go to
+/// the first entries, one each.
+fn split_by_weight(total: u64, weights: impl Iterator<Item = u64> + Clone) -> Vec<u64> {
+    let total_weight = weights.clone().map(u128::from).sum::<u128>();
+    let mut amounts = weights
+        .map(|weight| {
+            let amount = u128::from(total) * u128::from(weight) / total_weight;
+            u64::try_from(amount).expect("`amount` is at most `total`")
+        })
+        .collect::<Vec<_>>();
+
+    let unassigned = total - amounts.iter().sum::<u64>();
+    // Rounding down loses less than one lamport per entry.
+    for amount in amounts.iter_mut().take(unassigned as usize) {
+        *amount += 1;
+    }
+
+    amounts
+}
+
+fn delegate_tx<'context>(
+    funding: &'context Keypair,
+    funding_pubkey: Pubkey,
+    split_from: Option<Pubkey>,
+    Delegation {
+        vote_account,
+        stake_account,
+        seed,
+        lamports,
+    }: &'context Delegation,
+) -> impl Fn(/* blockhash_cache: */ &BlockhashCache) -> Transaction + 'context {
+    move |blockhash_cache: &BlockhashCache| -> Transaction {
+        let instructions = match split_from {
+            None => stake_instruction::create_account_with_seed_and_delegate_stake(
+                &funding_pubkey,
+                stake_account,
+                &funding_pubkey,
+                seed,
+                vote_account,
+                &Authorized::auto(&funding_pubkey),
+                &Lockup::default(),
+                *lamports,
+            ),
+            Some(split_from) => {
+                let mut instructions = stake_instruction::split_with_seed(
+                    &split_from,
+                    &funding_pubkey,
+                    *lamports,
+                    stake_account,
+                    &funding_pubkey,
+                    seed,
+                );
+                instructions.push(stake_instruction::delegate_stake(
+                    stake_account,
+                    &funding_pubkey,
+                    vote_account,
+                ));
+                instructions
+            }
+        };
+
+        Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&funding_pubkey),
+            &[funding],
+            blockhash_cache.get(),
+        )
+    }
+}
@@ -1,11 +1,65 @@
-use anyhow::Result;
+use anchor_lang::{AccountDeserialize as _, InstructionData as _, ToAccountMetas as _};
+use anyhow::{Context as _, Result, bail};
+use solana_program::{instruction::Instruction, pubkey::Pubkey, system_program};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use stake_caps_parameters as program;
 
 use crate::args::stake_caps_parameters::Command;
 
+mod get;
 mod set_parameters;
+mod transfer_authority;
 
 pub async fn run(command: Command) -> Result<()> {
     match command {
         Command::SetParameters(args) => set_parameters::run(args).await,
+        Command::Get(args) => get::run(args).await,
+        Command::TransferAuthority(args) => transfer_authority::run(args).await,
+    }
+}
+
+/// Address of the parameters account, used when one is not specified explicitly.
+pub fn default_parameters_account(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"parameters"], program_id).0
+}
+
+/// Fetches and parses the parameters account, checking that it is owned by the `program_id`.
+async fn read_parameters(
+    rpc_client: &RpcClient,
+    program_id: &Pubkey,
+    parameters_account: &Pubkey,
+) -> Result<program::Parameters> {
+    let account = rpc_client
+        .get_account(parameters_account)
+        .await
+        .with_context(|| format!("Failed to fetch parameters account at {parameters_account}"))?;
+
+    if account.owner != *program_id {
+        bail!(
+            "Parameters account {parameters_account} is owned by {}, expected {program_id}",
+            account.owner
+        );
+    }
+
+    program::Parameters::try_deserialize(&mut account.data.as_slice())
+        .with_context(|| format!("Failed to parse parameters account at {parameters_account}"))
+}
+
+fn set_parameters_instruction(
+    program_id: Pubkey,
+    signer: Pubkey,
+    parameters_account: Pubkey,
+    parameters: program::Parameters,
+) -> Instruction {
+    let accounts = program::accounts::SetParameters {
+        signer,
+        parameters: parameters_account,
+        system_program: system_program::id(),
+    };
+
+    Instruction {
+        program_id,
+        accounts: accounts.to_account_metas(None),
+        data: program::instruction::SetParameters { parameters }.data(),
     }
 }
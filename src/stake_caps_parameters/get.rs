@@ -0,0 +1,45 @@
+use anyhow::Result;
+use pythnet_heisenberg::output;
+use serde_json::json;
+use stake_caps_parameters as program;
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, stake_caps_parameters::get::GetArgs},
+    stake_caps_parameters::{default_parameters_account, read_parameters},
+};
+
+pub async fn run(
+    GetArgs {
+        json_rpc_url,
+        program_id,
+        parameters_account,
+    }: GetArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let parameters_account =
+        parameters_account.unwrap_or_else(|| default_parameters_account(&program_id));
+
+    let program::Parameters {
+        current_authority,
+        m,
+        z,
+    } = read_parameters(&rpc_client, &program_id, &parameters_account).await?;
+
+    output::result(
+        format!(
+            "Parameters account: {parameters_account}\n\
+             m: {m}\n\
+             z: {z}\n\
+             Current authority: {current_authority}"
+        ),
+        json!({
+            "parameters_account": parameters_account.to_string(),
+            "m": m,
+            "z": z,
+            "current_authority": current_authority.to_string(),
+        }),
+    );
+
+    Ok(())
+}
@@ -1,15 +1,22 @@
-use anchor_lang::{InstructionData, ToAccountMetas};
+use std::iter;
+
 use anyhow::{Context as _, Result};
-use solana_program::{instruction::Instruction, pubkey::Pubkey, system_program};
-use solana_sdk::signer::Signer as _;
+use pythnet_heisenberg::{
+    blockhash_cache::BlockhashCache,
+    keypair_ext::{read_signer, try_new_signed_with_payer},
+    output,
+    tx_sheppard::{TxOutcome, with_sheppard},
+};
+use serde_json::json;
 use stake_caps_parameters as program;
 
 use crate::{
     args::{
         json_rpc_url_args::get_rpc_client, stake_caps_parameters::set_parameters::SetParametersArgs,
     },
-    keypair_ext::read_keypair_file,
-    rpc_client_ext::RpcClientExt,
+    confirm::confirm_or_abort,
+    exit_code::TransactionsFailed,
+    stake_caps_parameters::{default_parameters_account, set_parameters_instruction},
 };
 
 pub async fn run(
@@ -21,45 +28,84 @@ pub async fn run(
         m,
         z,
         update_authority,
+        skip_preflight,
+        yes,
     }: SetParametersArgs,
 ) -> Result<()> {
     let rpc_client = get_rpc_client(json_rpc_url);
 
-    let signer = read_keypair_file(&signer_keypair)?;
+    let signer = read_signer(&signer_keypair, "signer")?;
     let signer_pubkey = signer.pubkey();
 
-    let parameters_account = parameters_account
-        .unwrap_or_else(|| Pubkey::find_program_address(&[b"parameters"], &program_id).0);
+    let parameters_account =
+        parameters_account.unwrap_or_else(|| default_parameters_account(&program_id));
 
-    let accounts = program::accounts::SetParameters {
-        signer: signer_pubkey,
-        parameters: parameters_account,
-        system_program: system_program::id(),
-    };
+    let current_authority = update_authority.unwrap_or(signer_pubkey);
 
-    let instruction = Instruction {
+    confirm_or_abort(
+        format!(
+            "Setting stake cap parameters in {parameters_account}\n\
+             Signer: {signer_pubkey}\n\
+             m: {m}\n\
+             z: {z}\n\
+             Authority after the update: {current_authority}"
+        ),
+        yes,
+    )?;
+
+    let instructions = [set_parameters_instruction(
         program_id,
-        accounts: accounts.to_account_metas(None),
-        data: program::instruction::SetParameters {
-            parameters: program::Parameters {
-                m,
-                z,
-                current_authority: update_authority.unwrap_or(signer_pubkey),
-            },
-        }
-        .data(),
-    };
+        signer_pubkey,
+        parameters_account,
+        program::Parameters {
+            m,
+            z,
+            current_authority,
+        },
+    )];
 
-    let signature = rpc_client
-        .send_with_payer_latest_blockhash_with_spinner(
-            &[instruction],
-            Some(&signer_pubkey),
-            &[&signer],
-        )
+    let outcomes = with_sheppard(&rpc_client)
+        .skip_preflight(skip_preflight)
+        .run(iter::once(|blockhash_cache: &BlockhashCache| {
+            // The signer may be a hardware wallet, that can fail to sign.
+            try_new_signed_with_payer(
+                &instructions,
+                Some(&signer_pubkey),
+                &[signer.as_ref()],
+                blockhash_cache.get(),
+            )
+        }))
         .await
-        .context("Transaction execution failed")?;
+        .context("Running stake cap parameters update transaction")?;
+
+    let signature = match &outcomes[..] {
+        [TxOutcome::Success(signature)] => signature,
+        [TxOutcome::Failed(error)] => {
+            return Err(TransactionsFailed {
+                failed: 1,
+                total: 1,
+            })
+            .context(format!("Transaction execution failed: {error}"));
+        }
+        _ => panic!("Expected exactly one outcome for a single transaction"),
+    };
 
-    println!("State cap parameters update tx: {signature}");
+    output::result(
+        format!(
+            "Parameters account: {parameters_account}\n\
+             m: {m}\n\
+             z: {z}\n\
+             Current authority: {current_authority}\n\
+             Stake cap parameters update tx: {signature}"
+        ),
+        json!({
+            "parameters_account": parameters_account.to_string(),
+            "m": m,
+            "z": z,
+            "current_authority": current_authority.to_string(),
+            "signature": signature.to_string(),
+        }),
+    );
 
     Ok(())
 }
@@ -0,0 +1,103 @@
+use anyhow::{Context as _, Result, bail};
+use pythnet_heisenberg::{
+    blockhash_cache::{BlockhashCache, with_blockhash},
+    keypair_ext::read_signer,
+    output,
+    rpc_client_ext::RpcClientExt,
+};
+use serde_json::json;
+use stake_caps_parameters as program;
+
+use crate::{
+    args::{
+        json_rpc_url_args::get_rpc_client,
+        stake_caps_parameters::transfer_authority::TransferAuthorityArgs,
+    },
+    confirm::confirm_or_abort,
+    stake_caps_parameters::{
+        default_parameters_account, read_parameters, set_parameters_instruction,
+    },
+};
+
+pub async fn run(
+    TransferAuthorityArgs {
+        json_rpc_url,
+        signer_keypair,
+        program_id,
+        parameters_account,
+        new_authority,
+        yes,
+    }: TransferAuthorityArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let signer = read_signer(&signer_keypair, "signer")?;
+    let signer_pubkey = signer.pubkey();
+
+    let parameters_account =
+        parameters_account.unwrap_or_else(|| default_parameters_account(&program_id));
+
+    let program::Parameters {
+        current_authority,
+        m,
+        z,
+    } = read_parameters(&rpc_client, &program_id, &parameters_account).await?;
+
+    if current_authority != signer_pubkey {
+        bail!(
+            "Signer {signer_pubkey} is not the current authority of {parameters_account}.\n\
+             Current authority: {current_authority}"
+        );
+    }
+
+    if current_authority == new_authority {
+        eprintln!("{new_authority} is already the authority of {parameters_account}");
+        return Ok(());
+    }
+
+    confirm_or_abort(
+        format!(
+            "Transferring authority over the stake cap parameters account {parameters_account}\n\
+             Current authority: {current_authority}\n\
+             New authority: {new_authority}\n\
+             Parameters are kept unchanged: m: {m}, z: {z}"
+        ),
+        yes,
+    )?;
+
+    let instruction = set_parameters_instruction(
+        program_id,
+        signer_pubkey,
+        parameters_account,
+        program::Parameters {
+            m,
+            z,
+            current_authority: new_authority,
+        },
+    );
+
+    let signature = with_blockhash(&rpc_client)
+        .run(async |blockhash_cache: &BlockhashCache| {
+            rpc_client
+                .send_with_payer_cached_blockhash(
+                    &[instruction],
+                    Some(&signer_pubkey),
+                    &[signer.as_ref()],
+                    blockhash_cache,
+                )
+                .await
+        })
+        .await
+        .context("Transaction execution failed")?;
+
+    output::result(
+        format!("Stake cap parameters authority transfer tx: {signature}"),
+        json!({
+            "parameters_account": parameters_account.to_string(),
+            "current_authority": new_authority.to_string(),
+            "signature": signature.to_string(),
+        }),
+    );
+
+    Ok(())
+}
@@ -1,11 +1,40 @@
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 
-use crate::args::transfer::Command;
+use crate::{args::transfer::Command, exit_code::ValidationFailed};
 
+mod airdrop;
+mod close_accounts;
+mod create_nonce_accounts;
+mod faucet;
 mod fill_up_to;
+mod fund_vote_accounts;
+mod memo;
+mod vote_accounts;
+mod watch_and_fill;
+mod withdraw_from_vote;
+
+pub use fill_up_to::top_up;
 
 pub async fn run(command: Command) -> Result<()> {
     match command {
-        Command::FillUpTo(args) => fill_up_to::run(args).await,
+        Command::FillUpTo(args) => {
+            args.check_are_valid().context(ValidationFailed)?;
+            fill_up_to::run(args).await
+        }
+        Command::Airdrop(args) => {
+            args.check_are_valid().context(ValidationFailed)?;
+            airdrop::run(args).await
+        }
+        Command::CreateNonceAccounts(args) => create_nonce_accounts::run(args).await,
+        Command::WatchAndFill(args) => watch_and_fill::run(args).await,
+        Command::FundVoteAccounts(args) => {
+            args.check_are_valid().context(ValidationFailed)?;
+            fund_vote_accounts::run(args).await
+        }
+        Command::WithdrawFromVote(args) => {
+            args.check_are_valid().context(ValidationFailed)?;
+            withdraw_from_vote::run(args).await
+        }
+        Command::CloseAccounts(args) => close_accounts::run(args).await,
     }
 }
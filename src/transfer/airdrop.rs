@@ -0,0 +1,233 @@
+//! Requests airdrops for multiple recipients in parallel, retrying failed requests with a backoff.
+
+use std::{fs, path::Path, str::FromStr as _, time::Duration};
+
+use anyhow::{Context as _, Result, bail};
+use futures::{StreamExt as _, stream};
+use indicatif::{ProgressBar, ProgressStyle};
+use log::warn;
+use pythnet_heisenberg::{dry_run, output, rpc_client_ext::RpcClientExt as _};
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig, hash::Hash, native_token::Sol, pubkey::Pubkey,
+    signature::Signature,
+};
+use tokio::time::sleep;
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, transfer::airdrop::AirdropArgs},
+    exit_code::check_transactions,
+    transfer::faucet::request_airdrop_transaction,
+};
+
+/// How often to check the status of a sent airdrop transaction.
+const STATUS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub async fn run(
+    AirdropArgs {
+        json_rpc_url,
+        faucet,
+        lamports,
+        recipients_file,
+        max_parallel,
+        retries,
+        retry_delay,
+        mut recipients,
+    }: AirdropArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let rpc_client = &rpc_client;
+    let faucet = faucet.as_deref();
+    let retry_delay: Duration = retry_delay.into();
+
+    if let Some(recipients_file) = recipients_file {
+        recipients.extend(read_recipients_file(&recipients_file)?);
+    }
+
+    // Airdrops are requested from the faucet, so there are no transactions to describe.
+    if dry_run::is_enabled() {
+        for recipient in &recipients {
+            output::result(
+                format!("Would airdrop {} to {recipient}", Sol(lamports)),
+                json!({
+                    "recipient": recipient.to_string(),
+                    "lamports": lamports,
+                }),
+            );
+        }
+        return Err(dry_run::Stopped.into());
+    }
+
+    let progress_bar = ProgressBar::new(recipients.len() as u64);
+    progress_bar.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed}] {bar:40} {pos}/{len} {wide_msg}")
+            .expect("ProgressStyle::template direct input to be correct"),
+    );
+
+    let results = stream::iter(recipients)
+        .map(|recipient| {
+            let progress_bar = &progress_bar;
+            async move {
+                let res = airdrop_with_retries(
+                    rpc_client,
+                    faucet,
+                    recipient,
+                    lamports,
+                    retries,
+                    retry_delay,
+                )
+                .await;
+                progress_bar.inc(1);
+                (recipient, res)
+            }
+        })
+        .buffer_unordered(max_parallel)
+        .collect::<Vec<_>>()
+        .await;
+    progress_bar.finish_and_clear();
+
+    let mut failed = 0;
+    for (recipient, res) in &results {
+        match res {
+            Ok(signature) => output::result(
+                format!("Airdropped {} to {recipient}: {signature}", Sol(lamports)),
+                json!({
+                    "recipient": recipient.to_string(),
+                    "lamports": lamports,
+                    "signature": signature.to_string(),
+                }),
+            ),
+            Err(err) => {
+                failed += 1;
+                output::result(
+                    format!("Airdrop to {recipient} failed: {err:#}"),
+                    json!({
+                        "recipient": recipient.to_string(),
+                        "lamports": lamports,
+                        "error": format!("{err:#}"),
+                    }),
+                );
+            }
+        }
+    }
+
+    output::notice(format!(
+        "Airdrops succeeded: {}, failed: {failed}",
+        results.len() - failed,
+    ));
+
+    check_transactions(failed, results.len())?;
+
+    Ok(())
+}
+
+/// Reads a file with one pubkey per line.
+fn read_recipients_file(path: &Path) -> Result<Vec<Pubkey>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read recipients file: {}", path.to_string_lossy()))?;
+
+    let mut recipients = vec![];
+    for (line_no, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let recipient = Pubkey::from_str(line).with_context(|| {
+            format!(
+                "{}:{}: invalid pubkey: {line}",
+                path.to_string_lossy(),
+                line_no + 1
+            )
+        })?;
+        recipients.push(recipient);
+    }
+
+    Ok(recipients)
+}
+
+async fn airdrop_with_retries(
+    rpc_client: &RpcClient,
+    faucet: Option<&str>,
+    recipient: Pubkey,
+    lamports: u64,
+    retries: usize,
+    retry_delay: Duration,
+) -> Result<Signature> {
+    let mut delay = retry_delay;
+    let mut attempt = 0;
+    loop {
+        match airdrop(rpc_client, faucet, recipient, lamports).await {
+            Ok(signature) => return Ok(signature),
+            Err(err) if attempt < retries => {
+                warn!("Airdrop to {recipient} failed, retrying in {delay:?}: {err:#}");
+                sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(err) => {
+                return Err(err.context(format!("Giving up after {} attempts", attempt + 1)));
+            }
+        }
+    }
+}
+
+/// Requests a single airdrop and waits for it to be confirmed.
+async fn airdrop(
+    rpc_client: &RpcClient,
+    faucet: Option<&str>,
+    recipient: Pubkey,
+    lamports: u64,
+) -> Result<Signature> {
+    let blockhash = rpc_client.get_latest_blockhash_for_tx().await?;
+
+    let signature = match faucet {
+        None => rpc_client
+            .request_airdrop_with_blockhash(&recipient, lamports, &blockhash)
+            .await
+            .context("Airdrop request failed")?,
+        Some(faucet) => {
+            let transaction =
+                request_airdrop_transaction(faucet, recipient, lamports, blockhash).await?;
+            rpc_client
+                .send_transaction(&transaction)
+                .await
+                .context("Failed to send the faucet transaction")?
+        }
+    };
+
+    wait_for_confirmation(rpc_client, &signature, &blockhash).await?;
+
+    Ok(signature)
+}
+
+/// Waits until the transaction is confirmed, or until its blockhash expires.
+async fn wait_for_confirmation(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+    blockhash: &Hash,
+) -> Result<()> {
+    loop {
+        // Validity is checked before the status, so a transaction that landed right before the
+        // blockhash expired is not reported as expired.
+        let blockhash_valid = rpc_client
+            .is_blockhash_valid(blockhash, CommitmentConfig::processed())
+            .await
+            .context("Failed to check blockhash validity")?;
+
+        let status = rpc_client
+            .get_signature_status_with_commitment(signature, rpc_client.commitment())
+            .await
+            .with_context(|| format!("Failed to get the status of {signature}"))?;
+        match status {
+            Some(Ok(())) => return Ok(()),
+            Some(Err(err)) => bail!("Airdrop transaction {signature} failed: {err}"),
+            None if !blockhash_valid => bail!("Airdrop transaction {signature} expired"),
+            None => (),
+        }
+
+        sleep(STATUS_POLL_INTERVAL).await;
+    }
+}
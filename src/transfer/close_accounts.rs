@@ -0,0 +1,312 @@
+//! Closes system accounts, by transferring out their whole balance.  The counterpart of the commands
+//! that create accounts in bulk, like `create-nonce-accounts`.
+
+use std::{fs, path::Path};
+
+use anyhow::{Context as _, Result, bail};
+use itertools::{Itertools as _, izip};
+use pythnet_heisenberg::{
+    blockhash_cache::BlockhashCache,
+    dry_run,
+    keypair_ext::read_keypair_file,
+    output,
+    rpc_client_ext::RpcClientExt as _,
+    tx_sheppard::{TxOutcome, with_sheppard},
+};
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account,
+    native_token::Sol,
+    nonce::{State as NonceState, state::Versions as NonceVersions},
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer as _,
+    system_instruction, system_program,
+    transaction::Transaction,
+};
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, transfer::close_accounts::CloseAccountsArgs},
+    exit_code::check_outcomes,
+};
+
+struct Closure<'signer> {
+    account: Pubkey,
+    lamports: u64,
+    /// Nonce accounts are closed with a nonce withdrawal, rather than with a transfer.
+    is_nonce: bool,
+    /// The account itself, or the nonce authority, for the nonce accounts.
+    signer: &'signer Keypair,
+}
+
+pub async fn run(
+    CloseAccountsArgs {
+        json_rpc_url,
+        account_keypair,
+        payer_keypair,
+        nonce_authority_keypair,
+        to,
+        report_file,
+    }: CloseAccountsArgs,
+) -> Result<()> {
+    let dry_run = dry_run::is_enabled();
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let rpc_client = &rpc_client;
+
+    let payer = read_keypair_file(&payer_keypair)?;
+    let payer_pubkey = payer.pubkey();
+    let nonce_authority = nonce_authority_keypair.map(read_keypair_file).transpose()?;
+    let nonce_authority = nonce_authority.as_ref().unwrap_or(&payer);
+    let to = to.unwrap_or(payer_pubkey);
+
+    let accounts = account_keypair
+        .iter()
+        .map(read_keypair_file)
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .unique_by(|account| account.pubkey())
+        .collect::<Vec<_>>();
+    for account in &accounts {
+        let pubkey = account.pubkey();
+        if pubkey == payer_pubkey {
+            bail!("{pubkey} pays for the transactions, so it can not be closed");
+        }
+        if pubkey == to {
+            bail!("{pubkey} receives the reclaimed SOL, so it can not be closed");
+        }
+    }
+
+    let (_slot, states) = rpc_client
+        .get_multiple_accounts_chunked(
+            &accounts
+                .iter()
+                .map(|account| account.pubkey())
+                .collect_vec(),
+        )
+        .await
+        .context("Failed to read the accounts to close")?;
+
+    let mut closures = vec![];
+    for (keypair, state) in izip!(&accounts, states) {
+        let account = keypair.pubkey();
+        let Some(Account {
+            lamports,
+            data,
+            owner,
+            ..
+        }) = state
+        else {
+            eprintln!("{account} does not exist, skipping");
+            continue;
+        };
+
+        if owner != system_program::id() {
+            bail!("{account} is owned by {owner}.  Only system program accounts can be closed");
+        }
+
+        let closure = if data.is_empty() {
+            Closure {
+                account,
+                lamports,
+                is_nonce: false,
+                signer: keypair,
+            }
+        } else {
+            let signer = match nonce_authority_of(account, &data)? {
+                // Uninitialized nonce accounts are withdrawn from by the account itself.
+                None => keypair,
+                Some(authority) if authority == nonce_authority.pubkey() => nonce_authority,
+                Some(authority) => bail!(
+                    "{account} is a nonce account with a different authority: {authority}.  Use \
+                     --nonce-authority-keypair to specify its keypair"
+                ),
+            };
+            Closure {
+                account,
+                lamports,
+                is_nonce: true,
+                signer,
+            }
+        };
+        closures.push(closure);
+    }
+
+    if closures.is_empty() {
+        output::notice("Nothing to close");
+        return Ok(());
+    }
+
+    for Closure {
+        account, lamports, ..
+    } in &closures
+    {
+        eprintln!("Closing {account}, reclaiming {} ...", Sol(*lamports));
+    }
+
+    if dry_run {
+        return print_dry_run(rpc_client, payer_pubkey, &closures, to).await;
+    }
+
+    let outcomes = with_sheppard(rpc_client)
+        .labels(closures.iter().map(|Closure { account, .. }| account))
+        .run(
+            closures
+                .iter()
+                .map(|closure| close_tx(&payer, payer_pubkey, closure, to)),
+        )
+        .await
+        .context("Running account closing transactions")?;
+
+    let mut closed = 0;
+    let mut reclaimed = 0;
+    for (
+        Closure {
+            account, lamports, ..
+        },
+        outcome,
+    ) in izip!(&closures, &outcomes)
+    {
+        let result = match outcome {
+            TxOutcome::Success(signature) => {
+                closed += 1;
+                reclaimed += lamports;
+                json!({
+                    "account": account.to_string(),
+                    "lamports": lamports,
+                    "signature": signature.to_string(),
+                })
+            }
+            TxOutcome::Failed(error) => json!({
+                "account": account.to_string(),
+                "lamports": lamports,
+                "error": error,
+            }),
+        };
+        output::json_result(result);
+    }
+
+    output::result(
+        format!(
+            "Closed {closed} accounts, reclaimed {} into {to}",
+            Sol(reclaimed)
+        ),
+        json!({
+            "closed": closed,
+            "reclaimed_lamports": reclaimed,
+            "to": to.to_string(),
+        }),
+    );
+
+    if let Some(report_file) = report_file {
+        write_report_file(&report_file, &closures, &outcomes)?;
+    }
+
+    check_outcomes(&outcomes)?;
+
+    Ok(())
+}
+
+/// Authority of a nonce account, or `None` if the account is not initialized.  Fails for system
+/// accounts with data that is not a nonce state.
+pub(super) fn nonce_authority_of(account: Pubkey, data: &[u8]) -> Result<Option<Pubkey>> {
+    let (versions, _) =
+        bincode::serde::decode_from_slice::<NonceVersions, _>(data, bincode::config::legacy())
+            .with_context(|| {
+                format!(
+                    "{account} holds {} bytes of data, that is not a nonce account state",
+                    data.len()
+                )
+            })?;
+
+    match NonceState::from(versions) {
+        NonceState::Uninitialized => Ok(None),
+        NonceState::Initialized(data) => Ok(Some(data.authority)),
+    }
+}
+
+async fn print_dry_run(
+    rpc_client: &RpcClient,
+    payer_pubkey: Pubkey,
+    closures: &[Closure<'_>],
+    to: Pubkey,
+) -> Result<()> {
+    let lamports_per_signature = rpc_client
+        .get_lamports_per_signature()
+        .await
+        .context("Estimating transaction fees")?;
+
+    // The payer signs every transaction, and the account or the nonce authority signs too, unless
+    // it is the payer.
+    let signatures = closures
+        .iter()
+        .map(|Closure { signer, .. }| 1 + u64::from(signer.pubkey() != payer_pubkey))
+        .sum::<u64>();
+    let total = closures
+        .iter()
+        .map(|Closure { lamports, .. }| *lamports)
+        .sum::<u64>();
+
+    eprintln!(
+        "Dry run.  No transactions were sent.\n\
+         Accounts to be closed: {}\n\
+         Total to reclaim into {to}: {}\n\
+         Estimated fees: {}",
+        closures.len(),
+        Sol(total),
+        Sol(signatures * lamports_per_signature),
+    );
+
+    Ok(())
+}
+
+fn close_tx<'context>(
+    payer: &'context Keypair,
+    payer_pubkey: Pubkey,
+    Closure {
+        account,
+        lamports,
+        is_nonce,
+        signer,
+    }: &'context Closure<'context>,
+    to: Pubkey,
+) -> impl Fn(/* blockhash_cache: */ &BlockhashCache) -> Transaction + 'context {
+    move |blockhash_cache: &BlockhashCache| -> Transaction {
+        let instruction = if *is_nonce {
+            system_instruction::withdraw_nonce_account(account, &signer.pubkey(), &to, *lamports)
+        } else {
+            system_instruction::transfer(account, &to, *lamports)
+        };
+
+        Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer_pubkey),
+            &[payer, *signer],
+            blockhash_cache.get(),
+        )
+    }
+}
+
+fn write_report_file(path: &Path, closures: &[Closure], outcomes: &[TxOutcome]) -> Result<()> {
+    let mut content = "# account,lamports,signature,error\n".to_owned();
+    for (
+        Closure {
+            account, lamports, ..
+        },
+        outcome,
+    ) in izip!(closures, outcomes)
+    {
+        let line = match outcome {
+            TxOutcome::Success(signature) => format!("{account},{lamports},{signature},\n"),
+            // Errors are free form text, so make sure they do not break the CSV structure.
+            TxOutcome::Failed(error) => format!(
+                "{account},{lamports},,\"{}\"\n",
+                error.replace('"', "\"\"").replace('\n', " ")
+            ),
+        };
+        content.push_str(&line);
+    }
+
+    fs::write(path, content)
+        .with_context(|| format!("Failed to write report file: {}", path.to_string_lossy()))
+}
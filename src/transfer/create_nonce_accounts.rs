@@ -0,0 +1,229 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use anyhow::{Context as _, Result, bail};
+use itertools::{Itertools as _, izip};
+use pythnet_heisenberg::{
+    blockhash_cache::BlockhashCache,
+    dry_run,
+    keypair_ext::{read_keypair_file, read_or_generate_keypair_file},
+    output,
+    rpc_client_ext::RpcClientExt as _,
+    tx_sheppard::{TxOutcome, with_sheppard},
+};
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account, native_token::Sol, nonce::State as NonceState, pubkey::Pubkey, rent::Rent,
+    signature::Keypair, signer::Signer as _, system_instruction, system_program,
+    transaction::Transaction,
+};
+
+use crate::{
+    args::{
+        json_rpc_url_args::get_rpc_client, transfer::create_nonce_accounts::CreateNonceAccountsArgs,
+    },
+    exit_code::check_outcomes,
+    transfer::close_accounts::nonce_authority_of,
+};
+
+pub async fn run(
+    CreateNonceAccountsArgs {
+        json_rpc_url,
+        payer_keypair,
+        nonce_authority,
+        count,
+        keypair_dir,
+        lamports,
+        accounts_file,
+    }: CreateNonceAccountsArgs,
+) -> Result<()> {
+    let dry_run = dry_run::is_enabled();
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let rpc_client = &rpc_client;
+
+    let payer = read_keypair_file(&payer_keypair)?;
+    let payer_pubkey = payer.pubkey();
+
+    let nonce_authority = nonce_authority.unwrap_or(payer_pubkey);
+
+    let lamports = lamports.unwrap_or_else(|| Rent::default().minimum_balance(NonceState::size()));
+
+    if dry_run {
+        return print_dry_run(rpc_client, &keypair_dir, count, nonce_authority, lamports).await;
+    }
+
+    fs::create_dir_all(&keypair_dir).with_context(|| {
+        format!(
+            "Failed to create --keypair-dir: {}",
+            keypair_dir.to_string_lossy()
+        )
+    })?;
+
+    let nonces = (0..count)
+        .map(|i| read_or_generate_keypair_file(keypair_dir.join(format!("nonce-{i}.json"))))
+        .collect::<Result<Vec<_>>>()?;
+
+    let (_slot, existing) = rpc_client
+        .get_multiple_accounts_chunked(&nonces.iter().map(|nonce| nonce.pubkey()).collect_vec())
+        .await
+        .context("Failed to read the nonce accounts")?;
+
+    // Accounts that are nonce accounts with the expected authority once the command is done.
+    let mut ready = HashSet::new();
+    let mut to_create = vec![];
+    for (nonce, account) in izip!(&nonces, existing) {
+        let address = nonce.pubkey();
+        let Some(Account { data, owner, .. }) = account else {
+            to_create.push(nonce);
+            continue;
+        };
+
+        if owner != system_program::id() || data.is_empty() {
+            bail!("{address} already exists, and is not a nonce account");
+        }
+        match nonce_authority_of(address, &data)? {
+            Some(authority) if authority == nonce_authority => {
+                eprintln!("{address} is already a nonce account, skipping");
+                ready.insert(address);
+            }
+            Some(authority) => bail!(
+                "{address} is already a nonce account with a different authority: {authority}"
+            ),
+            None => bail!("{address} already exists, and is not an initialized nonce account"),
+        }
+    }
+
+    output::notice(format!(
+        "Creating {} nonce accounts with {} each, authority: {} ...",
+        to_create.len(),
+        Sol(lamports),
+        nonce_authority,
+    ));
+
+    let outcomes = with_sheppard(rpc_client)
+        .labels(to_create.iter().map(|nonce| nonce.pubkey()))
+        .run(to_create.iter().map(|nonce| {
+            create_nonce_account_tx(&payer, payer_pubkey, nonce, nonce_authority, lamports)
+        }))
+        .await
+        .context("Running nonce account creation transactions")?;
+
+    for (nonce, outcome) in izip!(&to_create, &outcomes) {
+        let result = match outcome {
+            TxOutcome::Success(signature) => {
+                ready.insert(nonce.pubkey());
+                json!({
+                    "nonce_account": nonce.pubkey().to_string(),
+                    "signature": signature.to_string(),
+                })
+            }
+            TxOutcome::Failed(error) => json!({
+                "nonce_account": nonce.pubkey().to_string(),
+                "error": error,
+            }),
+        };
+        output::json_result(result);
+    }
+
+    // Accounts that failed to be created are left out, so that the file only lists usable nonce
+    // accounts.  A rerun retries them.
+    let accounts_file = accounts_file.unwrap_or_else(|| keypair_dir.join("nonce-accounts.txt"));
+    let accounts = nonces
+        .iter()
+        .map(|nonce| nonce.pubkey())
+        .filter(|address| ready.contains(address))
+        .map(|address| format!("{address}\n"))
+        .join("");
+    fs::write(&accounts_file, accounts).with_context(|| {
+        format!(
+            "Failed to write nonce account addresses into: {}",
+            accounts_file.to_string_lossy()
+        )
+    })?;
+
+    output::result(
+        format!(
+            "{} nonce account addresses written to: {}",
+            ready.len(),
+            accounts_file.to_string_lossy()
+        ),
+        json!({
+            "accounts_file": accounts_file.to_string_lossy(),
+            "accounts": ready.len(),
+        }),
+    );
+
+    check_outcomes(&outcomes)?;
+
+    Ok(())
+}
+
+async fn print_dry_run(
+    rpc_client: &RpcClient,
+    keypair_dir: &Path,
+    count: usize,
+    nonce_authority: Pubkey,
+    lamports: u64,
+) -> Result<()> {
+    for i in 0..count {
+        let path = keypair_dir.join(format!("nonce-{i}.json"));
+        if path.exists() {
+            let nonce = read_keypair_file(&path)?;
+            eprintln!(
+                "Creating {} with a balance of {} ...",
+                nonce.pubkey(),
+                Sol(lamports)
+            );
+        } else {
+            eprintln!(
+                "Creating a new account, to be written into {}, with a balance of {} ...",
+                path.to_string_lossy(),
+                Sol(lamports),
+            );
+        }
+    }
+
+    let lamports_per_signature = rpc_client
+        .get_lamports_per_signature()
+        .await
+        .context("Estimating transaction fees")?;
+
+    // Each transaction is signed by the payer and the new nonce account.
+    let signatures = 2 * count as u64;
+
+    eprintln!(
+        "Dry run.  No transactions were sent.\n\
+         Nonce authority: {}\n\
+         Accounts to be created: {}\n\
+         Total to transfer: {}\n\
+         Estimated fees: {}",
+        nonce_authority,
+        count,
+        Sol(lamports * count as u64),
+        Sol(signatures * lamports_per_signature),
+    );
+
+    Ok(())
+}
+
+fn create_nonce_account_tx<'context>(
+    payer: &'context Keypair,
+    payer_pubkey: Pubkey,
+    nonce: &'context Keypair,
+    nonce_authority: Pubkey,
+    lamports: u64,
+) -> impl Fn(/* blockhash_cache: */ &BlockhashCache) -> Transaction + 'context {
+    move |blockhash_cache: &BlockhashCache| -> Transaction {
+        Transaction::new_signed_with_payer(
+            &system_instruction::create_nonce_account(
+                &payer_pubkey,
+                &nonce.pubkey(),
+                &nonce_authority,
+                lamports,
+            ),
+            Some(&payer_pubkey),
+            &[payer, nonce],
+            blockhash_cache.get(),
+        )
+    }
+}
@@ -0,0 +1,94 @@
+//! `solana-faucet` pins an exact `solana-sdk` version, and the faucet protocol is trivial, so the
+//! client side is implemented here directly.
+//!
+//! A client sends a bincode serialized [`FaucetRequest`] over TCP.  The faucet responds with a
+//! two byte little endian length, followed by a bincode serialized transaction, signed by the
+//! faucet.  The client is responsible for sending this transaction to the cluster.  A zero length
+//! means the faucet refused the request.
+
+use std::time::Duration;
+
+use anyhow::{Context as _, Result, bail};
+use bincode::serde::{decode_from_slice, encode_to_vec};
+use serde::Serialize;
+use solana_sdk::{hash::Hash, packet::PACKET_DATA_SIZE, pubkey::Pubkey, transaction::Transaction};
+use tokio::{
+    io::{AsyncReadExt as _, AsyncWriteExt as _},
+    net::TcpStream,
+    time::timeout,
+};
+
+/// Same limits as the `solana-faucet` client uses.  An unresponsive faucet fails the request,
+/// rather than blocking it forever.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize)]
+enum FaucetRequest {
+    GetAirdrop {
+        lamports: u64,
+        to: Pubkey,
+        blockhash: Hash,
+    },
+}
+
+/// Asks the faucet at `faucet_addr` for a transaction that transfers `lamports` to `to`.
+pub async fn request_airdrop_transaction(
+    faucet_addr: &str,
+    to: Pubkey,
+    lamports: u64,
+    blockhash: Hash,
+) -> Result<Transaction> {
+    let request = encode_to_vec(
+        FaucetRequest::GetAirdrop {
+            lamports,
+            to,
+            blockhash,
+        },
+        bincode::config::legacy(),
+    )
+    .expect("Faucet request serializes");
+
+    let mut stream = timeout(CONNECT_TIMEOUT, TcpStream::connect(faucet_addr))
+        .await
+        .with_context(|| {
+            format!(
+                "Timed out connecting to the faucet at {faucet_addr}, after {}",
+                humantime::format_duration(CONNECT_TIMEOUT)
+            )
+        })?
+        .with_context(|| format!("Failed to connect to the faucet at {faucet_addr}"))?;
+    stream
+        .write_all(&request)
+        .await
+        .context("Failed to send a faucet request")?;
+
+    let read_timed_out = || {
+        format!(
+            "Timed out waiting for a faucet response, after {}",
+            humantime::format_duration(READ_TIMEOUT)
+        )
+    };
+
+    let len = timeout(READ_TIMEOUT, stream.read_u16_le())
+        .await
+        .with_context(read_timed_out)?
+        .context("Failed to read a faucet response")?;
+    let len = usize::from(len);
+    if len == 0 {
+        bail!("Faucet refused to airdrop {lamports} lamports to {to}");
+    }
+    if len > PACKET_DATA_SIZE {
+        bail!("Faucet response is too long: {len} bytes");
+    }
+
+    let mut buf = vec![0; len];
+    timeout(READ_TIMEOUT, stream.read_exact(&mut buf))
+        .await
+        .with_context(read_timed_out)?
+        .context("Failed to read a faucet response")?;
+
+    let (transaction, _) = decode_from_slice(&buf, bincode::config::legacy())
+        .context("Failed to deserialize the faucet transaction")?;
+    Ok(transaction)
+}
@@ -1,18 +1,35 @@
-use anyhow::{Context as _, Result};
-use futures::future::join_all;
-use solana_account_decoder::UiDataSliceConfig;
+use std::{
+    cmp,
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+    str::FromStr as _,
+};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use itertools::izip;
+use pythnet_heisenberg::{
+    blockhash_cache::BlockhashCache,
+    dry_run,
+    keypair_ext::read_keypair_file,
+    output,
+    rpc_client_ext::{RpcClientExt as _, TypedAccount},
+    tx_sheppard::{TxOutcome, with_sheppard},
+};
+use serde_json::json;
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
-use solana_rpc_client_api::config::RpcAccountInfoConfig;
 use solana_sdk::{
-    account::Account, native_token::Sol, pubkey::Pubkey, signature::Keypair, signer::Signer as _,
-    system_instruction, transaction::Transaction,
+    native_token::Sol, pubkey::Pubkey, signature::Keypair, signer::Signer as _, system_instruction,
+    transaction::Transaction,
 };
 
 use crate::{
-    args::{json_rpc_url_args::get_rpc_client, transfer::fill_up_to::FillUpToArgs},
-    blockhash_cache::BlockhashCache,
-    keypair_ext::read_keypair_file,
-    tx_sheppard::with_sheppard,
+    args::{
+        json_rpc_url_args::get_rpc_client,
+        transfer::fill_up_to::{FillUpToArgs, FundingOrder, TargetBalance, target_balance_parser},
+    },
+    exit_code::{ValidationFailed, check_outcomes},
+    transfer::memo::memo_instruction,
 };
 
 pub async fn run(
@@ -21,11 +38,16 @@ pub async fn run(
         signer_keypair,
         payer_keypair,
         from_keypair,
+        funding_order,
         target_balance,
+        targets_file,
         print_target_increments,
+        memo,
+        report_file,
         recepients,
     }: FillUpToArgs,
 ) -> Result<()> {
+    let dry_run = dry_run::is_enabled();
     let rpc_client = get_rpc_client(json_rpc_url);
     let rpc_client = &rpc_client;
 
@@ -35,29 +57,41 @@ pub async fn run(
     let payer = payer.as_ref().unwrap_or(&signer);
     let payer_pubkey = payer.pubkey();
 
-    let from = from_keypair.map(read_keypair_file).transpose()?;
-    let from = from.as_ref().unwrap_or(payer);
-    let from_pubkey = from.pubkey();
+    let from_keypairs = from_keypair
+        .iter()
+        .map(read_keypair_file)
+        .collect::<Result<Vec<_>>>()?;
+    let from = if from_keypairs.is_empty() {
+        vec![payer]
+    } else {
+        from_keypairs.iter().collect::<Vec<_>>()
+    };
+    let from = &from;
 
-    let actions = join_all(
-        recepients
-            .into_iter()
-            .map(|recepient| calculate_account_action(rpc_client, recepient, target_balance)),
-    )
-    .await
-    .into_iter()
-    .filter(|action_or_err| {
-        // Keep errors.
-        let Ok(AccountAction { add_lamports, .. }) = action_or_err else {
-            return true;
+    let targets = {
+        // `check_are_valid()` makes sure `--target-balance` is present when `recepients` is not
+        // empty.
+        let mut targets = match target_balance {
+            Some(target_balance) => recepients
+                .into_iter()
+                .map(|recepient| (recepient, target_balance, "command line".to_owned()))
+                .collect::<Vec<_>>(),
+            None => vec![],
         };
+        if let Some(targets_file) = targets_file {
+            targets.extend(read_targets_file(&targets_file)?);
+        }
+        check_unique_targets(&targets).context(ValidationFailed)?;
+        let targets = targets
+            .into_iter()
+            .map(|(recepient, target_balance, _location)| (recepient, target_balance))
+            .collect();
+        resolve_target_balances(rpc_client, targets).await?
+    };
 
-        // But skip any accounts that have enough already.
-        *add_lamports > 0
-    })
-    .collect::<Result<Vec<_>>>()?;
+    let actions = calculate_account_actions(rpc_client, &targets).await?;
 
-    if print_target_increments {
+    if print_target_increments || dry_run {
         print_account_actions(&actions);
     }
 
@@ -65,66 +99,281 @@ pub async fn run(
         .iter()
         .map(|AccountAction { add_lamports, .. }| *add_lamports)
         .sum::<u64>();
-    if !from_account_has_enough_balance(rpc_client, from_pubkey, minimum_balance).await? {
+    // Every transaction is signed by the signer, the payer, and the from accounts it draws on.
+    // Without knowing the draws yet, count all of them.
+    let signers_per_tx = [signer.pubkey(), payer_pubkey]
+        .into_iter()
+        .chain(from.iter().map(|from| from.pubkey()))
+        .collect::<HashSet<_>>()
+        .len();
+    let from_balances = from_accounts_have_enough_balance(
+        rpc_client,
+        &from.iter().map(|from| from.pubkey()).collect::<Vec<_>>(),
+        payer_pubkey,
+        (actions.len() * signers_per_tx) as u64,
+        minimum_balance,
+    )
+    .await?;
+
+    // Without enough funds the draws can not be planned.  A dry run still shows the summary, with
+    // the fees estimated as if every transfer drew on the first from account.
+    let draws = match &from_balances {
+        Some(from_balances) => assign_from_accounts(
+            &actions,
+            from_budgets(funding_order, from_balances, minimum_balance),
+        ),
+        None => single_source_draws(&actions),
+    };
+
+    if (print_target_increments || dry_run) && from.len() > 1 && from_balances.is_some() {
+        print_from_totals(from, &draws);
+    }
+
+    if dry_run {
+        print_dry_run_summary(
+            rpc_client,
+            signer.pubkey(),
+            payer_pubkey,
+            from,
+            &actions,
+            &draws,
+        )
+        .await?;
+    }
+
+    if from_balances.is_none() {
+        return Err(anyhow!(
+            "From accounts do not have enough funds to top up all the recipients"
+        ))
+        .context(ValidationFailed);
+    }
+
+    if dry_run {
         return Ok(());
     }
 
-    with_sheppard(rpc_client)
-        .run(
+    let memo = memo.as_deref();
+    let outcomes = with_sheppard(rpc_client)
+        .labels(
             actions
                 .iter()
-                .map(|action| fill_up_tx(&signer, payer, payer_pubkey, from, from_pubkey, action)),
+                .map(|AccountAction { recepient, .. }| recepient),
         )
+        .run(izip!(&actions, &draws).map(|(action, draws)| {
+            fill_up_tx(&signer, payer, payer_pubkey, from, action, draws, memo)
+        }))
         .await
         .with_context(|| "Running transfer transactions".to_owned())?;
 
-    Ok(())
-}
+    print_transfer_results(&actions, &outcomes);
+
+    if let Some(report_file) = report_file {
+        write_report_file(&report_file, &actions, &outcomes)?;
+    }
 
-struct AccountAction {
-    recepient: Pubkey,
-    create: bool,
-    add_lamports: u64,
+    check_outcomes(&outcomes)?;
+
+    Ok(())
 }
 
-async fn calculate_account_action(
+/// Tops up each of the `targets` to its target balance, drawing from the `from` account, which
+/// also pays the fees.  For commands that need funded accounts before they can start.
+pub async fn top_up(
     rpc_client: &RpcClient,
-    recepient: Pubkey,
-    target_balance: u64,
-) -> Result<AccountAction> {
-    // TODO It would be more efficient to use `get_multiple_accounts_with_config()`.  Note that it
-    // requires pagination, as the method can query only up to 100 addresses per request.
-    let account = rpc_client
-        .get_account_with_config(
-            &recepient,
-            RpcAccountInfoConfig {
-                data_slice: Some(UiDataSliceConfig {
-                    offset: 0,
-                    length: 0,
-                }),
-                ..RpcAccountInfoConfig::default()
-            },
+    from: &Keypair,
+    targets: &[(Pubkey, u64)],
+) -> Result<()> {
+    let from_pubkey = from.pubkey();
+
+    let actions = calculate_account_actions(rpc_client, targets).await?;
+    if actions.is_empty() {
+        return Ok(());
+    }
+    print_account_actions(&actions);
+
+    let minimum_balance = actions
+        .iter()
+        .map(|AccountAction { add_lamports, .. }| *add_lamports)
+        .sum::<u64>();
+    if from_accounts_have_enough_balance(
+        rpc_client,
+        &[from_pubkey],
+        from_pubkey,
+        actions.len() as u64,
+        minimum_balance,
+    )
+    .await?
+    .is_none()
+    {
+        bail!("{from_pubkey} does not have enough funds to top up all the accounts");
+    }
+
+    let from = [from];
+    let draws = single_source_draws(&actions);
+    let outcomes = with_sheppard(rpc_client)
+        .labels(
+            actions
+                .iter()
+                .map(|AccountAction { recepient, .. }| recepient),
         )
+        .run(izip!(&actions, &draws).map(|(action, draws)| {
+            fill_up_tx(from[0], from[0], from_pubkey, &from, action, draws, None)
+        }))
         .await
-        .with_context(|| format!("Reading account data for {recepient}"))?
-        .value;
+        .context("Running transfer transactions")?;
 
-    let Some(Account { lamports, .. }) = account else {
-        return Ok(AccountAction {
-            recepient,
-            create: true,
-            add_lamports: target_balance,
-        });
+    print_transfer_results(&actions, &outcomes);
+
+    check_outcomes(&outcomes)?;
+
+    Ok(())
+}
+
+/// Reads a CSV file with "[pubkey],[target lamports]" lines.  Every target comes with its location
+/// in the file, as "[path]:[line]".
+fn read_targets_file(path: &Path) -> Result<Vec<(Pubkey, TargetBalance, String)>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read targets file: {}", path.to_string_lossy()))?;
+
+    let mut targets = vec![];
+    for (line_no, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let context = || format!("{}:{}", path.to_string_lossy(), line_no + 1);
+
+        let Some((pubkey, target_balance)) = line.split_once(',') else {
+            bail!(
+                "{}: expected \"[pubkey],[target lamports]\", got: {line}",
+                context()
+            );
+        };
+
+        let pubkey = Pubkey::from_str(pubkey.trim())
+            .with_context(|| format!("{}: invalid pubkey: {}", context(), pubkey.trim()))?;
+        let target_balance = target_balance_parser(target_balance.trim())
+            .map_err(|err| anyhow!("{}: invalid target balance: {err}", context()))?;
+
+        targets.push((pubkey, target_balance, context()));
+    }
+
+    Ok(targets)
+}
+
+/// Fails if any account is listed more than once.  Top ups are computed from the current balance,
+/// so every entry would add to the same account independently, funding it above its target.
+fn check_unique_targets(targets: &[(Pubkey, TargetBalance, String)]) -> Result<()> {
+    let mut first_seen = HashMap::new();
+    for (recepient, _target_balance, location) in targets {
+        if let Some(first) = first_seen.insert(recepient, location) {
+            bail!("{location}: {recepient} is already listed as a target in: {first}");
+        }
+    }
+    Ok(())
+}
+
+/// Computes lamport amounts for the `rent-exempt+<lamports>` targets, from the current data sizes
+/// of the target accounts.
+async fn resolve_target_balances(
+    rpc_client: &RpcClient,
+    targets: Vec<(Pubkey, TargetBalance)>,
+) -> Result<Vec<(Pubkey, u64)>> {
+    let rent_exempt_targets = targets
+        .iter()
+        .filter(|(_, target)| matches!(target, TargetBalance::RentExemptPlus(_)))
+        .map(|(recepient, _)| *recepient)
+        .collect::<Vec<_>>();
+
+    // Accounts that do not exist yet are funded as accounts with no data.
+    let data_lens = if rent_exempt_targets.is_empty() {
+        vec![]
+    } else {
+        rpc_client
+            .get_account_data_lens(&rent_exempt_targets)
+            .await
+            .context("Reading target account sizes")?
+            .into_iter()
+            .map(|data_len| data_len.unwrap_or(0))
+            .collect::<Vec<_>>()
     };
 
-    Ok(AccountAction {
-        recepient,
-        create: false,
-        add_lamports: target_balance.saturating_sub(lamports),
-    })
+    let mut minimum_balances = HashMap::new();
+    for &data_len in &data_lens {
+        if minimum_balances.contains_key(&data_len) {
+            continue;
+        }
+        let minimum_balance = rpc_client
+            .get_minimum_balance_for_rent_exemption(data_len)
+            .await
+            .with_context(|| {
+                format!("Getting the rent exempt minimum for an account of {data_len} bytes")
+            })?;
+        minimum_balances.insert(data_len, minimum_balance);
+    }
+
+    let mut data_lens = data_lens.into_iter();
+    let targets = targets
+        .into_iter()
+        .map(|(recepient, target)| match target {
+            TargetBalance::Lamports(lamports) => (recepient, lamports),
+            TargetBalance::RentExemptPlus(extra) => {
+                let data_len = data_lens
+                    .next()
+                    .expect("There is a data size for every rent exempt target");
+                (recepient, minimum_balances[&data_len].saturating_add(extra))
+            }
+        })
+        .collect();
+
+    Ok(targets)
 }
 
-fn print_account_actions(actions: &[AccountAction]) {
+pub(super) struct AccountAction {
+    pub recepient: Pubkey,
+    pub create: bool,
+    pub add_lamports: u64,
+}
+
+/// Computes transfers required to bring each target account to the target balance.  Accounts that
+/// already have enough are skipped.
+pub(super) async fn calculate_account_actions(
+    rpc_client: &RpcClient,
+    targets: &[(Pubkey, u64)],
+) -> Result<Vec<AccountAction>> {
+    let recepients = targets
+        .iter()
+        .map(|(recepient, _)| *recepient)
+        .collect::<Vec<_>>();
+
+    // Only the balances are needed.
+    let accounts = rpc_client
+        .get_accounts_chunked::<()>(&recepients)
+        .await
+        .context("Reading target account balances")?;
+
+    let actions = izip!(targets, accounts)
+        .map(|(&(recepient, target_balance), account)| match account {
+            None => AccountAction {
+                recepient,
+                create: true,
+                add_lamports: target_balance,
+            },
+            Some(TypedAccount { lamports, .. }) => AccountAction {
+                recepient,
+                create: false,
+                add_lamports: target_balance.saturating_sub(lamports),
+            },
+        })
+        // Skip any accounts that have enough already.
+        .filter(|AccountAction { add_lamports, .. }| *add_lamports > 0)
+        .collect();
+
+    Ok(actions)
+}
+
+pub(super) fn print_account_actions(actions: &[AccountAction]) {
     for AccountAction {
         recepient,
         create,
@@ -147,57 +396,320 @@ fn print_account_actions(actions: &[AccountAction]) {
     }
 }
 
-async fn from_account_has_enough_balance(
+/// All transfers are drawn from a single from account.
+pub(super) fn single_source_draws(actions: &[AccountAction]) -> Vec<Vec<(usize, u64)>> {
+    actions
+        .iter()
+        .map(|AccountAction { add_lamports, .. }| vec![(0, *add_lamports)])
+        .collect()
+}
+
+/// Balance of a `from` account, and the part of it the transfers can not take.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct FromBalance {
+    balance: u64,
+    /// The rent exempt minimum, as a partially drained account has to stay rent exempt.  The
+    /// payer also keeps the fees for all the transactions.
+    reserve: u64,
+    is_payer: bool,
+}
+
+impl FromBalance {
+    /// Lamports the transfers can take, while leaving the account rent exempt.
+    fn spendable(&self) -> u64 {
+        self.balance.saturating_sub(self.reserve)
+    }
+}
+
+/// Checks that all the `from` accounts together can spend at least `minimum_balance` lamports,
+/// while staying rent exempt, with the `payer` also keeping enough to pay for `signatures`.
+/// Returns balances of individual `from` accounts, if they can, and `None` otherwise.
+pub(super) async fn from_accounts_have_enough_balance(
     rpc_client: &RpcClient,
-    from: Pubkey,
+    from: &[Pubkey],
+    payer: Pubkey,
+    signatures: u64,
     minimum_balance: u64,
-) -> Result<bool> {
-    let account = rpc_client
-        .get_account_with_config(
-            &from,
-            RpcAccountInfoConfig {
-                data_slice: Some(UiDataSliceConfig {
-                    offset: 0,
-                    length: 0,
-                }),
-                ..RpcAccountInfoConfig::default()
-            },
-        )
+) -> Result<Option<Vec<FromBalance>>> {
+    let accounts = rpc_client
+        .get_accounts_chunked::<()>(from)
         .await
-        .with_context(|| format!("Reading account data for {from}"))?
-        .value;
+        .context("Reading from accounts data")?;
 
-    let Some(Account { lamports, .. }) = account else {
-        eprintln!("From account ({from}) does not exist");
-        return Ok(false);
-    };
+    // Transfers can only draw from accounts without data.
+    let rent_exempt_minimum = rpc_client
+        .get_minimum_balance_for_rent_exemption(0)
+        .await
+        .context("Getting the rent exempt minimum for the from accounts")?;
+    let fees = signatures.saturating_mul(
+        rpc_client
+            .get_lamports_per_signature()
+            .await
+            .context("Estimating transaction fees")?,
+    );
 
-    if lamports < minimum_balance {
-        eprintln!(
-            "From account ({}) balance is below the required minimum balance.\n\
-             Current balance: {}\n\
-             Minimum required to cover all the recipients: {}",
-            from,
-            Sol(lamports),
-            Sol(minimum_balance),
-        );
-        return Ok(false);
+    let balances = izip!(from, accounts)
+        .map(|(from, account)| {
+            let balance = match account {
+                Some(TypedAccount { lamports, .. }) => lamports,
+                None => {
+                    eprintln!("From account ({from}) does not exist");
+                    0
+                }
+            };
+            let is_payer = *from == payer;
+            FromBalance {
+                balance,
+                reserve: rent_exempt_minimum + if is_payer { fees } else { 0 },
+                is_payer,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let total_spendable = balances.iter().map(FromBalance::spendable).sum::<u64>();
+    if total_spendable < minimum_balance {
+        let reserve = balances.iter().map(|balance| balance.reserve).sum::<u64>();
+        if from.len() == 1 {
+            eprintln!(
+                "From account ({}) balance is below the required minimum balance.\n\
+                 Current balance: {}\n\
+                 Kept for rent and fees: {}\n\
+                 Minimum required to cover all the recipients: {}",
+                from[0],
+                Sol(balances[0].balance),
+                Sol(reserve),
+                Sol(minimum_balance),
+            );
+        } else {
+            eprintln!(
+                "Combined balance of {} from accounts is below the required minimum balance.\n\
+                 Current combined balance: {}\n\
+                 Kept for rent and fees: {}\n\
+                 Minimum required to cover all the recipients: {}",
+                from.len(),
+                Sol(balances.iter().map(|balance| balance.balance).sum::<u64>()),
+                Sol(reserve),
+                Sol(minimum_balance),
+            );
+        }
+        return Ok(None);
+    }
+
+    Ok(Some(balances))
+}
+
+/// Computes how much each `from` account should contribute in total, in order to cover
+/// `required` lamports.
+///
+/// Accounts either keep their reserve, or, when a whole balance is needed, are drained completely.
+/// The payer is never drained, as it still needs to pay the fees.
+///
+/// Caller guarantees that the spendable `balances` add up to at least `required`.
+fn from_budgets(funding_order: FundingOrder, balances: &[FromBalance], required: u64) -> Vec<u64> {
+    match funding_order {
+        // Later accounts are only used when the earlier ones are exhausted.
+        FundingOrder::InOrder => {
+            let mut left = required;
+            let budgets = balances
+                .iter()
+                .map(|from| {
+                    let budget = if !from.is_payer && left >= from.balance {
+                        from.balance
+                    } else {
+                        cmp::min(left, from.spendable())
+                    };
+                    left -= budget;
+                    budget
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(left, 0, "`balances` must cover `required`");
+
+            budgets
+        }
+        FundingOrder::Proportional => {
+            let total_spendable = balances
+                .iter()
+                .map(|from| u128::from(from.spendable()))
+                .sum::<u128>();
+            let mut budgets = balances
+                .iter()
+                .map(|from| {
+                    let budget =
+                        u128::from(required) * u128::from(from.spendable()) / total_spendable;
+                    u64::try_from(budget).expect("`budget` is at most the spendable balance")
+                })
+                .collect::<Vec<_>>();
+
+            // Rounding down may leave a few lamports unassigned.  Take them from whoever still has
+            // some spendable balance left.
+            let mut unassigned = required - budgets.iter().sum::<u64>();
+            for (budget, from) in izip!(&mut budgets, balances) {
+                let extra = cmp::min(unassigned, from.spendable() - *budget);
+                *budget += extra;
+                unassigned -= extra;
+            }
+            assert_eq!(unassigned, 0, "`balances` must cover `required`");
+
+            budgets
+        }
+    }
+}
+
+/// Splits each action into one or more transfers, drawing on the `from` accounts, in order, within
+/// the specified `budgets`.  Returns `(from index, lamports)` pairs for each action.
+fn assign_from_accounts(
+    actions: &[AccountAction],
+    mut budgets: Vec<u64>,
+) -> Vec<Vec<(usize, u64)>> {
+    let mut from_idx = 0;
+    actions
+        .iter()
+        .map(|AccountAction { add_lamports, .. }| {
+            let mut draws = vec![];
+            let mut left = *add_lamports;
+            while left > 0 {
+                let draw = cmp::min(left, budgets[from_idx]);
+                if draw > 0 {
+                    draws.push((from_idx, draw));
+                    budgets[from_idx] -= draw;
+                    left -= draw;
+                }
+                if budgets[from_idx] == 0 {
+                    from_idx += 1;
+                }
+            }
+            draws
+        })
+        .collect()
+}
+
+fn print_from_totals(from: &[&Keypair], draws: &[Vec<(usize, u64)>]) {
+    let mut totals = vec![0u64; from.len()];
+    for (from_idx, lamports) in draws.iter().flatten() {
+        totals[*from_idx] += lamports;
+    }
+
+    for (from, total) in izip!(from, totals) {
+        eprintln!("Drawing {} from {} ...", Sol(total), from.pubkey());
+    }
+}
+
+/// In the JSON output mode, prints the outcome of every transfer.  In the text mode the progress bar
+/// already shows the transfer progress.
+pub(super) fn print_transfer_results(actions: &[AccountAction], outcomes: &[TxOutcome]) {
+    for (
+        AccountAction {
+            recepient,
+            add_lamports,
+            ..
+        },
+        outcome,
+    ) in izip!(actions, outcomes)
+    {
+        let result = match outcome {
+            TxOutcome::Success(signature) => json!({
+                "recipient": recepient.to_string(),
+                "lamports": add_lamports,
+                "signature": signature.to_string(),
+            }),
+            TxOutcome::Failed(error) => json!({
+                "recipient": recepient.to_string(),
+                "lamports": add_lamports,
+                "error": error,
+            }),
+        };
+        output::json_result(result);
+    }
+}
+
+/// Writes a CSV file with "[recipient],[lamports],[signature],[error]" lines, one for each action.
+fn write_report_file(path: &Path, actions: &[AccountAction], outcomes: &[TxOutcome]) -> Result<()> {
+    let mut content = "# recipient,lamports,signature,error\n".to_owned();
+    for (
+        AccountAction {
+            recepient,
+            add_lamports,
+            ..
+        },
+        outcome,
+    ) in izip!(actions, outcomes)
+    {
+        let line = match outcome {
+            TxOutcome::Success(signature) => format!("{recepient},{add_lamports},{signature},\n"),
+            // Errors are free form text, so make sure they do not break the CSV structure.
+            TxOutcome::Failed(error) => format!(
+                "{recepient},{add_lamports},,\"{}\"\n",
+                error.replace('"', "\"\"").replace('\n', " ")
+            ),
+        };
+        content.push_str(&line);
     }
 
-    Ok(true)
+    fs::write(path, content)
+        .with_context(|| format!("Failed to write report file: {}", path.to_string_lossy()))
 }
 
-fn fill_up_tx<'context>(
+/// Prints totals for the planned transfers, including the estimated transaction fees.
+pub(super) async fn print_dry_run_summary(
+    rpc_client: &RpcClient,
+    signer_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+    from: &[&Keypair],
+    actions: &[AccountAction],
+    draws: &[Vec<(usize, u64)>],
+) -> Result<()> {
+    let lamports_per_signature = rpc_client
+        .get_lamports_per_signature()
+        .await
+        .context("Estimating transaction fees")?;
+
+    // Each transfer transaction is signed by the signer, the payer, and by all the from accounts it
+    // draws on.
+    let signatures = draws
+        .iter()
+        .map(|draws| {
+            let mut signers = HashSet::from([signer_pubkey, payer_pubkey]);
+            signers.extend(draws.iter().map(|(from_idx, _)| from[*from_idx].pubkey()));
+            signers.len() as u64
+        })
+        .sum::<u64>();
+
+    let created = actions
+        .iter()
+        .filter(|AccountAction { create, .. }| *create)
+        .count();
+    let total = actions
+        .iter()
+        .map(|AccountAction { add_lamports, .. }| *add_lamports)
+        .sum::<u64>();
+
+    eprintln!(
+        "Dry run.  No transactions were sent.\n\
+         Transactions: {}\n\
+         Accounts to be created: {}\n\
+         Total to transfer: {}\n\
+         Estimated fees: {}",
+        actions.len(),
+        created,
+        Sol(total),
+        Sol(signatures * lamports_per_signature),
+    );
+
+    Ok(())
+}
+
+pub(super) fn fill_up_tx<'context>(
     signer: &'context Keypair,
     payer: &'context Keypair,
     payer_pubkey: Pubkey,
-    from: &'context Keypair,
-    from_pubkey: Pubkey,
+    from: &'context [&'context Keypair],
     AccountAction {
         recepient,
         create: _,
         add_lamports,
     }: &'context AccountAction,
+    draws: &'context [(usize, u64)],
+    memo: Option<&'context str>,
 ) -> impl Fn(/* blockhash_cache: */ &BlockhashCache) -> Transaction + 'context {
     move |blockhash_cache: &BlockhashCache| -> Transaction {
         assert!(
@@ -205,16 +717,22 @@ fn fill_up_tx<'context>(
             "`add_lamports` must be strictly positive when constructing a fill up transaction"
         );
 
+        let mut instructions = draws
+            .iter()
+            .map(|(from_idx, lamports)| {
+                system_instruction::transfer(&from[*from_idx].pubkey(), recepient, *lamports)
+            })
+            .collect::<Vec<_>>();
+        instructions.extend(memo.map(memo_instruction));
+
+        let mut signers = vec![signer, payer];
+        signers.extend(draws.iter().map(|(from_idx, _)| from[*from_idx]));
+
         Transaction::new_signed_with_payer(
-            &[system_instruction::transfer(
-                &from_pubkey,
-                recepient,
-                *add_lamports,
-            )],
+            &instructions,
             Some(&payer_pubkey),
-            &[&signer, &payer, &from],
+            &signers,
             blockhash_cache.get(),
         )
-        // }
     }
 }
@@ -0,0 +1,128 @@
+//! Tops up vote accounts to a target balance, the same way `fill-up-to` tops up regular accounts.
+
+use anyhow::{Context as _, Result, bail};
+use itertools::{Itertools as _, izip};
+use pythnet_heisenberg::{
+    dry_run, keypair_ext::read_keypair_file, output, tx_sheppard::with_sheppard,
+};
+use solana_sdk::{native_token::Sol, signer::Signer as _};
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, transfer::fund_vote_accounts::FundVoteAccountsArgs},
+    exit_code::check_outcomes,
+    transfer::{
+        fill_up_to::{
+            AccountAction, fill_up_tx, from_accounts_have_enough_balance, print_account_actions,
+            print_dry_run_summary, print_transfer_results, single_source_draws,
+        },
+        vote_accounts::{VoteAccount, all_vote_accounts, read_vote_accounts},
+    },
+};
+
+pub async fn run(
+    FundVoteAccountsArgs {
+        json_rpc_url,
+        from_keypair,
+        target_balance,
+        all_vote_accounts: all,
+        mut vote_accounts,
+    }: FundVoteAccountsArgs,
+) -> Result<()> {
+    let dry_run = dry_run::is_enabled();
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let rpc_client = &rpc_client;
+
+    let from = read_keypair_file(&from_keypair)?;
+    let from_pubkey = from.pubkey();
+
+    if all {
+        vote_accounts.extend(all_vote_accounts(rpc_client).await?);
+    }
+    let vote_accounts = vote_accounts.into_iter().unique().collect::<Vec<_>>();
+    let vote_accounts = read_vote_accounts(rpc_client, &vote_accounts).await?;
+
+    let mut actions = vec![];
+    for VoteAccount {
+        pubkey,
+        lamports,
+        rent_exempt_minimum,
+        ..
+    } in vote_accounts
+    {
+        if target_balance < rent_exempt_minimum {
+            bail!(
+                "--target-balance of {} is below the rent exempt minimum of {pubkey}: {}",
+                Sol(target_balance),
+                Sol(rent_exempt_minimum),
+            );
+        }
+        let add_lamports = target_balance.saturating_sub(lamports);
+        if add_lamports > 0 {
+            actions.push(AccountAction {
+                recepient: pubkey,
+                create: false,
+                add_lamports,
+            });
+        }
+    }
+
+    if actions.is_empty() {
+        output::notice(format!(
+            "All vote accounts already have at least {}",
+            Sol(target_balance)
+        ));
+        return Ok(());
+    }
+    print_account_actions(&actions);
+
+    let draws = single_source_draws(&actions);
+    let from = [&from];
+
+    if dry_run {
+        print_dry_run_summary(
+            rpc_client,
+            from_pubkey,
+            from_pubkey,
+            &from,
+            &actions,
+            &draws,
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let required = actions
+        .iter()
+        .map(|AccountAction { add_lamports, .. }| *add_lamports)
+        .sum::<u64>();
+    if from_accounts_have_enough_balance(
+        rpc_client,
+        &[from_pubkey],
+        from_pubkey,
+        actions.len() as u64,
+        required,
+    )
+    .await?
+    .is_none()
+    {
+        bail!("{from_pubkey} does not have enough funds to top up all the vote accounts");
+    }
+
+    let outcomes = with_sheppard(rpc_client)
+        .labels(
+            actions
+                .iter()
+                .map(|AccountAction { recepient, .. }| recepient),
+        )
+        .run(izip!(&actions, &draws).map(|(action, draws)| {
+            fill_up_tx(from[0], from[0], from_pubkey, &from, action, draws, None)
+        }))
+        .await
+        .context("Running transfer transactions")?;
+
+    print_transfer_results(&actions, &outcomes);
+
+    check_outcomes(&outcomes)?;
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+//! `spl-memo` is not a dependency, and the instruction is trivial, so it is constructed here
+//! directly.
+
+use solana_sdk::{instruction::Instruction, pubkey, pubkey::Pubkey};
+
+/// Address of the SPL Memo program, version 2.
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// Constructs an instruction that records `memo` in the transaction log.
+///
+/// No signer accounts are attached, so the memo program will not check any signatures.
+pub fn memo_instruction(memo: &str) -> Instruction {
+    Instruction {
+        program_id: MEMO_PROGRAM_ID,
+        accounts: vec![],
+        data: memo.as_bytes().to_vec(),
+    }
+}
@@ -0,0 +1,79 @@
+//! Vote account lookups shared by `fund-vote-accounts` and `withdraw-from-vote`.
+
+use std::str::FromStr as _;
+
+use anyhow::{Context as _, Result, bail};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::request::MAX_MULTIPLE_ACCOUNTS;
+use solana_sdk::{
+    pubkey::Pubkey,
+    rent::Rent,
+    vote::{program as vote_program, state::VoteState},
+};
+
+pub struct VoteAccount {
+    pub pubkey: Pubkey,
+    pub lamports: u64,
+    pub authorized_withdrawer: Pubkey,
+    /// Balance the account can not go below, without being closed.
+    pub rent_exempt_minimum: u64,
+}
+
+/// All the vote accounts of the cluster, including the delinquent ones.
+pub async fn all_vote_accounts(rpc_client: &RpcClient) -> Result<Vec<Pubkey>> {
+    let vote_accounts = rpc_client
+        .get_vote_accounts()
+        .await
+        .context("Failed to get vote accounts")?;
+
+    vote_accounts
+        .current
+        .iter()
+        .chain(&vote_accounts.delinquent)
+        .map(|vote_account| {
+            Pubkey::from_str(&vote_account.vote_pubkey).with_context(|| {
+                format!(
+                    "getVoteAccounts returned an invalid vote pubkey: {}",
+                    vote_account.vote_pubkey
+                )
+            })
+        })
+        .collect()
+}
+
+/// Reads the `pubkeys` vote accounts.  Fails if any of them does not exist, or is not a vote
+/// account.
+pub async fn read_vote_accounts(
+    rpc_client: &RpcClient,
+    pubkeys: &[Pubkey],
+) -> Result<Vec<VoteAccount>> {
+    let rent = Rent::default();
+
+    let mut vote_accounts = Vec::with_capacity(pubkeys.len());
+    for pubkeys in pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let accounts = rpc_client
+            .get_multiple_accounts(pubkeys)
+            .await
+            .context("Failed to read vote accounts")?;
+
+        for (pubkey, account) in pubkeys.iter().zip(accounts) {
+            let Some(account) = account else {
+                bail!("Vote account {pubkey} does not exist");
+            };
+            if account.owner != vote_program::id() {
+                bail!("{pubkey} is not a vote account.  Owner: {}", account.owner);
+            }
+            let vote_state = VoteState::deserialize(&account.data)
+                .with_context(|| format!("Failed to parse vote account {pubkey}"))?;
+
+            vote_accounts.push(VoteAccount {
+                pubkey: *pubkey,
+                lamports: account.lamports,
+                authorized_withdrawer: vote_state.authorized_withdrawer,
+                rent_exempt_minimum: rent.minimum_balance(account.data.len()),
+            });
+        }
+    }
+
+    Ok(vote_accounts)
+}
@@ -0,0 +1,179 @@
+//! Keeps a set of accounts funded, for as long as the command runs.
+//!
+//! Useful for long running benchmarks, where payers are constantly spending their balance on
+//! transaction fees.
+
+use anyhow::{Context as _, Result};
+use futures::{StreamExt as _, stream::select_all};
+use itertools::izip;
+use log::warn;
+use pythnet_heisenberg::{
+    dry_run, keypair_ext::read_keypair_file, output, tx_sheppard::with_sheppard,
+};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{native_token::Sol, pubkey::Pubkey, signature::Keypair, signer::Signer as _};
+use tokio::{
+    select,
+    signal::unix::{SignalKind, signal},
+    time::{MissedTickBehavior, interval},
+};
+use tokio_stream::wrappers::SignalStream;
+
+use crate::args::{json_rpc_url_args::get_rpc_client, transfer::watch_and_fill::WatchAndFillArgs};
+
+use super::fill_up_to::{
+    AccountAction, calculate_account_actions, fill_up_tx, print_account_actions,
+    print_dry_run_summary, print_transfer_results, single_source_draws,
+};
+
+pub async fn run(
+    WatchAndFillArgs {
+        json_rpc_url,
+        payer_keypair,
+        from_keypair,
+        target_balance,
+        threshold,
+        poll_interval,
+        recepients,
+    }: WatchAndFillArgs,
+) -> Result<()> {
+    let dry_run = dry_run::is_enabled();
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let rpc_client = &rpc_client;
+
+    let payer = read_keypair_file(&payer_keypair)?;
+    let payer_pubkey = payer.pubkey();
+
+    let from = from_keypair.map(read_keypair_file).transpose()?;
+    let from = from.as_ref().unwrap_or(&payer);
+
+    let threshold = threshold.unwrap_or(target_balance);
+
+    let targets = recepients
+        .into_iter()
+        .map(|recepient| (recepient, target_balance))
+        .collect::<Vec<_>>();
+
+    if dry_run {
+        let actions = accounts_to_fill_up(rpc_client, &targets, target_balance, threshold).await?;
+        print_account_actions(&actions);
+        let from = [from];
+        return print_dry_run_summary(
+            rpc_client,
+            payer_pubkey,
+            payer_pubkey,
+            &from,
+            &actions,
+            &single_source_draws(&actions),
+        )
+        .await;
+    }
+
+    let mut poll_timer = interval(poll_interval.into());
+    poll_timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let stop_signals = select_all([
+        SignalStream::new(signal(SignalKind::interrupt()).expect("Can install a SIGINT handler")),
+        SignalStream::new(signal(SignalKind::terminate()).expect("Can install a SIGTERM handler")),
+    ]);
+    tokio::pin!(stop_signals);
+
+    output::notice(format!(
+        "Watching {} accounts.  Topping up to {} when below {} ...",
+        targets.len(),
+        Sol(target_balance),
+        Sol(threshold),
+    ));
+
+    loop {
+        select! {
+            _at = poll_timer.tick() => (),
+            _ = stop_signals.next() => break,
+        }
+
+        let fill_up = fill_up_once(
+            rpc_client,
+            &payer,
+            payer_pubkey,
+            from,
+            &targets,
+            target_balance,
+            threshold,
+        );
+
+        select! {
+            res = fill_up => if let Err(err) = res {
+                warn!("Top up iteration failed: {err:#}");
+            },
+            _ = stop_signals.next() => break,
+        }
+    }
+
+    output::notice(format!("Stopped at: {}", chrono::Local::now()));
+
+    Ok(())
+}
+
+async fn fill_up_once(
+    rpc_client: &RpcClient,
+    payer: &Keypair,
+    payer_pubkey: Pubkey,
+    from: &Keypair,
+    targets: &[(Pubkey, u64)],
+    target_balance: u64,
+    threshold: u64,
+) -> Result<()> {
+    let actions = accounts_to_fill_up(rpc_client, targets, target_balance, threshold).await?;
+
+    if actions.is_empty() {
+        return Ok(());
+    }
+
+    let total = actions
+        .iter()
+        .map(|AccountAction { add_lamports, .. }| *add_lamports)
+        .sum::<u64>();
+    output::notice(format!(
+        "{}: Topping up {} accounts with {} in total ...",
+        chrono::Local::now(),
+        actions.len(),
+        Sol(total),
+    ));
+
+    let from = [from];
+    let draws = single_source_draws(&actions);
+
+    let outcomes = with_sheppard(rpc_client)
+        .labels(
+            actions
+                .iter()
+                .map(|AccountAction { recepient, .. }| recepient),
+        )
+        .run(izip!(&actions, &draws).map(|(action, draws)| {
+            fill_up_tx(payer, payer, payer_pubkey, &from, action, draws, None)
+        }))
+        .await
+        .context("Running transfer transactions")?;
+
+    print_transfer_results(&actions, &outcomes);
+
+    Ok(())
+}
+
+async fn accounts_to_fill_up(
+    rpc_client: &RpcClient,
+    targets: &[(Pubkey, u64)],
+    target_balance: u64,
+    threshold: u64,
+) -> Result<Vec<AccountAction>> {
+    let actions = calculate_account_actions(rpc_client, targets)
+        .await?
+        .into_iter()
+        .filter(|AccountAction { add_lamports, .. }| {
+            // Current balance is below the threshold.
+            target_balance - *add_lamports < threshold
+        })
+        .collect();
+
+    Ok(actions)
+}
@@ -0,0 +1,202 @@
+//! Withdraws the balance above a floor from vote accounts, using their withdraw authorities.
+
+use anyhow::{Context as _, Result, bail};
+use itertools::{Itertools as _, izip};
+use pythnet_heisenberg::{
+    blockhash_cache::BlockhashCache,
+    dry_run,
+    keypair_ext::read_keypair_file,
+    output,
+    rpc_client_ext::RpcClientExt as _,
+    tx_sheppard::{TxOutcome, with_sheppard},
+};
+use serde_json::json;
+use solana_sdk::{
+    native_token::Sol, pubkey::Pubkey, signature::Keypair, signer::Signer as _,
+    transaction::Transaction, vote::instruction as vote_instruction,
+};
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, transfer::withdraw_from_vote::WithdrawFromVoteArgs},
+    exit_code::check_outcomes,
+    transfer::vote_accounts::{VoteAccount, all_vote_accounts, read_vote_accounts},
+};
+
+struct Withdrawal<'authority> {
+    vote_account: Pubkey,
+    authority: &'authority Keypair,
+    lamports: u64,
+}
+
+pub async fn run(
+    WithdrawFromVoteArgs {
+        json_rpc_url,
+        withdraw_authority_keypair,
+        payer_keypair,
+        to,
+        keep_balance,
+        all_vote_accounts: all,
+        vote_accounts: explicit_vote_accounts,
+    }: WithdrawFromVoteArgs,
+) -> Result<()> {
+    let dry_run = dry_run::is_enabled();
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let rpc_client = &rpc_client;
+
+    let authorities = withdraw_authority_keypair
+        .iter()
+        .map(read_keypair_file)
+        .collect::<Result<Vec<_>>>()?;
+    let payer = payer_keypair.map(read_keypair_file).transpose()?;
+    // `clap` makes sure there is at least one `--withdraw-authority-keypair`.
+    let payer = payer.as_ref().unwrap_or(&authorities[0]);
+    let payer_pubkey = payer.pubkey();
+
+    let mut vote_accounts = explicit_vote_accounts.clone();
+    if all {
+        vote_accounts.extend(all_vote_accounts(rpc_client).await?);
+    }
+    let vote_accounts = vote_accounts.into_iter().unique().collect::<Vec<_>>();
+    let vote_accounts = read_vote_accounts(rpc_client, &vote_accounts).await?;
+
+    let mut withdrawals = vec![];
+    for VoteAccount {
+        pubkey,
+        lamports,
+        authorized_withdrawer,
+        rent_exempt_minimum,
+    } in vote_accounts
+    {
+        let Some(authority) = authorities
+            .iter()
+            .find(|authority| authority.pubkey() == authorized_withdrawer)
+        else {
+            // Accounts found via `--all-vote-accounts` are only withdrawn from when one of the
+            // authorities matches.
+            if explicit_vote_accounts.contains(&pubkey) {
+                bail!(
+                    "None of the --withdraw-authority-keypair keypairs can withdraw from \
+                     {pubkey}.  Authorized withdrawer: {authorized_withdrawer}"
+                );
+            }
+            continue;
+        };
+
+        let floor = keep_balance.unwrap_or_default().max(rent_exempt_minimum);
+        let lamports = lamports.saturating_sub(floor);
+        if lamports > 0 {
+            withdrawals.push(Withdrawal {
+                vote_account: pubkey,
+                authority,
+                lamports,
+            });
+        }
+    }
+
+    if withdrawals.is_empty() {
+        output::notice("Nothing to withdraw");
+        return Ok(());
+    }
+
+    for Withdrawal {
+        vote_account,
+        lamports,
+        ..
+    } in &withdrawals
+    {
+        eprintln!("Withdrawing {} from {vote_account} ...", Sol(*lamports));
+    }
+    let total = withdrawals
+        .iter()
+        .map(|Withdrawal { lamports, .. }| *lamports)
+        .sum::<u64>();
+
+    if dry_run {
+        let lamports_per_signature = rpc_client
+            .get_lamports_per_signature()
+            .await
+            .context("Estimating transaction fees")?;
+        // The payer signs every transaction, and the authority signs too, unless it is the payer.
+        let signatures = withdrawals
+            .iter()
+            .map(|Withdrawal { authority, .. }| 1 + u64::from(authority.pubkey() != payer_pubkey))
+            .sum::<u64>();
+        eprintln!(
+            "Dry run.  No transactions were sent.\n\
+             Transactions: {}\n\
+             Total to withdraw into {to}: {}\n\
+             Estimated fees: {}",
+            withdrawals.len(),
+            Sol(total),
+            Sol(signatures * lamports_per_signature),
+        );
+        return Ok(());
+    }
+
+    let outcomes = with_sheppard(rpc_client)
+        .labels(
+            withdrawals
+                .iter()
+                .map(|Withdrawal { vote_account, .. }| vote_account),
+        )
+        .run(
+            withdrawals
+                .iter()
+                .map(|withdrawal| withdraw_tx(payer, payer_pubkey, withdrawal, to)),
+        )
+        .await
+        .context("Running withdraw transactions")?;
+
+    for (
+        Withdrawal {
+            vote_account,
+            lamports,
+            ..
+        },
+        outcome,
+    ) in izip!(&withdrawals, &outcomes)
+    {
+        let result = match outcome {
+            TxOutcome::Success(signature) => json!({
+                "vote_account": vote_account.to_string(),
+                "lamports": lamports,
+                "signature": signature.to_string(),
+            }),
+            TxOutcome::Failed(error) => json!({
+                "vote_account": vote_account.to_string(),
+                "lamports": lamports,
+                "error": error,
+            }),
+        };
+        output::json_result(result);
+    }
+
+    check_outcomes(&outcomes)?;
+
+    Ok(())
+}
+
+fn withdraw_tx<'context>(
+    payer: &'context Keypair,
+    payer_pubkey: Pubkey,
+    Withdrawal {
+        vote_account,
+        authority,
+        lamports,
+    }: &'context Withdrawal<'context>,
+    to: Pubkey,
+) -> impl Fn(/* blockhash_cache: */ &BlockhashCache) -> Transaction + 'context {
+    move |blockhash_cache: &BlockhashCache| -> Transaction {
+        Transaction::new_signed_with_payer(
+            &[vote_instruction::withdraw(
+                vote_account,
+                &authority.pubkey(),
+                *lamports,
+                &to,
+            )],
+            Some(&payer_pubkey),
+            &[payer, *authority],
+            blockhash_cache.get(),
+        )
+    }
+}
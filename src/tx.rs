@@ -0,0 +1,21 @@
+use anyhow::{Context as _, Result};
+use pythnet_heisenberg::dry_run;
+
+use crate::{args::tx::Command, exit_code::ValidationFailed};
+
+mod build_and_send;
+mod landing_report;
+mod load;
+mod template;
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::LandingReport(args) => landing_report::run(args).await,
+        Command::BuildAndSend(args) => build_and_send::run(args).await,
+        Command::Load(args) => {
+            dry_run::check_not_enabled("tx load").context(ValidationFailed)?;
+            args.check_are_valid().context(ValidationFailed)?;
+            load::run(args).await
+        }
+    }
+}
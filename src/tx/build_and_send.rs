@@ -0,0 +1,168 @@
+//! Builds transactions from a YAML description of their instructions.
+
+use std::{fmt::Write as _, path::Path};
+
+use anyhow::{Context as _, Result, bail};
+use itertools::Itertools as _;
+use pythnet_heisenberg::{
+    blockhash_cache::BlockhashCache,
+    keypair_ext::try_new_signed_with_payer,
+    output,
+    tx_sheppard::{TxOutcome, with_sheppard},
+};
+use serde_json::json;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+use crate::{
+    args::{json_rpc_url_args::get_rpc_client, tx::build_and_send::BuildAndSendArgs},
+    confirm::confirm_or_abort,
+    exit_code::check_outcomes,
+    tx::template::{Signers, Template, build_instructions, read_template},
+    tx_cost::CostSummary,
+};
+
+pub async fn run(
+    BuildAndSendArgs {
+        json_rpc_url,
+        template: template_path,
+        skip_preflight,
+        report_costs,
+        yes,
+    }: BuildAndSendArgs,
+) -> Result<()> {
+    let template = read_template(&template_path)?;
+    let base_dir = template_path.parent().unwrap_or(Path::new("."));
+
+    let Template {
+        signers: signer_configs,
+        fee_payer,
+        transactions,
+    } = template;
+
+    let signers = Signers::load(signer_configs, base_dir)?;
+
+    if transactions.is_empty() {
+        bail!("Template does not have any transactions");
+    }
+
+    // Placeholders use the transaction position in the template as the index.
+    let transactions = transactions
+        .iter()
+        .zip(0u64..)
+        .map(|(tx, index)| {
+            let fee_payer = signers.fee_payer(fee_payer.as_deref(), index)?;
+            let instructions = build_instructions(tx, &signers, index)
+                .with_context(|| format!("Transaction {}", index + 1))?;
+            Ok((fee_payer, instructions))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let tx_signers = transactions
+        .iter()
+        .map(|(fee_payer, instructions)| signers.for_transaction(*fee_payer, instructions))
+        .collect::<Vec<_>>();
+
+    confirm_or_abort(summary(&transactions), yes)?;
+
+    let rpc_client = get_rpc_client(json_rpc_url);
+    let outcomes =
+        with_sheppard(&rpc_client)
+            .skip_preflight(skip_preflight)
+            .run(transactions.iter().zip(&tx_signers).map(
+                |((fee_payer, instructions), signers)| {
+                    // Signers may be hardware wallets, that can fail to sign.
+                    move |blockhash_cache: &BlockhashCache| {
+                        try_new_signed_with_payer(
+                            instructions,
+                            Some(fee_payer),
+                            signers,
+                            blockhash_cache.get(),
+                        )
+                    }
+                },
+            ))
+            .await
+            .context("Sending template transactions")?;
+
+    for (tx_index, outcome) in outcomes.iter().enumerate() {
+        let tx_no = tx_index + 1;
+        match outcome {
+            TxOutcome::Success(signature) => output::result(
+                format!("Transaction {tx_no}: {signature}"),
+                json!({ "transaction": tx_no, "signature": signature.to_string() }),
+            ),
+            TxOutcome::Failed(error) => output::result(
+                format!("Transaction {tx_no}: Failed: {error}"),
+                json!({ "transaction": tx_no, "error": error }),
+            ),
+        }
+    }
+
+    if report_costs {
+        let signatures = outcomes
+            .iter()
+            .filter_map(|outcome| match outcome {
+                TxOutcome::Success(signature) => Some(*signature),
+                TxOutcome::Failed(_) => None,
+            })
+            .collect::<Vec<_>>();
+        CostSummary::collect(&rpc_client, &signatures)
+            .await
+            .print(None, None);
+    }
+
+    check_outcomes(&outcomes)?;
+
+    Ok(())
+}
+
+fn summary(transactions: &[(Pubkey, Vec<Instruction>)]) -> String {
+    let mut text = format!("Sending {} transactions", transactions.len());
+    // Fee payers only differ when the fee payer is a pool.
+    let common_fee_payer = transactions
+        .iter()
+        .map(|(fee_payer, _instructions)| fee_payer)
+        .all_equal_value()
+        .ok();
+    if let Some(fee_payer) = common_fee_payer {
+        write!(text, "\nFee payer: {fee_payer}").expect("Writing into a String never fails");
+    }
+    for (tx_index, (fee_payer, instructions)) in transactions.iter().enumerate() {
+        write!(text, "\nTransaction {}:", tx_index + 1).expect("Writing into a String never fails");
+        if common_fee_payer.is_none() {
+            write!(text, "\n  Fee payer: {fee_payer}").expect("Writing into a String never fails");
+        }
+        for Instruction {
+            program_id,
+            accounts,
+            data,
+        } in instructions
+        {
+            write!(
+                text,
+                "\n  Program {program_id}, {} accounts, {} data bytes",
+                accounts.len(),
+                data.len()
+            )
+            .expect("Writing into a String never fails");
+            for AccountMeta {
+                pubkey,
+                is_signer,
+                is_writable,
+            } in accounts
+            {
+                let flags = match (is_signer, is_writable) {
+                    (true, true) => " (signer, writable)",
+                    (true, false) => " (signer)",
+                    (false, true) => " (writable)",
+                    (false, false) => "",
+                };
+                write!(text, "\n    {pubkey}{flags}").expect("Writing into a String never fails");
+            }
+        }
+    }
+    text
+}
@@ -0,0 +1,241 @@
+//! Turns a list of transaction signatures, produced by a benchmark, into a landing report.
+
+use std::{collections::BTreeMap, fs, path::Path, str::FromStr as _};
+
+use anyhow::{Context as _, Result, bail};
+use log::warn;
+use pythnet_heisenberg::output;
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::request::MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS;
+use solana_sdk::{clock::Slot, signature::Signature};
+use solana_transaction_status::TransactionStatus;
+
+use crate::args::{json_rpc_url_args::get_rpc_client, tx::landing_report::LandingReportArgs};
+
+pub async fn run(
+    LandingReportArgs {
+        json_rpc_url,
+        signatures_file,
+        no_per_slot,
+    }: LandingReportArgs,
+) -> Result<()> {
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let entries = read_signatures_file(&signatures_file)?;
+    if entries.is_empty() {
+        bail!(
+            "No signatures found in: {}",
+            signatures_file.to_string_lossy()
+        );
+    }
+
+    output::notice(format!(
+        "Fetching statuses of {} transactions...",
+        entries.len()
+    ));
+    let statuses = fetch_statuses(&rpc_client, &entries).await?;
+
+    let mut per_slot = BTreeMap::<Slot, u64>::new();
+    let mut errors = BTreeMap::<String, u64>::new();
+    let mut delays = vec![];
+    let mut succeeded = 0;
+    let mut failed = 0;
+    let mut not_found = 0;
+
+    for (SignatureEntry { sent_slot, .. }, status) in entries.iter().zip(&statuses) {
+        let Some(TransactionStatus { slot, err, .. }) = status else {
+            not_found += 1;
+            continue;
+        };
+
+        *per_slot.entry(*slot).or_default() += 1;
+        if let Some(sent_slot) = sent_slot {
+            delays.push(slot.saturating_sub(*sent_slot));
+        }
+
+        match err {
+            None => succeeded += 1,
+            Some(err) => {
+                failed += 1;
+                *errors.entry(err.to_string()).or_default() += 1;
+            }
+        }
+    }
+
+    delays.sort_unstable();
+
+    let total = entries.len();
+    let landed = succeeded + failed;
+
+    let mut text = format!(
+        "Transactions: {total}\n\
+         Landed: {landed} ({succeeded} succeeded, {failed} failed)\n\
+         Not found: {not_found}"
+    );
+
+    let mut landing_span_secs = None;
+    if let (Some(&first), Some(&last)) = (per_slot.keys().next(), per_slot.keys().next_back()) {
+        let span = last - first + 1;
+        let slots_with_landings = per_slot.len();
+        text.push_str(&format!(
+            "\nLanded in slots {first}..={last}: {slots_with_landings} of {span} slots had \
+             landings, {:.1} transactions per slot with landings",
+            landed as f64 / slots_with_landings as f64,
+        ));
+
+        landing_span_secs = landing_span(&rpc_client, first, last).await;
+        if let Some(landing_span_secs) = landing_span_secs {
+            text.push_str(&format!(
+                "\nLanding span, according to the block times: {landing_span_secs}s"
+            ));
+        }
+    }
+
+    let delay_percentiles = [50, 90, 99, 100]
+        .into_iter()
+        .filter_map(|percentile| {
+            let value = percentile_of(&delays, percentile)?;
+            Some((percentile, value))
+        })
+        .collect::<Vec<_>>();
+    if !delay_percentiles.is_empty() {
+        text.push_str("\nInclusion delay, in slots:");
+        for (percentile, value) in &delay_percentiles {
+            text.push_str(&format!("\n  p{percentile}: {value}"));
+        }
+    }
+
+    if !errors.is_empty() {
+        text.push_str("\nErrors:");
+        for (error, count) in &errors {
+            text.push_str(&format!("\n  {count}: {error}"));
+        }
+    }
+
+    if !no_per_slot && !per_slot.is_empty() {
+        text.push_str("\nPer slot landings:");
+        for (slot, count) in &per_slot {
+            text.push_str(&format!("\n  {slot}: {count}"));
+        }
+    }
+
+    output::result(
+        text,
+        json!({
+            "total": total,
+            "landed": landed,
+            "succeeded": succeeded,
+            "failed": failed,
+            "not_found": not_found,
+            "landing_span_secs": landing_span_secs,
+            "inclusion_delay_slots": delay_percentiles
+                .iter()
+                .map(|(percentile, value)| (format!("p{percentile}"), json!(value)))
+                .collect::<serde_json::Map<_, _>>(),
+            "errors": errors
+                .iter()
+                .map(|(error, count)| json!({ "error": error, "count": count }))
+                .collect::<Vec<_>>(),
+            "per_slot": if no_per_slot {
+                vec![]
+            } else {
+                per_slot
+                    .iter()
+                    .map(|(slot, count)| json!({ "slot": slot, "count": count }))
+                    .collect::<Vec<_>>()
+            },
+        }),
+    );
+
+    Ok(())
+}
+
+struct SignatureEntry {
+    signature: Signature,
+    sent_slot: Option<Slot>,
+}
+
+/// Reads a file with "[signature]" or "[signature],[sent slot]" lines.
+fn read_signatures_file(path: &Path) -> Result<Vec<SignatureEntry>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read signatures file: {}", path.to_string_lossy()))?;
+
+    let mut entries = vec![];
+    for (line_no, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let context = || format!("{}:{}", path.to_string_lossy(), line_no + 1);
+
+        let (signature, sent_slot) = match line.split_once(',') {
+            Some((signature, sent_slot)) => (signature.trim(), Some(sent_slot.trim())),
+            None => (line, None),
+        };
+
+        let signature = Signature::from_str(signature)
+            .with_context(|| format!("{}: invalid signature: {signature}", context()))?;
+        let sent_slot = sent_slot
+            .map(|sent_slot| {
+                sent_slot
+                    .parse::<Slot>()
+                    .with_context(|| format!("{}: invalid sent slot: {sent_slot}", context()))
+            })
+            .transpose()?;
+
+        entries.push(SignatureEntry {
+            signature,
+            sent_slot,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Statuses are returned in the same order as the `entries`.  Transactions that are unknown to
+/// the RPC node have a `None` status.
+async fn fetch_statuses(
+    rpc_client: &RpcClient,
+    entries: &[SignatureEntry],
+) -> Result<Vec<Option<TransactionStatus>>> {
+    let mut statuses = Vec::with_capacity(entries.len());
+    for chunk in entries.chunks(MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS) {
+        let signatures = chunk
+            .iter()
+            .map(|SignatureEntry { signature, .. }| *signature)
+            .collect::<Vec<_>>();
+        let chunk_statuses = rpc_client
+            .get_signature_statuses_with_history(&signatures)
+            .await
+            .context("Failed to get transaction statuses")?
+            .value;
+        statuses.extend(chunk_statuses);
+    }
+    Ok(statuses)
+}
+
+/// Time between the blocks of the `first` and the `last` slots, in seconds.  Block times are not
+/// always available, so any errors are just logged.
+async fn landing_span(rpc_client: &RpcClient, first: Slot, last: Slot) -> Option<i64> {
+    let block_time = async |slot| match rpc_client.get_block_time(slot).await {
+        Ok(block_time) => Some(block_time),
+        Err(err) => {
+            warn!("Failed to get block time for slot {slot}: {err}");
+            None
+        }
+    };
+    let first = block_time(first).await?;
+    let last = block_time(last).await?;
+    Some(last - first)
+}
+
+/// Nearest rank percentile of a sorted list of values.
+fn percentile_of(sorted: &[u64], percentile: usize) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (percentile * sorted.len()).div_ceil(100).max(1);
+    Some(sorted[rank - 1])
+}
@@ -0,0 +1,441 @@
+//! Sends transactions built from a template at a fixed rate, to load a cluster with traffic other
+//! than the price updates.
+//!
+//! Transactions are sent once, without waiting for them to land and without retries, same as the
+//! benchmark price updates.  RPC sends skip the preflight simulation.  Use the `--signatures-file`
+//! with a `tx landing-report` to see how many of them landed.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write as _},
+    net::{Ipv4Addr, SocketAddr},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context as _, Result, bail};
+use bincode::{self, serde::encode_to_vec};
+use futures::{
+    StreamExt as _,
+    stream::{FuturesUnordered, select_all},
+};
+use pythnet_heisenberg::{
+    blockhash_cache::BlockhashCache,
+    failure_rate::{FailureRate, FailureRateGuard},
+    keypair_ext::try_new_signed_with_payer,
+    node_address_service::{NodeAddressService, with_node_address_service},
+    output,
+    tx_records::{self, TxRecord},
+};
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::RpcSendTransactionConfig;
+use solana_sdk::{
+    clock::{NUM_CONSECUTIVE_LEADER_SLOTS, Slot},
+    hash::Hash,
+    signature::Signature,
+    transaction::Transaction,
+};
+use tokio::{
+    net::UdpSocket,
+    select,
+    signal::unix::{SignalKind, signal},
+    time::{Instant, MissedTickBehavior, interval, interval_at, sleep},
+};
+use tokio_stream::wrappers::SignalStream;
+
+use crate::{
+    args::{
+        json_rpc_url_args::get_rpc_client, price_store::benchmark1::SendMode, tx::load::LoadArgs,
+    },
+    tx::template::{Signers, Template, TransactionConfig, build_instructions, read_template},
+};
+
+/// How often the sender checks if more transactions are due.
+const SEND_TICK: Duration = Duration::from_millis(10);
+
+/// How often the `--abort-on-failure-rate` condition is checked.
+const ABORT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+pub async fn run(
+    LoadArgs {
+        json_rpc_url,
+        websocket_url,
+        template: template_path,
+        rate,
+        duration,
+        start_index,
+        send_mode,
+        fanout_slots,
+        max_in_flight,
+        stats_update_interval,
+        signatures_file,
+        abort,
+    }: LoadArgs,
+) -> Result<()> {
+    let template = read_template(&template_path)?;
+    let base_dir = template_path.parent().unwrap_or(Path::new("."));
+
+    let Template {
+        signers: signer_configs,
+        fee_payer,
+        transactions,
+    } = template;
+
+    let signers = Signers::load(signer_configs, base_dir)?;
+
+    if transactions.is_empty() {
+        bail!("Template does not have any transactions");
+    }
+
+    // Template errors do not depend on the index, so building every template transaction once
+    // catches them before anything is sent.
+    for (tx, index) in transactions.iter().zip(start_index..) {
+        signers.fee_payer(fee_payer.as_deref(), index)?;
+        build_instructions(tx, &signers, index)
+            .with_context(|| format!("Transaction {}", index - start_index + 1))?;
+    }
+
+    let mut signatures_out = signatures_file
+        .map(|path| {
+            File::create(&path).map(BufWriter::new).with_context(|| {
+                format!(
+                    "Failed to create signatures file: {}",
+                    path.to_string_lossy()
+                )
+            })
+        })
+        .transpose()?;
+
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let duration: Duration = duration.into();
+    let stats_update_interval: Duration = stats_update_interval.into();
+
+    output::notice(format!(
+        "Sending {rate} transactions per second, for {}",
+        humantime::format_duration(duration)
+    ));
+
+    let sender = Sender {
+        rpc_client: &rpc_client,
+        signers: &signers,
+        fee_payer: fee_payer.as_deref(),
+        transactions: &transactions,
+        rate,
+        start_index,
+        send_mode,
+        fanout_slots: fanout_slots.into(),
+        max_in_flight,
+    };
+
+    let start = Instant::now();
+    let (stats, aborted) = with_node_address_service(rpc_client.clone(), websocket_url.as_str())
+        .run(
+            async |blockhash_cache: &BlockhashCache, node_address_service: NodeAddressService| {
+                sender
+                    .run(
+                        blockhash_cache,
+                        &node_address_service,
+                        duration,
+                        stats_update_interval,
+                        signatures_out.as_mut(),
+                        abort.failure_rate_guard(),
+                    )
+                    .await
+            },
+        )
+        .await??;
+
+    if let Some(signatures_out) = signatures_out.as_mut() {
+        signatures_out
+            .flush()
+            .context("Failed to write the signatures file")?;
+    }
+
+    stats.print(start.elapsed());
+    stats.print_errors();
+
+    if let Some(failure_rate) = aborted {
+        bail!("Load aborted: {failure_rate}");
+    }
+
+    Ok(())
+}
+
+struct Sender<'env> {
+    rpc_client: &'env RpcClient,
+    signers: &'env Signers,
+    fee_payer: Option<&'env str>,
+    transactions: &'env [TransactionConfig],
+    rate: u32,
+    start_index: u64,
+    send_mode: SendMode,
+    fanout_slots: u64,
+    max_in_flight: usize,
+}
+
+impl Sender<'_> {
+    /// Sends transactions until the `duration` expires, or the process receives a SIGINT or a
+    /// SIGTERM.
+    async fn run(
+        &self,
+        blockhash_cache: &BlockhashCache,
+        node_address_service: &NodeAddressService,
+        duration: Duration,
+        stats_update_interval: Duration,
+        mut signatures_out: Option<&mut BufWriter<File>>,
+        mut failure_rate_guard: Option<FailureRateGuard>,
+    ) -> Result<(LoadStats, Option<FailureRate>)> {
+        let Self {
+            rpc_client,
+            rate,
+            send_mode,
+            fanout_slots,
+            max_in_flight,
+            ..
+        } = *self;
+
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+            .await
+            .context("Creation of a UDP socket")?;
+        let mut target_nodes = Vec::with_capacity(
+            usize::try_from(fanout_slots / NUM_CONSECUTIVE_LEADER_SLOTS + 1)
+                .expect("`fanout_slots / NUM_CONSECUTIVE_LEADER_SLOTS` fits into a usize"),
+        );
+
+        let mut rpc_sends = FuturesUnordered::new();
+
+        let mut stats = LoadStats::default();
+        let mut built = 0u64;
+        let mut aborted = None;
+
+        let start = Instant::now();
+        let end_timer = sleep(duration);
+        tokio::pin!(end_timer);
+
+        let stop_signals = select_all([
+            SignalStream::new(
+                signal(SignalKind::interrupt()).expect("Can install a SIGINT handler"),
+            ),
+            SignalStream::new(
+                signal(SignalKind::terminate()).expect("Can install a SIGTERM handler"),
+            ),
+        ]);
+        tokio::pin!(stop_signals);
+
+        let mut send_tick = interval(SEND_TICK);
+        send_tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut stats_update_interval =
+            interval_at(start + stats_update_interval, stats_update_interval);
+        let mut abort_check_interval = interval(ABORT_CHECK_INTERVAL);
+
+        loop {
+            select! {
+                _at = send_tick.tick() => {
+                    let due = (start.elapsed().as_secs_f64() * f64::from(rate)) as u64;
+                    if due <= built {
+                        continue;
+                    }
+
+                    let blockhash = blockhash_cache.get();
+                    let sent_slot = node_address_service.estimated_current_slot();
+                    if send_mode == SendMode::Udp {
+                        target_nodes.clear();
+                        node_address_service
+                            .get_tpu_for_next_in_schedule(&mut target_nodes, fanout_slots);
+                    }
+
+                    while built < due {
+                        if send_mode == SendMode::Rpc && rpc_sends.len() >= max_in_flight {
+                            break;
+                        }
+
+                        let transaction = self.build(built, blockhash)?;
+                        let number = built;
+                        built += 1;
+                        let signature = transaction.signatures[0];
+
+                        match send_mode {
+                            SendMode::Udp => {
+                                let buf = encode_to_vec(&transaction, bincode::config::legacy())
+                                    .context("Serialization of a load transaction")?;
+                                let sent_at = SystemTime::now();
+                                let res = send_udp(&socket, &buf, &target_nodes)
+                                    .await
+                                    .map(|()| signature);
+                                record_send(number, signature, sent_at, &res);
+                                stats.include(res);
+                                write_signature(&mut signatures_out, signature, sent_slot)?;
+                            }
+                            SendMode::Rpc => rpc_sends.push(async move {
+                                let sent_at = SystemTime::now();
+                                let res = rpc_client
+                                    .send_transaction_with_config(
+                                        &transaction,
+                                        RpcSendTransactionConfig {
+                                            skip_preflight: true,
+                                            ..RpcSendTransactionConfig::default()
+                                        },
+                                    )
+                                    .await
+                                    .map_err(|err| err.to_string());
+                                record_send(number, signature, sent_at, &res);
+                                (res, sent_slot)
+                            }),
+                        }
+                    }
+                }
+                send_res = rpc_sends.next(), if !rpc_sends.is_empty() => {
+                    if let Some((res, sent_slot)) = send_res {
+                        if let Ok(signature) = &res {
+                            write_signature(&mut signatures_out, *signature, sent_slot)?;
+                        }
+                        stats.include(res);
+                    }
+                }
+                _at = stats_update_interval.tick() => stats.print(start.elapsed()),
+                at = abort_check_interval.tick(), if failure_rate_guard.is_some() => {
+                    let guard = failure_rate_guard.as_mut().expect("Checked in the guard");
+                    if let Some(failure_rate) = guard.check(at, stats.sent, stats.failed) {
+                        output::notice(format!(
+                            "Stopping: {failure_rate}, at or above the --abort-on-failure-rate \
+                             of {}%",
+                            guard.threshold(),
+                        ));
+                        aborted = Some(failure_rate);
+                        break;
+                    }
+                }
+                () = &mut end_timer => break,
+                stop_res = stop_signals.next() => match stop_res {
+                    Some(()) => break,
+                    None => panic!("`stop_signals` stream show never complete"),
+                },
+            }
+        }
+
+        // Requests that are already sent are still counted.
+        while let Some((res, sent_slot)) = rpc_sends.next().await {
+            if let Ok(signature) = &res {
+                write_signature(&mut signatures_out, *signature, sent_slot)?;
+            }
+            stats.include(res);
+        }
+
+        Ok((stats, aborted))
+    }
+
+    /// Builds and signs the transaction with the specified number, counting from the
+    /// `start_index`.
+    fn build(&self, number: u64, blockhash: Hash) -> Result<Transaction> {
+        let index = self.start_index.wrapping_add(number);
+        let tx_config = &self.transactions[(number % self.transactions.len() as u64) as usize];
+        let fee_payer = self.signers.fee_payer(self.fee_payer, index)?;
+        let instructions = build_instructions(tx_config, self.signers, index)?;
+        let signers = self.signers.for_transaction(fee_payer, &instructions);
+        try_new_signed_with_payer(&instructions, Some(&fee_payer), &signers, blockhash)
+            .with_context(|| format!("Failed to sign transaction {index}"))
+    }
+}
+
+/// Sends a serialized transaction to all the `target_nodes`.  Succeeds if at least one of the
+/// sends succeeded.
+async fn send_udp(
+    socket: &UdpSocket,
+    buf: &[u8],
+    target_nodes: &[SocketAddr],
+) -> Result<(), String> {
+    if target_nodes.is_empty() {
+        return Err("No leader TPU addresses are known".to_owned());
+    }
+
+    let mut last_error = None;
+    let mut delivered = false;
+    for node_address in target_nodes {
+        match socket.send_to(buf, node_address).await {
+            Ok(sent) if sent == buf.len() => delivered = true,
+            Ok(_sent) => last_error = Some("Transaction does not fit into one packet".to_owned()),
+            Err(err) => last_error = Some(format!("UDP send failed: {err}")),
+        }
+    }
+
+    match (delivered, last_error) {
+        (true, _) | (false, None) => Ok(()),
+        (false, Some(error)) => Err(error),
+    }
+}
+
+fn write_signature(
+    signatures_out: &mut Option<&mut BufWriter<File>>,
+    signature: Signature,
+    sent_slot: Slot,
+) -> Result<()> {
+    if let Some(signatures_out) = signatures_out {
+        writeln!(signatures_out, "{signature},{sent_slot}")
+            .context("Failed to write the signatures file")?;
+    }
+    Ok(())
+}
+
+/// Load transactions are not confirmed, so only the send is recorded.
+fn record_send(
+    number: u64,
+    signature: Signature,
+    sent_at: SystemTime,
+    res: &Result<Signature, String>,
+) {
+    tx_records::write(&TxRecord {
+        index: number,
+        label: None,
+        signature,
+        sent_at,
+        confirmed_at: None,
+        slot: None,
+        retries: 0,
+        error: res.as_ref().err().map(String::as_str),
+    });
+}
+
+#[derive(Debug, Default)]
+struct LoadStats {
+    sent: u64,
+    failed: u64,
+    /// Number of failures with each error message.
+    errors: BTreeMap<String, u64>,
+}
+
+impl LoadStats {
+    fn include(&mut self, res: Result<Signature, String>) {
+        match res {
+            Ok(_signature) => self.sent += 1,
+            Err(error) => {
+                self.failed += 1;
+                *self.errors.entry(error).or_default() += 1;
+            }
+        }
+    }
+
+    fn print(&self, elapsed: Duration) {
+        let Self { sent, failed, .. } = self;
+        let actual_rate = *sent as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+        output::result(
+            format!("  Txs: {sent} sent / {failed} failed, {actual_rate:.1} tx/s"),
+            json!({
+                "sent_tx": sent,
+                "failed_tx": failed,
+                "elapsed_ms": elapsed.as_millis(),
+            }),
+        );
+    }
+
+    fn print_errors(&self) {
+        for (error, count) in &self.errors {
+            output::result(
+                format!("  Failed {count} times: {error}"),
+                json!({ "error": error, "count": count }),
+            );
+        }
+    }
+}
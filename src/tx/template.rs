@@ -0,0 +1,358 @@
+//! YAML description of transactions and their instructions, shared by `build-and-send` and `load`.
+//!
+//! Templates can have placeholders, that are substituted for every transaction built from the
+//! template, using the transaction index:
+//!
+//! * A signer with `keypairs: [...]`, rather than a single `keypair`, is a pool.  Transactions
+//!   use pool keypairs in turn.  Pools accept `dir://` entries, that expand into all the matching
+//!   keypair files.
+//! * An `index: <width>` data field encodes the transaction index as an unsigned integer of the
+//!   specified width.  Wider indices are truncated.
+
+use std::{fs, path::Path};
+
+use anyhow::{Context as _, Result, bail};
+use base64::{self, Engine as _};
+use pythnet_heisenberg::keypair_ext::{KEYPAIR_DIR_PREFIX, expand_keypair_dir, read_signer};
+use serde::Deserialize;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signer::Signer,
+};
+
+use crate::serde_pubkey;
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Template {
+    pub signers: Vec<SignerConfig>,
+    pub fee_payer: Option<String>,
+    pub transactions: Vec<TransactionConfig>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SignerConfig {
+    name: String,
+    /// Either `keypair` or `keypairs` is required.
+    keypair: Option<String>,
+    #[serde(default)]
+    keypairs: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionConfig {
+    instructions: Vec<InstructionConfig>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct InstructionConfig {
+    #[serde(deserialize_with = "serde_pubkey::from_str")]
+    program_id: Pubkey,
+    #[serde(default)]
+    accounts: Vec<AccountConfig>,
+    // Allows `data: { hex: ... }`, rather than the `data: !hex ...` form `serde_yaml` uses for
+    // enums by default.
+    #[serde(default, with = "serde_yaml::with::singleton_map_recursive")]
+    data: Option<DataConfig>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct AccountConfig {
+    /// Either `pubkey` or `signer` is required.
+    #[serde(default, deserialize_with = "serde_pubkey::optional_from_str")]
+    pubkey: Option<Pubkey>,
+    /// Name of one of the `signers`.
+    signer: Option<String>,
+    #[serde(default)]
+    writable: bool,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum DataConfig {
+    Base64(String),
+    Hex(String),
+    Fields(Vec<Field>),
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+enum Field {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    Bool(bool),
+    #[serde(deserialize_with = "serde_pubkey::from_str")]
+    Pubkey(Pubkey),
+    Hex(String),
+    String(String),
+    /// Transaction index placeholder.
+    Index(IndexWidth),
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum IndexWidth {
+    U8,
+    U16,
+    U32,
+    U64,
+}
+
+pub fn read_template(path: &Path) -> Result<Template> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read template: {}", path.to_string_lossy()))?;
+    serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse template: {}", path.to_string_lossy()))
+}
+
+/// Signers of a template, with the keypairs loaded.
+pub struct Signers {
+    signers: Vec<NamedSigner>,
+}
+
+struct NamedSigner {
+    name: String,
+    /// A single entry, unless the signer is a pool.
+    keypairs: Vec<(Box<dyn Signer>, Pubkey)>,
+}
+
+impl Signers {
+    /// Keypair paths are relative to the `base_dir`.
+    pub fn load(configs: Vec<SignerConfig>, base_dir: &Path) -> Result<Self> {
+        let mut signers: Vec<NamedSigner> = vec![];
+        for SignerConfig {
+            name,
+            keypair,
+            keypairs,
+        } in configs
+        {
+            if signers.iter().any(|existing| existing.name == name) {
+                bail!("Signer \"{name}\" is defined more than once");
+            }
+            let sources = match (keypair, keypairs.is_empty()) {
+                (Some(keypair), true) => vec![keypair],
+                (None, false) => keypairs,
+                _ => bail!("Signer \"{name}\" needs exactly one of `keypair` or `keypairs`"),
+            };
+            let sources = sources
+                .into_iter()
+                .map(|source| {
+                    if let Some(pattern) = source.strip_prefix(KEYPAIR_DIR_PREFIX) {
+                        let pattern = base_dir.join(pattern);
+                        return expand_keypair_dir(&format!(
+                            "{KEYPAIR_DIR_PREFIX}{}",
+                            pattern.to_string_lossy()
+                        ))
+                        .with_context(|| format!("Signer \"{name}\""));
+                    }
+                    // Hardware wallet URIs are not paths.
+                    if source.starts_with("usb://") {
+                        Ok(vec![source.into()])
+                    } else {
+                        Ok(vec![base_dir.join(source)])
+                    }
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let keypairs = sources
+                .into_iter()
+                .flatten()
+                .map(|source| {
+                    let signer = read_signer(&source, &name)?;
+                    let pubkey = signer.pubkey();
+                    Ok((signer, pubkey))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            signers.push(NamedSigner { name, keypairs });
+        }
+        Ok(Self { signers })
+    }
+
+    /// Address of the `name` signer, for the transaction at `index`.
+    pub fn pubkey(&self, name: &str, index: u64) -> Option<Pubkey> {
+        let NamedSigner { keypairs, .. } =
+            self.signers.iter().find(|signer| signer.name == name)?;
+        let (_signer, pubkey) = &keypairs[pool_position(index, keypairs.len())];
+        Some(*pubkey)
+    }
+
+    /// Fee payer for the transaction at `index`.  Defaults to the first signer.
+    pub fn fee_payer(&self, fee_payer: Option<&str>, index: u64) -> Result<Pubkey> {
+        match fee_payer {
+            Some(name) => self
+                .pubkey(name, index)
+                .with_context(|| format!("fee_payer: unknown signer \"{name}\"")),
+            None => match self.signers.first() {
+                Some(NamedSigner { name, .. }) => Ok(self
+                    .pubkey(name, index)
+                    .expect("Signers always have at least one keypair")),
+                None => bail!("At least one signer is required to pay for the transactions"),
+            },
+        }
+    }
+
+    /// Only the fee payer and the signers referenced by the `instructions` sign a transaction.
+    pub fn for_transaction(
+        &self,
+        fee_payer: Pubkey,
+        instructions: &[Instruction],
+    ) -> Vec<&dyn Signer> {
+        self.signers
+            .iter()
+            .flat_map(|NamedSigner { keypairs, .. }| keypairs)
+            .filter(|(_signer, pubkey)| {
+                *pubkey == fee_payer
+                    || instructions.iter().any(|instruction| {
+                        instruction
+                            .accounts
+                            .iter()
+                            .any(|meta| meta.is_signer && meta.pubkey == *pubkey)
+                    })
+            })
+            .map(|(signer, _pubkey)| signer.as_ref())
+            .collect()
+    }
+}
+
+fn pool_position(index: u64, len: usize) -> usize {
+    // `len` is never zero, and the remainder is below `len`.
+    (index % len as u64) as usize
+}
+
+/// Builds instructions of a transaction at `index`, substituting the placeholders.
+pub fn build_instructions(
+    TransactionConfig { instructions }: &TransactionConfig,
+    signers: &Signers,
+    index: u64,
+) -> Result<Vec<Instruction>> {
+    if instructions.is_empty() {
+        bail!("Transaction does not have any instructions");
+    }
+
+    instructions
+        .iter()
+        .enumerate()
+        .map(|(ix_index, instruction)| {
+            build_instruction(instruction, signers, index)
+                .with_context(|| format!("Instruction {}", ix_index + 1))
+        })
+        .collect()
+}
+
+fn build_instruction(
+    InstructionConfig {
+        program_id,
+        accounts,
+        data,
+    }: &InstructionConfig,
+    signers: &Signers,
+    index: u64,
+) -> Result<Instruction> {
+    let accounts = accounts
+        .iter()
+        .map(
+            |AccountConfig {
+                 pubkey,
+                 signer,
+                 writable,
+             }| {
+                let (pubkey, is_signer) = match (pubkey, signer) {
+                    (Some(pubkey), None) => (*pubkey, false),
+                    (None, Some(name)) => {
+                        let pubkey = signers
+                            .pubkey(name, index)
+                            .with_context(|| format!("Unknown signer \"{name}\""))?;
+                        (pubkey, true)
+                    }
+                    _ => bail!("Every account needs exactly one of `pubkey` or `signer`"),
+                };
+                Ok(AccountMeta {
+                    pubkey,
+                    is_signer,
+                    is_writable: *writable,
+                })
+            },
+        )
+        .collect::<Result<Vec<_>>>()?;
+
+    let data = match data {
+        None => vec![],
+        Some(DataConfig::Base64(data)) => base64::engine::general_purpose::STANDARD
+            .decode(data.trim())
+            .context("Invalid base64 data")?,
+        Some(DataConfig::Hex(data)) => decode_hex(data)?,
+        Some(DataConfig::Fields(fields)) => encode_fields(fields, index)?,
+    };
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+fn encode_fields(fields: &[Field], index: u64) -> Result<Vec<u8>> {
+    let mut data = vec![];
+    for field in fields {
+        match field {
+            Field::U8(value) => data.push(*value),
+            Field::U16(value) => data.extend(value.to_le_bytes()),
+            Field::U32(value) => data.extend(value.to_le_bytes()),
+            Field::U64(value) => data.extend(value.to_le_bytes()),
+            Field::I8(value) => data.extend(value.to_le_bytes()),
+            Field::I16(value) => data.extend(value.to_le_bytes()),
+            Field::I32(value) => data.extend(value.to_le_bytes()),
+            Field::I64(value) => data.extend(value.to_le_bytes()),
+            Field::Bool(value) => data.push(u8::from(*value)),
+            Field::Pubkey(value) => data.extend(value.to_bytes()),
+            Field::Hex(value) => data.extend(decode_hex(value)?),
+            Field::String(value) => {
+                let len = u32::try_from(value.len()).context("String field is too long")?;
+                data.extend(len.to_le_bytes());
+                data.extend(value.as_bytes());
+            }
+            Field::Index(width) => {
+                let bytes = index.to_le_bytes();
+                let len = match width {
+                    IndexWidth::U8 => 1,
+                    IndexWidth::U16 => 2,
+                    IndexWidth::U32 => 4,
+                    IndexWidth::U64 => 8,
+                };
+                data.extend(&bytes[..len]);
+            }
+        }
+    }
+    Ok(data)
+}
+
+/// Accepts an optional `0x` prefix, and whitespace between bytes.
+fn decode_hex(input: &str) -> Result<Vec<u8>> {
+    let digits = input
+        .trim()
+        .trim_start_matches("0x")
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+    if digits.len() % 2 != 0 {
+        bail!("Hex data has an odd number of digits: {input}");
+    }
+
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let byte = pair.iter().collect::<String>();
+            u8::from_str_radix(&byte, 16).with_context(|| format!("Invalid hex byte: {byte}"))
+        })
+        .collect()
+}
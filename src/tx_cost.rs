@@ -0,0 +1,306 @@
+//! Measures the on-chain cost of transactions a command has sent: compute units consumed and fees
+//! paid.  The data comes from the transaction metadata, so it is only available once transactions
+//! are confirmed.
+//!
+//! When the slots transactions were sent in are known, also measures how many slots it took for
+//! them to land.
+
+use futures::{StreamExt as _, stream};
+use log::warn;
+use pythnet_heisenberg::output;
+use rand::{Rng as _, rng};
+use serde_json::{Value, json};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::RpcTransactionConfig;
+use solana_sdk::{clock::Slot, commitment_config::CommitmentConfig, signature::Signature};
+use solana_transaction_status::{UiTransactionEncoding, UiTransactionStatusMeta};
+
+/// How many `getTransaction` requests to run in parallel.
+const FETCH_CONCURRENCY: usize = 16;
+
+/// A uniform random sample of the signatures of all the transactions sent during a long run.
+/// Fetching metadata for every transaction of a benchmark would take too long.
+///
+/// Every signature is paired with the slot the transaction was sent in.
+pub struct SignatureSample {
+    limit: usize,
+    seen: u64,
+    sample: Vec<(Signature, Slot)>,
+}
+
+impl SignatureSample {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            seen: 0,
+            sample: Vec::with_capacity(limit),
+        }
+    }
+
+    /// Reservoir sampling: every signature added so far has the same chance to be in the sample.
+    pub fn add(&mut self, signature: Signature, sent_slot: Slot) {
+        self.seen += 1;
+        if self.sample.len() < self.limit {
+            self.sample.push((signature, sent_slot));
+            return;
+        }
+
+        let index = rng().random_range(0..self.seen);
+        if let Some(slot) = usize::try_from(index)
+            .ok()
+            .and_then(|index| self.sample.get_mut(index))
+        {
+            *slot = (signature, sent_slot);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sample.len()
+    }
+}
+
+/// Compute units and fees of a set of transactions.
+pub struct CostSummary {
+    /// Transactions metadata was requested for.
+    requested: usize,
+    /// Sorted compute units consumed by each transaction that was found.
+    compute_units: Vec<u64>,
+    /// Fees paid by each transaction that was found, in lamports.
+    fees: Vec<u64>,
+    /// Sorted number of slots between the send and the landing of each transaction that was
+    /// found.  Empty, when the send slots are not known.
+    landing_latencies: Vec<u64>,
+    /// The send slot, and the landing slot, if the transaction was found, for every requested
+    /// transaction.  Empty, when the send slots are not known.
+    landings: Vec<(Slot, Option<Slot>)>,
+}
+
+impl CostSummary {
+    /// Fetches metadata for all the `signatures`.  Transactions that are not found, or that fail to
+    /// be fetched, are excluded from the summary.
+    pub async fn collect(rpc_client: &RpcClient, signatures: &[Signature]) -> Self {
+        let transactions = fetch_transactions(rpc_client, signatures).await;
+        Self::new(
+            signatures.len(),
+            transactions.into_iter().flatten().map(|(_slot, meta)| meta),
+        )
+    }
+
+    /// Same as [`collect()`](Self::collect), for all the signatures in the `sample`.  Also
+    /// measures the landing latency.
+    pub async fn collect_sample(rpc_client: &RpcClient, sample: &SignatureSample) -> Self {
+        let signatures = sample
+            .sample
+            .iter()
+            .map(|(signature, _sent_slot)| *signature)
+            .collect::<Vec<_>>();
+        let transactions = fetch_transactions(rpc_client, &signatures).await;
+
+        let landings = sample
+            .sample
+            .iter()
+            .zip(&transactions)
+            .map(|((_signature, sent_slot), transaction)| {
+                (*sent_slot, transaction.as_ref().map(|(slot, _meta)| *slot))
+            })
+            .collect::<Vec<_>>();
+        // Send slots are estimates, so a transaction may appear to land before it was sent.
+        let mut landing_latencies = landings
+            .iter()
+            .filter_map(|(sent_slot, slot)| Some(slot.as_ref()?.saturating_sub(*sent_slot)))
+            .collect::<Vec<_>>();
+        landing_latencies.sort_unstable();
+
+        Self {
+            landing_latencies,
+            landings,
+            ..Self::new(
+                signatures.len(),
+                transactions.into_iter().flatten().map(|(_slot, meta)| meta),
+            )
+        }
+    }
+
+    fn new(requested: usize, metas: impl IntoIterator<Item = UiTransactionStatusMeta>) -> Self {
+        let mut compute_units = vec![];
+        let mut fees = vec![];
+        for meta in metas {
+            fees.push(meta.fee);
+            if let Some(units) = Option::<u64>::from(meta.compute_units_consumed) {
+                compute_units.push(units);
+            }
+        }
+        compute_units.sort_unstable();
+
+        Self {
+            requested,
+            compute_units,
+            fees,
+            landing_latencies: vec![],
+            landings: vec![],
+        }
+    }
+
+    /// Percentage of the requested transactions that were found, if any were requested.
+    /// Transactions that failed to be fetched count as not landed.
+    pub fn landing_rate(&self) -> Option<f64> {
+        let Self {
+            requested, fees, ..
+        } = self;
+        (*requested != 0).then(|| fees.len() as f64 / *requested as f64 * 100.0)
+    }
+
+    /// The send slot, and the landing slot, if the transaction was found, for every requested
+    /// transaction.  Empty, when the send slots are not known.
+    pub fn landings(&self) -> &[(Slot, Option<Slot>)] {
+        &self.landings
+    }
+
+    /// Median number of slots it took for a transaction to land, if the send slots are known.
+    pub fn median_landing_latency(&self) -> Option<u64> {
+        (!self.landing_latencies.is_empty()).then(|| percentile_of(&self.landing_latencies, 50))
+    }
+
+    /// Prints the summary.  `label` marks the output when there is more than one summary.  When
+    /// `items_per_tx` is specified, per item costs are reported as well, with `item_name` used
+    /// in the text output.
+    pub fn print(&self, label: Option<&str>, items_per_tx: Option<(u64, &str)>) {
+        let Self {
+            requested,
+            compute_units,
+            fees,
+            landing_latencies,
+            landings: _,
+        } = self;
+        let found = fees.len();
+
+        let prefix = label.map(|label| format!("[{label}] ")).unwrap_or_default();
+        let mut text = format!("  {prefix}Costs of {found} out of {requested} transactions:");
+        let mut json = json!({
+            "requested_tx": requested,
+            "found_tx": found,
+        });
+
+        let total_fee = fees.iter().sum::<u64>();
+        let average_fee = (found > 0).then(|| total_fee as f64 / found as f64);
+        let average_units = (!compute_units.is_empty())
+            .then(|| compute_units.iter().sum::<u64>() as f64 / compute_units.len() as f64);
+
+        match average_units {
+            Some(average_units) => {
+                let min = compute_units[0];
+                let median = percentile_of(compute_units, 50);
+                let p90 = percentile_of(compute_units, 90);
+                let max = compute_units[compute_units.len() - 1];
+                text.push_str(&format!(
+                    "\n    Compute units: avg {average_units:.0} / min {min} / p50 {median} / \
+                     p90 {p90} / max {max}"
+                ));
+                json["compute_units"] = json!({
+                    "average": average_units,
+                    "min": min,
+                    "median": median,
+                    "p90": p90,
+                    "max": max,
+                });
+            }
+            None => text.push_str("\n    Compute units: n/a"),
+        }
+
+        match average_fee {
+            Some(average_fee) => {
+                text.push_str(&format!(
+                    "\n    Fees: total {total_fee} lamports / avg {average_fee:.0} lamports"
+                ));
+                json["fees"] = json!({
+                    "total_lamports": total_fee,
+                    "average_lamports": average_fee,
+                });
+            }
+            None => text.push_str("\n    Fees: n/a"),
+        }
+
+        if !landing_latencies.is_empty() {
+            let average =
+                landing_latencies.iter().sum::<u64>() as f64 / landing_latencies.len() as f64;
+            let median = percentile_of(landing_latencies, 50);
+            let p90 = percentile_of(landing_latencies, 90);
+            let max = landing_latencies[landing_latencies.len() - 1];
+            text.push_str(&format!(
+                "\n    Landing latency, slots: avg {average:.1} / p50 {median} / p90 {p90} / \
+                 max {max}"
+            ));
+            json["landing_latency_slots"] = json!({
+                "average": average,
+                "median": median,
+                "p90": p90,
+                "max": max,
+            });
+        }
+
+        if let Some((items_per_tx, item_name)) = items_per_tx {
+            let per_item = |value: Option<f64>| value.map(|value| value / items_per_tx as f64);
+            let units_per_item = per_item(average_units);
+            let fee_per_item = per_item(average_fee);
+            if let (Some(units_per_item), Some(fee_per_item)) = (units_per_item, fee_per_item) {
+                text.push_str(&format!(
+                    "\n    Per {item_name}: {units_per_item:.0} compute units / \
+                     {fee_per_item:.0} lamports"
+                ));
+            }
+            json["per_item"] = json!({
+                "items_per_tx": items_per_tx,
+                "compute_units": units_per_item,
+                "fee_lamports": fee_per_item,
+            });
+        }
+
+        if let Some(label) = label {
+            json["cluster"] = Value::from(label);
+        }
+
+        output::result(text, json!({ "costs": json }));
+    }
+}
+
+/// Fetches the landing slot and the metadata of every transaction in `signatures`, in the same
+/// order.  `None` for transactions that are not found, or that fail to be fetched.
+async fn fetch_transactions(
+    rpc_client: &RpcClient,
+    signatures: &[Signature],
+) -> Vec<Option<(Slot, UiTransactionStatusMeta)>> {
+    // `getTransaction` does not support the `processed` commitment.
+    let commitment = if rpc_client.commitment() == CommitmentConfig::processed() {
+        CommitmentConfig::confirmed()
+    } else {
+        rpc_client.commitment()
+    };
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Base64),
+        commitment: Some(commitment),
+        max_supported_transaction_version: Some(0),
+    };
+
+    stream::iter(signatures)
+        .map(|signature| async move {
+            match rpc_client
+                .get_transaction_with_config(signature, config)
+                .await
+            {
+                Ok(tx) => Some((tx.slot, tx.transaction.meta?)),
+                Err(err) => {
+                    warn!("Failed to fetch transaction {signature}: {err}");
+                    None
+                }
+            }
+        })
+        .buffered(FETCH_CONCURRENCY)
+        .collect()
+        .await
+}
+
+/// Nearest rank percentile of a non-empty sorted list of values.
+fn percentile_of(sorted: &[u64], percentile: usize) -> u64 {
+    let rank = (percentile * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
@@ -0,0 +1,162 @@
+//! `--tx-records-file`: one CSV row per transaction attempt, for offline analysis, for example with
+//! pandas or DuckDB.
+//!
+//! [`tx_sheppard`](crate::tx_sheppard) writes a row every time an attempt to land a transaction
+//! ends: when it is confirmed, when the send or the execution fails, or when the transaction does
+//! not show up in the chain and is sent again.  Benchmarks send transactions once, without waiting
+//! for them to land, so their rows only hold the send time, and the send error, if any.  Use
+//! `tx landing-report` to see which of them landed.
+//!
+//! Only CSV is supported.  Both pandas and DuckDB read it directly, and can convert it into Parquet
+//! if necessary.
+//!
+//! Recording is disabled by default, and [`write()`] does nothing until [`open()`] is called.
+
+use std::{
+    borrow::Cow,
+    fs::{File, OpenOptions},
+    io::{BufWriter, Write as _},
+    path::Path,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::SystemTime,
+};
+
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use log::warn;
+use solana_sdk::{clock::Slot, signature::Signature};
+
+const CSV_HEADER: &str = "index,label,signature,send_time,confirm_time,slot,retries,error";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static FILE: Mutex<Option<BufWriter<File>>> = Mutex::new(None);
+
+/// One attempt to land a transaction.
+#[derive(Debug, Clone, Copy)]
+pub struct TxRecord<'a> {
+    /// Position of the transaction among the transactions sent by a command.  All the attempts to
+    /// land the same transaction share the index.
+    pub index: u64,
+    pub label: Option<&'a str>,
+    pub signature: Signature,
+    pub sent_at: SystemTime,
+    /// When the transaction was seen at the target commitment level.  Only known when the
+    /// transaction landed, successfully or not.
+    pub confirmed_at: Option<SystemTime>,
+    /// Slot the transaction landed in.
+    pub slot: Option<Slot>,
+    /// Number of attempts made before this one.
+    pub retries: usize,
+    pub error: Option<&'a str>,
+}
+
+/// Starts recording into the file at the `path`.  Rows are appended, so the same file can be used
+/// across multiple runs.  The header row is only written into an empty file.
+pub fn open(path: &Path) -> Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| {
+            format!(
+                "Failed to open transaction records file: {}",
+                path.to_string_lossy()
+            )
+        })?;
+    let is_empty = file
+        .metadata()
+        .context("Failed to check the transaction records file size")?
+        .len()
+        == 0;
+
+    let mut file = BufWriter::new(file);
+    if is_empty {
+        writeln!(file, "{CSV_HEADER}")
+            .context("Failed to write into the transaction records file")?;
+    }
+
+    *FILE
+        .lock()
+        .expect("Transaction records lock is not poisoned") = Some(file);
+    ENABLED.store(true, Ordering::Relaxed);
+
+    Ok(())
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Appends a row for the `record`, if recording is enabled.
+///
+/// A failed write does not stop the command that sends the transactions, so it is only logged.
+pub fn write(record: &TxRecord) {
+    if !is_enabled() {
+        return;
+    }
+
+    let TxRecord {
+        index,
+        label,
+        signature,
+        sent_at,
+        confirmed_at,
+        slot,
+        retries,
+        error,
+    } = *record;
+
+    let mut file = FILE
+        .lock()
+        .expect("Transaction records lock is not poisoned");
+    let Some(file) = file.as_mut() else {
+        return;
+    };
+
+    let res = writeln!(
+        file,
+        "{index},{},{signature},{},{},{},{retries},{}",
+        csv_field(label.unwrap_or_default()),
+        format_time(sent_at),
+        confirmed_at.map(format_time).unwrap_or_default(),
+        slot.map(|slot| slot.to_string()).unwrap_or_default(),
+        csv_field(error.unwrap_or_default()),
+    );
+    if let Err(err) = res {
+        warn!("Failed to write into the transaction records file: {err}");
+    }
+}
+
+/// Flushes and closes the file.  Recording stops until the next [`open()`] call.
+pub fn close() {
+    ENABLED.store(false, Ordering::Relaxed);
+
+    let file = FILE
+        .lock()
+        .expect("Transaction records lock is not poisoned")
+        .take();
+    let Some(mut file) = file else {
+        return;
+    };
+    if let Err(err) = file.flush() {
+        warn!("Failed to write into the transaction records file: {err}");
+    }
+}
+
+/// RFC 3339 in UTC, with milliseconds.  Both pandas and DuckDB parse it as a timestamp.
+fn format_time(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Labels and error messages are free form text, and are quoted when necessary.
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
@@ -4,12 +4,38 @@
 //! [`TxSheppard`] is a solution to this problem, including a retry of the transaction execution, up
 //! to the specified number of times.
 //!
+//! Not every failure is worth a retry.  Errors are classified, see [`ErrorClass`], and a program
+//! error, or a fee payer without enough funds, fails the transaction right away.  An expired
+//! blockhash is retried as soon as a fresh blockhash is available, and an unhealthy RPC node gets
+//! an exponentially growing delay between the attempts.
+//!
 //! It also shows progress on the terminal, providing for a nice UI.
+//!
+//! Transactions can depend on each other, for multi-step setups, where an account needs to be
+//! created before it is initialized.  Independent chains still run in parallel.
+//!
+//! Builders may fail to sign, when one of the signers is a hardware wallet, see [`BuiltTx`].  Such
+//! a transaction fails right away, without any retries.
+//!
+//! Every attempt to land a transaction is written into the [`tx_records`] file, when one is open.
+//!
+//! In the [`dry_run`] mode, transactions are built and described, but not sent.
 
-use std::{cmp, collections::HashSet, time::Duration};
+use std::{
+    cmp,
+    collections::HashSet,
+    io,
+    net::{Ipv4Addr, SocketAddr},
+    time::{Duration, SystemTime},
+};
 
-use anyhow::Result;
-use futures::{StreamExt as _, future::BoxFuture, stream::FuturesUnordered};
+use anyhow::{Context as _, Result, bail};
+use bincode::{self, serde::encode_to_vec};
+use futures::{
+    StreamExt as _,
+    future::{self, BoxFuture},
+    stream::FuturesUnordered,
+};
 use indicatif::{ProgressBar, ProgressStyle};
 use itertools::izip;
 use log::warn;
@@ -17,40 +43,102 @@ use serde_json::json;
 use solana_program::vote::state::MAX_LOCKOUT_HISTORY;
 use solana_rpc_client::nonblocking::rpc_client::RpcClient;
 use solana_rpc_client_api::{
-    client_error::Error as RpcClientError, request::RpcRequest, response::Response as RpcResponse,
+    client_error::{Error as RpcClientError, ErrorKind as RpcClientErrorKind},
+    config::RpcSendTransactionConfig,
+    custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+    request::{RpcError, RpcResponseErrorData},
 };
 use solana_sdk::{
+    clock::Slot,
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    hash::Hash,
     signature::Signature,
+    signer::SignerError,
     transaction::{Transaction, TransactionError},
 };
-use solana_transaction_status::TransactionStatus;
 use tokio::{
+    net::UdpSocket,
     pin, select,
     time::{self, Instant, sleep},
 };
 use tokio_util::sync::CancellationToken;
 
-use crate::blockhash_cache::BlockhashCache;
+use crate::{
+    blockhash_cache::BlockhashCache,
+    cluster_rpc::ClusterRpc,
+    dry_run,
+    node_address_service::{NodeAddressService, SlotLeader},
+    output,
+    tx_records::{self, TxRecord},
+};
+
+#[cfg(test)]
+mod tests;
+
+/// How long to wait for a sent transaction to show up in the chain, before sending it again.
+///
+/// Would be nice to have this delay as a configuration option, similar to the other delays.  5
+/// slots allows us to wait for the next leader, but otherwise it is a rather random choice.  Plus
+/// time does not exactly match slots.
+const MAX_ABSENT_SLOTS: u64 = 5;
+
+/// How often the blockhash cache is refreshed while the transactions are executed.
+const BLOCKHASH_REFRESH_INTERVAL: Duration = Duration::from_millis(400);
+
+/// Upper bound for the delay between attempts when backing off from an unhealthy RPC node.
+const MAX_BACKOFF_DELAY: Duration = Duration::from_secs(10);
+
+/// What a transaction builder produces.  Builders with signers that can not fail, like keypairs,
+/// return a [`Transaction`], while builders with remote signers, like hardware wallets, return the
+/// result of [`try_new_signed_with_payer()`].
+///
+/// [`try_new_signed_with_payer()`]: crate::keypair_ext::try_new_signed_with_payer
+pub trait BuiltTx {
+    fn into_result(self) -> Result<Transaction, SignerError>;
+}
+
+impl BuiltTx for Transaction {
+    fn into_result(self) -> Result<Transaction, SignerError> {
+        Ok(self)
+    }
+}
+
+impl BuiltTx for Result<Transaction, SignerError> {
+    fn into_result(self) -> Result<Transaction, SignerError> {
+        self
+    }
+}
 
-pub fn with_sheppard(rpc_client: &RpcClient) -> RunWithTxSheppardArgs<'_> {
+/// Accepts any [`ClusterRpc`] implementation, though it is normally an [`RpcClient`].
+pub fn with_sheppard<Rpc: ClusterRpc + ?Sized>(rpc_client: &Rpc) -> RunWithTxSheppardArgs<'_, Rpc> {
     RunWithTxSheppardArgs {
         rpc_client,
         shutdown: None,
         rpc_failure_retry_delay: None,
         status_failure_retry_delay: None,
         retry_count: None,
+        skip_preflight: false,
+        commitment: None,
+        leaders: None,
+        labels: None,
+        dependencies: None,
     }
 }
 
-pub struct RunWithTxSheppardArgs<'rpc_client> {
-    rpc_client: &'rpc_client RpcClient,
+pub struct RunWithTxSheppardArgs<'rpc_client, Rpc: ?Sized = RpcClient> {
+    rpc_client: &'rpc_client Rpc,
     shutdown: Option<CancellationToken>,
     rpc_failure_retry_delay: Option<Duration>,
     status_failure_retry_delay: Option<Duration>,
     retry_count: Option<usize>,
+    skip_preflight: bool,
+    commitment: Option<CommitmentLevel>,
+    leaders: Option<(NodeAddressService, u64)>,
+    labels: Option<Vec<String>>,
+    dependencies: Option<Vec<Vec<usize>>>,
 }
 
-impl<'rpc_client> RunWithTxSheppardArgs<'rpc_client> {
+impl<'rpc_client, Rpc: ClusterRpc + ?Sized> RunWithTxSheppardArgs<'rpc_client, Rpc> {
     #[allow(unused)]
     pub fn shutdown_via(mut self, shutdown: CancellationToken) -> Self {
         self.shutdown = Some(shutdown);
@@ -69,19 +157,78 @@ impl<'rpc_client> RunWithTxSheppardArgs<'rpc_client> {
         self
     }
 
+    /// Number of times a transaction is retried, for the errors that are worth a retry.  See
+    /// [`ErrorClass`].
     #[allow(unused)]
     pub fn retry_count(mut self, count: usize) -> Self {
         self.retry_count = Some(count);
         self
     }
 
-    pub async fn run<'context, TxBuilder>(
+    /// Send transactions without running a preflight simulation on the RPC node.
+    #[allow(unused)]
+    pub fn skip_preflight(mut self, skip_preflight: bool) -> Self {
+        self.skip_preflight = skip_preflight;
+        self
+    }
+
+    /// Consider transactions executed once they reach this commitment level.
+    ///
+    /// Defaults to the commitment level of the `rpc_client`.
+    #[allow(unused)]
+    pub fn commitment(mut self, commitment: CommitmentLevel) -> Self {
+        self.commitment = Some(commitment);
+        self
+    }
+
+    /// Send transactions over UDP directly to the TPU ports of the leaders that cover the current
+    /// and the next `fanout_slots` slots, rather than via the RPC node.  The RPC node is still used
+    /// to track the transaction status.
+    ///
+    /// Saves an RPC round trip per transaction, which adds up on high latency connections.  There
+    /// is no preflight simulation on this path.  When no leader addresses are known, transactions
+    /// are sent via the RPC node, with the preflight skipped.
+    #[allow(unused)]
+    pub fn send_to_leaders(
+        mut self,
+        node_address_service: NodeAddressService,
+        fanout_slots: u64,
+    ) -> Self {
+        self.leaders = Some((node_address_service, fanout_slots));
+        self
+    }
+
+    /// Human readable labels for the transactions, in the same order as the builders, like the
+    /// recipient of a transfer.  Failures are reported with the label, rather than only with the
+    /// transaction index.
+    #[allow(unused)]
+    pub fn labels(mut self, labels: impl IntoIterator<Item = impl ToString>) -> Self {
+        self.labels = Some(labels.into_iter().map(|label| label.to_string()).collect());
+        self
+    }
+
+    /// For every builder, indices of the builders whose transactions need to succeed before its
+    /// transaction is sent.  Transactions that do not depend on each other are still sent in
+    /// parallel.  When a transaction fails, all the transactions that depend on it, directly or
+    /// indirectly, fail without being sent.
+    ///
+    /// Missing entries mean no dependencies.  Dependency cycles are reported as errors.
+    #[allow(unused)]
+    pub fn dependencies(mut self, dependencies: Vec<Vec<usize>>) -> Self {
+        self.dependencies = Some(dependencies);
+        self
+    }
+
+    /// Executes transactions produced by the `tx_builders`, returning an outcome for each of them,
+    /// in the same order as the builders.
+    pub async fn run<'context, TxBuilder, Tx>(
         self,
         tx_builders: impl Iterator<Item = TxBuilder> + Clone + 'context,
-    ) -> Result<()>
+    ) -> Result<Vec<TxOutcome>>
     where
         'rpc_client: 'context,
-        TxBuilder: Fn(/* blockhash_cache: */ &BlockhashCache) -> Transaction + 'context,
+        TxBuilder: Fn(/* blockhash_cache: */ &BlockhashCache) -> Tx + 'context,
+        Tx: BuiltTx,
     {
         let Self {
             rpc_client,
@@ -89,14 +236,49 @@ impl<'rpc_client> RunWithTxSheppardArgs<'rpc_client> {
             rpc_failure_retry_delay,
             status_failure_retry_delay,
             retry_count,
+            skip_preflight,
+            commitment,
+            leaders,
+            labels,
+            dependencies,
         } = self;
 
+        if dry_run::is_enabled() {
+            let blockhash_cache = BlockhashCache::uninitialized();
+            let transactions = tx_builders
+                .map(|builder| builder(&blockhash_cache).into_result())
+                .collect::<Result<Vec<_>, _>>()
+                .context("Failed to sign a transaction")?;
+            let stopped = dry_run::describe(
+                rpc_client,
+                &transactions,
+                labels.as_deref(),
+                dependencies.as_deref().unwrap_or_default(),
+            )
+            .await;
+            return Err(stopped.into());
+        }
+
         let shutdown = shutdown.unwrap_or_else(CancellationToken::new);
         let rpc_failure_retry_delay =
             rpc_failure_retry_delay.unwrap_or_else(|| Duration::from_millis(400));
         let status_failure_retry_delay =
             status_failure_retry_delay.unwrap_or_else(|| Duration::from_millis(3 * 400));
         let retry_count = retry_count.unwrap_or(3);
+        let commitment = commitment
+            .map(|commitment| CommitmentConfig { commitment })
+            .unwrap_or_else(|| rpc_client.commitment());
+
+        let send_path = match leaders {
+            None => SendPath::Rpc { skip_preflight },
+            Some((node_address_service, fanout_slots)) => SendPath::Leaders {
+                node_address_service,
+                fanout_slots,
+                socket: UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+                    .await
+                    .context("Creation of a UDP socket")?,
+            },
+        };
 
         run_impl(
             rpc_client,
@@ -104,32 +286,44 @@ impl<'rpc_client> RunWithTxSheppardArgs<'rpc_client> {
             rpc_failure_retry_delay,
             status_failure_retry_delay,
             retry_count,
+            &send_path,
+            commitment,
+            labels.as_deref(),
+            dependencies.as_deref().unwrap_or_default(),
             tx_builders,
         )
         .await
     }
 }
 
-async fn run_impl<'rpc_client, 'context, TxBuilder>(
-    rpc_client: &'rpc_client RpcClient,
+#[allow(clippy::too_many_arguments)]
+async fn run_impl<'rpc_client, 'context, Rpc, TxBuilder, Tx>(
+    rpc_client: &'rpc_client Rpc,
     shutdown: CancellationToken,
     rpc_failure_retry_delay: Duration,
     status_failure_retry_delay: Duration,
     retry_count: usize,
+    send_path: &SendPath,
+    commitment: CommitmentConfig,
+    labels: Option<&[String]>,
+    dependencies: &[Vec<usize>],
     tx_builders: impl Iterator<Item = TxBuilder> + 'context,
-) -> Result<()>
+) -> Result<Vec<TxOutcome>>
 where
     'rpc_client: 'context,
-    TxBuilder: Fn(/* blockhash_cache: */ &BlockhashCache) -> Transaction + 'context,
+    Rpc: ClusterRpc + ?Sized,
+    TxBuilder: Fn(/* blockhash_cache: */ &BlockhashCache) -> Tx + 'context,
+    Tx: BuiltTx,
 {
     let tx_builders = tx_builders.collect::<Vec<_>>();
+    let mut dependency_graph = DependencyGraph::new(tx_builders.len(), dependencies)?;
 
     let blockhash_cache = BlockhashCache::uninitialized();
     blockhash_cache.init(rpc_client).await;
     let blockhash_cache = &blockhash_cache;
 
     let blockhash_cache_refresh_task =
-        blockhash_cache.run_refresh_loop(rpc_client, Duration::from_millis(400), shutdown.clone());
+        blockhash_cache.run_refresh_loop(rpc_client, BLOCKHASH_REFRESH_INTERVAL, shutdown.clone());
     pin!(blockhash_cache_refresh_task);
 
     let tx_builder_count = tx_builders.len();
@@ -137,9 +331,25 @@ where
     let mut execution_status =
         vec![TargetExecutionStatus::Sending { retry_count }; tx_builder_count];
 
+    let attempts = AttemptRecorder {
+        labels,
+        retry_count,
+    };
+
+    let mut built_txs = BuiltTxs::new(tx_builder_count);
+
     let mut sending_txs = izip!(0usize.., tx_builders.iter())
+        .filter(|(idx, _builder)| dependency_graph.is_ready(*idx))
         .map(|(idx, builder)| {
-            send_one_tx(rpc_client, blockhash_cache, Duration::ZERO, idx, builder)
+            send_one_tx(
+                rpc_client,
+                blockhash_cache,
+                &mut built_txs,
+                send_path,
+                Duration::ZERO,
+                idx,
+                builder,
+            )
         })
         .collect::<FuturesUnordered<_>>();
 
@@ -163,6 +373,7 @@ where
         &mut last_status_check,
         &execution_status,
         &in_status_check,
+        commitment,
     );
 
     while !sending_txs.is_empty() || !in_status_check.is_empty() {
@@ -172,11 +383,16 @@ where
                 Some(send_res) => apply_send_result(
                     rpc_client,
                     blockhash_cache,
+                    &mut built_txs,
                     &tx_builders,
                     &mut execution_status,
                     &mut sending_txs,
                     &mut in_status_check,
+                    &mut dependency_graph,
+                    &mut failed_count,
+                    send_path,
                     rpc_failure_retry_delay,
+                    &attempts,
                     send_res,
                 ),
             },
@@ -185,13 +401,17 @@ where
                     Ok(status_results) => apply_status_result(
                         rpc_client,
                         blockhash_cache,
+                        &mut built_txs,
                         &tx_builders,
                         &mut execution_status,
                         &mut sending_txs,
                         &mut in_status_check,
+                        &mut dependency_graph,
                         &mut succeeded_count,
                         &mut failed_count,
+                        send_path,
                         status_failure_retry_delay,
+                        &attempts,
                         status_results,
                     ),
                     Err(error) => {
@@ -203,15 +423,18 @@ where
                     &mut last_status_check,
                     &execution_status,
                     &in_status_check,
+                    commitment,
                 );
             }
             _instant = progrss_update_timer.tick() => update_progress_bar(
                 &progress_bar,
                 sending_txs.len(),
+                dependency_graph.waiting(),
                 &execution_status,
                 &in_status_check,
                 succeeded_count,
                 failed_count,
+                send_path.current_leader(),
             ),
             () = &mut blockhash_cache_refresh_task => {
                 panic!("BlockhashCache should not stop until requested");
@@ -224,10 +447,12 @@ where
     update_progress_bar(
         &progress_bar,
         sending_txs.len(),
+        dependency_graph.waiting(),
         &execution_status,
         &in_status_check,
         succeeded_count,
         failed_count,
+        send_path.current_leader(),
     );
     progress_bar.finish_and_clear();
 
@@ -235,78 +460,286 @@ where
     blockhash_cache_refresh_task.await;
 
     if failed_count > 0 {
-        for status in execution_status {
+        for (idx, status) in execution_status.iter().enumerate() {
             let TargetExecutionStatus::Failed(error) = status else {
                 continue;
             };
-            println!("Transaction failed: {error}");
+            report_failure(idx, labels.and_then(|labels| labels.get(idx)), error);
+        }
+    }
+
+    let outcomes = execution_status
+        .into_iter()
+        .map(|status| match status {
+            TargetExecutionStatus::Success { signature } => TxOutcome::Success(signature),
+            TargetExecutionStatus::Failed(error) => TxOutcome::Failed(error),
+            TargetExecutionStatus::Sending { .. }
+            | TargetExecutionStatus::WaitingConfirmation { .. } => {
+                panic!("All transactions should be either successful or failed at this point")
+            }
+        })
+        .collect();
+
+    Ok(outcomes)
+}
+
+/// How transactions are delivered to the cluster.
+enum SendPath {
+    /// Via the RPC node `sendTransaction` call.
+    Rpc { skip_preflight: bool },
+    /// Directly to the TPU ports of the upcoming leaders.
+    Leaders {
+        node_address_service: NodeAddressService,
+        fanout_slots: u64,
+        socket: UdpSocket,
+    },
+}
+
+impl SendPath {
+    /// Only known when sending directly to the leaders.
+    fn current_leader(&self) -> Option<SlotLeader> {
+        match self {
+            SendPath::Rpc { .. } => None,
+            SendPath::Leaders {
+                node_address_service,
+                ..
+            } => node_address_service.current_leader(),
+        }
+    }
+}
+
+/// Last transaction built for each of the targets, along with the blockhash that was current when
+/// it was built.
+///
+/// Retries reuse the transaction until the blockhash changes, rather than building and signing an
+/// identical transaction again.  Signing dominates the CPU usage for large batches.
+struct BuiltTxs(Vec<Option<(Hash, Transaction)>>);
+
+impl BuiltTxs {
+    fn new(count: usize) -> Self {
+        Self(vec![None; count])
+    }
+
+    fn get_or_build<TxBuilder, Tx>(
+        &mut self,
+        idx: usize,
+        blockhash_cache: &BlockhashCache,
+        builder: TxBuilder,
+    ) -> Result<Transaction, SignerError>
+    where
+        TxBuilder: Fn(/* blockhash_cache: */ &BlockhashCache) -> Tx,
+        Tx: BuiltTx,
+    {
+        let blockhash = blockhash_cache.get();
+        match &self.0[idx] {
+            Some((built_with, tx)) if *built_with == blockhash => Ok(tx.clone()),
+            _ => {
+                let tx = builder(blockhash_cache).into_result()?;
+                self.0[idx] = Some((blockhash, tx.clone()));
+                Ok(tx)
+            }
         }
     }
 
-    Ok(())
+    /// A retry would build a new transaction, as the blockhash cache moved past the blockhash the
+    /// last transaction for the `idx` target was built with.
+    fn has_newer_blockhash(&self, idx: usize, blockhash_cache: &BlockhashCache) -> bool {
+        match &self.0[idx] {
+            Some((built_with, _tx)) => *built_with != blockhash_cache.get(),
+            None => true,
+        }
+    }
 }
 
-fn send_one_tx<'rpc_client, 'context, TxBuilder>(
-    rpc_client: &'rpc_client RpcClient,
+fn send_one_tx<'rpc_client, 'context, Rpc, TxBuilder, Tx>(
+    rpc_client: &'rpc_client Rpc,
     blockhash_cache: &BlockhashCache,
+    built_txs: &mut BuiltTxs,
+    send_path: &'context SendPath,
     delay: Duration,
     idx: usize,
     builder: TxBuilder,
 ) -> BoxFuture<'context, TxSendResult>
 where
     'rpc_client: 'context,
-    TxBuilder: Fn(/* blockhash_cache: */ &BlockhashCache) -> Transaction,
+    Rpc: ClusterRpc + ?Sized,
+    TxBuilder: Fn(/* blockhash_cache: */ &BlockhashCache) -> Tx,
+    Tx: BuiltTx,
 {
-    let tx = builder(blockhash_cache);
+    let tx = match built_txs.get_or_build(idx, blockhash_cache, builder) {
+        Ok(tx) => tx,
+        Err(error) => {
+            // Nothing was sent, so there is no signature to report.
+            return Box::pin(future::ready(TxSendResult::Fail {
+                idx,
+                signature: Signature::default(),
+                sent_at: SystemTime::now(),
+                error: Box::new(RpcClientErrorKind::SigningError(error).into()),
+            }));
+        }
+    };
     Box::pin(async move {
         if !delay.is_zero() {
             sleep(delay).await;
         }
 
-        let res = rpc_client.send_transaction(&tx).await;
-        TxSendResult::from_result(idx, res)
+        let signature = tx.signatures[0];
+        let sent_at = SystemTime::now();
+        let res = match send_path {
+            SendPath::Rpc { skip_preflight } => {
+                send_via_rpc(rpc_client, &tx, *skip_preflight).await
+            }
+            SendPath::Leaders {
+                node_address_service,
+                fanout_slots,
+                socket,
+            } => {
+                send_to_leaders(rpc_client, node_address_service, *fanout_slots, socket, &tx).await
+            }
+        };
+        match res {
+            Ok(signature) => TxSendResult::Success {
+                idx,
+                signature,
+                sent_at,
+            },
+            Err(error) => TxSendResult::Fail {
+                idx,
+                signature,
+                sent_at,
+                error: Box::new(error),
+            },
+        }
     })
 }
 
+async fn send_via_rpc<Rpc: ClusterRpc + ?Sized>(
+    rpc_client: &Rpc,
+    tx: &Transaction,
+    skip_preflight: bool,
+) -> Result<Signature, RpcClientError> {
+    rpc_client
+        .send_transaction_with_config(
+            tx,
+            RpcSendTransactionConfig {
+                skip_preflight,
+                preflight_commitment: Some(rpc_client.commitment().commitment),
+                ..RpcSendTransactionConfig::default()
+            },
+        )
+        .await
+}
+
+async fn send_to_leaders<Rpc: ClusterRpc + ?Sized>(
+    rpc_client: &Rpc,
+    node_address_service: &NodeAddressService,
+    fanout_slots: u64,
+    socket: &UdpSocket,
+    tx: &Transaction,
+) -> Result<Signature, RpcClientError> {
+    let mut target_nodes: Vec<SocketAddr> = vec![];
+    node_address_service.get_tpu_for_next_in_schedule(&mut target_nodes, fanout_slots);
+    if target_nodes.is_empty() {
+        return send_via_rpc(rpc_client, tx, true).await;
+    }
+
+    let buf = encode_to_vec(tx, bincode::config::legacy()).map_err(io::Error::other)?;
+
+    // A transaction is considered sent if at least one of the leaders received it.  Otherwise, the
+    // last error is reported.
+    let mut sent = false;
+    let mut last_error = None;
+    for node_address in target_nodes {
+        match socket.send_to(&buf, node_address).await {
+            Ok(_) => sent = true,
+            Err(err) => last_error = Some(err),
+        }
+    }
+
+    match last_error {
+        Some(err) if !sent => Err(err.into()),
+        _ => Ok(tx.signatures[0]),
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
-fn apply_send_result<'rpc_client, 'context, TxBuilder>(
-    rpc_client: &'rpc_client RpcClient,
+fn apply_send_result<'rpc_client, 'context, Rpc, TxBuilder, Tx>(
+    rpc_client: &'rpc_client Rpc,
     blockhash_cache: &BlockhashCache,
+    built_txs: &mut BuiltTxs,
     tx_builders: &[TxBuilder],
     execution_status: &mut [TargetExecutionStatus],
     sending_txs: &mut FuturesUnordered<BoxFuture<'context, TxSendResult>>,
     in_status_check: &mut HashSet<usize>,
+    dependency_graph: &mut DependencyGraph,
+    failed_count: &mut u64,
+    send_path: &'context SendPath,
     retry_delay: Duration,
+    attempts: &AttemptRecorder,
     send_result: TxSendResult,
 ) where
     'rpc_client: 'context,
-    TxBuilder: Fn(/* blockhash_cache: */ &BlockhashCache) -> Transaction,
+    Rpc: ClusterRpc + ?Sized,
+    TxBuilder: Fn(/* blockhash_cache: */ &BlockhashCache) -> Tx,
+    Tx: BuiltTx,
 {
     match send_result {
-        TxSendResult::Success { idx, signature } => {
-            execution_status[idx].send_success(signature);
+        TxSendResult::Success {
+            idx,
+            signature,
+            sent_at,
+        } => {
+            execution_status[idx].send_success(signature, sent_at);
             in_status_check.insert(idx);
         }
-        TxSendResult::Fail { idx, error } => {
-            let retry = execution_status[idx].send_failed(error);
+        TxSendResult::Fail {
+            idx,
+            signature,
+            sent_at,
+            error,
+        } => {
+            if tx_records::is_enabled() {
+                attempts.record(
+                    idx,
+                    signature,
+                    sent_at,
+                    execution_status[idx].retries_left(),
+                    None,
+                    Some(&error.to_string()),
+                );
+            }
+            let class = ErrorClass::of_send_error(&error);
+            let retries_used = attempts.retries_used(execution_status[idx].retries_left());
+            let retry = execution_status[idx].send_failed(*error, class.is_retried());
             if retry {
+                let delay = class.retry_delay(
+                    retry_delay,
+                    retries_used,
+                    built_txs.has_newer_blockhash(idx, blockhash_cache),
+                );
                 sending_txs.push(send_one_tx(
                     rpc_client,
                     blockhash_cache,
-                    retry_delay,
+                    built_txs,
+                    send_path,
+                    delay,
                     idx,
                     &tx_builders[idx],
                 ));
+            } else {
+                *failed_count += 1;
+                fail_dependents(dependency_graph, execution_status, failed_count, idx);
             }
         }
     }
 }
 
-fn start_status_check<'rpc_client>(
-    rpc_client: &'rpc_client RpcClient,
+fn start_status_check<'rpc_client, Rpc: ClusterRpc + ?Sized>(
+    rpc_client: &'rpc_client Rpc,
     last_status_check: &mut Instant,
     execution_status: &[TargetExecutionStatus],
     in_status_check: &HashSet<usize>,
+    commitment: CommitmentConfig,
 ) -> BoxFuture<'rpc_client, Result<Vec<TxStatusResult>, RpcClientError>> {
     let now = Instant::now();
     let iteration_time = now.duration_since(*last_status_check);
@@ -314,17 +747,10 @@ fn start_status_check<'rpc_client>(
     let delay = Duration::from_millis(500).saturating_sub(iteration_time);
     *last_status_check = now + delay;
 
-    let (indices, signatures): (Vec<usize>, Vec<String>) = in_status_check
+    let (indices, signatures): (Vec<usize>, Vec<Signature>) = in_status_check
         .iter()
         .copied()
-        .map(|idx| {
-            (
-                idx,
-                execution_status[idx]
-                    .signature_for_status_check()
-                    .to_string(),
-            )
-        })
+        .map(|idx| (idx, *execution_status[idx].signature_for_status_check()))
         .unzip();
 
     Box::pin(async move {
@@ -336,10 +762,7 @@ fn start_status_check<'rpc_client>(
             return Ok(vec![]);
         }
 
-        let results: RpcResponse<Vec<Option<TransactionStatus>>> = rpc_client
-            .send(RpcRequest::GetSignatureStatuses, json!([signatures]))
-            .await?;
-        let results = results.value;
+        let results = rpc_client.get_signature_statuses(&signatures).await?;
 
         let res = izip!(indices.into_iter(), results.into_iter())
             .map(|(idx, result)| {
@@ -347,16 +770,19 @@ fn start_status_check<'rpc_client>(
                     return TxStatusResult::Absent { idx };
                 };
 
-                match tx_status.confirmations {
-                    None => match tx_status.err {
-                        None => TxStatusResult::Success { idx },
-                        Some(error) => TxStatusResult::Fail { idx, error },
-                    },
-                    Some(confirmations) => {
-                        let confirmations = u8::try_from(confirmations).unwrap_or(u8::MAX);
-                        TxStatusResult::Pending { idx, confirmations }
-                    }
+                if tx_status.satisfies_commitment(commitment) {
+                    let slot = tx_status.slot;
+                    return match tx_status.err {
+                        None => TxStatusResult::Success { idx, slot },
+                        Some(error) => TxStatusResult::Fail { idx, error, slot },
+                    };
                 }
+
+                // `satisfies_commitment()` is always `true` for finalized transactions, so
+                // `confirmations` should always be present here.
+                let confirmations = tx_status.confirmations.unwrap_or(usize::MAX);
+                let confirmations = u8::try_from(confirmations).unwrap_or(u8::MAX);
+                TxStatusResult::Pending { idx, confirmations }
             })
             .collect::<Vec<_>>();
 
@@ -365,74 +791,165 @@ fn start_status_check<'rpc_client>(
 }
 
 #[allow(clippy::too_many_arguments)]
-fn apply_status_result<'rpc_client, 'context, TxBuilder>(
-    rpc_client: &'rpc_client RpcClient,
+fn apply_status_result<'rpc_client, 'context, Rpc, TxBuilder, Tx>(
+    rpc_client: &'rpc_client Rpc,
     blockhash_cache: &BlockhashCache,
+    built_txs: &mut BuiltTxs,
     tx_builders: &[TxBuilder],
     execution_status: &mut [TargetExecutionStatus],
     sending_txs: &mut FuturesUnordered<BoxFuture<'context, TxSendResult>>,
     in_status_check: &mut HashSet<usize>,
+    dependency_graph: &mut DependencyGraph,
     succeeded_count: &mut u64,
     failed_count: &mut u64,
+    send_path: &'context SendPath,
     retry_delay: Duration,
+    attempts: &AttemptRecorder,
     status_results: Vec<TxStatusResult>,
 ) where
     'rpc_client: 'context,
-    TxBuilder: Fn(/* blockhash_cache: */ &BlockhashCache) -> Transaction,
+    Rpc: ClusterRpc + ?Sized,
+    TxBuilder: Fn(/* blockhash_cache: */ &BlockhashCache) -> Tx,
+    Tx: BuiltTx,
 {
     for status_result in status_results.into_iter() {
         match status_result {
-            TxStatusResult::Success { idx } => {
+            TxStatusResult::Success { idx, slot } => {
                 in_status_check.remove(&idx);
+                let (signature, sent_at, retries_left) = execution_status[idx].sent();
+                attempts.record(idx, signature, sent_at, retries_left, Some(slot), None);
                 execution_status[idx].status_success();
                 *succeeded_count += 1;
-            }
-            TxStatusResult::Absent { idx } => match execution_status[idx].status_absent() {
-                StatusAbsentAction::WaitMore => (),
-                StatusAbsentAction::Retry => {
-                    in_status_check.remove(&idx);
+                for ready in dependency_graph.succeeded(idx) {
                     sending_txs.push(send_one_tx(
                         rpc_client,
                         blockhash_cache,
-                        retry_delay,
-                        idx,
-                        &tx_builders[idx],
+                        built_txs,
+                        send_path,
+                        Duration::ZERO,
+                        ready,
+                        &tx_builders[ready],
                     ));
                 }
-                StatusAbsentAction::Failed => {
-                    in_status_check.remove(&idx);
-                    *failed_count += 1;
+            }
+            TxStatusResult::Absent { idx } => {
+                let (signature, sent_at, retries_left) = execution_status[idx].sent();
+                let action = execution_status[idx].status_absent();
+                if !matches!(action, StatusAbsentAction::WaitMore) {
+                    attempts.record(
+                        idx,
+                        signature,
+                        sent_at,
+                        retries_left,
+                        None,
+                        Some(&format!(
+                            "Transaction not present in the chain after {MAX_ABSENT_SLOTS} slots"
+                        )),
+                    );
                 }
-            },
+                match action {
+                    StatusAbsentAction::WaitMore => (),
+                    StatusAbsentAction::Retry => {
+                        in_status_check.remove(&idx);
+                        sending_txs.push(send_one_tx(
+                            rpc_client,
+                            blockhash_cache,
+                            built_txs,
+                            send_path,
+                            retry_delay,
+                            idx,
+                            &tx_builders[idx],
+                        ));
+                    }
+                    StatusAbsentAction::Failed => {
+                        in_status_check.remove(&idx);
+                        *failed_count += 1;
+                        fail_dependents(dependency_graph, execution_status, failed_count, idx);
+                    }
+                }
+            }
             TxStatusResult::Pending { idx, confirmations } => {
                 execution_status[idx].status_pending(confirmations);
             }
-            TxStatusResult::Fail { idx, error } => {
+            TxStatusResult::Fail { idx, error, slot } => {
                 in_status_check.remove(&idx);
-                let retry = execution_status[idx].status_failed(error);
+                let (signature, sent_at, retries_left) = execution_status[idx].sent();
+                if tx_records::is_enabled() {
+                    attempts.record(
+                        idx,
+                        signature,
+                        sent_at,
+                        retries_left,
+                        Some(slot),
+                        Some(&error.to_string()),
+                    );
+                }
+                let class = ErrorClass::of_tx_error(&error);
+                let retry = execution_status[idx].status_failed(error, class.is_retried());
                 if retry {
+                    let delay = class.retry_delay(
+                        retry_delay,
+                        attempts.retries_used(retries_left),
+                        built_txs.has_newer_blockhash(idx, blockhash_cache),
+                    );
                     sending_txs.push(send_one_tx(
                         rpc_client,
                         blockhash_cache,
-                        retry_delay,
+                        built_txs,
+                        send_path,
+                        delay,
                         idx,
                         &tx_builders[idx],
                     ));
                 } else {
                     *failed_count += 1;
+                    fail_dependents(dependency_graph, execution_status, failed_count, idx);
                 }
             }
         }
     }
 }
 
+/// Transactions that depend on the failed transaction at `idx` fail without being sent.
+fn fail_dependents(
+    dependency_graph: &mut DependencyGraph,
+    execution_status: &mut [TargetExecutionStatus],
+    failed_count: &mut u64,
+    idx: usize,
+) {
+    for dependent in dependency_graph.failed(idx) {
+        execution_status[dependent].dependency_failed(idx);
+        *failed_count += 1;
+    }
+}
+
+fn report_failure(idx: usize, label: Option<&String>, error: &str) {
+    let text = match label {
+        Some(label) => format!("Transaction #{idx} ({label}) failed: {error}"),
+        None => format!("Transaction #{idx} failed: {error}"),
+    };
+    output::result(
+        text,
+        json!({
+            "failed_tx": {
+                "index": idx,
+                "label": label,
+                "error": error,
+            },
+        }),
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
 fn update_progress_bar(
     progress_bar: &ProgressBar,
     sending: usize,
+    waiting: usize,
     execution_status: &[TargetExecutionStatus],
     in_status_check: &HashSet<usize>,
     succeeded: u64,
     failed: u64,
+    leader: Option<SlotLeader>,
 ) {
     progress_bar.tick();
 
@@ -446,62 +963,80 @@ fn update_progress_bar(
         .unwrap_or(0);
     let min_confirmations = cmp::min(min_confirmations, MAX_CONFIRMATIONS);
 
-    if failed == 0 {
-        progress_bar.set_message(format!(
-            "[{min_confirmations}/{MAX_CONFIRMATIONS}] \
-             Sending: {sending} / Confirming: {awaiting_confirmation} / Succeeded: {succeeded}"
-        ));
-    } else {
-        progress_bar.set_message(format!(
-            "[{min_confirmations}/{MAX_CONFIRMATIONS}] \
-             Sending: {sending} / Confirming: {awaiting_confirmation} / Succeeded: {succeeded} \
-             Failed: {failed}"
-        ));
+    let mut message = format!(
+        "[{min_confirmations}/{MAX_CONFIRMATIONS}] \
+         Sending: {sending} / Confirming: {awaiting_confirmation} / Succeeded: {succeeded}"
+    );
+    if failed != 0 {
+        message.push_str(&format!(" Failed: {failed}"));
+    }
+    if waiting != 0 {
+        message.push_str(&format!(" / Waiting for dependencies: {waiting}"));
     }
+    // A stall is often caused by a single leader, that does not process transactions.
+    if let Some(leader) = leader {
+        message.push_str(&format!(" / Leader: {leader}"));
+    }
+    progress_bar.set_message(message);
+}
+
+/// Final state of a transaction executed by [`RunWithTxSheppardArgs::run()`].
+#[derive(Debug, Clone)]
+pub enum TxOutcome {
+    /// Transaction was executed successfully, with this signature.
+    Success(Signature),
+    /// We ran out of retries for this transaction.  Holds the last error.
+    Failed(String),
 }
 
 #[derive(Debug, Clone)]
 pub enum TargetExecutionStatus {
     /// An async operation that is sending the transaction into the cluster has been started, but
     /// not completed yet.
-    Sending {
-        retry_count: usize,
-    },
+    Sending { retry_count: usize },
     /// Transaction was sent, and we are waiting for it to be accepted.
     WaitingConfirmation {
         /// Moment when we started waiting for this target to land a transaction.
         wait_start: Instant,
+        /// Wall clock time of the send, for the transaction records.
+        sent_at: SystemTime,
         /// When we retry, the next status will have this field decreased.
         retry_count: usize,
         signature: Signature,
         /// Number of confirmations this transaction received.
         confirmations: Option<u8>,
     },
-    Success,
+    Success {
+        /// Signature of the transaction that landed.
+        signature: Signature,
+    },
     /// We ran out of retires for this target, and so we just record the last error.
     Failed(String),
 }
 
 impl TargetExecutionStatus {
-    fn send_success(&mut self, signature: Signature) {
+    fn send_success(&mut self, signature: Signature, sent_at: SystemTime) {
         *self = match self {
             Self::Sending { retry_count } => Self::WaitingConfirmation {
                 wait_start: Instant::now(),
+                sent_at,
                 retry_count: *retry_count,
                 signature,
                 confirmations: None,
             },
             Self::WaitingConfirmation { .. } => panic!("Currently in `WaitingConfirmation` state"),
-            Self::Success => panic!("Currently in `Success` state"),
+            Self::Success { .. } => panic!("Currently in `Success` state"),
             Self::Failed(_) => panic!("Currently in `Failed` state"),
         }
     }
 
-    fn send_failed(&mut self, error: RpcClientError) -> bool {
+    /// Returns `true` if the transaction should be sent again.  Errors that are not worth a `retry`
+    /// fail the transaction, even if there are retries left.
+    fn send_failed(&mut self, error: RpcClientError, retry: bool) -> bool {
         let res;
 
         (*self, res) = match self {
-            Self::Sending { retry_count } if *retry_count > 0 => (
+            Self::Sending { retry_count } if retry && *retry_count > 0 => (
                 Self::Sending {
                     retry_count: *retry_count - 1,
                 },
@@ -509,18 +1044,44 @@ impl TargetExecutionStatus {
             ),
             Self::Sending { retry_count: _ } => (Self::Failed(error.to_string()), false),
             Self::WaitingConfirmation { .. } => panic!("Currently in `WaitingConfirmation` state"),
-            Self::Success => panic!("Currently in `Success` state"),
+            Self::Success { .. } => panic!("Currently in `Success` state"),
             Self::Failed(_) => panic!("Currently in `Failed` state"),
         };
 
         res
     }
 
+    /// Number of retries left after the attempt that is currently in progress.
+    fn retries_left(&self) -> usize {
+        match self {
+            Self::Sending { retry_count } => *retry_count,
+            Self::WaitingConfirmation { retry_count, .. } => *retry_count,
+            Self::Success { .. } => panic!("Currently in `Success` state"),
+            Self::Failed(_) => panic!("Currently in `Failed` state"),
+        }
+    }
+
+    /// Signature and send time of the transaction that is waiting for confirmation, and the number
+    /// of retries left.
+    fn sent(&self) -> (Signature, SystemTime, usize) {
+        match self {
+            Self::Sending { .. } => panic!("Currently in `Sending` state"),
+            Self::WaitingConfirmation {
+                signature,
+                sent_at,
+                retry_count,
+                ..
+            } => (*signature, *sent_at, *retry_count),
+            Self::Success { .. } => panic!("Currently in `Success` state"),
+            Self::Failed(_) => panic!("Currently in `Failed` state"),
+        }
+    }
+
     fn signature_for_status_check(&self) -> &Signature {
         match self {
             Self::Sending { .. } => panic!("Currently in `Sending` state"),
             Self::WaitingConfirmation { signature, .. } => signature,
-            Self::Success => panic!("Currently in `Success` state"),
+            Self::Success { .. } => panic!("Currently in `Success` state"),
             Self::Failed(_) => panic!("Currently in `Failed` state"),
         }
     }
@@ -528,18 +1089,15 @@ impl TargetExecutionStatus {
     fn status_success(&mut self) {
         *self = match self {
             Self::Sending { .. } => panic!("Currently in `Sending` state"),
-            Self::WaitingConfirmation { .. } => Self::Success,
-            Self::Success => panic!("Currently in `Success` state"),
+            Self::WaitingConfirmation { signature, .. } => Self::Success {
+                signature: *signature,
+            },
+            Self::Success { .. } => panic!("Currently in `Success` state"),
             Self::Failed(_) => panic!("Currently in `Failed` state"),
         }
     }
 
     fn status_absent(&mut self) -> StatusAbsentAction {
-        // Would be nice to have this delay as a configuration option, similar to the other delays.
-        // 5 slots allows us to wait for the next leader, but otherwise it is a rather random
-        // choice.  Plus time does not exactly match slots.
-        const MAX_ABSENT_SLOTS: u64 = 5;
-
         match self {
             Self::Sending { .. } => panic!("Currently in `Sending` state"),
             Self::WaitingConfirmation {
@@ -561,7 +1119,7 @@ impl TargetExecutionStatus {
                     StatusAbsentAction::Failed
                 }
             }
-            Self::Success => panic!("Currently in `Success` state"),
+            Self::Success { .. } => panic!("Currently in `Success` state"),
             Self::Failed(_) => panic!("Currently in `Failed` state"),
         }
     }
@@ -572,34 +1130,48 @@ impl TargetExecutionStatus {
             Self::WaitingConfirmation { confirmations, .. } => {
                 *confirmations = Some(new_confirmations)
             }
-            Self::Success => panic!("Currently in `Success` state"),
+            Self::Success { .. } => panic!("Currently in `Success` state"),
             Self::Failed(_) => panic!("Currently in `Failed` state"),
         }
     }
 
-    fn status_failed(&mut self, error: TransactionError) -> bool {
+    /// Same as [`Self::send_failed()`], for a transaction that was executed with an `error`.
+    fn status_failed(&mut self, error: TransactionError, retry: bool) -> bool {
         let res;
         (*self, res) = match self {
             Self::Sending { .. } => panic!("Currently in `Sending` state"),
-            Self::WaitingConfirmation { retry_count, .. } if *retry_count > 0 => (
+            Self::WaitingConfirmation { retry_count, .. } if retry && *retry_count > 0 => (
                 Self::Sending {
                     retry_count: *retry_count - 1,
                 },
                 true,
             ),
             Self::WaitingConfirmation { .. } => (Self::Failed(error.to_string()), false),
-            Self::Success => panic!("Currently in `Success` state"),
+            Self::Success { .. } => panic!("Currently in `Success` state"),
             Self::Failed(_) => panic!("Currently in `Failed` state"),
         };
 
         res
     }
 
+    /// A transaction that is waiting for its dependencies is never sent, as the dependency at
+    /// `dependency_idx` failed.
+    fn dependency_failed(&mut self, dependency_idx: usize) {
+        *self = match self {
+            Self::Sending { .. } => Self::Failed(format!(
+                "Depends on transaction #{dependency_idx}, that failed"
+            )),
+            Self::WaitingConfirmation { .. } => panic!("Currently in `WaitingConfirmation` state"),
+            Self::Success { .. } => panic!("Currently in `Success` state"),
+            Self::Failed(_) => panic!("Currently in `Failed` state"),
+        }
+    }
+
     fn status_confirmations(&self) -> u8 {
         match self {
             Self::Sending { .. } => panic!("Currently in `Sending` state"),
             Self::WaitingConfirmation { confirmations, .. } => confirmations.unwrap_or(0),
-            Self::Success => panic!("Currently in `Success` state"),
+            Self::Success { .. } => panic!("Currently in `Success` state"),
             Self::Failed(_) => panic!("Currently in `Failed` state"),
         }
     }
@@ -612,22 +1184,277 @@ enum StatusAbsentAction {
 }
 
 enum TxSendResult {
-    Success { idx: usize, signature: Signature },
-    Fail { idx: usize, error: RpcClientError },
+    Success {
+        idx: usize,
+        signature: Signature,
+        sent_at: SystemTime,
+    },
+    Fail {
+        idx: usize,
+        signature: Signature,
+        sent_at: SystemTime,
+        error: Box<RpcClientError>,
+    },
 }
 
-impl TxSendResult {
-    fn from_result(idx: usize, res: Result<Signature, RpcClientError>) -> Self {
-        match res {
-            Ok(signature) => Self::Success { idx, signature },
-            Err(error) => Self::Fail { idx, error },
+enum TxStatusResult {
+    Success {
+        idx: usize,
+        slot: Slot,
+    },
+    Absent {
+        idx: usize,
+    },
+    Pending {
+        idx: usize,
+        confirmations: u8,
+    },
+    Fail {
+        idx: usize,
+        error: TransactionError,
+        slot: Slot,
+    },
+}
+
+/// Writes the attempts that ended into the [`tx_records`], when enabled.
+struct AttemptRecorder<'labels> {
+    labels: Option<&'labels [String]>,
+    /// Number of retries every transaction starts with.
+    retry_count: usize,
+}
+
+impl AttemptRecorder<'_> {
+    fn retries_used(&self, retries_left: usize) -> usize {
+        self.retry_count.saturating_sub(retries_left)
+    }
+
+    /// An attempt ends either without landing, or in the `landed_slot`, with an optional `error`.
+    fn record(
+        &self,
+        idx: usize,
+        signature: Signature,
+        sent_at: SystemTime,
+        retries_left: usize,
+        landed_slot: Option<Slot>,
+        error: Option<&str>,
+    ) {
+        tx_records::write(&TxRecord {
+            index: idx as u64,
+            label: self
+                .labels
+                .and_then(|labels| labels.get(idx))
+                .map(String::as_str),
+            signature,
+            sent_at,
+            confirmed_at: landed_slot.map(|_slot| SystemTime::now()),
+            slot: landed_slot,
+            retries: self.retries_used(retries_left),
+            error,
+        });
+    }
+}
+
+/// Errors a transaction attempt can fail with, grouped by how the transaction is retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// The blockhash is too old, or the RPC node does not know it yet.  The transaction is rebuilt
+    /// and resent as soon as the blockhash cache has a newer blockhash.
+    BlockhashExpired,
+    /// The fee payer can not pay for the transaction.  Not retried, as it would fail the same way.
+    InsufficientFunds,
+    /// Another transaction holds a lock on one of the accounts.  Retried after the usual delay.
+    AccountInUse,
+    /// The RPC node is behind the cluster, or can not be reached.  Retried with an exponential
+    /// backoff, to give the node time to recover.
+    NodeUnhealthy,
+    /// An instruction failed.  Program errors are deterministic, so they are not retried.
+    ProgramError,
+    /// A signer, such as a hardware wallet, failed to sign the transaction.  Not retried, as the
+    /// user would be asked to sign it again.
+    SigningFailed,
+    /// Anything else is retried after the usual delay.
+    Other,
+}
+
+impl ErrorClass {
+    fn of_send_error(error: &RpcClientError) -> Self {
+        // Preflight failures carry the error of the simulated transaction.
+        if let Some(error) = error.get_transaction_error() {
+            return Self::of_tx_error(&error);
+        }
+
+        match error.kind() {
+            RpcClientErrorKind::RpcError(RpcError::RpcResponseError {
+                code: JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+                ..
+            })
+            | RpcClientErrorKind::RpcError(RpcError::RpcResponseError {
+                data: RpcResponseErrorData::NodeUnhealthy { .. },
+                ..
+            })
+            | RpcClientErrorKind::Io(_)
+            | RpcClientErrorKind::Reqwest(_) => Self::NodeUnhealthy,
+            RpcClientErrorKind::SigningError(_) => Self::SigningFailed,
+            _ => Self::Other,
+        }
+    }
+
+    fn of_tx_error(error: &TransactionError) -> Self {
+        match error {
+            TransactionError::BlockhashNotFound => Self::BlockhashExpired,
+            TransactionError::InsufficientFundsForFee
+            | TransactionError::InsufficientFundsForRent { .. } => Self::InsufficientFunds,
+            TransactionError::AccountInUse => Self::AccountInUse,
+            TransactionError::InstructionError(..) => Self::ProgramError,
+            _ => Self::Other,
+        }
+    }
+
+    fn is_retried(self) -> bool {
+        match self {
+            Self::InsufficientFunds | Self::ProgramError | Self::SigningFailed => false,
+            Self::BlockhashExpired | Self::AccountInUse | Self::NodeUnhealthy | Self::Other => true,
+        }
+    }
+
+    /// Delay before the next attempt.  `delay` is the configured retry delay, and `retries_used` is
+    /// the number of retries made before the failed attempt.
+    fn retry_delay(
+        self,
+        delay: Duration,
+        retries_used: usize,
+        has_newer_blockhash: bool,
+    ) -> Duration {
+        match self {
+            Self::BlockhashExpired if has_newer_blockhash => Duration::ZERO,
+            // Retrying with the same blockhash would fail the same way.
+            Self::BlockhashExpired => BLOCKHASH_REFRESH_INTERVAL,
+            Self::NodeUnhealthy => {
+                let factor = 2u32.saturating_pow(u32::try_from(retries_used).unwrap_or(u32::MAX));
+                delay.saturating_mul(factor).min(MAX_BACKOFF_DELAY)
+            }
+            Self::InsufficientFunds
+            | Self::ProgramError
+            | Self::SigningFailed
+            | Self::AccountInUse
+            | Self::Other => delay,
         }
     }
 }
 
-enum TxStatusResult {
-    Success { idx: usize },
-    Absent { idx: usize },
-    Pending { idx: usize, confirmations: u8 },
-    Fail { idx: usize, error: TransactionError },
+/// Order of execution for the transactions set up with [`RunWithTxSheppardArgs::dependencies()`].
+struct DependencyGraph {
+    /// For every transaction, the number of its dependencies that did not succeed yet.
+    remaining: Vec<usize>,
+    /// For every transaction, transactions that depend on it.
+    dependents: Vec<Vec<usize>>,
+    /// Transactions that are not sent yet, because of their dependencies.
+    waiting: usize,
+}
+
+impl DependencyGraph {
+    fn new(count: usize, dependencies: &[Vec<usize>]) -> Result<Self> {
+        if dependencies.len() > count {
+            bail!(
+                "Dependencies are specified for {} transactions, while there are only {count}",
+                dependencies.len()
+            );
+        }
+
+        let mut remaining = vec![0; count];
+        let mut dependents = vec![vec![]; count];
+        for (idx, idx_dependencies) in dependencies.iter().enumerate() {
+            for &dependency in idx_dependencies.iter().collect::<HashSet<_>>() {
+                if dependency >= count {
+                    bail!(
+                        "Transaction #{idx} depends on #{dependency}, while there are only \
+                         {count} transactions"
+                    );
+                }
+                remaining[idx] += 1;
+                dependents[dependency].push(idx);
+            }
+        }
+
+        let graph = Self {
+            waiting: remaining
+                .iter()
+                .filter(|remaining| **remaining != 0)
+                .count(),
+            remaining,
+            dependents,
+        };
+        graph.check_no_cycles()?;
+        Ok(graph)
+    }
+
+    /// Every transaction needs to be reachable from the ones without dependencies.
+    fn check_no_cycles(&self) -> Result<()> {
+        let mut remaining = self.remaining.clone();
+        let mut ready = (0..remaining.len())
+            .filter(|idx| remaining[*idx] == 0)
+            .collect::<Vec<_>>();
+        let mut reached = 0;
+        while let Some(idx) = ready.pop() {
+            reached += 1;
+            for &dependent in &self.dependents[idx] {
+                remaining[dependent] -= 1;
+                if remaining[dependent] == 0 {
+                    ready.push(dependent);
+                }
+            }
+        }
+
+        if reached != remaining.len() {
+            let in_cycle = (0..remaining.len())
+                .filter(|idx| remaining[*idx] != 0)
+                .map(|idx| format!("#{idx}"))
+                .collect::<Vec<_>>();
+            bail!(
+                "Transaction dependencies form a cycle, involving transactions: {}",
+                in_cycle.join(", ")
+            );
+        }
+
+        Ok(())
+    }
+
+    fn is_ready(&self, idx: usize) -> bool {
+        self.remaining[idx] == 0
+    }
+
+    fn waiting(&self) -> usize {
+        self.waiting
+    }
+
+    /// Returns transactions that became ready to be sent, as the transaction at `idx` succeeded.
+    fn succeeded(&mut self, idx: usize) -> Vec<usize> {
+        let mut ready = vec![];
+        for &dependent in &self.dependents[idx] {
+            self.remaining[dependent] -= 1;
+            if self.remaining[dependent] == 0 {
+                self.waiting -= 1;
+                ready.push(dependent);
+            }
+        }
+        ready
+    }
+
+    /// Returns all the transactions that depend on the failed transaction at `idx`, directly or
+    /// indirectly, and are still waiting.  They are never going to become ready.
+    fn failed(&mut self, idx: usize) -> Vec<usize> {
+        let mut failed = vec![];
+        let mut pending = self.dependents[idx].clone();
+        while let Some(dependent) = pending.pop() {
+            // `usize::MAX` marks transactions that are already reported as failed.
+            if self.remaining[dependent] == usize::MAX {
+                continue;
+            }
+            self.remaining[dependent] = usize::MAX;
+            self.waiting -= 1;
+            failed.push(dependent);
+            pending.extend(&self.dependents[dependent]);
+        }
+        failed
+    }
 }
@@ -0,0 +1,321 @@
+//! Runs [`with_sheppard()`] against a [`MockRpc`], with a paused tokio clock, so that the slots, the
+//! blockhashes, and the retry delays all advance deterministically.
+
+use std::{
+    iter,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use solana_sdk::{
+    hash::hash,
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::{Signer as _, SignerError},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+
+use super::{MAX_ABSENT_SLOTS, TxOutcome, with_sheppard};
+use crate::{
+    blockhash_cache::BlockhashCache,
+    cluster_rpc::mock::{MockRpc, SendBehavior, SendError},
+};
+
+/// Builders for `count` transfers from the `payer`.  Every transfer moves a different amount, so
+/// that the transactions have different signatures.
+fn transfers(
+    payer: &Keypair,
+    count: u64,
+) -> impl Iterator<Item = impl Fn(&BlockhashCache) -> Transaction + '_> + Clone + '_ {
+    (0..count).map(move |idx| {
+        move |blockhash_cache: &BlockhashCache| {
+            Transaction::new_signed_with_payer(
+                &[system_instruction::transfer(
+                    &payer.pubkey(),
+                    &Pubkey::new_unique(),
+                    idx + 1,
+                )],
+                Some(&payer.pubkey()),
+                &[payer],
+                blockhash_cache.get(),
+            )
+        }
+    })
+}
+
+fn expect_success(outcome: &TxOutcome) -> Signature {
+    match outcome {
+        TxOutcome::Success(signature) => *signature,
+        TxOutcome::Failed(error) => panic!("Expected a success, got: {error}"),
+    }
+}
+
+fn expect_failure(outcome: &TxOutcome) -> &str {
+    match outcome {
+        TxOutcome::Success(signature) => panic!("Expected a failure, got: {signature}"),
+        TxOutcome::Failed(error) => error,
+    }
+}
+
+fn program_error() -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(1))
+}
+
+#[tokio::test(start_paused = true)]
+async fn lands_all_transactions() {
+    let rpc = MockRpc::new();
+    let payer = Keypair::new();
+
+    let outcomes = with_sheppard(&rpc).run(transfers(&payer, 3)).await.unwrap();
+
+    assert_eq!(outcomes.len(), 3);
+    let sent = rpc.sent();
+    assert_eq!(sent.len(), 3);
+    for outcome in &outcomes {
+        let signature = expect_success(outcome);
+        assert!(sent.iter().any(|tx| tx.signature == signature));
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn retries_failed_sends() {
+    let rpc = MockRpc::new();
+    let payer = Keypair::new();
+    rpc.on_next_sends([
+        SendBehavior::Reject(SendError::Other("Connection reset".to_owned())),
+        SendBehavior::Reject(SendError::Other("Connection reset".to_owned())),
+    ]);
+
+    let outcomes = with_sheppard(&rpc).run(transfers(&payer, 1)).await.unwrap();
+
+    expect_success(&outcomes[0]);
+    assert_eq!(rpc.sent().len(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn fails_after_running_out_of_retries() {
+    let rpc = MockRpc::new();
+    let payer = Keypair::new();
+    rpc.on_next_sends(
+        (0..3).map(|_| SendBehavior::Reject(SendError::Other("Connection reset".to_owned()))),
+    );
+
+    let outcomes = with_sheppard(&rpc)
+        .retry_count(2)
+        .run(transfers(&payer, 1))
+        .await
+        .unwrap();
+
+    let error = expect_failure(&outcomes[0]);
+    assert!(
+        error.contains("Connection reset"),
+        "Unexpected error: {error}"
+    );
+    assert_eq!(rpc.sent().len(), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn does_not_retry_program_errors() {
+    let rpc = MockRpc::new();
+    let payer = Keypair::new();
+    rpc.on_next_sends([SendBehavior::FailExecution(program_error())]);
+
+    let outcomes = with_sheppard(&rpc).run(transfers(&payer, 1)).await.unwrap();
+
+    expect_failure(&outcomes[0]);
+    assert_eq!(rpc.sent().len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn does_not_retry_signing_failures() {
+    let rpc = MockRpc::new();
+    let signing_attempts = AtomicUsize::new(0);
+
+    let outcomes = with_sheppard(&rpc)
+        .run(iter::once(|_blockhash_cache: &BlockhashCache| {
+            signing_attempts.fetch_add(1, Ordering::Relaxed);
+            Err::<Transaction, _>(SignerError::UserCancel("Rejected on the device".to_owned()))
+        }))
+        .await
+        .unwrap();
+
+    let error = expect_failure(&outcomes[0]);
+    assert!(
+        error.contains("Rejected on the device"),
+        "Unexpected error: {error}"
+    );
+    assert_eq!(signing_attempts.load(Ordering::Relaxed), 1);
+    assert!(rpc.sent().is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn does_not_retry_preflight_failures_that_would_repeat() {
+    for error in [program_error(), TransactionError::InsufficientFundsForFee] {
+        let rpc = MockRpc::new();
+        let payer = Keypair::new();
+        rpc.on_next_sends([SendBehavior::Reject(SendError::Preflight(error.clone()))]);
+
+        let outcomes = with_sheppard(&rpc).run(transfers(&payer, 1)).await.unwrap();
+
+        expect_failure(&outcomes[0]);
+        assert_eq!(rpc.sent().len(), 1, "Retried after: {error}");
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn retries_expired_blockhash_without_retry_delay() {
+    let rpc = MockRpc::new();
+    let payer = Keypair::new();
+    rpc.on_next_sends([SendBehavior::Reject(SendError::BlockhashNotFound)]);
+
+    let outcomes = with_sheppard(&rpc)
+        .rpc_failure_retry_delay(Duration::from_secs(10))
+        .run(transfers(&payer, 1))
+        .await
+        .unwrap();
+
+    let signature = expect_success(&outcomes[0]);
+    let sent = rpc.sent();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[1].signature, signature);
+    // The retry only waits for the blockhash cache to refresh.
+    assert!(sent[1].slot - sent[0].slot <= 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn gives_up_on_blockhash_that_never_becomes_valid() {
+    let rpc = MockRpc::new();
+    let payer = Keypair::new();
+    // The mock never produced this blockhash, so it looks the same as an expired one.
+    let stale_blockhash = hash(b"stale blockhash");
+
+    let outcomes = with_sheppard(&rpc)
+        .retry_count(2)
+        .run([()].into_iter().map(|()| {
+            let payer = &payer;
+            move |_blockhash_cache: &BlockhashCache| {
+                Transaction::new_signed_with_payer(
+                    &[system_instruction::transfer(
+                        &payer.pubkey(),
+                        &Pubkey::new_unique(),
+                        1,
+                    )],
+                    Some(&payer.pubkey()),
+                    &[payer],
+                    stale_blockhash,
+                )
+            }
+        }))
+        .await
+        .unwrap();
+
+    let error = expect_failure(&outcomes[0]);
+    assert!(
+        error.contains("Blockhash not found"),
+        "Unexpected error: {error}"
+    );
+    let sent = rpc.sent();
+    assert_eq!(sent.len(), 3);
+    assert!(sent.iter().all(|tx| !tx.accepted));
+}
+
+#[tokio::test(start_paused = true)]
+async fn resends_absent_transaction() {
+    let rpc = MockRpc::new();
+    let payer = Keypair::new();
+    rpc.on_next_sends([SendBehavior::Drop]);
+
+    let outcomes = with_sheppard(&rpc).run(transfers(&payer, 1)).await.unwrap();
+
+    let signature = expect_success(&outcomes[0]);
+    let sent = rpc.sent();
+    assert_eq!(sent.len(), 2);
+    assert!(sent[1].slot - sent[0].slot >= MAX_ABSENT_SLOTS);
+    assert_eq!(sent[1].signature, signature);
+}
+
+#[tokio::test(start_paused = true)]
+async fn fails_absent_transaction_after_retries() {
+    let rpc = MockRpc::new();
+    let payer = Keypair::new();
+    rpc.on_next_sends([SendBehavior::Drop, SendBehavior::Drop]);
+
+    let outcomes = with_sheppard(&rpc)
+        .retry_count(1)
+        .run(transfers(&payer, 1))
+        .await
+        .unwrap();
+
+    let error = expect_failure(&outcomes[0]);
+    assert!(error.contains("not present"), "Unexpected error: {error}");
+    assert_eq!(rpc.sent().len(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn backs_off_from_unhealthy_node() {
+    let rpc = MockRpc::new();
+    let payer = Keypair::new();
+    rpc.on_next_sends((0..3).map(|_| SendBehavior::Reject(SendError::NodeUnhealthy)));
+
+    let outcomes = with_sheppard(&rpc)
+        .retry_count(3)
+        .run(transfers(&payer, 1))
+        .await
+        .unwrap();
+
+    expect_success(&outcomes[0]);
+    let sent = rpc.sent();
+    assert_eq!(sent.len(), 4);
+    let gaps = sent
+        .windows(2)
+        .map(|pair| pair[1].at - pair[0].at)
+        .collect::<Vec<_>>();
+    assert!(
+        gaps.windows(2).all(|pair| pair[1] > pair[0]),
+        "Delays do not grow: {gaps:?}"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn sends_dependents_after_dependencies_land() {
+    let rpc = MockRpc::new();
+    let payer = Keypair::new();
+
+    let outcomes = with_sheppard(&rpc)
+        .dependencies(vec![vec![], vec![0]])
+        .run(transfers(&payer, 2))
+        .await
+        .unwrap();
+
+    let first = expect_success(&outcomes[0]);
+    let second = expect_success(&outcomes[1]);
+    let sent = rpc.sent();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[0].signature, first);
+    assert_eq!(sent[1].signature, second);
+    // The first transaction lands in the slot after it was sent.
+    assert!(sent[1].slot > sent[0].slot + 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn fails_dependents_of_failed_transaction() {
+    let rpc = MockRpc::new();
+    let payer = Keypair::new();
+    rpc.on_next_sends([SendBehavior::FailExecution(program_error())]);
+
+    let outcomes = with_sheppard(&rpc)
+        .dependencies(vec![vec![], vec![0]])
+        .run(transfers(&payer, 2))
+        .await
+        .unwrap();
+
+    expect_failure(&outcomes[0]);
+    let error = expect_failure(&outcomes[1]);
+    assert!(
+        error.contains("Depends on transaction #0"),
+        "Unexpected error: {error}"
+    );
+    assert_eq!(rpc.sent().len(), 1);
+}
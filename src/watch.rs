@@ -0,0 +1,16 @@
+use anyhow::{Context as _, Result};
+
+use crate::{args::watch::Command, exit_code::ValidationFailed};
+
+mod accounts;
+mod buffer_samples;
+pub mod decode;
+
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Accounts(args) => {
+            args.check_are_valid().context(ValidationFailed)?;
+            accounts::run(args).await
+        }
+    }
+}
@@ -0,0 +1,425 @@
+//! Subscribes to account changes, and reports every change as it happens.
+//!
+//! Meant as a general observability tool: it does not know anything about the accounts it is
+//! watching, unless a decoder is specified.
+//!
+//! Changes are reported with a delay since the start of their slot, in milliseconds and in slots,
+//! when the node provides slot notifications.
+
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    fs::{File, OpenOptions},
+    io::Write as _,
+    path::Path,
+    str::FromStr as _,
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use futures::{
+    Stream, StreamExt as _, future,
+    stream::{BoxStream, select_all},
+};
+use log::warn;
+use pythnet_heisenberg::{metrics_sink::MetricsSink, output, slot_clock::SlotClock};
+use serde_json::{Value, json};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::{
+    config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    request::MAX_MULTIPLE_ACCOUNTS,
+};
+use solana_sdk::{
+    account::Account, clock::Slot, commitment_config::CommitmentConfig, pubkey::Pubkey,
+};
+use tokio::{
+    select,
+    signal::unix::{SignalKind, signal},
+    time::Instant,
+};
+use tokio_stream::wrappers::SignalStream;
+use tokio_util::sync::CancellationToken;
+
+use crate::args::{
+    json_rpc_url_args::{get_rpc_client, websocket_url_for},
+    watch::accounts::{AccountDecoder, AccountsArgs},
+};
+
+use super::{buffer_samples::BufferSampler, decode::decode};
+
+pub async fn run(
+    AccountsArgs {
+        json_rpc_url,
+        websocket_url,
+        #[cfg(feature = "geyser")]
+        geyser,
+        account: accounts,
+        program,
+        hexdump,
+        decode,
+        record_file,
+        samples_file,
+        metrics,
+    }: AccountsArgs,
+) -> Result<()> {
+    let websocket_url = websocket_url.unwrap_or_else(|| websocket_url_for(&json_rpc_url.rpc_url));
+    let commitment = CommitmentConfig {
+        commitment: json_rpc_url.commitment,
+    };
+    let rpc_client = get_rpc_client(json_rpc_url);
+
+    let record_file = record_file.as_deref().map(open_record_file).transpose()?;
+    let buffer_sampler = samples_file
+        .as_deref()
+        .map(BufferSampler::open)
+        .transpose()?;
+
+    // Delays are only reported when slot notifications are available.
+    let slot_clock_exit = CancellationToken::new();
+    let _slot_clock_guard = slot_clock_exit.clone().drop_guard();
+    let slot_clock = match SlotClock::start(websocket_url.as_str(), slot_clock_exit).await {
+        Ok((slot_clock, _handle)) => Some(slot_clock),
+        Err(err) => {
+            warn!("Change delays will not be reported: {err:#}");
+            None
+        }
+    };
+
+    // Lamports delta for the very first change of an explicitly listed account is computed
+    // relative to the balance at the start.  For program accounts, the first change has no delta.
+    let last_lamports = initial_lamports(&rpc_client, &accounts).await?;
+
+    let reporter = ChangeReporter {
+        last_lamports,
+        hexdump,
+        decoder: decode,
+        record_file,
+        buffer_sampler,
+        metrics: metrics.start_sink(),
+        slot_clock,
+    };
+
+    #[cfg(feature = "geyser")]
+    if let Some(geyser) = geyser.into_config() {
+        let changes = pythnet_heisenberg::geyser::subscribe_accounts(
+            &geyser,
+            &accounts,
+            program.as_slice(),
+            commitment.commitment,
+        )
+        .await?
+        .map(|update| {
+            update.map(
+                |pythnet_heisenberg::geyser::AccountUpdate {
+                     pubkey,
+                     slot,
+                     account,
+                 }| (pubkey, slot, account),
+            )
+        });
+        return report_until_stopped(changes, reporter).await;
+    }
+
+    let pubsub_client = PubsubClient::new(websocket_url.as_str())
+        .await
+        .with_context(|| format!("Failed to connect to {websocket_url}"))?;
+
+    let account_config = RpcAccountInfoConfig {
+        encoding: Some(UiAccountEncoding::Base64),
+        commitment: Some(commitment),
+        ..RpcAccountInfoConfig::default()
+    };
+
+    let mut streams: Vec<BoxStream<'_, (Pubkey, Slot, UiAccount)>> = vec![];
+    let mut unsubscribes = vec![];
+
+    for &pubkey in &accounts {
+        let (stream, unsubscribe) = pubsub_client
+            .account_subscribe(&pubkey, Some(account_config.clone()))
+            .await
+            .with_context(|| format!("Failed to subscribe to {pubkey}"))?;
+        streams.push(
+            stream
+                .map(move |response| (pubkey, response.context.slot, response.value))
+                .boxed(),
+        );
+        unsubscribes.push(unsubscribe);
+    }
+
+    if let Some(program) = program {
+        let (stream, unsubscribe) = pubsub_client
+            .program_subscribe(
+                &program,
+                Some(RpcProgramAccountsConfig {
+                    account_config: account_config.clone(),
+                    ..RpcProgramAccountsConfig::default()
+                }),
+            )
+            .await
+            .with_context(|| format!("Failed to subscribe to accounts of {program}"))?;
+        streams.push(
+            stream
+                .filter_map(|response| {
+                    let slot = response.context.slot;
+                    let keyed_account = response.value;
+                    let res = match Pubkey::from_str(&keyed_account.pubkey) {
+                        Ok(pubkey) => Some((pubkey, slot, keyed_account.account)),
+                        Err(err) => {
+                            warn!("Notification for an invalid pubkey: {err}");
+                            None
+                        }
+                    };
+                    future::ready(res)
+                })
+                .boxed(),
+        );
+        unsubscribes.push(unsubscribe);
+    }
+
+    let changes = select_all(streams).filter_map(|(pubkey, slot, account)| {
+        let res = match account.decode::<Account>() {
+            Some(account) => Some(Ok((pubkey, slot, account))),
+            None => {
+                warn!("Failed to decode account data for {pubkey} at slot {slot}");
+                None
+            }
+        };
+        future::ready(res)
+    });
+
+    let res = report_until_stopped(changes, reporter).await;
+
+    for unsubscribe in unsubscribes {
+        unsubscribe().await;
+    }
+    pubsub_client
+        .shutdown()
+        .await
+        .context("Failed to disconnect the pubsub client")?;
+
+    res
+}
+
+/// Reports `changes` until either the stream ends, or the process receives a stop signal.
+async fn report_until_stopped(
+    changes: impl Stream<Item = Result<(Pubkey, Slot, Account)>>,
+    mut reporter: ChangeReporter,
+) -> Result<()> {
+    tokio::pin!(changes);
+
+    let stop_signals = select_all([
+        SignalStream::new(signal(SignalKind::interrupt()).expect("Can install a SIGINT handler")),
+        SignalStream::new(signal(SignalKind::terminate()).expect("Can install a SIGTERM handler")),
+    ]);
+    tokio::pin!(stop_signals);
+
+    output::notice("Watching for account changes.  Press Ctrl+C to stop.");
+
+    let res = loop {
+        select! {
+            change = changes.next() => {
+                let Some(change) = change else {
+                    break Err(anyhow!("Server closed all the subscriptions"));
+                };
+                let res = change.and_then(|(pubkey, slot, account)| {
+                    reporter.report(pubkey, slot, account)
+                });
+                if res.is_err() {
+                    break res;
+                }
+            }
+            _ = stop_signals.next() => break Ok(()),
+        }
+    };
+
+    if let Some(metrics) = reporter.metrics {
+        metrics.close().await;
+    }
+
+    if let Some(buffer_sampler) = reporter.buffer_sampler {
+        let finished = buffer_sampler.finish();
+        if res.is_ok() {
+            finished?;
+        }
+    }
+
+    res
+}
+
+fn open_record_file(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open record file: {}", path.to_string_lossy()))
+}
+
+/// Balances of the `accounts`, as of the moment the command starts.  Missing accounts have a zero
+/// balance.
+async fn initial_lamports(
+    rpc_client: &RpcClient,
+    accounts: &[Pubkey],
+) -> Result<HashMap<Pubkey, u64>> {
+    let mut res = HashMap::with_capacity(accounts.len());
+    for chunk in accounts.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let chunk_accounts = rpc_client
+            .get_multiple_accounts(chunk)
+            .await
+            .context("Failed to fetch initial account states")?;
+        for (pubkey, account) in chunk.iter().zip(chunk_accounts) {
+            res.insert(*pubkey, account.map_or(0, |account| account.lamports));
+        }
+    }
+    Ok(res)
+}
+
+struct ChangeReporter {
+    last_lamports: HashMap<Pubkey, u64>,
+    hexdump: bool,
+    decoder: Option<AccountDecoder>,
+    record_file: Option<File>,
+    buffer_sampler: Option<BufferSampler>,
+    metrics: Option<MetricsSink>,
+    slot_clock: Option<SlotClock>,
+}
+
+impl ChangeReporter {
+    fn report(
+        &mut self,
+        pubkey: Pubkey,
+        slot: Slot,
+        Account {
+            lamports,
+            data,
+            owner,
+            ..
+        }: Account,
+    ) -> Result<()> {
+        let Self {
+            last_lamports,
+            hexdump,
+            decoder,
+            record_file,
+            buffer_sampler,
+            metrics,
+            slot_clock,
+        } = self;
+
+        // Time since the slot of the change started, until the change was received.
+        let delay = slot_clock.as_ref().and_then(|slot_clock| {
+            let slot_start = slot_clock.slot_start(slot)?;
+            Some((slot_clock, Instant::now().duration_since(slot_start)))
+        });
+
+        let lamports_delta = last_lamports
+            .insert(pubkey, lamports)
+            .map(|prev| i128::from(lamports) - i128::from(prev));
+
+        let decoded = decoder.map(|decoder| {
+            decode(decoder, &data).unwrap_or_else(|err| json!({ "error": format!("{err:#}") }))
+        });
+
+        let mut text = format!("Slot {slot}: {pubkey}");
+        if let Some((slot_clock, delay)) = delay {
+            write!(
+                text,
+                ", {} after the slot start",
+                slot_clock.describe(delay)
+            )
+            .expect("Writing into a String never fails");
+        }
+        write!(text, "\n  Lamports: {lamports}").expect("Writing into a String never fails");
+        if let Some(lamports_delta) = lamports_delta {
+            write!(text, " ({lamports_delta:+})").expect("Writing into a String never fails");
+        }
+        write!(text, "\n  Owner: {owner}\n  Data: {} bytes", data.len())
+            .expect("Writing into a String never fails");
+        if let Some(decoded) = &decoded {
+            write!(text, "\n  Decoded: {decoded}").expect("Writing into a String never fails");
+        }
+        if *hexdump {
+            text.push('\n');
+            text.push_str(&hex_dump(&data));
+        }
+
+        let mut record = json!({
+            "slot": slot,
+            "account": pubkey.to_string(),
+            "lamports": lamports,
+            "lamports_delta": lamports_delta,
+            "owner": owner.to_string(),
+            "data_len": data.len(),
+        });
+        if let Some((slot_clock, delay)) = delay {
+            record["delay_ms"] = json!(delay.as_secs_f64() * 1000.0);
+            record["delay_slots"] = json!(slot_clock.to_slots(delay));
+        }
+        if let Some(decoded) = decoded {
+            record["decoded"] = decoded;
+        }
+        if *hexdump {
+            record["data_hex"] =
+                Value::String(data.iter().map(|byte| format!("{byte:02x}")).collect());
+        }
+
+        if let Some(record_file) = record_file {
+            writeln!(record_file, "{record}").context("Failed to write into the record file")?;
+        }
+
+        if let Some(buffer_sampler) = buffer_sampler {
+            buffer_sampler.record(pubkey, &data)?;
+        }
+
+        if let Some(metrics) = metrics {
+            let mut point = metrics
+                .point("account_change")
+                .tag("account", pubkey)
+                .tag("owner", owner)
+                .field_u64("slot", slot)
+                .field_u64("lamports", lamports)
+                .field_u64("data_len", data.len() as u64);
+            if let Some(lamports_delta) = lamports_delta {
+                point = point.field_f64("lamports_delta", lamports_delta as f64);
+            }
+            if let Some((_slot_clock, delay)) = delay {
+                point = point.field_f64("delay_ms", delay.as_secs_f64() * 1000.0);
+            }
+            metrics.submit(point);
+        }
+
+        output::result(text, record);
+
+        Ok(())
+    }
+}
+
+/// Formats `data` as 16 bytes per line, with offsets and an ASCII column.
+fn hex_dump(data: &[u8]) -> String {
+    let mut res = String::new();
+    for (line_no, line) in data.chunks(16).enumerate() {
+        let hex = line
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<Vec<_>>();
+        let ascii = line
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    char::from(byte)
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+        writeln!(
+            res,
+            "  {:08x}  {:<47}  |{ascii}|",
+            line_no * 16,
+            hex.join(" ")
+        )
+        .expect("Writing into a String never fails");
+    }
+    res.pop();
+    res
+}
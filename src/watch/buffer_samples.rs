@@ -0,0 +1,119 @@
+//! `--samples-file`: a time series of the prices held by the price buffers, for offline analysis.
+//!
+//! The Price Store clears a price buffer on the first update in a new slot, so the last state of a
+//! buffer in a slot holds all the prices the publisher has delivered in that slot.  A buffer can
+//! change several times within a slot, so the rows of a slot are only written once a change from a
+//! later slot arrives, or when the watch stops.
+
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufWriter, Write as _},
+    path::Path,
+};
+
+use anyhow::{Context as _, Result};
+use log::warn;
+use pythnet_heisenberg::price_store::{
+    accounts::BufferHeader, instructions::submit_prices::BufferedPrice,
+};
+use solana_sdk::pubkey::Pubkey;
+
+use super::decode::read_price_buffer;
+
+const CSV_HEADER: &str = "slot,publisher,feed_index,trading_status,price,confidence";
+
+pub struct BufferSampler {
+    file: BufWriter<File>,
+    /// Last state of every price buffer, that is not written yet.
+    pending: HashMap<Pubkey, (BufferHeader, Vec<BufferedPrice>)>,
+}
+
+impl BufferSampler {
+    /// Rows are appended, so the same file can be used across multiple runs.  The header row is
+    /// only written into an empty file.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open samples file: {}", path.to_string_lossy()))?;
+        let is_empty = file
+            .metadata()
+            .context("Failed to check the samples file size")?
+            .len()
+            == 0;
+
+        let mut file = BufWriter::new(file);
+        if is_empty {
+            writeln!(file, "{CSV_HEADER}").context("Failed to write into the samples file")?;
+        }
+
+        Ok(Self {
+            file,
+            pending: HashMap::new(),
+        })
+    }
+
+    /// Records a change of the buffer at the `pubkey`.  The previous state is written out if it is
+    /// from an earlier slot.
+    pub fn record(&mut self, pubkey: Pubkey, data: &[u8]) -> Result<()> {
+        let (header, prices) = match read_price_buffer(data) {
+            Ok(decoded) => decoded,
+            Err(err) => {
+                warn!("Not sampling {pubkey}: {err:#}");
+                return Ok(());
+            }
+        };
+
+        let previous = self.pending.insert(pubkey, (header, prices));
+        if let Some((previous_header, previous_prices)) = previous {
+            let BufferHeader { slot: previous, .. } = previous_header;
+            let BufferHeader { slot, .. } = header;
+            if previous != slot {
+                self.write(&previous_header, &previous_prices)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes out the last state of every buffer.
+    pub fn finish(mut self) -> Result<()> {
+        let pending = std::mem::take(&mut self.pending);
+        let mut pending = pending.into_values().collect::<Vec<_>>();
+        pending.sort_by_key(|(header, _prices)| {
+            let BufferHeader { slot, .. } = *header;
+            slot
+        });
+        for (header, prices) in pending {
+            self.write(&header, &prices)?;
+        }
+
+        self.file
+            .flush()
+            .context("Failed to write into the samples file")
+    }
+
+    fn write(&mut self, header: &BufferHeader, prices: &[BufferedPrice]) -> Result<()> {
+        let BufferHeader {
+            publisher, slot, ..
+        } = *header;
+        let publisher = Pubkey::new_from_array(publisher);
+
+        for buffered in prices {
+            let BufferedPrice {
+                price, confidence, ..
+            } = *buffered;
+            writeln!(
+                self.file,
+                "{slot},{publisher},{},{},{price},{confidence}",
+                buffered.feed_index(),
+                buffered.trading_status(),
+            )
+            .context("Failed to write into the samples file")?;
+        }
+
+        Ok(())
+    }
+}
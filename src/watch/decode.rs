@@ -0,0 +1,120 @@
+//! Decoders for the account layouts that `watch` commands know about.  `account decode` uses them
+//! as well.
+
+use std::mem::size_of;
+
+use anchor_lang::AccountDeserialize as _;
+use anyhow::{Context as _, Result, bail};
+use bytemuck::pod_read_unaligned;
+use pythnet_heisenberg::{
+    oracle::accounts::price::PriceAccount,
+    price_store::{accounts::BufferHeader, instructions::submit_prices::BufferedPrice},
+};
+use serde_json::{Value, json};
+use solana_sdk::pubkey::Pubkey;
+use stake_caps_parameters as stake_caps_program;
+
+use crate::args::watch::accounts::AccountDecoder;
+
+/// Decodes account `data`, returning the most interesting fields as a JSON object.
+pub fn decode(decoder: AccountDecoder, data: &[u8]) -> Result<Value> {
+    match decoder {
+        AccountDecoder::OraclePrice => decode_oracle_price(data),
+        AccountDecoder::PriceBuffer => decode_price_buffer(data),
+        AccountDecoder::StakeCapsParameters => decode_stake_caps_parameters(data),
+    }
+}
+
+fn decode_oracle_price(data: &[u8]) -> Result<Value> {
+    let Some(data) = data.get(..size_of::<PriceAccount>()) else {
+        bail!(
+            "Account is too small for a price account.  Expected at least {} bytes, got {}",
+            size_of::<PriceAccount>(),
+            data.len()
+        );
+    };
+    // Account data is not guaranteed to be aligned.
+    let price: PriceAccount = pod_read_unaligned(data);
+
+    Ok(json!({
+        "exponent": price.exponent,
+        "num_publishers": price.num,
+        "feed_index": price.feed_index,
+        "last_slot": price.last_slot,
+        "product_account": price.product_account.to_string(),
+        "agg": {
+            "price": price.agg.price,
+            "conf": price.agg.conf,
+            "status": price.agg.status,
+            "pub_slot": price.agg.pub_slot,
+        },
+    }))
+}
+
+fn decode_price_buffer(data: &[u8]) -> Result<Value> {
+    let (header, prices) = read_price_buffer(data)?;
+    let BufferHeader {
+        publisher, slot, ..
+    } = header;
+
+    Ok(json!({
+        "publisher": Pubkey::new_from_array(publisher).to_string(),
+        "slot": slot,
+        "prices": prices
+            .iter()
+            .map(|buffered| {
+                let BufferedPrice {
+                    price, confidence, ..
+                } = *buffered;
+                json!({
+                    "feed_index": buffered.feed_index(),
+                    "trading_status": buffered.trading_status(),
+                    "price": price,
+                    "confidence": confidence,
+                })
+            })
+            .collect::<Vec<_>>(),
+    }))
+}
+
+/// Reads the header of a Price Store price buffer, and the prices written in the header `slot`.
+pub fn read_price_buffer(data: &[u8]) -> Result<(BufferHeader, Vec<BufferedPrice>)> {
+    let Some(header) = data.get(..size_of::<BufferHeader>()) else {
+        bail!(
+            "Account is too small for a price buffer.  Expected at least {} bytes, got {}",
+            size_of::<BufferHeader>(),
+            data.len()
+        );
+    };
+    let header: BufferHeader = pod_read_unaligned(header);
+
+    let num_prices = usize::try_from(header.num_prices).unwrap_or(usize::MAX);
+    let prices = data[size_of::<BufferHeader>()..]
+        .chunks_exact(size_of::<BufferedPrice>())
+        .take(num_prices)
+        .map(pod_read_unaligned)
+        .collect::<Vec<BufferedPrice>>();
+    if prices.len() < num_prices {
+        bail!(
+            "Price buffer holds {num_prices} prices, but only has space for {}",
+            prices.len()
+        );
+    }
+
+    Ok((header, prices))
+}
+
+fn decode_stake_caps_parameters(mut data: &[u8]) -> Result<Value> {
+    let stake_caps_program::Parameters {
+        current_authority,
+        m,
+        z,
+    } = stake_caps_program::Parameters::try_deserialize(&mut data)
+        .context("Failed to parse a parameters account")?;
+
+    Ok(json!({
+        "current_authority": current_authority.to_string(),
+        "m": m,
+        "z": z,
+    }))
+}
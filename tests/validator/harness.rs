@@ -0,0 +1,233 @@
+//! Starts a `solana-test-validator` for a test, and runs the `pythnet-heisenberg` binary against
+//! it.
+
+use std::{
+    env,
+    net::TcpListener,
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{Keypair, write_keypair_file},
+    signer::Signer as _,
+};
+use tempfile::TempDir;
+
+/// How long to wait for the validator to become healthy.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often to check if the validator is healthy, during the startup.
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A program to load into the validator genesis.
+///
+/// Programs are deployed as upgradeable, with the validator payer as the upgrade authority, as the
+/// Oracle only lets its upgrade authority configure the permissions.
+pub struct Program {
+    pub id: Pubkey,
+    pub so: PathBuf,
+}
+
+impl Program {
+    /// A program at a random address, built into the file specified by the `var` environment
+    /// variable.  Panics if the variable is not set, as tests that need a program are ignored
+    /// unless requested explicitly.
+    pub fn from_env(var: &str) -> Self {
+        let so = env::var_os(var).unwrap_or_else(|| {
+            panic!("{var} needs to point to the program binary to run this test")
+        });
+
+        Self {
+            id: Keypair::new().pubkey(),
+            so: so.into(),
+        }
+    }
+}
+
+/// A running validator.  It is stopped, and its ledger is removed, on drop.
+pub struct TestValidator {
+    child: Child,
+    dir: TempDir,
+    rpc_url: String,
+    payer: Pubkey,
+    payer_keypair: String,
+    rpc_client: RpcClient,
+}
+
+impl TestValidator {
+    /// Starts a validator with the `programs`, and waits for it to become healthy.
+    ///
+    /// All the initial SOL is minted into a payer account, with the keypair in
+    /// [`payer_keypair()`](Self::payer_keypair).
+    pub fn start(programs: &[&Program]) -> Self {
+        let dir = TempDir::new().expect("Can create a temporary directory");
+
+        let payer = Keypair::new();
+        let payer_keypair = dir.path().join("payer.json");
+        write_keypair_file(&payer, &payer_keypair).expect("Can write the payer keypair");
+
+        let rpc_port = free_port_pair();
+        let faucet_port = free_port();
+        let rpc_url = format!("http://127.0.0.1:{rpc_port}");
+
+        let validator = env::var_os("HEISENBERG_TEST_VALIDATOR")
+            .unwrap_or_else(|| "solana-test-validator".into());
+        let mut command = Command::new(&validator);
+        command
+            .arg("--ledger")
+            .arg(dir.path().join("ledger"))
+            .arg("--quiet")
+            .arg("--rpc-port")
+            .arg(rpc_port.to_string())
+            .arg("--faucet-port")
+            .arg(faucet_port.to_string())
+            .arg("--mint")
+            .arg(payer.pubkey().to_string());
+        for Program { id, so } in programs {
+            command
+                .arg("--upgradeable-program")
+                .arg(id.to_string())
+                .arg(so)
+                .arg(payer.pubkey().to_string());
+        }
+        let child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap_or_else(|err| panic!("Failed to start {}: {err}", validator.to_string_lossy()));
+
+        let mut validator = Self {
+            child,
+            dir,
+            rpc_client: RpcClient::new_with_commitment(
+                rpc_url.clone(),
+                CommitmentConfig::confirmed(),
+            ),
+            rpc_url,
+            payer: payer.pubkey(),
+            payer_keypair: payer_keypair.to_string_lossy().into_owned(),
+        };
+        validator.wait_for_health();
+        validator
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer
+    }
+
+    pub fn payer_keypair(&self) -> &str {
+        &self.payer_keypair
+    }
+
+    /// Generates a keypair, and writes it into `<name>.json`, next to the ledger.  Returns the
+    /// address and the file path.
+    pub fn new_keypair_file(&self, name: &str) -> (Pubkey, String) {
+        let keypair = Keypair::new();
+        let path = self.dir.path().join(format!("{name}.json"));
+        write_keypair_file(&keypair, &path)
+            .unwrap_or_else(|err| panic!("Failed to write {name} keypair: {err}"));
+
+        (keypair.pubkey(), path.to_string_lossy().into_owned())
+    }
+
+    /// Runs a `pythnet-heisenberg` command against this validator, and fails the test if the
+    /// command fails.  `args` start with the area and the command names, and are followed by the
+    /// command arguments, except for the RPC URL and the commitment.
+    pub fn run(&self, args: &[&str]) {
+        let (area_and_command, args) = args.split_at(2);
+
+        let output = Command::new(env!("CARGO_BIN_EXE_pythnet-heisenberg"))
+            .args(area_and_command)
+            .args(["--rpc-url", &self.rpc_url, "--commitment", "confirmed"])
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .expect("Can run pythnet-heisenberg");
+
+        assert!(
+            output.status.success(),
+            "`{}` failed: {}\n\
+             stdout:\n{}\n\
+             stderr:\n{}",
+            area_and_command.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr),
+        );
+    }
+
+    pub fn balance(&self, address: Pubkey) -> u64 {
+        self.rpc_client
+            .get_balance(&address)
+            .unwrap_or_else(|err| panic!("Failed to get {address} balance: {err}"))
+    }
+
+    pub fn account(&self, address: Pubkey) -> Account {
+        self.rpc_client
+            .get_account(&address)
+            .unwrap_or_else(|err| panic!("Failed to get {address} account: {err}"))
+    }
+
+    /// Waits until the RPC node reports it is healthy.  Fails if the validator process exits
+    /// first.
+    fn wait_for_health(&mut self) {
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        let log = self.dir.path().join("ledger").join("validator.log");
+
+        loop {
+            let status = self
+                .child
+                .try_wait()
+                .expect("Can check the validator status");
+            if let Some(status) = status {
+                panic!(
+                    "Validator exited during the startup: {status}.  See the logs in {}",
+                    log.to_string_lossy()
+                );
+            }
+
+            if self.rpc_client.get_health().is_ok() {
+                return;
+            }
+
+            assert!(
+                Instant::now() < deadline,
+                "Validator did not become healthy within {STARTUP_TIMEOUT:?}"
+            );
+
+            sleep(HEALTH_POLL_INTERVAL);
+        }
+    }
+}
+
+impl Drop for TestValidator {
+    fn drop(&mut self) {
+        // The validator might have exited already.
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("0.0.0.0:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Can bind to a free port")
+        .port()
+}
+
+/// The validator uses the port after the RPC port for the WebSocket RPC.
+fn free_port_pair() -> u16 {
+    loop {
+        let port = free_port();
+        if port < u16::MAX && TcpListener::bind(("0.0.0.0", port + 1)).is_ok() {
+            return port;
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! End-to-end tests that run the `pythnet-heisenberg` binary against a local
+//! `solana-test-validator`, and check the resulting on-chain state.
+//!
+//! Only built with the `integration-tests` feature:
+//!
+//!   cargo test --features integration-tests --test validator
+//!
+//! Every test starts its own validator, on free ports, with a ledger in a temporary directory.
+//! `solana-test-validator` needs to be in the `PATH`, or `HEISENBERG_TEST_VALIDATOR` needs to point
+//! to it.
+//!
+//! The Oracle and the Price Store tests deploy the programs from these files.  They are marked as
+//! ignored, and fail if the corresponding variable is not set, so run them explicitly:
+//!
+//!   cargo test --features integration-tests --test validator -- --include-ignored
+//!
+//! * `HEISENBERG_TEST_ORACLE_SO` - the Oracle program, built from `pyth-client` with the Pythnet
+//!   price account size, for example from the `pythnet-update-oracle-v2.33.2` branch of
+//!   `https://github.com/ilya-bobyr/pyth-client.git`.
+//! * `HEISENBERG_TEST_PRICE_STORE_SO` - the Price Store program, built from the
+//!   `target_chains/solana/programs/pyth-price-store` directory of
+//!   `https://github.com/pyth-network/pyth-crosschain.git`.
+
+mod harness;
+mod oracle;
+mod price_store;
+mod transfer;
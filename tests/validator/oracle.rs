@@ -0,0 +1,120 @@
+use std::mem::size_of;
+
+use bytemuck::pod_read_unaligned;
+use pythnet_heisenberg::oracle::accounts::{
+    PC_ACCTYPE_PRICE, PC_MAGIC, price::PriceAccount, product::ProductAccount,
+};
+use solana_sdk::{signature::Keypair, signer::Signer as _};
+
+use crate::harness::{Program, TestValidator};
+
+#[test]
+#[ignore = "needs HEISENBERG_TEST_ORACLE_SO"]
+fn sets_up_a_price_feed() {
+    let oracle = Program::from_env("HEISENBERG_TEST_ORACLE_SO");
+    let validator = TestValidator::start(&[&oracle]);
+
+    let program_id = oracle.id.to_string();
+    let payer = validator.payer().to_string();
+    let funding_keypair = validator.payer_keypair();
+
+    validator.run(&[
+        "oracle",
+        "update-permissions",
+        "--program-id",
+        &program_id,
+        "--funding-keypair",
+        funding_keypair,
+        "--master-authority",
+        &payer,
+        "--data-curation-authority",
+        &payer,
+        "--security-authority",
+        &payer,
+        "--yes",
+    ]);
+
+    let (mapping, mapping_keypair) = validator.new_keypair_file("mapping");
+    validator.run(&[
+        "oracle",
+        "init-mapping",
+        "--program-id",
+        &program_id,
+        "--funding-keypair",
+        funding_keypair,
+        "--mapping-keypair",
+        &mapping_keypair,
+    ]);
+
+    let (product, product_keypair) = validator.new_keypair_file("product");
+    validator.run(&[
+        "oracle",
+        "add-product",
+        "--program-id",
+        &program_id,
+        "--funding-keypair",
+        funding_keypair,
+        "--mapping-keypair",
+        &mapping_keypair,
+        "--product-keypair",
+        &product_keypair,
+        "--metadata",
+        "symbol=Crypto.BTC/USD",
+    ]);
+
+    let (price, price_keypair) = validator.new_keypair_file("price");
+    validator.run(&[
+        "oracle",
+        "add-price",
+        "--program-id",
+        &program_id,
+        "--funding-keypair",
+        funding_keypair,
+        "--product-pubkey",
+        &product.to_string(),
+        "--price-keypair",
+        &price_keypair,
+        "--exponent",
+        "-8",
+    ]);
+
+    let publisher = Keypair::new().pubkey();
+    validator.run(&[
+        "oracle",
+        "add-publisher",
+        "--program-id",
+        &program_id,
+        "--funding-keypair",
+        funding_keypair,
+        "--price-keypair",
+        &price_keypair,
+        "--publisher-pubkey",
+        &publisher.to_string(),
+    ]);
+
+    assert_eq!(validator.account(mapping).owner, oracle.id);
+
+    let product_account = validator.account(product);
+    assert_eq!(product_account.owner, oracle.id);
+    let metadata = ProductAccount::metadata(&product_account.data).unwrap();
+    assert!(
+        metadata.contains(&("symbol".to_owned(), "Crypto.BTC/USD".to_owned())),
+        "Unexpected product metadata: {metadata:?}"
+    );
+    let ProductAccount {
+        first_price_account,
+        ..
+    } = pod_read_unaligned(&product_account.data[..size_of::<ProductAccount>()]);
+    assert_eq!(first_price_account, price);
+
+    let price_account = validator.account(price);
+    assert_eq!(price_account.owner, oracle.id);
+    let price_account: PriceAccount =
+        pod_read_unaligned(&price_account.data[..size_of::<PriceAccount>()]);
+    assert_eq!(price_account.header.magic_number, PC_MAGIC);
+    assert_eq!(price_account.header.account_type, PC_ACCTYPE_PRICE);
+    assert_eq!(price_account.exponent, -8);
+    assert_eq!(price_account.product_account, product);
+    assert_eq!(price_account.num, 1);
+    assert_eq!(price_account.comp[0].pub_, publisher);
+}
@@ -0,0 +1,90 @@
+use std::mem::size_of;
+
+use bytemuck::pod_read_unaligned;
+use pythnet_heisenberg::price_store::{
+    accounts::{BufferHeader, PublisherConfig},
+    instructions::{
+        compute_publisher_config_account,
+        submit_prices::{BufferedPrice, TradingStatus},
+    },
+};
+
+use crate::harness::{Program, TestValidator};
+
+#[test]
+#[ignore = "needs HEISENBERG_TEST_PRICE_STORE_SO"]
+fn publishes_prices_into_the_buffer() {
+    let price_store = Program::from_env("HEISENBERG_TEST_PRICE_STORE_SO");
+    let validator = TestValidator::start(&[&price_store]);
+
+    let program_id = price_store.id.to_string();
+    let payer_keypair = validator.payer_keypair();
+
+    validator.run(&[
+        "price-store",
+        "initialize",
+        "--program-id",
+        &program_id,
+        "--payer-keypair",
+        payer_keypair,
+        "--authority",
+        &validator.payer().to_string(),
+    ]);
+
+    let (publisher, publisher_keypair) = validator.new_keypair_file("publisher");
+    let (buffer, buffer_keypair) = validator.new_keypair_file("buffer");
+    validator.run(&[
+        "price-store",
+        "initialize-publisher",
+        "--program-id",
+        &program_id,
+        "--payer-keypair",
+        payer_keypair,
+        "--authority-keypair",
+        payer_keypair,
+        "--publisher-pubkey",
+        &publisher.to_string(),
+        "--price-buffer-keypair",
+        &buffer_keypair,
+        "--max-prices",
+        "10",
+    ]);
+
+    validator.run(&[
+        "price-store",
+        "submit-prices",
+        "--program-id",
+        &program_id,
+        "--payer-keypair",
+        payer_keypair,
+        "--publisher-keypair",
+        &publisher_keypair,
+        "--price-buffer-pubkey",
+        &buffer.to_string(),
+        "--price",
+        "trading:7:12345:10",
+    ]);
+
+    let (publisher_config, _bump) = compute_publisher_config_account(price_store.id, publisher);
+    let publisher_config = validator.account(publisher_config);
+    assert_eq!(publisher_config.owner, price_store.id);
+    let PublisherConfig { buffer_account, .. } =
+        pod_read_unaligned(&publisher_config.data[..size_of::<PublisherConfig>()]);
+    assert_eq!(buffer_account, buffer.to_bytes());
+
+    let buffer = validator.account(buffer);
+    assert_eq!(buffer.owner, price_store.id);
+    let (header, prices) = buffer.data.split_at(size_of::<BufferHeader>());
+    let BufferHeader {
+        publisher: buffer_publisher,
+        num_prices,
+        ..
+    } = pod_read_unaligned(header);
+    assert_eq!(buffer_publisher, publisher.to_bytes());
+    assert_eq!(num_prices, 1);
+    let price: BufferedPrice = pod_read_unaligned(&prices[..size_of::<BufferedPrice>()]);
+    assert_eq!(
+        price,
+        BufferedPrice::new(TradingStatus::Trading, 7, 12345, 10)
+    );
+}
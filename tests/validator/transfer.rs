@@ -0,0 +1,38 @@
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Keypair, signer::Signer as _};
+
+use crate::harness::TestValidator;
+
+#[test]
+fn fill_up_to_tops_up_existing_and_new_accounts() {
+    let validator = TestValidator::start(&[]);
+    let existing = Keypair::new().pubkey();
+    let new = Keypair::new().pubkey();
+
+    let fill_up_to = |target_balance: u64, recipients: &[&str]| {
+        let target_balance = target_balance.to_string();
+        let mut args = vec![
+            "transfer",
+            "fill-up-to",
+            "--signer-keypair",
+            validator.payer_keypair(),
+            "--target-balance",
+            &target_balance,
+        ];
+        args.extend(recipients);
+        validator.run(&args);
+    };
+
+    fill_up_to(LAMPORTS_PER_SOL, &[&existing.to_string()]);
+    assert_eq!(validator.balance(existing), LAMPORTS_PER_SOL);
+
+    fill_up_to(
+        2 * LAMPORTS_PER_SOL,
+        &[&existing.to_string(), &new.to_string()],
+    );
+    assert_eq!(validator.balance(existing), 2 * LAMPORTS_PER_SOL);
+    assert_eq!(validator.balance(new), 2 * LAMPORTS_PER_SOL);
+
+    // Accounts that are already at the target are not touched.
+    fill_up_to(LAMPORTS_PER_SOL, &[&existing.to_string()]);
+    assert_eq!(validator.balance(existing), 2 * LAMPORTS_PER_SOL);
+}